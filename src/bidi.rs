@@ -0,0 +1,1114 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Utilities for bidirectional text.
+//!
+//! This module implements the resolution of embedding levels and the reordering of lines as
+//! described in the Unicode Bidirectional Algorithm ([UAX #9][]).  The bidi classes of the
+//! characters are taken from a compact table that covers the right-to-left scripts, digits,
+//! punctuation, symbols and formatting characters.  All other characters are treated as strong
+//! left-to-right characters.
+//!
+//! [UAX #9]: https://www.unicode.org/reports/tr9/
+
+use std::borrow;
+use std::collections;
+
+use crate::style;
+
+/// The maximum explicit embedding level (BD2).
+const MAX_DEPTH: u8 = 125;
+
+/// The maximum number of nested bracket pairs (BD16).
+const MAX_BRACKETS: usize = 63;
+
+/// The bidirectional character type of a character.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum BidiClass {
+    L,
+    R,
+    AL,
+    EN,
+    ES,
+    ET,
+    AN,
+    CS,
+    NSM,
+    BN,
+    B,
+    S,
+    WS,
+    ON,
+    LRE,
+    LRO,
+    RLE,
+    RLO,
+    PDF,
+    LRI,
+    RLI,
+    FSI,
+    PDI,
+}
+
+use BidiClass::*;
+
+/// The characters that are not strong left-to-right, sorted by code point.
+const CLASSES: &[(u32, u32, BidiClass)] = &[
+    (0x0000, 0x0008, BN),
+    (0x0009, 0x0009, S),
+    (0x000A, 0x000A, B),
+    (0x000B, 0x000B, S),
+    (0x000C, 0x000C, WS),
+    (0x000D, 0x000D, B),
+    (0x000E, 0x001B, BN),
+    (0x001C, 0x001E, B),
+    (0x001F, 0x001F, S),
+    (0x0020, 0x0020, WS),
+    (0x0021, 0x0022, ON),
+    (0x0023, 0x0025, ET),
+    (0x0026, 0x002A, ON),
+    (0x002B, 0x002B, ES),
+    (0x002C, 0x002C, CS),
+    (0x002D, 0x002D, ES),
+    (0x002E, 0x002F, CS),
+    (0x0030, 0x0039, EN),
+    (0x003A, 0x003A, CS),
+    (0x003B, 0x0040, ON),
+    (0x005B, 0x0060, ON),
+    (0x007B, 0x007E, ON),
+    (0x007F, 0x0084, BN),
+    (0x0085, 0x0085, B),
+    (0x0086, 0x009F, BN),
+    (0x00A0, 0x00A0, CS),
+    (0x00A1, 0x00A1, ON),
+    (0x00A2, 0x00A5, ET),
+    (0x00A6, 0x00A9, ON),
+    (0x00AB, 0x00AC, ON),
+    (0x00AD, 0x00AD, BN),
+    (0x00AE, 0x00AF, ON),
+    (0x00B0, 0x00B1, ET),
+    (0x00B2, 0x00B3, EN),
+    (0x00B4, 0x00B4, ON),
+    (0x00B6, 0x00B8, ON),
+    (0x00B9, 0x00B9, EN),
+    (0x00BB, 0x00BF, ON),
+    (0x00D7, 0x00D7, ON),
+    (0x00F7, 0x00F7, ON),
+    (0x02B9, 0x02BA, ON),
+    (0x02C2, 0x02CF, ON),
+    (0x02D2, 0x02DF, ON),
+    (0x02E5, 0x02ED, ON),
+    (0x02EF, 0x02FF, ON),
+    (0x0300, 0x036F, NSM),
+    (0x0374, 0x0375, ON),
+    (0x037E, 0x037E, ON),
+    (0x0384, 0x0385, ON),
+    (0x0387, 0x0387, ON),
+    (0x03F6, 0x03F6, ON),
+    (0x0483, 0x0489, NSM),
+    (0x058A, 0x058A, ON),
+    (0x058D, 0x058E, ON),
+    (0x058F, 0x058F, ET),
+    (0x0590, 0x0590, R),
+    (0x0591, 0x05BD, NSM),
+    (0x05BE, 0x05BE, R),
+    (0x05BF, 0x05BF, NSM),
+    (0x05C0, 0x05C0, R),
+    (0x05C1, 0x05C2, NSM),
+    (0x05C3, 0x05C3, R),
+    (0x05C4, 0x05C5, NSM),
+    (0x05C6, 0x05C6, R),
+    (0x05C7, 0x05C7, NSM),
+    (0x05C8, 0x05FF, R),
+    (0x0600, 0x0605, AN),
+    (0x0606, 0x0607, ON),
+    (0x0608, 0x0608, AL),
+    (0x0609, 0x060A, ET),
+    (0x060B, 0x060B, AL),
+    (0x060C, 0x060C, CS),
+    (0x060D, 0x060D, AL),
+    (0x060E, 0x060F, ON),
+    (0x0610, 0x061A, NSM),
+    (0x061B, 0x064A, AL),
+    (0x064B, 0x065F, NSM),
+    (0x0660, 0x0669, AN),
+    (0x066A, 0x066A, ET),
+    (0x066B, 0x066C, AN),
+    (0x066D, 0x066F, AL),
+    (0x0670, 0x0670, NSM),
+    (0x0671, 0x06D5, AL),
+    (0x06D6, 0x06DC, NSM),
+    (0x06DD, 0x06DD, AN),
+    (0x06DE, 0x06DE, ON),
+    (0x06DF, 0x06E4, NSM),
+    (0x06E5, 0x06E6, AL),
+    (0x06E7, 0x06E8, NSM),
+    (0x06E9, 0x06E9, ON),
+    (0x06EA, 0x06ED, NSM),
+    (0x06EE, 0x06EF, AL),
+    (0x06F0, 0x06F9, EN),
+    (0x06FA, 0x0710, AL),
+    (0x0711, 0x0711, NSM),
+    (0x0712, 0x072F, AL),
+    (0x0730, 0x074A, NSM),
+    (0x074B, 0x07A5, AL),
+    (0x07A6, 0x07B0, NSM),
+    (0x07B1, 0x07BF, AL),
+    (0x07C0, 0x07EA, R),
+    (0x07EB, 0x07F3, NSM),
+    (0x07F4, 0x07F5, R),
+    (0x07F6, 0x07F9, ON),
+    (0x07FA, 0x07FC, R),
+    (0x07FD, 0x07FD, NSM),
+    (0x07FE, 0x0815, R),
+    (0x0816, 0x0819, NSM),
+    (0x081A, 0x081A, R),
+    (0x081B, 0x0823, NSM),
+    (0x0824, 0x0824, R),
+    (0x0825, 0x0827, NSM),
+    (0x0828, 0x0828, R),
+    (0x0829, 0x082D, NSM),
+    (0x082E, 0x0858, R),
+    (0x0859, 0x085B, NSM),
+    (0x085C, 0x085F, R),
+    (0x0860, 0x088F, AL),
+    (0x0890, 0x0891, AN),
+    (0x0892, 0x0897, AL),
+    (0x0898, 0x089F, NSM),
+    (0x08A0, 0x08C9, AL),
+    (0x08CA, 0x08E1, NSM),
+    (0x08E2, 0x08E2, AN),
+    (0x08E3, 0x0902, NSM),
+    (0x093A, 0x093A, NSM),
+    (0x093C, 0x093C, NSM),
+    (0x0941, 0x0948, NSM),
+    (0x094D, 0x094D, NSM),
+    (0x0951, 0x0957, NSM),
+    (0x0962, 0x0963, NSM),
+    (0x0981, 0x0981, NSM),
+    (0x09BC, 0x09BC, NSM),
+    (0x09C1, 0x09C4, NSM),
+    (0x09CD, 0x09CD, NSM),
+    (0x09E2, 0x09E3, NSM),
+    (0x09F2, 0x09F3, ET),
+    (0x0E31, 0x0E31, NSM),
+    (0x0E34, 0x0E3A, NSM),
+    (0x0E3F, 0x0E3F, ET),
+    (0x0E47, 0x0E4E, NSM),
+    (0x1680, 0x1680, WS),
+    (0x17DB, 0x17DB, ET),
+    (0x180E, 0x180E, BN),
+    (0x1AB0, 0x1AFF, NSM),
+    (0x1DC0, 0x1DFF, NSM),
+    (0x2000, 0x200A, WS),
+    (0x200B, 0x200D, BN),
+    (0x200F, 0x200F, R),
+    (0x2010, 0x2027, ON),
+    (0x2028, 0x2028, WS),
+    (0x2029, 0x2029, B),
+    (0x202A, 0x202A, LRE),
+    (0x202B, 0x202B, RLE),
+    (0x202C, 0x202C, PDF),
+    (0x202D, 0x202D, LRO),
+    (0x202E, 0x202E, RLO),
+    (0x202F, 0x202F, CS),
+    (0x2030, 0x2034, ET),
+    (0x2035, 0x2043, ON),
+    (0x2044, 0x2044, CS),
+    (0x2045, 0x205E, ON),
+    (0x205F, 0x205F, WS),
+    (0x2060, 0x2064, BN),
+    (0x2066, 0x2066, LRI),
+    (0x2067, 0x2067, RLI),
+    (0x2068, 0x2068, FSI),
+    (0x2069, 0x2069, PDI),
+    (0x206A, 0x206F, BN),
+    (0x2070, 0x2070, EN),
+    (0x2074, 0x2079, EN),
+    (0x207A, 0x207B, ES),
+    (0x207C, 0x207E, ON),
+    (0x2080, 0x2089, EN),
+    (0x208A, 0x208B, ES),
+    (0x208C, 0x208E, ON),
+    (0x20A0, 0x20CF, ET),
+    (0x20D0, 0x20F0, NSM),
+    (0x2100, 0x2101, ON),
+    (0x2103, 0x2106, ON),
+    (0x2108, 0x2109, ON),
+    (0x2114, 0x2114, ON),
+    (0x2116, 0x2118, ON),
+    (0x211E, 0x2123, ON),
+    (0x2125, 0x2125, ON),
+    (0x2127, 0x2127, ON),
+    (0x2129, 0x2129, ON),
+    (0x212E, 0x212E, ET),
+    (0x213A, 0x213B, ON),
+    (0x2140, 0x2144, ON),
+    (0x214A, 0x214D, ON),
+    (0x2150, 0x215F, ON),
+    (0x2189, 0x218B, ON),
+    (0x2190, 0x2211, ON),
+    (0x2212, 0x2212, ES),
+    (0x2213, 0x2213, ET),
+    (0x2214, 0x2335, ON),
+    (0x237B, 0x2394, ON),
+    (0x2396, 0x2426, ON),
+    (0x2440, 0x244A, ON),
+    (0x2460, 0x2487, ON),
+    (0x2488, 0x249B, EN),
+    (0x24EA, 0x26AB, ON),
+    (0x26AD, 0x27FF, ON),
+    (0x2900, 0x2B73, ON),
+    (0x2B76, 0x2B95, ON),
+    (0x2B97, 0x2BFF, ON),
+    (0x2CE5, 0x2CEA, ON),
+    (0x2CEF, 0x2CF1, NSM),
+    (0x2CF9, 0x2CFF, ON),
+    (0x2DE0, 0x2DFF, NSM),
+    (0x2E00, 0x2E5D, ON),
+    (0x2E80, 0x2FFF, ON),
+    (0x3000, 0x3000, WS),
+    (0x3001, 0x3004, ON),
+    (0x3008, 0x3020, ON),
+    (0x302A, 0x302D, NSM),
+    (0x3030, 0x3030, ON),
+    (0x3036, 0x3037, ON),
+    (0x303D, 0x303F, ON),
+    (0x3099, 0x309A, NSM),
+    (0x309B, 0x309C, ON),
+    (0x30A0, 0x30A0, ON),
+    (0x30FB, 0x30FB, ON),
+    (0xA490, 0xA4C6, ON),
+    (0xA60D, 0xA60F, ON),
+    (0xA66F, 0xA672, NSM),
+    (0xA673, 0xA673, ON),
+    (0xA674, 0xA67D, NSM),
+    (0xA67E, 0xA67F, ON),
+    (0xA700, 0xA721, ON),
+    (0xA788, 0xA788, ON),
+    (0xFB1D, 0xFB1D, R),
+    (0xFB1E, 0xFB1E, NSM),
+    (0xFB1F, 0xFB28, R),
+    (0xFB29, 0xFB29, ES),
+    (0xFB2A, 0xFB4F, R),
+    (0xFB50, 0xFD3D, AL),
+    (0xFD3E, 0xFD4F, ON),
+    (0xFD50, 0xFDCE, AL),
+    (0xFDCF, 0xFDCF, ON),
+    (0xFDF0, 0xFDFC, AL),
+    (0xFDFD, 0xFDFF, ON),
+    (0xFE00, 0xFE0F, NSM),
+    (0xFE10, 0xFE19, ON),
+    (0xFE20, 0xFE2F, NSM),
+    (0xFE30, 0xFE4F, ON),
+    (0xFE50, 0xFE50, CS),
+    (0xFE51, 0xFE51, ON),
+    (0xFE52, 0xFE52, CS),
+    (0xFE54, 0xFE54, ON),
+    (0xFE55, 0xFE55, CS),
+    (0xFE56, 0xFE5E, ON),
+    (0xFE5F, 0xFE5F, ET),
+    (0xFE60, 0xFE61, ON),
+    (0xFE62, 0xFE63, ES),
+    (0xFE64, 0xFE66, ON),
+    (0xFE68, 0xFE68, ON),
+    (0xFE69, 0xFE6A, ET),
+    (0xFE6B, 0xFE6B, ON),
+    (0xFE70, 0xFEFE, AL),
+    (0xFEFF, 0xFEFF, BN),
+    (0xFF01, 0xFF02, ON),
+    (0xFF03, 0xFF05, ET),
+    (0xFF06, 0xFF0A, ON),
+    (0xFF0B, 0xFF0B, ES),
+    (0xFF0C, 0xFF0C, CS),
+    (0xFF0D, 0xFF0D, ES),
+    (0xFF0E, 0xFF0F, CS),
+    (0xFF10, 0xFF19, EN),
+    (0xFF1A, 0xFF1A, CS),
+    (0xFF1B, 0xFF20, ON),
+    (0xFF3B, 0xFF40, ON),
+    (0xFF5B, 0xFF65, ON),
+    (0xFFE0, 0xFFE1, ET),
+    (0xFFE2, 0xFFE4, ON),
+    (0xFFE5, 0xFFE6, ET),
+    (0xFFE8, 0xFFEE, ON),
+    (0xFFF9, 0xFFFD, ON),
+    (0x10800, 0x10CFF, R),
+    (0x10D00, 0x10D23, AL),
+    (0x10D24, 0x10D27, NSM),
+    (0x10D30, 0x10D39, AN),
+    (0x10D3A, 0x10E5F, R),
+    (0x10E60, 0x10E7E, AN),
+    (0x10E7F, 0x10FFF, R),
+    (0x1D7CE, 0x1D7FF, EN),
+    (0x1E800, 0x1EC6F, R),
+    (0x1EC70, 0x1ECBF, AL),
+    (0x1ECC0, 0x1ECFF, R),
+    (0x1ED00, 0x1ED4F, AL),
+    (0x1ED50, 0x1EDFF, R),
+    (0x1EE00, 0x1EEEF, AL),
+    (0x1EEF0, 0x1EEF1, ON),
+    (0x1EEF2, 0x1EFFF, R),
+    (0x1F000, 0x1F0FF, ON),
+    (0x1F100, 0x1F10A, EN),
+    (0x1F10B, 0x1F10F, ON),
+    (0x1F12F, 0x1F12F, ON),
+    (0x1F16A, 0x1F16F, ON),
+    (0x1F1AD, 0x1F1AD, ON),
+    (0x1F260, 0x1F265, ON),
+    (0x1F300, 0x1FAFF, ON),
+    (0xE0001, 0xE0001, BN),
+    (0xE0020, 0xE007F, BN),
+    (0xE0100, 0xE01EF, NSM),
+];
+
+/// The bracket pairs (BD14, BD15) that are also mirrored in right-to-left text (L4).
+const BRACKETS: &[(char, char)] = &[
+    ('(', ')'),
+    ('[', ']'),
+    ('{', '}'),
+    ('\u{2045}', '\u{2046}'),
+    ('\u{207D}', '\u{207E}'),
+    ('\u{208D}', '\u{208E}'),
+    ('\u{2308}', '\u{2309}'),
+    ('\u{230A}', '\u{230B}'),
+    ('\u{2329}', '\u{232A}'),
+    ('\u{27E6}', '\u{27E7}'),
+    ('\u{27E8}', '\u{27E9}'),
+    ('\u{27EA}', '\u{27EB}'),
+    ('\u{2983}', '\u{2984}'),
+    ('\u{3008}', '\u{3009}'),
+    ('\u{300A}', '\u{300B}'),
+    ('\u{300C}', '\u{300D}'),
+    ('\u{300E}', '\u{300F}'),
+    ('\u{3010}', '\u{3011}'),
+    ('\u{3014}', '\u{3015}'),
+    ('\u{3016}', '\u{3017}'),
+    ('\u{FF08}', '\u{FF09}'),
+    ('\u{FF3B}', '\u{FF3D}'),
+    ('\u{FF5B}', '\u{FF5D}'),
+];
+
+/// Pairs of characters that are mirrored in right-to-left text (L4) but are not brackets.
+const MIRRORED: &[(char, char)] = &[
+    ('<', '>'),
+    ('\u{AB}', '\u{BB}'),
+    ('\u{2039}', '\u{203A}'),
+    ('\u{2264}', '\u{2265}'),
+    ('\u{2208}', '\u{220B}'),
+    ('\u{226A}', '\u{226B}'),
+    ('\u{2282}', '\u{2283}'),
+    ('\u{2286}', '\u{2287}'),
+    ('\u{FF1C}', '\u{FF1E}'),
+];
+
+fn bidi_class(c: char) -> BidiClass {
+    let c = u32::from(c);
+    CLASSES
+        .binary_search_by(|(start, end, _)| {
+            if *end < c {
+                std::cmp::Ordering::Less
+            } else if *start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .map(|idx| CLASSES[idx].2)
+        .unwrap_or(L)
+}
+
+fn is_isolate_initiator(class: BidiClass) -> bool {
+    matches!(class, LRI | RLI | FSI)
+}
+
+/// Returns whether the given class is removed by rule X9.
+fn is_removed(class: BidiClass) -> bool {
+    matches!(class, RLE | LRE | RLO | LRO | PDF | BN)
+}
+
+/// Returns the mirrored character for the given character (L4).
+fn mirror(c: char) -> char {
+    BRACKETS
+        .iter()
+        .chain(MIRRORED)
+        .find_map(|&(open, close)| {
+            if c == open {
+                Some(close)
+            } else if c == close {
+                Some(open)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(c)
+}
+
+/// Returns the level of the first strong character in the given classes, ignoring characters
+/// between isolate initiators and their matching PDI (P2, P3).
+///
+/// If `is_isolate` is set, the search stops at the first unmatched PDI.
+fn first_strong_level(classes: &[BidiClass], is_isolate: bool) -> Option<u8> {
+    let mut isolates = 0;
+    for class in classes {
+        match class {
+            L if isolates == 0 => return Some(0),
+            R | AL if isolates == 0 => return Some(1),
+            LRI | RLI | FSI => isolates += 1,
+            PDI if isolates > 0 => isolates -= 1,
+            PDI if is_isolate => break,
+            B => break,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Returns the paragraph embedding level of the given text that is determined by its first strong
+/// character, or `None` if the text does not contain a strong character (P2, P3).
+pub fn paragraph_level<'a>(text: impl IntoIterator<Item = &'a str>) -> Option<u8> {
+    let classes: Vec<_> = text
+        .into_iter()
+        .flat_map(str::chars)
+        .map(bidi_class)
+        .collect();
+    first_strong_level(&classes, false)
+}
+
+/// Resolves the embedding levels of the characters with the given classes for the given paragraph
+/// embedding level (X1–X10, W1–W7, N0–N2, I1–I2, L1).
+fn resolve_levels(chars: &[char], classes: &[BidiClass], paragraph_level: u8) -> Vec<u8> {
+    let len = classes.len();
+    let mut types = classes.to_vec();
+    let mut levels = vec![paragraph_level; len];
+
+    // BD9: the matching PDI for every isolate initiator
+    let mut matching_pdi = vec![None; len];
+    let mut has_initiator = vec![false; len];
+    let mut open_isolates = Vec::new();
+    for (i, class) in classes.iter().enumerate() {
+        match class {
+            LRI | RLI | FSI => open_isolates.push(i),
+            PDI => {
+                if let Some(initiator) = open_isolates.pop() {
+                    matching_pdi[initiator] = Some(i);
+                    has_initiator[i] = true;
+                }
+            }
+            B => open_isolates.clear(),
+            _ => {}
+        }
+    }
+
+    // X1–X8: explicit levels and directions
+    #[derive(Clone, Copy)]
+    struct Entry {
+        level: u8,
+        direction: Option<BidiClass>,
+        is_isolate: bool,
+    }
+    let initial = Entry {
+        level: paragraph_level,
+        direction: None,
+        is_isolate: false,
+    };
+    let mut stack = vec![initial];
+    let mut overflow_isolates = 0;
+    let mut overflow_embeddings = 0;
+    let mut valid_isolates = 0;
+    for i in 0..len {
+        let top = *stack.last().expect("Empty directional status stack");
+        let next_level = |rtl: bool| {
+            if rtl {
+                (top.level + 1) | 1
+            } else {
+                (top.level + 2) & !1
+            }
+        };
+        match classes[i] {
+            RLE | LRE | RLO | LRO => {
+                levels[i] = top.level;
+                let level = next_level(matches!(classes[i], RLE | RLO));
+                if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                    stack.push(Entry {
+                        level,
+                        direction: match classes[i] {
+                            RLO => Some(R),
+                            LRO => Some(L),
+                            _ => None,
+                        },
+                        is_isolate: false,
+                    });
+                } else if overflow_isolates == 0 {
+                    overflow_embeddings += 1;
+                }
+            }
+            RLI | LRI | FSI => {
+                levels[i] = top.level;
+                if let Some(direction) = top.direction {
+                    types[i] = direction;
+                }
+                let rtl = match classes[i] {
+                    RLI => true,
+                    LRI => false,
+                    _ => {
+                        let end = matching_pdi[i].unwrap_or(len);
+                        first_strong_level(&classes[i + 1..end], true) == Some(1)
+                    }
+                };
+                let level = next_level(rtl);
+                if level <= MAX_DEPTH && overflow_isolates == 0 && overflow_embeddings == 0 {
+                    valid_isolates += 1;
+                    stack.push(Entry {
+                        level,
+                        direction: None,
+                        is_isolate: true,
+                    });
+                } else {
+                    overflow_isolates += 1;
+                }
+            }
+            PDI => {
+                if overflow_isolates > 0 {
+                    overflow_isolates -= 1;
+                } else if valid_isolates > 0 {
+                    overflow_embeddings = 0;
+                    while stack.last().map(|entry| !entry.is_isolate).unwrap_or(false) {
+                        stack.pop();
+                    }
+                    stack.pop();
+                    valid_isolates -= 1;
+                }
+                let top = *stack.last().expect("Empty directional status stack");
+                levels[i] = top.level;
+                if let Some(direction) = top.direction {
+                    types[i] = direction;
+                }
+            }
+            PDF => {
+                levels[i] = top.level;
+                if overflow_isolates > 0 {
+                } else if overflow_embeddings > 0 {
+                    overflow_embeddings -= 1;
+                } else if !top.is_isolate && stack.len() >= 2 {
+                    stack.pop();
+                }
+            }
+            B => {
+                levels[i] = paragraph_level;
+                stack.truncate(1);
+                overflow_isolates = 0;
+                overflow_embeddings = 0;
+                valid_isolates = 0;
+            }
+            BN => levels[i] = top.level,
+            _ => {
+                levels[i] = top.level;
+                if let Some(direction) = top.direction {
+                    types[i] = direction;
+                }
+            }
+        }
+    }
+
+    // X9, X10: the level runs without the removed characters, combined into isolating run
+    // sequences
+    let mut runs: Vec<Vec<usize>> = Vec::new();
+    for i in (0..len).filter(|i| !is_removed(classes[*i])) {
+        match runs.last_mut() {
+            Some(run) if levels[*run.last().unwrap()] == levels[i] => run.push(i),
+            _ => runs.push(vec![i]),
+        }
+    }
+    let run_starts: collections::HashMap<_, _> = runs
+        .iter()
+        .enumerate()
+        .map(|(idx, run)| (run[0], idx))
+        .collect();
+    let mut sequences = Vec::new();
+    for run in &runs {
+        if classes[run[0]] == PDI && has_initiator[run[0]] {
+            continue;
+        }
+        let mut sequence = run.clone();
+        while let Some(pdi) = matching_pdi[*sequence.last().unwrap()] {
+            match run_starts.get(&pdi) {
+                Some(idx) => sequence.extend(&runs[*idx]),
+                None => break,
+            }
+        }
+        sequences.push(sequence);
+    }
+
+    let mut resolved = levels.clone();
+    for sequence in sequences {
+        resolve_sequence(
+            chars,
+            classes,
+            &mut types,
+            &levels,
+            &mut resolved,
+            &sequence,
+            paragraph_level,
+        );
+    }
+
+    // The removed characters get the level of the preceding character.
+    for i in 0..len {
+        if is_removed(classes[i]) {
+            resolved[i] = if i > 0 {
+                resolved[i - 1]
+            } else {
+                paragraph_level
+            };
+        }
+    }
+
+    // L1: segment separators, paragraph separators and trailing whitespace
+    let mut is_trailing = true;
+    for i in (0..len).rev() {
+        match classes[i] {
+            S | B => {
+                resolved[i] = paragraph_level;
+                is_trailing = true;
+            }
+            WS | LRI | RLI | FSI | PDI | BN | RLE | LRE | RLO | LRO | PDF if is_trailing => {
+                resolved[i] = paragraph_level;
+            }
+            _ => is_trailing = false,
+        }
+    }
+
+    resolved
+}
+
+/// Resolves the weak types, the neutral types and the implicit levels of the given isolating run
+/// sequence (W1–W7, N0–N2, I1–I2).
+fn resolve_sequence(
+    chars: &[char],
+    classes: &[BidiClass],
+    types: &mut [BidiClass],
+    levels: &[u8],
+    resolved: &mut [u8],
+    sequence: &[usize],
+    paragraph_level: u8,
+) {
+    let level = levels[sequence[0]];
+    let first = sequence[0];
+    let last = *sequence.last().unwrap();
+    let direction = |level: u8| if level % 2 == 1 { R } else { L };
+    let previous_level = (0..first)
+        .rev()
+        .find(|i| !is_removed(classes[*i]))
+        .map(|i| levels[i])
+        .unwrap_or(paragraph_level);
+    let next_level = if is_isolate_initiator(classes[last]) {
+        paragraph_level
+    } else {
+        (last + 1..classes.len())
+            .find(|i| !is_removed(classes[*i]))
+            .map(|i| levels[i])
+            .unwrap_or(paragraph_level)
+    };
+    let sos = direction(level.max(previous_level));
+    let eos = direction(level.max(next_level));
+    let embedding = direction(level);
+
+    let mut t: Vec<_> = sequence.iter().map(|i| types[*i]).collect();
+    let n = t.len();
+
+    // W1: non-spacing marks
+    for k in 0..n {
+        if t[k] == NSM {
+            t[k] = match k.checked_sub(1).map(|j| t[j]) {
+                None => sos,
+                Some(class) if is_isolate_initiator(class) || class == PDI => ON,
+                Some(class) => class,
+            };
+        }
+    }
+    // W2, W3: European numbers after Arabic letters, Arabic letters
+    let mut last_strong = sos;
+    for class in t.iter_mut() {
+        match *class {
+            L | R | AL => last_strong = *class,
+            EN if last_strong == AL => *class = AN,
+            _ => {}
+        }
+    }
+    for class in t.iter_mut() {
+        if *class == AL {
+            *class = R;
+        }
+    }
+    // W4: single separators between numbers
+    for k in 1..n.saturating_sub(1) {
+        match (t[k - 1], t[k], t[k + 1]) {
+            (EN, ES, EN) | (EN, CS, EN) => t[k] = EN,
+            (AN, CS, AN) => t[k] = AN,
+            _ => {}
+        }
+    }
+    // W5: terminators adjacent to European numbers
+    let mut k = 0;
+    while k < n {
+        if t[k] == ET {
+            let start = k;
+            while k < n && t[k] == ET {
+                k += 1;
+            }
+            let is_adjacent = (start > 0 && t[start - 1] == EN) || (k < n && t[k] == EN);
+            if is_adjacent {
+                for class in &mut t[start..k] {
+                    *class = EN;
+                }
+            }
+        } else {
+            k += 1;
+        }
+    }
+    // W6: remaining separators and terminators
+    for class in t.iter_mut() {
+        if matches!(*class, ES | ET | CS) {
+            *class = ON;
+        }
+    }
+    // W7: European numbers after left-to-right text
+    let mut last_strong = sos;
+    for class in t.iter_mut() {
+        match *class {
+            L | R => last_strong = *class,
+            EN if last_strong == L => *class = L,
+            _ => {}
+        }
+    }
+
+    // N0: bracket pairs
+    let strong = |class: BidiClass| match class {
+        L => Some(L),
+        R | EN | AN => Some(R),
+        _ => None,
+    };
+    let mut pairs = Vec::new();
+    let mut open_brackets: Vec<(char, usize)> = Vec::new();
+    for k in 0..n {
+        if t[k] != ON {
+            continue;
+        }
+        let c = chars[sequence[k]];
+        if let Some((_, close)) = BRACKETS.iter().find(|(open, _)| *open == c) {
+            if open_brackets.len() == MAX_BRACKETS {
+                break;
+            }
+            open_brackets.push((*close, k));
+        } else if BRACKETS.iter().any(|(_, close)| *close == c) {
+            if let Some(idx) = open_brackets.iter().rposition(|(close, _)| *close == c) {
+                pairs.push((open_brackets[idx].1, k));
+                open_brackets.truncate(idx);
+            }
+        }
+    }
+    pairs.sort_unstable();
+    let opposite = if embedding == L { R } else { L };
+    for (open, close) in pairs {
+        let inner: Vec<_> = t[open + 1..close]
+            .iter()
+            .filter_map(|c| strong(*c))
+            .collect();
+        let resolved_direction = if inner.contains(&embedding) {
+            Some(embedding)
+        } else if inner.contains(&opposite) {
+            let context = t[..open]
+                .iter()
+                .rev()
+                .find_map(|c| strong(*c))
+                .unwrap_or(sos);
+            Some(if context == opposite {
+                opposite
+            } else {
+                embedding
+            })
+        } else {
+            None
+        };
+        if let Some(resolved_direction) = resolved_direction {
+            for &bracket in &[open, close] {
+                t[bracket] = resolved_direction;
+                for k in bracket + 1..n {
+                    if classes[sequence[k]] != NSM {
+                        break;
+                    }
+                    t[k] = resolved_direction;
+                }
+            }
+        }
+    }
+
+    // N1, N2: neutrals
+    let is_neutral = |class: BidiClass| matches!(class, B | S | WS | ON | LRI | RLI | FSI | PDI);
+    let mut k = 0;
+    while k < n {
+        if is_neutral(t[k]) {
+            let start = k;
+            while k < n && is_neutral(t[k]) {
+                k += 1;
+            }
+            let before = if start == 0 {
+                sos
+            } else {
+                strong(t[start - 1]).unwrap_or(embedding)
+            };
+            let after = if k == n {
+                eos
+            } else {
+                strong(t[k]).unwrap_or(embedding)
+            };
+            let class = if before == after { before } else { embedding };
+            for neutral in &mut t[start..k] {
+                *neutral = class;
+            }
+        } else {
+            k += 1;
+        }
+    }
+
+    // I1, I2: implicit levels
+    for (k, i) in sequence.iter().enumerate() {
+        types[*i] = t[k];
+        resolved[*i] = match (level % 2 == 1, t[k]) {
+            (false, R) => level + 1,
+            (false, AN) | (false, EN) => level + 2,
+            (true, L) | (true, EN) | (true, AN) => level + 1,
+            _ => level,
+        };
+    }
+}
+
+/// Returns the visual order of items with the given levels (L2).
+fn visual_order(levels: &[u8]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..levels.len()).collect();
+    let max = levels.iter().copied().max().unwrap_or_default();
+    let min_odd = levels
+        .iter()
+        .copied()
+        .filter(|level| level % 2 == 1)
+        .min()
+        .unwrap_or(max + 1);
+    for level in (min_odd..=max).rev() {
+        let mut k = 0;
+        while k < order.len() {
+            if levels[order[k]] >= level {
+                let start = k;
+                while k < order.len() && levels[order[k]] >= level {
+                    k += 1;
+                }
+                order[start..k].reverse();
+            } else {
+                k += 1;
+            }
+        }
+    }
+    order
+}
+
+/// Reverses the given right-to-left text and mirrors its characters (L3, L4).
+///
+/// Non-spacing marks are kept after their base characters so that they are still placed on the
+/// correct glyph.
+fn reverse(s: &str) -> String {
+    let mut clusters: Vec<&str> = Vec::new();
+    let mut start = 0;
+    for (idx, c) in s.char_indices().skip(1) {
+        if bidi_class(c) != NSM {
+            clusters.push(&s[start..idx]);
+            start = idx;
+        }
+    }
+    clusters.push(&s[start..]);
+    clusters
+        .into_iter()
+        .rev()
+        .flat_map(str::chars)
+        .map(mirror)
+        .collect()
+}
+
+/// Returns the strings of the given line in visual order, using the given paragraph embedding
+/// level.
+///
+/// Strings that contain characters with different embedding levels are split.  Right-to-left
+/// strings are reversed and their characters are mirrored.  As the line is reordered on its own,
+/// the characters at the line boundaries are resolved with the paragraph embedding level.
+pub fn reorder_line(
+    line: Vec<style::StyledCow<'_>>,
+    paragraph_level: u8,
+) -> Vec<style::StyledCow<'_>> {
+    let chars: Vec<_> = line.iter().flat_map(|s| s.s.chars()).collect();
+    let classes: Vec<_> = chars.iter().copied().map(bidi_class).collect();
+    if paragraph_level == 0
+        && !classes
+            .iter()
+            .any(|class| matches!(class, R | AL | RLE | RLO | RLI | FSI))
+    {
+        return line;
+    }
+    let levels = resolve_levels(&chars, &classes, paragraph_level);
+
+    // The strings are split into pieces with the same level.
+    let mut pieces = Vec::new();
+    let mut levels = levels.into_iter();
+    for (idx, s) in line.iter().enumerate() {
+        let mut current: Option<(usize, u8)> = None;
+        for (pos, _) in s.s.char_indices() {
+            let level = levels.next().unwrap_or(paragraph_level);
+            match current {
+                Some((_, current_level)) if current_level == level => {}
+                Some((start, current_level)) => {
+                    pieces.push((idx, start..pos, current_level));
+                    current = Some((pos, level));
+                }
+                None => current = Some((pos, level)),
+            }
+        }
+        if let Some((start, level)) = current {
+            pieces.push((idx, start..s.s.len(), level));
+        }
+    }
+
+    let piece_levels: Vec<_> = pieces.iter().map(|(_, _, level)| *level).collect();
+    visual_order(&piece_levels)
+        .into_iter()
+        .map(|piece| {
+            let (idx, range, level) = &pieces[piece];
+            let s = &line[*idx];
+            let text = &s.s[range.clone()];
+            let text = if level % 2 == 1 {
+                borrow::Cow::Owned(reverse(text))
+            } else if text.len() == s.s.len() {
+                s.s.clone()
+            } else {
+                borrow::Cow::Owned(text.to_owned())
+            };
+            s.with_str(text)
+        })
+        .collect()
+}
+
+/// Returns whether the given text contains a strong right-to-left character.
+#[cfg(feature = "shaping")]
+pub fn is_rtl(s: &str) -> bool {
+    s.chars()
+        .map(bidi_class)
+        .any(|class| matches!(class, R | AL))
+}
+
+/// Restores the logical order of a string that has been reordered by [`reorder_line`][].
+///
+/// Strings with right-to-left characters have been reversed and mirrored, so they are reversed
+/// and mirrored again.  All other strings are already in logical order.
+#[cfg(feature = "shaping")]
+pub fn logical_order(s: &str) -> borrow::Cow<'_, str> {
+    if is_rtl(s) {
+        borrow::Cow::Owned(reverse(s))
+    } else {
+        borrow::Cow::Borrowed(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visual(text: &str, paragraph_level: u8) -> String {
+        let line = vec![style::StyledCow::new(text, style::Style::new())];
+        reorder_line(line, paragraph_level)
+            .into_iter()
+            .map(|s| s.s.into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn classes_sorted() {
+        for window in CLASSES.windows(2) {
+            assert!(window[0].0 <= window[0].1);
+            assert!(window[0].1 < window[1].0, "{:x?}", window);
+        }
+        assert_eq!(L, bidi_class('a'));
+        assert_eq!(R, bidi_class('א'));
+        assert_eq!(AL, bidi_class('م'));
+        assert_eq!(EN, bidi_class('7'));
+        assert_eq!(AN, bidi_class('٣'));
+        assert_eq!(NSM, bidi_class('\u{05B8}'));
+    }
+
+    #[test]
+    fn paragraph_level_detection() {
+        assert_eq!(Some(0), paragraph_level(vec!["123 abc"]));
+        assert_eq!(Some(1), paragraph_level(vec!["123 ", "שלום"]));
+        assert_eq!(Some(0), paragraph_level(vec!["\u{2067}שלום\u{2069} abc"]));
+        assert_eq!(None, paragraph_level(vec!["123 !"]));
+    }
+
+    #[test]
+    fn reorder_ltr() {
+        assert_eq!("abc def", visual("abc def", 0));
+        assert_eq!("abc םולש def", visual("abc שלום def", 0));
+        assert_eq!("abc 123 ,םולש def", visual("abc שלום, 123 def", 0));
+    }
+
+    #[test]
+    fn reorder_rtl() {
+        assert_eq!("ןולח 2.5 םולש", visual("שלום 2.5 חלון", 1));
+        assert_eq!("ןולח abc def םולש", visual("שלום abc def חלון", 1));
+        // The brackets are mirrored.
+        assert_eq!("(ןולח) םולש", visual("שלום (חלון)", 1));
+        assert_eq!("(abc) םולש", visual("שלום (abc)", 1));
+        // Trailing whitespace is moved to the start of the line.
+        assert_eq!(" םולש", visual("שלום ", 1));
+        // Non-spacing marks stay after their base character.
+        assert_eq!("בא\u{05B8}", visual("א\u{05B8}ב", 1));
+    }
+
+    #[test]
+    fn reorder_explicit() {
+        assert_eq!("\u{202E}cba\u{202C}", visual("\u{202E}abc\u{202C}", 0));
+        assert_eq!(
+            "abc \u{2069}םולש\u{2067}",
+            visual("\u{2067}שלום\u{2069} abc", 1)
+        );
+    }
+
+    #[test]
+    fn reorder_styled() {
+        let style = style::Style::new();
+        let line = vec![
+            style::StyledCow::new("abc ", style),
+            style::StyledCow::new("שלום ", style.bold()),
+            style::StyledCow::new("עולם", style),
+        ];
+        let line = reorder_line(line, 0);
+        let strings: Vec<_> = line.iter().map(|s| s.s.as_ref()).collect();
+        assert_eq!(vec!["abc ", "םלוע", " םולש"], strings);
+        assert!(line[2].style.is_bold());
+        assert!(!line[1].style.is_bold());
+    }
+
+    #[cfg(feature = "shaping")]
+    #[test]
+    fn restore_logical_order() {
+        for text in &["abc def", "שלום (חלון)", "א\u{05B8}ב"] {
+            let strings: Vec<_> =
+                reorder_line(vec![style::StyledCow::new(*text, style::Style::new())], 1)
+                    .into_iter()
+                    .map(|s| logical_order(&s.s).into_owned())
+                    .collect();
+            assert!(
+                strings.iter().all(|s| text.contains(s.as_str())),
+                "{:?}",
+                strings
+            );
+        }
+        assert_eq!("(!)", logical_order("(!)"));
+    }
+}
@@ -10,53 +10,133 @@
 //! - Containers:
 //!   - [`LinearLayout`][]: arranges its elements sequentially
 //!   - [`TableLayout`][]: arranges its elements in columns and rows
+//!   - [`ColumnLayout`][]: flows its elements into multiple columns
+//!   - [`PositionedLayout`][]: places its elements at absolute positions on the page
+//!   - [`Overlay`][]: renders its elements on top of each other
 //!   - [`OrderedList`][] and [`UnorderedList`][]: arrange their elements sequentially with bullet
 //!     points
+//!   - [`Repeat`][]: renders an element a given number of times
 //! - Text:
 //!   - [`Text`][]: a single line of text
 //!   - [`Paragraph`][]: a wrapped and aligned paragraph of text
+//!   - [`Heading`][]: a numbered heading that is added to the document outline
+//!   - [`TableOfContents`][]: a list of the headings of the document with their page numbers
 //! - Wrappers:
 //!   - [`FramedElement`][]: draws a frame around the wrapped element
+//!   - [`BoxDecoration`][]: draws a background and per-side borders with optionally rounded
+//!     corners around the wrapped element
 //!   - [`PaddedElement`][]: adds a padding to the wrapped element
+//!   - [`MarginedElement`][]: adds collapsing margins to the wrapped element
 //!   - [`StyledElement`][]: sets a default style for the wrapped element and its children
+//!   - [`TransparentElement`][]: draws the wrapped element with a reduced opacity
+//!   - [`RotatedElement`][]: rotates the wrapped element by an arbitrary angle
+//!   - [`LanguageElement`][]: marks the wrapped element as written in a different language
+//!   - [`LinkedElement`][]: adds a link to an anchor to the wrapped element
+//!   - [`BookmarkedElement`][]: adds a bookmark for the wrapped element to the document outline
+//!   - [`ContinuedElement`][]: adds continuation markers if the wrapped element is split across
+//!     pages
+//!   - [`KeepTogether`][]: moves the wrapped element to the next page instead of splitting it
 //! - Other:
 //!   - [`Image`][]: an image (requires the `images` feature)
+//!   - [`Svg`][]: a vector image in the SVG format (requires the `svg` feature)
+//!   - [`QrCode`][] and [`Barcode`][]: a QR code and a Code 128 or EAN-13 barcode (requires the
+//!     `barcodes` feature)
 //!   - [`Break`][]: adds forced line breaks as a spacer
 //!   - [`PageBreak`][]: adds a forced page break
+//!   - [`Spacer`][]: adds empty space with a fixed size
+//!   - [`Fill`][]: fills the remaining height of the page, optionally with content at the bottom
+//!   - [`SignatureLine`][]: a line with a label for signatures in forms
+//!   - [`HorizontalRule`][]: a horizontal line for separating sections
+//!   - [`Badge`][]: a short label on a rounded background for status indicators and tags
+//!   - [`Anchor`][]: marks a position as the target of internal links
+//!   - [`Fraction`][]: a stacked or diagonal fraction
+//!   - [`Canvas`][]: a drawing area with lines, rectangles, circles and Bézier paths
+//!   - [`chart`][]: bar, line and pie charts
+//!   - [`form`][]: interactive text fields, check boxes, radio buttons and dropdown lists
+//!   - [`HeaderLine`][]: a line with left-aligned, centered and right-aligned text for page
+//!     headers and footers
 //!
 //! You can create custom elements by implementing the [`Element`][] trait.
 //!
 //! [`Element`]: ../trait.Element.html
 //! [`LinearLayout`]: struct.LinearLayout.html
 //! [`TableLayout`]: struct.TableLayout.html
+//! [`ColumnLayout`]: struct.ColumnLayout.html
+//! [`PositionedLayout`]: struct.PositionedLayout.html
+//! [`Overlay`]: struct.Overlay.html
 //! [`OrderedList`]: struct.OrderedList.html
 //! [`UnorderedList`]: struct.UnorderedList.html
+//! [`Repeat`]: struct.Repeat.html
 //! [`Text`]: struct.Text.html
 //! [`Image`]: struct.Image.html
+//! [`Svg`]: struct.Svg.html
+//! [`QrCode`]: struct.QrCode.html
+//! [`Barcode`]: struct.Barcode.html
 //! [`Break`]: struct.Break.html
 //! [`PageBreak`]: struct.PageBreak.html
+//! [`Spacer`]: struct.Spacer.html
+//! [`Fill`]: struct.Fill.html
+//! [`SignatureLine`]: struct.SignatureLine.html
+//! [`HorizontalRule`]: struct.HorizontalRule.html
+//! [`Badge`]: struct.Badge.html
+//! [`Anchor`]: struct.Anchor.html
+//! [`Fraction`]: struct.Fraction.html
+//! [`Canvas`]: struct.Canvas.html
+//! [`chart`]: chart/index.html
+//! [`form`]: form/index.html
+//! [`HeaderLine`]: struct.HeaderLine.html
 //! [`Paragraph`]: struct.Paragraph.html
+//! [`Heading`]: struct.Heading.html
+//! [`TableOfContents`]: struct.TableOfContents.html
 //! [`FramedElement`]: struct.FramedElement.html
+//! [`BoxDecoration`]: struct.BoxDecoration.html
 //! [`PaddedElement`]: struct.PaddedElement.html
+//! [`MarginedElement`]: struct.MarginedElement.html
 //! [`StyledElement`]: struct.StyledElement.html
-
+//! [`TransparentElement`]: struct.TransparentElement.html
+//! [`RotatedElement`]: struct.RotatedElement.html
+//! [`LanguageElement`]: struct.LanguageElement.html
+//! [`LinkedElement`]: struct.LinkedElement.html
+//! [`BookmarkedElement`]: struct.BookmarkedElement.html
+//! [`ContinuedElement`]: struct.ContinuedElement.html
+//! [`KeepTogether`]: struct.KeepTogether.html
+
+#[cfg(feature = "barcodes")]
+mod barcode;
+pub mod chart;
+pub mod form;
 #[cfg(feature = "images")]
 mod images;
+#[cfg(feature = "barcodes")]
+mod qrcode;
+#[cfg(feature = "svg")]
+mod svg;
 
 use std::collections;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
 use std::iter;
 use std::mem;
 
+use crate::bidi;
 use crate::error::{Error, ErrorKind};
 use crate::fonts;
 use crate::render;
 use crate::style::Color;
-use crate::style::{LineStyle, Style, StyledString};
+use crate::style::{LineStyle, Style, StyledCow, StyledString, Wrap};
 use crate::wrap;
-use crate::{Alignment, Context, Element, Margins, Mm, Position, RenderResult, Size};
+use crate::{
+    Alignment, BreakPolicy, Context, Direction, Element, LineBreaking, Margins, Mm, Newline,
+    Overflow, Position, RenderResult, Rotation, Size, VerticalAlignment, Whitespace,
+};
 
+#[cfg(feature = "barcodes")]
+pub use barcode::Barcode;
 #[cfg(feature = "images")]
 pub use images::Image;
+#[cfg(feature = "barcodes")]
+pub use qrcode::{QrCode, QrErrorCorrection};
+#[cfg(feature = "svg")]
+pub use svg::Svg;
 
 /// Helper trait for creating boxed elements.
 pub trait IntoBoxedElement {
@@ -78,7 +158,12 @@ impl IntoBoxedElement for Box<dyn Element> {
 
 /// Arranges a list of elements sequentially.
 ///
-/// Currently, elements can only be arranged vertically.
+/// A vertical layout, created with [`vertical`][], arranges its elements from top to bottom.  A
+/// horizontal layout, created with [`horizontal`][], places its elements side by side, from left
+/// to right.  Per default, the elements of a horizontal layout share the available width equally.
+/// Use [`push_with_width`][] to add an element with a different weight or with a fixed width, see
+/// [`ColumnWidth`][].  If one of the elements of a horizontal layout does not fit on the current
+/// page, all elements are continued together on the next page, each in its own column.
 ///
 /// # Examples
 ///
@@ -98,27 +183,61 @@ impl IntoBoxedElement for Box<dyn Element> {
 ///     .element(elements::Paragraph::new("Test2"));
 /// ```
 ///
+/// Horizontal with a fixed-width first column:
+/// ```
+/// use genpdf::elements;
+/// let layout = elements::LinearLayout::horizontal()
+///     .element_with_width(
+///         elements::Paragraph::new("Label"),
+///         elements::ColumnWidth::Absolute(30.into()),
+///     )
+///     .element(elements::Paragraph::new("Value"));
+/// ```
+///
+/// [`vertical`]: #method.vertical
+/// [`horizontal`]: #method.horizontal
+/// [`push_with_width`]: #method.push_with_width
+/// [`ColumnWidth`]: enum.ColumnWidth.html
 pub struct LinearLayout {
     elements: Vec<Box<dyn Element>>,
+    orientation: Orientation,
+    widths: Vec<ColumnWidth>,
+    finished: Vec<bool>,
     render_idx: usize,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Orientation {
+    Vertical,
+    Horizontal,
+}
+
 impl LinearLayout {
-    fn new() -> LinearLayout {
+    fn new(orientation: Orientation) -> LinearLayout {
         LinearLayout {
             elements: Vec::new(),
+            orientation,
+            widths: Vec::new(),
+            finished: Vec::new(),
             render_idx: 0,
         }
     }
 
     /// Creates a new linear layout that arranges its elements vertically.
     pub fn vertical() -> LinearLayout {
-        LinearLayout::new()
+        LinearLayout::new(Orientation::Vertical)
+    }
+
+    /// Creates a new linear layout that arranges its elements horizontally.
+    pub fn horizontal() -> LinearLayout {
+        LinearLayout::new(Orientation::Horizontal)
     }
 
     /// Adds the given element to this layout.
+    ///
+    /// In a horizontal layout, the element gets the weight 1.
     pub fn push<E: IntoBoxedElement>(&mut self, element: E) {
-        self.elements.push(element.into_boxed_element());
+        self.push_with_width(element, ColumnWidth::Weight(1));
     }
 
     /// Adds the given element to this layout and it returns the layout.
@@ -127,6 +246,34 @@ impl LinearLayout {
         self
     }
 
+    /// Adds the given element with the given width to this layout.
+    ///
+    /// The width is only used for horizontal layouts.
+    pub fn push_with_width<E: IntoBoxedElement>(&mut self, element: E, width: ColumnWidth) {
+        self.elements.push(element.into_boxed_element());
+        self.widths.push(width);
+        self.finished.push(false);
+    }
+
+    /// Adds the given element with the given width to this layout and returns the layout.
+    ///
+    /// The width is only used for horizontal layouts.
+    pub fn element_with_width<E: IntoBoxedElement>(
+        mut self,
+        element: E,
+        width: ColumnWidth,
+    ) -> Self {
+        self.push_with_width(element, width);
+        self
+    }
+
+    /// Returns the index and the type name of the element that is currently rendered.
+    pub(crate) fn current_element(&self) -> Option<(usize, &'static str)> {
+        self.elements
+            .get(self.render_idx)
+            .map(|element| (self.render_idx, element.type_name()))
+    }
+
     fn render_vertical(
         &mut self,
         context: &Context,
@@ -135,8 +282,11 @@ impl LinearLayout {
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
         while area.size().height > Mm(0.0) && self.render_idx < self.elements.len() {
-            let element_result =
-                self.elements[self.render_idx].render(context, area.clone(), style)?;
+            let idx = self.render_idx;
+            let element = &mut self.elements[idx];
+            let element_result = element
+                .render(context, area.clone(), style)
+                .map_err(|err| err.with_element(idx, element.type_name()))?;
             area.add_offset(Position::new(0, element_result.size.height));
             result.size = result.size.stack_vertical(element_result.size);
             if element_result.has_more {
@@ -152,543 +302,651 @@ impl LinearLayout {
     fn measure_vertical(
         &mut self,
         context: &Context,
-        area: render::Area<'_>,
+        mut area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
-        while area.size().height > Mm(0.0) && self.render_idx < self.elements.len() {
-            let element_result =
-                self.elements[self.render_idx].measure(context, area.clone(), style)?;
+        let mut idx = self.render_idx;
+        while area.size().height > Mm(0.0) && idx < self.elements.len() {
+            let element = &mut self.elements[idx];
+            let element_result = element
+                .measure(context, area.clone(), style)
+                .map_err(|err| err.with_element(idx, element.type_name()))?;
+            area.add_offset(Position::new(0, element_result.size.height));
             result.size = result.size.stack_vertical(element_result.size);
             if element_result.has_more {
                 result.has_more = true;
                 return Ok(result);
             }
-            self.render_idx += 1;
+            idx += 1;
         }
-        result.has_more = self.render_idx < self.elements.len();
+        result.has_more = idx < self.elements.len();
         Ok(result)
     }
-}
 
-impl Element for LinearLayout {
-    fn render(
+    fn render_horizontal(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        // TODO: add horizontal layout
-        self.render_vertical(context, area, style)
+        let result = self.measure_horizontal(context, area.clone(), style)?;
+        if result.has_more && result.size == Size::default() {
+            // Nothing fits on this page, so we continue with all elements on the next page
+            return Ok(result);
+        }
+
+        let mut result = RenderResult::default();
+        let areas = area.split_widths(&self.column_widths(area.size().width));
+        let mut offset = Mm(0.0);
+        for (idx, area) in areas.into_iter().enumerate() {
+            let width = area.size().width;
+            if !self.finished[idx] {
+                let element = &mut self.elements[idx];
+                let element_result = element
+                    .render(context, area, style)
+                    .map_err(|err| err.with_element(idx, element.type_name()))?;
+                result.size.width = result.size.width.max(offset + element_result.size.width);
+                result.size.height = result.size.height.max(element_result.size.height);
+                result.has_more |= element_result.has_more;
+                self.finished[idx] = !element_result.has_more;
+            }
+            offset += width;
+        }
+        Ok(result)
     }
 
-    fn measure(
+    fn measure_horizontal(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        // TODO: add horizontal layout
-        self.measure_vertical(context, area, style)
-    }
-}
-
-impl<E: IntoBoxedElement> iter::Extend<E> for LinearLayout {
-    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        self.elements
-            .extend(iter.into_iter().map(|e| e.into_boxed_element()))
+        let mut result = RenderResult::default();
+        let mut is_rendered = false;
+        let mut is_deferred = false;
+        let areas = area.split_widths(&self.column_widths(area.size().width));
+        let mut offset = Mm(0.0);
+        for (idx, area) in areas.into_iter().enumerate() {
+            let width = area.size().width;
+            if !self.finished[idx] {
+                let element = &mut self.elements[idx];
+                let element_result = element
+                    .measure(context, area, style)
+                    .map_err(|err| err.with_element(idx, element.type_name()))?;
+                result.size.width = result.size.width.max(offset + element_result.size.width);
+                result.size.height = result.size.height.max(element_result.size.height);
+                result.has_more |= element_result.has_more;
+                if element_result.has_more && element_result.size == Size::default() {
+                    is_deferred = true;
+                } else {
+                    is_rendered = true;
+                }
+            }
+            offset += width;
+        }
+        if is_deferred && is_rendered {
+            // Only some of the elements fit on this page, so we move all elements to the next
+            // page to keep them together
+            result = RenderResult {
+                size: Size::default(),
+                has_more: true,
+            };
+        }
+        Ok(result)
     }
-}
-
-/// A single line of formatted text.
-///
-/// This element renders a single styled string on a single line.  It does not wrap it if the
-/// string is longer than the line.  Therefore you should prefer [`Paragraph`][] over `Text` for
-/// most use cases.
-///
-/// [`Paragraph`]: struct.Paragraph.html
-#[derive(Clone, Debug, Default)]
-pub struct Text {
-    text: StyledString,
-}
 
-impl Text {
-    /// Creates a new instance with the given styled string.
-    pub fn new(text: impl Into<StyledString>) -> Text {
-        Text { text: text.into() }
+    /// Returns the widths of the elements of a horizontal layout with the given total width.
+    ///
+    /// Elements with an absolute width get their width, and the remaining width is shared by the
+    /// other elements according to their weights.
+    fn column_widths(&self, width: Mm) -> Vec<Mm> {
+        let absolute: Mm = self.widths.iter().filter_map(ColumnWidth::absolute).sum();
+        let total_weight: usize = self.widths.iter().map(ColumnWidth::weight).sum();
+        let factor = if total_weight > 0 {
+            (width - absolute).max(Mm(0.0)) / total_weight as f64
+        } else {
+            Mm(0.0)
+        };
+        self.widths
+            .iter()
+            .map(|column| {
+                column
+                    .absolute()
+                    .unwrap_or_else(|| factor * column.weight() as f64)
+            })
+            .collect()
     }
 }
 
-impl Element for Text {
+impl Element for LinearLayout {
     fn render(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
-        mut style: Style,
+        style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        style.merge(self.text.style);
-        if area.print_str(
-            &context.font_cache,
-            Position::default(),
-            style,
-            &self.text.s,
-        )? {
-            result.size = Size::new(
-                style.str_width(&context.font_cache, &self.text.s),
-                style.line_height(&context.font_cache),
-            );
-        } else {
-            result.has_more = true;
+        match self.orientation {
+            Orientation::Vertical => self.render_vertical(context, area, style),
+            Orientation::Horizontal => self.render_horizontal(context, area, style),
         }
-        Ok(result)
     }
 
     fn measure(
         &mut self,
         context: &Context,
-        _: render::Area<'_>,
-        mut style: Style,
+        area: render::Area<'_>,
+        style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        style.merge(self.text.style);
+        match self.orientation {
+            Orientation::Vertical => self.measure_vertical(context, area, style),
+            Orientation::Horizontal => self.measure_horizontal(context, area, style),
+        }
+    }
+}
 
-        result.size = Size::new(
-            style.str_width(&context.font_cache, &self.text.s),
-            style.line_height(&context.font_cache),
-        );
-        Ok(result)
+impl<E: IntoBoxedElement> iter::Extend<E> for LinearLayout {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for element in iter {
+            self.push(element);
+        }
     }
 }
 
-/// A multi-line wrapped paragraph of formatted text.
-///
-/// If the text of this paragraph is longer than the page width, the paragraph is wrapped at word
-/// borders (and additionally at string borders if it contains multiple strings).  If a word in the
-/// paragraph is longer than the page width, the text is truncated.
-///
-/// Use the [`push`][], [`string`][], [`push_styled`][] and [`string_styled`][] methods to add
-/// strings to this paragraph.  Besides the styling of the text (see [`Style`][]), you can also set
-/// an [`Alignment`][] for the paragraph.
+/// Arranges a list of elements in multiple columns of equal width.
 ///
-/// The line height and spacing are calculated based on the style of each string.
+/// The elements are arranged vertically like in a [`LinearLayout`][], but if a column is full, the
+/// content continues in the next column, and if the last column is full, it continues in the first
+/// column on the next page.  Per default, the columns are balanced:  If the remaining content fits
+/// on the current page, it is distributed evenly over the columns instead of filling the first
+/// columns completely.  Use [`set_balanced`][] to disable this behavior.
 ///
-/// # Examples
+/// The columns are separated by a gutter with a default width of 5 mm.  Optionally, a vertical
+/// rule can be drawn between the columns, see [`set_rule`][].  It spans the used height of the
+/// columns on each page.
 ///
-/// With setters:
-/// ```
-/// use genpdf::{elements, style};
-/// let mut p = elements::Paragraph::default();
-/// p.push("This is an ");
-/// p.push_styled("important", style::Color::Rgb(255, 0, 0));
-/// p.push(" message!");
-/// p.set_alignment(genpdf::Alignment::Center);
-/// ```
+/// # Example
 ///
-/// Chained:
 /// ```
 /// use genpdf::{elements, style};
-/// let p = elements::Paragraph::default()
-///     .string("This is an ")
-///     .styled_string("important", style::Color::Rgb(255, 0, 0))
-///     .string(" message!")
-///     .aligned(genpdf::Alignment::Center);
+/// let layout = elements::ColumnLayout::new(2)
+///     .with_gutter(8)
+///     .with_rule(style::LineStyle::new().with_thickness(0.2))
+///     .element(elements::Paragraph::new("first"))
+///     .element(elements::Paragraph::new("second"));
 /// ```
 ///
-/// [`Style`]: ../style/struct.Style.html
-/// [`Alignment`]: ../enum.Alignment.html
-/// [`Element::styled`]: ../trait.Element.html#method.styled
-/// [`push`]: #method.push
-/// [`push_styled`]: #method.push_styled
-/// [`string`]: #method.string
-/// [`string_styled`]: #method.string_styled
-#[derive(Clone, Debug, Default)]
-pub struct Paragraph {
-    text: Vec<StyledString>,
-    words: collections::VecDeque<StyledString>,
-    style_applied: bool,
-    alignment: Alignment,
+/// [`LinearLayout`]: struct.LinearLayout.html
+/// [`set_balanced`]: #method.set_balanced
+/// [`set_rule`]: #method.set_rule
+pub struct ColumnLayout {
+    layout: LinearLayout,
+    columns: usize,
+    gutter: Mm,
+    rule: Option<LineStyle>,
+    is_balanced: bool,
 }
 
-impl Paragraph {
-    /// Creates a new paragraph with the given content.
-    pub fn new(text: impl Into<StyledString>) -> Paragraph {
-        Paragraph {
-            text: vec![text.into()],
-            ..Default::default()
+impl ColumnLayout {
+    /// Creates a new column layout with the given number of columns.
+    ///
+    /// If the number of columns is zero, one column is used.
+    pub fn new(columns: usize) -> ColumnLayout {
+        ColumnLayout {
+            layout: LinearLayout::vertical(),
+            columns: columns.max(1),
+            gutter: Mm::from(5),
+            rule: None,
+            is_balanced: true,
         }
     }
 
-    /// Sets the alignment of this paragraph.
-    pub fn set_alignment(&mut self, alignment: Alignment) {
-        self.alignment = alignment;
+    /// Sets the width of the gutter between the columns.
+    pub fn set_gutter(&mut self, gutter: impl Into<Mm>) {
+        self.gutter = gutter.into();
     }
 
-    /// Sets the alignment of this paragraph and returns the paragraph.
-    pub fn aligned(mut self, alignment: Alignment) -> Self {
-        self.set_alignment(alignment);
+    /// Sets the width of the gutter between the columns and returns the layout.
+    pub fn with_gutter(mut self, gutter: impl Into<Mm>) -> Self {
+        self.set_gutter(gutter);
         self
     }
 
-    /// Adds a string to the end of this paragraph.
-    pub fn push(&mut self, s: impl Into<StyledString>) {
-        self.text.push(s.into());
+    /// Sets the line style of the vertical rule that is drawn in the middle of the gutter between
+    /// the columns.
+    pub fn set_rule(&mut self, line_style: impl Into<LineStyle>) {
+        self.rule = Some(line_style.into());
     }
 
-    /// Adds a string to the end of this paragraph and returns the paragraph.
-    pub fn string(mut self, s: impl Into<StyledString>) -> Self {
-        self.push(s);
+    /// Sets the line style of the vertical rule between the columns and returns the layout.
+    pub fn with_rule(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_rule(line_style);
         self
     }
 
-    /// Adds a string with the given style to the end of this paragraph.
-    pub fn push_styled(&mut self, s: impl Into<String>, style: impl Into<Style>) {
-        self.text.push(StyledString::new(s, style))
+    /// Sets whether the columns are balanced if the remaining content fits on the current page.
+    pub fn set_balanced(&mut self, is_balanced: bool) {
+        self.is_balanced = is_balanced;
     }
 
-    /// Adds a string with the given style to the end of this paragraph and returns the paragraph.
-    pub fn styled_string(mut self, s: impl Into<String>, style: impl Into<Style>) -> Self {
-        self.push_styled(s, style);
+    /// Sets whether the columns are balanced and returns the layout.
+    pub fn with_balanced(mut self, is_balanced: bool) -> Self {
+        self.set_balanced(is_balanced);
         self
     }
 
-    fn get_offset(&self, width: Mm, max_width: Mm) -> Mm {
-        match self.alignment {
-            Alignment::Left => Mm::default(),
-            Alignment::Center => (max_width - width) / 2.0,
-            Alignment::Right => max_width - width,
-        }
+    /// Adds the given element to this layout.
+    pub fn push<E: IntoBoxedElement>(&mut self, element: E) {
+        self.layout.push(element);
     }
 
-    fn apply_style(&mut self, style: Style) {
-        if !self.style_applied {
-            for s in &mut self.text {
-                s.style = style.and(s.style);
-            }
-            self.style_applied = true;
+    /// Adds the given element to this layout and returns the layout.
+    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
+        self.push(element);
+        self
+    }
+
+    fn column_width(&self, area: &render::Area<'_>) -> Mm {
+        let gutters = self.gutter * (self.columns - 1) as f64;
+        (area.size().width - gutters) / self.columns as f64
+    }
+
+    /// Returns the height of the columns for the given area and whether the remaining content
+    /// exceeds the columns.
+    ///
+    /// If the columns are balanced and the remaining content fits into the given area, the
+    /// content height is divided by the number of columns.  As the content can only be split
+    /// between lines, this height is rounded up to a multiple of the line height so that the last
+    /// column is not longer than the other columns.
+    fn column_height(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<(Mm, bool), Error> {
+        let height = area.size().height;
+        let mut measure_area = area.clone();
+        measure_area.set_width(self.column_width(area));
+        measure_area.set_height(height * self.columns as f64);
+        let result = self.layout.measure(context, measure_area, style)?;
+        if result.has_more || result.size.height > height * self.columns as f64 {
+            Ok((height, true))
+        } else if self.is_balanced {
+            let line_height = style.line_height(&context.font_cache);
+            // Subtract a small tolerance so that rounding errors do not add another line.
+            let lines = (result.size.height.0 / self.columns as f64 / line_height.0 - 1e-6).ceil();
+            // Add a small tolerance so that rounding errors do not remove a line.
+            let balanced = line_height * lines + Mm(1e-6);
+            Ok((balanced.min(height), false))
+        } else {
+            Ok((height, false))
         }
     }
 }
 
-impl Element for Paragraph {
+impl Element for ColumnLayout {
     fn render(
         &mut self,
         context: &Context,
-        mut area: render::Area<'_>,
+        area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
-
-        self.apply_style(style);
-
-        if self.words.is_empty() {
-            if self.text.is_empty() {
-                return Ok(result);
+        let width = self.column_width(&area);
+        let column_height = if self.is_balanced {
+            self.column_height(context, &area, style)?.0
+        } else {
+            area.size().height
+        };
+        let mut rendered_columns = 0;
+        for column in 0..self.columns {
+            let mut column_area = area.clone();
+            column_area.add_offset(Position::new((width + self.gutter) * column as f64, 0));
+            column_area.set_width(width);
+            // The last column may use the full height so that no content is moved to the next
+            // page because of the balancing.
+            if column + 1 < self.columns {
+                column_area.set_height(column_height);
             }
-            self.words = wrap::Words::new(mem::take(&mut self.text)).collect();
-        }
-
-        let words = self.words.iter().map(Into::into);
-        let mut rendered_len = 0;
-        let mut wrapper = wrap::Wrapper::new(words, context, area.size().width);
-        for (line, delta) in &mut wrapper {
-            let width: Mm = line.iter().map(|s| s.width(&context.font_cache)).sum();
-            // Calculate the maximum line height
-            let metrics = line
-                .iter()
-                .map(|s| s.style.metrics(&context.font_cache))
-                .fold(fonts::Metrics::default(), |max, m| max.max(&m));
-            let position = Position::new(self.get_offset(width, area.size().width), 0);
-
-            if let Some(mut section) = area.text_section(&context.font_cache, position, metrics) {
-                for s in line {
-                    section.print_str(&s.s, s.style)?;
-                    rendered_len += s.s.len();
-                }
-                rendered_len -= delta;
-            } else {
-                result.has_more = true;
+            let column_result = self.layout.render(context, column_area, style)?;
+            result.size.height = result.size.height.max(column_result.size.height);
+            rendered_columns += 1;
+            result.has_more = column_result.has_more;
+            if !result.has_more {
                 break;
             }
-            result.size = result
-                .size
-                .stack_vertical(Size::new(width, metrics.line_height));
-            area.add_offset(Position::new(0, metrics.line_height));
-        }
-
-        if wrapper.has_overflowed() {
-            return Err(Error::new(
-                "Page overflowed while trying to wrap a string",
-                ErrorKind::PageSizeExceeded,
-            ));
         }
+        result.size.width = if rendered_columns > 1 {
+            area.size().width
+        } else {
+            width
+        };
 
-        // Remove the rendered data from self.words so that we don’t render it again on the next
-        // call to render.
-        while rendered_len > 0 && !self.words.is_empty() {
-            if self.words[0].s.len() <= rendered_len {
-                rendered_len -= self.words[0].s.len();
-                self.words.pop_front();
-            } else {
-                self.words[0].s.replace_range(..rendered_len, "");
-                rendered_len = 0;
+        if let Some(rule) = self.rule {
+            for column in 1..rendered_columns {
+                let x = (width + self.gutter) * column as f64 - self.gutter / 2.0;
+                area.draw_line(
+                    vec![Position::new(x, 0), Position::new(x, result.size.height)],
+                    rule,
+                );
             }
         }
-
         Ok(result)
     }
 
     fn measure(
         &mut self,
         context: &Context,
-        mut area: render::Area<'_>,
+        area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-
-        self.apply_style(style);
+        let (height, has_more) = self.column_height(context, &area, style)?;
+        Ok(RenderResult {
+            size: Size::new(area.size().width, height),
+            has_more,
+        })
+    }
+}
 
-        if self.words.is_empty() {
-            if self.text.is_empty() {
-                return Ok(result);
-            }
-            self.words = wrap::Words::new(mem::take(&mut self.text)).collect();
-        }
+impl<E: IntoBoxedElement> iter::Extend<E> for ColumnLayout {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        self.layout.extend(iter)
+    }
+}
 
-        let words = self.words.iter().map(Into::into);
-        let mut wrapper = wrap::Wrapper::new(words, context, area.size().width);
-        for (line, _) in &mut wrapper {
-            let width: Mm = line.iter().map(|s| s.width(&context.font_cache)).sum();
-            // Calculate the maximum line height
-            let metrics = line
-                .iter()
-                .map(|s| s.style.metrics(&context.font_cache))
-                .fold(fonts::Metrics::default(), |max, m| max.max(&m));
-
-            result.size = result
-                .size
-                .stack_vertical(Size::new(width, metrics.line_height));
-            area.add_offset(Position::new(0, metrics.line_height));
-        }
-
-        if wrapper.has_overflowed() {
-            return Err(Error::new(
-                "Page overflowed while trying to wrap a string",
-                ErrorKind::PageSizeExceeded,
-            ));
-        }
-
-        Ok(result)
-    }
+/// Places its elements at absolute positions on the page, regardless of the flow of the document.
+///
+/// The positions are relative to the upper left corner of the page, ignoring the page margins and
+/// the position of the layout in the document.  Per default, every element can use the area from
+/// its position to the lower right corner of the page.  Use [`push_with_size`][] to restrict it to
+/// a given size, for example to align text in a fixed field.  Content that does not fit into the
+/// area of an element is discarded.
+///
+/// The layout does not take up any space in the document, so the following elements are rendered
+/// as if it did not exist.  All elements are rendered on the page where the layout is placed.  To
+/// render the same elements on every page, for example as a pre-printed form template, return the
+/// layout from the background callback of a [`SimplePageDecorator`][].
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, Alignment, Element as _};
+/// let layout = elements::PositionedLayout::new()
+///     .element((20, 40), elements::Text::new("Invoice No. 1234"))
+///     .element_with_size(
+///         (150, 40),
+///         (40, 5),
+///         elements::Paragraph::new("EUR 99.00").aligned(Alignment::Right),
+///     );
+/// ```
+///
+/// [`push_with_size`]: #method.push_with_size
+/// [`SimplePageDecorator`]: ../struct.SimplePageDecorator.html
+#[derive(Default)]
+pub struct PositionedLayout {
+    elements: Vec<(Position, Option<Size>, Box<dyn Element>)>,
+    is_rendered: bool,
 }
 
-impl From<Vec<StyledString>> for Paragraph {
-    fn from(text: Vec<StyledString>) -> Paragraph {
-        Paragraph {
-            text,
-            ..Default::default()
-        }
+impl PositionedLayout {
+    /// Creates a new empty positioned layout.
+    pub fn new() -> PositionedLayout {
+        PositionedLayout::default()
     }
-}
 
-impl<T: Into<StyledString>> iter::Extend<T> for Paragraph {
-    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        for s in iter {
-            self.push(s);
-        }
+    /// Adds the given element at the given position on the page to this layout.
+    pub fn push<E: IntoBoxedElement>(&mut self, position: impl Into<Position>, element: E) {
+        self.elements
+            .push((position.into(), None, element.into_boxed_element()));
     }
-}
 
-impl<T: Into<StyledString>> iter::FromIterator<T> for Paragraph {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut paragraph = Paragraph::default();
-        paragraph.extend(iter);
-        paragraph
+    /// Adds the given element at the given position on the page to this layout, restricting it to
+    /// an area with the given size.
+    pub fn push_with_size<E: IntoBoxedElement>(
+        &mut self,
+        position: impl Into<Position>,
+        size: impl Into<Size>,
+        element: E,
+    ) {
+        self.elements.push((
+            position.into(),
+            Some(size.into()),
+            element.into_boxed_element(),
+        ));
     }
-}
 
-/// A line break.
-///
-/// This element inserts a given number of empty lines.
-///
-/// # Example
-///
-/// ```
-/// // Draws 5 empty lines (calculating the line height using the current style)
-/// let b = genpdf::elements::Break::new(5);
-/// ```
-#[derive(Clone, Copy, Debug, Default)]
-pub struct Break {
-    lines: f64,
-}
+    /// Adds the given element at the given position on the page to this layout and returns the
+    /// layout.
+    pub fn element<E: IntoBoxedElement>(
+        mut self,
+        position: impl Into<Position>,
+        element: E,
+    ) -> Self {
+        self.push(position, element);
+        self
+    }
 
-impl Break {
-    /// Creates a new break with the given number of lines.
-    pub fn new(lines: impl Into<f64>) -> Break {
-        Break {
-            lines: lines.into(),
-        }
+    /// Adds the given element at the given position on the page to this layout, restricting it to
+    /// an area with the given size, and returns the layout.
+    pub fn element_with_size<E: IntoBoxedElement>(
+        mut self,
+        position: impl Into<Position>,
+        size: impl Into<Size>,
+        element: E,
+    ) -> Self {
+        self.push_with_size(position, size, element);
+        self
     }
 }
 
-impl Element for Break {
+impl Element for PositionedLayout {
     fn render(
         &mut self,
         context: &Context,
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        if self.lines <= 0.0 {
-            return Ok(result);
+        if self.is_rendered {
+            return Ok(RenderResult::default());
         }
-        let line_height = style.line_height(&context.font_cache);
-        let break_height = line_height * self.lines;
-        if break_height < area.size().height {
-            result.size.height = break_height;
-            self.lines = 0.0;
-        } else {
-            result.size.height = area.size().height;
-            self.lines -= result.size.height.0 / line_height.0;
+        for (idx, (position, size, element)) in self.elements.iter_mut().enumerate() {
+            let mut element_area = area.page_area();
+            element_area.add_offset(*position);
+            if let Some(size) = size {
+                element_area.set_size(*size);
+            }
+            element
+                .render(context, element_area, style)
+                .map_err(|err| err.with_element(idx, element.type_name()))?;
         }
-        Ok(result)
+        self.is_rendered = true;
+        Ok(RenderResult::default())
     }
 
     fn measure(
         &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        style: Style,
+        _context: &Context,
+        _area: render::Area<'_>,
+        _style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        if self.lines <= 0.0 {
-            return Ok(result);
-        }
-        let line_height = style.line_height(&context.font_cache);
-        let break_height = line_height * self.lines;
-        if break_height < area.size().height {
-            result.size.height = break_height;
-        } else {
-            result.size.height = area.size().height;
-        }
-        Ok(result)
+        Ok(RenderResult::default())
     }
 }
 
-/// A page break.
+/// Renders its elements on top of each other in the same area.
 ///
-/// This element inserts a page break.
+/// Every element gets the full area, and the elements are drawn in the order they were added, so
+/// later elements are drawn on top of earlier elements.  The height of the overlay is the maximum
+/// height of its elements.  If one of the elements does not fit on the current page, the overlay
+/// continues on the next page with the remaining content of all unfinished elements.
 ///
 /// # Example
 ///
 /// ```
-/// let pb = genpdf::elements::PageBreak::new();
+/// use genpdf::{elements, style};
+/// let overlay = elements::Overlay::new()
+///     .element(elements::Canvas::new((60, 10)).with_shape(
+///         elements::Shape::rectangle((0, 0), (60, 10))
+///             .with_fill_color(style::Color::Rgb(255, 240, 200)),
+///     ))
+///     .element(elements::Paragraph::new("Text on a colored box"));
 /// ```
-#[derive(Clone, Copy, Debug, Default)]
-pub struct PageBreak {
-    cont: bool,
+#[derive(Default)]
+pub struct Overlay {
+    elements: Vec<Box<dyn Element>>,
+    finished: Vec<bool>,
 }
 
-impl PageBreak {
-    /// Creates a new page break.
-    pub fn new() -> PageBreak {
-        PageBreak::default()
+impl Overlay {
+    /// Creates a new empty overlay.
+    pub fn new() -> Overlay {
+        Overlay::default()
+    }
+
+    /// Adds the given element on top of the existing elements of this overlay.
+    pub fn push<E: IntoBoxedElement>(&mut self, element: E) {
+        self.elements.push(element.into_boxed_element());
+        self.finished.push(false);
+    }
+
+    /// Adds the given element on top of the existing elements of this overlay and returns the
+    /// overlay.
+    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
+        self.push(element);
+        self
     }
 }
 
-impl Element for PageBreak {
+impl Element for Overlay {
     fn render(
         &mut self,
-        _context: &Context,
-        _area: render::Area<'_>,
-        _style: Style,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
     ) -> Result<RenderResult, Error> {
-        if self.cont {
-            Ok(RenderResult::default())
-        } else {
-            // We don’t use (0,0) as the size as this might abort the render process if this is the
-            // first element on a new page, see the Rendering Process section of the crate
-            // documentation.
-            self.cont = true;
-            Ok(RenderResult {
-                size: Size::new(1, 0),
-                has_more: true,
-            })
+        let mut result = RenderResult::default();
+        let elements = self.elements.iter_mut().zip(self.finished.iter_mut());
+        for (idx, (element, finished)) in elements.enumerate() {
+            if *finished {
+                continue;
+            }
+            let element_result = element
+                .render(context, area.clone(), style)
+                .map_err(|err| err.with_element(idx, element.type_name()))?;
+            result.size.width = result.size.width.max(element_result.size.width);
+            result.size.height = result.size.height.max(element_result.size.height);
+            result.has_more |= element_result.has_more;
+            *finished = !element_result.has_more;
         }
+        Ok(result)
     }
 
     fn measure(
         &mut self,
-        _context: &Context,
-        _area: render::Area<'_>,
-        _style: Style,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
     ) -> Result<RenderResult, Error> {
-        if self.cont {
-            Ok(RenderResult::default())
-        } else {
-            // We don’t use (0,0) as the size as this might abort the render process if this is the
-            // first element on a new page, see the Rendering Process section of the crate
-            // documentation.
-            self.cont = true;
-            Ok(RenderResult {
-                size: Size::new(1, 0),
-                has_more: true,
-            })
+        let mut result = RenderResult::default();
+        let elements = self.elements.iter_mut().zip(self.finished.iter());
+        for (idx, (element, finished)) in elements.enumerate() {
+            if *finished {
+                continue;
+            }
+            let element_result = element
+                .measure(context, area.clone(), style)
+                .map_err(|err| err.with_element(idx, element.type_name()))?;
+            result.size.width = result.size.width.max(element_result.size.width);
+            result.size.height = result.size.height.max(element_result.size.height);
+            result.has_more |= element_result.has_more;
         }
+        Ok(result)
     }
 }
 
-/// Adds a padding to the wrapped element.
+impl<E: IntoBoxedElement> iter::Extend<E> for Overlay {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for element in iter {
+            self.push(element);
+        }
+    }
+}
+
+/// Renders an element a given number of times.
+///
+/// The repeated elements are arranged vertically like in a [`LinearLayout`][].  The element for
+/// every repetition is created by a closure that receives the index of the repetition, starting
+/// with zero.  For elements that implement `Clone`, you can also use [`cloned`][] to render copies
+/// of a single element.  This is useful for repetitive content like blank lines in forms.
 ///
 /// # Examples
 ///
-/// Direct usage:
 /// ```
 /// use genpdf::elements;
-/// let p = elements::PaddedElement::new(
-///     elements::Paragraph::new("text"),
-///     genpdf::Margins::trbl(5, 2, 5, 10),
-/// );
-/// ```
-///
-/// Using [`Element::padded`][]:
-/// ```
-/// use genpdf::{elements, Element as _};
-/// let p = elements::Paragraph::new("text")
-///     .padded(genpdf::Margins::trbl(5, 2, 5, 10));
+/// let lines = elements::Repeat::cloned(elements::SignatureLine::new(50), 3);
+/// let rows = elements::Repeat::new(5, |i| elements::Paragraph::new(format!("Row {}", i + 1)));
 /// ```
 ///
-/// [`Element::padded`]: ../trait.Element.html#method.padded
-#[derive(Clone, Debug, Default)]
-pub struct PaddedElement<E: Element> {
-    element: E,
-    padding: Margins,
+/// [`LinearLayout`]: struct.LinearLayout.html
+/// [`cloned`]: #method.cloned
+pub struct Repeat<E: Element> {
+    factory: Box<dyn FnMut(usize) -> E>,
+    count: usize,
+    render_idx: usize,
+    element: Option<E>,
 }
 
-impl<E: Element> PaddedElement<E> {
-    /// Creates a new padded element that wraps the given element with the given padding.
-    pub fn new(element: E, padding: impl Into<Margins>) -> PaddedElement<E> {
-        PaddedElement {
-            element,
-            padding: padding.into(),
+impl<E: Element> Repeat<E> {
+    /// Creates a new element that renders the elements returned by the given closure for the
+    /// indices from zero to `count - 1`.
+    pub fn new(count: usize, factory: impl FnMut(usize) -> E + 'static) -> Repeat<E> {
+        Repeat {
+            factory: Box::new(factory),
+            count,
+            render_idx: 0,
+            element: None,
         }
     }
+
+    /// Creates a new element that renders `count` copies of the given element.
+    pub fn cloned(element: E, count: usize) -> Repeat<E>
+    where
+        E: Clone + 'static,
+    {
+        Repeat::new(count, move |_| element.clone())
+    }
+
+    /// Returns the number of repetitions.
+    pub fn count(&self) -> usize {
+        self.count
+    }
 }
 
-impl<E: Element> Element for PaddedElement<E> {
+impl<E: Element> Element for Repeat<E> {
     fn render(
         &mut self,
         context: &Context,
         mut area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        area.add_margins(Margins {
-            bottom: Mm(0.0),
-            ..self.padding
-        });
-        let mut result = self.element.render(context, area, style)?;
-        result.size.width += self.padding.left + self.padding.right;
-        result.size.height += self.padding.top + self.padding.bottom;
+        let mut result = RenderResult::default();
+        while area.size().height > Mm(0.0) && self.render_idx < self.count {
+            let idx = self.render_idx;
+            let factory = &mut self.factory;
+            let element = self.element.get_or_insert_with(|| factory(idx));
+            let element_result = element
+                .render(context, area.clone(), style)
+                .map_err(|err| err.with_element(idx, element.type_name()))?;
+            area.add_offset(Position::new(0, element_result.size.height));
+            result.size = result.size.stack_vertical(element_result.size);
+            if element_result.has_more {
+                result.has_more = true;
+                return Ok(result);
+            }
+            self.element = None;
+            self.render_idx += 1;
+        }
+        result.has_more = self.render_idx < self.count;
         Ok(result)
     }
 
@@ -698,221 +956,4594 @@ impl<E: Element> Element for PaddedElement<E> {
         mut area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        area.add_margins(Margins {
-            bottom: Mm(0.0),
-            ..self.padding
-        });
-        let mut result = self.element.measure(context, area, style)?;
-        result.size.width += self.padding.left + self.padding.right;
-        result.size.height += self.padding.top + self.padding.bottom;
+        let mut result = RenderResult::default();
+        let mut idx = self.render_idx;
+        while area.size().height > Mm(0.0) && idx < self.count {
+            // Only the element that is currently rendered is stored, the following elements are
+            // created just for measuring them.
+            let element_result = if idx == self.render_idx {
+                let factory = &mut self.factory;
+                let element = self.element.get_or_insert_with(|| factory(idx));
+                element
+                    .measure(context, area.clone(), style)
+                    .map_err(|err| err.with_element(idx, element.type_name()))?
+            } else {
+                let mut element = (self.factory)(idx);
+                element
+                    .measure(context, area.clone(), style)
+                    .map_err(|err| err.with_element(idx, element.type_name()))?
+            };
+            area.add_offset(Position::new(0, element_result.size.height));
+            result.size = result.size.stack_vertical(element_result.size);
+            if element_result.has_more {
+                result.has_more = true;
+                return Ok(result);
+            }
+            idx += 1;
+        }
+        result.has_more = idx < self.count;
         Ok(result)
     }
 }
 
-/// Adds a default style to the wrapped element and its children.
+/// A single line of formatted text.
 ///
-/// # Examples
+/// This element renders a single styled string on a single line.  It does not wrap it if the
+/// string is longer than the line.  Therefore you should prefer [`Paragraph`][] over `Text` for
+/// most use cases.
 ///
-/// Direct usage:
-/// ```
-/// use genpdf::{elements, style};
-/// let p = elements::StyledElement::new(
-///     elements::Paragraph::new("text"),
-///     style::Effect::Bold,
-/// );
-/// ```
+/// Per default, newline characters in the string start a new line.  The resulting lines are not
+/// wrapped either.  You can change the [`Newline`][] treatment with [`set_newline`][].
 ///
-/// Using [`Element::styled`][]:
-/// ```
-/// use genpdf::{elements, style, Element as _};
-/// let p = elements::Paragraph::new("text")
-///     .styled(style::Effect::Bold);
-/// ```
-///
-/// [`Element::styled`]: ../trait.Element.html#method.styled
+/// [`Paragraph`]: struct.Paragraph.html
+/// [`Newline`]: ../enum.Newline.html
+/// [`set_newline`]: #method.set_newline
 #[derive(Clone, Debug, Default)]
-pub struct StyledElement<E: Element> {
-    element: E,
-    style: Style,
+pub struct Text {
+    text: StyledString,
+    whitespace: Whitespace,
+    newline: Newline,
+    whitespace_applied: bool,
+    rendered_lines: usize,
 }
 
-impl<E: Element> StyledElement<E> {
-    /// Creates a new styled element that wraps the given element with the given style.
-    pub fn new(element: E, style: impl Into<Style>) -> StyledElement<E> {
-        StyledElement {
-            element,
-            style: style.into(),
+impl Text {
+    /// Creates a new instance with the given styled string.
+    pub fn new(text: impl Into<StyledString>) -> Text {
+        Text {
+            text: text.into(),
+            whitespace: Whitespace::default(),
+            newline: Newline::default(),
+            whitespace_applied: false,
+            rendered_lines: 0,
+        }
+    }
+
+    /// Sets the treatment of consecutive whitespace for this text.
+    ///
+    /// Per default, runs of spaces and tabs are collapsed.  See [`Whitespace`][] for details.
+    ///
+    /// [`Whitespace`]: ../enum.Whitespace.html
+    pub fn set_whitespace(&mut self, whitespace: Whitespace) {
+        self.whitespace = whitespace;
+    }
+
+    /// Sets the treatment of consecutive whitespace for this text and returns the text.
+    ///
+    /// Per default, runs of spaces and tabs are collapsed.  See [`Whitespace`][] for details.
+    ///
+    /// [`Whitespace`]: ../enum.Whitespace.html
+    pub fn with_whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.set_whitespace(whitespace);
+        self
+    }
+
+    /// Sets the treatment of newline characters for this text.
+    ///
+    /// Per default, a newline starts a new line.  See [`Newline`][] for details.
+    ///
+    /// [`Newline`]: ../enum.Newline.html
+    pub fn set_newline(&mut self, newline: Newline) {
+        self.newline = newline;
+    }
+
+    /// Sets the treatment of newline characters for this text and returns the text.
+    ///
+    /// Per default, a newline starts a new line.  See [`Newline`][] for details.
+    ///
+    /// [`Newline`]: ../enum.Newline.html
+    pub fn with_newline(mut self, newline: Newline) -> Self {
+        self.set_newline(newline);
+        self
+    }
+
+    fn apply_whitespace(&mut self) {
+        if !self.whitespace_applied {
+            let text = std::slice::from_mut(&mut self.text);
+            wrap::apply_newlines(text, self.newline);
+            wrap::apply_whitespace(text, self.whitespace);
+            self.whitespace_applied = true;
         }
     }
+
+    fn remaining_lines(&self) -> impl Iterator<Item = &str> {
+        self.text.s.split('\n').skip(self.rendered_lines)
+    }
 }
 
-impl<E: Element> Element for StyledElement<E> {
+impl Element for Text {
     fn render(
         &mut self,
         context: &Context,
-        area: render::Area<'_>,
+        mut area: render::Area<'_>,
         mut style: Style,
     ) -> Result<RenderResult, Error> {
-        style.merge(self.style);
-        self.element.render(context, area, style)
+        let mut result = RenderResult::default();
+        self.apply_whitespace();
+        style.merge(self.text.style);
+        let line_height = style.line_height(&context.font_cache);
+        let lines: Vec<String> = self.remaining_lines().map(ToOwned::to_owned).collect();
+        for line in lines {
+            if let Some(color) = style.background() {
+                let metrics = style.metrics(&context.font_cache);
+                if metrics.glyph_height <= area.size().height {
+                    let mut fill_area = area.clone();
+                    let width = style.str_width(&context.font_cache, line.trim_end());
+                    fill_area.set_size(Size::new(width, metrics.glyph_height));
+                    fill_area.fill_color(color);
+                }
+            }
+            if !area.print_str(&context.font_cache, Position::default(), style, &line)? {
+                result.has_more = true;
+                break;
+            }
+            let width = style.str_width(&context.font_cache, &line);
+            if let Some(uri) = &self.text.link {
+                let link_width = style.str_width(&context.font_cache, line.trim_end());
+                area.add_uri_link(
+                    uri.as_str(),
+                    Position::default(),
+                    Size::new(link_width, line_height),
+                );
+            }
+            result.size = result.size.stack_vertical(Size::new(width, line_height));
+            area.add_offset(Position::new(0, line_height));
+            self.rendered_lines += 1;
+        }
+        Ok(result)
     }
 
     fn measure(
         &mut self,
         context: &Context,
-        area: render::Area<'_>,
+        _: render::Area<'_>,
         mut style: Style,
     ) -> Result<RenderResult, Error> {
-        style.merge(self.style);
-        self.element.measure(context, area, style)
+        let mut result = RenderResult::default();
+        self.apply_whitespace();
+        style.merge(self.text.style);
+        let line_height = style.line_height(&context.font_cache);
+        for line in self.remaining_lines() {
+            let width = style.str_width(&context.font_cache, line);
+            result.size = result.size.stack_vertical(Size::new(width, line_height));
+        }
+        Ok(result)
     }
 }
 
-/// Adds a frame around the wrapped element.
+/// A multi-line wrapped paragraph of formatted text.
+///
+/// If the text of this paragraph is longer than the page width, the paragraph is wrapped at word
+/// borders (and additionally at string borders if it contains multiple strings).  If a word in the
+/// paragraph is longer than the page width, the text is truncated.
+///
+/// Use the [`push`][], [`string`][], [`push_styled`][] and [`string_styled`][] methods to add
+/// strings to this paragraph.  Besides the styling of the text (see [`Style`][]), you can also set
+/// an [`Alignment`][] for the paragraph.  If the paragraph is justified, you can set a different
+/// alignment for the last line using [`set_last_line_alignment`][].  Per default, the lines are
+/// filled greedily.  You can change the [`LineBreaking`][] algorithm with
+/// [`set_line_breaking`][].  Runs of spaces and tabs are collapsed into a single space unless
+/// you change the [`Whitespace`][] treatment with [`set_whitespace`][].  Newline characters force
+/// a line break unless you change the [`Newline`][] treatment with [`set_newline`][].  Use
+/// [`push_link`][] and [`linked_string`][] to add strings that link to a URI.  Right-to-left text
+/// is reordered for display, and the base [`Direction`][] can be set with [`set_direction`][].
+///
+/// If the paragraph is split across pages, at least the number of lines set with
+/// [`set_orphans`][] is kept at the bottom of the page and at least the number of lines set with
+/// [`set_widows`][] is moved to the top of the next page.  If this is not possible, the complete
+/// paragraph is moved to the next page.
+///
+/// The line height and spacing are calculated based on the style of each string.
 ///
 /// # Examples
 ///
-/// Direct usage:
+/// With setters:
 /// ```
-/// use genpdf::elements;
-/// let p = elements::FramedElement::new(
-///     elements::Paragraph::new("text"),
-/// );
+/// use genpdf::{elements, style};
+/// let mut p = elements::Paragraph::default();
+/// p.push("This is an ");
+/// p.push_styled("important", style::Color::Rgb(255, 0, 0));
+/// p.push(" message!");
+/// p.set_alignment(genpdf::Alignment::Center);
 /// ```
 ///
-/// Using [`Element::framed`][]:
+/// Chained:
 /// ```
-/// use genpdf::{elements, style, Element as _};
-/// let p = elements::Paragraph::new("text").framed(style::LineStyle::new());
+/// use genpdf::{elements, style};
+/// let p = elements::Paragraph::default()
+///     .string("This is an ")
+///     .styled_string("important", style::Color::Rgb(255, 0, 0))
+///     .string(" message!")
+///     .aligned(genpdf::Alignment::Center);
 /// ```
 ///
-/// [`Element::framed`]: ../trait.Element.html#method.framed
+/// [`Style`]: ../style/struct.Style.html
+/// [`Alignment`]: ../enum.Alignment.html
+/// [`Element::styled`]: ../trait.Element.html#method.styled
+/// [`push`]: #method.push
+/// [`push_styled`]: #method.push_styled
+/// [`string`]: #method.string
+/// [`string_styled`]: #method.string_styled
+/// [`push_link`]: #method.push_link
+/// [`linked_string`]: #method.linked_string
+/// [`set_last_line_alignment`]: #method.set_last_line_alignment
+/// [`Direction`]: ../enum.Direction.html
+/// [`set_direction`]: #method.set_direction
+/// [`LineBreaking`]: ../enum.LineBreaking.html
+/// [`set_line_breaking`]: #method.set_line_breaking
+/// [`Whitespace`]: ../enum.Whitespace.html
+/// [`set_whitespace`]: #method.set_whitespace
+/// [`Newline`]: ../enum.Newline.html
+/// [`set_newline`]: #method.set_newline
+/// [`set_orphans`]: #method.set_orphans
+/// [`set_widows`]: #method.set_widows
 #[derive(Clone, Debug, Default)]
-pub struct FramedElement<E: Element> {
-    element: E,
-    is_first: bool,
-    line_style: LineStyle,
+pub struct Paragraph {
+    text: Vec<StyledString>,
+    words: collections::VecDeque<StyledString>,
+    style_applied: bool,
+    alignment: Option<Alignment>,
+    last_line_alignment: Option<Alignment>,
+    direction: Direction,
+    paragraph_level: u8,
+    line_breaking: LineBreaking,
+    whitespace: Whitespace,
+    newline: Newline,
+    widows: Option<usize>,
+    orphans: Option<usize>,
+    line_spacing: Option<f64>,
+    leading: Option<Mm>,
 }
 
-impl<E: Element> FramedElement<E> {
-    /// Creates a new framed element that wraps the given element.
-    pub fn new(element: E) -> FramedElement<E> {
-        FramedElement::with_line_style(element, LineStyle::new())
-    }
-
-    /// Creates a new framed element that wraps the given element,
-    /// and with the given line style.
-    pub fn with_line_style(element: E, line_style: impl Into<LineStyle>) -> FramedElement<E> {
-        Self {
-            is_first: true,
-            element,
-            line_style: line_style.into(),
+impl Paragraph {
+    /// Creates a new paragraph with the given content.
+    pub fn new(text: impl Into<StyledString>) -> Paragraph {
+        Paragraph {
+            text: vec![text.into()],
+            ..Default::default()
         }
     }
-}
-
-impl<E: Element> Element for FramedElement<E> {
-    fn render(
-        &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        // For the element area calculations, we have to take into account the full line thickness.
-        // For the frame area, we only need half because we specify the center of the line.
-        let line_thickness = self.line_style.thickness();
-        let line_offset = line_thickness / 2.0;
-
-        // Calculate the areas in which to draw the element and the frame.
-        let mut element_area = area.clone();
-        let mut frame_area = area.clone();
-        element_area.add_margins(Margins::trbl(
-            0,
-            line_thickness,
-            line_thickness,
-            line_thickness,
-        ));
-        frame_area.add_margins(Margins::trbl(0, line_offset, 0, line_offset));
-        if self.is_first {
-            element_area.add_margins(Margins::trbl(line_thickness, 0, 0, 0));
-            frame_area.add_margins(Margins::trbl(line_offset, 0, 0, 0));
-        }
 
-        // Draw the element.
-        let mut result = self.element.render(context, element_area, style)?;
-        result.size.width = area.size().width;
-        if result.has_more {
-            frame_area.set_height(result.size.height + line_offset);
-        } else {
-            frame_area.set_height(result.size.height + line_thickness);
-        }
+    /// Creates a new paragraph from the given markup with inline tags for bold, italic and colored
+    /// text.
+    ///
+    /// See [`style::parse_markup`][] for the supported tags.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use genpdf::elements;
+    /// let p = elements::Paragraph::from_markup("<b>bold</b> and <i>italic</i>")
+    ///     .expect("Invalid markup");
+    /// ```
+    ///
+    /// [`style::parse_markup`]: ../style/fn.parse_markup.html
+    pub fn from_markup(markup: &str) -> Result<Paragraph, Error> {
+        crate::style::parse_markup(markup).map(Paragraph::from)
+    }
 
-        // Draw the frame.
-        let top_left = Position::default();
-        let top_right = Position::new(frame_area.size().width, 0);
-        let bottom_left = Position::new(0, frame_area.size().height);
-        let bottom_right = Position::new(frame_area.size().width, frame_area.size().height);
+    /// Sets the alignment of this paragraph.
+    ///
+    /// If no alignment is set, the alignment of the style is used, see
+    /// [`Style::set_alignment`][].  If the style does not have an alignment either, the paragraph
+    /// is left-flushed, or right-flushed if it has a right-to-left direction, see
+    /// [`set_direction`][].
+    ///
+    /// [`set_direction`]: #method.set_direction
+    /// [`Style::set_alignment`]: ../style/struct.Style.html#method.set_alignment
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = Some(alignment);
+    }
 
-        if self.is_first {
-            result.size.height += line_thickness;
-            frame_area.draw_line(
-                vec![bottom_right, top_right, top_left, bottom_left],
-                self.line_style,
-            );
-        }
-        if !result.has_more {
-            result.size.height += line_thickness;
-            frame_area.draw_line(
-                vec![top_left, bottom_left, bottom_right, top_right],
-                self.line_style,
-            );
-        } else {
-            frame_area.draw_line(vec![top_left, bottom_left], self.line_style);
-            frame_area.draw_line(vec![top_right, bottom_right], self.line_style);
-        }
+    /// Sets the alignment of this paragraph and returns the paragraph.
+    pub fn aligned(mut self, alignment: Alignment) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
 
-        self.is_first = false;
+    /// Sets the alignment of the last line of this paragraph.
+    ///
+    /// This alignment is only used if the paragraph is justified, see [`Alignment::Justify`][].
+    /// Per default, the last line of a justified paragraph is left-flushed, or right-flushed if
+    /// the paragraph has a right-to-left direction.
+    ///
+    /// [`Alignment::Justify`]: ../enum.Alignment.html#variant.Justify
+    pub fn set_last_line_alignment(&mut self, alignment: Alignment) {
+        self.last_line_alignment = Some(alignment);
+    }
 
-        Ok(result)
+    /// Sets the alignment of the last line of this paragraph and returns the paragraph.
+    ///
+    /// This alignment is only used if the paragraph is justified, see [`Alignment::Justify`][].
+    /// Per default, the last line of a justified paragraph is left-flushed, or right-flushed if
+    /// the paragraph has a right-to-left direction.
+    ///
+    /// [`Alignment::Justify`]: ../enum.Alignment.html#variant.Justify
+    pub fn with_last_line_alignment(mut self, alignment: Alignment) -> Self {
+        self.set_last_line_alignment(alignment);
+        self
     }
 
-    fn measure(
-        &mut self,
-        context: &Context,
-        area: render::Area<'_>,
-        style: Style,
-    ) -> Result<RenderResult, Error> {
-        // For the element area calculations, we have to take into account the full line thickness.
-        // For the frame area, we only need half because we specify the center of the line.
-        let line_thickness = self.line_style.thickness();
+    /// Sets the base direction of this paragraph.
+    ///
+    /// Per default, paragraphs have a left-to-right direction.  Independent of the base
+    /// direction, right-to-left text like Arabic or Hebrew text is rendered in the correct visual
+    /// order.  The base direction determines the order of text runs with different directions
+    /// and the default alignment of the paragraph, see [`Direction`][].
+    ///
+    /// [`Direction`]: ../enum.Direction.html
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
 
-        // Calculate the areas in which to draw the element and the frame.
-        let mut element_area = area.clone();
-        element_area.add_margins(Margins::trbl(
-            0,
-            line_thickness,
-            line_thickness,
-            line_thickness,
-        ));
+    /// Sets the base direction of this paragraph and returns the paragraph.
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.set_direction(direction);
+        self
+    }
 
-        // Draw the element.
-        let mut result = self.element.measure(context, element_area, style)?;
-        result.size.width = area.size().width;
+    /// Sets the line breaking algorithm for this paragraph.
+    pub fn set_line_breaking(&mut self, line_breaking: LineBreaking) {
+        self.line_breaking = line_breaking;
+    }
 
-        if self.is_first {
-            result.size.height += line_thickness;
-        }
-        if !result.has_more {
-            result.size.height += line_thickness;
-        }
+    /// Sets the line breaking algorithm for this paragraph and returns the paragraph.
+    pub fn with_line_breaking(mut self, line_breaking: LineBreaking) -> Self {
+        self.set_line_breaking(line_breaking);
+        self
+    }
 
-        self.is_first = false;
+    /// Sets the line spacing factor for this paragraph.
+    ///
+    /// This overrides the line spacing of the style of the paragraph, see
+    /// [`Style::set_line_spacing`][], but not the line spacing of the styled strings in the
+    /// paragraph.
+    ///
+    /// [`Style::set_line_spacing`]: ../style/struct.Style.html#method.set_line_spacing
+    pub fn set_line_spacing(&mut self, line_spacing: f64) {
+        self.line_spacing = Some(line_spacing);
+        self.leading = None;
+    }
 
-        Ok(result)
+    /// Sets the line spacing factor for this paragraph and returns the paragraph.
+    ///
+    /// See [`set_line_spacing`][] for more information.
+    ///
+    /// [`set_line_spacing`]: #method.set_line_spacing
+    pub fn with_line_spacing(mut self, line_spacing: f64) -> Self {
+        self.set_line_spacing(line_spacing);
+        self
     }
-}
 
-/// An unordered list of elements with bullet points.
+    /// Sets the leading, i. e. the distance between the baselines of consecutive lines, for this
+    /// paragraph.
+    ///
+    /// This overrides the line spacing of the style of the paragraph, see
+    /// [`Style::set_leading`][], but not the line spacing of the styled strings in the paragraph.
+    ///
+    /// [`Style::set_leading`]: ../style/struct.Style.html#method.set_leading
+    pub fn set_leading(&mut self, leading: impl Into<Mm>) {
+        self.leading = Some(leading.into());
+        self.line_spacing = None;
+    }
+
+    /// Sets the leading for this paragraph and returns the paragraph.
+    ///
+    /// See [`set_leading`][] for more information.
+    ///
+    /// [`set_leading`]: #method.set_leading
+    pub fn with_leading(mut self, leading: impl Into<Mm>) -> Self {
+        self.set_leading(leading);
+        self
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are moved to the top of the next
+    /// page if the paragraph is split.
+    ///
+    /// If no number is set, the default of the document is used, see
+    /// [`Document::set_widows`][].
+    ///
+    /// [`Document::set_widows`]: ../struct.Document.html#method.set_widows
+    pub fn set_widows(&mut self, lines: usize) {
+        self.widows = Some(lines);
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are moved to the top of the next
+    /// page if the paragraph is split and returns the paragraph.
+    ///
+    /// See [`set_widows`][] for more information.
+    ///
+    /// [`set_widows`]: #method.set_widows
+    pub fn with_widows(mut self, lines: usize) -> Self {
+        self.set_widows(lines);
+        self
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are kept at the bottom of the page
+    /// if the paragraph is split.
+    ///
+    /// If no number is set, the default of the document is used, see
+    /// [`Document::set_orphans`][].
+    ///
+    /// [`Document::set_orphans`]: ../struct.Document.html#method.set_orphans
+    pub fn set_orphans(&mut self, lines: usize) {
+        self.orphans = Some(lines);
+    }
+
+    /// Sets the minimum number of lines of this paragraph that are kept at the bottom of the page
+    /// if the paragraph is split and returns the paragraph.
+    ///
+    /// See [`set_orphans`][] for more information.
+    ///
+    /// [`set_orphans`]: #method.set_orphans
+    pub fn with_orphans(mut self, lines: usize) -> Self {
+        self.set_orphans(lines);
+        self
+    }
+
+    /// Renders this paragraph into the given area with the given maximum height and returns the
+    /// remainder of the paragraph that did not fit.
+    ///
+    /// The remainder is `None` if the full paragraph has been rendered.  Otherwise it is a
+    /// paragraph that resumes exactly where this paragraph stopped, keeping its style and
+    /// settings.  It can be rendered into another area, for example to flow text through
+    /// multiple columns or boxes that are positioned by a custom element.  Note that a remainder
+    /// is also returned if nothing could be printed in the given area, so you should check the
+    /// size of the returned [`RenderResult`][] to avoid endless loops.
+    ///
+    /// [`RenderResult`]: ../struct.RenderResult.html
+    pub fn render_bounded(
+        mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+        max_height: Mm,
+    ) -> Result<(RenderResult, Option<Paragraph>), Error> {
+        if area.size().height > max_height {
+            area.set_height(max_height);
+        }
+        let result = self.render(context, area, style)?;
+        let remainder = if result.has_more { Some(self) } else { None };
+        Ok((result, remainder))
+    }
+
+    /// Sets the treatment of consecutive whitespace for this paragraph.
+    ///
+    /// Per default, runs of spaces and tabs are collapsed.  See [`Whitespace`][] for details and
+    /// for the interaction with wrapping.
+    ///
+    /// [`Whitespace`]: ../enum.Whitespace.html
+    pub fn set_whitespace(&mut self, whitespace: Whitespace) {
+        self.whitespace = whitespace;
+    }
+
+    /// Sets the treatment of consecutive whitespace for this paragraph and returns the paragraph.
+    ///
+    /// Per default, runs of spaces and tabs are collapsed.  See [`Whitespace`][] for details and
+    /// for the interaction with wrapping.
+    ///
+    /// [`Whitespace`]: ../enum.Whitespace.html
+    pub fn with_whitespace(mut self, whitespace: Whitespace) -> Self {
+        self.set_whitespace(whitespace);
+        self
+    }
+
+    /// Sets the treatment of newline characters for this paragraph.
+    ///
+    /// Per default, a newline forces a line break.  See [`Newline`][] for details.
+    ///
+    /// [`Newline`]: ../enum.Newline.html
+    pub fn set_newline(&mut self, newline: Newline) {
+        self.newline = newline;
+    }
+
+    /// Sets the treatment of newline characters for this paragraph and returns the paragraph.
+    ///
+    /// Per default, a newline forces a line break.  See [`Newline`][] for details.
+    ///
+    /// [`Newline`]: ../enum.Newline.html
+    pub fn with_newline(mut self, newline: Newline) -> Self {
+        self.set_newline(newline);
+        self
+    }
+
+    /// Splits the words of this paragraph into lines that fit into the given width.
+    ///
+    /// Returns the lines and the length difference between the input words and the line for
+    /// every line, see [`wrap::Wrapper`][], or `None` if the paragraph should be skipped according
+    /// to the [`Overflow`][] setting.
+    ///
+    /// [`wrap::Wrapper`]: ../wrap/struct.Wrapper.html
+    /// [`Overflow`]: ../enum.Overflow.html
+    fn wrap(
+        &self,
+        context: &Context,
+        width: Mm,
+        style: Style,
+    ) -> Result<Option<Vec<Line<'_>>>, Error> {
+        let overflow = context.overflow();
+        if width <= Mm(0.0) {
+            return match overflow {
+                Overflow::Error => Err(Error::new(
+                    "Tried to render a paragraph in an area without a positive width",
+                    ErrorKind::PageSizeExceeded,
+                )),
+                Overflow::Clip | Overflow::Skip | Overflow::Break => Ok(None),
+            };
+        }
+
+        let words = self.words.iter().map(Into::into);
+        if self.line_breaking == LineBreaking::Optimal && style.wrap() == Wrap::Words {
+            if let Some(lines) = wrap::break_lines_optimally(context, words.clone(), width) {
+                return Ok(Some(lines.into_iter().map(|line| (line, 0)).collect()));
+            }
+        }
+
+        let mut wrapper = wrap::Wrapper::new(words, context, width);
+        wrapper.set_wrap(style.wrap());
+        wrapper.set_clip_overflow(overflow == Overflow::Clip);
+        wrapper.set_break_overflow(overflow == Overflow::Break);
+        let lines = wrapper.by_ref().collect();
+        match wrapper.overflowed_word() {
+            Some(_) if overflow == Overflow::Skip => Ok(None),
+            Some(word) => Err(Error::new(
+                format!("Page overflowed while trying to wrap the string '{}'", word),
+                ErrorKind::PageSizeExceeded,
+            )),
+            None => Ok(Some(lines)),
+        }
+    }
+
+    /// Adds a string to the end of this paragraph.
+    pub fn push(&mut self, s: impl Into<StyledString>) {
+        self.text.push(s.into());
+    }
+
+    /// Adds a string to the end of this paragraph and returns the paragraph.
+    pub fn string(mut self, s: impl Into<StyledString>) -> Self {
+        self.push(s);
+        self
+    }
+
+    /// Adds a string with the given style to the end of this paragraph.
+    pub fn push_styled(&mut self, s: impl Into<String>, style: impl Into<Style>) {
+        self.text.push(StyledString::new(s, style))
+    }
+
+    /// Adds a string with the given style to the end of this paragraph and returns the paragraph.
+    pub fn styled_string(mut self, s: impl Into<String>, style: impl Into<Style>) -> Self {
+        self.push_styled(s, style);
+        self
+    }
+
+    /// Adds a string that links to the given URI to the end of this paragraph.
+    ///
+    /// See [`StyledString::set_link`][] for more information.
+    ///
+    /// [`StyledString::set_link`]: ../style/struct.StyledString.html#method.set_link
+    pub fn push_link(&mut self, s: impl Into<StyledString>, uri: impl Into<String>) {
+        self.text.push(s.into().with_link(uri))
+    }
+
+    /// Adds a string that links to the given URI to the end of this paragraph and returns the
+    /// paragraph.
+    ///
+    /// See [`StyledString::set_link`][] for more information.
+    ///
+    /// [`StyledString::set_link`]: ../style/struct.StyledString.html#method.set_link
+    pub fn linked_string(mut self, s: impl Into<StyledString>, uri: impl Into<String>) -> Self {
+        self.push_link(s, uri);
+        self
+    }
+
+    fn get_alignment(&self, style: Style, is_last_line: bool) -> Alignment {
+        let default = if self.paragraph_level % 2 == 1 {
+            Alignment::Right
+        } else {
+            Alignment::Left
+        };
+        let alignment = self
+            .alignment
+            .or_else(|| style.explicit_alignment())
+            .unwrap_or(default);
+        if alignment == Alignment::Justify && is_last_line {
+            self.last_line_alignment.unwrap_or(default)
+        } else {
+            alignment
+        }
+    }
+
+    fn get_offset(alignment: Alignment, width: Mm, max_width: Mm) -> Mm {
+        match alignment {
+            Alignment::Left | Alignment::Justify => Mm::default(),
+            Alignment::Center => (max_width - width) / 2.0,
+            Alignment::Right => max_width - width,
+        }
+    }
+
+    /// Returns the additional spacing for every space in the given line that is required to
+    /// justify the line.
+    fn get_word_spacing(context: &Context, line: &[StyledCow<'_>], max_width: Mm) -> Mm {
+        // Trailing spaces are not stretched and do not count towards the line width.
+        let mut width = Mm(0.0);
+        let mut spaces = 0;
+        let mut is_trailing = true;
+        for s in line.iter().rev() {
+            let text = if is_trailing { s.s.trim_end() } else { &s.s };
+            is_trailing &= text.is_empty();
+            width += s.style.str_width(&context.font_cache, text);
+            spaces += text.matches(' ').count();
+        }
+        if spaces > 0 && width < max_width {
+            (max_width - width) / spaces as f64
+        } else {
+            Mm(0.0)
+        }
+    }
+
+    /// Returns the number of the given lines that should be rendered into the given area, taking
+    /// into account the widow and orphan settings.
+    ///
+    /// If the lines cannot be split according to these settings, zero is returned so that the
+    /// paragraph is moved to the next page, unless the area is on an empty page.
+    fn fitting_lines(
+        &self,
+        context: &Context,
+        area: &render::Area<'_>,
+        lines: &[Line<'_>],
+    ) -> usize {
+        let mut height = area.size().height;
+        let mut fitting = 0;
+        for (line, _) in lines {
+            let metrics = line
+                .iter()
+                .map(|s| s.style.metrics(&context.font_cache))
+                .fold(fonts::Metrics::default(), |max, m| max.max(&m));
+            // The same check as in render::TextSection::new
+            if metrics.glyph_height > height {
+                break;
+            }
+            height -= metrics.line_height;
+            fitting += 1;
+        }
+        if fitting >= lines.len() || !area.has_content() {
+            return fitting;
+        }
+
+        let widows = self.widows.unwrap_or_else(|| context.widows());
+        let orphans = self.orphans.unwrap_or_else(|| context.orphans());
+        let mut lines_on_page = fitting.min(lines.len().saturating_sub(widows));
+        if lines_on_page < orphans {
+            lines_on_page = 0;
+        }
+        lines_on_page
+    }
+
+    /// Renders the given lines and returns the result and the number of bytes that have been
+    /// rendered.
+    fn render_lines(
+        &self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+        lines: Vec<Line<'_>>,
+    ) -> Result<(RenderResult, usize), Error> {
+        let mut result = RenderResult::default();
+        let mut rendered_len = 0;
+        let line_count = lines.len();
+        for (idx, (line, delta)) in lines.into_iter().enumerate() {
+            let width = line_width(context, &line);
+            // Calculate the maximum line height
+            let metrics = line
+                .iter()
+                .map(|s| s.style.metrics(&context.font_cache))
+                .fold(fonts::Metrics::default(), |max, m| max.max(&m));
+            let is_last_line = idx + 1 == line_count
+                || line
+                    .last()
+                    .map(|s| wrap::is_newline(&s.s))
+                    .unwrap_or_default();
+            let alignment = self.get_alignment(style, is_last_line);
+            let max_width = area.size().width;
+            let position = Position::new(Self::get_offset(alignment, width, max_width), 0);
+
+            let word_spacing = if alignment == Alignment::Justify {
+                Self::get_word_spacing(context, &line, max_width)
+            } else {
+                Mm(0.0)
+            };
+            let line = bidi::reorder_line(line, self.paragraph_level);
+            // The backgrounds have to be drawn before the text section is started
+            if metrics.glyph_height <= area.size().height {
+                fill_backgrounds(context, &area, &line, position.x, word_spacing, metrics);
+            }
+            if let Some(mut section) = area.text_section(&context.font_cache, position, metrics) {
+                section.set_word_spacing(word_spacing);
+                add_links(context, &area, &line, position.x, word_spacing, metrics);
+                for s in line {
+                    if !wrap::is_newline(&s.s) {
+                        section.print_str(&s.s, s.style)?;
+                    }
+                    rendered_len += s.s.len();
+                }
+                rendered_len -= delta;
+            } else {
+                result.has_more = true;
+                break;
+            }
+            result.size = result
+                .size
+                .stack_vertical(Size::new(width, metrics.line_height));
+            area.add_offset(Position::new(0, metrics.line_height));
+        }
+        Ok((result, rendered_len))
+    }
+
+    /// Determines the paragraph embedding level for the bidi algorithm from the direction and the
+    /// text of this paragraph.
+    fn set_paragraph_level(&mut self) {
+        self.paragraph_level = match self.direction {
+            Direction::LeftToRight => 0,
+            Direction::RightToLeft => 1,
+            Direction::Auto => {
+                bidi::paragraph_level(self.text.iter().map(|s| s.s.as_str())).unwrap_or_default()
+            }
+        };
+    }
+
+    fn apply_style(&mut self, mut style: Style) {
+        if !self.style_applied {
+            if let Some(line_spacing) = self.line_spacing {
+                style.set_line_spacing(line_spacing);
+            }
+            if let Some(leading) = self.leading {
+                style.set_leading(leading);
+            }
+            for s in &mut self.text {
+                s.style = style.and(s.style);
+            }
+            self.style_applied = true;
+        }
+    }
+}
+
+impl Element for Paragraph {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = RenderResult::default();
+
+        self.apply_style(style);
+
+        if self.words.is_empty() {
+            if self.text.is_empty() {
+                return Ok(result);
+            }
+            wrap::apply_newlines(&mut self.text, self.newline);
+            wrap::apply_whitespace(&mut self.text, self.whitespace);
+            self.set_paragraph_level();
+            self.words = wrap::Words::new(mem::take(&mut self.text)).collect();
+        }
+
+        let mut lines = match self.wrap(context, area.size().width, style)? {
+            Some(lines) => lines,
+            None => {
+                self.words.clear();
+                return Ok(result);
+            }
+        };
+        let line_count = lines.len();
+        lines.truncate(self.fitting_lines(context, &area, &lines));
+        let is_truncated = lines.len() < line_count;
+        let max_width = area.size().width;
+        let is_clipped = lines
+            .iter()
+            .any(|(line, _)| line_width(context, line) > max_width);
+        let (mut result, mut rendered_len) = if is_clipped {
+            area.with_clipping(|area| self.render_lines(context, area, style, lines))?
+        } else {
+            self.render_lines(context, area, style, lines)?
+        };
+        result.has_more |= is_truncated;
+
+        // Remove the rendered data from self.words so that we don’t render it again on the next
+        // call to render.  If the paragraph is printed on a single line, the text that did not fit
+        // into the line is discarded.
+        if style.wrap() != Wrap::Words && !result.has_more {
+            self.words.clear();
+        }
+        while rendered_len > 0 && !self.words.is_empty() {
+            if self.words[0].s.len() <= rendered_len {
+                rendered_len -= self.words[0].s.len();
+                self.words.pop_front();
+            } else {
+                self.words[0].s.replace_range(..rendered_len, "");
+                rendered_len = 0;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+
+        self.apply_style(style);
+
+        if self.words.is_empty() {
+            if self.text.is_empty() {
+                return Ok(result);
+            }
+            wrap::apply_newlines(&mut self.text, self.newline);
+            wrap::apply_whitespace(&mut self.text, self.whitespace);
+            self.set_paragraph_level();
+            self.words = wrap::Words::new(mem::take(&mut self.text)).collect();
+        }
+
+        let lines = self
+            .wrap(context, area.size().width, style)?
+            .unwrap_or_default();
+        for (line, _) in lines {
+            let width = line_width(context, &line);
+            // Calculate the maximum line height
+            let metrics = line
+                .iter()
+                .map(|s| s.style.metrics(&context.font_cache))
+                .fold(fonts::Metrics::default(), |max, m| max.max(&m));
+
+            result.size = result
+                .size
+                .stack_vertical(Size::new(width, metrics.line_height));
+            area.add_offset(Position::new(0, metrics.line_height));
+        }
+
+        Ok(result)
+    }
+}
+
+/// A wrapped line and the length difference between the input words and the line, see
+/// [`wrap::Wrapper`][].
+///
+/// [`wrap::Wrapper`]: ../wrap/struct.Wrapper.html
+type Line<'s> = (Vec<StyledCow<'s>>, usize);
+
+/// Adds the link annotations for the linked strings of the given line, starting at the given
+/// horizontal offset.
+///
+/// Adjacent strings with the same link are merged into one annotation.  Trailing spaces are not
+/// part of the annotation.
+fn add_links(
+    context: &Context,
+    area: &render::Area<'_>,
+    line: &[StyledCow<'_>],
+    mut x: Mm,
+    word_spacing: Mm,
+    metrics: fonts::Metrics,
+) {
+    let mut current: Option<(&str, Mm, Mm)> = None;
+    let add_link = |(uri, start, end): (&str, Mm, Mm)| {
+        let size = Size::new(end - start, metrics.line_height);
+        area.add_uri_link(uri, Position::new(start, 0), size);
+    };
+    for s in line.iter().filter(|s| !wrap::is_newline(&s.s)) {
+        let width = |text: &str| {
+            s.style.str_width(&context.font_cache, text)
+                + word_spacing * text.matches(' ').count() as f64
+        };
+        let end = x + width(s.s.trim_end());
+        match (current, s.link.as_deref()) {
+            (Some((uri, start, _)), Some(link)) if uri == link => {
+                current = Some((uri, start, end));
+            }
+            (_, link) => {
+                if let Some(current) = current {
+                    add_link(current);
+                }
+                current = link.map(|link| (link, x, end));
+            }
+        }
+        x += width(&s.s);
+    }
+    if let Some(current) = current {
+        add_link(current);
+    }
+}
+
+/// Fills the background of the strings of the given line that have a background color, starting
+/// at the given horizontal offset.
+///
+/// Adjacent strings with the same background color are filled with one rectangle.  Trailing
+/// spaces are not filled.
+fn fill_backgrounds(
+    context: &Context,
+    area: &render::Area<'_>,
+    line: &[StyledCow<'_>],
+    mut x: Mm,
+    word_spacing: Mm,
+    metrics: fonts::Metrics,
+) {
+    let mut current: Option<(Color, Mm, Mm)> = None;
+    let fill = |(color, start, end): (Color, Mm, Mm)| {
+        let mut area = area.clone();
+        area.add_offset(Position::new(start, 0));
+        area.set_size(Size::new(end - start, metrics.glyph_height));
+        area.fill_color(color);
+    };
+    for s in line.iter().filter(|s| !wrap::is_newline(&s.s)) {
+        let width = |text: &str| {
+            s.style.str_width(&context.font_cache, text)
+                + word_spacing * text.matches(' ').count() as f64
+        };
+        let end = x + width(s.s.trim_end());
+        match (current, s.style.background()) {
+            (Some((color, start, _)), Some(background)) if color == background => {
+                current = Some((color, start, end));
+            }
+            (_, background) => {
+                if let Some(current) = current {
+                    fill(current);
+                }
+                current = background.map(|background| (background, x, end));
+            }
+        }
+        x += width(&s.s);
+    }
+    if let Some(current) = current {
+        fill(current);
+    }
+}
+
+/// Returns the width of the given line.
+fn line_width(context: &Context, line: &[StyledCow<'_>]) -> Mm {
+    line.iter()
+        .filter(|s| !wrap::is_newline(&s.s))
+        .map(|s| s.width(&context.font_cache))
+        .sum()
+}
+
+impl From<Vec<StyledString>> for Paragraph {
+    fn from(text: Vec<StyledString>) -> Paragraph {
+        Paragraph {
+            text,
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Into<StyledString>> iter::Extend<T> for Paragraph {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for s in iter {
+            self.push(s);
+        }
+    }
+}
+
+impl<T: Into<StyledString>> iter::FromIterator<T> for Paragraph {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut paragraph = Paragraph::default();
+        paragraph.extend(iter);
+        paragraph
+    }
+}
+
+/// A heading of a section of the document.
+///
+/// A heading has a level from 1 (top-level section) to 6.  It is rendered like a paragraph using
+/// the heading style for its level that is set for the document, see
+/// [`Document::set_heading_style`][].  If heading numbering is enabled with
+/// [`Document::set_heading_numbering`][], the heading is prefixed with its section number, for
+/// example “1.2.3”.  The counter for a level is reset whenever a heading with a lower level is
+/// rendered.  Use [`set_numbered`][] to exclude a heading from the numbering.
+///
+/// Per default, every heading is added as a bookmark to the outline of the PDF document.  It is
+/// also registered with the [`Context`][] so that it can be listed in a table of contents, see
+/// [`Context::headings`][].
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let mut layout = elements::LinearLayout::vertical();
+/// layout.push(elements::Heading::new(1, "Introduction"));
+/// layout.push(elements::Paragraph::new("Text"));
+/// layout.push(elements::Heading::new(2, "Motivation"));
+/// ```
+///
+/// [`Document::set_heading_style`]: ../struct.Document.html#method.set_heading_style
+/// [`Document::set_heading_numbering`]: ../struct.Document.html#method.set_heading_numbering
+/// [`set_numbered`]: #method.set_numbered
+/// [`Context`]: ../struct.Context.html
+/// [`Context::headings`]: ../struct.Context.html#method.headings
+#[derive(Clone, Debug)]
+pub struct Heading {
+    level: usize,
+    text: StyledString,
+    alignment: Option<Alignment>,
+    is_numbered: bool,
+    has_bookmark: bool,
+    paragraph: Option<(Option<String>, Paragraph)>,
+    is_registered: bool,
+}
+
+impl Heading {
+    /// The highest supported heading level.
+    pub const MAX_LEVEL: usize = 6;
+
+    /// Creates a new heading with the given level and text.
+    ///
+    /// The level is clamped to the range from 1 to [`MAX_LEVEL`][].
+    ///
+    /// [`MAX_LEVEL`]: #associatedconstant.MAX_LEVEL
+    pub fn new(level: usize, text: impl Into<StyledString>) -> Heading {
+        Heading {
+            level: level.max(1).min(Self::MAX_LEVEL),
+            text: text.into(),
+            alignment: None,
+            is_numbered: true,
+            has_bookmark: true,
+            paragraph: None,
+            is_registered: false,
+        }
+    }
+
+    /// Returns the level of this heading.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Sets the alignment of this heading.
+    ///
+    /// If no alignment is set, the alignment of the style is used, see
+    /// [`Style::set_alignment`][].
+    ///
+    /// [`Style::set_alignment`]: ../style/struct.Style.html#method.set_alignment
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = Some(alignment);
+    }
+
+    /// Sets the alignment of this heading and returns the heading.
+    pub fn aligned(mut self, alignment: Alignment) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
+
+    /// Sets whether this heading is numbered if heading numbering is enabled for the document.
+    ///
+    /// Unnumbered headings do not change the section counters.  Per default, all headings are
+    /// numbered.
+    pub fn set_numbered(&mut self, is_numbered: bool) {
+        self.is_numbered = is_numbered;
+    }
+
+    /// Sets whether this heading is numbered if heading numbering is enabled for the document and
+    /// returns the heading.
+    pub fn numbered(mut self, is_numbered: bool) -> Self {
+        self.set_numbered(is_numbered);
+        self
+    }
+
+    /// Sets whether this heading is added as a bookmark to the document outline.
+    ///
+    /// Per default, all headings are added to the outline.
+    pub fn set_bookmark(&mut self, has_bookmark: bool) {
+        self.has_bookmark = has_bookmark;
+    }
+
+    /// Sets whether this heading is added as a bookmark to the document outline and returns the
+    /// heading.
+    pub fn with_bookmark(mut self, has_bookmark: bool) -> Self {
+        self.set_bookmark(has_bookmark);
+        self
+    }
+
+    /// Returns the section number and the paragraph for this heading, assigning the number when
+    /// this method is called for the first time.
+    fn paragraph(&mut self, context: &Context) -> &mut (Option<String>, Paragraph) {
+        let level = self.level;
+        let is_numbered = self.is_numbered;
+        let text = &self.text;
+        let alignment = self.alignment;
+        self.paragraph.get_or_insert_with(|| {
+            let number = if is_numbered {
+                context.next_heading_number(level)
+            } else {
+                None
+            };
+            let mut paragraph = Paragraph::default();
+            if let Some(alignment) = alignment {
+                paragraph.set_alignment(alignment);
+            }
+            if let Some(number) = &number {
+                paragraph.push(StyledString::new(format!("{} ", number), text.style));
+            }
+            paragraph.push(text.clone());
+            (number, paragraph)
+        })
+    }
+
+    fn style(&self, context: &Context, style: Style) -> Style {
+        style.and(context.heading_style(self.level))
+    }
+}
+
+impl Element for Heading {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let style = self.style(context, style);
+        let (number, paragraph) = self.paragraph(context);
+        let number = number.clone();
+        let result = paragraph.render(context, area.clone(), style)?;
+
+        if !self.is_registered && result.size.height > Mm(0.0) {
+            self.is_registered = true;
+            let title = self.text.s.clone();
+            if self.has_bookmark {
+                let bookmark = match &number {
+                    Some(number) => format!("{} {}", number, title),
+                    None => title.clone(),
+                };
+                area.add_bookmark(bookmark, self.level, Position::default());
+            }
+            context.add_heading(HeadingEntry {
+                level: self.level,
+                number,
+                title,
+                page: context.page_number(),
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let style = self.style(context, style);
+        self.paragraph(context).1.measure(context, area, style)
+    }
+}
+
+/// A heading that has been rendered, see [`Heading`][] and [`Context::headings`][].
+///
+/// [`Heading`]: struct.Heading.html
+/// [`Context::headings`]: ../struct.Context.html#method.headings
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HeadingEntry {
+    /// The level of the heading, from 1 to 6.
+    pub level: usize,
+    /// The section number of the heading, if it has been numbered.
+    pub number: Option<String>,
+    /// The text of the heading.
+    pub title: String,
+    /// The number of the page that contains the heading, starting with 1.
+    pub page: usize,
+}
+
+/// A table of contents that lists the headings of the document with their page numbers.
+///
+/// Every entry consists of the section number and the title of a [`Heading`][], indented
+/// according to its level, and the page number of the heading, aligned to the right and
+/// connected to the title with dot leaders.  Per default, the headings of all levels are listed,
+/// and every level is indented by 5 mm.
+///
+/// If the table of contents is printed after all headings, for example at the end of the
+/// document, it can be rendered with [`Document::render`][].  Otherwise, the headings are only
+/// known after the document has been rendered, so you have to use
+/// [`Document::render_in_passes`][] instead.  In this case, the table of contents lists the
+/// headings of the previous layout pass, see [`Context::previous_headings`][].
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let toc = elements::TableOfContents::new()
+///     .with_max_level(2)
+///     .with_indent(8);
+/// ```
+///
+/// [`Heading`]: struct.Heading.html
+/// [`Document::render`]: ../struct.Document.html#method.render
+/// [`Document::render_in_passes`]: ../struct.Document.html#method.render_in_passes
+/// [`Context::previous_headings`]: ../struct.Context.html#method.previous_headings
+#[derive(Clone, Debug)]
+pub struct TableOfContents {
+    max_level: usize,
+    indent: Mm,
+    leader: char,
+    rendered_entries: usize,
+}
+
+impl TableOfContents {
+    /// Creates a new table of contents with the default settings.
+    pub fn new() -> TableOfContents {
+        TableOfContents {
+            max_level: Heading::MAX_LEVEL,
+            indent: Mm(5.0),
+            leader: '.',
+            rendered_entries: 0,
+        }
+    }
+
+    /// Sets the maximum level of the headings that are listed in this table of contents.
+    pub fn set_max_level(&mut self, max_level: usize) {
+        self.max_level = max_level;
+    }
+
+    /// Sets the maximum level of the headings that are listed in this table of contents and
+    /// returns the table of contents.
+    pub fn with_max_level(mut self, max_level: usize) -> Self {
+        self.set_max_level(max_level);
+        self
+    }
+
+    /// Sets the indentation per heading level.
+    pub fn set_indent(&mut self, indent: impl Into<Mm>) {
+        self.indent = indent.into();
+    }
+
+    /// Sets the indentation per heading level and returns the table of contents.
+    pub fn with_indent(mut self, indent: impl Into<Mm>) -> Self {
+        self.set_indent(indent);
+        self
+    }
+
+    /// Sets the character that is repeated between the title and the page number.
+    ///
+    /// Use a space to disable the leader.
+    pub fn set_leader(&mut self, leader: char) {
+        self.leader = leader;
+    }
+
+    /// Sets the character that is repeated between the title and the page number and returns the
+    /// table of contents.
+    pub fn with_leader(mut self, leader: char) -> Self {
+        self.set_leader(leader);
+        self
+    }
+
+    /// Returns the headings that are listed in this table of contents and that have not been
+    /// rendered yet.
+    fn entries(&self, context: &Context) -> Vec<HeadingEntry> {
+        let headings = context
+            .previous_headings()
+            .map(<[_]>::to_vec)
+            .unwrap_or_else(|| context.headings());
+        headings
+            .into_iter()
+            .filter(|heading| heading.level <= self.max_level)
+            .skip(self.rendered_entries)
+            .collect()
+    }
+
+    fn render_entry(
+        &self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+        entry: &HeadingEntry,
+    ) -> Result<bool, Error> {
+        let font_cache = &context.font_cache;
+        let title = match &entry.number {
+            Some(number) => format!("{} {}", number, entry.title),
+            None => entry.title.clone(),
+        };
+        let page = entry.page.to_string();
+        let indent = self.indent * entry.level.saturating_sub(1) as f64;
+        if !area.print_str(font_cache, Position::new(indent, 0), style, &title)? {
+            return Ok(false);
+        }
+        let page_x = area.size().width - style.str_width(font_cache, &page);
+        area.print_str(font_cache, Position::new(page_x, 0), style, &page)?;
+
+        let leader_width = style.char_width(font_cache, self.leader);
+        if self.leader != ' ' && leader_width > Mm(0.0) {
+            let start = indent + style.str_width(font_cache, &title) + leader_width;
+            let end = page_x - leader_width;
+            let count = ((end - start).0 / leader_width.0).floor();
+            if count > 0.0 {
+                let leader = self.leader.to_string().repeat(count as usize);
+                let x = end - leader_width * count;
+                area.print_str(font_cache, Position::new(x, 0), style, &leader)?;
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl Default for TableOfContents {
+    fn default() -> TableOfContents {
+        TableOfContents::new()
+    }
+}
+
+impl Element for TableOfContents {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let line_height = style.line_height(&context.font_cache);
+        for entry in self.entries(context) {
+            if !self.render_entry(context, &area, style, &entry)? {
+                result.has_more = true;
+                break;
+            }
+            result.size = result
+                .size
+                .stack_vertical(Size::new(area.size().width, line_height));
+            area.add_offset(Position::new(0, line_height));
+            self.rendered_entries += 1;
+        }
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let line_height = style.line_height(&context.font_cache);
+        let entries = self.entries(context).len();
+        let fitting = (area.size().height.0 / line_height.0).floor() as usize;
+        let lines = entries.min(fitting);
+        result.has_more = lines < entries;
+        if lines > 0 {
+            result.size = Size::new(area.size().width, line_height * lines as f64);
+        }
+        Ok(result)
+    }
+}
+
+/// A line break.
+///
+/// This element inserts a given number of empty lines.  The treatment of breaks at the top and the
+/// bottom of a page depends on the [`BreakPolicy`][] of the document, see
+/// [`Document::set_break_policy`][].
+///
+/// # Example
+///
+/// ```
+/// // Draws 5 empty lines (calculating the line height using the current style)
+/// let b = genpdf::elements::Break::new(5);
+/// ```
+///
+/// [`BreakPolicy`]: ../enum.BreakPolicy.html
+/// [`Document::set_break_policy`]: ../struct.Document.html#method.set_break_policy
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Break {
+    lines: f64,
+}
+
+impl Break {
+    /// Creates a new break with the given number of lines.
+    pub fn new(lines: impl Into<f64>) -> Break {
+        Break {
+            lines: lines.into(),
+        }
+    }
+
+    /// Returns whether this break is discarded because it is at the top of an empty page.
+    fn is_collapsed(context: &Context, area: &render::Area<'_>) -> bool {
+        context.break_policy() == BreakPolicy::Collapse && !area.has_content()
+    }
+}
+
+impl Element for Break {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.lines <= 0.0 {
+            return Ok(result);
+        }
+        if Self::is_collapsed(context, &area) {
+            self.lines = 0.0;
+            return Ok(result);
+        }
+        let line_height = style.line_height(&context.font_cache);
+        let break_height = line_height * self.lines;
+        if break_height < area.size().height {
+            result.size.height = break_height;
+            self.lines = 0.0;
+        } else {
+            result.size.height = area.size().height;
+            self.lines -= result.size.height.0 / line_height.0;
+            if context.break_policy() == BreakPolicy::Collapse {
+                context.break_overflow_page.set(Some(context.page_number()));
+            }
+        }
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.lines <= 0.0 || Self::is_collapsed(context, &area) {
+            return Ok(result);
+        }
+        let line_height = style.line_height(&context.font_cache);
+        let break_height = line_height * self.lines;
+        if break_height < area.size().height {
+            result.size.height = break_height;
+        } else {
+            result.size.height = area.size().height;
+        }
+        Ok(result)
+    }
+}
+
+/// A page break.
+///
+/// This element inserts a page break.
+///
+/// # Example
+///
+/// ```
+/// let pb = genpdf::elements::PageBreak::new();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PageBreak {
+    cont: bool,
+}
+
+impl PageBreak {
+    /// Creates a new page break.
+    pub fn new() -> PageBreak {
+        PageBreak::default()
+    }
+}
+
+impl Element for PageBreak {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let break_overflow_page = context.break_overflow_page.take();
+        let is_collapsed = !area.has_content()
+            && break_overflow_page.is_some()
+            && break_overflow_page == context.page_number().checked_sub(1);
+        if self.cont || is_collapsed {
+            self.cont = true;
+            Ok(RenderResult::default())
+        } else {
+            // We don’t use (0,0) as the size as this might abort the render process if this is the
+            // first element on a new page, see the Rendering Process section of the crate
+            // documentation.
+            self.cont = true;
+            Ok(RenderResult {
+                size: Size::new(1, 0),
+                has_more: true,
+            })
+        }
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        _area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.cont {
+            Ok(RenderResult::default())
+        } else {
+            // Measuring does not consume the page break, so it is still inserted when this element
+            // is rendered.
+            Ok(RenderResult {
+                size: Size::new(1, 0),
+                has_more: true,
+            })
+        }
+    }
+}
+
+/// Empty space with a fixed size.
+///
+/// In contrast to a [`Break`][], the size of a spacer does not depend on the line height of the
+/// current style.  The width is only relevant if the spacer is used in a horizontal layout.  If
+/// the spacer is higher than the remaining space on the page, it fills the rest of the page and is
+/// not continued on the next page.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let spacer = elements::Spacer::vertical(12);
+/// ```
+///
+/// [`Break`]: struct.Break.html
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Spacer {
+    size: Size,
+}
+
+impl Spacer {
+    /// Creates a new spacer with the given size.
+    pub fn new(size: impl Into<Size>) -> Spacer {
+        Spacer { size: size.into() }
+    }
+
+    /// Creates a new spacer with the given height and no width.
+    pub fn vertical(height: impl Into<Mm>) -> Spacer {
+        Spacer::new(Size::new(0, height))
+    }
+
+    /// Creates a new spacer with the given width and no height.
+    pub fn horizontal(width: impl Into<Mm>) -> Spacer {
+        Spacer::new(Size::new(width, 0))
+    }
+
+    /// Returns the size of this spacer.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl Element for Spacer {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.measure(context, area, style)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        result.size.width = self.size.width.min(area.size().width);
+        result.size.height = self.size.height.min(area.size().height);
+        Ok(result)
+    }
+}
+
+/// Empty space that fills the remaining height of the current page.
+///
+/// If content is set with [`set_content`][], the content is rendered at the bottom of the
+/// filled space, for example to push a signature block or a summary to the bottom of the last
+/// page.  If the content does not fit into the remaining space of the page, the fill is moved to
+/// the next page.  If it does not fit on an empty page either, the content is rendered like a
+/// normal element from the top of the page.
+///
+/// A fill without content ends the current page, so all following elements are rendered on the
+/// next page.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let fill =
+///     elements::Fill::new().with_content(elements::SignatureLine::new(60).with_label("Signature"));
+/// ```
+///
+/// [`set_content`]: #method.set_content
+#[derive(Default)]
+pub struct Fill {
+    content: Option<Box<dyn Element>>,
+    is_rendered: bool,
+    is_split: bool,
+}
+
+impl Fill {
+    /// Creates a new fill without content.
+    pub fn new() -> Fill {
+        Fill::default()
+    }
+
+    /// Sets the content that is rendered at the bottom of the filled space.
+    pub fn set_content<E: IntoBoxedElement>(&mut self, content: E) {
+        self.content = Some(content.into_boxed_element());
+    }
+
+    /// Sets the content that is rendered at the bottom of the filled space and returns the fill.
+    pub fn with_content<E: IntoBoxedElement>(mut self, content: E) -> Self {
+        self.set_content(content);
+        self
+    }
+}
+
+impl Element for Fill {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.is_rendered {
+            return Ok(RenderResult::default());
+        }
+        let content = if let Some(content) = &mut self.content {
+            content
+        } else {
+            self.is_rendered = true;
+            return Ok(RenderResult {
+                size: Size::new(0, area.size().height),
+                has_more: false,
+            });
+        };
+
+        if !self.is_split {
+            let content_result = content.measure(context, area.clone(), style)?;
+            if content_result.has_more && area.has_content() {
+                return Ok(RenderResult {
+                    size: Size::default(),
+                    has_more: true,
+                });
+            }
+            if !content_result.has_more {
+                let height = area.size().height;
+                let mut result = area.with_translation(
+                    |area| content.render(context, area, style),
+                    |result| Position::new(0, height - result.size.height),
+                )?;
+                result.size.height = height;
+                self.is_rendered = !result.has_more;
+                return Ok(result);
+            }
+            self.is_split = true;
+        }
+
+        let result = content.render(context, area, style)?;
+        self.is_rendered = !result.has_more;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.is_rendered {
+            return Ok(RenderResult::default());
+        }
+        let content = if let Some(content) = &mut self.content {
+            content
+        } else {
+            return Ok(RenderResult {
+                size: Size::new(0, area.size().height),
+                has_more: false,
+            });
+        };
+
+        let result = content.measure(context, area.clone(), style)?;
+        if self.is_split {
+            Ok(result)
+        } else if result.has_more && area.has_content() {
+            Ok(RenderResult {
+                size: Size::default(),
+                has_more: true,
+            })
+        } else if result.has_more {
+            Ok(result)
+        } else {
+            Ok(RenderResult {
+                size: Size::new(result.size.width, area.size().height),
+                has_more: false,
+            })
+        }
+    }
+}
+
+/// A horizontal line with an optional label beneath it, for example for signatures or dates in
+/// forms and contracts.
+///
+/// The line is drawn with the given width after some empty space for the signature.  If the
+/// available width is smaller than the line width, the line is shortened.  The label is printed
+/// below the line using the style of the label string merged into the style of the element.
+///
+/// A signature line is never split across pages.  To arrange several signature lines side by
+/// side, you can use a [`TableLayout`][] without a cell decorator.
+///
+/// If a signature field is set using [`set_signature_field`][], the empty space above the line is
+/// added to the document as a signature form field that can be signed digitally, see the
+/// [`signature`](../signature/index.html) module.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let mut table = elements::TableLayout::new(vec![1, 1]);
+/// table
+///     .row()
+///     .element(elements::SignatureLine::new(60).with_label("Signature"))
+///     .element(
+///         elements::SignatureLine::new(40)
+///             .with_label(style::StyledString::new("Date", style::Style::new().italic())),
+///     )
+///     .push()
+///     .expect("Invalid table row");
+/// ```
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`set_signature_field`]: #method.set_signature_field
+#[derive(Clone, Debug)]
+pub struct SignatureLine {
+    width: Mm,
+    space: Mm,
+    label: Option<StyledString>,
+    line_style: LineStyle,
+    signature_field: Option<String>,
+    is_rendered: bool,
+}
+
+impl SignatureLine {
+    /// Creates a new signature line with the given width.
+    pub fn new(width: impl Into<Mm>) -> SignatureLine {
+        SignatureLine {
+            width: width.into(),
+            space: Mm::from(10),
+            label: None,
+            line_style: LineStyle::new(),
+            signature_field: None,
+            is_rendered: false,
+        }
+    }
+
+    /// Sets the label that is printed below the line.
+    pub fn set_label(&mut self, label: impl Into<StyledString>) {
+        self.label = Some(label.into());
+    }
+
+    /// Sets the label that is printed below the line and returns the signature line.
+    pub fn with_label(mut self, label: impl Into<StyledString>) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    /// Sets the height of the empty space above the line.
+    ///
+    /// Per default, the space is 10 mm high.
+    pub fn set_space(&mut self, space: impl Into<Mm>) {
+        self.space = space.into();
+    }
+
+    /// Sets the height of the empty space above the line and returns the signature line.
+    ///
+    /// Per default, the space is 10 mm high.
+    pub fn with_space(mut self, space: impl Into<Mm>) -> Self {
+        self.set_space(space);
+        self
+    }
+
+    /// Sets the line style for the line.
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.line_style = line_style.into();
+    }
+
+    /// Sets the line style for the line and returns the signature line.
+    pub fn with_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_line_style(line_style);
+        self
+    }
+
+    /// Adds a signature form field with the given name for the empty space above the line.
+    ///
+    /// The names of the signature fields in a document must be unique.
+    pub fn set_signature_field(&mut self, name: impl Into<String>) {
+        self.signature_field = Some(name.into());
+    }
+
+    /// Adds a signature form field with the given name for the empty space above the line and
+    /// returns the signature line.
+    ///
+    /// The names of the signature fields in a document must be unique.
+    pub fn with_signature_field(mut self, name: impl Into<String>) -> Self {
+        self.set_signature_field(name);
+        self
+    }
+
+    /// Returns the size of this element and the offset of the label.
+    fn layout(&self, context: &Context, area: &render::Area<'_>, style: Style) -> (Size, Mm) {
+        let width = self.width.min(area.size().width);
+        let label_offset = self.space + self.line_style.thickness();
+        let mut height = label_offset;
+        if let Some(label) = &self.label {
+            let style = style.and(label.style);
+            height += style.line_height(&context.font_cache);
+        }
+        (Size::new(width, height), label_offset)
+    }
+}
+
+impl Element for SignatureLine {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let (size, label_offset) = self.layout(context, &area, style);
+        if size.height > area.size().height {
+            result.has_more = true;
+            return Ok(result);
+        }
+
+        if let Some(name) = &self.signature_field {
+            area.add_signature_field(
+                name.clone(),
+                Position::default(),
+                Size::new(size.width, self.space),
+            );
+        }
+        let y = self.space + self.line_style.thickness() / 2.0;
+        area.draw_line(
+            vec![Position::new(0, y), Position::new(size.width, y)],
+            self.line_style,
+        );
+        if let Some(label) = &self.label {
+            area.print_str(
+                &context.font_cache,
+                Position::new(0, label_offset),
+                style.and(label.style),
+                &label.s,
+            )?;
+        }
+        self.is_rendered = true;
+        result.size = size;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let (size, _) = self.layout(context, &area, style);
+        if size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = size;
+        }
+        Ok(result)
+    }
+}
+
+/// A horizontal line across the available width, for example as a separator between sections.
+///
+/// The line is drawn with the line style set with [`set_line_style`][], so you can configure its
+/// thickness, color and dash pattern.  The margins set with [`set_margins`][] add empty space
+/// above and below the line and shorten it on the left and on the right.  Per default, there is
+/// a margin of 2 mm above and below the line.  A horizontal rule is never split across pages.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let rule = elements::HorizontalRule::new()
+///     .with_line_style(
+///         style::LineStyle::new()
+///             .with_thickness(0.5)
+///             .with_color(style::Color::Greyscale(128))
+///             .with_dash(&[2, 1]),
+///     )
+///     .with_margins((4, 0));
+/// ```
+///
+/// [`set_line_style`]: #method.set_line_style
+/// [`set_margins`]: #method.set_margins
+#[derive(Clone, Debug)]
+pub struct HorizontalRule {
+    line_style: LineStyle,
+    margins: Margins,
+    is_rendered: bool,
+}
+
+impl HorizontalRule {
+    /// Creates a new horizontal rule with the default line style.
+    pub fn new() -> HorizontalRule {
+        HorizontalRule {
+            line_style: LineStyle::new(),
+            margins: Margins::vh(2, 0),
+            is_rendered: false,
+        }
+    }
+
+    /// Sets the line style for the line.
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.line_style = line_style.into();
+    }
+
+    /// Sets the line style for the line and returns the horizontal rule.
+    pub fn with_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_line_style(line_style);
+        self
+    }
+
+    /// Sets the margins around the line.
+    ///
+    /// Per default, there is a margin of 2 mm above and below the line.
+    pub fn set_margins(&mut self, margins: impl Into<Margins>) {
+        self.margins = margins.into();
+    }
+
+    /// Sets the margins around the line and returns the horizontal rule.
+    ///
+    /// Per default, there is a margin of 2 mm above and below the line.
+    pub fn with_margins(mut self, margins: impl Into<Margins>) -> Self {
+        self.set_margins(margins);
+        self
+    }
+
+    /// Returns the size of this element for the given area.
+    fn size(&self, area: &render::Area<'_>) -> Size {
+        let height = self.margins.top + self.line_style.thickness() + self.margins.bottom;
+        Size::new(area.size().width, height)
+    }
+}
+
+impl Default for HorizontalRule {
+    fn default() -> HorizontalRule {
+        HorizontalRule::new()
+    }
+}
+
+impl Element for HorizontalRule {
+    fn render(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let size = self.size(&area);
+        if size.height > area.size().height {
+            result.has_more = true;
+            return Ok(result);
+        }
+
+        let y = self.margins.top + self.line_style.thickness() / 2.0;
+        let right = (size.width - self.margins.right).max(self.margins.left);
+        area.draw_line(
+            vec![Position::new(self.margins.left, y), Position::new(right, y)],
+            self.line_style,
+        );
+        self.is_rendered = true;
+        result.size = size;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let size = self.size(&area);
+        if size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = size;
+        }
+        Ok(result)
+    }
+}
+
+/// A short label on a colored background with rounded corners, for example for status indicators
+/// and tags.
+///
+/// The badge is sized to fit its label plus the padding, so it does not fill the available width.
+/// Per default, the label is printed on a light gray background with a padding of 0.5 mm at the
+/// top and bottom and 1.5 mm at the left and right, and the corner radius is half the height of
+/// the badge so that it has the shape of a pill.  A badge is never split across pages.  To
+/// arrange several badges side by side, you can use a [`TableLayout`][] without a cell decorator.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let badge = elements::Badge::new("Paid")
+///     .with_fill_color(style::Color::Rgb(0, 128, 0))
+///     .with_text_color(style::Color::Rgb(255, 255, 255))
+///     .with_radius(1);
+/// ```
+///
+/// [`TableLayout`]: struct.TableLayout.html
+#[derive(Clone, Debug)]
+pub struct Badge {
+    label: StyledString,
+    fill_color: Color,
+    text_color: Option<Color>,
+    radius: Option<Mm>,
+    padding: Margins,
+    is_rendered: bool,
+}
+
+impl Badge {
+    /// Creates a new badge with the given label.
+    pub fn new(label: impl Into<StyledString>) -> Badge {
+        Badge {
+            label: label.into(),
+            fill_color: Color::Greyscale(220),
+            text_color: None,
+            radius: None,
+            padding: Margins::vh(0.5, 1.5),
+            is_rendered: false,
+        }
+    }
+
+    /// Sets the background color of this badge.
+    pub fn set_fill_color(&mut self, color: Color) {
+        self.fill_color = color;
+    }
+
+    /// Sets the background color of this badge and returns the badge.
+    pub fn with_fill_color(mut self, color: Color) -> Self {
+        self.set_fill_color(color);
+        self
+    }
+
+    /// Sets the color of the label of this badge.
+    ///
+    /// Per default, the color of the style of the label is used.
+    pub fn set_text_color(&mut self, color: Color) {
+        self.text_color = Some(color);
+    }
+
+    /// Sets the color of the label of this badge and returns the badge.
+    ///
+    /// Per default, the color of the style of the label is used.
+    pub fn with_text_color(mut self, color: Color) -> Self {
+        self.set_text_color(color);
+        self
+    }
+
+    /// Sets the corner radius of this badge.
+    ///
+    /// The radius is limited to half the height of the badge.  Per default, the corners are fully
+    /// rounded.
+    pub fn set_radius(&mut self, radius: impl Into<Mm>) {
+        self.radius = Some(radius.into());
+    }
+
+    /// Sets the corner radius of this badge and returns the badge.
+    ///
+    /// The radius is limited to half the height of the badge.  Per default, the corners are fully
+    /// rounded.
+    pub fn with_radius(mut self, radius: impl Into<Mm>) -> Self {
+        self.set_radius(radius);
+        self
+    }
+
+    /// Sets the padding between the border of this badge and its label.
+    pub fn set_padding(&mut self, padding: impl Into<Margins>) {
+        self.padding = padding.into();
+    }
+
+    /// Sets the padding between the border of this badge and its label and returns the badge.
+    pub fn with_padding(mut self, padding: impl Into<Margins>) -> Self {
+        self.set_padding(padding);
+        self
+    }
+
+    /// Returns the style of the label.
+    fn label_style(&self, style: Style) -> Style {
+        let mut style = style.and(self.label.style);
+        if let Some(color) = self.text_color {
+            style.set_color(color);
+        }
+        style
+    }
+
+    /// Returns the size of this badge.
+    fn size(&self, context: &Context, style: Style) -> Size {
+        let style = self.label_style(style);
+        Size::new(
+            style.str_width(&context.font_cache, &self.label.s)
+                + self.padding.left
+                + self.padding.right,
+            style.line_height(&context.font_cache) + self.padding.top + self.padding.bottom,
+        )
+    }
+}
+
+impl Element for Badge {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let size = self.size(context, style);
+        if size.height > area.size().height {
+            result.has_more = true;
+            return Ok(result);
+        }
+
+        area.set_size(size);
+        area.fill_rounded(self.fill_color, self.radius.unwrap_or(size.height));
+        area.print_str(
+            &context.font_cache,
+            Position::new(self.padding.left, self.padding.top),
+            self.label_style(style),
+            &self.label.s,
+        )?;
+        self.is_rendered = true;
+        result.size = size;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let size = self.size(context, style);
+        if size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = size;
+        }
+        Ok(result)
+    }
+}
+
+/// A named anchor that marks the current position in the document as the target of internal
+/// links.
+///
+/// This element does not take any space.  Use [`LinkedElement`][] or [`Area::add_link`][] to add
+/// links to the anchor.  The links can be placed before or after the anchor.  The names of all
+/// anchors in a document must be unique, so an anchor must not be placed in an element that is
+/// rendered multiple times, for example in a page header.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let mut layout = elements::LinearLayout::vertical();
+/// layout.push(elements::Paragraph::new("See section 3.").link_to("sec3"));
+/// layout.push(elements::Anchor::new("sec3"));
+/// layout.push(elements::Paragraph::new("3. Results"));
+/// ```
+///
+/// [`LinkedElement`]: struct.LinkedElement.html
+/// [`Area::add_link`]: ../render/struct.Area.html#method.add_link
+#[derive(Clone, Debug)]
+pub struct Anchor {
+    anchor: render::Anchor,
+    is_rendered: bool,
+}
+
+impl Anchor {
+    /// Creates a new anchor with the given name.
+    pub fn new(anchor: impl Into<render::Anchor>) -> Anchor {
+        Anchor {
+            anchor: anchor.into(),
+            is_rendered: false,
+        }
+    }
+
+    /// Returns the anchor that is added to the document by this element.
+    pub fn anchor(&self) -> &render::Anchor {
+        &self.anchor
+    }
+}
+
+impl Element for Anchor {
+    fn render(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        if !self.is_rendered {
+            area.add_anchor(self.anchor.clone(), Position::default());
+            self.is_rendered = true;
+        }
+        Ok(RenderResult::default())
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        _area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        Ok(RenderResult::default())
+    }
+}
+
+/// The layout of a [`Fraction`][].
+///
+/// [`Fraction`]: struct.Fraction.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum FractionLayout {
+    /// The numerator is printed above the denominator, separated by a horizontal bar.
+    Stacked,
+    /// The raised numerator and the denominator are printed next to each other, separated by a
+    /// slash.
+    Diagonal,
+}
+
+impl Default for FractionLayout {
+    fn default() -> FractionLayout {
+        FractionLayout::Stacked
+    }
+}
+
+/// A fraction, optionally with a whole number part (mixed number).
+///
+/// The numerator and the denominator are printed with a reduced font size (like superscript
+/// text), the whole number part is printed with the font size of the style of this element.  Per
+/// default, the fraction is stacked, see [`FractionLayout`][].  The bar of a stacked fraction is
+/// drawn with the color of the style and a thickness depending on the font size, and it is
+/// centered vertically at the middle of the lowercase letters of the whole number part.
+///
+/// This element is never split across pages.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let half = elements::Fraction::new(1, 2);
+/// let mixed = elements::Fraction::new(3, 4)
+///     .with_whole(2)
+///     .with_layout(elements::FractionLayout::Diagonal);
+/// ```
+///
+/// [`FractionLayout`]: enum.FractionLayout.html
+#[derive(Clone, Debug)]
+pub struct Fraction {
+    numerator: String,
+    denominator: String,
+    whole: Option<String>,
+    layout: FractionLayout,
+    is_rendered: bool,
+}
+
+/// The positions of the parts of a [`Fraction`][] relative to the upper left corner of the
+/// element.
+///
+/// [`Fraction`]: struct.Fraction.html
+struct FractionParts {
+    size: Size,
+    whole: Position,
+    numerator: Position,
+    denominator: Position,
+    separator: Position,
+    bar_width: Mm,
+}
+
+impl Fraction {
+    /// Creates a new fraction with the given numerator and denominator.
+    pub fn new(numerator: impl ToString, denominator: impl ToString) -> Fraction {
+        Fraction {
+            numerator: numerator.to_string(),
+            denominator: denominator.to_string(),
+            whole: None,
+            layout: FractionLayout::default(),
+            is_rendered: false,
+        }
+    }
+
+    /// Sets the whole number part that is printed before the fraction.
+    pub fn set_whole(&mut self, whole: impl ToString) {
+        self.whole = Some(whole.to_string());
+    }
+
+    /// Sets the whole number part that is printed before the fraction and returns the fraction.
+    pub fn with_whole(mut self, whole: impl ToString) -> Self {
+        self.set_whole(whole);
+        self
+    }
+
+    /// Sets the layout of this fraction.
+    pub fn set_layout(&mut self, layout: FractionLayout) {
+        self.layout = layout;
+    }
+
+    /// Sets the layout of this fraction and returns the fraction.
+    pub fn with_layout(mut self, layout: FractionLayout) -> Self {
+        self.set_layout(layout);
+        self
+    }
+
+    /// Returns the style for the numerator and the denominator.
+    fn small_style(style: Style) -> Style {
+        let font_size = (f64::from(style.font_size()) * crate::style::SUPERSCRIPT_SIZE)
+            .round()
+            .max(1.0);
+        style.with_font_size(font_size as u8)
+    }
+
+    /// Returns the thickness of the bar of a stacked fraction.
+    fn bar_thickness(style: Style) -> Mm {
+        Mm::from(printpdf::Pt(f64::from(style.font_size()) * 0.06))
+    }
+
+    fn layout(&self, context: &Context, style: Style) -> FractionParts {
+        let font_cache = &context.font_cache;
+        let small_style = Self::small_style(style);
+        let metrics = style.metrics(font_cache);
+        let small_metrics = small_style.metrics(font_cache);
+
+        let (whole_width, whole_space) = match &self.whole {
+            Some(whole) => (
+                style.str_width(font_cache, whole),
+                small_style.str_width(font_cache, " "),
+            ),
+            None => (Mm(0.0), Mm(0.0)),
+        };
+        let x = whole_width + whole_space;
+        let numerator_width = small_style.str_width(font_cache, &self.numerator);
+        let denominator_width = small_style.str_width(font_cache, &self.denominator);
+
+        match self.layout {
+            FractionLayout::Stacked => {
+                let bar_width = numerator_width.max(denominator_width);
+                let thickness = Self::bar_thickness(style);
+                // The bar is placed at the middle of the lowercase letters, i. e. at roughly a
+                // quarter of the ascent above the baseline of the whole number part.
+                let bar_offset = small_metrics.line_height + thickness / 2.0;
+                let whole_y = (bar_offset + metrics.ascent / 4.0 - metrics.ascent).max(Mm(0.0));
+                let shift = (metrics.ascent - metrics.ascent / 4.0 - bar_offset).max(Mm(0.0));
+                let height = (shift + small_metrics.line_height * 2.0 + thickness)
+                    .max(whole_y + metrics.line_height);
+                FractionParts {
+                    size: Size::new(x + bar_width, height),
+                    whole: Position::new(0, whole_y),
+                    numerator: Position::new(x + (bar_width - numerator_width) / 2.0, shift),
+                    denominator: Position::new(
+                        x + (bar_width - denominator_width) / 2.0,
+                        shift + small_metrics.line_height + thickness,
+                    ),
+                    separator: Position::new(x, shift + bar_offset),
+                    bar_width,
+                }
+            }
+            FractionLayout::Diagonal => {
+                let slash_width = style.str_width(font_cache, "/");
+                FractionParts {
+                    size: Size::new(
+                        x + numerator_width + slash_width + denominator_width,
+                        metrics.line_height,
+                    ),
+                    whole: Position::new(0, 0),
+                    numerator: Position::new(x, 0),
+                    denominator: Position::new(
+                        x + numerator_width + slash_width,
+                        metrics.ascent - small_metrics.ascent,
+                    ),
+                    separator: Position::new(x + numerator_width, 0),
+                    bar_width: Mm(0.0),
+                }
+            }
+        }
+    }
+}
+
+impl Element for Fraction {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let parts = self.layout(context, style);
+        if parts.size.height > area.size().height {
+            result.has_more = true;
+            return Ok(result);
+        }
+
+        let font_cache = &context.font_cache;
+        let small_style = Self::small_style(style);
+        if let Some(whole) = &self.whole {
+            area.print_str(font_cache, parts.whole, style, whole)?;
+        }
+        area.print_str(font_cache, parts.numerator, small_style, &self.numerator)?;
+        area.print_str(
+            font_cache,
+            parts.denominator,
+            small_style,
+            &self.denominator,
+        )?;
+        match self.layout {
+            FractionLayout::Stacked => {
+                let mut line_style = LineStyle::new().with_thickness(Self::bar_thickness(style));
+                if let Some(color) = style.color() {
+                    line_style = line_style.with_color(color);
+                }
+                let end = parts.separator + Position::new(parts.bar_width, 0);
+                area.draw_line(vec![parts.separator, end], line_style);
+            }
+            FractionLayout::Diagonal => {
+                area.print_str(font_cache, parts.separator, style, "/")?;
+            }
+        }
+        self.is_rendered = true;
+        result.size = parts.size;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let parts = self.layout(context, style);
+        if parts.size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = parts.size;
+        }
+        Ok(result)
+    }
+}
+
+/// A line with a left-aligned, a centered and a right-aligned part, for example for page headers
+/// and footers.
+///
+/// This is the classic header layout of word processors, for example “Title | Chapter | Page 1”.
+/// All parts are optional.  If the parts fit next to each other, the center part is centered on
+/// the line.  Otherwise, the width of the line is distributed among the parts according to their
+/// widths, and the parts are wrapped into multiple lines so that they never overlap.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let mut decorator = genpdf::SimplePageDecorator::new();
+/// decorator.set_header(|page| {
+///     elements::HeaderLine::new()
+///         .with_left("Annual Report")
+///         .with_right(format!("Page {}", page))
+/// });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HeaderLine {
+    left: Option<StyledString>,
+    center: Option<StyledString>,
+    right: Option<StyledString>,
+    zones: Option<Vec<(Mm, Mm, Paragraph)>>,
+}
+
+impl HeaderLine {
+    /// Creates a new empty header line.
+    pub fn new() -> HeaderLine {
+        HeaderLine::default()
+    }
+
+    /// Sets the left-aligned part of this line.
+    pub fn set_left(&mut self, left: impl Into<StyledString>) {
+        self.left = Some(left.into());
+    }
+
+    /// Sets the left-aligned part of this line and returns the line.
+    pub fn with_left(mut self, left: impl Into<StyledString>) -> Self {
+        self.set_left(left);
+        self
+    }
+
+    /// Sets the centered part of this line.
+    pub fn set_center(&mut self, center: impl Into<StyledString>) {
+        self.center = Some(center.into());
+    }
+
+    /// Sets the centered part of this line and returns the line.
+    pub fn with_center(mut self, center: impl Into<StyledString>) -> Self {
+        self.set_center(center);
+        self
+    }
+
+    /// Sets the right-aligned part of this line.
+    pub fn set_right(&mut self, right: impl Into<StyledString>) {
+        self.right = Some(right.into());
+    }
+
+    /// Sets the right-aligned part of this line and returns the line.
+    pub fn with_right(mut self, right: impl Into<StyledString>) -> Self {
+        self.set_right(right);
+        self
+    }
+
+    /// Returns the horizontal offset, the width and the paragraph for every part of this line.
+    fn zones(&mut self, context: &Context, width: Mm, style: Style) -> &mut [(Mm, Mm, Paragraph)] {
+        let (left, center, right) = (&self.left, &self.center, &self.right);
+        self.zones.get_or_insert_with(|| {
+            let font_cache = &context.font_cache;
+            let parts = [
+                (left, Alignment::Left),
+                (center, Alignment::Center),
+                (right, Alignment::Right),
+            ];
+            let widths: Vec<_> = parts
+                .iter()
+                .map(|(s, _)| {
+                    s.as_ref()
+                        .map(|s| style.and(s.style).str_width(font_cache, &s.s))
+                        .unwrap_or_default()
+                })
+                .collect();
+
+            let side_width = (width - widths[1]) / 2.0;
+            let columns = if widths[0] <= side_width && widths[2] <= side_width {
+                // The center part is printed centered on the full line so that it is not wrapped
+                // due to rounding errors.
+                vec![
+                    (Mm(0.0), side_width),
+                    (Mm(0.0), width),
+                    (width - side_width, side_width),
+                ]
+            } else {
+                // The parts don’t fit next to each other, so we distribute the width according to
+                // their widths and keep a space between them.
+                let gap = style.str_width(font_cache, " ");
+                let available = (width - gap * 2.0).max(Mm(0.0));
+                let total: Mm = widths.iter().copied().sum();
+                let mut x = Mm(0.0);
+                let mut columns = Vec::new();
+                for w in &widths {
+                    let column_width = available * (w.0 / total.0);
+                    columns.push((x, column_width));
+                    x += column_width + gap;
+                }
+                columns
+            };
+
+            let mut zones = Vec::new();
+            for ((s, alignment), (x, column_width)) in parts.iter().zip(columns) {
+                if let Some(s) = s {
+                    let paragraph = Paragraph::new(s.clone()).aligned(*alignment);
+                    zones.push((x, column_width, paragraph));
+                }
+            }
+            zones
+        })
+    }
+}
+
+impl Element for HeaderLine {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        result.size.width = area.size().width;
+        for (x, width, paragraph) in self.zones(context, area.size().width, style) {
+            let mut zone_area = area.clone();
+            zone_area.add_offset(Position::new(*x, 0));
+            zone_area.set_width(*width);
+            let zone_result = paragraph.render(context, zone_area, style)?;
+            result.size.height = result.size.height.max(zone_result.size.height);
+            result.has_more |= zone_result.has_more;
+        }
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        result.size.width = area.size().width;
+        for (x, width, paragraph) in self.zones(context, area.size().width, style) {
+            let mut zone_area = area.clone();
+            zone_area.add_offset(Position::new(*x, 0));
+            zone_area.set_width(*width);
+            let zone_result = paragraph.measure(context, zone_area, style)?;
+            result.size.height = result.size.height.max(zone_result.size.height);
+            result.has_more |= zone_result.has_more;
+        }
+        Ok(result)
+    }
+}
+
+/// A drawing area with a fixed size that contains shapes like lines, rectangles, circles and
+/// Bézier paths.
+///
+/// The positions of the [`Shape`][]s are relative to the upper left corner of the canvas.  The
+/// shapes are drawn in the order they were added to the canvas and clipped at its borders.  A
+/// canvas is never split across pages:  If it does not fit on the current page, it is moved to
+/// the next page.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let canvas = elements::Canvas::new((40, 20))
+///     .with_shape(
+///         elements::Shape::rectangle((0, 0), (40, 20))
+///             .with_fill_color(style::Color::Rgb(230, 230, 230)),
+///     )
+///     .with_shape(
+///         elements::Shape::circle((10, 10), 5).with_fill_color(style::Color::Rgb(255, 0, 0)),
+///     )
+///     .with_shape(
+///         elements::Shape::path((20, 15))
+///             .curve_to((25, 0), (35, 0), (38, 15))
+///             .with_line_style(style::LineStyle::new().with_thickness(0.5)),
+///     );
+/// ```
+///
+/// [`Shape`]: struct.Shape.html
+#[derive(Clone, Debug)]
+pub struct Canvas {
+    size: Size,
+    shapes: Vec<Shape>,
+    is_rendered: bool,
+}
+
+impl Canvas {
+    /// Creates a new empty canvas with the given size.
+    pub fn new(size: impl Into<Size>) -> Canvas {
+        Canvas {
+            size: size.into(),
+            shapes: Vec::new(),
+            is_rendered: false,
+        }
+    }
+
+    /// Adds the given shape to this canvas.
+    pub fn push(&mut self, shape: Shape) {
+        self.shapes.push(shape);
+    }
+
+    /// Adds the given shape to this canvas and returns the canvas.
+    pub fn with_shape(mut self, shape: Shape) -> Self {
+        self.push(shape);
+        self
+    }
+}
+
+impl Element for Canvas {
+    fn render(
+        &mut self,
+        _context: &Context,
+        mut area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        if self.size.height > area.size().height {
+            result.has_more = true;
+            return Ok(result);
+        }
+
+        area.set_size(self.size);
+        area.with_clipping(|area| {
+            for shape in &self.shapes {
+                shape.draw(&area);
+            }
+        });
+        self.is_rendered = true;
+        result.size = self.size;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        if self.size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = self.size;
+        }
+        Ok(result)
+    }
+}
+
+/// A shape that can be drawn on a [`Canvas`][].
+///
+/// A shape is a path that consists of straight lines and cubic Bézier curves.  You can either use
+/// one of the constructors for common shapes like [`rectangle`][] and [`circle`][], or start a
+/// path with [`path`][] and extend it with [`line_to`][], [`curve_to`][] and the methods for
+/// relative movements like [`line_by`][].  All positions are relative to the upper left corner
+/// of the canvas.
+///
+/// The shape is filled with the fill color, if set, and its outline is drawn with the line style,
+/// if set.  If neither is set, the outline is drawn with the default line style.
+///
+/// [`Canvas`]: struct.Canvas.html
+/// [`rectangle`]: #method.rectangle
+/// [`circle`]: #method.circle
+/// [`path`]: #method.path
+/// [`line_to`]: #method.line_to
+/// [`curve_to`]: #method.curve_to
+/// [`line_by`]: #method.line_by
+#[derive(Clone, Debug)]
+pub struct Shape {
+    segments: Vec<render::PathSegment>,
+    start: Position,
+    current: Position,
+    fill_color: Option<Color>,
+    line_style: Option<LineStyle>,
+}
+
+impl Shape {
+    /// Starts a new path at the given position.
+    pub fn path(start: impl Into<Position>) -> Shape {
+        let start = start.into();
+        Shape {
+            segments: vec![render::PathSegment::MoveTo(start)],
+            start,
+            current: start,
+            fill_color: None,
+            line_style: None,
+        }
+    }
+
+    /// Creates a straight line between the given positions.
+    pub fn line(from: impl Into<Position>, to: impl Into<Position>) -> Shape {
+        Shape::path(from).line_to(to)
+    }
+
+    /// Creates a sequence of straight lines that connect the given points.
+    pub fn polyline<P: Into<Position>>(points: impl IntoIterator<Item = P>) -> Shape {
+        let mut points = points.into_iter().map(Into::into);
+        let mut shape = Shape::path(points.next().unwrap_or_default());
+        for point in points {
+            shape = shape.line_to(point);
+        }
+        shape
+    }
+
+    /// Creates a closed polygon with the given corners.
+    pub fn polygon<P: Into<Position>>(points: impl IntoIterator<Item = P>) -> Shape {
+        Shape::polyline(points).close()
+    }
+
+    /// Creates a rectangle with the given upper left corner and size.
+    pub fn rectangle(position: impl Into<Position>, size: impl Into<Size>) -> Shape {
+        let position = position.into();
+        let size = size.into();
+        Shape::path(position)
+            .line_by((size.width, 0))
+            .line_by((0, size.height))
+            .line_to((position.x, position.y + size.height))
+            .close()
+    }
+
+    /// Creates a circle with the given center and radius.
+    pub fn circle(center: impl Into<Position>, radius: impl Into<Mm>) -> Shape {
+        let radius = radius.into();
+        Shape::ellipse(center, radius, radius)
+    }
+
+    /// Creates an ellipse with the given center and horizontal and vertical radius.
+    ///
+    /// The ellipse is approximated with four Bézier curves.
+    pub fn ellipse(
+        center: impl Into<Position>,
+        radius_x: impl Into<Mm>,
+        radius_y: impl Into<Mm>,
+    ) -> Shape {
+        let center = center.into();
+        let (rx, ry) = (radius_x.into(), radius_y.into());
+        // The distance of the Bézier control points from the ends of a quarter ellipse relative to
+        // the radius
+        let k = 0.552_284_75;
+        let point = |x: f64, y: f64| center + Position::new(rx * x, ry * y);
+        Shape::path(point(1.0, 0.0))
+            .curve_to(point(1.0, k), point(k, 1.0), point(0.0, 1.0))
+            .curve_to(point(-k, 1.0), point(-1.0, k), point(-1.0, 0.0))
+            .curve_to(point(-1.0, -k), point(-k, -1.0), point(0.0, -1.0))
+            .curve_to(point(k, -1.0), point(1.0, -k), point(1.0, 0.0))
+            .close()
+    }
+
+    /// Starts a new subpath at the given position and returns the shape.
+    pub fn move_to(mut self, position: impl Into<Position>) -> Self {
+        let position = position.into();
+        self.segments.push(render::PathSegment::MoveTo(position));
+        self.start = position;
+        self.current = position;
+        self
+    }
+
+    /// Starts a new subpath at the given offset from the current position and returns the
+    /// shape.
+    pub fn move_by(self, offset: impl Into<Position>) -> Self {
+        let position = self.current + offset.into();
+        self.move_to(position)
+    }
+
+    /// Adds a straight line from the current position to the given position and returns the
+    /// shape.
+    pub fn line_to(mut self, position: impl Into<Position>) -> Self {
+        let position = position.into();
+        self.segments.push(render::PathSegment::LineTo(position));
+        self.current = position;
+        self
+    }
+
+    /// Adds a straight line from the current position by the given offset and returns the shape.
+    pub fn line_by(self, offset: impl Into<Position>) -> Self {
+        let position = self.current + offset.into();
+        self.line_to(position)
+    }
+
+    /// Adds a cubic Bézier curve from the current position to the given position and returns the
+    /// shape.
+    ///
+    /// The first and the second position are the control points of the curve.
+    pub fn curve_to(
+        mut self,
+        control1: impl Into<Position>,
+        control2: impl Into<Position>,
+        position: impl Into<Position>,
+    ) -> Self {
+        let position = position.into();
+        self.segments.push(render::PathSegment::CurveTo(
+            control1.into(),
+            control2.into(),
+            position,
+        ));
+        self.current = position;
+        self
+    }
+
+    /// Adds a cubic Bézier curve from the current position and returns the shape.
+    ///
+    /// The control points and the end of the curve are given as offsets from the current
+    /// position.
+    pub fn curve_by(
+        self,
+        control1: impl Into<Position>,
+        control2: impl Into<Position>,
+        offset: impl Into<Position>,
+    ) -> Self {
+        let current = self.current;
+        self.curve_to(
+            current + control1.into(),
+            current + control2.into(),
+            current + offset.into(),
+        )
+    }
+
+    /// Closes the current subpath with a straight line to its start and returns the shape.
+    pub fn close(mut self) -> Self {
+        self.segments.push(render::PathSegment::Close);
+        self.current = self.start;
+        self
+    }
+
+    /// Sets the color that this shape is filled with.
+    pub fn set_fill_color(&mut self, color: Color) {
+        self.fill_color = Some(color);
+    }
+
+    /// Sets the color that this shape is filled with and returns the shape.
+    pub fn with_fill_color(mut self, color: Color) -> Self {
+        self.set_fill_color(color);
+        self
+    }
+
+    /// Sets the line style for the outline of this shape.
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.line_style = Some(line_style.into());
+    }
+
+    /// Sets the line style for the outline of this shape and returns the shape.
+    pub fn with_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_line_style(line_style);
+        self
+    }
+
+    fn draw(&self, area: &render::Area<'_>) {
+        if let Some(color) = self.fill_color {
+            area.fill_path(&self.segments, color, false);
+        }
+        let line_style = match (self.line_style, self.fill_color) {
+            (Some(line_style), _) => Some(line_style),
+            (None, None) => Some(LineStyle::new()),
+            (None, Some(_)) => None,
+        };
+        if let Some(line_style) = line_style {
+            area.stroke_path(&self.segments, line_style);
+        }
+    }
+}
+
+/// Adds a padding to the wrapped element.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let p = elements::PaddedElement::new(
+///     elements::Paragraph::new("text"),
+///     genpdf::Margins::trbl(5, 2, 5, 10),
+/// );
+/// ```
+///
+/// Using [`Element::padded`][]:
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("text")
+///     .padded(genpdf::Margins::trbl(5, 2, 5, 10));
+/// ```
+///
+/// [`Element::padded`]: ../trait.Element.html#method.padded
+#[derive(Clone, Debug, Default)]
+pub struct PaddedElement<E: Element> {
+    element: E,
+    padding: Margins,
+}
+
+impl<E: Element> PaddedElement<E> {
+    /// Creates a new padded element that wraps the given element with the given padding.
+    pub fn new(element: E, padding: impl Into<Margins>) -> PaddedElement<E> {
+        PaddedElement {
+            element,
+            padding: padding.into(),
+        }
+    }
+}
+
+impl<E: Element> Element for PaddedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        area.add_margins(Margins {
+            bottom: Mm(0.0),
+            ..self.padding
+        });
+        let mut result = self.element.render(context, area, style)?;
+        result.size.width += self.padding.left + self.padding.right;
+        result.size.height += self.padding.top + self.padding.bottom;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        area.add_margins(Margins {
+            bottom: Mm(0.0),
+            ..self.padding
+        });
+        let mut result = self.element.measure(context, area, style)?;
+        result.size.width += self.padding.left + self.padding.right;
+        result.size.height += self.padding.top + self.padding.bottom;
+        Ok(result)
+    }
+}
+
+/// Adds margins around the wrapped element.
+///
+/// In contrast to a [`PaddedElement`][], the vertical margins of a margined element are collapsed
+/// with the margins of adjacent margined elements:  If a margined element directly follows
+/// another margined element, the space between them is the larger of the two margins instead of
+/// their sum.  If the wrapped element is split across pages, the top margin is only added on the
+/// first page and the bottom margin is only added on the last page.  The bottom margin is
+/// shortened if it does not fit on the page.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let p = elements::MarginedElement::new(
+///     elements::Paragraph::new("text"),
+///     genpdf::Margins::trbl(2, 5, 2, 5),
+/// );
+/// ```
+///
+/// Using [`Element::with_margins`][]:
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("text").with_margins((2, 5, 2, 5));
+/// ```
+///
+/// [`PaddedElement`]: struct.PaddedElement.html
+/// [`Element::with_margins`]: ../trait.Element.html#method.with_margins
+#[derive(Clone, Debug, Default)]
+pub struct MarginedElement<E: Element> {
+    element: E,
+    margins: Margins,
+    is_started: bool,
+}
+
+impl<E: Element> MarginedElement<E> {
+    /// Creates a new margined element that wraps the given element with the given margins.
+    pub fn new(element: E, margins: impl Into<Margins>) -> MarginedElement<E> {
+        MarginedElement {
+            element,
+            margins: margins.into(),
+            is_started: false,
+        }
+    }
+
+    /// Returns the top margin that is added in the given area.
+    ///
+    /// If the area directly follows the bottom margin of another margined element, the top
+    /// margin is reduced by that margin.
+    fn top_margin(&self, context: &Context, area: &render::Area<'_>) -> Mm {
+        if self.is_started {
+            return Mm(0.0);
+        }
+        match context.margin_end.get() {
+            Some((page, position, margin))
+                if page == context.page_number() && is_same_position(position, area.origin()) =>
+            {
+                (self.margins.top - margin).max(Mm(0.0))
+            }
+            _ => self.margins.top,
+        }
+    }
+
+    /// Calls the given function with the area for the wrapped element and adds the margins to
+    /// the result.
+    fn with_content_area(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        f: impl FnOnce(&mut E, render::Area<'_>) -> Result<RenderResult, Error>,
+    ) -> Result<RenderResult, Error> {
+        let top = self.top_margin(context, area);
+        let mut content_area = area.clone();
+        content_area.add_margins(Margins {
+            top,
+            bottom: Mm(0.0),
+            ..self.margins
+        });
+        let mut result = f(&mut self.element, content_area)?;
+        if !self.is_started && result.has_more && result.size == Size::default() {
+            // The element does not fit on this page, so we move it to the next page together with
+            // its top margin.
+            return Ok(result);
+        }
+        result.size.width += self.margins.left + self.margins.right;
+        result.size.height += top;
+        if !result.has_more {
+            let bottom = self
+                .margins
+                .bottom
+                .min(area.size().height - result.size.height)
+                .max(Mm(0.0));
+            result.size.height += bottom;
+        }
+        Ok(result)
+    }
+}
+
+/// Returns whether the given positions are equal, ignoring rounding errors.
+fn is_same_position(a: Position, b: Position) -> bool {
+    (a.x - b.x).0.abs() < 0.001 && (a.y - b.y).0.abs() < 0.001
+}
+
+impl<E: Element> Element for MarginedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.with_content_area(context, &area, |element, area| {
+            element.render(context, area, style)
+        })?;
+        if result.has_more && result.size == Size::default() && !self.is_started {
+            return Ok(result);
+        }
+        self.is_started = true;
+        if result.has_more {
+            context.margin_end.set(None);
+        } else {
+            let end = area.origin() + Position::new(0, result.size.height);
+            context
+                .margin_end
+                .set(Some((context.page_number(), end, self.margins.bottom)));
+        }
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.with_content_area(context, &area, |element, area| {
+            element.measure(context, area, style)
+        })
+    }
+}
+
+/// Adds a default style to the wrapped element and its children.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::{elements, style};
+/// let p = elements::StyledElement::new(
+///     elements::Paragraph::new("text"),
+///     style::Effect::Bold,
+/// );
+/// ```
+///
+/// Using [`Element::styled`][]:
+/// ```
+/// use genpdf::{elements, style, Element as _};
+/// let p = elements::Paragraph::new("text")
+///     .styled(style::Effect::Bold);
+/// ```
+///
+/// [`Element::styled`]: ../trait.Element.html#method.styled
+#[derive(Clone, Debug, Default)]
+pub struct StyledElement<E: Element> {
+    element: E,
+    style: Style,
+}
+
+impl<E: Element> StyledElement<E> {
+    /// Creates a new styled element that wraps the given element with the given style.
+    pub fn new(element: E, style: impl Into<Style>) -> StyledElement<E> {
+        StyledElement {
+            element,
+            style: style.into(),
+        }
+    }
+}
+
+impl<E: Element> Element for StyledElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        mut style: Style,
+    ) -> Result<RenderResult, Error> {
+        style.merge(self.style);
+        self.element.render(context, area, style)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        mut style: Style,
+    ) -> Result<RenderResult, Error> {
+        style.merge(self.style);
+        self.element.measure(context, area, style)
+    }
+}
+
+/// Draws the wrapped element with a reduced opacity.
+///
+/// The opacity ranges from 0 (transparent) to 1 (opaque).  If transparent elements are nested,
+/// their opacities are multiplied.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let p = elements::TransparentElement::new(
+///     elements::Paragraph::new("text"),
+///     0.5,
+/// );
+/// ```
+///
+/// Using [`Element::with_opacity`][]:
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("text").with_opacity(0.5);
+/// ```
+///
+/// [`Element::with_opacity`]: ../trait.Element.html#method.with_opacity
+#[derive(Clone, Debug, Default)]
+pub struct TransparentElement<E: Element> {
+    element: E,
+    opacity: f64,
+}
+
+impl<E: Element> TransparentElement<E> {
+    /// Creates a new transparent element that wraps the given element with the given opacity.
+    pub fn new(element: E, opacity: f64) -> TransparentElement<E> {
+        TransparentElement { element, opacity }
+    }
+}
+
+impl<E: Element> Element for TransparentElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let element = &mut self.element;
+        area.with_opacity(self.opacity, |area| element.render(context, area, style))
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.element.measure(context, area, style)
+    }
+}
+
+/// Rotates the wrapped element clockwise by an arbitrary angle.
+///
+/// Per default, the element is rotated around its center and moved so that the bounding box of
+/// the rotated element starts at the upper left corner of the area.  The size of the bounding box
+/// is used for the layout, so a text that is rotated by -90 degrees can be used as a vertical
+/// table header.  If the bounding box does not fit on the current page, the element is moved to
+/// the next page.
+///
+/// If an anchor is set with [`set_anchor`][], the element is rendered as usual and rotated around
+/// the anchor, a position relative to the upper left corner of the area.  The layout is not
+/// changed by the rotation, which is useful for watermarks.
+///
+/// Links and bookmarks of the wrapped element are not rotated.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let header = elements::RotatedElement::new(elements::Text::new("Quantity"), -90.0);
+/// ```
+///
+/// Using [`Element::rotated`][]:
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let watermark = elements::Text::new("DRAFT")
+///     .rotated(-45.0)
+///     .with_anchor((20, 5));
+/// ```
+///
+/// [`set_anchor`]: #method.set_anchor
+/// [`Element::rotated`]: ../trait.Element.html#method.rotated
+#[derive(Clone, Debug)]
+pub struct RotatedElement<E: Element> {
+    element: E,
+    rotation: Rotation,
+    anchor: Option<Position>,
+}
+
+impl<E: Element> RotatedElement<E> {
+    /// Creates a new element that rotates the given element clockwise by the given rotation.
+    pub fn new(element: E, rotation: impl Into<Rotation>) -> RotatedElement<E> {
+        RotatedElement {
+            element,
+            rotation: rotation.into(),
+            anchor: None,
+        }
+    }
+
+    /// Sets the anchor to rotate the element around, relative to the upper left corner of the
+    /// area.
+    ///
+    /// If an anchor is set, the rotation does not change the layout of the element.
+    pub fn set_anchor(&mut self, anchor: impl Into<Position>) {
+        self.anchor = Some(anchor.into());
+    }
+
+    /// Sets the anchor to rotate the element around and returns the element.
+    ///
+    /// See [`set_anchor`][] for more information.
+    ///
+    /// [`set_anchor`]: #method.set_anchor
+    pub fn with_anchor(mut self, anchor: impl Into<Position>) -> Self {
+        self.set_anchor(anchor);
+        self
+    }
+
+    /// Returns the size of the bounding box of an element with the given size after the rotation.
+    fn bounding_box(&self, size: Size) -> Size {
+        let degrees = self.rotation.degrees().unwrap_or_default();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (sin, cos) = (sin.abs(), cos.abs());
+        Size::new(
+            size.width * cos + size.height * sin,
+            size.width * sin + size.height * cos,
+        )
+    }
+}
+
+impl<E: Element> Element for RotatedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let element = &mut self.element;
+        if let Some(anchor) = self.anchor {
+            return area.with_rotation(self.rotation, anchor, |area| {
+                element.render(context, area, style)
+            });
+        }
+
+        let size = element.measure(context, area.clone(), style)?.size;
+        let bounding_box = self.bounding_box(size);
+        if bounding_box.height > area.size().height {
+            return Ok(RenderResult {
+                size: Size::new(0, 0),
+                has_more: true,
+            });
+        }
+
+        let mut element_area = area.clone();
+        element_area.add_offset(Position::new(
+            (bounding_box.width - size.width) / 2.0,
+            (bounding_box.height - size.height) / 2.0,
+        ));
+        element_area.set_size(area.size());
+        let center = Position::new(size.width / 2.0, size.height / 2.0);
+        let element = &mut self.element;
+        let mut result = element_area.with_rotation(self.rotation, center, |area| {
+            element.render(context, area, style)
+        })?;
+        result.size = bounding_box;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = self.element.measure(context, area.clone(), style)?;
+        if self.anchor.is_none() {
+            result.size = self.bounding_box(result.size);
+            if result.size.height > area.size().height {
+                result = RenderResult {
+                    size: Size::new(0, 0),
+                    has_more: true,
+                };
+            }
+        }
+        Ok(result)
+    }
+}
+
+/// Marks the wrapped element as written in a different natural language.
+///
+/// The language is given as a language tag as defined in [RFC 3066][], for example `fr-FR`.  It
+/// overrides the document language set with [`Document::set_language`][] for the wrapped element
+/// and is used to select the hyphenator, see [`Document::add_hyphenator`][].  The content of the
+/// element is enclosed in a marked-content sequence with a `Lang` property so that assistive
+/// technologies can detect the language change.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let p = elements::LanguageElement::new(
+///     elements::Paragraph::new("Je pense, donc je suis."),
+///     "fr-FR",
+/// );
+/// ```
+///
+/// Using [`Element::with_language`][]:
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("Je pense, donc je suis.").with_language("fr-FR");
+/// ```
+///
+/// [RFC 3066]: https://tools.ietf.org/html/rfc3066
+/// [`Document::set_language`]: ../struct.Document.html#method.set_language
+/// [`Document::add_hyphenator`]: ../struct.Document.html#method.add_hyphenator
+/// [`Element::with_language`]: ../trait.Element.html#method.with_language
+#[derive(Clone, Debug, Default)]
+pub struct LanguageElement<E: Element> {
+    element: E,
+    language: String,
+}
+
+impl<E: Element> LanguageElement<E> {
+    /// Creates a new element that wraps the given element with the given language.
+    pub fn new(element: E, language: impl Into<String>) -> LanguageElement<E> {
+        LanguageElement {
+            element,
+            language: language.into(),
+        }
+    }
+
+    /// Returns the language of this element.
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+}
+
+impl<E: Element> Element for LanguageElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let element = &mut self.element;
+        let previous = context.replace_language(Some(self.language.clone()));
+        let result =
+            area.with_language(&self.language, |area| element.render(context, area, style));
+        context.replace_language(previous);
+        result
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let previous = context.replace_language(Some(self.language.clone()));
+        let result = self.element.measure(context, area, style);
+        context.replace_language(previous);
+        result
+    }
+}
+
+/// Adds a link to an [`Anchor`][] that covers the area of the wrapped element.
+///
+/// If the wrapped element is split across pages, a link is added on every page.  The anchor may
+/// be placed anywhere in the document.  If no anchor with the given name exists when the document
+/// is rendered, an error is returned.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let p = elements::LinkedElement::new(elements::Paragraph::new("See section 3."), "sec3");
+/// ```
+///
+/// Using [`Element::link_to`][]:
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let p = elements::Paragraph::new("See section 3.").link_to("sec3");
+/// ```
+///
+/// [`Anchor`]: struct.Anchor.html
+/// [`Element::link_to`]: ../trait.Element.html#method.link_to
+#[derive(Clone, Debug)]
+pub struct LinkedElement<E: Element> {
+    element: E,
+    anchor: render::Anchor,
+}
+
+impl<E: Element> LinkedElement<E> {
+    /// Creates a new element that wraps the given element and links to the given anchor.
+    pub fn new(element: E, anchor: impl Into<render::Anchor>) -> LinkedElement<E> {
+        LinkedElement {
+            element,
+            anchor: anchor.into(),
+        }
+    }
+
+    /// Returns the anchor that this element links to.
+    pub fn anchor(&self) -> &render::Anchor {
+        &self.anchor
+    }
+}
+
+impl<E: Element> Element for LinkedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.element.render(context, area.clone(), style)?;
+        if result.size.width > Mm(0.0) && result.size.height > Mm(0.0) {
+            area.add_link(self.anchor.clone(), Position::default(), result.size);
+        }
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.element.measure(context, area, style)
+    }
+}
+
+/// Adds a bookmark for the wrapped element to the outline of the PDF document.
+///
+/// The bookmark points to the upper left corner of the wrapped element on the page where it is
+/// first printed.  The level starts with 1 for top-level entries.  A bookmark is attached to the
+/// last preceding bookmark with a lower level, including the bookmarks added by [`Heading`][]
+/// elements.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let table = elements::BookmarkedElement::new(elements::Text::new("Table"), "Results", 1);
+/// ```
+///
+/// Using [`Element::with_bookmark`][]:
+/// ```
+/// use genpdf::{elements, Element as _};
+/// let table = elements::Text::new("Table").with_bookmark("Results", 1);
+/// ```
+///
+/// [`Heading`]: struct.Heading.html
+/// [`Element::with_bookmark`]: ../trait.Element.html#method.with_bookmark
+#[derive(Clone, Debug)]
+pub struct BookmarkedElement<E: Element> {
+    element: E,
+    title: String,
+    level: usize,
+    is_registered: bool,
+}
+
+impl<E: Element> BookmarkedElement<E> {
+    /// Creates a new element that wraps the given element and adds a bookmark with the given
+    /// title and level for it.
+    pub fn new(element: E, title: impl Into<String>, level: usize) -> BookmarkedElement<E> {
+        BookmarkedElement {
+            element,
+            title: title.into(),
+            level,
+            is_registered: false,
+        }
+    }
+
+    /// Returns the title of the bookmark.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the level of the bookmark.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+}
+
+impl<E: Element> Element for BookmarkedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.element.render(context, area.clone(), style)?;
+        if !self.is_registered && (result.size.height > Mm(0.0) || !result.has_more) {
+            self.is_registered = true;
+            area.add_bookmark(self.title.clone(), self.level, Position::default());
+        }
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.element.measure(context, area, style)
+    }
+}
+
+/// Adds a frame around the wrapped element.
+///
+/// # Examples
+///
+/// Direct usage:
+/// ```
+/// use genpdf::elements;
+/// let p = elements::FramedElement::new(
+///     elements::Paragraph::new("text"),
+/// );
+/// ```
+///
+/// Using [`Element::framed`][]:
+/// ```
+/// use genpdf::{elements, style, Element as _};
+/// let p = elements::Paragraph::new("text").framed(style::LineStyle::new());
+/// ```
+///
+/// [`Element::framed`]: ../trait.Element.html#method.framed
+#[derive(Clone, Debug, Default)]
+pub struct FramedElement<E: Element> {
+    element: E,
+    is_first: bool,
+    line_style: LineStyle,
+}
+
+impl<E: Element> FramedElement<E> {
+    /// Creates a new framed element that wraps the given element.
+    pub fn new(element: E) -> FramedElement<E> {
+        FramedElement::with_line_style(element, LineStyle::new())
+    }
+
+    /// Creates a new framed element that wraps the given element,
+    /// and with the given line style.
+    pub fn with_line_style(element: E, line_style: impl Into<LineStyle>) -> FramedElement<E> {
+        Self {
+            is_first: true,
+            element,
+            line_style: line_style.into(),
+        }
+    }
+}
+
+impl<E: Element> Element for FramedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        // For the element area calculations, we have to take into account the full line thickness.
+        // For the frame area, we only need half because we specify the center of the line.
+        let line_thickness = self.line_style.thickness();
+        let line_offset = line_thickness / 2.0;
+
+        // Calculate the areas in which to draw the element and the frame.
+        let mut element_area = area.clone();
+        let mut frame_area = area.clone();
+        element_area.add_margins(Margins::trbl(
+            0,
+            line_thickness,
+            line_thickness,
+            line_thickness,
+        ));
+        frame_area.add_margins(Margins::trbl(0, line_offset, 0, line_offset));
+        if self.is_first {
+            element_area.add_margins(Margins::trbl(line_thickness, 0, 0, 0));
+            frame_area.add_margins(Margins::trbl(line_offset, 0, 0, 0));
+        }
+
+        // Draw the element.
+        let mut result = self.element.render(context, element_area, style)?;
+        result.size.width = area.size().width;
+        if result.has_more {
+            frame_area.set_height(result.size.height + line_offset);
+        } else {
+            frame_area.set_height(result.size.height + line_thickness);
+        }
+
+        // Draw the frame.
+        let top_left = Position::default();
+        let top_right = Position::new(frame_area.size().width, 0);
+        let bottom_left = Position::new(0, frame_area.size().height);
+        let bottom_right = Position::new(frame_area.size().width, frame_area.size().height);
+
+        let radius = self.line_style.corner_radius();
+        let draw_frame = |points: &[Position], is_closed: bool| {
+            if radius > Mm(0.0) {
+                let segments = rounded_polyline(points, radius, is_closed);
+                frame_area.stroke_path(&segments, self.line_style);
+            } else {
+                frame_area.draw_line(points.to_vec(), self.line_style);
+            }
+        };
+
+        if self.is_first && !result.has_more && radius > Mm(0.0) {
+            // A rounded frame on a single page is drawn as one closed path so that the rounded
+            // corners are not covered by the sides.
+            result.size.height += line_thickness * 2.0;
+            draw_frame(&[top_left, top_right, bottom_right, bottom_left], true);
+        } else {
+            if self.is_first {
+                result.size.height += line_thickness;
+                draw_frame(&[bottom_right, top_right, top_left, bottom_left], false);
+            }
+            if !result.has_more {
+                result.size.height += line_thickness;
+                draw_frame(&[top_left, bottom_left, bottom_right, top_right], false);
+            } else {
+                draw_frame(&[top_left, bottom_left], false);
+                draw_frame(&[top_right, bottom_right], false);
+            }
+        }
+
+        self.is_first = false;
+
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        // For the element area calculations, we have to take into account the full line thickness.
+        // For the frame area, we only need half because we specify the center of the line.
+        let line_thickness = self.line_style.thickness();
+
+        // Calculate the areas in which to draw the element and the frame.
+        let mut element_area = area.clone();
+        element_area.add_margins(Margins::trbl(
+            0,
+            line_thickness,
+            line_thickness,
+            line_thickness,
+        ));
+
+        // Draw the element.
+        let mut result = self.element.measure(context, element_area, style)?;
+        result.size.width = area.size().width;
+
+        if self.is_first {
+            result.size.height += line_thickness;
+        }
+        if !result.has_more {
+            result.size.height += line_thickness;
+        }
+
+        self.is_first = false;
+
+        Ok(result)
+    }
+}
+
+/// Draws a background and borders around the wrapped element.
+///
+/// In contrast to a [`FramedElement`][], every side of the box can have its own border line style
+/// or no border at all.  The box can also have rounded corners, a background color and a padding
+/// between the borders and the wrapped element.  The box always uses the full width of the
+/// available area.
+///
+/// If the wrapped element is split across pages, the top border is only drawn on the first page
+/// and the bottom border is only drawn on the last page.  The corners at the page break are not
+/// rounded.  If a rounded corner is between two borders with different line styles, each half of
+/// the corner is drawn with the line style of the adjacent border.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let p = elements::BoxDecoration::new(elements::Paragraph::new("text"))
+///     .with_border(style::LineStyle::new().with_thickness(0.3))
+///     .with_left_border(style::LineStyle::new().with_thickness(1.5))
+///     .with_corner_radius(2)
+///     .with_background_color(style::Color::Rgb(240, 240, 240))
+///     .with_padding(2);
+/// ```
+///
+/// [`FramedElement`]: struct.FramedElement.html
+#[derive(Clone, Debug, Default)]
+pub struct BoxDecoration<E: Element> {
+    element: E,
+    borders: [Option<LineStyle>; 4],
+    corner_radius: Mm,
+    background_color: Option<Color>,
+    padding: Margins,
+    is_continued: bool,
+}
+
+impl<E: Element> BoxDecoration<E> {
+    const TOP: usize = 0;
+    const RIGHT: usize = 1;
+    const BOTTOM: usize = 2;
+    const LEFT: usize = 3;
+
+    /// Creates a new box decoration without borders and background for the given element.
+    pub fn new(element: E) -> BoxDecoration<E> {
+        BoxDecoration {
+            element,
+            borders: [None; 4],
+            corner_radius: Mm(0.0),
+            background_color: None,
+            padding: Margins::default(),
+            is_continued: false,
+        }
+    }
+
+    /// Sets the line style for all borders.
+    pub fn set_border(&mut self, line_style: impl Into<LineStyle>) {
+        self.borders = [Some(line_style.into()); 4];
+    }
+
+    /// Sets the line style for all borders and returns the box decoration.
+    pub fn with_border(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_border(line_style);
+        self
+    }
+
+    /// Sets the line style for the top border.
+    pub fn set_top_border(&mut self, line_style: impl Into<LineStyle>) {
+        self.borders[Self::TOP] = Some(line_style.into());
+    }
+
+    /// Sets the line style for the top border and returns the box decoration.
+    pub fn with_top_border(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_top_border(line_style);
+        self
+    }
+
+    /// Sets the line style for the right border.
+    pub fn set_right_border(&mut self, line_style: impl Into<LineStyle>) {
+        self.borders[Self::RIGHT] = Some(line_style.into());
+    }
+
+    /// Sets the line style for the right border and returns the box decoration.
+    pub fn with_right_border(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_right_border(line_style);
+        self
+    }
+
+    /// Sets the line style for the bottom border.
+    pub fn set_bottom_border(&mut self, line_style: impl Into<LineStyle>) {
+        self.borders[Self::BOTTOM] = Some(line_style.into());
+    }
+
+    /// Sets the line style for the bottom border and returns the box decoration.
+    pub fn with_bottom_border(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_bottom_border(line_style);
+        self
+    }
+
+    /// Sets the line style for the left border.
+    pub fn set_left_border(&mut self, line_style: impl Into<LineStyle>) {
+        self.borders[Self::LEFT] = Some(line_style.into());
+    }
+
+    /// Sets the line style for the left border and returns the box decoration.
+    pub fn with_left_border(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_left_border(line_style);
+        self
+    }
+
+    /// Removes all borders.
+    pub fn clear_borders(&mut self) {
+        self.borders = [None; 4];
+    }
+
+    /// Sets the radius of the rounded corners.
+    ///
+    /// The radius is limited to half the width and height of the box.  Per default, the corners
+    /// are not rounded.
+    pub fn set_corner_radius(&mut self, radius: impl Into<Mm>) {
+        self.corner_radius = radius.into();
+    }
+
+    /// Sets the radius of the rounded corners and returns the box decoration.
+    ///
+    /// The radius is limited to half the width and height of the box.  Per default, the corners
+    /// are not rounded.
+    pub fn with_corner_radius(mut self, radius: impl Into<Mm>) -> Self {
+        self.set_corner_radius(radius);
+        self
+    }
+
+    /// Sets the background color of the box.
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = Some(color);
+    }
+
+    /// Sets the background color of the box and returns the box decoration.
+    pub fn with_background_color(mut self, color: Color) -> Self {
+        self.set_background_color(color);
+        self
+    }
+
+    /// Sets the padding between the borders and the wrapped element.
+    pub fn set_padding(&mut self, padding: impl Into<Margins>) {
+        self.padding = padding.into();
+    }
+
+    /// Sets the padding between the borders and the wrapped element and returns the box
+    /// decoration.
+    pub fn with_padding(mut self, padding: impl Into<Margins>) -> Self {
+        self.set_padding(padding);
+        self
+    }
+
+    /// Returns the thickness of the border on the given side, or zero if it is not drawn.
+    fn thickness(&self, side: usize) -> Mm {
+        self.borders[side]
+            .map(|line_style| line_style.thickness())
+            .unwrap_or_default()
+    }
+
+    /// Returns the spaces between the edges of the given area and the wrapped element.  The
+    /// bottom space is always reserved so that the bottom border fits if the element ends on this
+    /// page.
+    fn insets(&self) -> Margins {
+        let top = if self.is_continued {
+            Mm(0.0)
+        } else {
+            self.thickness(Self::TOP) + self.padding.top
+        };
+        Margins::trbl(
+            top,
+            self.thickness(Self::RIGHT) + self.padding.right,
+            self.thickness(Self::BOTTOM) + self.padding.bottom,
+            self.thickness(Self::LEFT) + self.padding.left,
+        )
+    }
+
+    /// Returns the size of the box for the given element result.
+    fn size(&self, area: &render::Area<'_>, result: &RenderResult) -> Size {
+        let insets = self.insets();
+        let mut height = insets.top + result.size.height;
+        if !result.has_more {
+            height += insets.bottom;
+        }
+        Size::new(area.size().width, height)
+    }
+
+    /// Returns the corners of the box with the given size, starting with the upper left corner
+    /// and going clockwise, as well as the radius of each corner.
+    ///
+    /// The corners are the centers of the borders.  Every corner is given as the corner point and
+    /// the center of its arc.
+    fn corners(&self, size: Size, has_more: bool) -> ([(Position, Position); 4], [Mm; 4]) {
+        // The borders are drawn inside of the box, so we have to move their centers by half of
+        // their thickness.
+        let left = self.thickness(Self::LEFT) / 2.0;
+        let right = size.width - self.thickness(Self::RIGHT) / 2.0;
+        let top = if self.is_continued {
+            Mm(0.0)
+        } else {
+            self.thickness(Self::TOP) / 2.0
+        };
+        let bottom = if has_more {
+            size.height
+        } else {
+            size.height - self.thickness(Self::BOTTOM) / 2.0
+        };
+        let radius = self
+            .corner_radius
+            .min((right - left) / 2.0)
+            .min((bottom - top) / 2.0)
+            .max(Mm(0.0));
+        let corners = [
+            (
+                Position::new(left, top),
+                Position::new(left + radius, top + radius),
+            ),
+            (
+                Position::new(right, top),
+                Position::new(right - radius, top + radius),
+            ),
+            (
+                Position::new(right, bottom),
+                Position::new(right - radius, bottom - radius),
+            ),
+            (
+                Position::new(left, bottom),
+                Position::new(left + radius, bottom - radius),
+            ),
+        ];
+        // The corners at a page break are not rounded.
+        let top_radius = if self.is_continued { Mm(0.0) } else { radius };
+        let bottom_radius = if has_more { Mm(0.0) } else { radius };
+        (
+            corners,
+            [top_radius, top_radius, bottom_radius, bottom_radius],
+        )
+    }
+
+    /// Fills the background of the box with the given size.
+    fn draw_background(&self, area: &render::Area<'_>, size: Size, has_more: bool) {
+        let color = if let Some(color) = self.background_color {
+            color
+        } else {
+            return;
+        };
+        let (corners, radii) = self.corners(size, has_more);
+        let mut segments = Vec::new();
+        for (corner, ((point, center), radius)) in corners.iter().zip(&radii).enumerate() {
+            let (point, segment) = if *radius > Mm(0.0) {
+                let angle = corner_angle(corner);
+                let segment = arc_segment(*center, *radius, angle, angle + FRAC_PI_2);
+                (arc_point(*center, *radius, angle), Some(segment))
+            } else {
+                (*point, None)
+            };
+            if corner == 0 {
+                segments.push(render::PathSegment::MoveTo(point));
+            } else {
+                segments.push(render::PathSegment::LineTo(point));
+            }
+            segments.extend(segment);
+        }
+        segments.push(render::PathSegment::Close);
+        area.fill_path(&segments, color, false);
+    }
+
+    /// Draws the borders of the box with the given size.
+    fn draw_borders(&self, area: &render::Area<'_>, size: Size, has_more: bool) {
+        let (corners, radii) = self.corners(size, has_more);
+        let is_drawn = [!self.is_continued, true, !has_more, true];
+        for (side, (border, is_drawn)) in self.borders.iter().zip(&is_drawn).enumerate() {
+            let line_style = match border {
+                Some(line_style) if *is_drawn => *line_style,
+                _ => continue,
+            };
+            // A side goes from the corner with the same index to the next corner.  It includes
+            // the adjacent halves of the arcs of these corners.
+            let (start, end) = (side, (side + 1) % 4);
+            let mut segments = Vec::new();
+            if radii[start] > Mm(0.0) {
+                let (center, radius) = (corners[start].1, radii[start]);
+                let angle = corner_angle(start) + FRAC_PI_4;
+                let point = arc_point(center, radius, angle);
+                segments.push(render::PathSegment::MoveTo(point));
+                segments.push(arc_segment(center, radius, angle, angle + FRAC_PI_4));
+            } else {
+                segments.push(render::PathSegment::MoveTo(corners[start].0));
+            }
+            if radii[end] > Mm(0.0) {
+                let (center, radius) = (corners[end].1, radii[end]);
+                let angle = corner_angle(end);
+                segments.push(render::PathSegment::LineTo(arc_point(
+                    center, radius, angle,
+                )));
+                segments.push(arc_segment(center, radius, angle, angle + FRAC_PI_4));
+            } else {
+                segments.push(render::PathSegment::LineTo(corners[end].0));
+            }
+            area.stroke_path(&segments, line_style);
+        }
+    }
+}
+
+/// Returns the path of a polyline through the given points with rounded corners.
+///
+/// The inner points and, if the path is closed, also the first and the last point are rounded
+/// with the given radius.  The radius is limited so that the arcs of two adjacent corners do not
+/// overlap.
+fn rounded_polyline(points: &[Position], radius: Mm, is_closed: bool) -> Vec<render::PathSegment> {
+    let n = points.len();
+    let is_rounded = |i: usize| radius > Mm(0.0) && n > 2 && (is_closed || (i > 0 && i + 1 < n));
+    // Returns the point on the line from a to b with the given distance from a.
+    let towards = |a: Position, b: Position, distance: Mm| {
+        let length = ((b.x - a.x).0.powi(2) + (b.y - a.y).0.powi(2)).sqrt();
+        if length > 0.0 {
+            let factor = distance.0 / length;
+            Position::new(a.x + (b.x - a.x) * factor, a.y + (b.y - a.y) * factor)
+        } else {
+            a
+        }
+    };
+    // Returns the maximum radius of the corner at point i towards the neighbor j.
+    let limit = |i: usize, j: usize| {
+        let (a, b) = (points[i], points[j]);
+        let length = Mm(((b.x - a.x).0.powi(2) + (b.y - a.y).0.powi(2)).sqrt());
+        if is_rounded(j) {
+            length / 2.0
+        } else {
+            length
+        }
+    };
+    // Returns the start and end of the arc at point i and the Bézier curve for the arc.
+    let corner = |i: usize| {
+        let (prev, next) = ((i + n - 1) % n, (i + 1) % n);
+        let radius = radius.min(limit(i, prev)).min(limit(i, next));
+        let point = points[i];
+        let start = towards(point, points[prev], radius);
+        let end = towards(point, points[next], radius);
+        // The distance of the Bézier control points from the ends of a circular arc
+        let k = radius * 0.552_284_75;
+        let curve =
+            render::PathSegment::CurveTo(towards(start, point, k), towards(end, point, k), end);
+        (start, end, curve)
+    };
+
+    let mut segments = Vec::new();
+    // Adds a line to the given point unless the path already ends there.
+    let line_to = |segments: &mut Vec<render::PathSegment>, point: Position| {
+        let end = match segments.last() {
+            Some(render::PathSegment::MoveTo(end)) => Some(*end),
+            Some(render::PathSegment::LineTo(end)) => Some(*end),
+            Some(render::PathSegment::CurveTo(_, _, end)) => Some(*end),
+            _ => None,
+        };
+        if end != Some(point) {
+            segments.push(render::PathSegment::LineTo(point));
+        }
+    };
+    for (i, point) in points.iter().enumerate() {
+        if is_rounded(i) {
+            let (start, end, curve) = corner(i);
+            if i == 0 {
+                segments.push(render::PathSegment::MoveTo(end));
+            } else {
+                line_to(&mut segments, start);
+                segments.push(curve);
+            }
+        } else if i == 0 {
+            segments.push(render::PathSegment::MoveTo(*point));
+        } else {
+            line_to(&mut segments, *point);
+        }
+    }
+    if is_closed && n > 0 {
+        if is_rounded(0) {
+            let (start, _, curve) = corner(0);
+            line_to(&mut segments, start);
+            segments.push(curve);
+        }
+        segments.push(render::PathSegment::Close);
+    }
+    segments
+}
+
+/// Returns the angle at which the arc of the corner with the given index starts, measured
+/// clockwise from the positive x axis.  The corners are numbered clockwise, starting with the upper
+/// left corner.
+fn corner_angle(corner: usize) -> f64 {
+    FRAC_PI_2 * (corner as f64 + 2.0)
+}
+
+/// Returns the point on the circle with the given center and radius at the given angle, measured
+/// clockwise from the positive x axis.
+fn arc_point(center: Position, radius: Mm, angle: f64) -> Position {
+    Position::new(
+        center.x + radius * angle.cos(),
+        center.y + radius * angle.sin(),
+    )
+}
+
+/// Returns a Bézier curve that approximates the arc of the circle with the given center and radius
+/// between the given angles.  The arc should not span more than a quarter circle.
+fn arc_segment(center: Position, radius: Mm, start: f64, end: f64) -> render::PathSegment {
+    let k = 4.0 / 3.0 * ((end - start) / 4.0).tan();
+    let p0 = arc_point(center, radius, start);
+    let p3 = arc_point(center, radius, end);
+    let p1 = Position::new(
+        p0.x - radius * (k * start.sin()),
+        p0.y + radius * (k * start.cos()),
+    );
+    let p2 = Position::new(
+        p3.x + radius * (k * end.sin()),
+        p3.y - radius * (k * end.cos()),
+    );
+    render::PathSegment::CurveTo(p1, p2, p3)
+}
+
+impl<E: Element> Element for BoxDecoration<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut element_area = area.clone();
+        element_area.add_margins(self.insets());
+
+        // The background has to be drawn before the element, so we measure the element first.
+        let measured = self.element.measure(context, element_area.clone(), style)?;
+        if !self.is_continued && measured.has_more && measured.size == Size::default() {
+            return Ok(measured);
+        }
+        self.draw_background(&area, self.size(&area, &measured), measured.has_more);
+
+        let result = self.element.render(context, element_area, style)?;
+        let size = self.size(&area, &result);
+        self.draw_borders(&area, size, result.has_more);
+        self.is_continued = result.has_more;
+        Ok(RenderResult {
+            size,
+            has_more: result.has_more,
+        })
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut element_area = area.clone();
+        element_area.add_margins(self.insets());
+        let result = self.element.measure(context, element_area, style)?;
+        if !self.is_continued && result.has_more && result.size == Size::default() {
+            return Ok(result);
+        }
+        Ok(RenderResult {
+            size: self.size(&area, &result),
+            has_more: result.has_more,
+        })
+    }
+}
+
+/// Adds continuation markers to the wrapped element if it is split across multiple pages.
+///
+/// If the wrapped element does not fit on the current page, this element prints a marker below
+/// the content on the current page (per default “(continued on next page)”) and a marker above the
+/// content on the next page (per default “(continued)”).  The markers are not printed if the
+/// wrapped element is not split.  The space for the marker at the bottom of the page is always
+/// reserved, so the wrapped element might be split earlier than without the markers.
+///
+/// The markers are paragraphs, so you can customize their text, style and alignment.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style, Alignment};
+/// let table = elements::TableLayout::new(vec![1, 1]);
+/// let table = elements::ContinuedElement::new(table)
+///     .with_bottom_marker(
+///         elements::Paragraph::new("continued on next page")
+///             .styled_string(" …", style::Effect::Bold)
+///             .aligned(Alignment::Right),
+///     )
+///     .with_top_marker(elements::Paragraph::new("… continued"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ContinuedElement<E: Element> {
+    element: E,
+    top_marker: Paragraph,
+    bottom_marker: Paragraph,
+    is_continuation: bool,
+}
+
+impl<E: Element> ContinuedElement<E> {
+    /// Creates a new element that adds the default continuation markers to the given element.
+    pub fn new(element: E) -> ContinuedElement<E> {
+        ContinuedElement {
+            element,
+            top_marker: Paragraph::new(StyledString::new("(continued)", Style::new().italic())),
+            bottom_marker: Paragraph::new(StyledString::new(
+                "(continued on next page)",
+                Style::new().italic(),
+            ))
+            .aligned(Alignment::Right),
+            is_continuation: false,
+        }
+    }
+
+    /// Sets the marker that is printed above the content on the continuation pages.
+    pub fn set_top_marker(&mut self, marker: impl Into<Paragraph>) {
+        self.top_marker = marker.into();
+    }
+
+    /// Sets the marker that is printed above the content on the continuation pages and returns
+    /// this element.
+    pub fn with_top_marker(mut self, marker: impl Into<Paragraph>) -> Self {
+        self.set_top_marker(marker);
+        self
+    }
+
+    /// Sets the marker that is printed below the content if it is continued on the next page.
+    pub fn set_bottom_marker(&mut self, marker: impl Into<Paragraph>) {
+        self.bottom_marker = marker.into();
+    }
+
+    /// Sets the marker that is printed below the content if it is continued on the next page and
+    /// returns this element.
+    pub fn with_bottom_marker(mut self, marker: impl Into<Paragraph>) -> Self {
+        self.set_bottom_marker(marker);
+        self
+    }
+}
+
+impl<E: Element> Element for ContinuedElement<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+
+        if self.is_continuation {
+            let marker_result = self
+                .top_marker
+                .clone()
+                .render(context, area.clone(), style)?;
+            if marker_result.has_more {
+                result.has_more = true;
+                return Ok(result);
+            }
+            area.add_offset(Position::new(0, marker_result.size.height));
+            result.size = marker_result.size;
+        }
+
+        // Reserve space for the bottom marker in case the element has to be split.  The small
+        // slack makes sure that the marker still fits into the reserved space after rounding.
+        let marker_height = self
+            .bottom_marker
+            .clone()
+            .measure(context, area.clone(), style)?
+            .size
+            .height
+            + Mm(1e-6);
+        let mut element_area = area.clone();
+        element_area.set_height((area.size().height - marker_height).max(Mm(0.0)));
+
+        let element_result = self.element.render(context, element_area.clone(), style)?;
+        result.size = result.size.stack_vertical(element_result.size);
+        result.has_more = element_result.has_more;
+
+        // Only print the bottom marker if some content of the element has been printed on this
+        // page, i. e. if the element has actually been split.  Some elements, for example tables
+        // with cell decorations, may exceed the area they have been rendered in, so we make sure
+        // that the marker is printed in the reserved space.
+        if element_result.has_more && element_result.size.height > Mm(0.0) {
+            let height = element_result.size.height.min(element_area.size().height);
+            area.add_offset(Position::new(0, height));
+            let marker_result = self.bottom_marker.clone().render(context, area, style)?;
+            result.size = result.size.stack_vertical(marker_result.size);
+            self.is_continuation = true;
+        }
+
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_continuation {
+            let marker_result = self
+                .top_marker
+                .clone()
+                .measure(context, area.clone(), style)?;
+            area.add_offset(Position::new(0, marker_result.size.height));
+            result.size = marker_result.size;
+        }
+        let element_result = self.element.measure(context, area, style)?;
+        result.size = result.size.stack_vertical(element_result.size);
+        result.has_more = element_result.has_more;
+        Ok(result)
+    }
+}
+
+/// Moves the wrapped element to the next page if it would otherwise be split across pages.
+///
+/// Before the wrapped element is rendered for the first time, it is measured in the available
+/// area.  If it does not fit, nothing is rendered on the current page, and the element is
+/// rendered on the next page instead.  If the element does not even fit on an empty page, it is
+/// split as usual.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let section = elements::KeepTogether::new(
+///     elements::LinearLayout::vertical()
+///         .element(elements::Paragraph::new("Title"))
+///         .element(elements::Paragraph::new("Content")),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct KeepTogether<E: Element> {
+    element: E,
+    is_started: bool,
+}
+
+impl<E: Element> KeepTogether<E> {
+    /// Creates a new element that keeps the given element together on one page.
+    pub fn new(element: E) -> KeepTogether<E> {
+        KeepTogether {
+            element,
+            is_started: false,
+        }
+    }
+
+    /// Returns whether the wrapped element has to be moved to the next page because it does not
+    /// fit into the given area.
+    fn is_moved(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<bool, Error> {
+        // On an empty page, moving the element would not help
+        if self.is_started || !area.has_content() {
+            return Ok(false);
+        }
+        // Some elements report their full size instead of setting the has_more flag if they do not
+        // fit into the area
+        let result = self.element.measure(context, area.clone(), style)?;
+        Ok(result.has_more || result.size.height > area.size().height)
+    }
+}
+
+impl<E: Element> Element for KeepTogether<E> {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.is_moved(context, &area, style)? {
+            return Ok(RenderResult {
+                size: Size::new(0, 0),
+                has_more: true,
+            });
+        }
+        self.is_started = true;
+        self.element.render(context, area, style)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        if self.is_moved(context, &area, style)? {
+            return Ok(RenderResult {
+                size: Size::new(0, 0),
+                has_more: true,
+            });
+        }
+        self.element.measure(context, area, style)
+    }
+}
+
+/// The bullet point symbols and indentations per nesting level of lists.
+///
+/// The levels that are set for a list are inherited by the lists nested in it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ListLevels {
+    bullets: Vec<String>,
+    indents: Vec<Mm>,
+}
+
+impl ListLevels {
+    const DEFAULT_BULLETS: [&'static str; 3] = ["•", "◦", "–"];
+
+    /// Returns the bullet point symbol for the given nesting level.  If there are not enough
+    /// symbols, the last one is used.
+    fn bullet(&self, depth: usize) -> String {
+        if self.bullets.is_empty() {
+            let idx = depth.min(Self::DEFAULT_BULLETS.len() - 1);
+            Self::DEFAULT_BULLETS[idx].to_owned()
+        } else {
+            let idx = depth.min(self.bullets.len() - 1);
+            self.bullets[idx].clone()
+        }
+    }
+
+    /// Returns the indentation for the given nesting level, if set.  If there are not enough
+    /// indentations, the last one is used.
+    fn indent(&self, depth: usize) -> Option<Mm> {
+        self.indents
+            .get(depth)
+            .or_else(|| self.indents.last())
+            .copied()
+    }
+
+    /// Returns these levels with the non-empty settings of the given levels.
+    fn merge(&self, other: &ListLevels) -> ListLevels {
+        ListLevels {
+            bullets: if other.bullets.is_empty() {
+                self.bullets.clone()
+            } else {
+                other.bullets.clone()
+            },
+            indents: if other.indents.is_empty() {
+                self.indents.clone()
+            } else {
+                other.indents.clone()
+            },
+        }
+    }
+}
+
+/// Calls the given function with the current nesting level while the nesting level is increased
+/// and the given levels are set for the nested lists.
+fn with_list_levels<T>(
+    context: &Context,
+    levels: &ListLevels,
+    f: impl FnOnce(usize) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let depth = context.list_depth.get();
+    let parent_levels = context.list_levels.replace(levels.clone());
+    context.list_depth.set(depth + 1);
+    let result = f(depth);
+    context.list_depth.set(depth);
+    context.list_levels.replace(parent_levels);
+    result
+}
+
+/// An unordered list of elements with bullet points.
+///
+/// Per default, the bullet point symbol depends on the nesting level of the list:  Top-level
+/// lists use `•`, lists nested in another list use `◦` and all deeper levels use `–`.  You can
+/// change the symbols and the indentation per level with [`set_bullets`][] and
+/// [`set_indents`][].  These settings are inherited by all lists nested in this list.  To use the
+/// same symbol regardless of the nesting level, use [`with_bullet`][].
 ///
 /// # Examples
 ///
@@ -943,55 +5574,104 @@ impl<E: Element> Element for FramedElement<E> {
 ///     .element(elements::Paragraph::new("third"));
 /// ```
 ///
-/// Nested list using a [`LinearLayout`][]:
+/// Nested list with custom bullet symbols and indentations per level:
 /// ```
 /// use genpdf::elements;
 /// let list = elements::UnorderedList::new()
-///     .element(
-///         elements::OrderedList::new()
-///             .element(elements::Paragraph::new("Sublist with bullet point"))
-///     )
+///     .with_bullets(vec!["*", "-"])
+///     .with_indents(vec![8, 6])
+///     .element(elements::Paragraph::new("first"))
 ///     .element(
 ///         elements::LinearLayout::vertical()
-///             .element(elements::Paragraph::new("Sublist without bullet point:"))
+///             .element(elements::Paragraph::new("second:"))
 ///             .element(
-///                 elements::OrderedList::new()
-///                     .element(elements::Paragraph::new("first"))
-///                     .element(elements::Paragraph::new("second"))
+///                 elements::UnorderedList::new()
+///                     .element(elements::Paragraph::new("nested"))
 ///             )
 ///     );
 /// ```
 ///
-/// [`LinearLayout`]: struct.LinearLayout.html
+/// [`set_bullets`]: #method.set_bullets
+/// [`set_indents`]: #method.set_indents
+/// [`with_bullet`]: #method.with_bullet
 pub struct UnorderedList {
     layout: LinearLayout,
+    items: Vec<Box<dyn Element>>,
     bullet: Option<String>,
+    levels: ListLevels,
 }
 
 impl UnorderedList {
-    /// Creates a new unordered list with the default bullet point symbol.
+    /// Creates a new unordered list with the default bullet point symbols.
     pub fn new() -> UnorderedList {
         UnorderedList {
             layout: LinearLayout::vertical(),
+            items: Vec::new(),
             bullet: None,
+            levels: ListLevels::default(),
         }
     }
 
-    /// Creates a new unordered list with the given bullet point symbol.
+    /// Creates a new unordered list with the given bullet point symbol for all nesting levels.
     pub fn with_bullet(bullet: impl Into<String>) -> UnorderedList {
         UnorderedList {
-            layout: LinearLayout::vertical(),
             bullet: Some(bullet.into()),
+            ..UnorderedList::new()
         }
     }
 
+    /// Sets the bullet point symbols per nesting level, starting with the top level.
+    ///
+    /// If a list is nested deeper than the number of symbols, the last symbol is used.  Lists
+    /// nested in this list use these symbols unless they set their own symbols.
+    pub fn set_bullets<I, S>(&mut self, bullets: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.levels.bullets = bullets.into_iter().map(Into::into).collect();
+    }
+
+    /// Sets the bullet point symbols per nesting level, starting with the top level, and returns
+    /// the list.
+    pub fn with_bullets<I, S>(mut self, bullets: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.set_bullets(bullets);
+        self
+    }
+
+    /// Sets the indentations per nesting level, starting with the top level, see
+    /// [`BulletPoint::set_indent`][].
+    ///
+    /// If a list is nested deeper than the number of indentations, the last indentation is used.
+    /// Lists nested in this list use these indentations unless they set their own indentations.
+    ///
+    /// [`BulletPoint::set_indent`]: struct.BulletPoint.html#method.set_indent
+    pub fn set_indents<I, M>(&mut self, indents: I)
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<Mm>,
+    {
+        self.levels.indents = indents.into_iter().map(Into::into).collect();
+    }
+
+    /// Sets the indentations per nesting level, starting with the top level, and returns the
+    /// list.
+    pub fn with_indents<I, M>(mut self, indents: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<Mm>,
+    {
+        self.set_indents(indents);
+        self
+    }
+
     /// Adds an element to this list.
     pub fn push<E: Element + 'static>(&mut self, element: E) {
-        let mut point = BulletPoint::new(element);
-        if let Some(bullet) = &self.bullet {
-            point.set_bullet(bullet.clone());
-        }
-        self.layout.push(point);
+        self.items.push(Box::new(element));
     }
 
     /// Adds an element to this list and returns the list.
@@ -999,6 +5679,22 @@ impl UnorderedList {
         self.push(element);
         self
     }
+
+    /// Moves the elements that have been added since the last call into the layout and assigns
+    /// their bullet points.
+    fn bullet_items(&mut self, depth: usize, levels: &ListLevels) {
+        for item in mem::take(&mut self.items) {
+            let mut point = BulletPoint::new(ListItem(item));
+            match &self.bullet {
+                Some(bullet) => point.set_bullet(bullet.clone()),
+                None => point.set_bullet(levels.bullet(depth)),
+            }
+            if let Some(indent) = levels.indent(depth) {
+                point.set_indent(indent);
+            }
+            self.layout.push(point);
+        }
+    }
 }
 
 impl Element for UnorderedList {
@@ -1008,7 +5704,11 @@ impl Element for UnorderedList {
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        self.layout.render(context, area, style)
+        let levels = context.list_levels.borrow().merge(&self.levels);
+        with_list_levels(context, &levels, |depth| {
+            self.bullet_items(depth, &levels);
+            self.layout.render(context, area, style)
+        })
     }
 
     fn measure(
@@ -1017,7 +5717,11 @@ impl Element for UnorderedList {
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        self.layout.measure(context, area, style)
+        let levels = context.list_levels.borrow().merge(&self.levels);
+        with_list_levels(context, &levels, |depth| {
+            self.bullet_items(depth, &levels);
+            self.layout.measure(context, area, style)
+        })
     }
 }
 
@@ -1027,23 +5731,113 @@ impl Default for UnorderedList {
     }
 }
 
-impl<E: Element + 'static> iter::Extend<E> for UnorderedList {
-    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        for element in iter {
-            self.push(element);
-        }
+impl<E: Element + 'static> iter::Extend<E> for UnorderedList {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for element in iter {
+            self.push(element);
+        }
+    }
+}
+
+impl<E: Element + 'static> iter::FromIterator<E> for UnorderedList {
+    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+        let mut list = Self::default();
+        list.extend(iter);
+        list
+    }
+}
+
+/// The numbering style of an [`OrderedList`][].
+///
+/// [`OrderedList`]: struct.OrderedList.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Numbering {
+    /// Arabic numbers (1, 2, 3, …).
+    Arabic,
+    /// Lowercase letters (a, b, c, …, z, aa, ab, …).
+    LowerAlpha,
+    /// Uppercase letters (A, B, C, …, Z, AA, AB, …).
+    UpperAlpha,
+    /// Lowercase roman numerals (i, ii, iii, …).
+    LowerRoman,
+    /// Uppercase roman numerals (I, II, III, …).
+    UpperRoman,
+}
+
+impl Default for Numbering {
+    fn default() -> Numbering {
+        Numbering::Arabic
+    }
+}
+
+impl Numbering {
+    /// Formats the given number with this numbering style.
+    ///
+    /// Letters and roman numerals are only defined for positive numbers, so zero is always
+    /// formatted as an arabic number.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use genpdf::elements::Numbering;
+    /// assert_eq!("28", Numbering::Arabic.format(28));
+    /// assert_eq!("ab", Numbering::LowerAlpha.format(28));
+    /// assert_eq!("XXVIII", Numbering::UpperRoman.format(28));
+    /// ```
+    pub fn format(&self, number: usize) -> String {
+        match self {
+            _ if number == 0 => number.to_string(),
+            Numbering::Arabic => number.to_string(),
+            Numbering::LowerAlpha => format_alpha(number),
+            Numbering::UpperAlpha => format_alpha(number).to_uppercase(),
+            Numbering::LowerRoman => format_roman(number).to_lowercase(),
+            Numbering::UpperRoman => format_roman(number),
+        }
+    }
+}
+
+fn format_alpha(mut number: usize) -> String {
+    let mut letters = Vec::new();
+    while number > 0 {
+        number -= 1;
+        letters.push(char::from(b'a' + (number % 26) as u8));
+        number /= 26;
     }
+    letters.iter().rev().collect()
 }
 
-impl<E: Element + 'static> iter::FromIterator<E> for UnorderedList {
-    fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
-        let mut list = Self::default();
-        list.extend(iter);
-        list
+fn format_roman(mut number: usize) -> String {
+    const NUMERALS: &[(usize, &str)] = &[
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut s = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= *value {
+            s.push_str(numeral);
+            number -= value;
+        }
     }
+    s
 }
 
-/// An ordered list of elements with arabic numbers.
+/// An ordered list of elements with automatic numbering.
+///
+/// Per default, the elements are numbered with arabic numbers starting at 1.  You can change the
+/// numbering style with [`set_numbering`][] and the indentation of the elements with
+/// [`set_indent`][].  The numbers are assigned when the list is rendered, so these settings apply
+/// to all elements of the list, regardless of the order of the method calls.
 ///
 /// # Examples
 ///
@@ -1074,7 +5868,7 @@ impl<E: Element + 'static> iter::FromIterator<E> for UnorderedList {
 ///     .element(elements::Paragraph::new("third"));
 /// ```
 ///
-/// Nested list using a [`LinearLayout`][]:
+/// Nested list with roman numerals using a [`LinearLayout`][]:
 /// ```
 /// use genpdf::elements;
 /// let list = elements::OrderedList::new()
@@ -1086,7 +5880,9 @@ impl<E: Element + 'static> iter::FromIterator<E> for UnorderedList {
 ///         elements::LinearLayout::vertical()
 ///             .element(elements::Paragraph::new("Sublist without number:"))
 ///             .element(
-///                 elements::UnorderedList::new()
+///                 elements::OrderedList::new()
+///                     .with_numbering(elements::Numbering::LowerRoman)
+///                     .with_indent(6)
 ///                     .element(elements::Paragraph::new("first"))
 ///                     .element(elements::Paragraph::new("second"))
 ///             )
@@ -1094,9 +5890,14 @@ impl<E: Element + 'static> iter::FromIterator<E> for UnorderedList {
 /// ```
 ///
 /// [`LinearLayout`]: struct.LinearLayout.html
+/// [`set_numbering`]: #method.set_numbering
+/// [`set_indent`]: #method.set_indent
 pub struct OrderedList {
     layout: LinearLayout,
+    items: Vec<Box<dyn Element>>,
     number: usize,
+    numbering: Numbering,
+    indent: Option<Mm>,
 }
 
 impl OrderedList {
@@ -1109,16 +5910,46 @@ impl OrderedList {
     pub fn with_start(start: usize) -> OrderedList {
         OrderedList {
             layout: LinearLayout::vertical(),
+            items: Vec::new(),
             number: start,
+            numbering: Numbering::Arabic,
+            indent: None,
         }
     }
 
+    /// Sets the numbering style for this list.
+    pub fn set_numbering(&mut self, numbering: Numbering) {
+        self.numbering = numbering;
+    }
+
+    /// Sets the numbering style for this list and returns the list.
+    pub fn with_numbering(mut self, numbering: Numbering) -> Self {
+        self.set_numbering(numbering);
+        self
+    }
+
+    /// Sets the indentation of the elements of this list, see [`BulletPoint::set_indent`][].
+    ///
+    /// As the elements of a nested list are indented relative to the enclosing list, this is the
+    /// indentation per nesting level.  If this method is not called, the indentation for the
+    /// nesting level of this list set with [`UnorderedList::set_indents`][] for an enclosing list
+    /// is used.
+    ///
+    /// [`BulletPoint::set_indent`]: struct.BulletPoint.html#method.set_indent
+    /// [`UnorderedList::set_indents`]: struct.UnorderedList.html#method.set_indents
+    pub fn set_indent(&mut self, indent: impl Into<Mm>) {
+        self.indent = Some(indent.into());
+    }
+
+    /// Sets the indentation of the elements of this list and returns the list.
+    pub fn with_indent(mut self, indent: impl Into<Mm>) -> Self {
+        self.set_indent(indent);
+        self
+    }
+
     /// Adds an element to this list.
     pub fn push<E: Element + 'static>(&mut self, element: E) {
-        let mut point = BulletPoint::new(element);
-        point.set_bullet(format!("{}.", self.number));
-        self.layout.push(point);
-        self.number += 1;
+        self.items.push(Box::new(element));
     }
 
     /// Adds an element to this list and returns the list.
@@ -1126,6 +5957,20 @@ impl OrderedList {
         self.push(element);
         self
     }
+
+    /// Moves the elements that have been added since the last call into the layout and assigns
+    /// their numbers.
+    fn number_items(&mut self, depth: usize, levels: &ListLevels) {
+        for item in mem::take(&mut self.items) {
+            let mut point = BulletPoint::new(ListItem(item));
+            point.set_bullet(format!("{}.", self.numbering.format(self.number)));
+            if let Some(indent) = self.indent.or_else(|| levels.indent(depth)) {
+                point.set_indent(indent);
+            }
+            self.layout.push(point);
+            self.number += 1;
+        }
+    }
 }
 
 impl Element for OrderedList {
@@ -1135,7 +5980,11 @@ impl Element for OrderedList {
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        self.layout.render(context, area, style)
+        let levels = context.list_levels.borrow().clone();
+        with_list_levels(context, &levels, |depth| {
+            self.number_items(depth, &levels);
+            self.layout.render(context, area, style)
+        })
     }
 
     fn measure(
@@ -1144,7 +5993,11 @@ impl Element for OrderedList {
         area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        self.layout.measure(context, area, style)
+        let levels = context.list_levels.borrow().clone();
+        with_list_levels(context, &levels, |depth| {
+            self.number_items(depth, &levels);
+            self.layout.measure(context, area, style)
+        })
     }
 }
 
@@ -1170,6 +6023,36 @@ impl<E: Element + 'static> iter::FromIterator<E> for OrderedList {
     }
 }
 
+/// A boxed element of an [`OrderedList`][] that can be wrapped in a [`BulletPoint`][].
+///
+/// [`OrderedList`]: struct.OrderedList.html
+/// [`BulletPoint`]: struct.BulletPoint.html
+struct ListItem(Box<dyn Element>);
+
+impl Element for ListItem {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.0.render(context, area, style)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.0.measure(context, area, style)
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.0.type_name()
+    }
+}
+
 /// A bullet point in a list.
 ///
 /// This is a helper element for the [`OrderedList`][] and [`UnorderedList`][] types, but you can
@@ -1218,6 +6101,20 @@ impl<E: Element> BulletPoint<E> {
         self.set_bullet(bullet);
         self
     }
+
+    /// Sets the indentation of the element of this bullet point.
+    ///
+    /// The bullet point symbol is printed right-aligned in the indentation.  The default
+    /// indentation is 10 mm.
+    pub fn set_indent(&mut self, indent: impl Into<Mm>) {
+        self.indent = indent.into();
+    }
+
+    /// Sets the indentation of the element of this bullet point and returns the bullet point.
+    pub fn with_indent(mut self, indent: impl Into<Mm>) -> Self {
+        self.set_indent(indent);
+        self
+    }
 }
 
 impl<E: Element> Element for BulletPoint<E> {
@@ -1275,6 +6172,16 @@ pub trait CellDecorator {
         let _ = (num_columns, num_rows);
     }
 
+    /// Starts the repetition of the header rows at the top of a new page.
+    ///
+    /// This function is called before the header rows of a table (see
+    /// [`TableLayout::set_header_rows`][]) are decorated again.  The header rows are decorated
+    /// with their original row indizes, so decorators that keep track of the last decorated row
+    /// should reset this state.
+    ///
+    /// [`TableLayout::set_header_rows`]: struct.TableLayout.html#method.set_header_rows
+    fn start_header_rows(&mut self) {}
+
     /// Prepares the cell with the given indizes and returns the area for rendering the cell.
     fn prepare_cell<'p>(
         &self,
@@ -1296,6 +6203,334 @@ pub trait CellDecorator {
         area: render::Area<'_>,
         row_height: Mm,
     ) -> Mm;
+
+    /// Prepares the cell with the given indizes that spans the given number of columns and rows
+    /// and returns the area for rendering the cell.
+    ///
+    /// The indizes are the indizes of the leftmost column and the first row of the cell.  Per
+    /// default, this method calls [`prepare_cell`][] with the area of the merged cells.
+    ///
+    /// [`prepare_cell`]: #method.prepare_cell
+    fn prepare_spanned_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        let _ = span;
+        self.prepare_cell(column, row, area)
+    }
+
+    /// Styles the cell with the given indizes that spans the given number of columns and rows and
+    /// returns the total height of the cell.
+    ///
+    /// The indizes are the indizes of the leftmost column and the first row of the cell, and the
+    /// row height is the height of the content of the merged cells.  Per default, this method
+    /// calls [`decorate_cell`][] with the area of the merged cells.
+    ///
+    /// [`decorate_cell`]: #tymethod.decorate_cell
+    fn decorate_spanned_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        let _ = span;
+        self.decorate_cell(column, row, has_more, area, row_height)
+    }
+
+    /// Prepares the cell with the given indizes, span and style and returns the area for rendering
+    /// the cell.
+    ///
+    /// Per default, this method calls [`prepare_spanned_cell`][] and ignores the style.
+    ///
+    /// [`prepare_spanned_cell`]: #method.prepare_spanned_cell
+    fn prepare_styled_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        style: &CellStyle,
+        area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        let _ = style;
+        self.prepare_spanned_cell(column, row, span, area)
+    }
+
+    /// Styles the cell with the given indizes, span and style and returns the total height of the
+    /// cell.
+    ///
+    /// Per default, this method calls [`decorate_spanned_cell`][] and ignores the style.
+    ///
+    /// [`decorate_spanned_cell`]: #method.decorate_spanned_cell
+    #[allow(clippy::too_many_arguments)]
+    fn decorate_styled_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        style: &CellStyle,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        let _ = style;
+        self.decorate_spanned_cell(column, row, span, has_more, area, row_height)
+    }
+}
+
+/// The number of columns and rows spanned by a cell of a [`TableLayout`][].
+///
+/// [`TableLayout`]: struct.TableLayout.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellSpan {
+    /// The number of columns spanned by the cell.
+    pub columns: usize,
+    /// The number of rows spanned by the cell.
+    pub rows: usize,
+}
+
+impl CellSpan {
+    /// Creates a new cell span with the given number of columns and rows.
+    pub fn new(columns: usize, rows: usize) -> CellSpan {
+        CellSpan { columns, rows }
+    }
+}
+
+impl Default for CellSpan {
+    fn default() -> CellSpan {
+        CellSpan::new(1, 1)
+    }
+}
+
+/// The style of a single cell of a [`TableLayout`][].
+///
+/// Per default, a cell uses the padding and the vertical alignment of the table, see
+/// [`TableLayout::set_cell_padding`][] and [`TableLayout::set_vertical_alignment`][], the
+/// background color of its row and the line style of the cell decorator.  The cell style can be
+/// set with [`TableLayoutRow::element_with_style`][].
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style, VerticalAlignment};
+/// let style = elements::CellStyle::new()
+///     .with_padding(2)
+///     .with_vertical_alignment(VerticalAlignment::Center)
+///     .with_background_color(style::Color::Rgb(240, 240, 240))
+///     .with_border_line_style(style::LineStyle::new().with_thickness(0.5));
+/// ```
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`TableLayout::set_cell_padding`]: struct.TableLayout.html#method.set_cell_padding
+/// [`TableLayout::set_vertical_alignment`]: struct.TableLayout.html#method.set_vertical_alignment
+/// [`TableLayoutRow::element_with_style`]: struct.TableLayoutRow.html#method.element_with_style
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CellStyle {
+    padding: Option<Margins>,
+    vertical_alignment: Option<VerticalAlignment>,
+    background_color: Option<Color>,
+    border_line_style: Option<LineStyle>,
+}
+
+impl CellStyle {
+    /// Creates a new cell style that uses the settings of the table.
+    pub fn new() -> CellStyle {
+        CellStyle::default()
+    }
+
+    /// Returns the padding of the cell, or `None` if the padding of the table is used.
+    pub fn padding(&self) -> Option<Margins> {
+        self.padding
+    }
+
+    /// Sets the padding between the border and the content of the cell.
+    pub fn set_padding(&mut self, padding: impl Into<Margins>) {
+        self.padding = Some(padding.into());
+    }
+
+    /// Sets the padding between the border and the content of the cell and returns the style.
+    pub fn with_padding(mut self, padding: impl Into<Margins>) -> Self {
+        self.set_padding(padding);
+        self
+    }
+
+    /// Returns the vertical alignment of the cell, or `None` if the alignment of the table is
+    /// used.
+    pub fn vertical_alignment(&self) -> Option<VerticalAlignment> {
+        self.vertical_alignment
+    }
+
+    /// Sets the vertical alignment of the content of the cell.
+    pub fn set_vertical_alignment(&mut self, vertical_alignment: VerticalAlignment) {
+        self.vertical_alignment = Some(vertical_alignment);
+    }
+
+    /// Sets the vertical alignment of the content of the cell and returns the style.
+    pub fn with_vertical_alignment(mut self, vertical_alignment: VerticalAlignment) -> Self {
+        self.set_vertical_alignment(vertical_alignment);
+        self
+    }
+
+    /// Returns the background color of the cell, or `None` if the background color of the row
+    /// is used.
+    pub fn background_color(&self) -> Option<Color> {
+        self.background_color
+    }
+
+    /// Sets the background color of the cell.
+    ///
+    /// This overrides the background color of the row, see
+    /// [`TableLayoutRow::set_background_color`][].
+    ///
+    /// [`TableLayoutRow::set_background_color`]: struct.TableLayoutRow.html#method.set_background_color
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = Some(color);
+    }
+
+    /// Sets the background color of the cell and returns the style.
+    ///
+    /// See [`set_background_color`][] for more information.
+    ///
+    /// [`set_background_color`]: #method.set_background_color
+    pub fn with_background_color(mut self, color: Color) -> Self {
+        self.set_background_color(color);
+        self
+    }
+
+    /// Returns the line style for the borders of the cell, or `None` if the line style of the
+    /// cell decorator is used.
+    pub fn border_line_style(&self) -> Option<LineStyle> {
+        self.border_line_style
+    }
+
+    /// Sets the line style for the borders of the cell.
+    ///
+    /// The cell decorator still decides which borders are drawn, but it uses this line style
+    /// instead of its own.  The [`FrameCellDecorator`][] draws the top and left border of every
+    /// cell, and the right and bottom border only at the outer edges of the table, so the line
+    /// style applies to these borders.
+    ///
+    /// [`FrameCellDecorator`]: struct.FrameCellDecorator.html
+    pub fn set_border_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.border_line_style = Some(line_style.into());
+    }
+
+    /// Sets the line style for the borders of the cell and returns the style.
+    ///
+    /// See [`set_border_line_style`][] for more information.
+    ///
+    /// [`set_border_line_style`]: #method.set_border_line_style
+    pub fn with_border_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_border_line_style(line_style);
+        self
+    }
+}
+
+/// The style of a row of a [`TableLayout`][] that is returned by a row decorator.
+///
+/// See [`TableLayout::set_row_decorator`][].
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let style = elements::RowStyle::new()
+///     .with_background_color(style::Color::Rgb(240, 240, 240))
+///     .with_text_style(style::Effect::Italic);
+/// ```
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`TableLayout::set_row_decorator`]: struct.TableLayout.html#method.set_row_decorator
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RowStyle {
+    background_color: Option<Color>,
+    text_style: Style,
+}
+
+impl RowStyle {
+    /// Creates a new row style that does not change the row.
+    pub fn new() -> RowStyle {
+        RowStyle::default()
+    }
+
+    /// Returns the background color of the row, if set.
+    pub fn background_color(&self) -> Option<Color> {
+        self.background_color
+    }
+
+    /// Sets the background color of the row.
+    ///
+    /// This color is only used for cells that do not have a background color and that are in a
+    /// row without a background color, see [`TableLayoutRow::set_background_color`][] and
+    /// [`CellStyle::set_background_color`][].
+    ///
+    /// [`TableLayoutRow::set_background_color`]: struct.TableLayoutRow.html#method.set_background_color
+    /// [`CellStyle::set_background_color`]: struct.CellStyle.html#method.set_background_color
+    pub fn set_background_color(&mut self, color: Color) {
+        self.background_color = Some(color);
+    }
+
+    /// Sets the background color of the row and returns the style.
+    ///
+    /// See [`set_background_color`][] for more information.
+    ///
+    /// [`set_background_color`]: #method.set_background_color
+    pub fn with_background_color(mut self, color: Color) -> Self {
+        self.set_background_color(color);
+        self
+    }
+
+    /// Returns the style that is applied to the content of the cells of the row.
+    pub fn text_style(&self) -> Style {
+        self.text_style
+    }
+
+    /// Sets the style that is applied to the content of the cells of the row.
+    ///
+    /// The style is merged into the style of the table, so the styles of the cell elements still
+    /// take precedence.
+    pub fn set_text_style(&mut self, style: impl Into<Style>) {
+        self.text_style = style.into();
+    }
+
+    /// Sets the style that is applied to the content of the cells of the row and returns the
+    /// style.
+    ///
+    /// See [`set_text_style`][] for more information.
+    ///
+    /// [`set_text_style`]: #method.set_text_style
+    pub fn with_text_style(mut self, style: impl Into<Style>) -> Self {
+        self.set_text_style(style);
+        self
+    }
+}
+
+/// A border of a table that is drawn by a [`FrameCellDecorator`][].
+///
+/// See [`FrameCellDecorator::set_border_line_style`][] and [`FrameCellDecorator::hide_border`][].
+///
+/// [`FrameCellDecorator`]: struct.FrameCellDecorator.html
+/// [`FrameCellDecorator::set_border_line_style`]: struct.FrameCellDecorator.html#method.set_border_line_style
+/// [`FrameCellDecorator::hide_border`]: struct.FrameCellDecorator.html#method.hide_border
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TableBorder {
+    /// The outer border above the first row.
+    Top,
+    /// The outer border on the right of the last column.
+    Right,
+    /// The outer border below the last row.
+    Bottom,
+    /// The outer border on the left of the first column.
+    Left,
+    /// The inner borders between two rows.
+    InnerHorizontal,
+    /// The inner borders between two columns.
+    InnerVertical,
 }
 
 /// A cell decorator that draws frames around table cells.
@@ -1304,13 +6539,57 @@ pub trait CellDecorator {
 /// inner, outer and continuation borders are drawn.  A continuation border is a border between a
 /// cell and the page margin that occurs if a cell has to be wrapped to a new page.
 ///
+/// Per default, all borders are drawn with the same [`LineStyle`][].  You can use a different line
+/// style for the inner borders by calling [`set_inner_line_style`][].  If the line style of the top
+/// or bottom border has a corner radius, see [`LineStyle::set_corner_radius`][], the outer corners
+/// of the table are rounded.
+///
+/// For more control, you can set the line style for the individual outer borders and for the
+/// inner horizontal and vertical borders with [`set_border_line_style`][], or hide them with
+/// [`hide_border`][].  For example, a table with horizontal rules only can be created by hiding
+/// the left, right and inner vertical borders.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let decorator = elements::FrameCellDecorator::with_line_style(
+///     true,
+///     true,
+///     false,
+///     style::LineStyle::new().with_thickness(0.3),
+/// )
+/// .with_inner_line_style(style::LineStyle::new().with_dash(&[1.0, 0.5]));
+/// ```
+///
+/// Horizontal rules only:
+///
+/// ```
+/// use genpdf::{elements, style};
+/// use elements::TableBorder;
+/// let decorator = elements::FrameCellDecorator::new(true, true, false)
+///     .with_border_line_style(TableBorder::Top, style::LineStyle::new().with_thickness(0.5))
+///     .with_border_line_style(TableBorder::Bottom, style::LineStyle::new().with_thickness(0.5))
+///     .with_hidden_border(TableBorder::Left)
+///     .with_hidden_border(TableBorder::Right)
+///     .with_hidden_border(TableBorder::InnerVertical);
+/// ```
+///
 /// [`TableLayout`]: struct.TableLayout.html
+/// [`LineStyle`]: ../style/struct.LineStyle.html
+/// [`set_inner_line_style`]: #method.set_inner_line_style
+/// [`set_border_line_style`]: #method.set_border_line_style
+/// [`hide_border`]: #method.hide_border
+/// [`LineStyle::set_corner_radius`]: ../style/struct.LineStyle.html#method.set_corner_radius
 #[derive(Clone, Debug, Default)]
 pub struct FrameCellDecorator {
     inner: bool,
     outer: bool,
     cont: bool,
     line_style: LineStyle,
+    inner_line_style: Option<LineStyle>,
+    border_line_styles: [Option<LineStyle>; 6],
+    hidden_borders: [bool; 6],
     num_columns: usize,
     num_rows: usize,
     last_row: Option<usize>,
@@ -1328,188 +6607,778 @@ impl FrameCellDecorator {
         }
     }
 
-    /// Creates a new frame cell decorator with the given border settings, as well as a line style.
-    pub fn with_line_style(
-        inner: bool,
-        outer: bool,
-        cont: bool,
-        line_style: impl Into<LineStyle>,
-    ) -> FrameCellDecorator {
-        Self {
-            inner,
-            outer,
-            cont,
-            line_style: line_style.into(),
-            ..Default::default()
+    /// Creates a new frame cell decorator with the given border settings, as well as a line style.
+    pub fn with_line_style(
+        inner: bool,
+        outer: bool,
+        cont: bool,
+        line_style: impl Into<LineStyle>,
+    ) -> FrameCellDecorator {
+        Self {
+            inner,
+            outer,
+            cont,
+            line_style: line_style.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the line style for all borders.
+    ///
+    /// This also resets the line style for the inner borders set with
+    /// [`set_inner_line_style`](#method.set_inner_line_style) and the line styles for individual
+    /// borders set with [`set_border_line_style`](#method.set_border_line_style).
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.line_style = line_style.into();
+        self.inner_line_style = None;
+        self.border_line_styles = Default::default();
+    }
+
+    /// Sets the line style for the inner borders.
+    ///
+    /// The outer and continuation borders are still drawn with the default line style.
+    pub fn set_inner_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.inner_line_style = Some(line_style.into());
+    }
+
+    /// Sets the line style for the inner borders and returns the decorator.
+    ///
+    /// The outer and continuation borders are still drawn with the default line style.
+    pub fn with_inner_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_inner_line_style(line_style);
+        self
+    }
+
+    /// Sets the line style for the given border.
+    ///
+    /// The border is drawn with this line style even if the inner or outer borders have been
+    /// disabled in the constructor, unless it is hidden with [`hide_border`][].  Continuation
+    /// borders are not affected.
+    ///
+    /// [`hide_border`]: #method.hide_border
+    pub fn set_border_line_style(&mut self, border: TableBorder, line_style: impl Into<LineStyle>) {
+        self.border_line_styles[border as usize] = Some(line_style.into());
+    }
+
+    /// Sets the line style for the given border and returns the decorator.
+    ///
+    /// See [`set_border_line_style`][] for more information.
+    ///
+    /// [`set_border_line_style`]: #method.set_border_line_style
+    pub fn with_border_line_style(
+        mut self,
+        border: TableBorder,
+        line_style: impl Into<LineStyle>,
+    ) -> Self {
+        self.set_border_line_style(border, line_style);
+        self
+    }
+
+    /// Hides the given border.
+    ///
+    /// The border is not drawn, even if a line style has been set for it with
+    /// [`set_border_line_style`][].  Continuation borders are not affected.
+    ///
+    /// [`set_border_line_style`]: #method.set_border_line_style
+    pub fn hide_border(&mut self, border: TableBorder) {
+        self.hidden_borders[border as usize] = true;
+    }
+
+    /// Hides the given border and returns the decorator.
+    ///
+    /// See [`hide_border`][] for more information.
+    ///
+    /// [`hide_border`]: #method.hide_border
+    pub fn with_hidden_border(mut self, border: TableBorder) -> Self {
+        self.hide_border(border);
+        self
+    }
+
+    fn inner_line_style(&self) -> LineStyle {
+        self.inner_line_style.unwrap_or(self.line_style)
+    }
+
+    /// Returns the line style for the given border, or `None` if it is not drawn.  The default
+    /// line style is used if no line style has been set for this border.
+    fn border_line_style(
+        &self,
+        border: TableBorder,
+        default: Option<LineStyle>,
+    ) -> Option<LineStyle> {
+        if self.hidden_borders[border as usize] {
+            None
+        } else {
+            self.border_line_styles[border as usize].or(default)
         }
     }
 
-    fn print_left(&self, column: usize) -> bool {
+    fn left_line_style(&self, column: usize) -> Option<LineStyle> {
         if column == 0 {
-            self.outer
+            let default = Some(self.line_style).filter(|_| self.outer);
+            self.border_line_style(TableBorder::Left, default)
         } else {
-            self.inner
+            let default = Some(self.inner_line_style()).filter(|_| self.inner);
+            self.border_line_style(TableBorder::InnerVertical, default)
         }
     }
 
-    fn print_right(&self, column: usize) -> bool {
-        if column + 1 == self.num_columns {
-            self.outer
+    fn right_line_style(&self, column: usize, span: CellSpan) -> Option<LineStyle> {
+        if column + span.columns == self.num_columns {
+            let default = Some(self.line_style).filter(|_| self.outer);
+            self.border_line_style(TableBorder::Right, default)
         } else {
-            false
+            None
         }
     }
 
-    fn print_top(&self, row: usize) -> bool {
+    fn top_line_style(&self, row: usize) -> Option<LineStyle> {
         if self.last_row.map(|last_row| row > last_row).unwrap_or(true) {
             if row == 0 {
-                self.outer
+                let default = Some(self.line_style).filter(|_| self.outer);
+                self.border_line_style(TableBorder::Top, default)
             } else {
-                self.inner
+                let default = Some(self.inner_line_style()).filter(|_| self.inner);
+                self.border_line_style(TableBorder::InnerHorizontal, default)
             }
         } else {
-            self.cont
+            Some(self.line_style).filter(|_| self.cont)
         }
     }
 
-    fn print_bottom(&self, row: usize, has_more: bool) -> bool {
+    fn bottom_line_style(&self, row: usize, span: CellSpan, has_more: bool) -> Option<LineStyle> {
         if has_more {
-            self.cont
-        } else if row + 1 == self.num_rows {
-            self.outer
+            Some(self.line_style).filter(|_| self.cont)
+        } else if row + span.rows >= self.num_rows {
+            let default = Some(self.line_style).filter(|_| self.outer);
+            self.border_line_style(TableBorder::Bottom, default)
         } else {
-            false
+            None
         }
     }
 }
 
+fn line_thickness(line_style: Option<LineStyle>) -> Mm {
+    line_style.map(|s| s.thickness()).unwrap_or_default()
+}
+
+/// Replaces the given line style with the border line style of the given cell style, if set.
+fn cell_line_style(line_style: Option<LineStyle>, style: &CellStyle) -> Option<LineStyle> {
+    line_style.map(|line_style| style.border_line_style().unwrap_or(line_style))
+}
+
 impl CellDecorator for FrameCellDecorator {
     fn set_table_size(&mut self, num_columns: usize, num_rows: usize) {
         self.num_columns = num_columns;
         self.num_rows = num_rows;
     }
 
+    fn start_header_rows(&mut self) {
+        self.last_row = None;
+    }
+
     fn prepare_cell<'p>(
         &self,
         column: usize,
         row: usize,
+        area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        self.prepare_spanned_cell(column, row, CellSpan::default(), area)
+    }
+
+    fn decorate_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        self.decorate_spanned_cell(column, row, CellSpan::default(), has_more, area, row_height)
+    }
+
+    fn prepare_spanned_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        area: render::Area<'p>,
+    ) -> render::Area<'p> {
+        self.prepare_styled_cell(column, row, span, &CellStyle::default(), area)
+    }
+
+    fn decorate_spanned_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        let style = CellStyle::default();
+        self.decorate_styled_cell(column, row, span, &style, has_more, area, row_height)
+    }
+
+    fn prepare_styled_cell<'p>(
+        &self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        style: &CellStyle,
         mut area: render::Area<'p>,
     ) -> render::Area<'p> {
-        let margin = self.line_style.thickness();
         let margins = Margins::trbl(
-            if self.print_top(row) {
-                margin
+            line_thickness(cell_line_style(self.top_line_style(row), style)),
+            line_thickness(cell_line_style(self.right_line_style(column, span), style)),
+            line_thickness(cell_line_style(
+                self.bottom_line_style(row, span, false),
+                style,
+            )),
+            line_thickness(cell_line_style(self.left_line_style(column), style)),
+        );
+        area.add_margins(margins);
+        area
+    }
+
+    fn decorate_styled_cell(
+        &mut self,
+        column: usize,
+        row: usize,
+        span: CellSpan,
+        style: &CellStyle,
+        has_more: bool,
+        area: render::Area<'_>,
+        row_height: Mm,
+    ) -> Mm {
+        let top_line_style = cell_line_style(self.top_line_style(row), style);
+        let bottom_line_style = cell_line_style(self.bottom_line_style(row, span, has_more), style);
+        let left_line_style = cell_line_style(self.left_line_style(column), style);
+        let right_line_style = cell_line_style(self.right_line_style(column, span), style);
+
+        let size = area.size();
+
+        let left = Mm::from(0);
+        let right = size.width;
+        let top = Mm::from(0);
+        let bottom =
+            row_height + line_thickness(bottom_line_style) + line_thickness(top_line_style);
+
+        let mut total_height = row_height;
+
+        // The outer corners of the table are rounded if the line style of the top or bottom
+        // border has a corner radius.
+        let is_first_column = column == 0;
+        let is_last_column = column + span.columns == self.num_columns;
+        let is_top = row == 0;
+        let is_bottom = row + span.rows >= self.num_rows && !has_more;
+        let max_radius = (right - left) / 2.0;
+        let max_radius = max_radius.min((bottom - top) / 2.0).max(Mm(0.0));
+        let corner_radius = |line_style: Option<LineStyle>, is_row_edge: bool| {
+            let line_style = line_style.filter(|_| is_row_edge);
+            let radius = line_style.map(|s| s.corner_radius()).unwrap_or_default();
+            let radius = radius.min(max_radius);
+            let left = left_line_style.filter(|_| is_first_column).map(|_| radius);
+            let right = right_line_style.filter(|_| is_last_column).map(|_| radius);
+            (left.unwrap_or_default(), right.unwrap_or_default())
+        };
+        let (top_left, top_right) = corner_radius(top_line_style, is_top);
+        let (bottom_left, bottom_right) = corner_radius(bottom_line_style, is_bottom);
+
+        let left_x = left + line_thickness(left_line_style) / 2.0;
+        let right_x = right - line_thickness(right_line_style) / 2.0;
+        let draw_horizontal = |line_style: LineStyle, y: Mm, r: (Mm, Mm), direction: f64| {
+            let mut points = Vec::new();
+            if r.0 > Mm(0.0) {
+                points.push(Position::new(left_x, y + r.0 * direction));
+                points.push(Position::new(left_x, y));
             } else {
-                0.into()
-            },
-            if self.print_right(column) {
-                margin
+                points.push(Position::new(left, y));
+            }
+            if r.1 > Mm(0.0) {
+                points.push(Position::new(right_x, y));
+                points.push(Position::new(right_x, y + r.1 * direction));
             } else {
-                0.into()
-            },
-            if self.print_bottom(row, false) {
-                margin
+                points.push(Position::new(right, y));
+            }
+            if r.0 > Mm(0.0) || r.1 > Mm(0.0) {
+                let radius = r.0.max(r.1);
+                area.stroke_path(&rounded_polyline(&points, radius, false), line_style);
             } else {
-                0.into()
-            },
-            if self.print_left(column) {
-                margin
+                area.draw_line(points, line_style);
+            }
+        };
+
+        if let Some(line_style) = top_line_style {
+            let line_offset = line_style.thickness() / 2.0;
+            draw_horizontal(line_style, top + line_offset, (top_left, top_right), 1.0);
+            total_height += line_style.thickness();
+        }
+
+        let top_y = |radius: Mm| {
+            if radius > Mm(0.0) {
+                top + line_thickness(top_line_style) / 2.0 + radius
             } else {
-                0.into()
-            },
-        );
-        area.add_margins(margins);
-        area
+                top
+            }
+        };
+        let bottom_y = |radius: Mm| {
+            if radius > Mm(0.0) {
+                bottom - line_thickness(bottom_line_style) / 2.0 - radius
+            } else {
+                bottom
+            }
+        };
+
+        if let Some(line_style) = right_line_style {
+            let line_offset = line_style.thickness() / 2.0;
+            area.draw_line(
+                vec![
+                    Position::new(right - line_offset, top_y(top_right)),
+                    Position::new(right - line_offset, bottom_y(bottom_right)),
+                ],
+                line_style,
+            );
+        }
+
+        if let Some(line_style) = bottom_line_style {
+            let line_offset = line_style.thickness() / 2.0;
+            draw_horizontal(
+                line_style,
+                bottom - line_offset,
+                (bottom_left, bottom_right),
+                -1.0,
+            );
+            total_height += line_style.thickness();
+        }
+
+        if let Some(line_style) = left_line_style {
+            let line_offset = line_style.thickness() / 2.0;
+            area.draw_line(
+                vec![
+                    Position::new(left + line_offset, top_y(top_left)),
+                    Position::new(left + line_offset, bottom_y(bottom_left)),
+                ],
+                line_style,
+            );
+        }
+
+        if column + span.columns == self.num_columns {
+            self.last_row = Some(row);
+        }
+
+        total_height
+    }
+}
+
+/// Distributes the given width to columns with the given minimum and maximum content widths and
+/// returns the share of every column.
+///
+/// If there is enough space for the maximum widths, the remaining space is distributed
+/// proportionally to the maximum widths.  Otherwise, every column gets its minimum width and the
+/// remaining space is distributed proportionally to the difference between the maximum and the
+/// minimum width.  If the width is smaller than the sum of the minimum widths, the columns are
+/// shrunk proportionally to their minimum widths.
+fn distribute_widths(min_widths: &[Mm], max_widths: &[Mm], width: Mm) -> Vec<f64> {
+    let total = |widths: &[Mm]| widths.iter().fold(Mm::from(0), |sum, width| sum + *width);
+    let (min_total, max_total) = (total(min_widths), total(max_widths));
+    let widths: Vec<_> = if max_total <= width {
+        max_widths.to_vec()
+    } else if min_total < width {
+        let factor = (width - min_total).0 / (max_total - min_total).0;
+        min_widths
+            .iter()
+            .zip(max_widths)
+            .map(|(min_width, max_width)| *min_width + (*max_width - *min_width) * factor)
+            .collect()
+    } else {
+        min_widths.to_vec()
+    };
+
+    let total_width = total(&widths);
+    if total_width > Mm::from(0) {
+        widths.iter().map(|w| w.0 / total_width.0).collect()
+    } else {
+        vec![1.0 / widths.len() as f64; widths.len()]
+    }
+}
+
+/// A row of a table layout.
+///
+/// This is a helper struct for populating a [`TableLayout`][].  After you have added all elements
+/// to the row using [`push_element`][] or [`element`][], you can append the row to the table
+/// layout by calling [`push`][].  To add a cell that spans multiple columns, use
+/// [`push_element_span`][] or [`element_span`][].  To add a cell that spans multiple rows, use
+/// [`push_element_row_span`][] or [`element_row_span`][].  The following rows do not contain
+/// elements for the columns that are covered by this cell.
+///
+/// # Examples
+///
+/// With setters:
+/// ```
+/// use genpdf::elements;
+/// let mut table = elements::TableLayout::new(vec![1, 1]);
+/// let mut row = table.row();
+/// row.push_element(elements::Paragraph::new("Cell 1"));
+/// row.push_element(elements::Paragraph::new("Cell 2"));
+/// row.push().expect("Invalid table row");
+/// ```
+///
+/// Chained:
+/// ```
+/// use genpdf::elements;
+/// let table = elements::TableLayout::new(vec![1, 1])
+///     .row()
+///     .element(elements::Paragraph::new("Cell 1"))
+///     .element(elements::Paragraph::new("Cell 2"))
+///     .push()
+///     .expect("Invalid table row");
+/// ```
+///
+/// With a cell spanning two columns:
+/// ```
+/// use genpdf::elements;
+/// let table = elements::TableLayout::new(vec![1, 1, 1])
+///     .row()
+///     .element_span(elements::Paragraph::new("Cells 1 and 2"), 2)
+///     .element(elements::Paragraph::new("Cell 3"))
+///     .push()
+///     .expect("Invalid table row");
+/// ```
+///
+/// With a cell spanning two rows:
+/// ```
+/// use genpdf::elements;
+/// let mut table = elements::TableLayout::new(vec![1, 1]);
+/// table
+///     .row()
+///     .element_row_span(elements::Paragraph::new("Label"), 2)
+///     .element(elements::Paragraph::new("Value 1"))
+///     .push()
+///     .expect("Invalid table row");
+/// table
+///     .row()
+///     .element(elements::Paragraph::new("Value 2"))
+///     .push()
+///     .expect("Invalid table row");
+/// ```
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`push`]: #method.push
+/// [`push_element`]: #method.push_element
+/// [`element`]: #method.element
+/// [`push_element_span`]: #method.push_element_span
+/// [`element_span`]: #method.element_span
+/// [`push_element_row_span`]: #method.push_element_row_span
+/// [`element_row_span`]: #method.element_row_span
+pub struct TableLayoutRow<'a> {
+    table_layout: &'a mut TableLayout,
+    elements: Vec<Box<dyn Element>>,
+    spans: Vec<CellSpan>,
+    styles: Vec<CellStyle>,
+    factories: Vec<Option<CellFactory>>,
+    background_color: Option<Color>,
+}
+
+impl<'a> TableLayoutRow<'a> {
+    fn new(table_layout: &'a mut TableLayout) -> TableLayoutRow<'a> {
+        TableLayoutRow {
+            table_layout,
+            elements: Vec::new(),
+            spans: Vec::new(),
+            styles: Vec::new(),
+            factories: Vec::new(),
+            background_color: None,
+        }
+    }
+
+    /// Adds the given element to this row.
+    pub fn push_element<E: IntoBoxedElement>(&mut self, element: E) {
+        self.push_element_span(element, 1);
+    }
+
+    /// Adds the given element to this row and returns the row.
+    #[must_use]
+    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
+        self.push_element(element);
+        self
+    }
+
+    /// Adds the given element to this row as a cell that spans the given number of columns.
+    pub fn push_element_span<E: IntoBoxedElement>(&mut self, element: E, span: usize) {
+        self.push_cell(element, CellSpan::new(span, 1));
+    }
+
+    /// Adds the given element to this row as a cell that spans the given number of columns and
+    /// returns the row.
+    #[must_use]
+    pub fn element_span<E: IntoBoxedElement>(mut self, element: E, span: usize) -> Self {
+        self.push_element_span(element, span);
+        self
+    }
+
+    /// Adds the given element to this row as a cell that spans the given number of rows.
+    ///
+    /// The cell also covers the column in the following rows, so these rows must contain one
+    /// element less.  The rows that are connected by cells spanning multiple rows are always
+    /// printed on the same page.
+    pub fn push_element_row_span<E: IntoBoxedElement>(&mut self, element: E, rows: usize) {
+        self.push_cell(element, CellSpan::new(1, rows));
+    }
+
+    /// Adds the given element to this row as a cell that spans the given number of rows and
+    /// returns the row.
+    #[must_use]
+    pub fn element_row_span<E: IntoBoxedElement>(mut self, element: E, rows: usize) -> Self {
+        self.push_element_row_span(element, rows);
+        self
+    }
+
+    /// Adds the given element to this row as a cell with the given span.
+    pub fn push_cell<E: IntoBoxedElement>(&mut self, element: E, span: CellSpan) {
+        self.push_styled_cell(element, span, CellStyle::default());
+    }
+
+    /// Adds the given element to this row as a cell with the given style.
+    pub fn push_element_with_style<E: IntoBoxedElement>(&mut self, element: E, style: CellStyle) {
+        self.push_styled_cell(element, CellSpan::default(), style);
+    }
+
+    /// Adds the given element to this row as a cell with the given style and returns the row.
+    #[must_use]
+    pub fn element_with_style<E: IntoBoxedElement>(mut self, element: E, style: CellStyle) -> Self {
+        self.push_element_with_style(element, style);
+        self
+    }
+
+    /// Adds the given element to this row as a cell with the given span and style.
+    pub fn push_styled_cell<E: IntoBoxedElement>(
+        &mut self,
+        element: E,
+        span: CellSpan,
+        style: CellStyle,
+    ) {
+        self.elements.push(element.into_boxed_element());
+        self.spans.push(span);
+        self.styles.push(style);
+        self.factories.push(None);
+    }
+
+    /// Adds a copy of the given element to this row.
+    ///
+    /// The element is cloned whenever the cell is rendered, so this method can be used for the
+    /// cells of header rows that are repeated on every page, see
+    /// [`TableLayout::set_header_rows`][].
+    ///
+    /// [`TableLayout::set_header_rows`]: struct.TableLayout.html#method.set_header_rows
+    pub fn push_cloned_element<E: Element + Clone + 'static>(&mut self, element: E) {
+        self.push_cloned_cell(element, CellSpan::default());
+    }
+
+    /// Adds a copy of the given element to this row and returns the row.
+    ///
+    /// See [`push_cloned_element`][] for more information.
+    ///
+    /// [`push_cloned_element`]: #method.push_cloned_element
+    #[must_use]
+    pub fn cloned_element<E: Element + Clone + 'static>(mut self, element: E) -> Self {
+        self.push_cloned_element(element);
+        self
+    }
+
+    /// Adds a copy of the given element to this row as a cell with the given span.
+    ///
+    /// See [`push_cloned_element`][] for more information.
+    ///
+    /// [`push_cloned_element`]: #method.push_cloned_element
+    pub fn push_cloned_cell<E: Element + Clone + 'static>(&mut self, element: E, span: CellSpan) {
+        self.elements.push(Box::new(element.clone()));
+        self.spans.push(span);
+        self.styles.push(CellStyle::default());
+        self.factories
+            .push(Some(Box::new(move || Box::new(element.clone()))));
+    }
+
+    /// Sets the background color for this row.
+    pub fn set_background_color(mut self, color: Color) -> Self {
+        self.background_color = Some(color);
+        self
+    }
+
+    /// Tries to append this row to the table.
+    ///
+    /// This method fails if the number of columns spanned by the elements in this row does not
+    /// match the number of columns in the table that are not covered by cells of the previous
+    /// rows, or if an element spans zero columns or rows.
+    pub fn push(self) -> Result<(), Error> {
+        self.table_layout.push_spanned_row(
+            self.elements,
+            self.spans,
+            self.styles,
+            self.factories,
+            self.background_color,
+        )
     }
+}
 
-    fn decorate_cell(
-        &mut self,
-        column: usize,
-        row: usize,
-        has_more: bool,
-        area: render::Area<'_>,
-        row_height: Mm,
-    ) -> Mm {
-        let print_top = self.print_top(row);
-        let print_bottom = self.print_bottom(row, has_more);
-        let print_left = self.print_left(column);
-        let print_right = self.print_right(column);
+impl<'a, E: IntoBoxedElement> iter::Extend<E> for TableLayoutRow<'a> {
+    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
+        for element in iter {
+            self.push_element(element);
+        }
+    }
+}
 
-        let size = area.size();
-        let line_offset = self.line_style.thickness() / 2.0;
+/// A function that creates a new copy of the element of a table cell.
+type CellFactory = Box<dyn Fn() -> Box<dyn Element>>;
 
-        let left = Mm::from(0);
-        let right = size.width;
-        let top = Mm::from(0);
-        let bottom = row_height
-            + if print_bottom {
-                self.line_style.thickness()
-            } else {
-                0.into()
-            }
-            + if print_top {
-                self.line_style.thickness()
-            } else {
-                0.into()
-            };
+/// A function that returns the style of the table row with the given index.
+type RowDecorator = Box<dyn Fn(usize) -> RowStyle>;
 
-        let mut total_height = row_height;
+/// A row of a table layout.
+struct TableRow {
+    /// The elements of the cells.
+    cells: Vec<Box<dyn Element>>,
+    /// The functions that create new copies of the cells that have been added as cloned elements.
+    factories: Vec<Option<CellFactory>>,
+    /// The logical indizes of the first columns of the cells.
+    columns: Vec<usize>,
+    /// The number of columns and rows spanned by the cells.
+    spans: Vec<CellSpan>,
+    /// The styles of the cells.
+    styles: Vec<CellStyle>,
+    background_color: Option<Color>,
+}
 
-        if print_top {
-            area.draw_line(
-                vec![
-                    Position::new(left, top + line_offset),
-                    Position::new(right, top + line_offset),
-                ],
-                self.line_style,
-            );
-            total_height += self.line_style.thickness();
-        }
+/// The position and style of a table cell.
+#[derive(Clone, Copy, Debug)]
+struct CellColumns {
+    /// The logical index of the first column of the cell.
+    column: usize,
+    /// The visual index of the leftmost column of the cell.
+    visual_column: usize,
+    /// The number of columns and rows spanned by the cell.
+    span: CellSpan,
+    /// The padding of the cell.
+    padding: Margins,
+    /// The vertical alignment of the content of the cell.
+    vertical_alignment: VerticalAlignment,
+    /// The style of the cell.
+    style: CellStyle,
+    /// The background color of the cell, taking into account the styles of the row.
+    background_color: Option<Color>,
+    /// The style for the content of the cell that is set by the row decorator.
+    text_style: Style,
+}
 
-        if print_right {
-            area.draw_line(
-                vec![
-                    Position::new(right - line_offset, top),
-                    Position::new(right - line_offset, bottom),
-                ],
-                self.line_style,
-            );
-        }
+impl CellColumns {
+    /// Returns the area of this cell, given the areas of the columns from left to right.
+    fn area<'p>(&self, areas: &[render::Area<'p>]) -> render::Area<'p> {
+        let columns = &areas[self.visual_column..self.visual_column + self.span.columns];
+        let mut area = columns[0].clone();
+        area.set_width(
+            columns
+                .iter()
+                .fold(Mm::from(0), |width, area| width + area.size().width),
+        );
+        area
+    }
 
-        if print_bottom {
-            area.draw_line(
-                vec![
-                    Position::new(left, bottom - line_offset),
-                    Position::new(right, bottom - line_offset),
-                ],
-                self.line_style,
-            );
-            total_height += self.line_style.thickness();
-        }
+    /// Returns the area for the content of this cell, given the area of the cell without the
+    /// borders.
+    fn content_area<'p>(&self, mut area: render::Area<'p>) -> render::Area<'p> {
+        area.add_margins(self.padding);
+        area
+    }
 
-        if print_left {
-            area.draw_line(
-                vec![
-                    Position::new(left + line_offset, top),
-                    Position::new(left + line_offset, bottom),
-                ],
-                self.line_style,
-            );
+    /// Returns the vertical padding of this cell.
+    fn vertical_padding(&self) -> Mm {
+        self.padding.top + self.padding.bottom
+    }
+}
+
+/// The width of a column of a [`TableLayout`][] or of an element of a horizontal
+/// [`LinearLayout`][].
+///
+/// See [`TableLayout::from_column_widths`][] and [`LinearLayout::push_with_width`][].
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`LinearLayout`]: struct.LinearLayout.html
+/// [`TableLayout::from_column_widths`]: struct.TableLayout.html#method.from_column_widths
+/// [`LinearLayout::push_with_width`]: struct.LinearLayout.html#method.push_with_width
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum ColumnWidth {
+    /// The column gets a share of the width that is not used by absolute columns, relative to the
+    /// weights of the other columns.
+    Weight(usize),
+    /// The column has a fixed width.
+    Absolute(Mm),
+}
+
+impl ColumnWidth {
+    fn weight(&self) -> usize {
+        match self {
+            ColumnWidth::Weight(weight) => *weight,
+            ColumnWidth::Absolute(_) => 0,
         }
+    }
 
-        if column + 1 == self.num_columns {
-            self.last_row = Some(row);
+    fn absolute(&self) -> Option<Mm> {
+        match self {
+            ColumnWidth::Weight(_) => None,
+            ColumnWidth::Absolute(width) => Some(*width),
         }
+    }
+}
 
-        total_height
+/// The sizing mode for the columns of a [`TableLayout`][].
+///
+/// See [`TableLayout::set_column_widths`][].
+///
+/// [`TableLayout`]: struct.TableLayout.html
+/// [`TableLayout::set_column_widths`]: struct.TableLayout.html#method.set_column_widths
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ColumnWidths {
+    /// The columns share the width of the table according to the column weights.
+    Weighted,
+    /// The column widths are calculated from the content of the cells.
+    ///
+    /// Similar to the automatic table layout of HTML, every column gets at least the width of its
+    /// widest word and, if there is enough space, the width of its content without line breaks.
+    /// The remaining space is distributed proportionally to these widths.  Columns with an
+    /// absolute width keep their width.
+    Auto,
+}
+
+impl Default for ColumnWidths {
+    fn default() -> ColumnWidths {
+        ColumnWidths::Weighted
     }
 }
 
-/// A row of a table layout.
+/// Arranges elements in columns and rows.
 ///
-/// This is a helper struct for populating a [`TableLayout`][].  After you have added all elements
-/// to the row using [`push_element`][] or [`element`][], you can append the row to the table
-/// layout by calling [`push`][].
+/// This struct can be used to layout arbitrary elements in columns in rows, or to draw typical
+/// tables.  You can customize the cell style by providing a [`CellDecorator`][] implementation.
+/// If you want to print a typical table with borders around the cells, use the
+/// [`FrameCellDecorator`][].
+///
+/// The column widths are determined by the weights that have been set in the constructor.  To
+/// mix weighted columns with columns that have a fixed width, use [`from_column_widths`][].  You
+/// can also calculate the column widths from the content of the cells with
+/// [`set_column_widths`][].  The table always uses the full width of the provided area.  Per
+/// default, the columns are arranged from left to right.  For right-to-left documents, you can
+/// reverse the column order with [`set_right_to_left`][].
+///
+/// Every row must contain one cell per column.  A cell can span multiple columns if it is added
+/// with [`TableLayoutRow::element_span`][].
+///
+/// If the table is continued on the next page, the header rows set with [`set_header_rows`][] are
+/// repeated at the top of the page.
+///
+/// Per default, the text in the cells is wrapped into multiple lines.  You can print the cells of
+/// a column on a single line by setting a wrapping mode for the column with
+/// [`set_column_wrap`][].  To change the wrapping mode of a single cell, apply a style with a
+/// wrapping mode to the cell element, see [`Style::set_wrap`][].
 ///
 /// # Examples
 ///
@@ -1517,6 +7386,7 @@ impl CellDecorator for FrameCellDecorator {
 /// ```
 /// use genpdf::elements;
 /// let mut table = elements::TableLayout::new(vec![1, 1]);
+/// table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
 /// let mut row = table.row();
 /// row.push_element(elements::Paragraph::new("Cell 1"));
 /// row.push_element(elements::Paragraph::new("Cell 2"));
@@ -1534,120 +7404,645 @@ impl CellDecorator for FrameCellDecorator {
 ///     .expect("Invalid table row");
 /// ```
 ///
-/// [`TableLayout`]: struct.TableLayout.html
-/// [`push`]: #method.push
-/// [`push_element`]: #method.push_element
-/// [`element`]: #method.element
-pub struct TableLayoutRow<'a> {
-    table_layout: &'a mut TableLayout,
-    elements: Vec<Box<dyn Element>>,
-    background_color: Option<Color>,
+/// [`CellDecorator`]: trait.CellDecorator.html
+/// [`FrameCellDecorator`]: struct.FrameCellDecorator.html
+/// [`from_column_widths`]: #method.from_column_widths
+/// [`set_column_widths`]: #method.set_column_widths
+/// [`set_column_wrap`]: #method.set_column_wrap
+/// [`set_header_rows`]: #method.set_header_rows
+/// [`set_right_to_left`]: #method.set_right_to_left
+/// [`Style::set_wrap`]: ../style/struct.Style.html#method.set_wrap
+/// [`TableLayoutRow::element_span`]: struct.TableLayoutRow.html#method.element_span
+pub struct TableLayout {
+    columns: Vec<ColumnWidth>,
+    column_widths: ColumnWidths,
+    auto_widths: Option<Vec<f64>>,
+    column_wraps: Vec<Option<Wrap>>,
+    line_spacing: Option<f64>,
+    is_right_to_left: bool,
+    is_fit_to_width: bool,
+    scale: Option<f64>,
+    rows: Vec<TableRow>,
+    covered_rows: Vec<usize>,
+    header_rows: usize,
+    cell_padding: Margins,
+    vertical_alignment: VerticalAlignment,
+    background_opacity: f64,
+    render_idx: usize,
+    is_continued: bool,
+    cell_decorator: Option<Box<dyn CellDecorator>>,
+    row_decorator: Option<RowDecorator>,
 }
 
-impl<'a> TableLayoutRow<'a> {
-    fn new(table_layout: &'a mut TableLayout) -> TableLayoutRow<'a> {
-        TableLayoutRow {
-            table_layout,
-            elements: Vec::new(),
-            background_color: None,
+impl TableLayout {
+    /// Creates a new table layout with the given column weights.
+    ///
+    /// The column weights are used to determine the relative width of the columns.  The number of
+    /// column weights determines the number of columns in the table.
+    pub fn new(column_weights: Vec<usize>) -> TableLayout {
+        TableLayout::from_column_widths(
+            column_weights
+                .into_iter()
+                .map(ColumnWidth::Weight)
+                .collect(),
+        )
+    }
+
+    /// Creates a new table layout with the given column widths.
+    ///
+    /// Columns with an [absolute width][] have a fixed width.  The remaining width of the table
+    /// is shared by the [weighted columns][] according to their weights.  If the table is scaled
+    /// down with [`set_fit_to_width`][], the absolute widths are scaled too.  The number of column
+    /// widths determines the number of columns in the table.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use genpdf::{elements, Mm};
+    /// use elements::ColumnWidth;
+    /// let table = elements::TableLayout::from_column_widths(vec![
+    ///     ColumnWidth::Absolute(Mm::from(40)),
+    ///     ColumnWidth::Weight(1),
+    /// ]);
+    /// ```
+    ///
+    /// [absolute width]: enum.ColumnWidth.html#variant.Absolute
+    /// [weighted columns]: enum.ColumnWidth.html#variant.Weight
+    /// [`set_fit_to_width`]: #method.set_fit_to_width
+    pub fn from_column_widths(columns: Vec<ColumnWidth>) -> TableLayout {
+        TableLayout {
+            column_wraps: vec![None; columns.len()],
+            columns,
+            column_widths: ColumnWidths::default(),
+            auto_widths: None,
+            line_spacing: None,
+            is_right_to_left: false,
+            is_fit_to_width: false,
+            scale: None,
+            rows: Vec::new(),
+            covered_rows: Vec::new(),
+            header_rows: 0,
+            cell_padding: Margins::default(),
+            vertical_alignment: VerticalAlignment::default(),
+            background_opacity: 1.0,
+            render_idx: 0,
+            is_continued: false,
+            cell_decorator: None,
+            row_decorator: None,
+        }
+    }
+
+    /// Sets the cell decorator for this table.
+    pub fn set_cell_decorator(&mut self, decorator: impl CellDecorator + 'static) {
+        self.cell_decorator = Some(Box::from(decorator));
+    }
+
+    /// Sets the row decorator for this table.
+    ///
+    /// The row decorator is called with the index of every row when it is rendered and returns
+    /// the [`RowStyle`][] for the row.  This can be used to style rows depending on their
+    /// position, for example to draw alternating background colors.  Repeated header rows keep
+    /// the index of the original row.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use genpdf::{elements, style};
+    /// let mut table = elements::TableLayout::new(vec![1, 1]);
+    /// table.set_row_decorator(|row| {
+    ///     if row % 2 == 1 {
+    ///         elements::RowStyle::new().with_background_color(style::Color::Greyscale(230))
+    ///     } else {
+    ///         elements::RowStyle::new()
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// [`RowStyle`]: struct.RowStyle.html
+    pub fn set_row_decorator(&mut self, decorator: impl Fn(usize) -> RowStyle + 'static) {
+        self.row_decorator = Some(Box::new(decorator));
+    }
+
+    /// Sets the row decorator for this table and returns the table.
+    ///
+    /// See [`set_row_decorator`][] for more information.
+    ///
+    /// [`set_row_decorator`]: #method.set_row_decorator
+    pub fn with_row_decorator(mut self, decorator: impl Fn(usize) -> RowStyle + 'static) -> Self {
+        self.set_row_decorator(decorator);
+        self
+    }
+
+    /// Sets the sizing mode for the columns of this table.
+    ///
+    /// Per default, the columns share the width of the table according to the column weights that
+    /// have been set in the constructor.  With [`ColumnWidths::Auto`][], the column widths are
+    /// calculated from the content of the cells when the table is rendered for the first time,
+    /// and the column weights only determine the number of columns.
+    ///
+    /// [`ColumnWidths::Auto`]: enum.ColumnWidths.html#variant.Auto
+    pub fn set_column_widths(&mut self, column_widths: ColumnWidths) {
+        self.column_widths = column_widths;
+    }
+
+    /// Sets the sizing mode for the columns of this table and returns the table.
+    ///
+    /// See [`set_column_widths`][] for more information.
+    ///
+    /// [`set_column_widths`]: #method.set_column_widths
+    pub fn with_column_widths(mut self, column_widths: ColumnWidths) -> Self {
+        self.set_column_widths(column_widths);
+        self
+    }
+
+    /// Sets the wrapping mode for the cells in the given column.
+    ///
+    /// If the column index is out of range, this method has no effect.
+    pub fn set_column_wrap(&mut self, column: usize, wrap: Wrap) {
+        if let Some(column_wrap) = self.column_wraps.get_mut(column) {
+            *column_wrap = Some(wrap);
+        }
+    }
+
+    /// Sets the wrapping mode for the cells in the given column and returns the table.
+    ///
+    /// If the column index is out of range, this method has no effect.
+    pub fn with_column_wrap(mut self, column: usize, wrap: Wrap) -> Self {
+        self.set_column_wrap(column, wrap);
+        self
+    }
+
+    /// Sets the line spacing factor for the content of the cells of this table.
+    ///
+    /// This can be used to set a tighter line spacing for table cells than for the document body.
+    /// The row heights are calculated using this line spacing.  Per default, the line spacing of
+    /// the table style is used.
+    pub fn set_line_spacing(&mut self, line_spacing: f64) {
+        self.line_spacing = Some(line_spacing);
+    }
+
+    /// Sets the line spacing factor for the content of the cells of this table and returns the
+    /// table.
+    ///
+    /// This can be used to set a tighter line spacing for table cells than for the document body.
+    /// The row heights are calculated using this line spacing.  Per default, the line spacing of
+    /// the table style is used.
+    pub fn with_line_spacing(mut self, line_spacing: f64) -> Self {
+        self.set_line_spacing(line_spacing);
+        self
+    }
+
+    /// Sets the padding between the borders and the content of the cells of this table.
+    ///
+    /// The padding of a single cell can be changed with [`CellStyle::set_padding`][].  Per
+    /// default, the cells do not have a padding.
+    ///
+    /// [`CellStyle::set_padding`]: struct.CellStyle.html#method.set_padding
+    pub fn set_cell_padding(&mut self, padding: impl Into<Margins>) {
+        self.cell_padding = padding.into();
+    }
+
+    /// Sets the padding between the borders and the content of the cells of this table and
+    /// returns the table.
+    ///
+    /// See [`set_cell_padding`][] for more information.
+    ///
+    /// [`set_cell_padding`]: #method.set_cell_padding
+    pub fn with_cell_padding(mut self, padding: impl Into<Margins>) -> Self {
+        self.set_cell_padding(padding);
+        self
+    }
+
+    /// Sets the vertical alignment of the content of the cells of this table.
+    ///
+    /// Cells that are lower than their row are aligned relative to the tallest cell of the row.
+    /// If a row is split across pages, its cells are top-aligned.  The alignment of a single cell
+    /// can be changed with [`CellStyle::set_vertical_alignment`][].  Per default, the cells are
+    /// top-aligned.
+    ///
+    /// [`CellStyle::set_vertical_alignment`]: struct.CellStyle.html#method.set_vertical_alignment
+    pub fn set_vertical_alignment(&mut self, vertical_alignment: VerticalAlignment) {
+        self.vertical_alignment = vertical_alignment;
+    }
+
+    /// Sets the vertical alignment of the content of the cells of this table and returns the
+    /// table.
+    ///
+    /// See [`set_vertical_alignment`][] for more information.
+    ///
+    /// [`set_vertical_alignment`]: #method.set_vertical_alignment
+    pub fn with_vertical_alignment(mut self, vertical_alignment: VerticalAlignment) -> Self {
+        self.set_vertical_alignment(vertical_alignment);
+        self
+    }
+
+    /// Sets the opacity of the background colors of the cells of this table, from 0 (transparent)
+    /// to 1 (opaque).
+    ///
+    /// This can be used to draw translucent backgrounds so that content below the table, for
+    /// example a watermark, remains visible.  The opacity does not apply to the content and the
+    /// borders of the cells.  Per default, the backgrounds are opaque.
+    pub fn set_background_opacity(&mut self, opacity: f64) {
+        self.background_opacity = opacity.max(0.0).min(1.0);
+    }
+
+    /// Sets the opacity of the background colors of the cells of this table and returns the
+    /// table.
+    ///
+    /// See [`set_background_opacity`][] for more information.
+    ///
+    /// [`set_background_opacity`]: #method.set_background_opacity
+    pub fn with_background_opacity(mut self, opacity: f64) -> Self {
+        self.set_background_opacity(opacity);
+        self
+    }
+
+    /// Sets whether the columns of this table are arranged from right to left.
+    ///
+    /// If this option is set, the first column is the rightmost column of the table.  The column
+    /// weights and the cells of the rows are still given in the logical order, starting with the
+    /// first column.  The cell decorator receives the visual column indices, i. e. the column
+    /// index 0 refers to the leftmost column.  Per default, the columns are arranged from left to
+    /// right.
+    pub fn set_right_to_left(&mut self, is_right_to_left: bool) {
+        self.is_right_to_left = is_right_to_left;
+    }
+
+    /// Sets whether the columns of this table are arranged from right to left and returns the
+    /// table.
+    ///
+    /// See [`set_right_to_left`][] for more information.
+    ///
+    /// [`set_right_to_left`]: #method.set_right_to_left
+    pub fn with_right_to_left(mut self, is_right_to_left: bool) -> Self {
+        self.set_right_to_left(is_right_to_left);
+        self
+    }
+
+    /// Sets the number of header rows of this table.
+    ///
+    /// The first rows of the table are header rows that are repeated at the top of every page if
+    /// the table is continued on the next page.  The repeated rows are rendered with new copies of
+    /// their cells and decorated like their first occurrence.  Therefore the cells of the header
+    /// rows have to be added with [`TableLayoutRow::push_cloned_element`][] or the related
+    /// methods, and they must not span rows that are not header rows.  Otherwise, rendering the
+    /// table fails.  Per default, the table does not have header rows.
+    ///
+    /// [`TableLayoutRow::push_cloned_element`]: struct.TableLayoutRow.html#method.push_cloned_element
+    pub fn set_header_rows(&mut self, header_rows: usize) {
+        self.header_rows = header_rows;
+    }
+
+    /// Sets the number of header rows of this table and returns the table.
+    ///
+    /// See [`set_header_rows`][] for more information.
+    ///
+    /// [`set_header_rows`]: #method.set_header_rows
+    pub fn with_header_rows(mut self, header_rows: usize) -> Self {
+        self.set_header_rows(header_rows);
+        self
+    }
+
+    /// Sets whether this table is scaled down so that its content fits into the columns.
+    ///
+    /// The columns always share the full width of the table according to their weights.  If this
+    /// option is set, the table measures its cells before it is rendered.  If a cell contains a
+    /// word that is wider than its column, or if the content of a single-line column (see
+    /// [`set_column_wrap`][]) is wider than its column, the table is laid out on a wider virtual
+    /// area and scaled down uniformly so that it fits into the available width.  This reduces the
+    /// font size, the line widths and the padding while keeping the column proportions, and the
+    /// text in all cells is wrapped at the scaled column widths.  Per default, the table is not
+    /// scaled and content that does not fit is handled according to the [`Overflow`][] setting.
+    ///
+    /// [`set_column_wrap`]: #method.set_column_wrap
+    /// [`Overflow`]: ../enum.Overflow.html
+    pub fn set_fit_to_width(&mut self, is_fit_to_width: bool) {
+        self.is_fit_to_width = is_fit_to_width;
+    }
+
+    /// Sets whether this table is scaled down so that its content fits into the columns and
+    /// returns the table.
+    ///
+    /// See [`set_fit_to_width`][] for more information.
+    ///
+    /// [`set_fit_to_width`]: #method.set_fit_to_width
+    pub fn with_fit_to_width(mut self, is_fit_to_width: bool) -> Self {
+        self.set_fit_to_width(is_fit_to_width);
+        self
+    }
+
+    /// Calculates the column widths from the content of the cells if [`ColumnWidths::Auto`][] is
+    /// set and the widths have not been calculated yet.
+    ///
+    /// [`ColumnWidths::Auto`]: enum.ColumnWidths.html#variant.Auto
+    fn calculate_auto_widths(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<(), Error> {
+        if self.column_widths == ColumnWidths::Auto && self.auto_widths.is_none() {
+            // Measure the cells with clipped overflows so that words that are wider than the
+            // measuring area do not cause an error
+            let previous_overflow = context.replace_overflow(Overflow::Clip);
+            let widths = self.measure_content_widths(context, area, style);
+            context.replace_overflow(previous_overflow);
+            let (min_widths, max_widths) = widths?;
+            let width = self.relative_width(area.size().width);
+            self.auto_widths = Some(distribute_widths(&min_widths, &max_widths, width));
+        }
+        Ok(())
+    }
+
+    /// Returns the minimum and maximum content widths of the columns.
+    ///
+    /// The minimum width is the width of the widest word, and the maximum width is the width of
+    /// the content without line breaks.  Both include the padding of the cell and the cell
+    /// decorator.  If a cell spans multiple columns and is wider than these columns, the
+    /// difference is distributed evenly to them.  Columns with an absolute width have the content
+    /// width zero.
+    fn measure_content_widths(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<(Vec<Mm>, Vec<Mm>), Error> {
+        let num_columns = self.columns.len();
+        let cell_styles: Vec<_> = (0..num_columns)
+            .map(|i| self.column_style(i, style))
+            .collect();
+        let cell_columns: Vec<_> = (0..self.rows.len()).map(|i| self.cell_columns(i)).collect();
+
+        let mut min_widths = vec![Mm::from(0); num_columns];
+        let mut max_widths = vec![Mm::from(0); num_columns];
+        let mut spanned_cells = Vec::new();
+        for (row_idx, row) in self.rows.iter_mut().enumerate() {
+            for (element, cell) in row.cells.iter_mut().zip(&cell_columns[row_idx]) {
+                let mut cell_area = area.clone();
+                if let Some(decorator) = &self.cell_decorator {
+                    cell_area = decorator.prepare_styled_cell(
+                        cell.visual_column,
+                        row_idx,
+                        cell.span,
+                        &cell.style,
+                        cell_area,
+                    );
+                }
+                cell_area = cell.content_area(cell_area);
+                let padding = area.size().width - cell_area.size().width;
+                let cell_style = cell_styles[cell.column].and(cell.text_style);
+                let (idx, type_name) = (row_idx * num_columns + cell.column, element.type_name());
+                let cell_error = |err: Error| err.with_element(idx, type_name);
+
+                cell_area.set_width(Mm(f64::MAX));
+                let max_width = element
+                    .measure(context, cell_area.clone(), cell_style)
+                    .map_err(cell_error)?
+                    .size
+                    .width;
+                let min_width = if cell_style.wrap() == Wrap::Words {
+                    // In the smallest possible area, every word is printed on a line of its own
+                    cell_area.set_width(Mm(f64::MIN_POSITIVE));
+                    element
+                        .measure(context, cell_area, cell_style)
+                        .map_err(cell_error)?
+                        .size
+                        .width
+                } else {
+                    max_width
+                };
+
+                let (min_width, max_width) = (min_width + padding, max_width + padding);
+                if cell.span.columns == 1 {
+                    min_widths[cell.column] = min_widths[cell.column].max(min_width);
+                    max_widths[cell.column] = max_widths[cell.column].max(max_width);
+                } else {
+                    spanned_cells.push((cell.column, cell.span.columns, min_width, max_width));
+                }
+            }
+        }
+
+        // Absolute columns keep their width, so they do not take part in the distribution
+        let is_relative: Vec<_> = self
+            .columns
+            .iter()
+            .map(|c| c.absolute().is_none())
+            .collect();
+
+        for (column, span, min_width, max_width) in spanned_cells {
+            let absolute = self.columns[column..column + span]
+                .iter()
+                .filter_map(ColumnWidth::absolute)
+                .fold(Mm::from(0), |sum, width| sum + width);
+            let relative_columns = is_relative[column..column + span]
+                .iter()
+                .filter(|is_relative| **is_relative)
+                .count();
+            if relative_columns == 0 {
+                continue;
+            }
+            let bounds = vec![(&mut min_widths, min_width), (&mut max_widths, max_width)];
+            for (widths, width) in bounds {
+                let columns = &mut widths[column..column + span];
+                let total = columns.iter().fold(absolute, |sum, width| sum + *width);
+                if width > total {
+                    let extra = (width - total) / relative_columns as f64;
+                    let relative = is_relative[column..column + span].iter();
+                    for (column_width, _) in columns.iter_mut().zip(relative).filter(|(_, r)| **r) {
+                        *column_width += extra;
+                    }
+                }
+            }
+        }
+        for (column, is_relative) in is_relative.into_iter().enumerate() {
+            if !is_relative {
+                min_widths[column] = Mm::from(0);
+                max_widths[column] = Mm::from(0);
+            }
+        }
+        Ok((min_widths, max_widths))
+    }
+
+    /// Calculates the scale factor for the table if [`set_fit_to_width`][] is enabled and the
+    /// factor has not been calculated yet, and returns it.
+    ///
+    /// [`set_fit_to_width`]: #method.set_fit_to_width
+    fn fit_to_width(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<Option<f64>, Error> {
+        if self.is_fit_to_width && self.scale.is_none() {
+            // Measure the cells with clipped overflows so that we get the width of words that do
+            // not fit into their column instead of an error
+            let previous_overflow = context.replace_overflow(Overflow::Clip);
+            let scale = self.measure_scale(context, area, style);
+            context.replace_overflow(previous_overflow);
+            self.scale = Some(scale?);
+        }
+        Ok(self.scale.filter(|scale| *scale < 1.0))
+    }
+
+    /// Returns the factor that the table has to be scaled with so that all cells fit into their
+    /// columns.
+    fn measure_scale(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+    ) -> Result<f64, Error> {
+        let areas = self.split_columns(area);
+        let cell_styles: Vec<_> = (0..self.columns.len())
+            .map(|i| self.column_style(i, style))
+            .collect();
+        let cell_columns: Vec<_> = (0..self.rows.len()).map(|i| self.cell_columns(i)).collect();
+
+        let mut scale = 1.0f64;
+        for (row_idx, row) in self.rows.iter_mut().enumerate() {
+            for (element, cell) in row.cells.iter_mut().zip(&cell_columns[row_idx]) {
+                let mut cell_area = cell.area(&areas);
+                let column_width = cell_area.size().width;
+                if let Some(decorator) = &self.cell_decorator {
+                    cell_area = decorator.prepare_styled_cell(
+                        cell.visual_column,
+                        row_idx,
+                        cell.span,
+                        &cell.style,
+                        cell_area,
+                    );
+                }
+                cell_area = cell.content_area(cell_area);
+                // The padding of the cell and the cell decorator is scaled together with the
+                // content
+                let padding = column_width - cell_area.size().width;
+                let cell_style = cell_styles[cell.column].and(cell.text_style);
+                if cell_style.wrap() != Wrap::Words {
+                    // Single-line cells are measured without a width limit to get their full width
+                    cell_area.set_width(Mm(f64::MAX));
+                }
+                let width = element.measure(context, cell_area, cell_style)?.size.width;
+                if width + padding > column_width && column_width > Mm(0.0) {
+                    scale = scale.min(column_width.0 / (width + padding).0);
+                }
+            }
         }
+        // Leave some room for rounding errors so that the widest content fits into the scaled
+        // column
+        Ok(if scale < 1.0 { scale * 0.999 } else { scale })
     }
 
-    /// Adds the given element to this row.
-    pub fn push_element<E: IntoBoxedElement>(&mut self, element: E) {
-        self.elements.push(element.into_boxed_element());
+    /// Splits the given area into the areas for the columns, from left to right.
+    fn split_columns<'p>(&self, area: &render::Area<'p>) -> Vec<render::Area<'p>> {
+        let mut widths = self.column_widths(area.size().width);
+        if self.is_right_to_left {
+            widths.reverse();
+        }
+        area.split_widths(&widths)
     }
 
-    /// Adds the given element to this row and returns the row.
-    #[must_use]
-    pub fn element<E: IntoBoxedElement>(mut self, element: E) -> Self {
-        self.push_element(element);
-        self
+    /// Returns the widths of the columns of this table with the given total width, starting with
+    /// the first column.
+    ///
+    /// Columns with an absolute width get their width, and the remaining width is shared by the
+    /// other columns.
+    fn column_widths(&self, width: Mm) -> Vec<Mm> {
+        let width = self.relative_width(width);
+        let relative_widths: Vec<_> = if let Some(auto_widths) = &self.auto_widths {
+            auto_widths.iter().map(|share| width * *share).collect()
+        } else {
+            let total_weight: usize = self.columns.iter().map(ColumnWidth::weight).sum();
+            let factor = if total_weight > 0 {
+                width / total_weight as f64
+            } else {
+                Mm::from(0)
+            };
+            self.columns
+                .iter()
+                .map(|column| factor * column.weight() as f64)
+                .collect()
+        };
+        self.columns
+            .iter()
+            .zip(relative_widths)
+            .map(|(column, relative_width)| column.absolute().unwrap_or(relative_width))
+            .collect()
     }
 
-    /// Sets the background color for this row.
-    pub fn set_background_color(mut self, color: Color) -> Self {
-        self.background_color = Some(color);
-        self
+    /// Returns the width that is left for the columns without an absolute width if the table has
+    /// the given total width.
+    fn relative_width(&self, width: Mm) -> Mm {
+        let absolute = self
+            .columns
+            .iter()
+            .filter_map(ColumnWidth::absolute)
+            .fold(Mm::from(0), |sum, width| sum + width);
+        (width - absolute).max(Mm::from(0))
     }
 
-    /// Tries to append this row to the table.
-    ///
-    /// This method fails if the number of elements in this row does not match the number of
-    /// columns in the table.
-    pub fn push(self) -> Result<(), Error> {
-        self.table_layout
-            .push_row(self.elements, self.background_color)
+    /// Returns the visual index, counting from the left, of the leftmost column of a cell that
+    /// starts at the column with the given index and spans the given number of columns.
+    fn visual_column(&self, column: usize, span: usize) -> usize {
+        if self.is_right_to_left {
+            self.columns.len() - column - span
+        } else {
+            column
+        }
     }
-}
 
-impl<'a, E: IntoBoxedElement> iter::Extend<E> for TableLayoutRow<'a> {
-    fn extend<I: IntoIterator<Item = E>>(&mut self, iter: I) {
-        self.elements
-            .extend(iter.into_iter().map(|e| e.into_boxed_element()))
+    /// Returns the positions of the cells of the given row.
+    fn cell_columns(&self, row_idx: usize) -> Vec<CellColumns> {
+        let row = &self.rows[row_idx];
+        let row_style = self
+            .row_decorator
+            .as_ref()
+            .map(|decorator| decorator(row_idx))
+            .unwrap_or_default();
+        row.columns
+            .iter()
+            .zip(&row.spans)
+            .zip(&row.styles)
+            .map(|((&column, &span), style)| CellColumns {
+                column,
+                visual_column: self.visual_column(column, span.columns),
+                span,
+                padding: style.padding().unwrap_or(self.cell_padding),
+                vertical_alignment: style
+                    .vertical_alignment()
+                    .unwrap_or(self.vertical_alignment),
+                style: *style,
+                background_color: style
+                    .background_color()
+                    .or(row.background_color)
+                    .or(row_style.background_color),
+                text_style: row_style.text_style,
+            })
+            .collect()
     }
-}
-
-/// Arranges elements in columns and rows.
-///
-/// This struct can be used to layout arbitrary elements in columns in rows, or to draw typical
-/// tables.  You can customize the cell style by providing a [`CellDecorator`][] implementation.
-/// If you want to print a typical table with borders around the cells, use the
-/// [`FrameCellDecorator`][].
-///
-/// The column widths are determined by the weights that have been set in the constructor.  The
-/// table always uses the full width of the provided area.
-///
-/// # Examples
-///
-/// With setters:
-/// ```
-/// use genpdf::elements;
-/// let mut table = elements::TableLayout::new(vec![1, 1]);
-/// table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
-/// let mut row = table.row();
-/// row.push_element(elements::Paragraph::new("Cell 1"));
-/// row.push_element(elements::Paragraph::new("Cell 2"));
-/// row.push().expect("Invalid table row");
-/// ```
-///
-/// Chained:
-/// ```
-/// use genpdf::elements;
-/// let table = elements::TableLayout::new(vec![1, 1])
-///     .row()
-///     .element(elements::Paragraph::new("Cell 1"))
-///     .element(elements::Paragraph::new("Cell 2"))
-///     .push()
-///     .expect("Invalid table row");
-/// ```
-///
-/// [`CellDecorator`]: trait.CellDecorator.html
-/// [`FrameCellDecorator`]: struct.FrameCellDecorator.html
-pub struct TableLayout {
-    column_weights: Vec<usize>,
-    rows: Vec<(Vec<Box<dyn Element>>, Option<Color>)>,
-    render_idx: usize,
-    cell_decorator: Option<Box<dyn CellDecorator>>,
-}
 
-impl TableLayout {
-    /// Creates a new table layout with the given column weights.
+    /// Returns the index after the last row of the group of rows starting at the given index.
     ///
-    /// The column weights are used to determine the relative width of the columns.  The number of
-    /// column weights determines the number of columns in the table.
-    pub fn new(column_weights: Vec<usize>) -> TableLayout {
-        TableLayout {
-            column_weights,
-            rows: Vec::new(),
-            render_idx: 0,
-            cell_decorator: None,
+    /// A group consists of all rows that are connected by cells spanning multiple rows.
+    fn row_group_end(&self, start: usize) -> usize {
+        let mut end = start + 1;
+        let mut idx = start;
+        while idx < end && idx < self.rows.len() {
+            for span in &self.rows[idx].spans {
+                end = end.max(idx + span.rows);
+            }
+            idx += 1;
         }
+        end.min(self.rows.len())
     }
 
-    /// Sets the cell decorator for this table.
-    pub fn set_cell_decorator(&mut self, decorator: impl CellDecorator + 'static) {
-        self.cell_decorator = Some(Box::from(decorator));
+    fn column_style(&self, column: usize, mut style: Style) -> Style {
+        if let Some(line_spacing) = self.line_spacing {
+            style.set_line_spacing(line_spacing);
+        }
+        if let Some(wrap) = self.column_wraps.get(column).copied().flatten() {
+            style.set_wrap(wrap);
+        }
+        style
     }
 
     /// Adds a row to this table using the [`TableLayoutRow`][] helper struct.
@@ -1666,19 +8061,409 @@ impl TableLayout {
         row: Vec<Box<dyn Element>>,
         background_color: Option<Color>,
     ) -> Result<(), Error> {
-        if row.len() == self.column_weights.len() {
-            self.rows.push((row, background_color));
-            Ok(())
-        } else {
-            Err(Error::new(
+        let spans = vec![CellSpan::default(); row.len()];
+        let styles = vec![CellStyle::default(); row.len()];
+        let factories = row.iter().map(|_| None).collect();
+        self.push_spanned_row(row, spans, styles, factories, background_color)
+    }
+
+    /// Adds a row with the given spans for the cells to this table.
+    ///
+    /// The cells are placed in the columns that are not covered by cells of the previous rows.
+    fn push_spanned_row(
+        &mut self,
+        cells: Vec<Box<dyn Element>>,
+        spans: Vec<CellSpan>,
+        styles: Vec<CellStyle>,
+        factories: Vec<Option<CellFactory>>,
+        background_color: Option<Color>,
+    ) -> Result<(), Error> {
+        let num_columns = self.columns.len();
+        if spans.iter().any(|span| span.columns == 0 || span.rows == 0) {
+            return Err(Error::new(
+                "Table cells must span at least one column and one row",
+                ErrorKind::InvalidData,
+            ));
+        }
+        let mut covered_rows = self.covered_rows.clone();
+        covered_rows.resize(num_columns, 0);
+        let free_columns = covered_rows.iter().filter(|rows| **rows == 0).count();
+        let spanned_columns: usize = spans.iter().map(|span| span.columns).sum();
+        if spanned_columns != free_columns {
+            return Err(Error::new(
                 format!(
                     "Expected {} elements in table row, received {}",
-                    self.column_weights.len(),
-                    row.len()
+                    free_columns, spanned_columns
                 ),
                 ErrorKind::InvalidData,
-            ))
+            ));
+        }
+
+        let mut columns = Vec::with_capacity(spans.len());
+        let mut column = 0;
+        for span in &spans {
+            while covered_rows[column] > 0 {
+                column += 1;
+            }
+            let end = column + span.columns;
+            if covered_rows[column..end].iter().any(|rows| *rows > 0) {
+                return Err(Error::new(
+                    "Table cell overlaps a cell of a previous row",
+                    ErrorKind::InvalidData,
+                ));
+            }
+            columns.push(column);
+            column = end;
+        }
+
+        for rows in &mut covered_rows {
+            *rows = rows.saturating_sub(1);
+        }
+        for (column, span) in columns.iter().zip(&spans) {
+            for rows in &mut covered_rows[*column..*column + span.columns] {
+                *rows = span.rows - 1;
+            }
+        }
+        self.covered_rows = covered_rows;
+        self.rows.push(TableRow {
+            cells,
+            factories,
+            columns,
+            spans,
+            styles,
+            background_color,
+        });
+        Ok(())
+    }
+
+    fn render_rows(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if !self.is_continued {
+            self.check_header_rows()?;
+        } else if self.header_rows > 0 && self.render_idx >= self.header_rows {
+            if let Some(height) = self.render_header_rows(context, area.clone(), style)? {
+                result.size.height += height;
+                area.add_offset(Position::new(0, height));
+            } else {
+                return Ok(RenderResult {
+                    has_more: true,
+                    ..Default::default()
+                });
+            }
+        }
+        self.is_continued = true;
+
+        let rows_result = self.render_rows_until(context, area, style, self.rows.len())?;
+        if rows_result.has_more && rows_result.size.height == Mm::from(0) {
+            // None of the rows fit on this page
+            result.size = Size::new(0, 0);
+        } else {
+            result.size.width = rows_result.size.width;
+            result.size.height += rows_result.size.height;
+        }
+        result.has_more = rows_result.has_more;
+        Ok(result)
+    }
+
+    /// Renders the rows up to the given index, starting with the current row.
+    fn render_rows_until(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+        end: usize,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        result.size.width = area.size().width;
+        while self.render_idx < end {
+            let group_end = self.row_group_end(self.render_idx);
+            if self.is_single_row(self.render_idx, group_end) {
+                let row_result = self.render_row(context, area.clone(), style)?;
+                result.size.height += row_result.size.height;
+                area.add_offset(Position::new(0, row_result.size.height));
+                if row_result.has_more {
+                    break;
+                }
+                self.render_idx += 1;
+            } else if let Some(height) =
+                self.render_row_group(context, area.clone(), style, group_end)?
+            {
+                result.size.height += height;
+                area.add_offset(Position::new(0, height));
+                self.render_idx = group_end;
+            } else {
+                // The row group is moved to the next page
+                break;
+            }
+        }
+        result.has_more = self.render_idx < end;
+        Ok(result)
+    }
+
+    /// Checks that the header rows can be repeated on every page.
+    fn check_header_rows(&self) -> Result<(), Error> {
+        if self.header_rows > self.rows.len() {
+            return Err(Error::new(
+                format!(
+                    "The table has {} header rows, but only {} rows",
+                    self.header_rows,
+                    self.rows.len()
+                ),
+                ErrorKind::InvalidData,
+            ));
+        }
+        for (idx, row) in self.rows[..self.header_rows].iter().enumerate() {
+            if row.factories.iter().any(Option::is_none) {
+                return Err(Error::new(
+                    "The cells of table header rows must be added as cloned elements",
+                    ErrorKind::InvalidData,
+                ));
+            }
+            if row
+                .spans
+                .iter()
+                .any(|span| idx + span.rows > self.header_rows)
+            {
+                return Err(Error::new(
+                    "The cells of table header rows must not span the following rows",
+                    ErrorKind::InvalidData,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders new copies of the header rows and returns their total height, or `None` if they
+    /// do not fit into the given area.
+    fn render_header_rows(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<Option<Mm>, Error> {
+        // The header rows are rendered with the original row indizes so that they are decorated
+        // like their first occurrence.
+        let mut cells: Vec<Vec<_>> = self.rows[..self.header_rows]
+            .iter()
+            .map(|row| row.factories.iter().flatten().map(|f| f()).collect())
+            .collect();
+        for (row, cells) in self.rows.iter_mut().zip(&mut cells) {
+            mem::swap(&mut row.cells, cells);
+        }
+        if let Some(decorator) = &mut self.cell_decorator {
+            decorator.start_header_rows();
+        }
+
+        let render_idx = mem::replace(&mut self.render_idx, 0);
+        let result = self.render_rows_until(context, area, style, self.header_rows);
+        self.render_idx = render_idx;
+
+        for (row, cells) in self.rows.iter_mut().zip(&mut cells) {
+            mem::swap(&mut row.cells, cells);
+        }
+        let result = result?;
+        Ok(Some(result.size.height).filter(|_| !result.has_more))
+    }
+
+    /// Returns whether the rows in the given range are a single row without cells that span
+    /// multiple rows.
+    fn is_single_row(&self, start: usize, end: usize) -> bool {
+        end == start + 1 && self.rows[start].spans.iter().all(|span| span.rows == 1)
+    }
+
+    fn measure_rows(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        let mut total_height = Mm::from(0);
+
+        let mut start = 0;
+        while start < self.rows.len() {
+            let end = self.row_group_end(start);
+            let heights = self.measure_row_group(context, &area, style, start, end)?;
+            total_height += heights
+                .iter()
+                .fold(Mm::from(0), |sum, (height, _)| sum + *height);
+            start = end;
+        }
+        result.size.height = total_height;
+
+        Ok(result)
+    }
+
+    /// Measures the rows in the given range and returns the content height and the total height,
+    /// including the borders drawn by the cell decorator, of every row.
+    ///
+    /// The height of a cell that spans multiple rows is distributed to the rows it spans.  If the
+    /// spanned rows are not high enough for the content of the cell, the height of its last row is
+    /// increased.
+    fn measure_row_group(
+        &mut self,
+        context: &Context,
+        area: &render::Area<'_>,
+        style: Style,
+        start: usize,
+        end: usize,
+    ) -> Result<Vec<(Mm, Mm)>, Error> {
+        let areas = self.split_columns(area);
+        let cell_styles: Vec<_> = (0..self.columns.len())
+            .map(|i| self.column_style(i, style))
+            .collect();
+        let cell_columns: Vec<_> = (start..end).map(|i| self.cell_columns(i)).collect();
+        let num_columns = self.columns.len();
+
+        let mut heights = vec![(Mm::from(0), Mm::from(0)); end - start];
+        let mut spanned_cells = Vec::new();
+        for (i, row) in self.rows[start..end].iter_mut().enumerate() {
+            let row_idx = start + i;
+            for (element, cell) in row.cells.iter_mut().zip(&cell_columns[i]) {
+                let area = cell.area(&areas);
+                let mut cell_area = area.clone();
+                if let Some(decorator) = &self.cell_decorator {
+                    cell_area = decorator.prepare_styled_cell(
+                        cell.visual_column,
+                        row_idx,
+                        cell.span,
+                        &cell.style,
+                        cell_area,
+                    );
+                }
+                let border = area.size().height - cell_area.size().height;
+                let height = element
+                    .measure(
+                        context,
+                        cell.content_area(cell_area),
+                        cell_styles[cell.column].and(cell.text_style),
+                    )
+                    .map_err(|err| {
+                        err.with_element(row_idx * num_columns + cell.column, element.type_name())
+                    })?
+                    .size
+                    .height
+                    + cell.vertical_padding();
+                let last = (i + cell.span.rows).min(end - start) - 1;
+                if last == i {
+                    heights[i].0 = heights[i].0.max(height);
+                    heights[i].1 = heights[i].1.max(border);
+                } else {
+                    spanned_cells.push((i, last, height, border));
+                }
+            }
+        }
+        for (height, total_height) in &mut heights {
+            *total_height += *height;
+        }
+
+        spanned_cells.sort_by_key(|(_, last, _, _)| *last);
+        for (first, last, height, border) in spanned_cells {
+            let available = heights[first..=last]
+                .iter()
+                .fold(Mm::from(0), |sum, (_, total_height)| sum + *total_height)
+                - border;
+            if height > available {
+                heights[last].0 += height - available;
+                heights[last].1 += height - available;
+            }
+        }
+        Ok(heights)
+    }
+
+    /// Renders the rows in the given range on the current page and returns the total height, or
+    /// `None` if the rows do not fit into the given area.
+    fn render_row_group(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+        end: usize,
+    ) -> Result<Option<Mm>, Error> {
+        let start = self.render_idx;
+        let heights = self.measure_row_group(context, &area, style, start, end)?;
+        let total_height = heights
+            .iter()
+            .fold(Mm::from(0), |sum, (_, total_height)| sum + *total_height);
+        if total_height > area.size().height {
+            return Ok(None);
+        }
+
+        let cell_styles: Vec<_> = (0..self.columns.len())
+            .map(|i| self.column_style(i, style))
+            .collect();
+        let cell_columns: Vec<_> = (start..end).map(|i| self.cell_columns(i)).collect();
+        let num_columns = self.columns.len();
+
+        let mut areas = self.split_columns(&area);
+        for (i, row) in self.rows[start..end].iter_mut().enumerate() {
+            let row_idx = start + i;
+            let mut cells: Vec<_> = row.cells.iter_mut().zip(&cell_columns[i]).collect();
+            // The cells are decorated from left to right.
+            cells.sort_by_key(|(_, cell)| cell.visual_column);
+            for (element, cell) in cells {
+                let last = (i + cell.span.rows).min(end - start) - 1;
+                let cell_height = heights[i..=last]
+                    .iter()
+                    .fold(Mm::from(0), |sum, (_, total_height)| sum + *total_height);
+                let area = cell.area(&areas);
+                let mut cell_area = area.clone();
+                if let Some(decorator) = &self.cell_decorator {
+                    cell_area = decorator.prepare_styled_cell(
+                        cell.visual_column,
+                        row_idx,
+                        cell.span,
+                        &cell.style,
+                        cell_area,
+                    );
+                }
+                let border = area.size().height - cell_area.size().height;
+                let content_height = cell_height - border;
+
+                if let Some(color) = cell.background_color {
+                    let mut fill_area = cell_area.clone();
+                    fill_area.set_height(content_height);
+                    fill_area.with_opacity(self.background_opacity, |area| area.fill_color(color));
+                }
+                let (idx, type_name) = (row_idx * num_columns + cell.column, element.type_name());
+                let cell_error = |err: Error| err.with_element(idx, type_name);
+                let cell_style = cell_styles[cell.column].and(cell.text_style);
+                let mut content_area = cell.content_area(cell_area);
+                if cell.vertical_alignment != VerticalAlignment::Top {
+                    let height = element
+                        .measure(context, content_area.clone(), cell_style)
+                        .map_err(cell_error)?
+                        .size
+                        .height;
+                    let free_space = content_height - cell.vertical_padding() - height;
+                    content_area
+                        .add_offset(Position::new(0, cell.vertical_alignment.offset(free_space)));
+                }
+                element
+                    .render(context, content_area, cell_style)
+                    .map_err(cell_error)?;
+                if let Some(decorator) = &mut self.cell_decorator {
+                    decorator.decorate_styled_cell(
+                        cell.visual_column,
+                        row_idx,
+                        cell.span,
+                        &cell.style,
+                        false,
+                        area,
+                        content_height,
+                    );
+                }
+            }
+            for area in &mut areas {
+                area.add_offset(Position::new(0, heights[i].1));
+            }
         }
+        Ok(Some(total_height))
     }
 
     fn render_row(
@@ -1689,55 +8474,104 @@ impl TableLayout {
     ) -> Result<RenderResult, Error> {
         let mut result = RenderResult::default();
 
-        let areas = area.split_horizontally(&self.column_weights);
+        let areas = self.split_columns(&area);
         let render_idx = self.render_idx;
 
         let mut row_height = Mm::from(0);
-        let (row_elements, background_color) = &mut self.rows[self.render_idx];
-
-        let cell_areas = if let Some(decorator) = &self.cell_decorator {
-            areas
-                .iter()
-                .enumerate()
-                .map(|(i, area)| decorator.prepare_cell(i, render_idx, area.clone()))
-                .collect()
-        } else {
-            areas.clone()
+        let cell_styles: Vec<_> = (0..self.columns.len())
+            .map(|i| self.column_style(i, style))
+            .collect();
+        let cell_columns = self.cell_columns(render_idx);
+        let cell_areas: Vec<_> = cell_columns
+            .iter()
+            .map(|cell| {
+                let area = cell.area(&areas);
+                if let Some(decorator) = &self.cell_decorator {
+                    decorator.prepare_styled_cell(
+                        cell.visual_column,
+                        render_idx,
+                        cell.span,
+                        &cell.style,
+                        area,
+                    )
+                } else {
+                    area
+                }
+            })
+            .collect();
+
+        let row = &mut self.rows[render_idx];
+        let num_columns = self.columns.len();
+        let cell_error = |column: usize, element: &dyn Element| {
+            let idx = render_idx * num_columns + column;
+            let type_name = element.type_name();
+            move |err: Error| err.with_element(idx, type_name)
         };
 
         // Calculate the row height by measuring the elements without rendering them
-        for (area, element) in cell_areas.iter().zip(row_elements.iter_mut()) {
-            let element_result = element.measure(context, area.clone(), style)?;
-            row_height = row_height.max(element_result.size.height);
-        }
-
-        if let Some(_) = background_color {
-            println!("measure row_height: {:?}", row_height);
+        let mut cell_heights = Vec::with_capacity(cell_columns.len());
+        let mut is_split = false;
+        for ((element, cell), area) in row.cells.iter_mut().zip(&cell_columns).zip(&cell_areas) {
+            let element_result = element
+                .measure(
+                    context,
+                    cell.content_area(area.clone()),
+                    cell_styles[cell.column].and(cell.text_style),
+                )
+                .map_err(cell_error(cell.column, element.as_ref()))?;
+            let cell_height = element_result.size.height + cell.vertical_padding();
+            row_height = row_height.max(cell_height);
+            cell_heights.push(cell_height);
+            is_split |= element_result.has_more;
         }
 
-        if let Some(color) = background_color {
-            for area in &cell_areas {
+        for (cell, area) in cell_columns.iter().zip(&cell_areas) {
+            if let Some(color) = cell.background_color {
                 let mut fill_area = area.clone();
                 fill_area.set_height(row_height);
-                fill_area.fill_color(*color);
+                fill_area.with_opacity(self.background_opacity, |area| area.fill_color(color));
             }
         }
 
-        for (area, element) in cell_areas.iter().zip(row_elements.iter_mut()) {
-            let element_result = element.render(context, area.clone(), style)?;
+        for (((element, cell), area), cell_height) in row
+            .cells
+            .iter_mut()
+            .zip(&cell_columns)
+            .zip(&cell_areas)
+            .zip(cell_heights)
+        {
+            let mut content_area = cell.content_area(area.clone());
+            if !is_split {
+                // Rows that are split across pages are always top-aligned
+                let offset = cell.vertical_alignment.offset(row_height - cell_height);
+                content_area.add_offset(Position::new(0, offset));
+            }
+            let element_result = element
+                .render(
+                    context,
+                    content_area,
+                    cell_styles[cell.column].and(cell.text_style),
+                )
+                .map_err(cell_error(cell.column, element.as_ref()))?;
             result.has_more |= element_result.has_more;
-            row_height = row_height.max(element_result.size.height);
+            row_height = row_height.max(element_result.size.height + cell.vertical_padding());
         }
         result.size.height = row_height;
 
-        if let Some(_) = background_color {
-            println!("actual row_height: {:?}", row_height);
-        }
-
         if let Some(decorator) = &mut self.cell_decorator {
-            for (i, area) in areas.into_iter().enumerate() {
-                let height =
-                    decorator.decorate_cell(i, self.render_idx, result.has_more, area, row_height);
+            // The cells are decorated from left to right.
+            let mut cells = cell_columns;
+            cells.sort_by_key(|cell| cell.visual_column);
+            for cell in cells {
+                let height = decorator.decorate_styled_cell(
+                    cell.visual_column,
+                    render_idx,
+                    cell.span,
+                    &cell.style,
+                    result.has_more,
+                    cell.area(&areas),
+                    row_height,
+                );
                 result.size.height = result.size.height.max(height);
             }
         }
@@ -1750,72 +8584,48 @@ impl Element for TableLayout {
     fn render(
         &mut self,
         context: &Context,
-        mut area: render::Area<'_>,
+        area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        if self.column_weights.is_empty() {
-            return Ok(result);
+        if self.columns.is_empty() {
+            return Ok(RenderResult::default());
         }
         if let Some(decorator) = &mut self.cell_decorator {
-            decorator.set_table_size(self.column_weights.len(), self.rows.len());
+            decorator.set_table_size(self.columns.len(), self.rows.len());
         }
-        result.size.width = area.size().width;
-        while self.render_idx < self.rows.len() {
-            let row_result = self.render_row(context, area.clone(), style)?;
-            result.size.height += row_result.size.height;
-            area.add_offset(Position::new(0, row_result.size.height));
-            if row_result.has_more {
-                break;
-            }
-            self.render_idx += 1;
+        self.calculate_auto_widths(context, &area, style)?;
+        if let Some(scale) = self.fit_to_width(context, &area, style)? {
+            let mut result =
+                area.with_scale(scale, |area| self.render_rows(context, area, style))?;
+            result.size = Size::new(result.size.width * scale, result.size.height * scale);
+            Ok(result)
+        } else {
+            self.render_rows(context, area, style)
         }
-        result.has_more = self.render_idx < self.rows.len();
-        Ok(result)
     }
 
     /// Measures the height of the table without rendering it.
     fn measure(
         &mut self,
         context: &Context,
-        area: render::Area<'_>,
+        mut area: render::Area<'_>,
         style: Style,
     ) -> Result<RenderResult, Error> {
-        let mut result = RenderResult::default();
-        let mut total_height = Mm::from(0);
-
-        if self.column_weights.is_empty() {
-            return Ok(result);
+        if self.columns.is_empty() {
+            return Ok(RenderResult::default());
         }
-
         if let Some(decorator) = &mut self.cell_decorator {
-            decorator.set_table_size(self.column_weights.len(), self.rows.len());
+            decorator.set_table_size(self.columns.len(), self.rows.len());
         }
-
-        let areas = area.split_horizontally(&self.column_weights);
-
-        for (row_idx, (row_elements, _)) in self.rows.iter_mut().enumerate() {
-            let mut row_height = Mm::from(0);
-
-            let cell_areas = if let Some(decorator) = &self.cell_decorator {
-                areas
-                    .iter()
-                    .enumerate()
-                    .map(|(i, area)| decorator.prepare_cell(i, row_idx, area.clone()))
-                    .collect()
-            } else {
-                areas.clone()
-            };
-
-            for (area, element) in cell_areas.iter().zip(row_elements.iter_mut()) {
-                let element_result = element.measure(context, area.clone(), style)?;
-                row_height = row_height.max(element_result.size.height);
-            }
-
-            total_height += row_height;
+        self.calculate_auto_widths(context, &area, style)?;
+        if let Some(scale) = self.fit_to_width(context, &area, style)? {
+            let size = area.size();
+            area.set_size(Size::new(size.width / scale, size.height / scale));
+            let mut result = self.measure_rows(context, area, style)?;
+            result.size = Size::new(result.size.width * scale, result.size.height * scale);
+            Ok(result)
+        } else {
+            self.measure_rows(context, area, style)
         }
-        result.size.height = total_height;
-
-        Ok(result)
     }
 }
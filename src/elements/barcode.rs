@@ -0,0 +1,519 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Barcode support for genpdf-rs.
+
+use crate::error::{Error, ErrorKind};
+use crate::render::{self, PathSegment};
+use crate::style::{self, Color};
+use crate::{Alignment, Context, Element, Mm, Position, RenderResult, Size};
+
+/// The bar and space widths of the Code 128 symbols, indexed by their value.
+const CODE128_PATTERNS: [&str; 107] = [
+    "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212",
+    "221213", "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221",
+    "223211", "221132", "221231", "213212", "223112", "312131", "311222", "321122", "321221",
+    "312212", "322112", "322211", "212123", "212321", "232121", "111323", "131123", "131321",
+    "112313", "132113", "132311", "211313", "231113", "231311", "112133", "112331", "132131",
+    "113123", "113321", "133121", "313121", "211331", "231131", "213113", "213311", "213131",
+    "311123", "311321", "331121", "312113", "312311", "332111", "314111", "221411", "431111",
+    "111224", "111422", "121124", "121421", "141122", "141221", "112214", "112412", "122114",
+    "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111", "111242",
+    "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+    "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311",
+    "113141", "114131", "311141", "411131", "211412", "211214", "211232", "2331112",
+];
+
+/// The EAN-13 patterns of the digits in the left-hand odd parity (L) encoding.
+const EAN_PATTERNS: [u8; 10] = [
+    0b0001101, 0b0011001, 0b0010011, 0b0111101, 0b0100011, 0b0110001, 0b0101111, 0b0111011,
+    0b0110111, 0b0001011,
+];
+
+/// The parities (set bits for the even parity G encoding) of the left-hand digits of an EAN-13
+/// code, indexed by the first digit.
+const EAN_PARITIES: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011, 0b011001, 0b011100, 0b010101, 0b010110,
+    0b011010,
+];
+
+/// The default width of a module (the narrowest bar or space).
+const DEFAULT_MODULE_WIDTH: f64 = 0.33;
+
+/// The default height of the bars.
+const DEFAULT_HEIGHT: f64 = 15.0;
+
+/// A linear barcode that is rendered as vector rectangles.
+///
+/// *Only available if the `barcodes` feature is enabled.*
+///
+/// The supported symbologies are [Code 128][`code128`], which can encode all ASCII characters,
+/// and [EAN-13][`ean13`] for product numbers.  Per default, the narrowest bar is 0.33 mm wide,
+/// the bars are 15 mm high, the code is surrounded by a quiet zone of ten modules and it is
+/// left-aligned.  If enabled with [`set_human_readable`][], the encoded text is printed below the
+/// bars with the current style.  A barcode is never split across pages:  If it does not fit on
+/// the current page, it is moved to the next page.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let code = elements::Barcode::code128("RK123456789DE")
+///     .expect("Failed to encode barcode")
+///     .with_module_width(0.4)
+///     .with_height(20)
+///     .with_human_readable(true);
+/// ```
+///
+/// [`code128`]: #method.code128
+/// [`ean13`]: #method.ean13
+/// [`set_human_readable`]: #method.set_human_readable
+#[derive(Clone, Debug)]
+pub struct Barcode {
+    /// The dark modules from left to right.
+    modules: Vec<bool>,
+    text: String,
+    module_width: Mm,
+    height: Mm,
+    quiet_zone: usize,
+    color: Color,
+    alignment: Alignment,
+    is_human_readable: bool,
+    is_rendered: bool,
+}
+
+impl Barcode {
+    fn new(modules: Vec<bool>, text: String) -> Barcode {
+        Barcode {
+            modules,
+            text,
+            module_width: Mm(DEFAULT_MODULE_WIDTH),
+            height: Mm(DEFAULT_HEIGHT),
+            quiet_zone: 10,
+            color: Color::Rgb(0, 0, 0),
+            alignment: Alignment::default(),
+            is_human_readable: false,
+            is_rendered: false,
+        }
+    }
+
+    /// Encodes the given text as a Code 128 barcode.
+    ///
+    /// The code sets A, B and C are selected automatically to get a short code.  Returns an error
+    /// if the text is empty or contains characters that are not ASCII.
+    pub fn code128(text: impl Into<String>) -> Result<Barcode, Error> {
+        let text = text.into();
+        let values = code128_values(text.as_bytes())?;
+        let mut modules = Vec::new();
+        for value in values {
+            let widths = CODE128_PATTERNS[usize::from(value)].bytes();
+            for (i, width) in widths.enumerate() {
+                let is_bar = i % 2 == 0;
+                modules.extend(std::iter::repeat(is_bar).take(usize::from(width - b'0')));
+            }
+        }
+        Ok(Barcode::new(modules, text))
+    }
+
+    /// Encodes the given product number as an EAN-13 barcode.
+    ///
+    /// The number must consist of twelve digits, or of thirteen digits including the check digit.
+    /// The check digit is calculated if it is missing.  Returns an error if the number has a
+    /// different length, contains other characters or if the check digit is wrong.
+    pub fn ean13(number: impl AsRef<str>) -> Result<Barcode, Error> {
+        let number = number.as_ref();
+        let invalid = |msg: &str| {
+            Error::new(
+                format!("Invalid EAN-13 number {}: {}", number, msg),
+                ErrorKind::InvalidData,
+            )
+        };
+        if !number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid("only digits are allowed"));
+        }
+        let mut digits: Vec<u8> = number.bytes().map(|b| b - b'0').collect();
+        let check_digit = ean13_check_digit(&digits[..digits.len().min(12)]);
+        match digits.len() {
+            12 => digits.push(check_digit),
+            13 if digits[12] == check_digit => {}
+            13 => return Err(invalid("wrong check digit")),
+            _ => return Err(invalid("expected 12 or 13 digits")),
+        }
+
+        let mut modules = Vec::with_capacity(95);
+        let mut push = |bits: u8, len: usize| {
+            modules.extend((0..len).rev().map(|i| (bits >> i) & 1 == 1));
+        };
+        push(0b101, 3);
+        let parities = EAN_PARITIES[usize::from(digits[0])];
+        for (i, digit) in digits[1..7].iter().enumerate() {
+            let pattern = EAN_PATTERNS[usize::from(*digit)];
+            if (parities >> (5 - i)) & 1 == 1 {
+                // The G encoding is the reversed R encoding.
+                push((!pattern & 0x7F).reverse_bits() >> 1, 7);
+            } else {
+                push(pattern, 7);
+            }
+        }
+        push(0b01010, 5);
+        for digit in &digits[7..] {
+            push(!EAN_PATTERNS[usize::from(*digit)] & 0x7F, 7);
+        }
+        push(0b101, 3);
+
+        let text = digits
+            .iter()
+            .map(|digit| char::from(digit + b'0'))
+            .collect();
+        Ok(Barcode::new(modules, text))
+    }
+
+    /// Returns the human-readable text of this barcode.
+    ///
+    /// For EAN-13 codes, this includes the check digit.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the number of modules of this barcode, without the quiet zone.
+    pub fn module_count(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Sets the width of a module, the narrowest bar or space.
+    pub fn set_module_width(&mut self, width: impl Into<Mm>) {
+        self.module_width = width.into();
+    }
+
+    /// Sets the width of a module, the narrowest bar or space, and returns the barcode.
+    pub fn with_module_width(mut self, width: impl Into<Mm>) -> Self {
+        self.set_module_width(width);
+        self
+    }
+
+    /// Sets the height of the bars.
+    pub fn set_height(&mut self, height: impl Into<Mm>) {
+        self.height = height.into();
+    }
+
+    /// Sets the height of the bars and returns the barcode.
+    pub fn with_height(mut self, height: impl Into<Mm>) -> Self {
+        self.set_height(height);
+        self
+    }
+
+    /// Sets the width of the quiet zone left and right of the bars in modules.
+    pub fn set_quiet_zone(&mut self, modules: usize) {
+        self.quiet_zone = modules;
+    }
+
+    /// Sets the width of the quiet zone left and right of the bars in modules and returns the
+    /// barcode.
+    pub fn with_quiet_zone(mut self, modules: usize) -> Self {
+        self.set_quiet_zone(modules);
+        self
+    }
+
+    /// Sets the color of the bars.
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    /// Sets the color of the bars and returns the barcode.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.set_color(color);
+        self
+    }
+
+    /// Sets whether the text of the barcode is printed below the bars.
+    pub fn set_human_readable(&mut self, is_human_readable: bool) {
+        self.is_human_readable = is_human_readable;
+    }
+
+    /// Sets whether the text of the barcode is printed below the bars and returns the barcode.
+    pub fn with_human_readable(mut self, is_human_readable: bool) -> Self {
+        self.set_human_readable(is_human_readable);
+        self
+    }
+
+    /// Sets the alignment to use for this barcode.
+    pub fn set_alignment(&mut self, alignment: impl Into<Alignment>) {
+        self.alignment = alignment.into();
+    }
+
+    /// Sets the alignment to use for this barcode and returns it.
+    pub fn with_alignment(mut self, alignment: impl Into<Alignment>) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
+
+    fn size(&self, context: &Context, style: style::Style) -> Size {
+        let modules = self.modules.len() + 2 * self.quiet_zone;
+        let mut height = self.height;
+        if self.is_human_readable {
+            height += style.line_height(&context.font_cache);
+        }
+        Size::new(self.module_width * modules as f64, height)
+    }
+}
+
+impl Element for Barcode {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: style::Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.measure(context, area.clone(), style)?;
+        if result.has_more || self.is_rendered {
+            return Ok(result);
+        }
+
+        area.add_offset(Position::new(
+            offset(self.alignment, area.size().width, result.size.width),
+            0,
+        ));
+        let start = self.module_width * self.quiet_zone as f64;
+        let mut segments = Vec::new();
+        for (first, last) in dark_runs(&self.modules) {
+            let x = start + self.module_width * first as f64;
+            let width = self.module_width * (last - first) as f64;
+            push_rectangle(
+                &mut segments,
+                Position::new(x, 0),
+                Size::new(width, self.height),
+            );
+        }
+        area.fill_path(&segments, self.color, false);
+
+        if self.is_human_readable {
+            let bars_width = self.module_width * self.modules.len() as f64;
+            let text_width = style.str_width(&context.font_cache, &self.text);
+            let x = start + (bars_width - text_width) / 2.0;
+            area.print_str(
+                &context.font_cache,
+                Position::new(x, self.height),
+                style,
+                &self.text,
+            )?;
+        }
+        self.is_rendered = true;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: style::Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let size = self.size(context, style);
+        if size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = size;
+        }
+        Ok(result)
+    }
+}
+
+/// A code set of Code 128.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CodeSet {
+    A,
+    B,
+    C,
+}
+
+impl CodeSet {
+    /// Returns the set A or B, depending on the first character that can only be encoded with one
+    /// of them.
+    fn select(data: &[u8]) -> CodeSet {
+        data.iter()
+            .find_map(|b| match b {
+                0..=31 => Some(CodeSet::A),
+                96..=127 => Some(CodeSet::B),
+                _ => None,
+            })
+            .unwrap_or(CodeSet::B)
+    }
+
+    fn start(self) -> u8 {
+        match self {
+            CodeSet::A => 103,
+            CodeSet::B => 104,
+            CodeSet::C => 105,
+        }
+    }
+
+    fn switch(self) -> u8 {
+        match self {
+            CodeSet::A => 101,
+            CodeSet::B => 100,
+            CodeSet::C => 99,
+        }
+    }
+
+    fn value(self, b: u8) -> Option<u8> {
+        match (self, b) {
+            (CodeSet::A, 0..=31) => Some(b + 64),
+            (CodeSet::A, 32..=95) | (CodeSet::B, 32..=127) => Some(b - 32),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the values of the Code 128 symbols for the given data, including the start code, the
+/// checksum and the stop code.
+fn code128_values(data: &[u8]) -> Result<Vec<u8>, Error> {
+    if data.is_empty() {
+        return Err(Error::new(
+            "Code 128 barcodes cannot be empty",
+            ErrorKind::InvalidData,
+        ));
+    }
+    if let Some(c) = data.iter().find(|b| !b.is_ascii()) {
+        return Err(Error::new(
+            format!(
+                "Code 128 barcodes can only contain ASCII characters, not {:#x}",
+                c
+            ),
+            ErrorKind::InvalidData,
+        ));
+    }
+
+    let mut values = Vec::new();
+    let mut set: Option<CodeSet> = None;
+    let mut i = 0;
+    while i < data.len() {
+        let digits = data[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+        let use_c = if set == Some(CodeSet::C) {
+            digits >= 2
+        } else {
+            // Switch to the set C for an even number of digits, but not before a single pair.
+            digits % 2 == 0 && (digits >= 4 || (digits == 2 && i == 0 && data.len() == 2))
+        };
+        let next = if use_c {
+            CodeSet::C
+        } else {
+            match set {
+                Some(set) if set.value(data[i]).is_some() && set != CodeSet::C => set,
+                _ => CodeSet::select(&data[i..]),
+            }
+        };
+        if set != Some(next) {
+            values.push(if set.is_none() {
+                next.start()
+            } else {
+                next.switch()
+            });
+            set = Some(next);
+        }
+        if next == CodeSet::C {
+            values.push((data[i] - b'0') * 10 + data[i + 1] - b'0');
+            i += 2;
+        } else {
+            values.push(next.value(data[i]).unwrap_or_default());
+            i += 1;
+        }
+    }
+
+    let checksum = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| i.max(1) * usize::from(*value))
+        .sum::<usize>()
+        % 103;
+    values.push(checksum as u8);
+    values.push(106);
+    Ok(values)
+}
+
+/// Calculates the check digit for the first twelve digits of an EAN-13 number.
+fn ean13_check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .enumerate()
+        .map(|(i, digit)| u32::from(*digit) * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+/// Returns the horizontal offset of an element with the given width in an area with the given
+/// width.
+pub(super) fn offset(alignment: Alignment, max_width: Mm, width: Mm) -> Mm {
+    match alignment {
+        Alignment::Left | Alignment::Justify => Mm::default(),
+        Alignment::Center => (max_width - width) / 2.0,
+        Alignment::Right => max_width - width,
+    }
+}
+
+/// Returns the start and end indices of all runs of dark modules.
+pub(super) fn dark_runs(modules: &[bool]) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for (i, &is_dark) in modules.iter().chain(std::iter::once(&false)).enumerate() {
+        match (is_dark, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                runs.push((s, i));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+/// Adds a closed rectangle with the given position and size to the path.
+pub(super) fn push_rectangle(segments: &mut Vec<PathSegment>, position: Position, size: Size) {
+    let right = position.x + size.width;
+    let bottom = position.y + size.height;
+    segments.push(PathSegment::MoveTo(position));
+    segments.push(PathSegment::LineTo(Position::new(right, position.y)));
+    segments.push(PathSegment::LineTo(Position::new(right, bottom)));
+    segments.push(PathSegment::LineTo(Position::new(position.x, bottom)));
+    segments.push(PathSegment::Close);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{code128_values, Barcode};
+
+    #[test]
+    fn test_code128_values() {
+        assert_eq!(
+            vec![104, 33, 34, 35, 1, 106],
+            code128_values(b"ABC").unwrap()
+        );
+        assert_eq!(vec![105, 12, 34, 82, 106], code128_values(b"1234").unwrap());
+        assert_eq!(
+            vec![104, 17, 99, 23, 45, 100, 65, 16, 106],
+            code128_values(b"12345a").unwrap()
+        );
+        assert_eq!(vec![103, 33, 73, 76, 106], code128_values(b"A\t").unwrap());
+        assert!(code128_values(b"").is_err());
+        assert!(code128_values("ä".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_ean13() {
+        let code = Barcode::ean13("400638133393").unwrap();
+        assert_eq!("4006381333931", code.text());
+        assert_eq!(95, code.module_count());
+        let modules: String = code.modules[..17]
+            .iter()
+            .map(|is_dark| if *is_dark { '1' } else { '0' })
+            .collect();
+        // The start guard, the digit 0 in the L encoding and the digit 0 in the G encoding.
+        assert_eq!("10100011010100111", modules);
+        assert!(Barcode::ean13("4006381333932").is_err());
+        assert!(Barcode::ean13("40063813339").is_err());
+        assert!(Barcode::ean13("40063813339a").is_err());
+    }
+}
@@ -0,0 +1,721 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Charts for reports.
+//!
+//! This module provides elements that render data as vector charts:
+//! - [`BarChart`][]: shows the values of one or more [`Series`][] as grouped bars
+//! - [`LineChart`][]: shows the values of one or more [`Series`][] as lines
+//! - [`PieChart`][]: shows the shares of a total as slices of a circle
+//!
+//! The charts have a fixed size.  Their labels and their legend are printed with the current
+//! style, so the font and the font size can be changed by wrapping a chart in a
+//! [`StyledElement`][].  A chart is never split across pages:  If it does not fit on the current
+//! page, it is moved to the next page.
+//!
+//! # Example
+//!
+//! ```
+//! use genpdf::elements::chart;
+//! let chart = chart::BarChart::new((120, 60), vec!["Q1", "Q2", "Q3", "Q4"])
+//!     .with_series(chart::Series::new("2020", vec![12.0, 15.5, 9.0, 20.0]))
+//!     .with_series(chart::Series::new("2021", vec![14.0, 17.0, 13.5, 22.0]));
+//! ```
+//!
+//! [`BarChart`]: struct.BarChart.html
+//! [`LineChart`]: struct.LineChart.html
+//! [`PieChart`]: struct.PieChart.html
+//! [`Series`]: struct.Series.html
+//! [`StyledElement`]: ../struct.StyledElement.html
+
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use crate::error::Error;
+use crate::render::{self, PathSegment};
+use crate::style::{Color, LineStyle, Style};
+use crate::{Context, Element, Mm, Position, RenderResult, Size};
+
+/// The colors that are used for series and slices without an explicit color.
+const PALETTE: [Color; 8] = [
+    Color::Rgb(31, 119, 180),
+    Color::Rgb(255, 127, 14),
+    Color::Rgb(44, 160, 44),
+    Color::Rgb(214, 39, 40),
+    Color::Rgb(148, 103, 189),
+    Color::Rgb(140, 86, 75),
+    Color::Rgb(227, 119, 194),
+    Color::Rgb(127, 127, 127),
+];
+
+/// The gap between labels and the chart.
+const GAP: Mm = Mm(1.5);
+
+/// A named series of values in a [`BarChart`][] or [`LineChart`][].
+///
+/// [`BarChart`]: struct.BarChart.html
+/// [`LineChart`]: struct.LineChart.html
+#[derive(Clone, Debug)]
+pub struct Series {
+    name: String,
+    values: Vec<f64>,
+    color: Option<Color>,
+}
+
+impl Series {
+    /// Creates a new series with the given name and values, one value per category.
+    pub fn new(name: impl Into<String>, values: impl IntoIterator<Item = f64>) -> Series {
+        Series {
+            name: name.into(),
+            values: values.into_iter().collect(),
+            color: None,
+        }
+    }
+
+    /// Sets the color of this series.
+    ///
+    /// Per default, the series is drawn with a color from a built-in palette.
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+
+    /// Sets the color of this series and returns it.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.set_color(color);
+        self
+    }
+}
+
+/// A chart that shows the values of one or more series as grouped vertical bars.
+///
+/// Every category is labeled below the horizontal axis, and the vertical axis is labeled with
+/// evenly spaced values that always include zero.  Per default, the chart has a legend with the
+/// names of the series below the chart.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements::chart;
+/// let chart = chart::BarChart::new((120, 60), vec!["Jan", "Feb", "Mar"])
+///     .with_series(chart::Series::new("Revenue", vec![120.0, 98.5, 143.0]))
+///     .with_legend(false);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BarChart {
+    chart: CategoryChart,
+}
+
+impl BarChart {
+    /// Creates a new bar chart with the given size and categories without any series.
+    pub fn new<S: Into<String>>(
+        size: impl Into<Size>,
+        categories: impl IntoIterator<Item = S>,
+    ) -> BarChart {
+        BarChart {
+            chart: CategoryChart::new(size.into(), categories),
+        }
+    }
+
+    /// Adds the given series to this chart.
+    pub fn push(&mut self, series: Series) {
+        self.chart.series.push(series);
+    }
+
+    /// Adds the given series to this chart and returns the chart.
+    pub fn with_series(mut self, series: Series) -> Self {
+        self.push(series);
+        self
+    }
+
+    /// Sets whether the legend is shown below the chart.
+    pub fn set_legend(&mut self, has_legend: bool) {
+        self.chart.has_legend = has_legend;
+    }
+
+    /// Sets whether the legend is shown below the chart and returns the chart.
+    pub fn with_legend(mut self, has_legend: bool) -> Self {
+        self.set_legend(has_legend);
+        self
+    }
+}
+
+impl Element for BarChart {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.chart.render(context, area, style, true)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        Ok(measure(self.chart.size, self.chart.is_rendered, &area))
+    }
+}
+
+/// A chart that shows the values of one or more series as lines.
+///
+/// The values are placed at the centers of the categories, which are labeled below the
+/// horizontal axis.  The vertical axis is labeled with evenly spaced values that always include
+/// zero.  Per default, the chart has a legend with the names of the series below the chart.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements::chart;
+/// let chart = chart::LineChart::new((120, 60), vec!["Mon", "Tue", "Wed", "Thu", "Fri"])
+///     .with_series(chart::Series::new("Visitors", vec![310.0, 256.0, 402.0, 378.0, 190.0]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct LineChart {
+    chart: CategoryChart,
+}
+
+impl LineChart {
+    /// Creates a new line chart with the given size and categories without any series.
+    pub fn new<S: Into<String>>(
+        size: impl Into<Size>,
+        categories: impl IntoIterator<Item = S>,
+    ) -> LineChart {
+        LineChart {
+            chart: CategoryChart::new(size.into(), categories),
+        }
+    }
+
+    /// Adds the given series to this chart.
+    pub fn push(&mut self, series: Series) {
+        self.chart.series.push(series);
+    }
+
+    /// Adds the given series to this chart and returns the chart.
+    pub fn with_series(mut self, series: Series) -> Self {
+        self.push(series);
+        self
+    }
+
+    /// Sets whether the legend is shown below the chart.
+    pub fn set_legend(&mut self, has_legend: bool) {
+        self.chart.has_legend = has_legend;
+    }
+
+    /// Sets whether the legend is shown below the chart and returns the chart.
+    pub fn with_legend(mut self, has_legend: bool) -> Self {
+        self.set_legend(has_legend);
+        self
+    }
+}
+
+impl Element for LineChart {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        self.chart.render(context, area, style, false)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        Ok(measure(self.chart.size, self.chart.is_rendered, &area))
+    }
+}
+
+/// A chart that shows the shares of a total as slices of a circle.
+///
+/// The slices start at the top and are drawn clockwise.  Slices without a positive value are
+/// ignored.  Per default, the chart has a legend with the labels of the slices and their
+/// percentage below the chart.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements::chart;
+/// let chart = chart::PieChart::new((80, 60))
+///     .with_slice("Rent", 950.0)
+///     .with_slice("Food", 420.0)
+///     .with_slice("Other", 230.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PieChart {
+    size: Size,
+    slices: Vec<(String, f64, Color)>,
+    has_legend: bool,
+    is_rendered: bool,
+}
+
+impl PieChart {
+    /// Creates a new pie chart with the given size without any slices.
+    pub fn new(size: impl Into<Size>) -> PieChart {
+        PieChart {
+            size: size.into(),
+            slices: Vec::new(),
+            has_legend: true,
+            is_rendered: false,
+        }
+    }
+
+    /// Adds a slice with the given label and value to this chart, using a color from a built-in
+    /// palette.
+    pub fn push(&mut self, label: impl Into<String>, value: f64) {
+        let color = PALETTE[self.slices.len() % PALETTE.len()];
+        self.push_with_color(label, value, color);
+    }
+
+    /// Adds a slice with the given label, value and color to this chart.
+    pub fn push_with_color(&mut self, label: impl Into<String>, value: f64, color: Color) {
+        self.slices.push((label.into(), value, color));
+    }
+
+    /// Adds a slice with the given label and value to this chart and returns the chart.
+    pub fn with_slice(mut self, label: impl Into<String>, value: f64) -> Self {
+        self.push(label, value);
+        self
+    }
+
+    /// Sets whether the legend is shown below the chart.
+    pub fn set_legend(&mut self, has_legend: bool) {
+        self.has_legend = has_legend;
+    }
+
+    /// Sets whether the legend is shown below the chart and returns the chart.
+    pub fn with_legend(mut self, has_legend: bool) -> Self {
+        self.set_legend(has_legend);
+        self
+    }
+}
+
+impl Element for PieChart {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let result = measure(self.size, self.is_rendered, &area);
+        if result.has_more || self.is_rendered {
+            return Ok(result);
+        }
+        area.set_size(self.size);
+
+        let slices: Vec<_> = self
+            .slices
+            .iter()
+            .filter(|(_, value, _)| *value > 0.0)
+            .collect();
+        let total: f64 = slices.iter().map(|(_, value, _)| value).sum();
+        let legend: Vec<_> = if self.has_legend {
+            slices
+                .iter()
+                .map(|(label, value, color)| {
+                    let share = format!("{:.0} %", value / total * 100.0);
+                    (format!("{} ({})", label, share), *color)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let legend_height = render_legend(context, &area, style, &legend)?;
+
+        let height = self.size.height - legend_height;
+        let radius = self.size.width.min(height) / 2.0 - GAP;
+        let center = Position::new(self.size.width / 2.0, height / 2.0);
+        let separator = LineStyle::new()
+            .with_thickness(0.3)
+            .with_color(Color::Rgb(255, 255, 255));
+        let mut start = -FRAC_PI_2;
+        for (_, value, color) in &slices {
+            let end = start + value / total * 2.0 * PI;
+            let segments = slice_segments(center, radius, start, end);
+            area.fill_path(&segments, *color, false);
+            if slices.len() > 1 {
+                area.stroke_path(&segments, separator);
+            }
+            start = end;
+        }
+
+        self.is_rendered = true;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: Style,
+    ) -> Result<RenderResult, Error> {
+        Ok(measure(self.size, self.is_rendered, &area))
+    }
+}
+
+/// The common implementation of bar and line charts.
+#[derive(Clone, Debug)]
+struct CategoryChart {
+    size: Size,
+    categories: Vec<String>,
+    series: Vec<Series>,
+    has_legend: bool,
+    is_rendered: bool,
+}
+
+impl CategoryChart {
+    fn new<S: Into<String>>(size: Size, categories: impl IntoIterator<Item = S>) -> Self {
+        CategoryChart {
+            size,
+            categories: categories.into_iter().map(Into::into).collect(),
+            series: Vec::new(),
+            has_legend: true,
+            is_rendered: false,
+        }
+    }
+
+    fn color(&self, idx: usize) -> Color {
+        self.series[idx]
+            .color
+            .unwrap_or(PALETTE[idx % PALETTE.len()])
+    }
+
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: Style,
+        is_bar_chart: bool,
+    ) -> Result<RenderResult, Error> {
+        let result = measure(self.size, self.is_rendered, &area);
+        if result.has_more || self.is_rendered {
+            return Ok(result);
+        }
+        area.set_size(self.size);
+        let font_cache = &context.font_cache;
+        let line_height = style.line_height(font_cache);
+
+        let legend: Vec<_> = if self.has_legend {
+            (0..self.series.len())
+                .map(|i| (self.series[i].name.clone(), self.color(i)))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let legend_height = render_legend(context, &area, style, &legend)?;
+
+        let values = self.series.iter().flat_map(|series| &series.values);
+        let axis = ValueAxis::new(values.copied().filter(|value| value.is_finite()));
+        let labels: Vec<_> = axis.ticks().map(|tick| axis.label(tick)).collect();
+        let label_width = labels
+            .iter()
+            .map(|label| style.str_width(font_cache, label))
+            .fold(Mm(0.0), Mm::max);
+
+        let left = label_width + GAP;
+        let top = line_height / 2.0;
+        let right = self.size.width - GAP;
+        let bottom = self.size.height - legend_height - line_height - GAP;
+        if right <= left || bottom <= top {
+            self.is_rendered = true;
+            return Ok(result);
+        }
+        let y = |value: f64| top + (bottom - top) * ((axis.max - value) / (axis.max - axis.min));
+
+        // Grid lines and value labels
+        let grid_style = LineStyle::new()
+            .with_thickness(0.1)
+            .with_color(Color::Greyscale(200));
+        for (tick, label) in axis.ticks().zip(&labels) {
+            let tick_y = y(tick);
+            area.draw_line(
+                vec![Position::new(left, tick_y), Position::new(right, tick_y)],
+                grid_style,
+            );
+            let x = left - GAP - style.str_width(font_cache, label);
+            print_label(
+                context,
+                &area,
+                style,
+                Position::new(x, tick_y - line_height / 2.0),
+                label,
+            )?;
+        }
+
+        // Category labels
+        let category_width = (right - left) / self.categories.len().max(1) as f64;
+        for (i, category) in self.categories.iter().enumerate() {
+            let center = left + category_width * (i as f64 + 0.5);
+            let x = center - style.str_width(font_cache, category) / 2.0;
+            print_label(
+                context,
+                &area,
+                style,
+                Position::new(x, bottom + GAP),
+                category,
+            )?;
+        }
+
+        // Data
+        let zero = y(0.0);
+        if is_bar_chart {
+            let bar_width = category_width * 0.8 / self.series.len().max(1) as f64;
+            for (i, series) in self.series.iter().enumerate() {
+                let mut segments = Vec::new();
+                for (j, value) in series.values.iter().take(self.categories.len()).enumerate() {
+                    if !value.is_finite() || *value == 0.0 {
+                        continue;
+                    }
+                    let x = left + category_width * (j as f64 + 0.1) + bar_width * i as f64;
+                    let value_y = y(*value);
+                    let bar_top = value_y.min(zero);
+                    let bar_bottom = value_y.max(zero);
+                    segments.push(PathSegment::MoveTo(Position::new(x, bar_top)));
+                    segments.push(PathSegment::LineTo(Position::new(x + bar_width, bar_top)));
+                    segments.push(PathSegment::LineTo(Position::new(
+                        x + bar_width,
+                        bar_bottom,
+                    )));
+                    segments.push(PathSegment::LineTo(Position::new(x, bar_bottom)));
+                    segments.push(PathSegment::Close);
+                }
+                if !segments.is_empty() {
+                    area.fill_path(&segments, self.color(i), false);
+                }
+            }
+        } else {
+            for (i, series) in self.series.iter().enumerate() {
+                let line_style = LineStyle::new()
+                    .with_thickness(0.5)
+                    .with_color(self.color(i));
+                let points: Vec<_> = series
+                    .values
+                    .iter()
+                    .take(self.categories.len())
+                    .enumerate()
+                    .filter(|(_, value)| value.is_finite())
+                    .map(|(j, value)| {
+                        Position::new(left + category_width * (j as f64 + 0.5), y(*value))
+                    })
+                    .collect();
+                if points.len() > 1 {
+                    area.draw_line(points, line_style);
+                }
+            }
+        }
+
+        // Axes
+        let axis_style = LineStyle::new().with_thickness(0.2);
+        area.draw_line(
+            vec![Position::new(left, top), Position::new(left, bottom)],
+            axis_style,
+        );
+        area.draw_line(
+            vec![Position::new(left, zero), Position::new(right, zero)],
+            axis_style,
+        );
+
+        self.is_rendered = true;
+        Ok(result)
+    }
+}
+
+/// Returns the result of measuring a chart with the given size.
+fn measure(size: Size, is_rendered: bool, area: &render::Area<'_>) -> RenderResult {
+    let mut result = RenderResult::default();
+    if !is_rendered {
+        if size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = size;
+        }
+    }
+    result
+}
+
+/// Prints the given label with the given style at the given position.
+///
+/// The label is printed even if it reaches beyond the bottom of the area, for example because of
+/// rounding errors.
+fn print_label(
+    context: &Context,
+    area: &render::Area<'_>,
+    style: Style,
+    position: Position,
+    label: &str,
+) -> Result<(), Error> {
+    let mut area = area.clone();
+    area.add_offset(position);
+    area.set_height(style.line_height(&context.font_cache));
+    area.print_str(&context.font_cache, Position::default(), style, label)?;
+    Ok(())
+}
+
+/// Renders the legend with the given items at the bottom of the area and returns its height.
+///
+/// The items are arranged in rows, separated by a gap.
+fn render_legend(
+    context: &Context,
+    area: &render::Area<'_>,
+    style: Style,
+    items: &[(String, Color)],
+) -> Result<Mm, Error> {
+    if items.is_empty() {
+        return Ok(Mm(0.0));
+    }
+    let font_cache = &context.font_cache;
+    let line_height = style.line_height(font_cache);
+    let marker_size = line_height * 0.6;
+    let spacing = GAP * 2.0;
+
+    let mut rows: Vec<Vec<(Mm, &str, Color)>> = vec![Vec::new()];
+    let mut x = Mm(0.0);
+    for (label, color) in items {
+        let width = marker_size + GAP + style.str_width(font_cache, label);
+        if x > Mm(0.0) && x + width > area.size().width {
+            rows.push(Vec::new());
+            x = Mm(0.0);
+        }
+        if let Some(row) = rows.last_mut() {
+            row.push((x, label, *color));
+        }
+        x += width + spacing;
+    }
+
+    let height = line_height * rows.len() as f64 + GAP;
+    let top = area.size().height - line_height * rows.len() as f64;
+    for (i, row) in rows.iter().enumerate() {
+        let row_width = row
+            .last()
+            .map(|(x, label, _)| *x + marker_size + GAP + style.str_width(font_cache, label))
+            .unwrap_or_default();
+        let offset = (area.size().width - row_width) / 2.0;
+        let y = top + line_height * i as f64;
+        for (x, label, color) in row {
+            let mut marker = area.clone();
+            marker.add_offset(Position::new(
+                offset + *x,
+                y + (line_height - marker_size) / 2.0,
+            ));
+            marker.set_size(Size::new(marker_size, marker_size));
+            marker.fill_color(*color);
+            let position = Position::new(offset + *x + marker_size + GAP, y);
+            print_label(context, area, style, position, label)?;
+        }
+    }
+    Ok(height)
+}
+
+/// Returns the path of a pie slice between the given angles, measured clockwise from the
+/// positive x axis.
+fn slice_segments(center: Position, radius: Mm, start: f64, end: f64) -> Vec<PathSegment> {
+    let point = |angle: f64| {
+        Position::new(
+            center.x + radius * angle.cos(),
+            center.y + radius * angle.sin(),
+        )
+    };
+    let mut segments = vec![
+        PathSegment::MoveTo(center),
+        PathSegment::LineTo(point(start)),
+    ];
+    // Approximate the arc with Bézier curves that span at most a quarter circle.
+    let count = ((end - start) / FRAC_PI_2).ceil().max(1.0);
+    let step = (end - start) / count;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    for i in 0..count as usize {
+        let a0 = start + step * i as f64;
+        let a1 = a0 + step;
+        let p0 = point(a0);
+        let p3 = point(a1);
+        let p1 = Position::new(
+            p0.x - radius * (k * a0.sin()),
+            p0.y + radius * (k * a0.cos()),
+        );
+        let p2 = Position::new(
+            p3.x + radius * (k * a1.sin()),
+            p3.y - radius * (k * a1.cos()),
+        );
+        segments.push(PathSegment::CurveTo(p1, p2, p3));
+    }
+    segments.push(PathSegment::Close);
+    segments
+}
+
+/// A value axis with evenly spaced ticks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ValueAxis {
+    min: f64,
+    max: f64,
+    step: f64,
+}
+
+impl ValueAxis {
+    /// Creates an axis for the given values that always includes zero.
+    fn new(values: impl Iterator<Item = f64>) -> ValueAxis {
+        let (min, max) = values.fold((0.0f64, 0.0f64), |(min, max), value| {
+            (min.min(value), max.max(value))
+        });
+        let max = if max > min { max } else { min + 1.0 };
+        let raw_step = (max - min) / 5.0;
+        let magnitude = 10f64.powf(raw_step.log10().floor());
+        let step = [1.0, 2.0, 5.0, 10.0]
+            .iter()
+            .map(|factor| factor * magnitude)
+            .find(|step| *step >= raw_step)
+            .unwrap_or(10.0 * magnitude);
+        let min = (min / step).floor() * step;
+        let max = ((max / step).ceil() * step).max(min + step);
+        ValueAxis { min, max, step }
+    }
+
+    fn ticks(&self) -> impl Iterator<Item = f64> {
+        let axis = *self;
+        let count = ((axis.max - axis.min) / axis.step).round() as usize;
+        (0..=count).map(move |i| axis.min + axis.step * i as f64)
+    }
+
+    fn label(&self, value: f64) -> String {
+        let decimals = (-self.step.log10().floor()).max(0.0) as usize;
+        let label = format!("{:.*}", decimals, value);
+        // Avoid printing negative zero.
+        if label
+            .trim_start_matches('-')
+            .chars()
+            .all(|c| c == '0' || c == '.')
+        {
+            label.trim_start_matches('-').to_owned()
+        } else {
+            label
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValueAxis;
+
+    #[test]
+    fn test_value_axis() {
+        let axis = |values: &[f64]| {
+            let axis = ValueAxis::new(values.iter().copied());
+            axis.ticks()
+                .map(|tick| axis.label(tick))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["0.0", "0.2", "0.4", "0.6", "0.8", "1.0"], axis(&[]));
+        assert_eq!(
+            vec!["0", "5", "10", "15", "20", "25"],
+            axis(&[12.0, 15.5, 22.0])
+        );
+        assert_eq!(
+            vec!["-20", "0", "20", "40", "60", "80"],
+            axis(&[-10.0, 42.0, 80.0])
+        );
+        assert_eq!(vec!["0.0", "0.2", "0.4", "0.6"], axis(&[0.25, 0.6]));
+    }
+}
@@ -0,0 +1,565 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Interactive form fields.
+//!
+//! This module provides elements that are added to the document as interactive form fields
+//! (AcroForm fields) so that the recipients of the document can fill it in with a PDF viewer:
+//! - [`TextField`][]: a single-line or multi-line text field
+//! - [`CheckBox`][]: a check box with an optional label
+//! - [`RadioButton`][]: a radio button with an optional label, grouped by its name
+//! - [`Dropdown`][]: a dropdown list with a fixed set of options
+//!
+//! Signature fields can be added with [`SignatureLine::set_signature_field`][].
+//!
+//! Every field has a name that identifies it in the form data.  The names must be unique within a
+//! document, except for the radio buttons of a group that share the name of the group.  The
+//! fields are drawn with a border using the current style and are never split across pages.  The
+//! values of the text fields and dropdown lists are displayed by the PDF viewer using Helvetica.
+//! Form fields are not supported in PDF/A documents.
+//!
+//! # Example
+//!
+//! ```
+//! use genpdf::elements::{self, form};
+//! let mut layout = elements::LinearLayout::vertical();
+//! layout.push(form::TextField::new("name", 80).with_value("Jane Doe"));
+//! layout.push(form::CheckBox::new("newsletter").with_label("Subscribe to the newsletter"));
+//! layout.push(form::RadioButton::new("size", "S").with_label("Small"));
+//! layout.push(form::RadioButton::new("size", "L").with_label("Large").with_checked(true));
+//! layout.push(form::Dropdown::new("country", 50, vec!["Germany", "France", "Italy"]));
+//! ```
+//!
+//! [`TextField`]: struct.TextField.html
+//! [`CheckBox`]: struct.CheckBox.html
+//! [`RadioButton`]: struct.RadioButton.html
+//! [`Dropdown`]: struct.Dropdown.html
+//! [`SignatureLine::set_signature_field`]: ../struct.SignatureLine.html#method.set_signature_field
+
+use crate::error::Error;
+use crate::render::{self, FormField, PathSegment};
+use crate::style::{Color, LineStyle, Style, StyledString};
+use crate::{Context, Element, Mm, Position, RenderResult, Size};
+
+use super::Shape;
+
+/// The padding between the border of a text field or a dropdown list and its content.
+const PADDING: Mm = Mm(1.0);
+
+/// The gap between a check box or a radio button and its label.
+const GAP: Mm = Mm(1.5);
+
+/// A text field that can be filled in with a PDF viewer.
+///
+/// The height of the text field is determined by the number of lines and the line height of the
+/// current style.  Per default, the text field has a single line.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements::form;
+/// let field = form::TextField::new("comments", 120)
+///     .with_lines(4)
+///     .with_max_len(500);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TextField {
+    name: String,
+    width: Mm,
+    lines: usize,
+    value: String,
+    max_len: Option<usize>,
+    line_style: LineStyle,
+    is_rendered: bool,
+}
+
+impl TextField {
+    /// Creates a new empty text field with the given name and width.
+    pub fn new(name: impl Into<String>, width: impl Into<Mm>) -> TextField {
+        TextField {
+            name: name.into(),
+            width: width.into(),
+            lines: 1,
+            value: String::new(),
+            max_len: None,
+            line_style: LineStyle::new(),
+            is_rendered: false,
+        }
+    }
+
+    /// Sets the initial value of this text field.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+    }
+
+    /// Sets the initial value of this text field and returns it.
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    /// Sets the number of lines of this text field.
+    ///
+    /// If the number of lines is larger than one, the text field may contain line breaks.
+    pub fn set_lines(&mut self, lines: usize) {
+        self.lines = lines.max(1);
+    }
+
+    /// Sets the number of lines of this text field and returns it.
+    pub fn with_lines(mut self, lines: usize) -> Self {
+        self.set_lines(lines);
+        self
+    }
+
+    /// Sets the maximum number of characters of this text field.
+    pub fn set_max_len(&mut self, max_len: usize) {
+        self.max_len = Some(max_len);
+    }
+
+    /// Sets the maximum number of characters of this text field and returns it.
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.set_max_len(max_len);
+        self
+    }
+
+    /// Sets the line style for the border of this text field.
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.line_style = line_style.into();
+    }
+
+    /// Sets the line style for the border of this text field and returns it.
+    pub fn with_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_line_style(line_style);
+        self
+    }
+
+    fn size(&self, context: &Context, area: &render::Area<'_>, style: Style) -> Size {
+        let line_height = style.line_height(&context.font_cache);
+        Size::new(
+            self.width.min(area.size().width),
+            line_height * self.lines as f64 + PADDING * 2.0,
+        )
+    }
+}
+
+impl Element for TextField {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let size = self.size(context, &area, style);
+        let result = measure(size, self.is_rendered, &area);
+        if result.has_more || self.is_rendered {
+            return Ok(result);
+        }
+
+        Shape::rectangle(Position::default(), size)
+            .with_line_style(self.line_style)
+            .draw(&area);
+        let field = FormField::Text {
+            value: self.value.clone(),
+            multiline: self.lines > 1,
+            max_len: self.max_len,
+        };
+        area.add_form_field(self.name.clone(), field, Position::default(), size);
+        self.is_rendered = true;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let size = self.size(context, &area, style);
+        Ok(measure(size, self.is_rendered, &area))
+    }
+}
+
+/// A check box that can be checked with a PDF viewer.
+///
+/// The size of the check box is determined by the line height of the current style.  The optional
+/// label is printed to the right of the check box.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements::form;
+/// let check_box = form::CheckBox::new("terms")
+///     .with_label("I accept the terms and conditions")
+///     .with_checked(true);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CheckBox {
+    name: String,
+    toggle: Toggle,
+}
+
+impl CheckBox {
+    /// Creates a new unchecked check box with the given name and without a label.
+    pub fn new(name: impl Into<String>) -> CheckBox {
+        CheckBox {
+            name: name.into(),
+            toggle: Toggle::new(),
+        }
+    }
+
+    /// Sets whether this check box is initially checked.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.toggle.checked = checked;
+    }
+
+    /// Sets whether this check box is initially checked and returns it.
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.set_checked(checked);
+        self
+    }
+
+    /// Sets the label that is printed to the right of this check box.
+    pub fn set_label(&mut self, label: impl Into<StyledString>) {
+        self.toggle.label = Some(label.into());
+    }
+
+    /// Sets the label that is printed to the right of this check box and returns it.
+    pub fn with_label(mut self, label: impl Into<StyledString>) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    /// Sets the line style for the border of this check box.
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.toggle.line_style = line_style.into();
+    }
+
+    /// Sets the line style for the border of this check box and returns it.
+    pub fn with_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_line_style(line_style);
+        self
+    }
+}
+
+impl Element for CheckBox {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let field = FormField::CheckBox {
+            checked: self.toggle.checked,
+        };
+        self.toggle.render(context, area, style, &self.name, field)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        Ok(self.toggle.measure(context, &area, style))
+    }
+}
+
+/// A radio button that can be selected with a PDF viewer.
+///
+/// All radio buttons with the same name form a group, and only one radio button of a group can be
+/// selected.  Every radio button of a group should have a different value that is used as the
+/// value of the group if the radio button is selected.  If several radio buttons of a group are
+/// initially checked, only the first one is selected.
+///
+/// The size of the radio button is determined by the line height of the current style.  The
+/// optional label is printed to the right of the radio button.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements::{self, form};
+/// let mut layout = elements::LinearLayout::vertical();
+/// layout.push(form::RadioButton::new("payment", "card").with_label("Credit card"));
+/// layout.push(form::RadioButton::new("payment", "invoice").with_label("Invoice"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RadioButton {
+    group: String,
+    value: String,
+    toggle: Toggle,
+}
+
+impl RadioButton {
+    /// Creates a new unchecked radio button with the given group name and value and without a
+    /// label.
+    pub fn new(group: impl Into<String>, value: impl Into<String>) -> RadioButton {
+        RadioButton {
+            group: group.into(),
+            value: value.into(),
+            toggle: Toggle::new(),
+        }
+    }
+
+    /// Sets whether this radio button is initially selected.
+    pub fn set_checked(&mut self, checked: bool) {
+        self.toggle.checked = checked;
+    }
+
+    /// Sets whether this radio button is initially selected and returns it.
+    pub fn with_checked(mut self, checked: bool) -> Self {
+        self.set_checked(checked);
+        self
+    }
+
+    /// Sets the label that is printed to the right of this radio button.
+    pub fn set_label(&mut self, label: impl Into<StyledString>) {
+        self.toggle.label = Some(label.into());
+    }
+
+    /// Sets the label that is printed to the right of this radio button and returns it.
+    pub fn with_label(mut self, label: impl Into<StyledString>) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    /// Sets the line style for the border of this radio button.
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.toggle.line_style = line_style.into();
+    }
+
+    /// Sets the line style for the border of this radio button and returns it.
+    pub fn with_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_line_style(line_style);
+        self
+    }
+}
+
+impl Element for RadioButton {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let field = FormField::RadioButton {
+            value: self.value.clone(),
+            checked: self.toggle.checked,
+        };
+        self.toggle.render(context, area, style, &self.group, field)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        Ok(self.toggle.measure(context, &area, style))
+    }
+}
+
+/// A dropdown list with a fixed set of options that can be selected with a PDF viewer.
+///
+/// The height of the dropdown list is determined by the line height of the current style.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements::form;
+/// let dropdown = form::Dropdown::new("priority", 40, vec!["Low", "Normal", "High"])
+///     .with_value("Normal");
+/// ```
+#[derive(Clone, Debug)]
+pub struct Dropdown {
+    name: String,
+    width: Mm,
+    options: Vec<String>,
+    value: Option<String>,
+    line_style: LineStyle,
+    is_rendered: bool,
+}
+
+impl Dropdown {
+    /// Creates a new dropdown list with the given name, width and options without a selected
+    /// option.
+    pub fn new<S: Into<String>>(
+        name: impl Into<String>,
+        width: impl Into<Mm>,
+        options: impl IntoIterator<Item = S>,
+    ) -> Dropdown {
+        Dropdown {
+            name: name.into(),
+            width: width.into(),
+            options: options.into_iter().map(Into::into).collect(),
+            value: None,
+            line_style: LineStyle::new(),
+            is_rendered: false,
+        }
+    }
+
+    /// Sets the initially selected option of this dropdown list.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = Some(value.into());
+    }
+
+    /// Sets the initially selected option of this dropdown list and returns it.
+    pub fn with_value(mut self, value: impl Into<String>) -> Self {
+        self.set_value(value);
+        self
+    }
+
+    /// Sets the line style for the border of this dropdown list.
+    pub fn set_line_style(&mut self, line_style: impl Into<LineStyle>) {
+        self.line_style = line_style.into();
+    }
+
+    /// Sets the line style for the border of this dropdown list and returns it.
+    pub fn with_line_style(mut self, line_style: impl Into<LineStyle>) -> Self {
+        self.set_line_style(line_style);
+        self
+    }
+
+    fn size(&self, context: &Context, area: &render::Area<'_>, style: Style) -> Size {
+        let line_height = style.line_height(&context.font_cache);
+        Size::new(
+            self.width.min(area.size().width),
+            line_height + PADDING * 2.0,
+        )
+    }
+}
+
+impl Element for Dropdown {
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let size = self.size(context, &area, style);
+        let result = measure(size, self.is_rendered, &area);
+        if result.has_more || self.is_rendered {
+            return Ok(result);
+        }
+
+        Shape::rectangle(Position::default(), size)
+            .with_line_style(self.line_style)
+            .draw(&area);
+        // A small triangle at the right end indicates the dropdown list.
+        let arrow = (size.height - PADDING * 2.0) * 0.4;
+        let center = Position::new(size.width - PADDING - arrow, size.height / 2.0);
+        let segments = [
+            PathSegment::MoveTo(center + Position::new(arrow * -0.5, arrow * -0.25)),
+            PathSegment::LineTo(center + Position::new(arrow / 2.0, arrow * -0.25)),
+            PathSegment::LineTo(center + Position::new(0, arrow / 4.0)),
+            PathSegment::Close,
+        ];
+        let color = style.color().unwrap_or(Color::Rgb(0, 0, 0));
+        area.fill_path(&segments, color, false);
+        let field = FormField::Dropdown {
+            options: self.options.clone(),
+            value: self.value.clone(),
+        };
+        area.add_form_field(self.name.clone(), field, Position::default(), size);
+        self.is_rendered = true;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+    ) -> Result<RenderResult, Error> {
+        let size = self.size(context, &area, style);
+        Ok(measure(size, self.is_rendered, &area))
+    }
+}
+
+/// The common implementation of [`CheckBox`][] and [`RadioButton`][].
+///
+/// [`CheckBox`]: struct.CheckBox.html
+/// [`RadioButton`]: struct.RadioButton.html
+#[derive(Clone, Debug)]
+struct Toggle {
+    label: Option<StyledString>,
+    checked: bool,
+    line_style: LineStyle,
+    is_rendered: bool,
+}
+
+impl Toggle {
+    fn new() -> Toggle {
+        Toggle {
+            label: None,
+            checked: false,
+            line_style: LineStyle::new(),
+            is_rendered: false,
+        }
+    }
+
+    /// Returns the size of the element and the side length of the box.
+    fn layout(&self, context: &Context, area: &render::Area<'_>, style: Style) -> (Size, Mm) {
+        let line_height = style.line_height(&context.font_cache);
+        let side = line_height * 0.75;
+        let mut width = side;
+        if let Some(label) = &self.label {
+            width += GAP
+                + style
+                    .and(label.style)
+                    .str_width(&context.font_cache, &label.s);
+        }
+        (Size::new(width.min(area.size().width), line_height), side)
+    }
+
+    fn render(
+        &mut self,
+        context: &Context,
+        area: render::Area<'_>,
+        style: Style,
+        name: &str,
+        field: FormField,
+    ) -> Result<RenderResult, Error> {
+        let (size, side) = self.layout(context, &area, style);
+        let result = measure(size, self.is_rendered, &area);
+        if result.has_more || self.is_rendered {
+            return Ok(result);
+        }
+
+        let position = Position::new(0, (size.height - side) / 2.0);
+        let shape = if let FormField::RadioButton { .. } = field {
+            Shape::circle(position + Position::new(side / 2.0, side / 2.0), side / 2.0)
+        } else {
+            Shape::rectangle(position, Size::new(side, side))
+        };
+        shape.with_line_style(self.line_style).draw(&area);
+        area.add_form_field(name, field, position, Size::new(side, side));
+        if let Some(label) = &self.label {
+            area.print_str(
+                &context.font_cache,
+                Position::new(side + GAP, 0),
+                style.and(label.style),
+                &label.s,
+            )?;
+        }
+        self.is_rendered = true;
+        Ok(result)
+    }
+
+    fn measure(&self, context: &Context, area: &render::Area<'_>, style: Style) -> RenderResult {
+        let (size, _) = self.layout(context, area, style);
+        measure(size, self.is_rendered, area)
+    }
+}
+
+/// Returns the result of measuring a form field with the given size.
+fn measure(size: Size, is_rendered: bool, area: &render::Area<'_>) -> RenderResult {
+    let mut result = RenderResult::default();
+    if !is_rendered {
+        if size.height > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = size;
+        }
+    }
+    result
+}
@@ -57,6 +57,12 @@ pub struct Image {
     /// Scaling of the image, default is 1:1.
     scale: Scale,
 
+    /// The target width of the image, overrides the horizontal scale if set.
+    width: Option<Mm>,
+
+    /// The target height of the image, overrides the vertical scale if set.
+    height: Option<Mm>,
+
     /// The number of degrees of clockwise rotation.
     rotation: Rotation,
 
@@ -78,6 +84,8 @@ impl Image {
                 alignment: Alignment::default(),
                 position: None,
                 scale: Scale::default(),
+                width: None,
+                height: None,
                 rotation: Rotation::default(),
                 dpi: None,
             })
@@ -138,6 +146,46 @@ impl Image {
         self
     }
 
+    /// Scales the image to the given width.
+    ///
+    /// If no height is set, the aspect ratio of the image is preserved.  This overrides the scale
+    /// set with [`set_scale`][].
+    ///
+    /// [`set_scale`]: #method.set_scale
+    pub fn set_width(&mut self, width: impl Into<Mm>) {
+        self.width = Some(width.into());
+    }
+
+    /// Scales the image to the given width and returns it.
+    ///
+    /// See [`set_width`][] for more information.
+    ///
+    /// [`set_width`]: #method.set_width
+    pub fn with_width(mut self, width: impl Into<Mm>) -> Self {
+        self.set_width(width);
+        self
+    }
+
+    /// Scales the image to the given height.
+    ///
+    /// If no width is set, the aspect ratio of the image is preserved.  This overrides the scale
+    /// set with [`set_scale`][].
+    ///
+    /// [`set_scale`]: #method.set_scale
+    pub fn set_height(&mut self, height: impl Into<Mm>) {
+        self.height = Some(height.into());
+    }
+
+    /// Scales the image to the given height and returns it.
+    ///
+    /// See [`set_height`][] for more information.
+    ///
+    /// [`set_height`]: #method.set_height
+    pub fn with_height(mut self, height: impl Into<Mm>) -> Self {
+        self.set_height(height);
+        self
+    }
+
     /// Sets the alignment to use for this image.
     pub fn set_alignment(&mut self, alignment: impl Into<Alignment>) {
         self.alignment = alignment.into();
@@ -152,26 +200,45 @@ impl Image {
     /// Determines the offset from left-side based on provided Alignment.
     fn get_offset(&self, width: Mm, max_width: Mm) -> Position {
         let horizontal_offset = match self.alignment {
-            Alignment::Left => Mm::default(),
+            Alignment::Left | Alignment::Justify => Mm::default(),
             Alignment::Center => (max_width - width) / 2.0,
             Alignment::Right => max_width - width,
         };
         Position::new(horizontal_offset, 0)
     }
 
-    /// Calculates a guess for the size of the image based on the dpi/pixel-count/scale.
-    fn get_size(&self) -> Size {
+    /// Calculates the size of the image without scaling based on the dpi/pixel-count.
+    fn get_natural_size(&self) -> Size {
         let mmpi: f64 = 25.4; // millimeters per inch
                               // Assume 300 DPI to be consistent with printpdf.
         let dpi: f64 = self.dpi.unwrap_or(300.0);
         let (px_width, px_height) = self.data.dimensions();
-        let (scale_width, scale_height): (f64, f64) = (self.scale.x, self.scale.y);
         Size::new(
-            mmpi * ((scale_width * px_width as f64) / dpi),
-            mmpi * ((scale_height * px_height as f64) / dpi),
+            mmpi * (px_width as f64 / dpi),
+            mmpi * (px_height as f64 / dpi),
         )
     }
 
+    /// Calculates the scale of the image, taking into account the target width and height.
+    fn get_scale(&self) -> Scale {
+        let natural_size = self.get_natural_size();
+        let scale_x = self.width.map(|width| width.0 / natural_size.width.0);
+        let scale_y = self.height.map(|height| height.0 / natural_size.height.0);
+        match (scale_x, scale_y) {
+            (Some(x), Some(y)) => Scale::new(x, y),
+            (Some(x), None) => Scale::new(x, x),
+            (None, Some(y)) => Scale::new(y, y),
+            (None, None) => self.scale,
+        }
+    }
+
+    /// Calculates a guess for the size of the image based on the dpi/pixel-count/scale.
+    fn get_size(&self) -> Size {
+        let natural_size = self.get_natural_size();
+        let scale = self.get_scale();
+        Size::new(natural_size.width * scale.x, natural_size.height * scale.y)
+    }
+
     /// Sets the clockwise rotation of the image around the bottom left corner.
     pub fn set_clockwise_rotation(&mut self, rotation: impl Into<Rotation>) {
         self.rotation = rotation.into();
@@ -223,7 +290,13 @@ impl Element for Image {
         position += bb_origin;
 
         // Insert/render the image with the overridden/calculated position.
-        area.add_image(&self.data, position, self.scale, self.rotation, self.dpi);
+        area.add_image(
+            &self.data,
+            position,
+            self.get_scale(),
+            self.rotation,
+            self.dpi,
+        );
 
         // Always false as we can't safely do this unless we want to try to do "sub-images".
         // This is technically possible with the `image` package, but it is potentially more
@@ -316,8 +389,8 @@ fn bounding_box_offset_and_size(rotation: &Rotation, size: &Size) -> (Position,
 
 #[cfg(test)]
 mod tests {
-    use super::bounding_box_offset_and_size;
-    use crate::{Position, Rotation, Size};
+    use super::{bounding_box_offset_and_size, Image};
+    use crate::{Position, Rotation, Scale, Size};
     use float_cmp::approx_eq;
 
     macro_rules! assert_approx_eq {
@@ -549,4 +622,20 @@ mod tests {
         test_position(size, 90.0, Position::new(100, 200));
         test_position(size, 180.0, Position::new(200, 0));
     }
+
+    #[test]
+    fn test_target_size() {
+        // 300 x 600 pixels at 300 dpi are 25.4 x 50.8 mm
+        let image = Image::from_dynamic_image(image::DynamicImage::new_rgb8(300, 600)).unwrap();
+        assert_approx_eq!(Size, Size::new(25.4, 50.8), image.get_size());
+
+        let image = image.with_scale(Scale::new(2, 3));
+        assert_approx_eq!(Size, Size::new(50.8, 152.4), image.get_size());
+
+        let image = image.with_width(12.7);
+        assert_approx_eq!(Size, Size::new(12.7, 25.4), image.get_size());
+
+        let image = image.with_height(10);
+        assert_approx_eq!(Size, Size::new(12.7, 10), image.get_size());
+    }
 }
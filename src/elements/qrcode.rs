@@ -0,0 +1,847 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! QR code support for genpdf-rs.
+
+use super::barcode::{dark_runs, offset, push_rectangle};
+use crate::error::{Error, ErrorKind};
+use crate::render;
+use crate::style::{self, Color};
+use crate::{Alignment, Context, Element, Mm, Position, RenderResult, Size};
+
+/// The default size of a module (one square of the code).
+const DEFAULT_MODULE_SIZE: f64 = 0.5;
+
+/// The characters that can be encoded in the alphanumeric mode, ordered by their value.
+const ALPHANUMERIC_CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+/// The number of error correction codewords per block, indexed by the error correction level and
+/// the version.
+const ECC_CODEWORDS_PER_BLOCK: [[u8; 41]; 4] = [
+    [
+        0, 7, 10, 15, 20, 26, 18, 20, 24, 30, 18, 20, 24, 26, 30, 22, 24, 28, 30, 28, 28, 28, 28,
+        30, 30, 26, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28,
+        28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    ],
+    [
+        0, 13, 22, 18, 26, 18, 24, 18, 22, 20, 24, 28, 26, 24, 20, 30, 24, 28, 28, 26, 30, 28, 30,
+        30, 30, 30, 28, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+    [
+        0, 17, 28, 22, 16, 22, 28, 26, 26, 24, 28, 24, 28, 22, 24, 24, 30, 28, 28, 26, 28, 30, 24,
+        30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30, 30,
+    ],
+];
+
+/// The number of error correction blocks, indexed by the error correction level and the version.
+const ECC_BLOCKS: [[u8; 41]; 4] = [
+    [
+        0, 1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 4, 6, 6, 6, 6, 7, 8, 8, 9, 9, 10, 12, 12, 12, 13,
+        14, 15, 16, 17, 18, 19, 19, 20, 21, 22, 24, 25,
+    ],
+    [
+        0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21,
+        23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+    ],
+    [
+        0, 1, 1, 2, 2, 4, 4, 6, 6, 8, 8, 8, 10, 12, 16, 12, 17, 16, 18, 21, 20, 23, 23, 25, 27, 29,
+        34, 34, 35, 38, 40, 43, 45, 48, 51, 53, 56, 59, 62, 65, 68,
+    ],
+    [
+        0, 1, 1, 2, 4, 4, 4, 5, 6, 8, 8, 11, 11, 16, 16, 18, 16, 19, 21, 25, 25, 25, 34, 30, 32,
+        35, 37, 40, 42, 45, 48, 51, 54, 57, 60, 63, 66, 70, 74, 77, 81,
+    ],
+];
+
+/// The error correction level of a [`QrCode`][].
+///
+/// A higher level allows a reader to restore more damaged data, but requires a larger code for
+/// the same data.
+///
+/// [`QrCode`]: struct.QrCode.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QrErrorCorrection {
+    /// About 7 % of the data can be restored.
+    Low,
+    /// About 15 % of the data can be restored.
+    Medium,
+    /// About 25 % of the data can be restored.
+    Quartile,
+    /// About 30 % of the data can be restored.
+    High,
+}
+
+impl Default for QrErrorCorrection {
+    fn default() -> QrErrorCorrection {
+        QrErrorCorrection::Medium
+    }
+}
+
+impl QrErrorCorrection {
+    fn index(self) -> usize {
+        match self {
+            QrErrorCorrection::Low => 0,
+            QrErrorCorrection::Medium => 1,
+            QrErrorCorrection::Quartile => 2,
+            QrErrorCorrection::High => 3,
+        }
+    }
+
+    fn format_bits(self) -> u32 {
+        match self {
+            QrErrorCorrection::Low => 1,
+            QrErrorCorrection::Medium => 0,
+            QrErrorCorrection::Quartile => 3,
+            QrErrorCorrection::High => 2,
+        }
+    }
+}
+
+/// A QR code that is rendered as vector rectangles.
+///
+/// *Only available if the `barcodes` feature is enabled.*
+///
+/// The data is encoded by a built-in encoder in the numeric, alphanumeric or byte mode, whichever
+/// is the most compact for the complete data, using the smallest version (symbol size) that can
+/// hold the data.  Text should be passed as UTF-8.
+///
+/// Per default, every module (one square of the code) is 0.5 mm wide, the code is surrounded by
+/// a quiet zone of four modules and it is left-aligned.  Use [`set_size`][] to set the size of
+/// the complete code including the quiet zone.  A QR code is never split across pages:  If it
+/// does not fit on the current page, it is moved to the next page.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let code = elements::QrCode::new("https://example.org", elements::QrErrorCorrection::Medium)
+///     .expect("Failed to encode QR code")
+///     .with_size(30)
+///     .with_alignment(genpdf::Alignment::Center);
+/// ```
+///
+/// [`set_size`]: #method.set_size
+#[derive(Clone, Debug)]
+pub struct QrCode {
+    version: usize,
+    /// The number of modules per side, without the quiet zone.
+    width: usize,
+    /// The dark modules, row by row.
+    modules: Vec<bool>,
+    quiet_zone: usize,
+    size: Option<Mm>,
+    color: Color,
+    alignment: Alignment,
+    is_rendered: bool,
+}
+
+impl QrCode {
+    /// Encodes the given data with the given error correction level.
+    ///
+    /// Returns an error if the data is too long to fit into a QR code of version 40.
+    pub fn new(
+        data: impl AsRef<[u8]>,
+        error_correction: QrErrorCorrection,
+    ) -> Result<QrCode, Error> {
+        let data = data.as_ref();
+        let mode = Mode::select(data);
+        let payload_bits = mode.payload_bits(data.len());
+        let version = (1..=40)
+            .find(|&version| {
+                let bits = 4 + mode.count_bits(version) + payload_bits;
+                bits <= data_codewords(version, error_correction) * 8
+            })
+            .ok_or_else(|| {
+                Error::new(
+                    format!("The data is too long for a QR code: {} bytes", data.len()),
+                    ErrorKind::InvalidData,
+                )
+            })?;
+
+        let mut bits = BitBuffer::default();
+        bits.push(mode.indicator(), 4);
+        bits.push(data.len() as u32, mode.count_bits(version));
+        mode.encode(data, &mut bits);
+        let codewords = bits.into_codewords(data_codewords(version, error_correction));
+        let codewords = add_error_correction(&codewords, version, error_correction);
+
+        let mut matrix = Matrix::new(version);
+        matrix.draw_function_patterns();
+        matrix.draw_codewords(&codewords);
+        let mask = (0..8)
+            .min_by_key(|&mask| {
+                let mut matrix = matrix.clone();
+                matrix.apply_mask(mask);
+                matrix.draw_format_bits(error_correction, mask);
+                matrix.penalty()
+            })
+            .unwrap_or_default();
+        matrix.apply_mask(mask);
+        matrix.draw_format_bits(error_correction, mask);
+
+        Ok(QrCode {
+            version,
+            width: matrix.width,
+            modules: matrix.modules,
+            quiet_zone: 4,
+            size: None,
+            color: Color::Rgb(0, 0, 0),
+            alignment: Alignment::default(),
+            is_rendered: false,
+        })
+    }
+
+    /// Returns the version of this code, a number between 1 and 40 that determines its number of
+    /// modules.
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Returns the number of modules per side of this code, without the quiet zone.
+    pub fn module_count(&self) -> usize {
+        self.width
+    }
+
+    /// Returns whether the module in the given column and row is dark.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column or the row is not smaller than the [`module_count`][].
+    ///
+    /// [`module_count`]: #method.module_count
+    pub fn is_dark(&self, column: usize, row: usize) -> bool {
+        assert!(column < self.width && row < self.width);
+        self.modules[row * self.width + column]
+    }
+
+    /// Sets the width and the height of the code, including the quiet zone.
+    pub fn set_size(&mut self, size: impl Into<Mm>) {
+        self.size = Some(size.into());
+    }
+
+    /// Sets the width and the height of the code, including the quiet zone, and returns the code.
+    pub fn with_size(mut self, size: impl Into<Mm>) -> Self {
+        self.set_size(size);
+        self
+    }
+
+    /// Sets the width of the quiet zone around the code in modules.
+    ///
+    /// Readers require a quiet zone of four modules, but it can be reduced if the code is
+    /// surrounded by a light area anyway.
+    pub fn set_quiet_zone(&mut self, modules: usize) {
+        self.quiet_zone = modules;
+    }
+
+    /// Sets the width of the quiet zone around the code in modules and returns the code.
+    pub fn with_quiet_zone(mut self, modules: usize) -> Self {
+        self.set_quiet_zone(modules);
+        self
+    }
+
+    /// Sets the color of the dark modules.
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    /// Sets the color of the dark modules and returns the code.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.set_color(color);
+        self
+    }
+
+    /// Sets the alignment to use for this code.
+    pub fn set_alignment(&mut self, alignment: impl Into<Alignment>) {
+        self.alignment = alignment.into();
+    }
+
+    /// Sets the alignment to use for this code and returns it.
+    pub fn with_alignment(mut self, alignment: impl Into<Alignment>) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
+
+    fn total_modules(&self) -> usize {
+        self.width + 2 * self.quiet_zone
+    }
+
+    fn total_size(&self) -> Mm {
+        self.size
+            .unwrap_or_else(|| Mm(DEFAULT_MODULE_SIZE * self.total_modules() as f64))
+    }
+}
+
+impl Element for QrCode {
+    fn render(
+        &mut self,
+        context: &Context,
+        mut area: render::Area<'_>,
+        style: style::Style,
+    ) -> Result<RenderResult, Error> {
+        let result = self.measure(context, area.clone(), style)?;
+        if result.has_more || self.is_rendered {
+            return Ok(result);
+        }
+
+        let size = self.total_size();
+        area.add_offset(Position::new(
+            offset(self.alignment, area.size().width, size),
+            0,
+        ));
+        let module = size / self.total_modules() as f64;
+        let mut segments = Vec::new();
+        for row in 0..self.width {
+            let modules = &self.modules[row * self.width..(row + 1) * self.width];
+            let y = module * (row + self.quiet_zone) as f64;
+            for (start, end) in dark_runs(modules) {
+                let x = module * (start + self.quiet_zone) as f64;
+                let width = module * (end - start) as f64;
+                push_rectangle(&mut segments, Position::new(x, y), Size::new(width, module));
+            }
+        }
+        if !segments.is_empty() {
+            area.fill_path(&segments, self.color, false);
+        }
+        self.is_rendered = true;
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: style::Style,
+    ) -> Result<RenderResult, Error> {
+        let mut result = RenderResult::default();
+        if self.is_rendered {
+            return Ok(result);
+        }
+        let size = self.total_size();
+        if size > area.size().height {
+            result.has_more = true;
+        } else {
+            result.size = Size::new(size, size);
+        }
+        Ok(result)
+    }
+}
+
+/// The encoding mode of the data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Mode {
+    Numeric,
+    Alphanumeric,
+    Byte,
+}
+
+impl Mode {
+    fn select(data: &[u8]) -> Mode {
+        if data.is_empty() {
+            Mode::Byte
+        } else if data.iter().all(u8::is_ascii_digit) {
+            Mode::Numeric
+        } else if data.iter().all(|b| ALPHANUMERIC_CHARSET.contains(b)) {
+            Mode::Alphanumeric
+        } else {
+            Mode::Byte
+        }
+    }
+
+    fn indicator(self) -> u32 {
+        match self {
+            Mode::Numeric => 0x1,
+            Mode::Alphanumeric => 0x2,
+            Mode::Byte => 0x4,
+        }
+    }
+
+    fn count_bits(self, version: usize) -> usize {
+        let idx = match version {
+            1..=9 => 0,
+            10..=26 => 1,
+            _ => 2,
+        };
+        match self {
+            Mode::Numeric => [10, 12, 14][idx],
+            Mode::Alphanumeric => [9, 11, 13][idx],
+            Mode::Byte => [8, 16, 16][idx],
+        }
+    }
+
+    fn payload_bits(self, len: usize) -> usize {
+        match self {
+            Mode::Numeric => len / 3 * 10 + [0, 4, 7][len % 3],
+            Mode::Alphanumeric => len / 2 * 11 + len % 2 * 6,
+            Mode::Byte => len * 8,
+        }
+    }
+
+    fn encode(self, data: &[u8], bits: &mut BitBuffer) {
+        match self {
+            Mode::Numeric => {
+                for chunk in data.chunks(3) {
+                    let value = chunk
+                        .iter()
+                        .fold(0, |value, digit| value * 10 + u32::from(digit - b'0'));
+                    bits.push(value, chunk.len() * 3 + 1);
+                }
+            }
+            Mode::Alphanumeric => {
+                let value = |b: &u8| {
+                    ALPHANUMERIC_CHARSET
+                        .iter()
+                        .position(|c| c == b)
+                        .unwrap_or_default() as u32
+                };
+                for chunk in data.chunks(2) {
+                    if let [a, b] = chunk {
+                        bits.push(value(a) * 45 + value(b), 11);
+                    } else {
+                        bits.push(value(&chunk[0]), 6);
+                    }
+                }
+            }
+            Mode::Byte => {
+                for b in data {
+                    bits.push(u32::from(*b), 8);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct BitBuffer(Vec<bool>);
+
+impl BitBuffer {
+    fn push(&mut self, value: u32, len: usize) {
+        self.0.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+    }
+
+    /// Terminates and pads the data to the given number of codewords.
+    fn into_codewords(mut self, count: usize) -> Vec<u8> {
+        let capacity = count * 8;
+        let terminator = (capacity - self.0.len()).min(4);
+        self.push(0, terminator);
+        let padding = (8 - self.0.len() % 8) % 8;
+        self.push(0, padding);
+        let mut codewords: Vec<u8> = self
+            .0
+            .chunks(8)
+            .map(|byte| {
+                byte.iter()
+                    .fold(0, |value, &bit| value << 1 | u8::from(bit))
+            })
+            .collect();
+        for pad in [0xEC, 0x11].iter().cycle() {
+            if codewords.len() >= count {
+                break;
+            }
+            codewords.push(*pad);
+        }
+        codewords
+    }
+}
+
+/// Returns the number of modules that can be used for data and error correction codewords.
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let alignments = version / 7 + 2;
+        result -= (25 * alignments - 10) * alignments - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: usize, error_correction: QrErrorCorrection) -> usize {
+    let level = error_correction.index();
+    raw_data_modules(version) / 8
+        - usize::from(ECC_CODEWORDS_PER_BLOCK[level][version])
+            * usize::from(ECC_BLOCKS[level][version])
+}
+
+/// Splits the data into blocks, adds the error correction codewords and interleaves the blocks.
+fn add_error_correction(
+    data: &[u8],
+    version: usize,
+    error_correction: QrErrorCorrection,
+) -> Vec<u8> {
+    let level = error_correction.index();
+    let block_count = usize::from(ECC_BLOCKS[level][version]);
+    let ecc_len = usize::from(ECC_CODEWORDS_PER_BLOCK[level][version]);
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = block_count - raw_codewords % block_count;
+    let short_block_len = raw_codewords / block_count;
+
+    let divisor = reed_solomon_divisor(ecc_len);
+    let mut blocks = Vec::with_capacity(block_count);
+    let mut start = 0;
+    for i in 0..block_count {
+        let data_len = short_block_len - ecc_len + usize::from(i >= short_blocks);
+        let block_data = &data[start..start + data_len];
+        start += data_len;
+        blocks.push((block_data, reed_solomon_remainder(block_data, &divisor)));
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..=short_block_len - ecc_len {
+        for (data, _) in &blocks {
+            if let Some(b) = data.get(i) {
+                result.push(*b);
+            }
+        }
+    }
+    for i in 0..ecc_len {
+        for (_, ecc) in &blocks {
+            result.push(ecc[i]);
+        }
+    }
+    result
+}
+
+/// Multiplies two elements of the Galois field GF(2^8) with the polynomial 0x11D.
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= u16::from((y >> i) & 1) * u16::from(x);
+    }
+    z as u8
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for b in data {
+        let factor = b ^ result.remove(0);
+        result.push(0);
+        for (x, y) in result.iter_mut().zip(divisor) {
+            *x ^= gf_multiply(*y, factor);
+        }
+    }
+    result
+}
+
+/// The module matrix of a QR code during its construction.
+#[derive(Clone, Debug)]
+struct Matrix {
+    version: usize,
+    width: usize,
+    modules: Vec<bool>,
+    is_function: Vec<bool>,
+}
+
+impl Matrix {
+    fn new(version: usize) -> Matrix {
+        let width = version * 4 + 17;
+        Matrix {
+            version,
+            width,
+            modules: vec![false; width * width],
+            is_function: vec![false; width * width],
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.width + x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, is_dark: bool) {
+        self.modules[y * self.width + x] = is_dark;
+        self.is_function[y * self.width + x] = true;
+    }
+
+    fn draw_function_patterns(&mut self) {
+        let width = self.width;
+        for i in 0..width {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+
+        self.draw_finder_pattern(3, 3);
+        self.draw_finder_pattern(width - 4, 3);
+        self.draw_finder_pattern(3, width - 4);
+
+        let positions = self.alignment_positions();
+        let last = positions.len().saturating_sub(1);
+        for (i, &x) in positions.iter().enumerate() {
+            for (j, &y) in positions.iter().enumerate() {
+                let is_finder = (i == 0 && (j == 0 || j == last)) || (i == last && j == 0);
+                if !is_finder {
+                    self.draw_alignment_pattern(x, y);
+                }
+            }
+        }
+
+        // Reserve the format information area, it is drawn after selecting the mask.
+        self.draw_format_bits(QrErrorCorrection::Low, 0);
+        self.draw_version();
+    }
+
+    fn draw_finder_pattern(&mut self, x: usize, y: usize) {
+        for dy in -4i32..=4 {
+            for dx in -4i32..=4 {
+                let (xx, yy) = (x as i32 + dx, y as i32 + dy);
+                if (0..self.width as i32).contains(&xx) && (0..self.width as i32).contains(&yy) {
+                    let distance = dx.abs().max(dy.abs());
+                    self.set_function(xx as usize, yy as usize, distance != 2 && distance != 4);
+                }
+            }
+        }
+    }
+
+    fn draw_alignment_pattern(&mut self, x: usize, y: usize) {
+        for dy in -2i32..=2 {
+            for dx in -2i32..=2 {
+                let (xx, yy) = ((x as i32 + dx) as usize, (y as i32 + dy) as usize);
+                self.set_function(xx, yy, dx.abs().max(dy.abs()) != 1);
+            }
+        }
+    }
+
+    fn alignment_positions(&self) -> Vec<usize> {
+        if self.version == 1 {
+            return Vec::new();
+        }
+        let count = self.version / 7 + 2;
+        let step = if self.version == 32 {
+            26
+        } else {
+            (self.version * 4 + count * 2 + 1) / (count * 2 - 2) * 2
+        };
+        let mut positions: Vec<_> = (0..count - 1).map(|i| self.width - 7 - i * step).collect();
+        positions.push(6);
+        positions.reverse();
+        positions
+    }
+
+    fn draw_format_bits(&mut self, error_correction: QrErrorCorrection, mask: usize) {
+        let data = error_correction.format_bits() << 3 | mask as u32;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+
+        let width = self.width;
+        for i in 0..6 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+
+        for i in 0..8 {
+            self.set_function(width - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, width - 15 + i, bit(i));
+        }
+        self.set_function(8, width - 8, true);
+    }
+
+    fn draw_version(&mut self) {
+        if self.version < 7 {
+            return;
+        }
+        let mut remainder = self.version as u32;
+        for _ in 0..12 {
+            remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+        }
+        let bits = (self.version as u32) << 12 | remainder;
+        for i in 0..18 {
+            let is_dark = (bits >> i) & 1 == 1;
+            let a = self.width - 11 + i % 3;
+            let b = i / 3;
+            self.set_function(a, b, is_dark);
+            self.set_function(b, a, is_dark);
+        }
+    }
+
+    /// Draws the codewords in the zigzag pattern from the bottom right corner.
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let width = self.width;
+        let mut i = 0;
+        let mut right = width - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..width {
+                for j in 0..2 {
+                    let x = right - j;
+                    let is_upward = (right + 1) & 2 == 0;
+                    let y = if is_upward {
+                        width - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.is_function[y * width + x] && i < codewords.len() * 8 {
+                        self.modules[y * width + x] = (codewords[i / 8] >> (7 - i % 8)) & 1 == 1;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.width {
+            for x in 0..self.width {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                let idx = y * self.width + x;
+                if invert && !self.is_function[idx] {
+                    self.modules[idx] = !self.modules[idx];
+                }
+            }
+        }
+    }
+
+    /// Calculates the penalty score of the matrix as defined in the QR code specification.
+    fn penalty(&self) -> usize {
+        let width = self.width;
+        let mut penalty = 0;
+
+        let lines = (0..width).flat_map(|i| {
+            let row: Vec<_> = (0..width).map(|x| self.get(x, i)).collect();
+            let column: Vec<_> = (0..width).map(|y| self.get(i, y)).collect();
+            vec![row, column]
+        });
+        for line in lines {
+            let mut run = 1;
+            for i in 1..=width {
+                if i < width && line[i] == line[i - 1] {
+                    run += 1;
+                } else {
+                    if run >= 5 {
+                        penalty += run - 2;
+                    }
+                    run = 1;
+                }
+            }
+
+            const FINDER: [bool; 11] = [
+                true, false, true, true, true, false, true, false, false, false, false,
+            ];
+            for window in line.windows(11) {
+                if window == FINDER || window.iter().rev().eq(FINDER.iter()) {
+                    penalty += 40;
+                }
+            }
+        }
+
+        for y in 0..width - 1 {
+            for x in 0..width - 1 {
+                let color = self.get(x, y);
+                if color == self.get(x + 1, y)
+                    && color == self.get(x, y + 1)
+                    && color == self.get(x + 1, y + 1)
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        let total = width * width;
+        let dark = self.modules.iter().filter(|is_dark| **is_dark).count();
+        let deviation = (dark * 20).max(total * 10) - (dark * 20).min(total * 10);
+        penalty += deviation / total * 10;
+
+        penalty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{add_error_correction, BitBuffer, Matrix, Mode, QrCode, QrErrorCorrection};
+
+    #[test]
+    fn test_data_codewords() {
+        let data = b"HELLO WORLD";
+        let mode = Mode::select(data);
+        assert_eq!(Mode::Alphanumeric, mode);
+        let mut bits = BitBuffer::default();
+        bits.push(mode.indicator(), 4);
+        bits.push(data.len() as u32, mode.count_bits(1));
+        mode.encode(data, &mut bits);
+        let codewords = bits.into_codewords(16);
+        assert_eq!(
+            vec![32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17],
+            codewords
+        );
+        assert_eq!(
+            vec![196, 35, 39, 119, 235, 215, 231, 226, 93, 23],
+            add_error_correction(&codewords, 1, QrErrorCorrection::Medium)[16..].to_vec()
+        );
+    }
+
+    #[test]
+    fn test_format_bits() {
+        let mut matrix = Matrix::new(1);
+        matrix.draw_format_bits(QrErrorCorrection::Low, 4);
+        let bits: Vec<_> = (0..6)
+            .chain(7..9)
+            .map(|y| matrix.get(8, y))
+            .chain([7, 5, 4, 3, 2, 1, 0].iter().map(|&x| matrix.get(x, 8)))
+            .map(u8::from)
+            .collect();
+        // The format bits 110011000101111 for the level L and the mask 4, starting with the
+        // least significant bit.
+        assert_eq!(vec![1, 1, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1], bits);
+    }
+
+    #[test]
+    fn test_version() {
+        let version =
+            |data: &str, error_correction| QrCode::new(data, error_correction).unwrap().version();
+        assert_eq!(1, version("01234567", QrErrorCorrection::Medium));
+        assert_eq!(1, version("HELLO WORLD", QrErrorCorrection::Quartile));
+        assert_eq!(2, version("HELLO WORLD", QrErrorCorrection::High));
+        assert_eq!(40, version(&"a".repeat(2953), QrErrorCorrection::Low));
+        assert!(QrCode::new("a".repeat(2954), QrErrorCorrection::Low).is_err());
+        assert_eq!(
+            21,
+            QrCode::new("", QrErrorCorrection::High)
+                .unwrap()
+                .module_count()
+        );
+    }
+}
@@ -0,0 +1,1213 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! SVG support for genpdf-rs.
+
+use std::f64::consts::{FRAC_PI_2, PI};
+use std::fs;
+use std::io;
+use std::path;
+use std::str;
+
+use crate::error::{Context as _, Error, ErrorKind};
+use crate::markup;
+use crate::render::{self, PathSegment};
+use crate::style::{self, Color, LineStyle};
+use crate::{Alignment, Context, Element, Mm, Position, RenderResult, Scale, Size};
+
+/// The number of millimeters per SVG user unit (CSS pixel).
+const MM_PER_PX: f64 = 25.4 / 96.0;
+
+/// A vector image in the SVG format.
+///
+/// *Only available if the `svg` feature is enabled.*
+///
+/// The image is parsed by a small built-in parser and rendered as PDF drawing operations, so it
+/// keeps its full resolution at every scale.  The image is scaled and aligned like an
+/// [`Image`][]:  Per default, it is rendered with its natural size, given by the `width` and
+/// `height` attributes of the `svg` element, and left-aligned.
+///
+/// # Supported Features
+///
+/// - the shapes `path`, `rect`, `circle`, `ellipse`, `line`, `polyline` and `polygon`
+/// - groups (`g`) and the `transform` attribute
+/// - the `viewBox` and `preserveAspectRatio` attributes of the `svg` element
+/// - the properties `fill`, `fill-opacity`, `fill-rule`, `stroke`, `stroke-opacity`,
+///   `stroke-width`, `stroke-dasharray`, `opacity`, `display` and `visibility`, set as
+///   attributes or in the `style` attribute
+/// - colors as `#rgb`, `#rrggbb`, `rgb(r, g, b)` or with one of the basic color names
+/// - the named character references `&lt;`, `&gt;`, `&amp;`, `&quot;`, `&apos;` and `&nbsp;`
+///   and numeric character references
+///
+/// Other elements, like text, gradients and references with `use`, are ignored.  Painting with
+/// a gradient or a pattern falls back to the fallback color, if set.  Group opacity is
+/// approximated by applying it to every shape of the group.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::elements;
+/// let svg: elements::Svg = r#"<svg width="20mm" height="10mm" viewBox="0 0 2 1">
+///         <rect width="1" height="1" fill="red"/>
+///         <circle cx="1.5" cy="0.5" r="0.5" stroke="blue" stroke-width="0.1"/>
+///     </svg>"#
+///     .parse()
+///     .expect("Failed to parse SVG image");
+/// let svg = svg.with_alignment(genpdf::Alignment::Center);
+/// ```
+///
+/// [`Image`]: struct.Image.html
+#[derive(Clone, Debug)]
+pub struct Svg {
+    shapes: Vec<Shape>,
+
+    /// The natural size of the image as given by the `svg` element.
+    natural_size: Size,
+
+    /// Used for positioning if no absolute position is given.
+    alignment: Alignment,
+
+    /// The absolute position within the given area.
+    position: Option<Position>,
+
+    /// Scaling of the image, default is 1:1.
+    scale: Scale,
+
+    /// The target width of the image, overrides the horizontal scale if set.
+    width: Option<Mm>,
+
+    /// The target height of the image, overrides the vertical scale if set.
+    height: Option<Mm>,
+}
+
+impl Svg {
+    /// Creates a new SVG image from the given reader.
+    pub fn from_reader(mut reader: impl io::Read) -> Result<Self, Error> {
+        let mut data = String::new();
+        reader
+            .read_to_string(&mut data)
+            .context("Could not read SVG image")?;
+        data.parse()
+    }
+
+    /// Creates a new SVG image by reading from the given path.
+    pub fn from_path(path: impl AsRef<path::Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("Could not read SVG image from path {}", path.display()))?;
+        data.parse()
+    }
+
+    /// Translates the image over to position.
+    pub fn set_position(&mut self, position: impl Into<Position>) {
+        self.position = Some(position.into());
+    }
+
+    /// Translates the image over to position and returns it.
+    pub fn with_position(mut self, position: impl Into<Position>) -> Self {
+        self.set_position(position);
+        self
+    }
+
+    /// Scales the image.
+    pub fn set_scale(&mut self, scale: impl Into<Scale>) {
+        self.scale = scale.into();
+    }
+
+    /// Scales the image and returns it.
+    pub fn with_scale(mut self, scale: impl Into<Scale>) -> Self {
+        self.set_scale(scale);
+        self
+    }
+
+    /// Scales the image to the given width.
+    ///
+    /// If no height is set, the aspect ratio of the image is preserved.  This overrides the scale
+    /// set with [`set_scale`][].
+    ///
+    /// [`set_scale`]: #method.set_scale
+    pub fn set_width(&mut self, width: impl Into<Mm>) {
+        self.width = Some(width.into());
+    }
+
+    /// Scales the image to the given width and returns it.
+    ///
+    /// See [`set_width`][] for more information.
+    ///
+    /// [`set_width`]: #method.set_width
+    pub fn with_width(mut self, width: impl Into<Mm>) -> Self {
+        self.set_width(width);
+        self
+    }
+
+    /// Scales the image to the given height.
+    ///
+    /// If no width is set, the aspect ratio of the image is preserved.  This overrides the scale
+    /// set with [`set_scale`][].
+    ///
+    /// [`set_scale`]: #method.set_scale
+    pub fn set_height(&mut self, height: impl Into<Mm>) {
+        self.height = Some(height.into());
+    }
+
+    /// Scales the image to the given height and returns it.
+    ///
+    /// See [`set_height`][] for more information.
+    ///
+    /// [`set_height`]: #method.set_height
+    pub fn with_height(mut self, height: impl Into<Mm>) -> Self {
+        self.set_height(height);
+        self
+    }
+
+    /// Sets the alignment to use for this image.
+    pub fn set_alignment(&mut self, alignment: impl Into<Alignment>) {
+        self.alignment = alignment.into();
+    }
+
+    /// Sets the alignment to use for this image and returns it.
+    pub fn with_alignment(mut self, alignment: impl Into<Alignment>) -> Self {
+        self.set_alignment(alignment);
+        self
+    }
+
+    /// Calculates the scale of the image, taking into account the target width and height.
+    fn get_scale(&self) -> Scale {
+        let scale_x = self.width.map(|width| width.0 / self.natural_size.width.0);
+        let scale_y = self
+            .height
+            .map(|height| height.0 / self.natural_size.height.0);
+        match (scale_x, scale_y) {
+            (Some(x), Some(y)) => Scale::new(x, y),
+            (Some(x), None) => Scale::new(x, x),
+            (None, Some(y)) => Scale::new(y, y),
+            (None, None) => self.scale,
+        }
+    }
+
+    /// Calculates the position and the size of the image in the given area.
+    fn layout(&self, area: &render::Area<'_>) -> (Position, Scale, RenderResult) {
+        let scale = self.get_scale();
+        let size = Size::new(
+            self.natural_size.width * scale.x,
+            self.natural_size.height * scale.y,
+        );
+        let mut result = RenderResult::default();
+        let position = if let Some(position) = self.position {
+            position
+        } else {
+            result.size = size;
+            let max_width = area.size().width;
+            let offset = match self.alignment {
+                Alignment::Left | Alignment::Justify => Mm::default(),
+                Alignment::Center => (max_width - size.width) / 2.0,
+                Alignment::Right => max_width - size.width,
+            };
+            Position::new(offset, 0)
+        };
+        (position, scale, result)
+    }
+}
+
+impl str::FromStr for Svg {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let root = parse_xml(s)?;
+        if local_name(&root.name) != "svg" {
+            return Err(invalid(format!(
+                "The root element of an SVG image must be svg, not {}",
+                root.name
+            )));
+        }
+
+        let (matrix, natural_size) = viewport(&root)?;
+        let mut shapes = Vec::new();
+        convert_children(&root, matrix, &Paint::default(), &mut shapes)?;
+        Ok(Svg {
+            shapes,
+            natural_size,
+            alignment: Alignment::default(),
+            position: None,
+            scale: Scale::default(),
+            width: None,
+            height: None,
+        })
+    }
+}
+
+impl Element for Svg {
+    fn render(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: style::Style,
+    ) -> Result<RenderResult, Error> {
+        let (position, scale, result) = self.layout(&area);
+        let mut svg_area = area.clone();
+        svg_area.add_offset(position);
+        svg_area.set_size(Size::new(
+            self.natural_size.width * scale.x,
+            self.natural_size.height * scale.y,
+        ));
+        svg_area.with_clipping(|area| {
+            for shape in &self.shapes {
+                shape.draw(&area, scale);
+            }
+        });
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        _context: &Context,
+        area: render::Area<'_>,
+        _style: style::Style,
+    ) -> Result<RenderResult, Error> {
+        Ok(self.layout(&area).2)
+    }
+}
+
+fn invalid(msg: String) -> Error {
+    Error::new(msg, ErrorKind::InvalidData)
+}
+
+/// A filled or stroked path in millimeters relative to the upper left corner of the image.
+#[derive(Clone, Debug)]
+struct Shape {
+    segments: Vec<Segment>,
+    fill: Option<(Color, f64)>,
+    is_even_odd: bool,
+    stroke: Option<(Color, f64)>,
+    stroke_width: f64,
+    dash_pattern: Vec<f64>,
+}
+
+impl Shape {
+    fn draw(&self, area: &render::Area<'_>, scale: Scale) {
+        let position = |(x, y): (f64, f64)| Position::new(x * scale.x, y * scale.y);
+        let segments: Vec<_> = self
+            .segments
+            .iter()
+            .map(|segment| match *segment {
+                Segment::Move(p) => PathSegment::MoveTo(position(p)),
+                Segment::Line(p) => PathSegment::LineTo(position(p)),
+                Segment::Curve(p1, p2, p3) => {
+                    PathSegment::CurveTo(position(p1), position(p2), position(p3))
+                }
+                Segment::Close => PathSegment::Close,
+            })
+            .collect();
+
+        if let Some((color, opacity)) = self.fill {
+            area.with_opacity(opacity, |area| {
+                area.fill_path(&segments, color, self.is_even_odd)
+            });
+        }
+        if let Some((color, opacity)) = self.stroke {
+            let factor = (scale.x * scale.y).abs().sqrt();
+            let mut line_style = LineStyle::new()
+                .with_thickness(self.stroke_width * factor)
+                .with_color(color);
+            let dash_pattern: Vec<_> = self.dash_pattern.iter().map(|l| l * factor).collect();
+            line_style.set_dash(&dash_pattern);
+            area.with_opacity(opacity, |area| area.stroke_path(&segments, line_style));
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Segment {
+    Move((f64, f64)),
+    Line((f64, f64)),
+    Curve((f64, f64), (f64, f64), (f64, f64)),
+    Close,
+}
+
+/// An affine transformation `[a b c d e f]` as defined in the SVG specification.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Matrix([f64; 6]);
+
+impl Matrix {
+    const IDENTITY: Matrix = Matrix([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    fn translate(x: f64, y: f64) -> Matrix {
+        Matrix([1.0, 0.0, 0.0, 1.0, x, y])
+    }
+
+    fn scale(x: f64, y: f64) -> Matrix {
+        Matrix([x, 0.0, 0.0, y, 0.0, 0.0])
+    }
+
+    /// Returns the transformation that first applies `other` and then this transformation.
+    fn then(&self, other: &Matrix) -> Matrix {
+        let [a, b, c, d, e, f] = self.0;
+        let [oa, ob, oc, od, oe, of] = other.0;
+        Matrix([
+            a * oa + c * ob,
+            b * oa + d * ob,
+            a * oc + c * od,
+            b * oc + d * od,
+            a * oe + c * of + e,
+            b * oe + d * of + f,
+        ])
+    }
+
+    fn apply(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        let [a, b, c, d, e, f] = self.0;
+        (a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// Returns the factor that lengths are scaled with, averaged over both axes.
+    fn length_factor(&self) -> f64 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+}
+
+/// The inherited painting properties.
+#[derive(Clone, Debug)]
+struct Paint {
+    color: Color,
+    fill: Option<Color>,
+    fill_opacity: f64,
+    is_even_odd: bool,
+    stroke: Option<Color>,
+    stroke_opacity: f64,
+    stroke_width: f64,
+    dash_pattern: Vec<f64>,
+    opacity: f64,
+    is_visible: bool,
+}
+
+impl Default for Paint {
+    fn default() -> Paint {
+        Paint {
+            color: Color::Rgb(0, 0, 0),
+            fill: Some(Color::Rgb(0, 0, 0)),
+            fill_opacity: 1.0,
+            is_even_odd: false,
+            stroke: None,
+            stroke_opacity: 1.0,
+            stroke_width: 1.0,
+            dash_pattern: Vec::new(),
+            opacity: 1.0,
+            is_visible: true,
+        }
+    }
+}
+
+impl Paint {
+    /// Returns the painting properties for the given element that inherits from this paint.
+    fn inherit(&self, node: &Node) -> Result<Option<Paint>, Error> {
+        let mut paint = self.clone();
+        let style = node.attribute("style").unwrap_or_default();
+        let declarations = style.split(';').filter_map(|declaration| {
+            let mut parts = declaration.splitn(2, ':');
+            Some((parts.next()?, parts.next()?))
+        });
+        let properties: Vec<_> = node
+            .attributes
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .chain(declarations)
+            .collect();
+        // The color property must be known before other properties can refer to it.
+        for (name, value) in &properties {
+            if name.trim() == "color" {
+                paint.color = parse_color(value.trim(), self.color).unwrap_or(paint.color);
+            }
+        }
+        for (name, value) in properties {
+            let value = value.trim();
+            match name.trim() {
+                "fill" => paint.fill = parse_paint(value, paint.color, paint.fill)?,
+                "stroke" => paint.stroke = parse_paint(value, paint.color, paint.stroke)?,
+                "fill-opacity" => paint.fill_opacity = parse_opacity(value)?,
+                "stroke-opacity" => paint.stroke_opacity = parse_opacity(value)?,
+                "opacity" => paint.opacity = self.opacity * parse_opacity(value)?,
+                "fill-rule" => paint.is_even_odd = value == "evenodd",
+                "stroke-width" => {
+                    paint.stroke_width = parse_length(value)
+                        .ok_or_else(|| invalid(format!("Invalid stroke width in SVG: {}", value)))?
+                }
+                "stroke-dasharray" => {
+                    paint.dash_pattern = if value == "none" {
+                        Vec::new()
+                    } else {
+                        let lengths = parse_numbers(value).ok_or_else(|| {
+                            invalid(format!("Invalid dash array in SVG: {}", value))
+                        })?;
+                        // An odd number of lengths is repeated to yield an even number.
+                        if lengths.len() % 2 == 1 {
+                            lengths.repeat(2)
+                        } else {
+                            lengths
+                        }
+                    }
+                }
+                "display" if value == "none" => return Ok(None),
+                "visibility" => paint.is_visible = value != "hidden" && value != "collapse",
+                _ => {}
+            }
+        }
+        Ok(Some(paint))
+    }
+
+    fn shape(&self, segments: Vec<Segment>, matrix: Matrix) -> Option<Shape> {
+        let fill = self
+            .fill
+            .map(|color| (color, self.opacity * self.fill_opacity));
+        let stroke = self
+            .stroke
+            .filter(|_| self.stroke_width > 0.0)
+            .map(|color| (color, self.opacity * self.stroke_opacity));
+        if segments.is_empty() || !self.is_visible || (fill.is_none() && stroke.is_none()) {
+            return None;
+        }
+        let factor = matrix.length_factor();
+        let dash_pattern = if self.dash_pattern.iter().all(|l| *l >= 0.0)
+            && self.dash_pattern.iter().any(|l| *l > 0.0)
+            && self.dash_pattern.len() <= 6
+        {
+            self.dash_pattern.iter().map(|l| l * factor).collect()
+        } else {
+            Vec::new()
+        };
+        let point = |p| matrix.apply(p);
+        Some(Shape {
+            segments: segments
+                .into_iter()
+                .map(|segment| match segment {
+                    Segment::Move(p) => Segment::Move(point(p)),
+                    Segment::Line(p) => Segment::Line(point(p)),
+                    Segment::Curve(p1, p2, p3) => Segment::Curve(point(p1), point(p2), point(p3)),
+                    Segment::Close => Segment::Close,
+                })
+                .collect(),
+            fill,
+            is_even_odd: self.is_even_odd,
+            stroke,
+            stroke_width: self.stroke_width * factor,
+            dash_pattern,
+        })
+    }
+}
+
+/// Calculates the transformation from the user space into millimeters and the natural size of
+/// the image from the attributes of the root `svg` element.
+fn viewport(root: &Node) -> Result<(Matrix, Size), Error> {
+    let length = |name| -> Result<Option<f64>, Error> {
+        match root.attribute(name) {
+            Some(value) if value.trim().ends_with('%') => Ok(None),
+            Some(value) => parse_length(value)
+                .map(Some)
+                .ok_or_else(|| invalid(format!("Invalid {} in SVG: {}", name, value))),
+            None => Ok(None),
+        }
+    };
+    let view_box = match root.attribute("viewBox") {
+        Some(value) => match parse_numbers(value).as_deref() {
+            Some(&[x, y, width, height]) if width > 0.0 && height > 0.0 => {
+                Some((x, y, width, height))
+            }
+            _ => return Err(invalid(format!("Invalid viewBox in SVG: {}", value))),
+        },
+        None => None,
+    };
+
+    let (width, height) = match (length("width")?, length("height")?, view_box) {
+        (Some(width), Some(height), _) => (width, height),
+        (Some(width), None, Some((_, _, w, h))) => (width, width * h / w),
+        (None, Some(height), Some((_, _, w, h))) => (height * w / h, height),
+        (None, None, Some((_, _, w, h))) => (w, h),
+        _ => {
+            return Err(invalid(
+                "SVG images must have a width and a height or a viewBox".to_owned(),
+            ))
+        }
+    };
+    if width <= 0.0 || height <= 0.0 {
+        return Err(invalid("SVG images must not be empty".to_owned()));
+    }
+
+    let to_mm = Matrix::scale(MM_PER_PX, MM_PER_PX);
+    let size = Size::new(width * MM_PER_PX, height * MM_PER_PX);
+    let (x, y, w, h) = match view_box {
+        Some(view_box) => view_box,
+        None => return Ok((to_mm, size)),
+    };
+
+    let aspect = root
+        .attribute("preserveAspectRatio")
+        .unwrap_or("xMidYMid meet");
+    let mut parts = aspect.split_whitespace();
+    let align = parts.next().unwrap_or("xMidYMid");
+    let is_slice = parts.next() == Some("slice");
+    let (scale_x, scale_y) = (width / w, height / h);
+    let matrix = if align == "none" {
+        Matrix::scale(scale_x, scale_y).then(&Matrix::translate(-x, -y))
+    } else {
+        let scale = if is_slice {
+            scale_x.max(scale_y)
+        } else {
+            scale_x.min(scale_y)
+        };
+        let offset = |align: Option<&str>, available: f64, used: f64| match align {
+            Some("Min") => 0.0,
+            Some("Max") => available - used,
+            _ => (available - used) / 2.0,
+        };
+        let offset_x = offset(align.get(1..4), width, w * scale);
+        let offset_y = offset(align.get(5..8), height, h * scale);
+        Matrix::translate(offset_x, offset_y)
+            .then(&Matrix::scale(scale, scale))
+            .then(&Matrix::translate(-x, -y))
+    };
+    Ok((to_mm.then(&matrix), size))
+}
+
+fn convert_children(
+    node: &Node,
+    matrix: Matrix,
+    paint: &Paint,
+    shapes: &mut Vec<Shape>,
+) -> Result<(), Error> {
+    for child in &node.children {
+        let paint = match paint.inherit(child)? {
+            Some(paint) => paint,
+            None => continue,
+        };
+        let mut matrix = match child.attribute("transform") {
+            Some(transform) => matrix.then(&parse_transform(transform)?),
+            None => matrix,
+        };
+        let number = |name| -> Result<f64, Error> {
+            match child.attribute(name) {
+                Some(value) => parse_length(value)
+                    .ok_or_else(|| invalid(format!("Invalid {} in SVG: {}", name, value))),
+                None => Ok(0.0),
+            }
+        };
+
+        let mut path = PathBuilder::default();
+        match local_name(&child.name) {
+            "g" | "a" => convert_children(child, matrix, &paint, shapes)?,
+            "svg" => {
+                matrix = matrix.then(&Matrix::translate(number("x")?, number("y")?));
+                convert_children(child, matrix, &paint, shapes)?;
+            }
+            "path" => parse_path(child.attribute("d").unwrap_or_default(), &mut path)?,
+            "rect" => {
+                let (x, y, width, height) = (
+                    number("x")?,
+                    number("y")?,
+                    number("width")?,
+                    number("height")?,
+                );
+                let (rx, ry) = match (child.attribute("rx"), child.attribute("ry")) {
+                    (None, None) => (0.0, 0.0),
+                    (Some(_), None) => (number("rx")?, number("rx")?),
+                    (None, Some(_)) => (number("ry")?, number("ry")?),
+                    (Some(_), Some(_)) => (number("rx")?, number("ry")?),
+                };
+                let (rx, ry) = (rx.min(width / 2.0), ry.min(height / 2.0));
+                if width > 0.0 && height > 0.0 {
+                    path.move_to((x + rx, y));
+                    path.line_to((x + width - rx, y));
+                    path.arc_to(rx, ry, 0.0, false, true, (x + width, y + ry));
+                    path.line_to((x + width, y + height - ry));
+                    path.arc_to(rx, ry, 0.0, false, true, (x + width - rx, y + height));
+                    path.line_to((x + rx, y + height));
+                    path.arc_to(rx, ry, 0.0, false, true, (x, y + height - ry));
+                    path.line_to((x, y + ry));
+                    path.arc_to(rx, ry, 0.0, false, true, (x + rx, y));
+                    path.close();
+                }
+            }
+            name @ "circle" | name @ "ellipse" => {
+                let (cx, cy) = (number("cx")?, number("cy")?);
+                let (rx, ry) = if name == "circle" {
+                    (number("r")?, number("r")?)
+                } else {
+                    (number("rx")?, number("ry")?)
+                };
+                if rx > 0.0 && ry > 0.0 {
+                    path.move_to((cx + rx, cy));
+                    path.arc_to(rx, ry, 0.0, false, true, (cx, cy + ry));
+                    path.arc_to(rx, ry, 0.0, false, true, (cx - rx, cy));
+                    path.arc_to(rx, ry, 0.0, false, true, (cx, cy - ry));
+                    path.arc_to(rx, ry, 0.0, false, true, (cx + rx, cy));
+                    path.close();
+                }
+            }
+            "line" => {
+                path.move_to((number("x1")?, number("y1")?));
+                path.line_to((number("x2")?, number("y2")?));
+            }
+            name @ "polyline" | name @ "polygon" => {
+                let points = child.attribute("points").unwrap_or_default();
+                let numbers = parse_numbers(points)
+                    .ok_or_else(|| invalid(format!("Invalid points in SVG: {}", points)))?;
+                for (idx, point) in numbers.chunks_exact(2).enumerate() {
+                    if idx == 0 {
+                        path.move_to((point[0], point[1]));
+                    } else {
+                        path.line_to((point[0], point[1]));
+                    }
+                }
+                if name == "polygon" && !path.segments.is_empty() {
+                    path.close();
+                }
+            }
+            _ => {}
+        }
+
+        // Lines without an area cannot be filled.
+        let mut paint = paint;
+        if local_name(&child.name) == "line" {
+            paint.fill = None;
+        }
+        shapes.extend(paint.shape(path.segments, matrix));
+    }
+    Ok(())
+}
+
+/// A builder for paths in the user space that keeps track of the current point.
+#[derive(Debug, Default)]
+struct PathBuilder {
+    segments: Vec<Segment>,
+    current: (f64, f64),
+    start: (f64, f64),
+}
+
+impl PathBuilder {
+    fn move_to(&mut self, p: (f64, f64)) {
+        self.segments.push(Segment::Move(p));
+        self.current = p;
+        self.start = p;
+    }
+
+    fn line_to(&mut self, p: (f64, f64)) {
+        self.ensure_start();
+        self.segments.push(Segment::Line(p));
+        self.current = p;
+    }
+
+    fn curve_to(&mut self, p1: (f64, f64), p2: (f64, f64), p: (f64, f64)) {
+        self.ensure_start();
+        self.segments.push(Segment::Curve(p1, p2, p));
+        self.current = p;
+    }
+
+    fn quad_to(&mut self, c: (f64, f64), p: (f64, f64)) {
+        let (x0, y0) = self.current;
+        let p1 = (x0 + 2.0 / 3.0 * (c.0 - x0), y0 + 2.0 / 3.0 * (c.1 - y0));
+        let p2 = (p.0 + 2.0 / 3.0 * (c.0 - p.0), p.1 + 2.0 / 3.0 * (c.1 - p.1));
+        self.curve_to(p1, p2, p);
+    }
+
+    /// Adds an elliptical arc as defined in the SVG specification, approximated with cubic
+    /// Bézier curves.
+    fn arc_to(
+        &mut self,
+        rx: f64,
+        ry: f64,
+        angle: f64,
+        large_arc: bool,
+        sweep: bool,
+        p: (f64, f64),
+    ) {
+        let from = self.current;
+        if from == p {
+            return;
+        }
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(p);
+            return;
+        }
+
+        // See the implementation notes of the SVG specification, appendix F.6.5.
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let (dx, dy) = ((from.0 - p.0) / 2.0, (from.1 - p.1) / 2.0);
+        let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            coefficient = -coefficient;
+        }
+        let (cx1, cy1) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+        let cx = cos * cx1 - sin * cy1 + (from.0 + p.0) / 2.0;
+        let cy = sin * cx1 + cos * cy1 + (from.1 + p.1) / 2.0;
+
+        let vector_angle = |(ux, uy): (f64, f64), (vx, vy): (f64, f64)| {
+            (ux * vy - uy * vx).atan2(ux * vx + uy * vy)
+        };
+        let u = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+        let v = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+        let theta = vector_angle((1.0, 0.0), u);
+        let mut delta = vector_angle(u, v);
+        if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        } else if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        }
+
+        let count = (delta.abs() / FRAC_PI_2).ceil().max(1.0);
+        let step = delta / count;
+        let t = 4.0 / 3.0 * (step / 4.0).tan();
+        let point = |a: f64| {
+            let (x, y) = (rx * a.cos(), ry * a.sin());
+            (cx + x * cos - y * sin, cy + x * sin + y * cos)
+        };
+        let derivative = |a: f64| {
+            let (x, y) = (-rx * a.sin(), ry * a.cos());
+            (x * cos - y * sin, x * sin + y * cos)
+        };
+        for i in 0..count as usize {
+            let (a1, a2) = (theta + i as f64 * step, theta + (i + 1) as f64 * step);
+            let (p1, d1, p2, d2) = (point(a1), derivative(a1), point(a2), derivative(a2));
+            let end = if i + 1 == count as usize { p } else { p2 };
+            self.curve_to(
+                (p1.0 + t * d1.0, p1.1 + t * d1.1),
+                (p2.0 - t * d2.0, p2.1 - t * d2.1),
+                end,
+            );
+        }
+    }
+
+    fn close(&mut self) {
+        if !self.segments.is_empty() {
+            self.segments.push(Segment::Close);
+            self.current = self.start;
+        }
+    }
+
+    /// Starts a subpath at the current point if drawing starts after a closed subpath.
+    fn ensure_start(&mut self) {
+        if matches!(self.segments.last(), None | Some(Segment::Close)) {
+            self.segments.push(Segment::Move(self.current));
+            self.start = self.current;
+        }
+    }
+}
+
+/// A scanner for the numbers and flags in path data, point lists and transformations.
+struct Scanner<'a> {
+    s: &'a str,
+}
+
+impl<'a> Scanner<'a> {
+    fn skip_separators(&mut self) {
+        self.s = self
+            .s
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.skip_separators();
+        self.s.is_empty()
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_separators();
+        self.s.chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.s = &self.s[c.len_utf8()..];
+        Some(c)
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_separators();
+        let bytes = self.s.as_bytes();
+        let mut end = 0;
+        if matches!(bytes.first(), Some(b'+') | Some(b'-')) {
+            end += 1;
+        }
+        let mut has_dot = false;
+        while let Some(b) = bytes.get(end) {
+            match b {
+                b'0'..=b'9' => {}
+                b'.' if !has_dot => has_dot = true,
+                _ => break,
+            }
+            end += 1;
+        }
+        if matches!(bytes.get(end), Some(b'e') | Some(b'E')) {
+            let mut exponent_end = end + 1;
+            if matches!(bytes.get(exponent_end), Some(b'+') | Some(b'-')) {
+                exponent_end += 1;
+            }
+            if bytes.get(exponent_end).map_or(false, u8::is_ascii_digit) {
+                end = exponent_end;
+                while bytes.get(end).map_or(false, u8::is_ascii_digit) {
+                    end += 1;
+                }
+            }
+        }
+        let number = self.s[..end].parse().ok()?;
+        self.s = &self.s[end..];
+        Some(number)
+    }
+
+    fn point(&mut self) -> Option<(f64, f64)> {
+        Some((self.number()?, self.number()?))
+    }
+
+    fn flag(&mut self) -> Option<bool> {
+        match self.peek()? {
+            '0' => self.next_char().map(|_| false),
+            '1' => self.next_char().map(|_| true),
+            _ => None,
+        }
+    }
+
+    fn starts_with_number(&mut self) -> bool {
+        matches!(
+            self.peek(),
+            Some('0'..='9') | Some('+') | Some('-') | Some('.')
+        )
+    }
+}
+
+fn parse_numbers(s: &str) -> Option<Vec<f64>> {
+    let mut scanner = Scanner { s };
+    let mut numbers = Vec::new();
+    while !scanner.is_empty() {
+        numbers.push(scanner.number()?);
+    }
+    Some(numbers)
+}
+
+fn parse_path(d: &str, path: &mut PathBuilder) -> Result<(), Error> {
+    let error = || invalid(format!("Invalid path data in SVG: {}", d));
+    let mut scanner = Scanner { s: d };
+    let mut command = None;
+    // The second control point of the last curve, used for the S and T commands.
+    let mut last_control: Option<(char, (f64, f64))> = None;
+    while !scanner.is_empty() {
+        if !scanner.starts_with_number() {
+            command = scanner.next_char();
+        } else if command.is_none() {
+            return Err(error());
+        }
+        let c = command.ok_or_else(error)?;
+        let is_relative = c.is_ascii_lowercase();
+        let (x0, y0) = path.current;
+        let abs = |(x, y): (f64, f64)| {
+            if is_relative {
+                (x0 + x, y0 + y)
+            } else {
+                (x, y)
+            }
+        };
+        let reflected = |kind: char| match last_control {
+            Some((k, (x, y))) if k == kind => (2.0 * x0 - x, 2.0 * y0 - y),
+            _ => (x0, y0),
+        };
+        let mut control = None;
+        match c.to_ascii_uppercase() {
+            'M' => {
+                path.move_to(abs(scanner.point().ok_or_else(error)?));
+                // Following coordinate pairs are implicit line commands.
+                command = Some(if is_relative { 'l' } else { 'L' });
+            }
+            'L' => path.line_to(abs(scanner.point().ok_or_else(error)?)),
+            'H' => {
+                let x = scanner.number().ok_or_else(error)?;
+                path.line_to((if is_relative { x0 + x } else { x }, y0));
+            }
+            'V' => {
+                let y = scanner.number().ok_or_else(error)?;
+                path.line_to((x0, if is_relative { y0 + y } else { y }));
+            }
+            'C' => {
+                let p1 = abs(scanner.point().ok_or_else(error)?);
+                let p2 = abs(scanner.point().ok_or_else(error)?);
+                let p = abs(scanner.point().ok_or_else(error)?);
+                path.curve_to(p1, p2, p);
+                control = Some(('C', p2));
+            }
+            'S' => {
+                let p1 = reflected('C');
+                let p2 = abs(scanner.point().ok_or_else(error)?);
+                let p = abs(scanner.point().ok_or_else(error)?);
+                path.curve_to(p1, p2, p);
+                control = Some(('C', p2));
+            }
+            'Q' => {
+                let c = abs(scanner.point().ok_or_else(error)?);
+                let p = abs(scanner.point().ok_or_else(error)?);
+                path.quad_to(c, p);
+                control = Some(('Q', c));
+            }
+            'T' => {
+                let c = reflected('Q');
+                let p = abs(scanner.point().ok_or_else(error)?);
+                path.quad_to(c, p);
+                control = Some(('Q', c));
+            }
+            'A' => {
+                let rx = scanner.number().ok_or_else(error)?;
+                let ry = scanner.number().ok_or_else(error)?;
+                let angle = scanner.number().ok_or_else(error)?;
+                let large_arc = scanner.flag().ok_or_else(error)?;
+                let sweep = scanner.flag().ok_or_else(error)?;
+                let p = abs(scanner.point().ok_or_else(error)?);
+                path.arc_to(rx, ry, angle, large_arc, sweep, p);
+            }
+            'Z' => {
+                path.close();
+                command = None;
+            }
+            _ => return Err(error()),
+        }
+        last_control = control;
+    }
+    Ok(())
+}
+
+fn parse_transform(s: &str) -> Result<Matrix, Error> {
+    let error = || invalid(format!("Invalid transform in SVG: {}", s));
+    let mut matrix = Matrix::IDENTITY;
+    let mut rest = s.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    while !rest.is_empty() {
+        let open = rest.find('(').ok_or_else(error)?;
+        let close = rest.find(')').ok_or_else(error)?;
+        let name = rest[..open].trim();
+        let args = parse_numbers(rest.get(open + 1..close).ok_or_else(error)?).ok_or_else(error)?;
+        let transform = match (name, args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Matrix([a, b, c, d, e, f]),
+            ("translate", &[x]) => Matrix::translate(x, 0.0),
+            ("translate", &[x, y]) => Matrix::translate(x, y),
+            ("scale", &[x]) => Matrix::scale(x, x),
+            ("scale", &[x, y]) => Matrix::scale(x, y),
+            ("rotate", &[angle]) | ("rotate", &[angle, _, _]) => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let rotation = Matrix([cos, sin, -sin, cos, 0.0, 0.0]);
+                if let [_, x, y] = args.as_slice() {
+                    Matrix::translate(*x, *y)
+                        .then(&rotation)
+                        .then(&Matrix::translate(-x, -y))
+                } else {
+                    rotation
+                }
+            }
+            ("skewX", &[angle]) => Matrix([1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0]),
+            ("skewY", &[angle]) => Matrix([1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+            _ => return Err(error()),
+        };
+        matrix = matrix.then(&transform);
+        rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Ok(matrix)
+}
+
+/// Parses a length and returns it in user units.
+fn parse_length(s: &str) -> Option<f64> {
+    let s = s.trim();
+    let unit_start = s
+        .find(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E')
+        .unwrap_or(s.len());
+    let factor = match &s[unit_start..] {
+        "" | "px" => 1.0,
+        "pt" => 96.0 / 72.0,
+        "pc" => 16.0,
+        "in" => 96.0,
+        "cm" => 96.0 / 2.54,
+        "mm" => 96.0 / 25.4,
+        _ => return None,
+    };
+    s[..unit_start]
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(|n| n * factor)
+}
+
+fn parse_opacity(s: &str) -> Result<f64, Error> {
+    let opacity = match s.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().map(|p| p / 100.0),
+        None => s.parse::<f64>(),
+    };
+    opacity
+        .map(|opacity| opacity.max(0.0).min(1.0))
+        .map_err(|_| invalid(format!("Invalid opacity in SVG: {}", s)))
+}
+
+/// Parses the value of the `fill` or `stroke` property.
+fn parse_paint(s: &str, current: Color, inherited: Option<Color>) -> Result<Option<Color>, Error> {
+    match s {
+        "none" => Ok(None),
+        "inherit" => Ok(inherited),
+        _ => {
+            if let Some(reference) = s.strip_prefix("url(") {
+                // Gradients and patterns are not supported, so we use the fallback color.
+                let fallback = reference
+                    .find(')')
+                    .map(|end| reference[end + 1..].trim())
+                    .unwrap_or("");
+                return if fallback.is_empty() || fallback == "none" {
+                    Ok(None)
+                } else {
+                    parse_paint(fallback, current, inherited)
+                };
+            }
+            parse_color(s, current)
+                .map(Some)
+                .ok_or_else(|| invalid(format!("Invalid color in SVG: {}", s)))
+        }
+    }
+}
+
+/// Parses a color or the `currentColor` keyword.
+fn parse_color(s: &str, current: Color) -> Option<Color> {
+    if s.eq_ignore_ascii_case("currentcolor") {
+        Some(current)
+    } else {
+        markup::parse_color(s)
+    }
+}
+
+/// An element of a parsed XML document.
+#[derive(Clone, Debug)]
+struct Node {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Returns the name without the namespace prefix.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Parses the elements of the given XML document and returns its root element.
+///
+/// Text content is not needed for the supported SVG features and skipped.
+fn parse_xml(s: &str) -> Result<Node, Error> {
+    let mut stack: Vec<Node> = Vec::new();
+    let mut root = None;
+    let mut rest = s;
+    while let Some(idx) = rest.find('<') {
+        rest = &rest[idx..];
+        let skip = [("<!--", "-->"), ("<![CDATA[", "]]>"), ("<?", "?>")]
+            .iter()
+            .find(|(start, _)| rest.starts_with(start));
+        if let Some((start, end)) = skip {
+            let len = rest[start.len()..]
+                .find(end)
+                .ok_or_else(|| invalid("Unterminated markup in SVG image".to_owned()))?;
+            rest = &rest[start.len() + len + end.len()..];
+            continue;
+        }
+
+        let end = find_tag_end(rest)
+            .ok_or_else(|| invalid("Unterminated tag in SVG image".to_owned()))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('!') {
+            continue;
+        }
+
+        let node = if let Some(name) = tag.strip_prefix('/') {
+            match stack.pop() {
+                Some(node) if node.name == name.trim() => node,
+                _ => {
+                    return Err(invalid(format!(
+                        "Unexpected closing tag in SVG image: {}",
+                        tag
+                    )))
+                }
+            }
+        } else {
+            let (tag, is_closed) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let node = parse_tag(tag)?;
+            if !is_closed {
+                stack.push(node);
+                continue;
+            }
+            node
+        };
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None if root.is_none() => root = Some(node),
+            None => return Err(invalid("SVG images must have one root element".to_owned())),
+        }
+    }
+    if let Some(node) = stack.last() {
+        return Err(invalid(format!("Unclosed tag in SVG image: {}", node.name)));
+    }
+    root.ok_or_else(|| invalid("SVG images must have a root element".to_owned()))
+}
+
+/// Returns the index of the `>` character that ends the tag at the start of the given string.
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(idx),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_tag(tag: &str) -> Result<Node, Error> {
+    let error = || invalid(format!("Invalid tag in SVG image: <{}>", tag));
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    if name.is_empty() {
+        return Err(error());
+    }
+
+    let mut attributes = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let mut parts = rest.splitn(2, '=');
+        let attribute = parts.next().unwrap_or_default();
+        let value = parts.next().ok_or_else(error)?;
+        let value = value.trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or_else(error)?;
+        let end = value[1..].find(quote).ok_or_else(error)?;
+        attributes.push((
+            attribute.trim().to_owned(),
+            markup::decode_entities(&value[1..=end])?,
+        ));
+        rest = value[end + 2..].trim_start();
+    }
+    Ok(Node {
+        name: name.to_owned(),
+        attributes,
+        children: Vec::new(),
+    })
+}
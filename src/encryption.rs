@@ -0,0 +1,529 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Encryption and password protection for generated PDF documents.
+//!
+//! PDF documents can be encrypted with a user password and an owner password, see
+//! [`Document::set_encryption`][].  The user password is required to open the document.  The
+//! owner password is required to change the permissions of the document, for example to allow
+//! printing or copying text.  The document is encrypted using the standard security handler
+//! defined in the PDF specification with a 128-bit RC4 or AES key.
+//!
+//! Note that the permissions are only enforced by the PDF viewer.  Everybody who can open the
+//! document can also decrypt it, so the permissions should not be used to protect confidential
+//! data.  Use a user password instead.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use genpdf::encryption;
+//! # let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None).unwrap();
+//! let mut doc = genpdf::Document::new(font_family);
+//! doc.set_encryption(encryption::EncryptionOptions {
+//!     user_password: "secret".to_owned(),
+//!     owner_password: "top secret".to_owned(),
+//!     permissions: encryption::Permissions {
+//!         copy: false,
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! });
+//! ```
+//!
+//! [`Document::set_encryption`]: ../struct.Document.html#method.set_encryption
+
+use std::io;
+
+use aes::cipher::{BlockEncrypt as _, BlockEncryptMut as _, KeyInit as _, KeyIvInit as _};
+use md5::Digest as _;
+use rc4::StreamCipher as _;
+
+use crate::error::{Context as _, Error, ErrorKind};
+
+/// The padding string used to pad passwords to 32 bytes, see algorithm 2 in section 7.6.3.3 of the
+/// PDF specification.
+const PASSWORD_PADDING: [u8; 32] = [
+    0x28, 0xbf, 0x4e, 0x5e, 0x4e, 0x75, 0x8a, 0x41, 0x64, 0x00, 0x4e, 0x56, 0xff, 0xfa, 0x01, 0x08,
+    0x2e, 0x2e, 0x00, 0xb6, 0xd0, 0x68, 0x3e, 0x80, 0x2f, 0x0c, 0xa9, 0xfe, 0x64, 0x53, 0x69, 0x7a,
+];
+
+/// The length of the encryption key in bytes.
+const KEY_LENGTH: usize = 16;
+
+/// The encryption algorithm used for a PDF document.
+///
+/// The default algorithm is AES.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EncryptionAlgorithm {
+    /// RC4 with a 128-bit key (PDF 1.4, revision 3 of the standard security handler).
+    Rc4,
+    /// AES with a 128-bit key (PDF 1.6, revision 4 of the standard security handler).
+    Aes128,
+}
+
+impl Default for EncryptionAlgorithm {
+    fn default() -> EncryptionAlgorithm {
+        EncryptionAlgorithm::Aes128
+    }
+}
+
+/// The permissions for a user that opened an encrypted PDF document with the user password.
+///
+/// Per default, all permissions are granted.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Permissions {
+    /// Whether the document may be printed.
+    pub print: bool,
+    /// Whether the document may be printed in high quality.  If this is `false` but `print` is
+    /// `true`, the document may only be printed in a low-level representation.
+    pub print_high_quality: bool,
+    /// Whether the content of the document may be modified.
+    pub modify: bool,
+    /// Whether text and graphics may be copied from the document.
+    pub copy: bool,
+    /// Whether annotations may be added or modified.
+    pub annotate: bool,
+    /// Whether form fields may be filled in.
+    pub fill_forms: bool,
+    /// Whether text and graphics may be extracted for accessibility purposes, for example by
+    /// screen readers.
+    pub extract_for_accessibility: bool,
+    /// Whether pages may be inserted, rotated or deleted and whether bookmarks may be created.
+    pub assemble: bool,
+}
+
+impl Permissions {
+    /// Returns permissions that grant everything.
+    pub fn all() -> Permissions {
+        Permissions {
+            print: true,
+            print_high_quality: true,
+            modify: true,
+            copy: true,
+            annotate: true,
+            fill_forms: true,
+            extract_for_accessibility: true,
+            assemble: true,
+        }
+    }
+
+    /// Returns permissions that grant nothing.
+    pub fn none() -> Permissions {
+        Permissions {
+            print: false,
+            print_high_quality: false,
+            modify: false,
+            copy: false,
+            annotate: false,
+            fill_forms: false,
+            extract_for_accessibility: false,
+            assemble: false,
+        }
+    }
+
+    /// Returns the value of the `P` entry of the encryption dictionary for these permissions.
+    fn to_flags(self) -> i32 {
+        // Bits 7, 8 and 13–32 must be set, bits 1 and 2 must be cleared.
+        let mut flags: u32 = 0xffff_f0c0;
+        let bits = [
+            (self.print, 3),
+            (self.modify, 4),
+            (self.copy, 5),
+            (self.annotate, 6),
+            (self.fill_forms, 9),
+            (self.extract_for_accessibility, 10),
+            (self.assemble, 11),
+            (self.print_high_quality, 12),
+        ];
+        for (is_granted, bit) in &bits {
+            if *is_granted {
+                flags |= 1 << (bit - 1);
+            }
+        }
+        flags as i32
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Permissions {
+        Permissions::all()
+    }
+}
+
+/// The encryption settings for a PDF document, see [`Document::set_encryption`][].
+///
+/// Passwords may only contain characters from the Latin-1 character set.  If the owner password
+/// is empty, the user password is used as the owner password.  If the user password is empty,
+/// the document can be opened without a password, but the permissions still apply.
+///
+/// [`Document::set_encryption`]: ../struct.Document.html#method.set_encryption
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EncryptionOptions {
+    /// The password that is required to open the document.
+    pub user_password: String,
+    /// The password that is required to change the permissions of the document.
+    pub owner_password: String,
+    /// The permissions for users that opened the document with the user password.
+    pub permissions: Permissions,
+    /// The encryption algorithm.
+    pub algorithm: EncryptionAlgorithm,
+}
+
+/// Encrypts the strings and streams of a PDF document.
+pub(crate) struct Encryptor {
+    key: [u8; KEY_LENGTH],
+    algorithm: EncryptionAlgorithm,
+    encryption_id: lopdf::ObjectId,
+    iv_cipher: aes::Aes128,
+    counter: u128,
+}
+
+impl Encryptor {
+    /// Adds the encryption dictionary for the given options to the given document and returns an
+    /// encryptor for its objects.
+    pub fn new(doc: &mut lopdf::Document, options: &EncryptionOptions) -> Result<Encryptor, Error> {
+        let user_password = pad_password(&options.user_password)?;
+        let owner_password = if options.owner_password.is_empty() {
+            user_password
+        } else {
+            pad_password(&options.owner_password)?
+        };
+        let flags = options.permissions.to_flags();
+
+        let mut encryptor = Encryptor {
+            key: [0; KEY_LENGTH],
+            algorithm: options.algorithm,
+            encryption_id: (0, 0),
+            iv_cipher: aes::Aes128::new(&random_key()?.into()),
+            counter: 0,
+        };
+
+        let file_id = match doc.trailer.get(b"ID") {
+            Ok(lopdf::Object::Array(ids)) => ids.first().and_then(|id| id.as_str().ok()),
+            _ => None,
+        };
+        let file_id = if let Some(file_id) = file_id {
+            file_id.to_vec()
+        } else {
+            let file_id = encryptor.random_block().to_vec();
+            let id = lopdf::Object::String(file_id.clone(), lopdf::StringFormat::Hexadecimal);
+            doc.trailer.set("ID", vec![id.clone(), id]);
+            file_id
+        };
+
+        // Algorithm 3: computing the O value
+        let mut hash = md5(&owner_password);
+        for _ in 0..50 {
+            hash = md5(&hash);
+        }
+        let owner_value = rc4_iterated(&hash, &user_password);
+
+        // Algorithm 2: computing the encryption key
+        let mut data = user_password.to_vec();
+        data.extend(&owner_value);
+        data.extend(&flags.to_le_bytes());
+        data.extend(&file_id);
+        let mut hash = md5(&data);
+        for _ in 0..50 {
+            hash = md5(&hash);
+        }
+        encryptor.key = hash;
+
+        // Algorithm 5: computing the U value
+        let mut data = PASSWORD_PADDING.to_vec();
+        data.extend(&file_id);
+        let mut user_value = rc4_iterated(&encryptor.key, &md5(&data));
+        user_value.extend(&[0; 16]);
+
+        let mut dict = lopdf::Dictionary::new();
+        dict.set("Filter", lopdf::Object::Name(b"Standard".to_vec()));
+        dict.set("Length", (KEY_LENGTH * 8) as i64);
+        dict.set(
+            "O",
+            lopdf::Object::String(owner_value, lopdf::StringFormat::Hexadecimal),
+        );
+        dict.set(
+            "U",
+            lopdf::Object::String(user_value, lopdf::StringFormat::Hexadecimal),
+        );
+        dict.set("P", i64::from(flags));
+        let version = match options.algorithm {
+            EncryptionAlgorithm::Rc4 => {
+                dict.set("V", 2);
+                dict.set("R", 3);
+                "1.4"
+            }
+            EncryptionAlgorithm::Aes128 => {
+                let mut filter = lopdf::Dictionary::new();
+                filter.set("Type", lopdf::Object::Name(b"CryptFilter".to_vec()));
+                filter.set("CFM", lopdf::Object::Name(b"AESV2".to_vec()));
+                filter.set("AuthEvent", lopdf::Object::Name(b"DocOpen".to_vec()));
+                filter.set("Length", KEY_LENGTH as i64);
+                let mut filters = lopdf::Dictionary::new();
+                filters.set("StdCF", filter);
+                dict.set("V", 4);
+                dict.set("R", 4);
+                dict.set("CF", filters);
+                dict.set("StmF", lopdf::Object::Name(b"StdCF".to_vec()));
+                dict.set("StrF", lopdf::Object::Name(b"StdCF".to_vec()));
+                "1.6"
+            }
+        };
+        if doc.version.as_str() < version {
+            doc.version = version.to_owned();
+        }
+        encryptor.encryption_id = doc.add_object(dict);
+        doc.trailer
+            .set("Encrypt", lopdf::Object::Reference(encryptor.encryption_id));
+        Ok(encryptor)
+    }
+
+    /// Encrypts all objects of the given document except for the encryption dictionary.
+    pub fn encrypt_document(&mut self, doc: &mut lopdf::Document) {
+        for (id, object) in doc.objects.iter_mut() {
+            self.encrypt_object(*id, object);
+        }
+    }
+
+    /// Encrypts all strings and streams in the given object, unless it is the encryption
+    /// dictionary or a cross-reference stream.
+    pub fn encrypt_object(&mut self, id: lopdf::ObjectId, object: &mut lopdf::Object) {
+        if id == self.encryption_id {
+            return;
+        }
+        match object {
+            lopdf::Object::String(s, format) => {
+                *s = self.encrypt(id, s);
+                *format = lopdf::StringFormat::Hexadecimal;
+            }
+            lopdf::Object::Array(array) => {
+                for object in array {
+                    self.encrypt_object(id, object);
+                }
+            }
+            lopdf::Object::Dictionary(dict) => {
+                for (_, object) in dict.iter_mut() {
+                    self.encrypt_object(id, object);
+                }
+            }
+            lopdf::Object::Stream(stream) => {
+                let is_xref = stream
+                    .dict
+                    .get(b"Type")
+                    .and_then(lopdf::Object::as_name)
+                    .map(|name| name == b"XRef")
+                    .unwrap_or(false);
+                if !is_xref {
+                    for (_, object) in stream.dict.iter_mut() {
+                        self.encrypt_object(id, object);
+                    }
+                    let content = self.encrypt(id, &stream.content);
+                    stream.set_content(content);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Encrypts the given data as part of the object with the given ID (algorithm 1).
+    fn encrypt(&mut self, id: lopdf::ObjectId, data: &[u8]) -> Vec<u8> {
+        let mut key = self.key.to_vec();
+        key.extend(&id.0.to_le_bytes()[..3]);
+        key.extend(&id.1.to_le_bytes());
+        if self.algorithm == EncryptionAlgorithm::Aes128 {
+            key.extend(b"sAlT");
+        }
+        // The object key has the length of the encryption key plus five bytes, but at most 16.
+        let key = md5(&key);
+        match self.algorithm {
+            EncryptionAlgorithm::Rc4 => rc4(&key, data),
+            EncryptionAlgorithm::Aes128 => {
+                let iv = self.random_block();
+                aes128_cbc(&key, &iv, data)
+            }
+        }
+    }
+
+    /// Returns 16 unpredictable bytes for an initialization vector.
+    ///
+    /// The blocks are generated by encrypting a counter with a random key, i. e. they are the key
+    /// stream of AES in CTR mode.
+    fn random_block(&mut self) -> [u8; 16] {
+        self.counter += 1;
+        let mut block = self.counter.to_le_bytes().into();
+        self.iv_cipher.encrypt_block(&mut block);
+        block.into()
+    }
+}
+
+/// Returns a random key from the random number generator of the operating system.
+fn random_key() -> Result<[u8; 16], Error> {
+    let mut key = [0; 16];
+    getrandom::getrandom(&mut key)
+        .map_err(io::Error::from)
+        .context("Failed to generate a random key for the encryption")?;
+    Ok(key)
+}
+
+/// Encodes the given password using Latin-1 and pads or truncates it to 32 bytes.
+fn pad_password(password: &str) -> Result<[u8; 32], Error> {
+    let mut bytes = Vec::with_capacity(32);
+    for c in password.chars() {
+        if u32::from(c) > 0xff {
+            return Err(Error::new(
+                format!("Unsupported character in PDF password: {}", c),
+                ErrorKind::UnsupportedEncoding,
+            ));
+        }
+        bytes.push(u32::from(c) as u8);
+    }
+    bytes.truncate(32);
+    let len = bytes.len();
+    bytes.extend(&PASSWORD_PADDING[..32 - len]);
+    let mut padded = [0; 32];
+    padded.copy_from_slice(&bytes);
+    Ok(padded)
+}
+
+/// Encrypts the given data 20 times with RC4, using the given key XOR the iteration number as the
+/// key for each iteration (see algorithms 3 and 5 of the PDF specification).
+fn rc4_iterated(key: &[u8; KEY_LENGTH], data: &[u8]) -> Vec<u8> {
+    let mut data = data.to_vec();
+    for i in 0..20 {
+        let mut key = *key;
+        for byte in key.iter_mut() {
+            *byte ^= i;
+        }
+        data = rc4(&key, &data);
+    }
+    data
+}
+
+/// Encrypts or decrypts the given data using the RC4 cipher with a 128-bit key.
+fn rc4(key: &[u8; KEY_LENGTH], data: &[u8]) -> Vec<u8> {
+    let mut data = data.to_vec();
+    rc4::Rc4::<rc4::consts::U16>::new(key.into()).apply_keystream(&mut data);
+    data
+}
+
+/// Encrypts the given data using AES with a 128-bit key in CBC mode with PKCS#5 padding and
+/// prepends the initialization vector.
+fn aes128_cbc(key: &[u8; KEY_LENGTH], iv: &[u8; 16], data: &[u8]) -> Vec<u8> {
+    let mut output = iv.to_vec();
+    output.extend(
+        cbc::Encryptor::<aes::Aes128>::new(key.into(), iv.into())
+            .encrypt_padded_vec_mut::<aes::cipher::block_padding::Pkcs7>(data),
+    );
+    output
+}
+
+/// Computes the MD5 hash of the given data.
+fn md5(data: &[u8]) -> [u8; 16] {
+    md5::Md5::digest(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::cipher::{BlockDecryptMut as _, KeyIvInit as _};
+
+    use super::{EncryptionAlgorithm, EncryptionOptions, Encryptor, Permissions};
+
+    fn hex(data: &[u8]) -> String {
+        data.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Creates an encryptor for a document with a fixed file ID and the passwords `user` and
+    /// `owner`.
+    fn encryptor(algorithm: EncryptionAlgorithm) -> (lopdf::Document, Encryptor) {
+        let mut doc = lopdf::Document::with_version("1.3");
+        let id = lopdf::Object::string_literal("genpdf-test-id01");
+        doc.trailer.set("ID", vec![id.clone(), id]);
+        let options = EncryptionOptions {
+            user_password: "user".to_owned(),
+            owner_password: "owner".to_owned(),
+            permissions: Permissions::all(),
+            algorithm,
+        };
+        let encryptor = Encryptor::new(&mut doc, &options).unwrap();
+        (doc, encryptor)
+    }
+
+    /// Returns the given string entry of the encryption dictionary as a hex string.
+    fn get_entry(doc: &lopdf::Document, encryptor: &Encryptor, key: &[u8]) -> String {
+        let dict = doc
+            .get_object(encryptor.encryption_id)
+            .and_then(lopdf::Object::as_dict)
+            .unwrap();
+        hex(dict.get(key).and_then(lopdf::Object::as_str).unwrap())
+    }
+
+    fn encrypt_string(encryptor: &mut Encryptor, id: lopdf::ObjectId, s: &str) -> Vec<u8> {
+        let mut object = lopdf::Object::string_literal(s);
+        encryptor.encrypt_object(id, &mut object);
+        object.as_str().unwrap().to_vec()
+    }
+
+    // The reference values were computed with an independent implementation of algorithms 1, 2, 3
+    // and 5 of the PDF specification.
+
+    #[test]
+    fn rc4() {
+        let (doc, mut encryptor) = encryptor(EncryptionAlgorithm::Rc4);
+        assert_eq!("1.4", doc.version);
+        assert_eq!(
+            "0ba3835f88f90388e74e54584125ce142be0de24c6b0d37746e075b891756671",
+            get_entry(&doc, &encryptor, b"O")
+        );
+        assert_eq!(
+            "67815c8b9812facfbc26d92a36954c8800000000000000000000000000000000",
+            get_entry(&doc, &encryptor, b"U")
+        );
+        assert_eq!(
+            "4e1cd2f07834ab96bfe79557ee",
+            hex(&encrypt_string(&mut encryptor, (7, 0), "Hello, World!"))
+        );
+    }
+
+    #[test]
+    fn aes128() {
+        let (doc, mut encryptor) = encryptor(EncryptionAlgorithm::Aes128);
+        assert_eq!("1.6", doc.version);
+        // The O and U values do not depend on the algorithm.
+        assert_eq!(
+            "0ba3835f88f90388e74e54584125ce142be0de24c6b0d37746e075b891756671",
+            get_entry(&doc, &encryptor, b"O")
+        );
+        assert_eq!(
+            "67815c8b9812facfbc26d92a36954c8800000000000000000000000000000000",
+            get_entry(&doc, &encryptor, b"U")
+        );
+
+        let first = encrypt_string(&mut encryptor, (7, 0), "Hello, World!");
+        let second = encrypt_string(&mut encryptor, (7, 0), "Hello, World!");
+        // The initialization vector is prepended and must not be reused.
+        assert_eq!(32, first.len());
+        assert_ne!(first[..16], second[..16]);
+
+        let key = unhex("7fa79bba8fc3758127fe44407e1fa1e4");
+        for data in &[first, second] {
+            let (iv, data) = data.split_at(16);
+            let decrypted = cbc::Decryptor::<aes::Aes128>::new(key[..].into(), iv.into())
+                .decrypt_padded_vec_mut::<aes::cipher::block_padding::Pkcs7>(data)
+                .unwrap();
+            assert_eq!(b"Hello, World!", decrypted.as_slice());
+        }
+    }
+
+    #[test]
+    fn permissions() {
+        assert_eq!(-4, Permissions::all().to_flags());
+        assert_eq!(-3904, Permissions::none().to_flags());
+    }
+}
@@ -44,12 +44,15 @@ impl<T, E: Into<ErrorKind>> Context<T> for Result<T, E> {
 
 /// An error that occured in a `genpdf` function.
 ///
-/// The error consists of an error message (provided by the `Display` implementation) and an error
-/// kind, see [`kind`](#method.kind).
+/// The error consists of an error message (provided by the `Display` implementation), an error
+/// kind, see [`kind`](#method.kind), and, if the error occured during the rendering process, the
+/// location of the element that caused the error, see [`location`](#method.location).
 #[derive(Debug)]
 pub struct Error {
     msg: String,
     kind: ErrorKind,
+    // The location is boxed to keep the size of the error and of results small.
+    location: Option<Box<Location>>,
 }
 
 impl Error {
@@ -58,6 +61,7 @@ impl Error {
         Error {
             msg: msg.into(),
             kind: kind.into(),
+            location: None,
         }
     }
 
@@ -65,11 +69,139 @@ impl Error {
     pub fn kind(&self) -> &ErrorKind {
         &self.kind
     }
+
+    /// Returns the location of the element that caused this error, if known.
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_deref()
+    }
+
+    /// Adds the given element to the location of this error and returns the error.
+    ///
+    /// Container elements should call this method for errors returned by their children, passing
+    /// the index of the child in the container and its [`Element::type_name`][].  The elements are
+    /// added from the innermost to the outermost element.
+    ///
+    /// [`Element::type_name`]: ../trait.Element.html#method.type_name
+    pub fn with_element(mut self, index: usize, type_name: &'static str) -> Error {
+        let element = ElementLocation { index, type_name };
+        let location = match self.location.take().map(|location| *location) {
+            None => Location::Element {
+                page: None,
+                elements: vec![element],
+            },
+            Some(Location::Page(page)) => Location::Element {
+                page: Some(page),
+                elements: vec![element],
+            },
+            Some(Location::Element { page, mut elements }) => {
+                elements.insert(0, element);
+                Location::Element { page, elements }
+            }
+        };
+        self.location = Some(Box::new(location));
+        self
+    }
+
+    /// Sets the page number (starting with 1) for the location of this error if it has not been
+    /// set yet and returns the error.
+    pub fn with_page(mut self, page: usize) -> Error {
+        match self.location.as_deref_mut() {
+            None => self.location = Some(Box::new(Location::Page(page))),
+            Some(Location::Page(_)) => {}
+            Some(Location::Element { page: p, .. }) => {
+                p.get_or_insert(page);
+            }
+        }
+        self
+    }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(&self.msg)
+        f.write_str(&self.msg)?;
+        if let Some(location) = &self.location {
+            write!(f, " ({})", location)?;
+        }
+        Ok(())
+    }
+}
+
+/// The location in the document that caused an [`Error`][], see [`Error::location`][].
+///
+/// [`Error`]: struct.Error.html
+/// [`Error::location`]: struct.Error.html#method.location
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Location {
+    /// The error occured on the page with the given number (starting with 1), but it was not
+    /// caused by an element, for example if the page decorator failed.
+    Page(usize),
+    /// The error was caused by an element.
+    Element {
+        /// The number of the page (starting with 1) that was rendered when the error occured, if
+        /// known.
+        page: Option<usize>,
+        /// The path to the element, starting with the children of the document.
+        elements: Vec<ElementLocation>,
+    },
+}
+
+impl Location {
+    /// Returns the number of the page (starting with 1) that was rendered when the error occured,
+    /// if known.
+    pub fn page(&self) -> Option<usize> {
+        match self {
+            Location::Page(page) => Some(*page),
+            Location::Element { page, .. } => *page,
+        }
+    }
+
+    /// Returns the path to the element that caused the error, starting with the children of the
+    /// document.  The path is empty if the error was not caused by an element.
+    pub fn elements(&self) -> &[ElementLocation] {
+        match self {
+            Location::Page(_) => &[],
+            Location::Element { elements, .. } => elements,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(page) = self.page() {
+            write!(f, "page {}", page)?;
+            if !self.elements().is_empty() {
+                f.write_str(", ")?;
+            }
+        }
+        for (i, element) in self.elements().iter().enumerate() {
+            if i > 0 {
+                f.write_str(" > ")?;
+            }
+            write!(f, "{}", element)?;
+        }
+        Ok(())
+    }
+}
+
+/// An element in the path of a [`Location`][].
+///
+/// [`Location`]: struct.Location.html
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElementLocation {
+    /// The index of the element in its parent.
+    ///
+    /// For tables, this is the index of the cell counted row by row.
+    pub index: usize,
+    /// The type name of the element, see [`Element::type_name`][].
+    ///
+    /// [`Element::type_name`]: ../trait.Element.html#method.type_name
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for ElementLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "element {} ({})", self.index, self.type_name)
     }
 }
 
@@ -81,9 +213,13 @@ impl error::Error for Error {
             ErrorKind::InvalidFont => None,
             ErrorKind::PageSizeExceeded => None,
             ErrorKind::UnsupportedEncoding => None,
+            ErrorKind::AlreadyRendered => None,
+            ErrorKind::UnsupportedFeature => None,
+            ErrorKind::FontNotFound => None,
             ErrorKind::IoError(err) => Some(err),
             ErrorKind::PdfError(err) => Some(err),
             ErrorKind::PdfIndexError(err) => Some(err),
+            ErrorKind::LopdfError(err) => Some(err),
             ErrorKind::RusttypeError(err) => Some(err),
             #[cfg(feature = "images")]
             ErrorKind::ImageError(err) => Some(err),
@@ -105,12 +241,21 @@ pub enum ErrorKind {
     PageSizeExceeded,
     /// A string with unsupported characters was used with a built-in font.
     UnsupportedEncoding,
+    /// A document was rendered a second time.
+    AlreadyRendered,
+    /// A feature was used that is not allowed by the conformance level of the document, see
+    /// [`Conformance`](../enum.Conformance.html).
+    UnsupportedFeature,
+    /// A font could not be found on the system.
+    FontNotFound,
     /// An IO error.
     IoError(io::Error),
     /// An error caused by invalid data in `printpdf`.
     PdfError(printpdf::PdfError),
     /// An error caused by an invalid index in `printpdf`.
     PdfIndexError(printpdf::IndexError),
+    /// An error caused by `lopdf` while post-processing the generated PDF document.
+    LopdfError(lopdf::Error),
     /// An error caused by `rusttype`.
     RusttypeError(rusttype::Error),
     /// An error caused by `image`.
@@ -149,6 +294,12 @@ impl From<printpdf::PdfError> for ErrorKind {
     }
 }
 
+impl From<lopdf::Error> for ErrorKind {
+    fn from(error: lopdf::Error) -> ErrorKind {
+        ErrorKind::LopdfError(error)
+    }
+}
+
 impl From<rusttype::Error> for ErrorKind {
     fn from(error: rusttype::Error) -> ErrorKind {
         ErrorKind::RusttypeError(error)
@@ -4,9 +4,18 @@
 //! Fonts, font families and a font cache.
 //!
 //! Before you can use a font in a PDF document, you have to load the [`FontData`][] for it, either
-//! from a file ([`FontData::load`][]) or from bytes ([`FontData::new`][]).  See the [`rusttype`][]
-//! crate for the supported data formats.  Use the [`from_files`][] function to load a font family
-//! from a set of files following the default naming conventions.
+//! from a file ([`FontData::load`][]) or from bytes ([`FontData::new`][],
+//! [`FontData::from_bytes`][]).  See the [`rusttype`][] crate for the supported data formats.
+//! TrueType fonts (`.ttf`) and OpenType fonts with CFF outlines (`.otf`) are supported.  To load a
+//! font from a font collection (`.ttc`), use [`FontData::load_from_collection`][] or
+//! [`FontData::new_from_collection`][] with the index of the font in the collection.  Variable
+//! fonts are supported by creating a static instance for the selected axis values or named
+//! instance, see [`FontData::new_instance`][] and [`FontFamily::from_variable_font`][].  Use the
+//! [`from_files`][] function to load a font family from a set of files following the default naming
+//! conventions, or [`FontFamily::from_bytes`][] to load a font family from bytes, for example from
+//! fonts that are embedded into the binary with [`include_bytes!`][].  This does not require a font
+//! directory on the file system.  If the `system-fonts` feature is enabled, you can also use the
+//! [`find_system_font`][] function to load an installed font family by its name.
 //!
 //! The [`FontCache`][] caches all loaded fonts.  A [`Font`][] is a reference to a cached font in
 //! the [`FontCache`][].  A [`FontFamily`][] is a collection of a regular, a bold, an italic and a
@@ -16,19 +25,33 @@
 //! returns a reference to the cached data that you then can use with the [`Style`][] struct to
 //! change the font family of an element.
 //!
+//! Characters that are not supported by a font are rendered with the replacement glyph of the font.
+//! To avoid this, you can register fallback font families with
+//! [`Document::add_fallback_font_family`][], for example for CJK characters or emoji.  Characters
+//! that are missing in the font of a string are then rendered with the first fallback font family
+//! that supports them.
+//!
 //! There are two methods for using fonts in a PDF font:  You can either embed the font data into
 //! the PDF file.  Or you can use one of the three built-in font families ([`Builtin`][]) that PDF
 //! viewers are expected to support.  You can choose between the two methods when loading the font
 //! ([`from_files`][], [`FontData::load`][], [`FontData::new`][]).
 //!
-//! If you choose a built-in font family, you still have to provide the font data so that `genpdf`
-//! has access to its glyph metrics.  Note that it is sufficient to use a font that is metrically
-//! identical to the built-in font.  For example, you can use the Liberation fonts instad of the
-//! proprietary Helvetica, Times and Courier fonts.
+//! Use the [`from_builtin`][] function to load a built-in font family without any font files.  In
+//! this case, `genpdf` uses the glyph metrics of the standard PDF fonts that are bundled with this
+//! crate.  Kerning is not supported for these fonts.  Alternatively, you can provide the font data
+//! so that `genpdf` has access to its glyph metrics.  Note that it is sufficient to use a font
+//! that is metrically identical to the built-in font.  For example, you can use the Liberation
+//! fonts instad of the proprietary Helvetica, Times and Courier fonts.
 //!
 //! Built-in fonts can only be used with characters that are supported by the [Windows-1252][]
 //! encoding.
 //!
+//! If the `shaping` feature is enabled, text printed with embedded fonts is shaped using
+//! [`rustybuzz`][] so that ligatures, contextual forms and mark positioning are applied, see
+//! [`Font::shape`][].  This is required for complex scripts like Arabic, Devanagari or Thai.
+//! Vertical glyph offsets are not supported.  Without the `shaping` feature, only kerning is
+//! applied.
+//!
 //! **Note:**  The [`Font`][] and [`FontFamily<Font>`][`FontFamily`] structs are only valid for the
 //! [`FontCache`][] they have been created with.  If you dont use the low-level [`render`][] module
 //! directly, only use the [`Document::add_font_family`][] method to add fonts!
@@ -48,29 +71,52 @@
 //! [`render`]: ../render/
 //! [`Document`]: ../struct.Document.html
 //! [`Document::add_font_family`]: ../struct.Document.html#method.add_font_family
+//! [`Document::add_fallback_font_family`]: ../struct.Document.html#method.add_fallback_font_family
 //! [`Style`]: ../style/struct.Style.html
 //! [`from_files`]: fn.from_files.html
+//! [`from_builtin`]: fn.from_builtin.html
+//! [`find_system_font`]: fn.find_system_font.html
 //! [`Builtin`]: enum.Builtin.html
 //! [`FontCache`]: struct.FontCache.html
 //! [`FontCache::load_pdf_fonts`]: struct.FontCache.html#method.load_pdf_fonts
 //! [`FontData`]: struct.FontData.html
 //! [`FontData::new`]: struct.FontData.html#method.new
 //! [`FontData::load`]: struct.FontData.html#method.load
+//! [`FontData::from_bytes`]: struct.FontData.html#method.from_bytes
+//! [`FontData::new_from_collection`]: struct.FontData.html#method.new_from_collection
+//! [`FontData::load_from_collection`]: struct.FontData.html#method.load_from_collection
+//! [`FontData::new_instance`]: struct.FontData.html#method.new_instance
+//! [`FontFamily::from_variable_font`]: struct.FontFamily.html#method.from_variable_font
+//! [`FontFamily::from_bytes`]: struct.FontFamily.html#method.from_bytes
+//! [`include_bytes!`]: https://doc.rust-lang.org/std/macro.include_bytes.html
 //! [`Font`]: struct.Font.html
+//! [`Font::shape`]: struct.Font.html#method.shape
 //! [`FontFamily`]: struct.FontFamily.html
 //! [`rusttype`]: https://docs.rs/rusttype
+//! [`rustybuzz`]: https://docs.rs/rustybuzz
 //! [`rusttype::Font`]: https://docs.rs/rusttype/0.8.3/rusttype/struct.Font.html
 //! [`printpdf`]: https://docs.rs/printpdf
 //! [`printpdf::IndirectFontRef`]: https://docs.rs/printpdf/0.3.2/printpdf/types/plugins/graphics/two_dimensional/font/struct.IndirectFontRef.html
 //! [Windows-1252]: https://en.wikipedia.org/wiki/Windows-1252
 
+mod afm;
+mod instance;
+pub(crate) mod sfnt;
+pub(crate) mod subset;
+
+use std::cell;
+use std::collections;
 use std::fmt;
 use std::fs;
+use std::io;
 use std::path;
 
+#[cfg(feature = "shaping")]
+use crate::bidi;
 use crate::error::{Context as _, Error, ErrorKind};
 use crate::render;
 use crate::style::Style;
+use crate::wrap;
 use crate::Mm;
 
 /// Stores font data that can be referenced by a [`Font`][] or [`FontFamily`][].
@@ -89,6 +135,9 @@ pub struct FontCache {
     // a font, but the default font is always loaded in new, so this options is always some
     // (outside of new).
     default_font_family: Option<FontFamily<Font>>,
+    fallback_font_families: Vec<FontFamily<Font>>,
+    missing_glyphs: cell::RefCell<collections::BTreeSet<char>>,
+    used_glyphs: cell::RefCell<collections::BTreeMap<usize, collections::BTreeSet<u16>>>,
 }
 
 impl FontCache {
@@ -98,20 +147,36 @@ impl FontCache {
             fonts: Vec::new(),
             pdf_fonts: Vec::new(),
             default_font_family: None,
+            fallback_font_families: Vec::new(),
+            missing_glyphs: Default::default(),
+            used_glyphs: Default::default(),
         };
         font_cache.default_font_family = Some(font_cache.add_font_family(default_font_family));
         font_cache
     }
 
     /// Adds the given font to the cache and returns a reference to it.
+    ///
+    /// If the cache already contains an embedded font with the same data, a reference to the
+    /// existing font is returned so that the font is only embedded once.
     pub fn add_font(&mut self, font_data: FontData) -> Font {
-        let is_builtin = match &font_data.raw_data {
-            RawFontData::Builtin(_) => true,
-            RawFontData::Embedded(_) => false,
+        let (idx, is_builtin) = match &font_data.raw_data {
+            RawFontData::Builtin(_) => (None, true),
+            RawFontData::Embedded(data) => {
+                let idx = self.fonts.iter().position(|font| match &font.raw_data {
+                    RawFontData::Embedded(other) => other == data,
+                    RawFontData::Builtin(_) => false,
+                });
+                (idx, false)
+            }
         };
-        let font = Font::new(self.fonts.len(), is_builtin, &font_data.rt_font);
-        self.fonts.push(font_data);
-        font
+        if let Some(idx) = idx {
+            Font::new(idx, is_builtin, &self.fonts[idx])
+        } else {
+            let font = Font::new(self.fonts.len(), is_builtin, &font_data);
+            self.fonts.push(font_data);
+            font
+        }
     }
 
     /// Adds the given font family to the cache and returns a reference to it.
@@ -124,6 +189,48 @@ impl FontCache {
         }
     }
 
+    /// Adds the given font family to the cache, registers it as a fallback font family and returns
+    /// a reference to it.
+    ///
+    /// Characters that are not supported by the font of a string are rendered with the first
+    /// fallback font family that supports them, see [`font_for_char`][].  The fallback font
+    /// families are tried in the order they have been added.
+    ///
+    /// [`font_for_char`]: #method.font_for_char
+    pub fn add_fallback_font_family(&mut self, family: FontFamily<FontData>) -> FontFamily<Font> {
+        let family = self.add_font_family(family);
+        self.fallback_font_families.push(family);
+        family
+    }
+
+    /// Returns the registered fallback font families in the order they are tried.
+    pub fn fallback_font_families(&self) -> &[FontFamily<Font>] {
+        &self.fallback_font_families
+    }
+
+    /// Returns the font that should be used to print the given character with the given font and
+    /// style.
+    ///
+    /// If the given font does not contain a glyph for the character, the font for the given style
+    /// of the first fallback font family that contains a glyph is returned.  If no fallback font
+    /// contains a glyph either, the given font is returned.
+    ///
+    /// This method may only be called with [`Font`][] instances that have been created by this
+    /// font cache.
+    ///
+    /// [`Font`]: struct.Font.html
+    pub fn font_for_char(&self, font: Font, style: Style, c: char) -> Font {
+        if self.fallback_font_families.is_empty() || font.has_glyph(self, c) {
+            font
+        } else {
+            self.fallback_font_families
+                .iter()
+                .map(|family| family.get(style))
+                .find(|fallback| fallback.has_glyph(self, c))
+                .unwrap_or(font)
+        }
+    }
+
     /// Embeds all loaded fonts into the document generated by the given renderer and caches a
     /// reference to them.
     pub fn load_pdf_fonts(&mut self, renderer: &render::Renderer) -> Result<(), Error> {
@@ -131,13 +238,72 @@ impl FontCache {
         for font in &self.fonts {
             let pdf_font = match &font.raw_data {
                 RawFontData::Builtin(builtin) => renderer.add_builtin_font(*builtin)?,
-                RawFontData::Embedded(data) => renderer.add_embedded_font(&data)?,
+                RawFontData::Embedded(data) => renderer.add_embedded_font(data)?,
             };
             self.pdf_fonts.push(pdf_font);
         }
         Ok(())
     }
 
+    /// Registers subsets of all embedded fonts with the given renderer so that the embedded font
+    /// data only contains the glyphs that have been printed with them.
+    ///
+    /// This method should be called after all text has been printed.  Embedded fonts that have
+    /// not been used at all only contain the replacement glyph.  See
+    /// [`Renderer::add_font_subset`][] for more information.
+    ///
+    /// [`Renderer::add_font_subset`]: ../render/struct.Renderer.html#method.add_font_subset
+    pub fn subset_pdf_fonts(&self, renderer: &mut render::Renderer) {
+        let used_glyphs = self.used_glyphs.borrow();
+        for (idx, font) in self.fonts.iter().enumerate() {
+            if let RawFontData::Embedded(data) = &font.raw_data {
+                let glyph_ids = used_glyphs.get(&idx).cloned().unwrap_or_default();
+                renderer.add_font_subset(data, glyph_ids);
+            }
+        }
+    }
+
+    /// Returns the IDs of all glyphs that have been printed with the given font.
+    ///
+    /// This method may only be called with [`Font`][] instances that have been created by this
+    /// font cache.  Glyphs of built-in fonts are not recorded.
+    ///
+    /// [`Font`]: struct.Font.html
+    pub fn used_glyphs(&self, font: Font) -> collections::BTreeSet<u16> {
+        self.used_glyphs
+            .borrow()
+            .get(&font.idx)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Records that the given glyphs have been printed with the given font.
+    pub(crate) fn add_used_glyphs(&self, font: Font, glyph_ids: &[u16]) {
+        self.used_glyphs
+            .borrow_mut()
+            .entry(font.idx)
+            .or_default()
+            .extend(glyph_ids);
+    }
+
+    /// Returns all characters that have been printed with a font that does not contain a glyph
+    /// for them.
+    ///
+    /// These characters are rendered using the replacement glyph of the font (typically an empty
+    /// box).  Characters that cannot be encoded for a built-in font cause an error instead, see
+    /// [`ErrorKind::UnsupportedEncoding`][].
+    ///
+    /// [`ErrorKind::UnsupportedEncoding`]: ../error/enum.ErrorKind.html#variant.UnsupportedEncoding
+    pub fn missing_glyphs(&self) -> collections::BTreeSet<char> {
+        self.missing_glyphs.borrow().clone()
+    }
+
+    /// Records that the given character has been printed with a font that does not contain a
+    /// glyph for it.
+    pub(crate) fn add_missing_glyph(&self, c: char) {
+        self.missing_glyphs.borrow_mut().insert(c);
+    }
+
     /// Returns the default font family for this font cache.
     pub fn default_font_family(&self) -> FontFamily<Font> {
         self.default_font_family
@@ -158,11 +324,19 @@ impl FontCache {
     /// Returns a reference to the Rusttype font for the given font, if available.
     ///
     /// This method may only be called with [`Font`][] instances that have been created by this
-    /// font cache.
+    /// font cache.  Built-in fonts loaded with [`from_builtin`][] don’t have a Rusttype font.
     ///
     /// [`Font`]: struct.Font.html
-    pub fn get_rt_font(&self, font: Font) -> &rusttype::Font<'static> {
-        &self.fonts[font.idx].rt_font
+    /// [`from_builtin`]: fn.from_builtin.html
+    pub fn get_rt_font(&self, font: Font) -> Option<&rusttype::Font<'static>> {
+        match &self.fonts[font.idx].glyphs {
+            Glyphs::Rusttype(rt_font) => Some(rt_font),
+            Glyphs::Builtin(_) => None,
+        }
+    }
+
+    fn get_glyphs(&self, font: Font) -> &Glyphs {
+        &self.fonts[font.idx].glyphs
     }
 }
 
@@ -171,8 +345,10 @@ impl FontCache {
 /// [`FontCache`]: struct.FontCache.html
 #[derive(Clone, Debug)]
 pub struct FontData {
-    rt_font: rusttype::Font<'static>,
+    glyphs: Glyphs,
     raw_data: RawFontData,
+    underline: (f32, f32),
+    strikethrough: (f32, f32),
 }
 
 impl FontData {
@@ -183,13 +359,50 @@ impl FontData {
     /// documentation](index.html) for more information).  In this case, the given font must be
     /// metrically identical to the built-in font.
     ///
+    /// If the data is a font collection, the first font of the collection is used.  See
+    /// [`new_from_collection`][] for loading other fonts from a collection.
+    ///
     /// [`rusttype`]: https://docs.rs/rusttype
+    /// [`new_from_collection`]: #method.new_from_collection
     pub fn new(data: Vec<u8>, builtin: Option<printpdf::BuiltinFont>) -> Result<FontData, Error> {
+        FontData::new_from_collection(data, 0, builtin)
+    }
+
+    /// Loads the font with the given index from the given font collection data.
+    ///
+    /// The font is extracted from the collection so that only the data for this font is embedded
+    /// in the PDF file.  If the data is a single font instead of a collection, the index must be
+    /// zero.  If the collection does not contain a font with the given index, an error of the kind
+    /// [`ErrorKind::InvalidFont`][] is returned.  See [`new`][] for more information.
+    ///
+    /// [`new`]: #method.new
+    /// [`ErrorKind::InvalidFont`]: ../error/enum.ErrorKind.html#variant.InvalidFont
+    pub fn new_from_collection(
+        data: Vec<u8>,
+        index: u32,
+        builtin: Option<printpdf::BuiltinFont>,
+    ) -> Result<FontData, Error> {
+        let data = if sfnt::is_collection(&data) {
+            sfnt::extract_font(&data, index)
+        } else {
+            Some(data).filter(|_| index == 0)
+        }
+        .ok_or_else(|| {
+            Error::new(
+                format!(
+                    "The font collection does not contain a font with index {}",
+                    index
+                ),
+                ErrorKind::InvalidFont,
+            )
+        })?;
         let raw_data = if let Some(builtin) = builtin {
             RawFontData::Builtin(builtin)
         } else {
             RawFontData::Embedded(data.clone())
         };
+        let underline = read_underline(&data);
+        let strikethrough = read_strikethrough(&data);
         let rt_font = rusttype::Font::from_bytes(data).context("Failed to read rusttype font")?;
         if rt_font.units_per_em() == 0 {
             Err(Error::new(
@@ -197,10 +410,47 @@ impl FontData {
                 ErrorKind::InvalidFont,
             ))
         } else {
-            Ok(FontData { rt_font, raw_data })
+            // If the font does not provide the metrics, we use typical values
+            let units_per_em = f32::from(rt_font.units_per_em());
+            Ok(FontData {
+                glyphs: Glyphs::Rusttype(rt_font),
+                raw_data,
+                underline: underline.unwrap_or((-0.1 * units_per_em, 0.05 * units_per_em)),
+                strikethrough: strikethrough.unwrap_or((0.25 * units_per_em, 0.05 * units_per_em)),
+            })
         }
     }
 
+    /// Loads a font from the given bytes.
+    ///
+    /// This method is a convenience wrapper for [`new`][] that copies the given data so that it
+    /// can be used with static data, for example fonts that are embedded with
+    /// [`include_bytes!`][].
+    ///
+    /// [`new`]: #method.new
+    /// [`include_bytes!`]: https://doc.rust-lang.org/std/macro.include_bytes.html
+    pub fn from_bytes(
+        data: &[u8],
+        builtin: Option<printpdf::BuiltinFont>,
+    ) -> Result<FontData, Error> {
+        FontData::new(data.to_vec(), builtin)
+    }
+
+    /// Creates the font data for the given built-in font using the bundled glyph metrics, if
+    /// available.
+    fn builtin(builtin: printpdf::BuiltinFont) -> Option<FontData> {
+        afm::Metrics::get(builtin).map(|metrics| {
+            // The AFM files don’t specify a strikethrough line, so we center it on the x-height
+            let thickness = f32::from(metrics.underline_thickness());
+            FontData {
+                glyphs: Glyphs::Builtin(metrics),
+                raw_data: RawFontData::Builtin(builtin),
+                underline: (f32::from(metrics.underline_position()), thickness),
+                strikethrough: (f32::from(metrics.x_height()) / 2.0, thickness),
+            }
+        })
+    }
+
     /// Loads the font at the given path.
     ///
     /// The path must point to a file that can be read by [`rusttype`][].  If `builtin` is set, a
@@ -217,6 +467,144 @@ impl FontData {
             .with_context(|| format!("Failed to open font file {}", path.as_ref().display()))?;
         FontData::new(data, builtin)
     }
+
+    /// Loads the font with the given index from the font collection at the given path.
+    ///
+    /// See [`load`][] and [`new_from_collection`][] for more information.
+    ///
+    /// [`load`]: #method.load
+    /// [`new_from_collection`]: #method.new_from_collection
+    pub fn load_from_collection(
+        path: impl AsRef<path::Path>,
+        index: u32,
+        builtin: Option<printpdf::BuiltinFont>,
+    ) -> Result<FontData, Error> {
+        let data = fs::read(path.as_ref())
+            .with_context(|| format!("Failed to open font file {}", path.as_ref().display()))?;
+        FontData::new_from_collection(data, index, builtin)
+    }
+
+    /// Loads a static instance of the given variable font with the given axis values.
+    ///
+    /// The axes are identified by their four-letter tag, for example `wght` for the weight and
+    /// `wdth` for the width.  Values outside of the range of an axis are clamped, and axes that
+    /// are not set use their default value.  PDF does not support variable fonts, so the glyph
+    /// variations for the selected values are applied to the outlines and only the resulting
+    /// static font is embedded.  See [`new`][] for more information.
+    ///
+    /// Only variable fonts with TrueType outlines are supported.  The variations of the hinting
+    /// instructions and of the font-wide metrics, for example the ascent, are ignored.  If the
+    /// font is not a variable font or does not have one of the given axes, an error of the kind
+    /// [`ErrorKind::InvalidFont`][] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use genpdf::fonts;
+    /// let data = std::fs::read("./fonts/Inter.ttf").expect("Failed to read font file");
+    /// let semibold = fonts::FontData::new_instance(data, &[("wght", 600.0)], None)
+    ///     .expect("Failed to load font instance");
+    /// ```
+    ///
+    /// [`new`]: #method.new
+    /// [`ErrorKind::InvalidFont`]: ../error/enum.ErrorKind.html#variant.InvalidFont
+    pub fn new_instance(
+        data: Vec<u8>,
+        axes: &[(&str, f32)],
+        builtin: Option<printpdf::BuiltinFont>,
+    ) -> Result<FontData, Error> {
+        let font_axes = instance::axes(&data)
+            .ok_or_else(|| Error::new("The font is not a variable font", ErrorKind::InvalidFont))?;
+        let values = axes
+            .iter()
+            .map(|(tag, value)| {
+                font_axes
+                    .iter()
+                    .find(|axis| axis.tag == tag.as_bytes())
+                    .map(|axis| (axis.tag, *value))
+                    .ok_or_else(|| {
+                        Error::new(
+                            format!("The font does not have the variation axis {}", tag),
+                            ErrorKind::InvalidFont,
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        FontData::instantiate(&data, &values, builtin)
+    }
+
+    /// Loads the named instance with the given name of the given variable font.
+    ///
+    /// The name is the subfamily name of the instance as defined by the font, for example `Bold`
+    /// or `Condensed Light`.  Names are compared case-insensitively.  If the font does not have an
+    /// instance with the given name, an error of the kind [`ErrorKind::InvalidFont`][] is returned.
+    /// See [`new_instance`][] for more information.
+    ///
+    /// [`new_instance`]: #method.new_instance
+    /// [`ErrorKind::InvalidFont`]: ../error/enum.ErrorKind.html#variant.InvalidFont
+    pub fn new_named_instance(
+        data: Vec<u8>,
+        name: &str,
+        builtin: Option<printpdf::BuiltinFont>,
+    ) -> Result<FontData, Error> {
+        let values = instance::named_instance(&data, name).ok_or_else(|| {
+            Error::new(
+                format!("The font does not have a named instance {}", name),
+                ErrorKind::InvalidFont,
+            )
+        })?;
+        FontData::instantiate(&data, &values, builtin)
+    }
+
+    fn instantiate(
+        data: &[u8],
+        values: &[([u8; 4], f32)],
+        builtin: Option<printpdf::BuiltinFont>,
+    ) -> Result<FontData, Error> {
+        if instance::axes(data).is_none() {
+            return Err(Error::new(
+                "The font is not a variable font",
+                ErrorKind::InvalidFont,
+            ));
+        }
+        let data = instance::instantiate(data, values).ok_or_else(|| {
+            Error::new(
+                "Failed to create an instance of the variable font",
+                ErrorKind::InvalidFont,
+            )
+        })?;
+        FontData::new(data, builtin)
+    }
+}
+
+/// The source of the glyph metrics of a font.
+#[derive(Clone, Debug)]
+enum Glyphs {
+    Rusttype(rusttype::Font<'static>),
+    Builtin(&'static afm::Metrics),
+}
+
+impl Glyphs {
+    /// Returns the unscaled units per em, ascent, descent and line gap of the font.
+    fn v_metrics(&self) -> (f32, f32, f32, f32) {
+        match self {
+            Glyphs::Rusttype(rt_font) => {
+                let v_metrics = rt_font.v_metrics_unscaled();
+                (
+                    f32::from(rt_font.units_per_em()),
+                    v_metrics.ascent,
+                    v_metrics.descent,
+                    v_metrics.line_gap,
+                )
+            }
+            Glyphs::Builtin(metrics) => (
+                f32::from(metrics.units_per_em()),
+                f32::from(metrics.ascent()),
+                f32::from(metrics.descent()),
+                0.0,
+            ),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -321,6 +709,102 @@ impl<T: Clone + Copy + fmt::Debug + PartialEq> FontFamily<T> {
     }
 }
 
+impl FontFamily<FontData> {
+    /// Loads a font family from the given bytes for the regular, bold, italic and bold italic
+    /// fonts.
+    ///
+    /// In contrast to [`from_files`][], this method does not require the fonts to be stored in a
+    /// directory on the file system, so it can be used with fonts that are embedded with
+    /// [`include_bytes!`][].  If `builtin` is set, built-in PDF fonts are used instead of
+    /// embedding the fonts in the PDF file (see the [module documentation](index.html) for more
+    /// information).  In this case, the given fonts must be metrically identical to the built-in
+    /// fonts.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use genpdf::fonts;
+    ///
+    /// // static REGULAR: &[u8] = include_bytes!("../fonts/LiberationSans-Regular.ttf");
+    /// // ...
+    /// # static REGULAR: &[u8] = &[];
+    /// # static BOLD: &[u8] = &[];
+    /// # static ITALIC: &[u8] = &[];
+    /// # static BOLD_ITALIC: &[u8] = &[];
+    /// let font_family = fonts::FontFamily::from_bytes(REGULAR, BOLD, ITALIC, BOLD_ITALIC, None)
+    ///     .expect("Failed to load font family");
+    /// let doc = genpdf::Document::new(font_family);
+    /// ```
+    ///
+    /// [`from_files`]: fn.from_files.html
+    /// [`include_bytes!`]: https://doc.rust-lang.org/std/macro.include_bytes.html
+    pub fn from_bytes(
+        regular: &[u8],
+        bold: &[u8],
+        italic: &[u8],
+        bold_italic: &[u8],
+        builtin: Option<Builtin>,
+    ) -> Result<FontFamily<FontData>, Error> {
+        let from_bytes = |data: &[u8], style: FontStyle| {
+            FontData::from_bytes(data, builtin.map(|b| b.style(style)))
+        };
+        Ok(FontFamily {
+            regular: from_bytes(regular, FontStyle::Regular)?,
+            bold: from_bytes(bold, FontStyle::Bold)?,
+            italic: from_bytes(italic, FontStyle::Italic)?,
+            bold_italic: from_bytes(bold_italic, FontStyle::BoldItalic)?,
+        })
+    }
+
+    /// Loads a font family from the given variable font.
+    ///
+    /// The regular and bold fonts are instances of the given font with the weights 400 and 700
+    /// (`wght` axis).  If the italic styles are provided by a separate variable font, pass it as
+    /// `italic`.  Otherwise, the italic fonts are created from the given font, too.  For the
+    /// italic fonts, the `ital` axis is set to 1 if the font has this axis.  See
+    /// [`FontData::new_instance`][] for more information.  If `builtin` is set, built-in PDF
+    /// fonts are used instead of embedding the fonts in the PDF file (see the [module
+    /// documentation](index.html) for more information).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use genpdf::fonts;
+    /// let upright = std::fs::read("./fonts/Inter.ttf").expect("Failed to read font file");
+    /// let italic = std::fs::read("./fonts/Inter-Italic.ttf").expect("Failed to read font file");
+    /// let font_family = fonts::FontFamily::from_variable_font(&upright, Some(&italic), None)
+    ///     .expect("Failed to load font family");
+    /// let doc = genpdf::Document::new(font_family);
+    /// ```
+    ///
+    /// [`FontData::new_instance`]: struct.FontData.html#method.new_instance
+    pub fn from_variable_font(
+        data: &[u8],
+        italic: Option<&[u8]>,
+        builtin: Option<Builtin>,
+    ) -> Result<FontFamily<FontData>, Error> {
+        // Axes that are not supported by the font are ignored by instance::instantiate.
+        let instance = |data: &[u8], values: &[([u8; 4], f32)], style: FontStyle| {
+            FontData::instantiate(data, values, builtin.map(|b| b.style(style)))
+        };
+        let italic = italic.unwrap_or(data);
+        Ok(FontFamily {
+            regular: instance(data, &[(*b"wght", 400.0)], FontStyle::Regular)?,
+            bold: instance(data, &[(*b"wght", 700.0)], FontStyle::Bold)?,
+            italic: instance(
+                italic,
+                &[(*b"wght", 400.0), (*b"ital", 1.0)],
+                FontStyle::Italic,
+            )?,
+            bold_italic: instance(
+                italic,
+                &[(*b"wght", 700.0), (*b"ital", 1.0)],
+                FontStyle::BoldItalic,
+            )?,
+        })
+    }
+}
+
 /// A reference to a font cached by a [`FontCache`][].
 ///
 /// See the [module documentation](index.html) for details on the internals.
@@ -334,20 +818,20 @@ pub struct Font {
     line_height: Mm,
     glyph_height: Mm,
     ascent: Mm,
+    underline: LineMetrics,
+    strikethrough: LineMetrics,
 }
 
 impl Font {
-    fn new(idx: usize, is_builtin: bool, rt_font: &rusttype::Font<'static>) -> Font {
-        let units_per_em = rt_font.units_per_em();
-        assert!(units_per_em != 0);
+    fn new(idx: usize, is_builtin: bool, font_data: &FontData) -> Font {
+        let (units_per_em, ascent, descent, line_gap) = font_data.glyphs.v_metrics();
+        assert!(units_per_em != 0.0);
 
-        let units_per_em = f32::from(units_per_em);
-        let v_metrics = rt_font.v_metrics_unscaled();
-        let glyph_height = (v_metrics.ascent - v_metrics.descent) / units_per_em;
+        let glyph_height = (ascent - descent) / units_per_em;
         let scale = rusttype::Scale::uniform(glyph_height);
 
-        let ascent = v_metrics.ascent / units_per_em;
-        let line_height = glyph_height + v_metrics.line_gap / units_per_em;
+        let ascent = ascent / units_per_em;
+        let line_height = glyph_height + line_gap / units_per_em;
 
         Font {
             idx,
@@ -356,6 +840,8 @@ impl Font {
             line_height: printpdf::Pt(f64::from(line_height)).into(),
             glyph_height: printpdf::Pt(f64::from(glyph_height)).into(),
             ascent: printpdf::Pt(f64::from(ascent)).into(),
+            underline: LineMetrics::from_units(font_data.underline, units_per_em),
+            strikethrough: LineMetrics::from_units(font_data.strikethrough, units_per_em),
         }
     }
 
@@ -364,6 +850,25 @@ impl Font {
         self.is_builtin
     }
 
+    /// Returns whether this font contains a glyph for the given character.
+    ///
+    /// Built-in fonts only support characters that can be encoded with the [Windows-1252][]
+    /// encoding.
+    ///
+    /// The given [`FontCache`][] must be the font cache that loaded this font.
+    ///
+    /// [`FontCache`]: struct.FontCache.html
+    /// [Windows-1252]: https://en.wikipedia.org/wiki/Windows-1252
+    pub fn has_glyph(&self, font_cache: &FontCache, c: char) -> bool {
+        if self.is_builtin && afm::encode(c).is_none() {
+            return false;
+        }
+        match font_cache.get_glyphs(*self) {
+            Glyphs::Rusttype(rt_font) => rt_font.glyph(c).id().0 != 0,
+            Glyphs::Builtin(_) => true,
+        }
+    }
+
     /// Returns the line height for text with this font and the given font size.
     pub fn get_line_height(&self, font_size: u8) -> Mm {
         self.line_height * f64::from(font_size)
@@ -379,6 +884,18 @@ impl Font {
         self.ascent * f64::from(font_size)
     }
 
+    /// Returns the position and thickness of the underline for text with this font and the given
+    /// font size.
+    pub fn underline_metrics(&self, font_size: u8) -> LineMetrics {
+        self.underline.scale(font_size)
+    }
+
+    /// Returns the position and thickness of the strikethrough line for text with this font and
+    /// the given font size.
+    pub fn strikethrough_metrics(&self, font_size: u8) -> LineMetrics {
+        self.strikethrough.scale(font_size)
+    }
+
     /// Returns the width of a character with this font and the given font size.
     ///
     /// The given [`FontCache`][] must be the font cache that loaded this font.
@@ -405,23 +922,42 @@ impl Font {
     }
 
     fn char_h_metrics(&self, font_cache: &FontCache, c: char) -> rusttype::HMetrics {
-        font_cache
-            .get_rt_font(*self)
-            .glyph(c)
-            .scaled(self.scale)
-            .h_metrics()
+        if c == wrap::SOFT_HYPHEN {
+            // Soft hyphens are not printed, see render::TextSection::print_str
+            return rusttype::HMetrics {
+                advance_width: 0.0,
+                left_side_bearing: 0.0,
+            };
+        }
+        match font_cache.get_glyphs(*self) {
+            Glyphs::Rusttype(rt_font) => rt_font.glyph(c).scaled(self.scale).h_metrics(),
+            Glyphs::Builtin(metrics) => rusttype::HMetrics {
+                advance_width: f32::from(metrics.char_width(c)) / f32::from(metrics.units_per_em()),
+                left_side_bearing: 0.0,
+            },
+        }
     }
 
     /// Returns the width of a string with this font and the given font size.
     ///
+    /// If the `shaping` feature is enabled and this font is embedded, the width of the shaped
+    /// string is returned, see [`shape`][].
+    ///
     /// The given [`FontCache`][] must be the font cache that loaded this font.
     ///
     /// [`FontCache`]: struct.FontCache.html
+    /// [`shape`]: #method.shape
     pub fn str_width(&self, font_cache: &FontCache, s: &str, font_size: u8) -> Mm {
-        let str_width: Mm = font_cache
-            .get_rt_font(*self)
-            .glyphs_for(s.chars())
-            .map(|g| g.scaled(self.scale).h_metrics().advance_width)
+        #[cfg(feature = "shaping")]
+        {
+            if let Some(glyphs) = self.shape(font_cache, s) {
+                let width: f32 = glyphs.iter().map(|glyph| glyph.advance).sum();
+                return Mm::from(printpdf::Pt(f64::from(width * f32::from(font_size))));
+            }
+        }
+        let str_width: Mm = s
+            .chars()
+            .map(|c| self.char_h_metrics(font_cache, c).advance_width)
             .map(|w| Mm::from(printpdf::Pt(f64::from(w * f32::from(font_size)))))
             .sum();
         let kerning_width: Mm = self
@@ -445,7 +981,10 @@ impl Font {
     where
         I: IntoIterator<Item = char>,
     {
-        let font = font_cache.get_rt_font(*self);
+        let font = match font_cache.get_glyphs(*self) {
+            Glyphs::Rusttype(rt_font) => rt_font,
+            Glyphs::Builtin(_) => return iter.into_iter().map(|_| 0.0).collect(),
+        };
         font.glyphs_for(iter.into_iter())
             .scan(None, |last, g| {
                 let pos = if let Some(last) = last {
@@ -468,10 +1007,72 @@ impl Font {
     where
         I: IntoIterator<Item = char>,
     {
-        let font = font_cache.get_rt_font(*self);
-        font.glyphs_for(iter.into_iter())
-            .map(|g| g.id().0 as u16)
-            .collect()
+        match font_cache.get_glyphs(*self) {
+            Glyphs::Rusttype(rt_font) => rt_font
+                .glyphs_for(iter.into_iter())
+                .map(|g| g.id().0 as u16)
+                .collect(),
+            Glyphs::Builtin(_) => iter
+                .into_iter()
+                .map(|c| afm::encode(c).map(u16::from).unwrap_or_default())
+                .collect(),
+        }
+    }
+
+    /// Shapes the given string with this font and returns the resulting glyphs in visual order.
+    ///
+    /// The string must be in logical order.  If it contains right-to-left characters, it is
+    /// shaped as right-to-left text, otherwise as left-to-right text.  The script and the language
+    /// are guessed from the string.  Returns `None` for built-in fonts and fonts that cannot be
+    /// read by [`rustybuzz`][].
+    ///
+    /// The given [`FontCache`][] must be the font cache that loaded this font.
+    ///
+    /// *Only available if the `shaping` feature is enabled.*
+    ///
+    /// [`FontCache`]: struct.FontCache.html
+    /// [`rustybuzz`]: https://docs.rs/rustybuzz
+    #[cfg(feature = "shaping")]
+    pub fn shape(&self, font_cache: &FontCache, s: &str) -> Option<Vec<ShapedGlyph>> {
+        let data = match &font_cache.fonts[self.idx].raw_data {
+            RawFontData::Embedded(data) => data,
+            RawFontData::Builtin(_) => return None,
+        };
+        let rt_font = font_cache.get_rt_font(*self)?;
+        let face = rustybuzz::Face::from_slice(data, 0)?;
+        let units_per_em = f32::from(rt_font.units_per_em());
+
+        let mut buffer = rustybuzz::UnicodeBuffer::new();
+        buffer.push_str(s);
+        buffer.guess_segment_properties();
+        buffer.set_direction(if bidi::is_rtl(s) {
+            rustybuzz::Direction::RightToLeft
+        } else {
+            rustybuzz::Direction::LeftToRight
+        });
+        let glyphs = rustybuzz::shape(&face, &[], buffer);
+
+        let shaped = glyphs
+            .glyph_infos()
+            .iter()
+            .zip(glyphs.glyph_positions())
+            .map(|(info, position)| {
+                let default_advance = rt_font
+                    .glyph(rusttype::GlyphId(info.glyph_id))
+                    .scaled(self.scale)
+                    .h_metrics()
+                    .advance_width;
+                ShapedGlyph {
+                    // The PDF content stream uses two-byte glyph IDs
+                    glyph_id: info.glyph_id as u16,
+                    cluster: info.cluster as usize,
+                    advance: position.x_advance as f32 / units_per_em,
+                    offset: position.x_offset as f32 / units_per_em,
+                    default_advance,
+                }
+            })
+            .collect();
+        Some(shaped)
     }
 
     /// Calculate the metrics of a given font size for this font.
@@ -491,10 +1092,63 @@ fn from_file(
     builtin: Option<Builtin>,
 ) -> Result<FontData, Error> {
     let builtin = builtin.map(|b| b.style(style));
-    FontData::load(
-        &dir.as_ref().join(format!("{}-{}.ttf", name, style)),
-        builtin,
-    )
+    let path = dir.as_ref().join(format!("{}-{}.ttf", name, style));
+    let otf_path = path.with_extension("otf");
+    if path.exists() {
+        FontData::load(path, builtin)
+    } else if otf_path.exists() {
+        FontData::load(otf_path, builtin)
+    } else {
+        Err(Error::new(
+            format!(
+                "Failed to open font file {} or {}",
+                path.display(),
+                otf_path.display()
+            ),
+            io::Error::from(io::ErrorKind::NotFound),
+        ))
+    }
+}
+
+/// Returns the data of the table with the given tag in the given TrueType or OpenType font data.
+///
+/// For font collections, the first font is used, like in [`rusttype::Font::from_bytes`][].
+///
+/// [`rusttype::Font::from_bytes`]: https://docs.rs/rusttype/0.8.3/rusttype/struct.Font.html#method.from_bytes
+fn find_table<'a>(data: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    let read_u32 = |offset: usize| sfnt::read_u32(data, offset).map(|value| value as usize);
+    let start = sfnt::offset_table(data, 0)?;
+    let num_tables = usize::from(sfnt::read_u16(data, start + 4)?);
+    let record = (0..num_tables)
+        .map(|i| start + 12 + 16 * i)
+        .find(|&record| data.get(record..record + 4) == Some(&tag[..]))?;
+    let offset = read_u32(record + 8)?;
+    data.get(offset..offset + read_u32(record + 12)?)
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    data.get(offset..offset + 2)
+        .map(|b| i16::from_be_bytes([b[0], b[1]]))
+}
+
+/// Reads the center position and the thickness of the underline from the `post` table of the
+/// given font data in font units.
+fn read_underline(data: &[u8]) -> Option<(f32, f32)> {
+    let post = find_table(data, b"post")?;
+    // The position is the top of the underline
+    let position = f32::from(read_i16(post, 8)?);
+    let thickness = f32::from(read_i16(post, 10)?);
+    Some((position - thickness / 2.0, thickness)).filter(|_| thickness > 0.0)
+}
+
+/// Reads the center position and the thickness of the strikethrough line from the `OS/2` table of
+/// the given font data in font units.
+fn read_strikethrough(data: &[u8]) -> Option<(f32, f32)> {
+    let os2 = find_table(data, b"OS/2")?;
+    // The position is the top of the strikethrough line
+    let thickness = f32::from(read_i16(os2, 26)?);
+    let position = f32::from(read_i16(os2, 28)?);
+    Some((position - thickness / 2.0, thickness)).filter(|_| thickness > 0.0)
 }
 
 /// Loads the font family at the given path with the given name.
@@ -505,6 +1159,8 @@ fn from_file(
 /// - `{name}-Italic.ttf`
 /// - `{name}-BoldItalic.ttf`
 ///
+/// If a `.ttf` file does not exist, the OpenType font with the `.otf` extension is used instead.
+///
 /// If `builtin` is set, built-in PDF fonts are used instead of embedding the fonts in the PDF file
 /// (see the [module documentation](index.html) for more information).  In this case, the given
 /// fonts must be metrically identical to the built-in fonts.
@@ -522,6 +1178,151 @@ pub fn from_files(
     })
 }
 
+/// Finds the font family with the given name in the fonts installed on the system and loads it.
+///
+/// The font family is searched in the default font directories of the operating system, for
+/// example `/usr/share/fonts` on Linux, `/Library/Fonts` on macOS and `C:\Windows\Fonts` on
+/// Windows.  For every style of the font family, the best matching font is used.  If the font
+/// family does not have a bold or italic variant, the closest variant, typically the regular font,
+/// is used instead.  If the font family cannot be found, an error of the kind
+/// [`ErrorKind::FontNotFound`][] is returned.
+///
+/// If `builtin` is set, built-in PDF fonts are used instead of embedding the fonts in the PDF file
+/// (see the [module documentation](index.html) for more information).  In this case, the found
+/// fonts must be metrically identical to the built-in fonts.
+///
+/// *Only available if the `system-fonts` feature is enabled.*
+///
+/// # Example
+///
+/// ```no_run
+/// use genpdf::fonts;
+/// let font_family = fonts::find_system_font("DejaVu Sans", None)
+///     .expect("Failed to find font family");
+/// let doc = genpdf::Document::new(font_family);
+/// ```
+///
+/// [`ErrorKind::FontNotFound`]: ../error/enum.ErrorKind.html#variant.FontNotFound
+#[cfg(feature = "system-fonts")]
+pub fn find_system_font(
+    name: &str,
+    builtin: Option<Builtin>,
+) -> Result<FontFamily<FontData>, Error> {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+    let find = |style: FontStyle| {
+        let (weight, font_style) = match style {
+            FontStyle::Regular => (fontdb::Weight::NORMAL, fontdb::Style::Normal),
+            FontStyle::Bold => (fontdb::Weight::BOLD, fontdb::Style::Normal),
+            FontStyle::Italic => (fontdb::Weight::NORMAL, fontdb::Style::Italic),
+            FontStyle::BoldItalic => (fontdb::Weight::BOLD, fontdb::Style::Italic),
+        };
+        let query = fontdb::Query {
+            families: &[fontdb::Family::Name(name)],
+            weight,
+            stretch: fontdb::Stretch::Normal,
+            style: font_style,
+        };
+        let id = db.query(&query).ok_or_else(|| {
+            Error::new(
+                format!("Could not find the system font {} ({})", name, style),
+                ErrorKind::FontNotFound,
+            )
+        })?;
+        let builtin = builtin.map(|b| b.style(style));
+        db.with_face_data(id, |data, index| {
+            FontData::new_from_collection(data.to_vec(), index, builtin)
+        })
+        .unwrap_or_else(|| {
+            Err(Error::new(
+                format!("Failed to read the system font {} ({})", name, style),
+                ErrorKind::FontNotFound,
+            ))
+        })
+    };
+    Ok(FontFamily {
+        regular: find(FontStyle::Regular)?,
+        bold: find(FontStyle::Bold)?,
+        italic: find(FontStyle::Italic)?,
+        bold_italic: find(FontStyle::BoldItalic)?,
+    })
+}
+
+/// Loads the given built-in font family using the glyph metrics bundled with this crate.
+///
+/// In contrast to [`from_files`][], this function does not require any font files and the fonts
+/// are not embedded in the PDF file.  Kerning is not supported for these fonts.  Note that
+/// built-in fonts can only be used with characters that are supported by the [Windows-1252][]
+/// encoding.
+///
+/// [`from_files`]: fn.from_files.html
+/// [Windows-1252]: https://en.wikipedia.org/wiki/Windows-1252
+pub fn from_builtin(builtin: Builtin) -> FontFamily<FontData> {
+    let font_data = |style| {
+        FontData::builtin(builtin.style(style))
+            .expect("Missing glyph metrics for built-in font family")
+    };
+    FontFamily {
+        regular: font_data(FontStyle::Regular),
+        bold: font_data(FontStyle::Bold),
+        italic: font_data(FontStyle::Italic),
+        bold_italic: font_data(FontStyle::BoldItalic),
+    }
+}
+
+/// A glyph of a shaped string, see [`Font::shape`][].
+///
+/// All horizontal values are given in em, so they have to be multiplied with the font size.
+///
+/// *Only available if the `shaping` feature is enabled.*
+///
+/// [`Font::shape`]: struct.Font.html#method.shape
+#[cfg(feature = "shaping")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapedGlyph {
+    /// The ID of the glyph in the font.  The ID zero is used for characters that are not
+    /// supported by the font.
+    pub glyph_id: u16,
+    /// The byte index of the first character in the shaped string that is represented by this
+    /// glyph.
+    pub cluster: usize,
+    /// The horizontal advance of this glyph after shaping.
+    pub advance: f32,
+    /// The horizontal offset of this glyph from its position after shaping.
+    pub offset: f32,
+    /// The horizontal advance of this glyph as stored in the font, as used by PDF viewers.
+    pub default_advance: f32,
+}
+
+/// The position and thickness of a line drawn below or through text, for example an underline, at
+/// a given scale.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LineMetrics {
+    /// The distance of the center of the line from the baseline, with positive values above and
+    /// negative values below the baseline.
+    pub position: Mm,
+    /// The thickness of the line.
+    pub thickness: Mm,
+}
+
+impl LineMetrics {
+    /// Creates the metrics for the font size one from the given position and thickness in font
+    /// units.
+    fn from_units((position, thickness): (f32, f32), units_per_em: f32) -> LineMetrics {
+        LineMetrics {
+            position: printpdf::Pt(f64::from(position / units_per_em)).into(),
+            thickness: printpdf::Pt(f64::from(thickness / units_per_em)).into(),
+        }
+    }
+
+    fn scale(&self, font_size: u8) -> LineMetrics {
+        LineMetrics {
+            position: self.position * f64::from(font_size),
+            thickness: self.thickness * f64::from(font_size),
+        }
+    }
+}
+
 /// The metrics of a font at a given scale.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Metrics {
@@ -0,0 +1,922 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Instancing of variable TrueType fonts.
+//!
+//! PDF does not support font variations, so we create a static instance of a variable font by
+//! applying the glyph variations from the `gvar` table for the selected axis values to the
+//! outlines in the `glyf` table.  The advance widths are adjusted using the phantom points of the
+//! glyph variations.  The `HVAR` table and the variations of the hinting instructions and the
+//! font-wide metrics are ignored.  Fonts with CFF2 outlines are not supported.
+
+use std::borrow;
+
+use super::sfnt::{
+    self, read_u16, read_u32, ARGS_ARE_XY_VALUES, ARG_1_AND_2_ARE_WORDS, MORE_COMPONENTS,
+    WE_HAVE_AN_X_AND_Y_SCALE, WE_HAVE_A_SCALE, WE_HAVE_A_TWO_BY_TWO, WE_HAVE_INSTRUCTIONS,
+};
+
+/// The tables that are removed from the instance because they only apply to variable fonts.
+const VARIATION_TABLES: &[&[u8; 4]] = &[
+    b"HVAR", b"MVAR", b"STAT", b"VVAR", b"avar", b"cvar", b"fvar", b"gvar",
+];
+
+// The flags of the points of simple glyphs in the `glyf` table
+const ON_CURVE_POINT: u8 = 0x01;
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const REPEAT_FLAG: u8 = 0x08;
+const X_IS_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE: u8 = 0x20;
+const OVERLAP_SIMPLE: u8 = 0x40;
+
+// The flags of the tuple variation headers in the `gvar` table
+const SHARED_POINT_NUMBERS: u16 = 0x8000;
+const EMBEDDED_PEAK_TUPLE: u16 = 0x8000;
+const INTERMEDIATE_REGION: u16 = 0x4000;
+const PRIVATE_POINT_NUMBERS: u16 = 0x2000;
+
+/// A variation axis of a variable font, as stored in the `fvar` table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Axis {
+    pub tag: [u8; 4],
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+}
+
+impl Axis {
+    /// Clamps the given value to the range of this axis and maps it to the range -1 to 1.
+    fn normalize(&self, value: f32) -> f32 {
+        let value = value.max(self.min).min(self.max);
+        if value < self.default {
+            (value - self.default) / (self.default - self.min)
+        } else if value > self.default {
+            (value - self.default) / (self.max - self.default)
+        } else {
+            0.0
+        }
+    }
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    read_u16(data, offset).map(|value| value as i16)
+}
+
+fn read_f2dot14(data: &[u8], offset: usize) -> Option<f32> {
+    read_i16(data, offset).map(|value| f32::from(value) / 16384.0)
+}
+
+fn read_fixed(data: &[u8], offset: usize) -> Option<f32> {
+    read_u32(data, offset).map(|value| value as i32 as f32 / 65536.0)
+}
+
+fn read_tuple(data: &[u8], offset: usize, axis_count: usize) -> Option<Vec<f32>> {
+    (0..axis_count)
+        .map(|i| read_f2dot14(data, offset + 2 * i))
+        .collect()
+}
+
+/// Returns the variation axes of the given font, or `None` if it is not a variable font.
+pub fn axes(data: &[u8]) -> Option<Vec<Axis>> {
+    let tables = sfnt::read_tables(data, sfnt::offset_table(data, 0)?)?;
+    read_axes(tables.get(b"fvar")?)
+}
+
+fn read_axes(fvar: &[u8]) -> Option<Vec<Axis>> {
+    let offset = usize::from(read_u16(fvar, 4)?);
+    let count = usize::from(read_u16(fvar, 8)?);
+    let size = usize::from(read_u16(fvar, 10)?);
+    (0..count)
+        .map(|i| {
+            let record = offset + i * size;
+            let mut tag = [0; 4];
+            tag.copy_from_slice(fvar.get(record..record + 4)?);
+            Some(Axis {
+                tag,
+                min: read_fixed(fvar, record + 4)?,
+                default: read_fixed(fvar, record + 8)?,
+                max: read_fixed(fvar, record + 12)?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the axis values of the named instance of the given font with the given subfamily
+/// name, for example `Bold`.  The names are compared case-insensitively.
+pub fn named_instance(data: &[u8], name: &str) -> Option<Vec<([u8; 4], f32)>> {
+    let tables = sfnt::read_tables(data, sfnt::offset_table(data, 0)?)?;
+    let fvar = tables.get(b"fvar")?;
+    let names = tables.get(b"name")?;
+    let axes = read_axes(fvar)?;
+    let offset = usize::from(read_u16(fvar, 4)?) + axes.len() * usize::from(read_u16(fvar, 10)?);
+    let count = usize::from(read_u16(fvar, 12)?);
+    let size = usize::from(read_u16(fvar, 14)?);
+    let name = name.to_lowercase();
+    (0..count).map(|i| offset + i * size).find_map(|record| {
+        let name_id = read_u16(fvar, record)?;
+        if !read_names(names, name_id)
+            .iter()
+            .any(|n| n.to_lowercase() == name)
+        {
+            return None;
+        }
+        axes.iter()
+            .enumerate()
+            .map(|(i, axis)| Some((axis.tag, read_fixed(fvar, record + 4 + 4 * i)?)))
+            .collect()
+    })
+}
+
+/// Returns all Unicode and ASCII strings with the given name ID from the given `name` table.
+fn read_names(names: &[u8], name_id: u16) -> Vec<String> {
+    let count = read_u16(names, 2).unwrap_or(0);
+    let storage = usize::from(read_u16(names, 4).unwrap_or(0));
+    (0..usize::from(count))
+        .map(|i| 6 + 12 * i)
+        .filter(|&record| read_u16(names, record + 6) == Some(name_id))
+        .filter_map(|record| {
+            let platform = read_u16(names, record)?;
+            let encoding = read_u16(names, record + 2)?;
+            let length = usize::from(read_u16(names, record + 8)?);
+            let offset = storage + usize::from(read_u16(names, record + 10)?);
+            let bytes = names.get(offset..offset + length)?;
+            match (platform, encoding) {
+                (0, _) | (3, _) => {
+                    let units: Vec<_> = bytes.chunks(2).filter_map(|b| read_u16(b, 0)).collect();
+                    Some(String::from_utf16_lossy(&units))
+                }
+                (1, 0) => Some(bytes.iter().copied().map(char::from).collect()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Creates a static instance of the given variable font with the given axis values.
+///
+/// Axes that are not set use their default value.  Returns `None` if the font is not a variable
+/// font with TrueType outlines or if it is malformed.
+pub fn instantiate(data: &[u8], values: &[([u8; 4], f32)]) -> Option<Vec<u8>> {
+    let start = sfnt::offset_table(data, 0)?;
+    let mut tables = sfnt::read_tables(data, start)?;
+    let axes = read_axes(tables.get(b"fvar")?)?;
+    let values: Vec<_> = axes
+        .iter()
+        .map(|axis| {
+            values
+                .iter()
+                .rev()
+                .find(|(tag, _)| *tag == axis.tag)
+                .map(|(_, value)| *value)
+                .unwrap_or(axis.default)
+        })
+        .collect();
+    let mut coords: Vec<_> = axes
+        .iter()
+        .zip(&values)
+        .map(|(axis, value)| axis.normalize(*value))
+        .collect();
+    if let Some(avar) = tables.get(b"avar") {
+        apply_avar(avar, &mut coords)?;
+    }
+    // The coordinates in the font are stored as F2DOT14 values.
+    for coord in &mut coords {
+        *coord = (*coord * 16384.0).round() / 16384.0;
+    }
+
+    let glyf = tables.get(b"glyf")?.clone();
+    let num_glyphs = usize::from(read_u16(tables.get(b"maxp")?, 4)?);
+    let offsets = sfnt::glyph_offsets(&tables)?;
+    let metrics = read_h_metrics(tables.get(b"hhea")?, tables.get(b"hmtx")?, num_glyphs)?;
+    let gvar = Gvar::new(tables.get(b"gvar")?, coords.len())?;
+
+    let mut glyphs = Vec::with_capacity(num_glyphs);
+    let mut hmtx = Vec::with_capacity(4 * num_glyphs);
+    let mut max_advance = 0;
+    for (id, (advance, lsb)) in metrics.into_iter().enumerate() {
+        let glyph = glyf.get(offsets[id]..*offsets.get(id + 1)?)?;
+        let outline = Outline::new(glyph)?;
+        let x_min = if glyph.is_empty() {
+            0
+        } else {
+            i32::from(read_i16(glyph, 2)?)
+        };
+
+        // The phantom points for the horizontal metrics are appended to the points of the glyph.
+        let left = x_min - i32::from(lsb);
+        let right = left + i32::from(advance);
+        let mut points = outline.points();
+        points.extend_from_slice(&[(left, 0), (right, 0), (0, 0), (0, 0)]);
+        let deltas = gvar.deltas(id, &coords, &points, outline.end_points())?;
+        let (new_glyph, new_x_min) = outline.write(glyph, &deltas);
+        let phantom = &deltas[deltas.len() - 4..];
+        let left = left + phantom[0].0.round() as i32;
+        let right = right + phantom[1].0.round() as i32;
+        let advance = (right - left).max(0).min(i32::from(u16::MAX)) as u16;
+        let lsb = new_x_min.unwrap_or(x_min) - left;
+        max_advance = max_advance.max(advance);
+        hmtx.extend_from_slice(&advance.to_be_bytes());
+        hmtx.extend_from_slice(&(lsb as i16).to_be_bytes());
+        glyphs.push(new_glyph);
+    }
+
+    let mut hhea = tables.get(b"hhea")?.to_vec();
+    hhea.get_mut(10..12)?
+        .copy_from_slice(&max_advance.to_be_bytes());
+    hhea.get_mut(34..36)?
+        .copy_from_slice(&(num_glyphs as u16).to_be_bytes());
+    tables.insert(*b"hhea", borrow::Cow::Owned(hhea));
+    tables.insert(*b"hmtx", borrow::Cow::Owned(hmtx));
+    sfnt::write_glyphs(&mut tables, glyphs.iter().map(Vec::as_slice));
+
+    let weight = axes
+        .iter()
+        .zip(&values)
+        .find(|(axis, _)| &axis.tag == b"wght")
+        .map(|(_, value)| value.round().max(1.0).min(1000.0) as u16);
+    if let (Some(weight), Some(os2)) = (weight, tables.get_mut(b"OS/2")) {
+        if let Some(weight_class) = os2.to_mut().get_mut(4..6) {
+            weight_class.copy_from_slice(&weight.to_be_bytes());
+        }
+    }
+
+    let tables = tables
+        .into_iter()
+        .filter(|(tag, _)| !VARIATION_TABLES.contains(&tag))
+        .collect();
+    Some(sfnt::write_font(&data[start..start + 4], tables))
+}
+
+/// Maps the given normalized coordinates using the segment maps of the given `avar` table.
+fn apply_avar(avar: &[u8], coords: &mut [f32]) -> Option<()> {
+    let mut offset = 8;
+    for coord in coords {
+        let count = usize::from(read_u16(avar, offset)?);
+        let map = (0..count)
+            .map(|i| {
+                let from = read_f2dot14(avar, offset + 2 + 4 * i)?;
+                let to = read_f2dot14(avar, offset + 4 + 4 * i)?;
+                Some((from, to))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        offset += 2 + 4 * count;
+        let segment = map
+            .windows(2)
+            .find(|segment| (segment[0].0..=segment[1].0).contains(&*coord));
+        if let Some(&[(from0, to0), (from1, to1)]) = segment {
+            if from1 > from0 {
+                *coord = to0 + (to1 - to0) * (*coord - from0) / (from1 - from0);
+            } else {
+                *coord = to0;
+            }
+        }
+    }
+    Some(())
+}
+
+/// Reads the advance widths and left side bearings of all glyphs.
+fn read_h_metrics(hhea: &[u8], hmtx: &[u8], num_glyphs: usize) -> Option<Vec<(u16, i16)>> {
+    let num_metrics = usize::from(read_u16(hhea, 34)?);
+    let mut advance = 0;
+    (0..num_glyphs)
+        .map(|i| {
+            if i < num_metrics {
+                advance = read_u16(hmtx, 4 * i)?;
+                Some((advance, read_i16(hmtx, 4 * i + 2)?))
+            } else {
+                Some((
+                    advance,
+                    read_i16(hmtx, 4 * num_metrics + 2 * (i - num_metrics))?,
+                ))
+            }
+        })
+        .collect()
+}
+
+/// Returns the scalar for a tuple variation with the given peak and intermediate region for the
+/// given normalized coordinates.
+fn scalar(coords: &[f32], peak: &[f32], region: Option<&(Vec<f32>, Vec<f32>)>) -> f32 {
+    let mut scalar = 1.0;
+    for (i, (&coord, &peak)) in coords.iter().zip(peak).enumerate() {
+        if peak == 0.0 {
+            continue;
+        }
+        if coord == 0.0 {
+            return 0.0;
+        }
+        if let Some((start, end)) = region {
+            let (start, end) = (start[i], end[i]);
+            if start > peak || peak > end || (start < 0.0 && end > 0.0) {
+                continue;
+            }
+            if !(start..=end).contains(&coord) {
+                return 0.0;
+            } else if coord < peak {
+                scalar *= (coord - start) / (peak - start);
+            } else if coord > peak {
+                scalar *= (end - coord) / (end - peak);
+            }
+        } else if !(peak.min(0.0)..=peak.max(0.0)).contains(&coord) {
+            return 0.0;
+        } else {
+            scalar *= coord / peak;
+        }
+    }
+    scalar
+}
+
+/// Reads packed point numbers and returns `None` if the data applies to all points.
+fn unpack_points(data: &[u8], offset: &mut usize) -> Option<Option<Vec<u16>>> {
+    let first = *data.get(*offset)?;
+    *offset += 1;
+    let count = if first == 0 {
+        return Some(None);
+    } else if first & 0x80 != 0 {
+        let second = *data.get(*offset)?;
+        *offset += 1;
+        (usize::from(first & 0x7f) << 8) | usize::from(second)
+    } else {
+        usize::from(first)
+    };
+    let mut points = Vec::with_capacity(count);
+    let mut point = 0u16;
+    while points.len() < count {
+        let control = *data.get(*offset)?;
+        *offset += 1;
+        for _ in 0..=(control & 0x7f) {
+            let delta = if control & 0x80 != 0 {
+                *offset += 2;
+                read_u16(data, *offset - 2)?
+            } else {
+                *offset += 1;
+                u16::from(*data.get(*offset - 1)?)
+            };
+            point = point.wrapping_add(delta);
+            points.push(point);
+        }
+    }
+    Some(Some(points))
+}
+
+/// Reads the given number of packed deltas.
+fn unpack_deltas(data: &[u8], offset: &mut usize, count: usize) -> Option<Vec<i16>> {
+    let mut deltas = Vec::with_capacity(count);
+    while deltas.len() < count {
+        let control = *data.get(*offset)?;
+        *offset += 1;
+        for _ in 0..=(control & 0x3f) {
+            let delta = if control & 0x80 != 0 {
+                0
+            } else if control & 0x40 != 0 {
+                *offset += 2;
+                read_i16(data, *offset - 2)?
+            } else {
+                *offset += 1;
+                i16::from(*data.get(*offset - 1)? as i8)
+            };
+            deltas.push(delta);
+        }
+    }
+    deltas.truncate(count);
+    Some(deltas)
+}
+
+/// Interpolates the delta of an untouched point from the deltas of two reference points.
+fn interpolate(point: i32, (c1, d1): (i32, f32), (c2, d2): (i32, f32)) -> f32 {
+    if c1 == c2 {
+        return if (d1 - d2).abs() < f32::EPSILON {
+            d1
+        } else {
+            0.0
+        };
+    }
+    let ((c1, d1), (c2, d2)) = if c1 < c2 {
+        ((c1, d1), (c2, d2))
+    } else {
+        ((c2, d2), (c1, d1))
+    };
+    if point <= c1 {
+        d1
+    } else if point >= c2 {
+        d2
+    } else {
+        d1 + (d2 - d1) * (point - c1) as f32 / (c2 - c1) as f32
+    }
+}
+
+/// Infers the deltas of the untouched points of a contour from the touched points.
+fn interpolate_contour(points: &[(i32, i32)], deltas: &mut [(f32, f32)], touched: &[bool]) {
+    let touched: Vec<_> = (0..points.len()).filter(|&i| touched[i]).collect();
+    for (j, &prev) in touched.iter().enumerate() {
+        let next = touched[(j + 1) % touched.len()];
+        let mut i = (prev + 1) % points.len();
+        while i != next {
+            deltas[i] = (
+                interpolate(
+                    points[i].0,
+                    (points[prev].0, deltas[prev].0),
+                    (points[next].0, deltas[next].0),
+                ),
+                interpolate(
+                    points[i].1,
+                    (points[prev].1, deltas[prev].1),
+                    (points[next].1, deltas[next].1),
+                ),
+            );
+            i = (i + 1) % points.len();
+        }
+    }
+}
+
+/// Writes the given coordinate delta of a simple glyph and returns the flags for it.
+fn write_coord(delta: i32, data: &mut Vec<u8>, short: u8, same_or_positive: u8) -> u8 {
+    if delta == 0 {
+        same_or_positive
+    } else if delta.abs() <= 0xff {
+        data.push(delta.abs() as u8);
+        if delta > 0 {
+            short | same_or_positive
+        } else {
+            short
+        }
+    } else {
+        data.extend_from_slice(&(delta as i16).to_be_bytes());
+        0
+    }
+}
+
+/// The glyph variations stored in a `gvar` table.
+struct Gvar<'a> {
+    data: &'a [u8],
+    axis_count: usize,
+    shared_tuples: Vec<Vec<f32>>,
+    offsets: Vec<usize>,
+}
+
+impl<'a> Gvar<'a> {
+    fn new(data: &'a [u8], axis_count: usize) -> Option<Gvar<'a>> {
+        if usize::from(read_u16(data, 4)?) != axis_count {
+            return None;
+        }
+        let shared_tuple_count = usize::from(read_u16(data, 6)?);
+        let shared_tuples_offset = read_u32(data, 8)? as usize;
+        let glyph_count = usize::from(read_u16(data, 12)?);
+        let is_long = read_u16(data, 14)? & 1 != 0;
+        let array_offset = read_u32(data, 16)? as usize;
+        let offsets = (0..=glyph_count)
+            .map(|i| {
+                let offset = if is_long {
+                    read_u32(data, 20 + 4 * i)? as usize
+                } else {
+                    usize::from(read_u16(data, 20 + 2 * i)?) * 2
+                };
+                Some(array_offset + offset)
+            })
+            .collect::<Option<_>>()?;
+        let shared_tuples = (0..shared_tuple_count)
+            .map(|i| read_tuple(data, shared_tuples_offset + 2 * axis_count * i, axis_count))
+            .collect::<Option<_>>()?;
+        Some(Gvar {
+            data,
+            axis_count,
+            shared_tuples,
+            offsets,
+        })
+    }
+
+    /// Returns the deltas for the given points of the glyph with the given ID at the given
+    /// normalized coordinates.
+    ///
+    /// The points must include the phantom points.  If the glyph is a simple glyph, the end
+    /// points of its contours must be set so that the deltas of untouched points can be inferred.
+    fn deltas(
+        &self,
+        glyph_id: usize,
+        coords: &[f32],
+        points: &[(i32, i32)],
+        end_points: &[u16],
+    ) -> Option<Vec<(f32, f32)>> {
+        let mut deltas = vec![(0.0, 0.0); points.len()];
+        let data = match (self.offsets.get(glyph_id), self.offsets.get(glyph_id + 1)) {
+            (Some(&start), Some(&end)) if start < end => self.data.get(start..end)?,
+            _ => return Some(deltas),
+        };
+
+        let header = read_u16(data, 0)?;
+        let mut offset = usize::from(read_u16(data, 2)?);
+        let shared_points = if header & SHARED_POINT_NUMBERS != 0 {
+            unpack_points(data, &mut offset)?
+        } else {
+            None
+        };
+        let mut header_offset = 4;
+        for _ in 0..(header & 0x0fff) {
+            let size = usize::from(read_u16(data, header_offset)?);
+            let index = read_u16(data, header_offset + 2)?;
+            header_offset += 4;
+            let peak = if index & EMBEDDED_PEAK_TUPLE != 0 {
+                header_offset += 2 * self.axis_count;
+                read_tuple(data, header_offset - 2 * self.axis_count, self.axis_count)?
+            } else {
+                self.shared_tuples.get(usize::from(index & 0x0fff))?.clone()
+            };
+            let region = if index & INTERMEDIATE_REGION != 0 {
+                let start = read_tuple(data, header_offset, self.axis_count)?;
+                header_offset += 2 * self.axis_count;
+                let end = read_tuple(data, header_offset, self.axis_count)?;
+                header_offset += 2 * self.axis_count;
+                Some((start, end))
+            } else {
+                None
+            };
+            let tuple = data.get(offset..offset + size)?;
+            offset += size;
+
+            let scalar = scalar(coords, &peak, region.as_ref());
+            if scalar == 0.0 {
+                continue;
+            }
+            let mut tuple_offset = 0;
+            let tuple_points = if index & PRIVATE_POINT_NUMBERS != 0 {
+                unpack_points(tuple, &mut tuple_offset)?
+            } else {
+                shared_points.clone()
+            };
+            let count = tuple_points.as_ref().map_or(points.len(), Vec::len);
+            let xs = unpack_deltas(tuple, &mut tuple_offset, count)?;
+            let ys = unpack_deltas(tuple, &mut tuple_offset, count)?;
+            let tuple_deltas = self::tuple_deltas(points, end_points, tuple_points, &xs, &ys);
+            for (delta, tuple_delta) in deltas.iter_mut().zip(tuple_deltas) {
+                delta.0 += scalar * tuple_delta.0;
+                delta.1 += scalar * tuple_delta.1;
+            }
+        }
+        Some(deltas)
+    }
+}
+
+/// Returns the deltas of a tuple variation for all points, inferring the deltas of untouched
+/// points of simple glyphs.
+fn tuple_deltas(
+    points: &[(i32, i32)],
+    end_points: &[u16],
+    indices: Option<Vec<u16>>,
+    xs: &[i16],
+    ys: &[i16],
+) -> Vec<(f32, f32)> {
+    let indices = match indices {
+        Some(indices) => indices,
+        None => {
+            return xs
+                .iter()
+                .zip(ys)
+                .map(|(&x, &y)| (f32::from(x), f32::from(y)))
+                .collect()
+        }
+    };
+    let mut deltas = vec![(0.0, 0.0); points.len()];
+    let mut touched = vec![false; points.len()];
+    for ((&i, &x), &y) in indices.iter().zip(xs).zip(ys) {
+        let i = usize::from(i);
+        if i < points.len() {
+            deltas[i] = (f32::from(x), f32::from(y));
+            touched[i] = true;
+        }
+    }
+    let mut start = 0;
+    for &end in end_points {
+        let end = usize::from(end);
+        if end < start || end >= points.len() {
+            break;
+        }
+        interpolate_contour(
+            &points[start..=end],
+            &mut deltas[start..=end],
+            &touched[start..=end],
+        );
+        start = end + 1;
+    }
+    deltas
+}
+
+/// The outline of a glyph in the `glyf` table.
+enum Outline<'a> {
+    Empty,
+    Simple {
+        end_points: Vec<u16>,
+        instructions: &'a [u8],
+        flags: Vec<u8>,
+        points: Vec<(i32, i32)>,
+    },
+    Composite {
+        components: Vec<Component<'a>>,
+        instructions: Option<&'a [u8]>,
+    },
+}
+
+/// A component of a composite glyph.
+struct Component<'a> {
+    flags: u16,
+    glyph_id: u16,
+    args: (i32, i32),
+    transform: &'a [u8],
+}
+
+impl<'a> Outline<'a> {
+    fn new(glyph: &'a [u8]) -> Option<Outline<'a>> {
+        if glyph.is_empty() {
+            return Some(Outline::Empty);
+        }
+        let num_contours = read_i16(glyph, 0)?;
+        if num_contours < 0 {
+            return Outline::composite(glyph);
+        }
+
+        let end_points = (0..usize::from(num_contours as u16))
+            .map(|i| read_u16(glyph, 10 + 2 * i))
+            .collect::<Option<Vec<_>>>()?;
+        let num_points = end_points.last().map_or(0, |&end| usize::from(end) + 1);
+        let mut offset = 10 + 2 * end_points.len();
+        let instructions_len = usize::from(read_u16(glyph, offset)?);
+        let instructions = glyph.get(offset + 2..offset + 2 + instructions_len)?;
+        offset += 2 + instructions_len;
+
+        let mut flags = Vec::with_capacity(num_points);
+        while flags.len() < num_points {
+            let flag = *glyph.get(offset)?;
+            offset += 1;
+            let repeat = if flag & REPEAT_FLAG != 0 {
+                offset += 1;
+                *glyph.get(offset - 1)?
+            } else {
+                0
+            };
+            for _ in 0..=repeat {
+                flags.push(flag);
+            }
+        }
+        flags.truncate(num_points);
+
+        let mut read_coords = |short: u8, same_or_positive: u8| {
+            let mut value = 0;
+            flags
+                .iter()
+                .map(|&flag| {
+                    if flag & short != 0 {
+                        offset += 1;
+                        let delta = i32::from(*glyph.get(offset - 1)?);
+                        value += if flag & same_or_positive != 0 {
+                            delta
+                        } else {
+                            -delta
+                        };
+                    } else if flag & same_or_positive == 0 {
+                        offset += 2;
+                        value += i32::from(read_i16(glyph, offset - 2)?);
+                    }
+                    Some(value)
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+        let xs = read_coords(X_SHORT_VECTOR, X_IS_SAME_OR_POSITIVE)?;
+        let ys = read_coords(Y_SHORT_VECTOR, Y_IS_SAME_OR_POSITIVE)?;
+        Some(Outline::Simple {
+            end_points,
+            instructions,
+            flags,
+            points: xs.into_iter().zip(ys).collect(),
+        })
+    }
+
+    fn composite(glyph: &'a [u8]) -> Option<Outline<'a>> {
+        let mut components = Vec::new();
+        let mut offset = 10;
+        loop {
+            let flags = read_u16(glyph, offset)?;
+            let glyph_id = read_u16(glyph, offset + 2)?;
+            offset += 4;
+            let is_signed = flags & ARGS_ARE_XY_VALUES != 0;
+            let args = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+                offset += 4;
+                let (arg1, arg2) = (read_u16(glyph, offset - 4)?, read_u16(glyph, offset - 2)?);
+                if is_signed {
+                    (i32::from(arg1 as i16), i32::from(arg2 as i16))
+                } else {
+                    (i32::from(arg1), i32::from(arg2))
+                }
+            } else {
+                offset += 2;
+                let (arg1, arg2) = (*glyph.get(offset - 2)?, *glyph.get(offset - 1)?);
+                if is_signed {
+                    (i32::from(arg1 as i8), i32::from(arg2 as i8))
+                } else {
+                    (i32::from(arg1), i32::from(arg2))
+                }
+            };
+            let transform_len = if flags & WE_HAVE_A_SCALE != 0 {
+                2
+            } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                4
+            } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                8
+            } else {
+                0
+            };
+            let transform = glyph.get(offset..offset + transform_len)?;
+            offset += transform_len;
+            components.push(Component {
+                flags,
+                glyph_id,
+                args,
+                transform,
+            });
+            if flags & MORE_COMPONENTS == 0 {
+                break;
+            }
+        }
+        let instructions = if components
+            .iter()
+            .any(|component| component.flags & WE_HAVE_INSTRUCTIONS != 0)
+        {
+            let len = usize::from(read_u16(glyph, offset)?);
+            Some(glyph.get(offset + 2..offset + 2 + len)?)
+        } else {
+            None
+        };
+        Some(Outline::Composite {
+            components,
+            instructions,
+        })
+    }
+
+    /// Returns the points of this outline that can be varied, without the phantom points.
+    ///
+    /// For composite glyphs, every component is represented by one point with its offset.
+    fn points(&self) -> Vec<(i32, i32)> {
+        match self {
+            Outline::Empty => Vec::new(),
+            Outline::Simple { points, .. } => points.clone(),
+            Outline::Composite { components, .. } => {
+                components.iter().map(|component| component.args).collect()
+            }
+        }
+    }
+
+    /// Returns the end points of the contours if this is a simple glyph.
+    fn end_points(&self) -> &[u16] {
+        match self {
+            Outline::Simple { end_points, .. } => end_points,
+            _ => &[],
+        }
+    }
+
+    /// Applies the given deltas to this outline and returns the new glyph data and the new
+    /// minimum x coordinate, if it has changed.
+    fn write(&self, glyph: &[u8], deltas: &[(f32, f32)]) -> (Vec<u8>, Option<i32>) {
+        let mut data = Vec::new();
+        match self {
+            Outline::Empty => (data, None),
+            Outline::Simple {
+                end_points,
+                instructions,
+                flags,
+                points,
+            } => {
+                let points: Vec<_> = points
+                    .iter()
+                    .zip(deltas)
+                    .map(|((x, y), (dx, dy))| (x + dx.round() as i32, y + dy.round() as i32))
+                    .collect();
+                let x_min = points.iter().map(|p| p.0).min().unwrap_or(0);
+                let y_min = points.iter().map(|p| p.1).min().unwrap_or(0);
+                let x_max = points.iter().map(|p| p.0).max().unwrap_or(0);
+                let y_max = points.iter().map(|p| p.1).max().unwrap_or(0);
+                data.extend_from_slice(&(end_points.len() as i16).to_be_bytes());
+                for value in &[x_min, y_min, x_max, y_max] {
+                    data.extend_from_slice(&(*value as i16).to_be_bytes());
+                }
+                for end_point in end_points {
+                    data.extend_from_slice(&end_point.to_be_bytes());
+                }
+                data.extend_from_slice(&(instructions.len() as u16).to_be_bytes());
+                data.extend_from_slice(instructions);
+
+                let mut new_flags = Vec::with_capacity(points.len());
+                let mut xs = Vec::new();
+                let mut ys = Vec::new();
+                let mut previous = (0, 0);
+                for (flag, point) in flags.iter().zip(&points) {
+                    let mut flag = flag & (ON_CURVE_POINT | OVERLAP_SIMPLE);
+                    flag |= write_coord(
+                        point.0 - previous.0,
+                        &mut xs,
+                        X_SHORT_VECTOR,
+                        X_IS_SAME_OR_POSITIVE,
+                    );
+                    flag |= write_coord(
+                        point.1 - previous.1,
+                        &mut ys,
+                        Y_SHORT_VECTOR,
+                        Y_IS_SAME_OR_POSITIVE,
+                    );
+                    new_flags.push(flag);
+                    previous = *point;
+                }
+                data.extend_from_slice(&new_flags);
+                data.extend_from_slice(&xs);
+                data.extend_from_slice(&ys);
+                (data, Some(x_min))
+            }
+            Outline::Composite {
+                components,
+                instructions,
+            } => {
+                // The bounding box of composite glyphs is not updated.
+                data.extend_from_slice(&glyph[..10]);
+                for (component, (dx, dy)) in components.iter().zip(deltas) {
+                    let mut args = component.args;
+                    if component.flags & ARGS_ARE_XY_VALUES != 0 {
+                        args.0 += dx.round() as i32;
+                        args.1 += dy.round() as i32;
+                    }
+                    let flags = component.flags | ARG_1_AND_2_ARE_WORDS;
+                    data.extend_from_slice(&flags.to_be_bytes());
+                    data.extend_from_slice(&component.glyph_id.to_be_bytes());
+                    data.extend_from_slice(&(args.0 as u16).to_be_bytes());
+                    data.extend_from_slice(&(args.1 as u16).to_be_bytes());
+                    data.extend_from_slice(component.transform);
+                }
+                if let Some(instructions) = instructions {
+                    data.extend_from_slice(&(instructions.len() as u16).to_be_bytes());
+                    data.extend_from_slice(instructions);
+                }
+                (data, None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        let axis = Axis {
+            tag: *b"wght",
+            min: 100.0,
+            default: 400.0,
+            max: 900.0,
+        };
+        assert_eq!(-1.0, axis.normalize(50.0));
+        assert_eq!(-0.5, axis.normalize(250.0));
+        assert_eq!(0.0, axis.normalize(400.0));
+        assert_eq!(0.6, axis.normalize(700.0));
+        assert_eq!(1.0, axis.normalize(1000.0));
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(1.0, scalar(&[0.5], &[0.0], None));
+        assert_eq!(0.0, scalar(&[0.0], &[1.0], None));
+        assert_eq!(0.5, scalar(&[0.5], &[1.0], None));
+        assert_eq!(0.0, scalar(&[-0.5], &[1.0], None));
+        let region = (vec![0.0], vec![1.0]);
+        assert_eq!(1.0, scalar(&[0.5], &[0.5], Some(&region)));
+        assert_eq!(0.5, scalar(&[0.75], &[0.5], Some(&region)));
+    }
+
+    #[test]
+    fn packed_data() {
+        let mut offset = 0;
+        assert_eq!(Some(None), unpack_points(&[0], &mut offset));
+        let mut offset = 0;
+        assert_eq!(
+            Some(Some(vec![1, 3, 259])),
+            unpack_points(&[3, 0x01, 1, 2, 0x80, 1, 0], &mut offset)
+        );
+        assert_eq!(7, offset);
+
+        let mut offset = 0;
+        assert_eq!(
+            Some(vec![0, 0, -1, 300]),
+            unpack_deltas(&[0x81, 0x00, 0xff, 0x40, 0x01, 0x2c], &mut offset, 4)
+        );
+        assert_eq!(6, offset);
+    }
+
+    #[test]
+    fn interpolation() {
+        assert_eq!(5.0, interpolate(0, (0, 5.0), (0, 5.0)));
+        assert_eq!(0.0, interpolate(0, (0, 5.0), (0, 3.0)));
+        assert_eq!(2.0, interpolate(-10, (0, 2.0), (10, 4.0)));
+        assert_eq!(3.0, interpolate(5, (10, 4.0), (0, 2.0)));
+        assert_eq!(4.0, interpolate(20, (0, 2.0), (10, 4.0)));
+
+        let points = [(0, 0), (10, 0), (10, 10), (0, 10)];
+        let mut deltas = [(1.0, 0.0), (0.0, 0.0), (3.0, 0.0), (0.0, 0.0)];
+        interpolate_contour(&points, &mut deltas, &[true, false, true, false]);
+        assert_eq!([(1.0, 0.0), (3.0, 0.0), (3.0, 0.0), (1.0, 0.0)], deltas);
+    }
+}
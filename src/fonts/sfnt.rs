@@ -0,0 +1,235 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Reading and writing the table directory of TrueType and OpenType fonts and collections.
+//!
+//! A font file starts with an offset table that contains the records for all tables of the font.
+//! A font collection (`ttcf`) starts with a header that contains the offsets of the offset tables
+//! of all fonts in the collection, and the fonts may share tables.
+
+use std::borrow;
+use std::collections;
+
+/// The tables of a font, indexed by tag.
+pub type Tables<'a> = collections::BTreeMap<[u8; 4], borrow::Cow<'a, [u8]>>;
+
+// The flags of the components of composite glyphs in the `glyf` table
+pub const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+pub const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+pub const WE_HAVE_A_SCALE: u16 = 0x0008;
+pub const MORE_COMPONENTS: u16 = 0x0020;
+pub const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+pub const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+pub const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+pub fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+pub fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// Returns the TrueType checksum of the given data, padded with zeros to a multiple of four bytes.
+pub fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Pads the given data with zeros to a multiple of four bytes.
+pub fn pad(data: &mut Vec<u8>) {
+    while data.len() % 4 != 0 {
+        data.push(0);
+    }
+}
+
+/// Returns whether the given data is a font collection.
+pub fn is_collection(data: &[u8]) -> bool {
+    data.starts_with(b"ttcf")
+}
+
+/// Returns whether the given data is an OpenType font with CFF outlines.
+pub fn is_cff(data: &[u8]) -> bool {
+    data.starts_with(b"OTTO")
+}
+
+/// Returns the number of fonts in the given font data, or `None` if the data is not a valid font
+/// collection.  A single font is treated as a collection with one font.
+pub fn font_count(data: &[u8]) -> Option<u32> {
+    if is_collection(data) {
+        read_u32(data, 8)
+    } else {
+        Some(1)
+    }
+}
+
+/// Returns the offset of the offset table of the font with the given index in the given font
+/// data.
+pub fn offset_table(data: &[u8], index: u32) -> Option<usize> {
+    if is_collection(data) {
+        if index >= font_count(data)? {
+            return None;
+        }
+        read_u32(data, 12 + 4 * index as usize).map(|offset| offset as usize)
+    } else if index == 0 {
+        Some(0)
+    } else {
+        None
+    }
+}
+
+/// Reads the tables of the font with the offset table at the given offset.
+pub fn read_tables(data: &[u8], start: usize) -> Option<Tables<'_>> {
+    let num_tables = usize::from(read_u16(data, start + 4)?);
+    let mut tables = Tables::new();
+    for record in (0..num_tables).map(|i| start + 12 + 16 * i) {
+        let mut tag = [0; 4];
+        tag.copy_from_slice(data.get(record..record + 4)?);
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        let table = data.get(offset..offset.checked_add(length)?)?;
+        tables.insert(tag, borrow::Cow::Borrowed(table));
+    }
+    Some(tables)
+}
+
+/// Writes a font with the given version tag and tables.
+///
+/// The table directory, the table checksums and the checksum adjustment of the `head` table are
+/// recomputed.
+pub fn write_font(version: &[u8], mut tables: Tables<'_>) -> Vec<u8> {
+    if let Some(head) = tables.get_mut(b"head") {
+        if let Some(adjustment) = head.to_mut().get_mut(8..12) {
+            adjustment.copy_from_slice(&[0; 4]);
+        }
+    }
+
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.leading_zeros() as u16;
+    let search_range: u16 = (1 << entry_selector) * 16;
+    let mut font = Vec::new();
+    font.extend_from_slice(version);
+    font.extend_from_slice(&num_tables.to_be_bytes());
+    font.extend_from_slice(&search_range.to_be_bytes());
+    font.extend_from_slice(&entry_selector.to_be_bytes());
+    font.extend_from_slice(&(num_tables * 16 - search_range).to_be_bytes());
+    let mut offset = 12 + 16 * tables.len();
+    let mut head_offset = None;
+    for (tag, table) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        font.extend_from_slice(tag);
+        font.extend_from_slice(&checksum(table).to_be_bytes());
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += (table.len() + 3) / 4 * 4;
+    }
+    for table in tables.values() {
+        font.extend_from_slice(table);
+        pad(&mut font);
+    }
+    if let Some(head_offset) = head_offset.filter(|offset| offset + 12 <= font.len()) {
+        let adjustment = 0xB1B0_AFBA_u32.wrapping_sub(checksum(&font));
+        font[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+    font
+}
+
+/// Returns the offsets of the glyphs in the `glyf` table as stored in the `loca` table.
+///
+/// The returned vector contains one more entry than there are glyphs so that the data of the glyph
+/// with ID `i` is stored at `offsets[i]..offsets[i + 1]`.
+pub fn glyph_offsets(tables: &Tables<'_>) -> Option<Vec<usize>> {
+    let head = tables.get(b"head")?;
+    let loca = tables.get(b"loca")?;
+    let num_glyphs = usize::from(read_u16(tables.get(b"maxp")?, 4)?);
+    let is_long = read_u16(head, 50)? == 1;
+    (0..=num_glyphs)
+        .map(|i| {
+            if is_long {
+                read_u32(loca, 4 * i).map(|offset| offset as usize)
+            } else {
+                read_u16(loca, 2 * i).map(|offset| usize::from(offset) * 2)
+            }
+        })
+        .collect()
+}
+
+/// Writes the given glyphs to the `glyf` and `loca` tables of the given font and changes its
+/// `head` table to the long `loca` format so that the glyphs can be padded to four bytes.
+pub fn write_glyphs<'a>(tables: &mut Tables<'_>, glyphs: impl IntoIterator<Item = &'a [u8]>) {
+    let mut glyf = Vec::new();
+    let mut loca = Vec::new();
+    for glyph in glyphs {
+        loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+        glyf.extend_from_slice(glyph);
+        pad(&mut glyf);
+    }
+    loca.extend_from_slice(&(glyf.len() as u32).to_be_bytes());
+    if let Some(head) = tables.get_mut(b"head") {
+        if let Some(format) = head.to_mut().get_mut(50..52) {
+            format.copy_from_slice(&1u16.to_be_bytes());
+        }
+    }
+    tables.insert(*b"glyf", borrow::Cow::Owned(glyf));
+    tables.insert(*b"loca", borrow::Cow::Owned(loca));
+}
+
+/// Extracts the font with the given index from the given font collection as a standalone font.
+///
+/// Returns `None` if the data is not a font collection or if it does not contain a font with the
+/// given index.
+pub fn extract_font(data: &[u8], index: u32) -> Option<Vec<u8>> {
+    if !is_collection(data) {
+        return None;
+    }
+    let start = offset_table(data, index)?;
+    let version = data.get(start..start + 4)?;
+    let tables = read_tables(data, start)?;
+    Some(write_font(version, tables))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(0, checksum(&[]));
+        assert_eq!(0x0102_0304, checksum(&[1, 2, 3, 4]));
+        assert_eq!(0x0102_0304 + 0x0500_0000, checksum(&[1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn collections() {
+        let mut tables = Tables::new();
+        tables.insert(*b"abcd", borrow::Cow::Borrowed(&[1, 2, 3][..]));
+        let font = write_font(b"\0\x01\0\0", tables.clone());
+        assert_eq!(Some(tables), read_tables(&font, 0));
+        assert_eq!(Some(1), font_count(&font));
+        assert_eq!(Some(0), offset_table(&font, 0));
+        assert_eq!(None, offset_table(&font, 1));
+        assert_eq!(None, extract_font(&font, 0));
+
+        // A collection with two fonts that share the same table
+        let mut collection = b"ttcf\0\x01\0\0\0\0\0\x02".to_vec();
+        collection.extend_from_slice(&20u32.to_be_bytes());
+        collection.extend_from_slice(&20u32.to_be_bytes());
+        collection.extend_from_slice(&font[..12]);
+        collection.extend_from_slice(&font[12..20]);
+        collection.extend_from_slice(&48u32.to_be_bytes());
+        collection.extend_from_slice(&font[24..28]);
+        collection.extend_from_slice(&font[28..]);
+        assert_eq!(Some(2), font_count(&collection));
+        assert_eq!(Some(20), offset_table(&collection, 1));
+        assert_eq!(None, offset_table(&collection, 2));
+        assert_eq!(Some(font), extract_font(&collection, 1));
+        assert_eq!(None, extract_font(&collection, 2));
+    }
+}
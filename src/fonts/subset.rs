@@ -0,0 +1,123 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Subsetting of TrueType fonts.
+//!
+//! The text of a PDF document references the glyphs of embedded fonts by their glyph ID, so the
+//! subset fonts keep the glyph IDs of the original font.  The outlines of all unused glyphs are
+//! removed from the `glyf` table, and all tables that are not required for rendering the glyphs
+//! are dropped.
+
+use std::collections;
+
+use super::sfnt::{
+    self, read_u16, ARG_1_AND_2_ARE_WORDS, MORE_COMPONENTS, WE_HAVE_AN_X_AND_Y_SCALE,
+    WE_HAVE_A_SCALE, WE_HAVE_A_TWO_BY_TWO,
+};
+
+/// The tables that are kept in the subset font, sorted by tag.
+const TABLES: &[&[u8; 4]] = &[
+    b"OS/2", b"cmap", b"cvt ", b"fpgm", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp",
+    b"name", b"post", b"prep",
+];
+
+/// Returns the IDs of the components of the given glyph if it is a composite glyph.
+fn components(glyph: &[u8]) -> Option<Vec<u16>> {
+    let mut components = Vec::new();
+    // Simple glyphs have a non-negative number of contours, empty glyphs have no data at all.
+    if glyph.is_empty() || read_u16(glyph, 0)? & 0x8000 == 0 {
+        return Some(components);
+    }
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph, offset)?;
+        components.push(read_u16(glyph, offset + 2)?);
+        offset += 4;
+        offset += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        if flags & WE_HAVE_A_SCALE != 0 {
+            offset += 2;
+        } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            offset += 4;
+        } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+            offset += 8;
+        }
+        if flags & MORE_COMPONENTS == 0 {
+            return Some(components);
+        }
+    }
+}
+
+/// Creates a subset of the given TrueType font data that only contains the outlines of the given
+/// glyphs, the components of composite glyphs and the `.notdef` glyph.
+///
+/// Returns `None` if the data is not a single TrueType font with a `glyf` table, for example a
+/// font collection or an OpenType font with CFF outlines.
+pub fn subset_font(data: &[u8], glyph_ids: &collections::BTreeSet<u16>) -> Option<Vec<u8>> {
+    if sfnt::is_collection(data) {
+        return None;
+    }
+    let mut tables = sfnt::read_tables(data, 0)?;
+
+    // The glyf table is borrowed from the font data, so cloning it is cheap.
+    let glyf = tables.get(b"glyf")?.clone();
+    let num_glyphs = usize::from(read_u16(tables.get(b"maxp")?, 4)?);
+    let offsets = sfnt::glyph_offsets(&tables)?;
+    let glyph = |id: usize| glyf.get(offsets[id]..offsets[id + 1]);
+
+    let mut kept = collections::BTreeSet::new();
+    let mut queue: Vec<u16> = Some(0)
+        .into_iter()
+        .chain(glyph_ids.iter().copied())
+        .collect();
+    while let Some(id) = queue.pop() {
+        if usize::from(id) < num_glyphs && kept.insert(id) {
+            queue.extend(components(glyph(usize::from(id))?)?);
+        }
+    }
+
+    let glyphs = (0..num_glyphs)
+        .map(|id| {
+            if kept.contains(&(id as u16)) {
+                glyph(id)
+            } else {
+                Some(&[][..])
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    sfnt::write_glyphs(&mut tables, glyphs);
+    let tables = tables
+        .into_iter()
+        .filter(|(tag, _)| TABLES.contains(&tag))
+        .collect();
+    Some(sfnt::write_font(&data[..4], tables))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_glyphs() {
+        assert_eq!(Some(vec![]), components(&[]));
+        assert_eq!(Some(vec![]), components(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 0]));
+        let glyph = [
+            0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0, // header
+            0x00, 0x21, 0x00, 0x05, 0x00, 0x00, 0x00, 0x00, // words, more components
+            0x00, 0x08, 0x00, 0x07, 0x00, 0x00, 0x40, 0x00, // byte args, scale
+        ];
+        assert_eq!(Some(vec![5, 7]), components(&glyph));
+        assert_eq!(None, components(&glyph[..20]));
+    }
+
+    #[test]
+    fn invalid_fonts() {
+        let glyph_ids = collections::BTreeSet::new();
+        assert_eq!(None, subset_font(b"", &glyph_ids));
+        assert_eq!(None, subset_font(b"ttcf\0\x01\0\0", &glyph_ids));
+        assert_eq!(None, subset_font(b"\0\x01\0\0\0\0\0\0\0\0\0\0", &glyph_ids));
+    }
+}
@@ -0,0 +1,617 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Conversion of HTML documents into elements.
+//!
+//! *Only available if the `html` feature is enabled.*
+//!
+//! This module converts a document written in a constrained subset of HTML into a tree of
+//! elements that can be added to a [`Document`][], see [`to_elements`][].
+//!
+//! # Supported Elements
+//!
+//! - block elements:
+//!   - `<p>`, `<div>`, `<html>` and `<body>`
+//!   - `<h1>` to `<h6>`, converted to [`Heading`][] elements
+//!   - `<ul>` and `<ol>` (with an optional `start` attribute) with `<li>` items, converted to
+//!     [`UnorderedList`][] and [`OrderedList`][] elements
+//!   - `<table>` with `<tr>` rows that are optionally grouped in `<thead>`, `<tbody>` and
+//!     `<tfoot>`, and with `<th>` and `<td>` cells that may have a `colspan` attribute,
+//!     converted to [`TableLayout`][] elements with at most 1000 columns
+//! - inline elements:
+//!   - `<b>` and `<strong>`, `<i>` and `<em>`, `<u>`, `<s>` and `<del>`, `<sup>` and `<sub>`
+//!   - `<span>`
+//!   - `<a>` with an `href` attribute
+//!   - `<br>`
+//!
+//! All elements can have a `style` attribute with these CSS properties:  `color`,
+//! `background-color`, `font-weight`, `font-style`, `text-decoration` and `text-align`.
+//! Colors can be written as `#rgb`, `#rrggbb`, `rgb(r, g, b)` or with one of the basic color
+//! names.  Other properties are ignored.
+//!
+//! The document must be well-formed, i. e. all elements except for `<br>` must be closed
+//! explicitly.  Table cells may only contain inline content.  Comments and declarations like
+//! `<!DOCTYPE html>` are ignored.  The named entities `&lt;`, `&gt;`, `&amp;`, `&quot;`,
+//! `&apos;` and `&nbsp;` as well as numeric character references are supported.  If the document
+//! does not match these rules, an error with the kind [`ErrorKind::InvalidData`][] is returned.
+//!
+//! # Example
+//!
+//! ```
+//! use genpdf::html;
+//! let layout = html::to_elements(
+//!     "<h1>Report</h1><p>The results are <span style=\"color: #008000\">good</span>.</p>",
+//! )
+//! .expect("Invalid HTML");
+//! ```
+//!
+//! [`Document`]: ../struct.Document.html
+//! [`to_elements`]: fn.to_elements.html
+//! [`Heading`]: ../elements/struct.Heading.html
+//! [`UnorderedList`]: ../elements/struct.UnorderedList.html
+//! [`OrderedList`]: ../elements/struct.OrderedList.html
+//! [`TableLayout`]: ../elements/struct.TableLayout.html
+//! [`ErrorKind::InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+
+use crate::elements;
+use crate::error::{Error, ErrorKind};
+use crate::markup::{decode_entities, parse_color};
+use crate::style::{Style, StyledString};
+use crate::{Alignment, Element as _};
+
+/// Converts the given HTML document into a vertical layout of elements.
+///
+/// See the [module documentation](index.html) for the supported subset of HTML.  Consecutive
+/// blocks are separated by an empty line.
+pub fn to_elements(html: &str) -> Result<elements::LinearLayout, Error> {
+    let nodes = parse(html)?;
+    convert_blocks(&nodes, 1.0)
+}
+
+/// The maximum number of columns of a table, as in the `colspan` limit of the HTML standard.
+const MAX_COLUMNS: usize = 1000;
+
+fn invalid(msg: String) -> Error {
+    Error::new(msg, ErrorKind::InvalidData)
+}
+
+/// The attributes of an HTML element as pairs of lowercase names and decoded values.
+type Attributes = Vec<(String, String)>;
+
+/// A node of a parsed HTML document.
+#[derive(Clone, Debug)]
+enum Node {
+    Text(String),
+    Element {
+        name: String,
+        attributes: Attributes,
+        children: Vec<Node>,
+    },
+}
+
+impl Node {
+    fn is_blank(&self) -> bool {
+        matches!(self, Node::Text(text) if text.trim().is_empty())
+    }
+
+    fn children(&self) -> &[Node] {
+        match self {
+            Node::Text(_) => &[],
+            Node::Element { children, .. } => children,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Node::Text(text) => text.trim().to_owned(),
+            Node::Element { name, .. } => format!("<{}>", name),
+        }
+    }
+}
+
+const VOID_ELEMENTS: &[&str] = &["br"];
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "b", "br", "del", "em", "i", "s", "span", "strong", "sub", "sup", "u",
+];
+
+fn attribute<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, value)| value.as_str())
+}
+
+fn parse(html: &str) -> Result<Vec<Node>, Error> {
+    let mut nodes = Vec::new();
+    let mut stack: Vec<(String, Attributes, Vec<Node>)> = Vec::new();
+    let mut rest = html;
+    loop {
+        let idx = rest.find('<').unwrap_or(rest.len());
+        if idx > 0 {
+            let text = decode_entities(&rest[..idx])?;
+            let children = stack.last_mut().map(|e| &mut e.2).unwrap_or(&mut nodes);
+            children.push(Node::Text(text));
+        }
+        rest = &rest[idx..];
+        if rest.is_empty() {
+            break;
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment
+                .find("-->")
+                .ok_or_else(|| invalid("Unterminated comment in HTML".to_owned()))?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        let end = find_tag_end(rest)
+            .ok_or_else(|| invalid(format!("Unterminated tag in HTML: {}", rest)))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('!') || tag.starts_with('?') {
+            continue;
+        }
+
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim().to_ascii_lowercase();
+            match stack.pop() {
+                Some((open, attributes, children)) if open == name => {
+                    let parent = stack.last_mut().map(|e| &mut e.2).unwrap_or(&mut nodes);
+                    parent.push(Node::Element {
+                        name,
+                        attributes,
+                        children,
+                    });
+                }
+                _ => return Err(invalid(format!("Unexpected closing tag in HTML: {}", tag))),
+            }
+        } else {
+            let (tag, is_closed) = match tag.trim_end().strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let (name, attributes) = parse_tag(tag)?;
+            if is_closed || VOID_ELEMENTS.contains(&name.as_str()) {
+                let children = stack.last_mut().map(|e| &mut e.2).unwrap_or(&mut nodes);
+                children.push(Node::Element {
+                    name,
+                    attributes,
+                    children: Vec::new(),
+                });
+            } else {
+                stack.push((name, attributes, Vec::new()));
+            }
+        }
+    }
+    if let Some((name, _, _)) = stack.last() {
+        return Err(invalid(format!("Unclosed tag in HTML: {}", name)));
+    }
+    Ok(nodes)
+}
+
+/// Returns the index of the `>` character that ends the tag at the start of the given string.
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (idx, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(idx),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_tag(tag: &str) -> Result<(String, Attributes), Error> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = tag[..name_end].to_ascii_lowercase();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(invalid(format!("Invalid tag in HTML: <{}>", tag)));
+    }
+
+    let mut attributes = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let attribute = rest[..end].to_ascii_lowercase();
+        rest = rest[end..].trim_start();
+        let value = if let Some(value) = rest.strip_prefix('=') {
+            let value = value.trim_start();
+            let (raw, remainder) = match value.chars().next() {
+                Some(quote @ '"') | Some(quote @ '\'') => {
+                    let end = value[1..].find(quote).ok_or_else(|| {
+                        invalid(format!("Unterminated attribute value in HTML: {}", tag))
+                    })?;
+                    (&value[1..=end], &value[end + 2..])
+                }
+                _ => {
+                    let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                    (&value[..end], &value[end..])
+                }
+            };
+            rest = remainder.trim_start();
+            decode_entities(raw)?
+        } else {
+            String::new()
+        };
+        attributes.push((attribute, value));
+    }
+    Ok((name, attributes))
+}
+
+/// Parses the `style` attribute of an element.
+fn parse_style(attributes: &[(String, String)]) -> Result<Style, Error> {
+    let mut style = Style::new();
+    let css = attribute(attributes, "style").unwrap_or_default();
+    for declaration in css.split(';').filter(|d| !d.trim().is_empty()) {
+        let mut parts = declaration.splitn(2, ':');
+        let (property, value) = match (parts.next(), parts.next()) {
+            (Some(property), Some(value)) => (property, value),
+            _ => return Err(invalid(format!("Invalid style in HTML: {}", declaration))),
+        };
+        let property = property.trim().to_ascii_lowercase();
+        let value = value.trim().to_ascii_lowercase();
+        let invalid_value =
+            || invalid(format!("Invalid value for {} in HTML: {}", property, value));
+        match property.as_str() {
+            "color" => style.set_color(parse_color(&value).ok_or_else(invalid_value)?),
+            "background-color" => {
+                style.set_background(parse_color(&value).ok_or_else(invalid_value)?)
+            }
+            "font-weight" => match value.as_str() {
+                "bold" | "bolder" | "600" | "700" | "800" | "900" => style.set_bold(),
+                "normal" | "lighter" | "100" | "200" | "300" | "400" | "500" => {}
+                _ => return Err(invalid_value()),
+            },
+            "font-style" => match value.as_str() {
+                "italic" | "oblique" => style.set_italic(),
+                "normal" => {}
+                _ => return Err(invalid_value()),
+            },
+            "text-decoration" => {
+                for decoration in value.split_whitespace() {
+                    match decoration {
+                        "underline" => style.set_underline(),
+                        "line-through" => style.set_strikethrough(),
+                        "none" => {}
+                        _ => return Err(invalid_value()),
+                    }
+                }
+            }
+            "text-align" => style.set_alignment(match value.as_str() {
+                "left" | "start" => Alignment::Left,
+                "right" | "end" => Alignment::Right,
+                "center" => Alignment::Center,
+                "justify" => Alignment::Justify,
+                _ => return Err(invalid_value()),
+            }),
+            _ => {}
+        }
+    }
+    Ok(style)
+}
+
+/// A vertical layout of blocks that are separated by breaks with the given height.
+struct Blocks {
+    layout: elements::LinearLayout,
+    spacing: f64,
+    is_empty: bool,
+}
+
+impl Blocks {
+    fn push<E: elements::IntoBoxedElement>(&mut self, element: E) {
+        if !self.is_empty && self.spacing > 0.0 {
+            self.layout.push(elements::Break::new(self.spacing));
+        }
+        self.layout.push(element);
+        self.is_empty = false;
+    }
+
+    fn push_inline(&mut self, strings: &mut Vec<StyledString>) {
+        let strings = normalize_whitespace(std::mem::take(strings));
+        if !strings.is_empty() {
+            self.push(elements::Paragraph::from(strings));
+        }
+    }
+}
+
+fn convert_blocks(nodes: &[Node], spacing: f64) -> Result<elements::LinearLayout, Error> {
+    let mut blocks = Blocks {
+        layout: elements::LinearLayout::vertical(),
+        spacing,
+        is_empty: true,
+    };
+    let mut inline = Vec::new();
+    for node in nodes {
+        match node {
+            Node::Element {
+                name,
+                attributes,
+                children,
+            } if !INLINE_ELEMENTS.contains(&name.as_str()) => {
+                blocks.push_inline(&mut inline);
+                let style = parse_style(attributes)?;
+                convert_block(&mut blocks, name, attributes, children, style)?;
+            }
+            _ => convert_inline(node, Style::new(), None, &mut inline)?,
+        }
+    }
+    blocks.push_inline(&mut inline);
+    Ok(blocks.layout)
+}
+
+fn convert_block(
+    blocks: &mut Blocks,
+    name: &str,
+    attributes: &[(String, String)],
+    children: &[Node],
+    style: Style,
+) -> Result<(), Error> {
+    match name {
+        "html" | "body" | "div" | "p" => {
+            blocks.push(convert_blocks(children, blocks.spacing)?.styled(style));
+        }
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+            let level = name[1..]
+                .parse()
+                .map_err(|_| invalid(format!("Invalid HTML heading: {}", name)))?;
+            let mut strings = Vec::new();
+            for child in children {
+                convert_inline(child, Style::new(), None, &mut strings)?;
+            }
+            let text: String = normalize_whitespace(strings)
+                .into_iter()
+                .map(|s| s.s)
+                .collect();
+            blocks.push(elements::Heading::new(level, text).styled(style));
+        }
+        "ul" | "ol" => {
+            let mut items = Vec::new();
+            for child in children.iter().filter(|child| !child.is_blank()) {
+                match child {
+                    Node::Element {
+                        name,
+                        attributes,
+                        children,
+                    } if name == "li" => {
+                        let style = parse_style(attributes)?;
+                        items.push(convert_blocks(children, 0.0)?.styled(style));
+                    }
+                    _ => {
+                        return Err(invalid(format!(
+                            "Unexpected content in HTML list: {}",
+                            child.describe()
+                        )))
+                    }
+                }
+            }
+            if name == "ol" {
+                let start = match attribute(attributes, "start") {
+                    Some(start) => start
+                        .trim()
+                        .parse()
+                        .map_err(|_| invalid(format!("Invalid list start in HTML: {}", start)))?,
+                    None => 1,
+                };
+                let mut list = elements::OrderedList::with_start(start);
+                items.into_iter().for_each(|item| list.push(item));
+                blocks.push(list.styled(style));
+            } else {
+                let mut list = elements::UnorderedList::new();
+                items.into_iter().for_each(|item| list.push(item));
+                blocks.push(list.styled(style));
+            }
+        }
+        "table" => {
+            if let Some(table) = convert_table(children)? {
+                blocks.push(table.styled(style));
+            }
+        }
+        _ => return Err(invalid(format!("Unsupported element in HTML: <{}>", name))),
+    }
+    Ok(())
+}
+
+fn convert_table(children: &[Node]) -> Result<Option<elements::TableLayout>, Error> {
+    let mut rows = Vec::new();
+    let mut header_rows = 0;
+    for child in children.iter().filter(|child| !child.is_blank()) {
+        match child {
+            Node::Element { name, .. } if name == "tr" => rows.push(child),
+            Node::Element { name, children, .. }
+                if name == "thead" || name == "tbody" || name == "tfoot" =>
+            {
+                let start = rows.len();
+                for row in children.iter().filter(|child| !child.is_blank()) {
+                    match row {
+                        Node::Element { name, .. } if name == "tr" => rows.push(row),
+                        _ => {
+                            return Err(invalid(format!(
+                                "Unexpected content in HTML table: {}",
+                                row.describe()
+                            )))
+                        }
+                    }
+                }
+                if name == "thead" && header_rows == start {
+                    header_rows = rows.len();
+                }
+            }
+            _ => {
+                return Err(invalid(format!(
+                    "Unexpected content in HTML table: {}",
+                    child.describe()
+                )))
+            }
+        }
+    }
+
+    let mut cells = Vec::new();
+    for row in rows {
+        let mut row_cells = Vec::new();
+        for cell in row.children().iter().filter(|child| !child.is_blank()) {
+            match cell {
+                Node::Element {
+                    name,
+                    attributes,
+                    children,
+                } if name == "td" || name == "th" => {
+                    let span = match attribute(attributes, "colspan") {
+                        Some(span) => span
+                            .trim()
+                            .parse()
+                            .ok()
+                            .filter(|span| *span > 0 && *span <= MAX_COLUMNS)
+                            .ok_or_else(|| invalid(format!("Invalid colspan in HTML: {}", span)))?,
+                        None => 1,
+                    };
+                    let mut style = parse_style(attributes)?;
+                    if name == "th" {
+                        style = style.bold();
+                    }
+                    let mut strings = Vec::new();
+                    for child in children {
+                        convert_inline(child, Style::new(), None, &mut strings)?;
+                    }
+                    let paragraph = elements::Paragraph::from(normalize_whitespace(strings));
+                    row_cells.push((paragraph.styled(style).padded(1), span));
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "Unexpected content in HTML table row: {}",
+                        cell.describe()
+                    )))
+                }
+            }
+        }
+        cells.push(row_cells);
+    }
+
+    let columns = cells
+        .iter()
+        .map(|row| row.iter().map(|(_, span)| span).sum())
+        .max()
+        .unwrap_or(0);
+    if columns == 0 {
+        return Ok(None);
+    } else if columns > MAX_COLUMNS {
+        return Err(invalid(format!(
+            "HTML table has too many columns: {}",
+            columns
+        )));
+    }
+    let mut table = elements::TableLayout::new(vec![1; columns]);
+    table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+    table.set_header_rows(header_rows);
+    for row_cells in cells {
+        let mut row = table.row();
+        let mut count = 0;
+        for (cell, span) in row_cells {
+            count += span;
+            row.push_cloned_cell(cell, elements::CellSpan::new(span, 1));
+        }
+        for _ in count..columns {
+            row.push_cloned_element(elements::Paragraph::default().padded(1));
+        }
+        row.push()?;
+    }
+    Ok(Some(table))
+}
+
+fn convert_inline(
+    node: &Node,
+    style: Style,
+    link: Option<&str>,
+    strings: &mut Vec<StyledString>,
+) -> Result<(), Error> {
+    match node {
+        Node::Text(text) => {
+            let text: String = text
+                .chars()
+                .map(|c| if c.is_ascii_whitespace() { ' ' } else { c })
+                .collect();
+            let mut s = StyledString::new(text, style);
+            s.link = link.map(ToOwned::to_owned);
+            strings.push(s);
+        }
+        Node::Element {
+            name,
+            attributes,
+            children,
+        } => {
+            let mut style = style.and(parse_style(attributes)?);
+            let mut link = link;
+            match name.as_str() {
+                "b" | "strong" => style.set_bold(),
+                "i" | "em" => style.set_italic(),
+                "u" => style.set_underline(),
+                "s" | "del" => style.set_strikethrough(),
+                "sup" => style.set_superscript(),
+                "sub" => style.set_subscript(),
+                "a" => link = attribute(attributes, "href").or(link),
+                "span" => {}
+                "br" => {
+                    strings.push(StyledString::new("\n", style));
+                    return Ok(());
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "Unexpected element in HTML inline content: <{}>",
+                        name
+                    )))
+                }
+            }
+            for child in children {
+                convert_inline(child, style, link, strings)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collapses runs of spaces and removes spaces at the start and end of lines.
+fn normalize_whitespace(strings: Vec<StyledString>) -> Vec<StyledString> {
+    let mut result: Vec<StyledString> = Vec::new();
+    let mut skip_space = true;
+    for mut s in strings {
+        let mut text = String::with_capacity(s.s.len());
+        for c in s.s.chars() {
+            match c {
+                ' ' if skip_space => {}
+                '\n' => {
+                    if text.ends_with(' ') {
+                        text.pop();
+                    } else if text.is_empty() {
+                        if let Some(last) = result.last_mut() {
+                            if last.s.ends_with(' ') {
+                                last.s.pop();
+                            }
+                        }
+                    }
+                    text.push(c);
+                    skip_space = true;
+                }
+                _ => {
+                    text.push(c);
+                    skip_space = c == ' ';
+                }
+            }
+        }
+        s.s = text;
+        result.push(s);
+    }
+    result.retain(|s| !s.s.is_empty());
+    while let Some(last) = result.last_mut() {
+        let len = last.s.trim_end_matches(' ').len();
+        last.s.truncate(len);
+        if !last.s.is_empty() {
+            break;
+        }
+        result.pop();
+    }
+    result
+}
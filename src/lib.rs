@@ -66,7 +66,9 @@
 //! # Rendering Process
 //!
 //! The rendering process is started by calling the [`Document::render`][] or
-//! [`Document::render_to_file`][] methods.  You can only render a document once.  Before the
+//! [`Document::render_to_file`][] methods.  You can only render a document once:  If you call one
+//! of these methods again, it returns an error of the kind [`ErrorKind::AlreadyRendered`][], even
+//! if you added new elements to the document in the meantime.  Before the
 //! rendering starts, the PDF document is created and all loaded fonts are embedded into the
 //! document.
 //!
@@ -118,11 +120,12 @@
 //! # Known Issues
 //!
 //! - Currently, `genpdf` adds all loaded fonts to the PDF document, even if they are not used.
-//!   `printpdf` then adds all available glyphs for these fonts to the document, even if they are
-//!   not used in the document.  This increases the file size by 100–200 KiB per font (500–1000 KiB
-//!   per font family).  Until this is fixed, you can pass the generated file through `ps2pdf` to
-//!   significantly reduce its size.  Alternatively, you can use a built-in font if you don’t need
-//!   any characters that are not supported by the [Windows-1252][] encoding.
+//!   Per default, the embedded TrueType fonts are subset so that they only contain the glyphs
+//!   that are used in the document, see [`Document::set_font_subsetting`][].  But `printpdf`
+//!   still adds the widths and the Unicode mappings of all available glyphs to the document,
+//!   which increases the file size by a few KiB per font.  Fonts with CFF outlines are not
+//!   subset.  Alternatively, you can use a built-in font if you don’t need any characters that
+//!   are not supported by the [Windows-1252][] encoding.
 //!
 //! [`printpdf`]: https://docs.rs/printpdf
 //! [`rusttype`]: https://docs.rs/rusttype
@@ -132,6 +135,8 @@
 //! [`Document`]: struct.Document.html
 //! [`Document::render`]: struct.Document.html#method.render
 //! [`Document::render_to_file`]: struct.Document.html#method.render_to_file
+//! [`Document::set_font_subsetting`]: struct.Document.html#method.set_font_subsetting
+//! [`ErrorKind::AlreadyRendered`]: error/enum.ErrorKind.html#variant.AlreadyRendered
 //! [`Document::load_font_family`]: struct.Document.html#method.load_font_family
 //! [`Element`]: trait.Element.html
 //! [`Element::render`]: trait.Element.html#tymethod.render
@@ -153,14 +158,27 @@
 
 #![warn(missing_docs, rust_2018_idioms)]
 
+mod bidi;
+#[cfg(any(feature = "html", feature = "svg"))]
+mod markup;
+mod pdfa;
 mod wrap;
 
 pub mod elements;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod error;
 pub mod fonts;
+#[cfg(feature = "html")]
+pub mod html;
+#[cfg(feature = "markdown")]
+pub mod markdown;
 pub mod render;
+pub mod signature;
 pub mod style;
 
+use std::cell;
+use std::collections;
 use std::fs;
 use std::io;
 use std::path;
@@ -207,6 +225,11 @@ impl Mm {
     pub fn max(self, other: Mm) -> Mm {
         Mm(self.0.max(other.0))
     }
+
+    /// Returns the minimum of this value and the given value.
+    pub fn min(self, other: Mm) -> Mm {
+        Mm(self.0.min(other.0))
+    }
 }
 
 impl From<i8> for Mm {
@@ -276,9 +299,10 @@ impl From<Mm> for printpdf::Pt {
     }
 }
 
-/// The alignment of a [`Paragraph`][] or ['Image'][].
+/// The alignment of a [`Paragraph`][] or [`Image`][].
 ///
-/// The default alignment is left-flushed.
+/// The default alignment is left-flushed.  Justified alignment is only supported for paragraphs.
+/// For other elements, it is treated as left-flushed.
 ///
 /// [`Paragraph`]: elements/struct.Paragraph.html
 /// [`Image`]: elements/struct.Image.html
@@ -290,6 +314,14 @@ pub enum Alignment {
     Right,
     /// Centered.
     Center,
+    /// Justified, i. e. left- and right-flushed.
+    ///
+    /// The space between the words is stretched so that the lines fill the available width.  The
+    /// last line of the paragraph and lines that end with a forced line break are not stretched
+    /// and left-flushed per default, see [`Paragraph::set_last_line_alignment`][].
+    ///
+    /// [`Paragraph::set_last_line_alignment`]: elements/struct.Paragraph.html#method.set_last_line_alignment
+    Justify,
 }
 
 impl Default for Alignment {
@@ -298,6 +330,218 @@ impl Default for Alignment {
     }
 }
 
+/// The base direction of a paragraph, see [`Paragraph::set_direction`][].
+///
+/// The characters of a line are reordered according to the Unicode Bidirectional Algorithm so that
+/// right-to-left text, for example Arabic or Hebrew text, is rendered in the correct visual order.
+/// The base direction determines the order of the runs with different directions and the default
+/// alignment of the paragraph.
+///
+/// [`Paragraph::set_direction`]: elements/struct.Paragraph.html#method.set_direction
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Direction {
+    /// Left-to-right.
+    LeftToRight,
+    /// Right-to-left.  Paragraphs with this direction are right-flushed per default.
+    RightToLeft,
+    /// Determined by the first character of the paragraph with a strong direction.  If there is
+    /// no such character, left-to-right is used.
+    Auto,
+}
+
+impl Default for Direction {
+    fn default() -> Direction {
+        Direction::LeftToRight
+    }
+}
+
+/// The vertical alignment of the content of a page or a table cell.
+///
+/// The default alignment is top-aligned.  See [`Document::set_vertical_alignment`][] and
+/// [`TableLayout::set_vertical_alignment`][].
+///
+/// [`Document::set_vertical_alignment`]: struct.Document.html#method.set_vertical_alignment
+/// [`TableLayout::set_vertical_alignment`]: elements/struct.TableLayout.html#method.set_vertical_alignment
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum VerticalAlignment {
+    /// Top-aligned.
+    Top,
+    /// Centered.
+    Center,
+    /// Bottom-aligned.
+    Bottom,
+}
+
+impl Default for VerticalAlignment {
+    fn default() -> VerticalAlignment {
+        VerticalAlignment::Top
+    }
+}
+
+impl VerticalAlignment {
+    /// Returns the vertical offset for content with this alignment if the given amount of space
+    /// is unused.
+    fn offset(&self, free_space: Mm) -> Mm {
+        let free_space = free_space.max(Mm(0.0));
+        match self {
+            VerticalAlignment::Top => Mm(0.0),
+            VerticalAlignment::Center => free_space / 2.0,
+            VerticalAlignment::Bottom => free_space,
+        }
+    }
+}
+
+/// The treatment of consecutive whitespace in a [`Text`][] or [`Paragraph`][].
+///
+/// Per default, runs of spaces and tabs are collapsed into a single space.  This only affects
+/// spaces and tabs, but not the wrapping of the text.
+///
+/// [`Text`]: elements/struct.Text.html
+/// [`Paragraph`]: elements/struct.Paragraph.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Whitespace {
+    /// Replaces runs of spaces and tabs with a single space.
+    Collapse,
+    /// Preserves all spaces and replaces every tab with a space.
+    ///
+    /// If a paragraph is wrapped, a run of spaces is kept together with the preceding word so that
+    /// a line is never broken within the run.  The spaces at the end of a line count towards its
+    /// width.
+    Preserve,
+}
+
+impl Default for Whitespace {
+    fn default() -> Whitespace {
+        Whitespace::Collapse
+    }
+}
+
+/// The treatment of newline characters (`'\n'`) in a [`Text`][] or [`Paragraph`][].
+///
+/// Per default, a newline forces a line break.  A carriage return directly before a newline
+/// (`"\r\n"`) is ignored.
+///
+/// [`Text`]: elements/struct.Text.html
+/// [`Paragraph`]: elements/struct.Paragraph.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Newline {
+    /// Starts a new line after every newline character.
+    ///
+    /// A [`Text`][] is printed on multiple lines, but the lines are still not wrapped.  In a
+    /// justified [`Paragraph`][], a line that ends with a newline is aligned like the last line.
+    ///
+    /// [`Text`]: elements/struct.Text.html
+    /// [`Paragraph`]: elements/struct.Paragraph.html
+    Break,
+    /// Replaces every newline character with a space.
+    ///
+    /// The space is treated like any other space, i. e. it is collapsed with adjacent spaces
+    /// unless whitespace is preserved, see [`Whitespace`][].
+    ///
+    /// [`Whitespace`]: enum.Whitespace.html
+    Space,
+}
+
+impl Default for Newline {
+    fn default() -> Newline {
+        Newline::Break
+    }
+}
+
+/// The behavior if a [`Paragraph`][] is rendered into an area that is too narrow for its content.
+///
+/// This applies to areas without a positive width and to words that cannot be split so that they
+/// fit into a line.
+///
+/// [`Paragraph`]: elements/struct.Paragraph.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Overflow {
+    /// Returns an error of the kind [`ErrorKind::PageSizeExceeded`][].
+    ///
+    /// [`ErrorKind::PageSizeExceeded`]: error/enum.ErrorKind.html#variant.PageSizeExceeded
+    Error,
+    /// Prints the words that are too long on a line of their own and clips them at the border of
+    /// the area.  Nothing is printed if the area does not have a positive width.
+    Clip,
+    /// Silently discards the paragraph.
+    Skip,
+    /// Breaks the words that are too long at the last character that fits into the line.
+    ///
+    /// The parts of the word are followed by the mark set with
+    /// [`Document::set_word_break_mark`][], per default nothing.  Nothing is printed if the area
+    /// does not have a positive width.
+    ///
+    /// [`Document::set_word_break_mark`]: struct.Document.html#method.set_word_break_mark
+    Break,
+}
+
+impl Default for Overflow {
+    fn default() -> Overflow {
+        Overflow::Error
+    }
+}
+
+/// The treatment of the space added by [`Break`][] elements at page boundaries.
+///
+/// A page boundary is the top of a page, including the start of the document and the page after
+/// a [`PageBreak`][], and the bottom of a page if the break does not fit into the remaining
+/// space.  A page counts as empty until text, lines, shapes or images have been drawn on it by an
+/// element.  The content drawn by the page decorator does not count.
+///
+/// [`Break`]: elements/struct.Break.html
+/// [`PageBreak`]: elements/struct.PageBreak.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum BreakPolicy {
+    /// Always adds the space of a break.
+    ///
+    /// A break on an empty page adds empty space at the top of the page.  If a break does not
+    /// fit into the remaining space of a page, it fills the rest of the page and the following
+    /// elements are rendered on the next page.
+    Preserve,
+    /// Suppresses the space of a break at page boundaries.
+    ///
+    /// The following rules apply:
+    /// - A break on an empty page is discarded, so the page content starts at the top of the
+    ///   page.
+    /// - If a break does not fit into the remaining space of a page, it fills the rest of the
+    ///   page, but the remaining space of the break is discarded.
+    /// - A [`PageBreak`][] on an empty page is discarded if the previous page has been ended by a
+    ///   break, so that a break directly before a page break never produces an empty page.
+    ///
+    /// [`PageBreak`]: elements/struct.PageBreak.html
+    Collapse,
+}
+
+impl Default for BreakPolicy {
+    fn default() -> BreakPolicy {
+        BreakPolicy::Preserve
+    }
+}
+
+/// The line breaking algorithm used for a [`Paragraph`][].
+///
+/// [`Paragraph`]: elements/struct.Paragraph.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LineBreaking {
+    /// Fills every line with as many words as possible (first fit).
+    ///
+    /// This is fast and supports hyphenation.
+    Greedy,
+    /// Chooses the line breaks for the whole paragraph so that the lines are as even as possible.
+    ///
+    /// This uses a simplified version of the Knuth-Plass line breaking algorithm and produces
+    /// better results for justified paragraphs, but it is slower than greedy line breaking.
+    /// Words are never hyphenated.  If a word is longer than a line, greedy line breaking is used
+    /// instead.
+    Optimal,
+}
+
+impl Default for LineBreaking {
+    fn default() -> LineBreaking {
+        LineBreaking::Greedy
+    }
+}
+
 /// A position on a PDF layer, measured in millimeters.
 ///
 /// All positions used by `genpdf` are measured from the top left corner of the reference area.
@@ -440,26 +684,314 @@ impl<W: Into<Mm>, H: Into<Mm>> From<(W, H)> for Size {
 /// A paper size like A4, legal or letter.
 ///
 /// This enum provides variants for typical paper sizes that can be converted into [`Size`][]
-/// instances.
+/// instances.  Per default, the paper sizes are converted in portrait orientation.  Use the
+/// [`landscape`][] method to get the size in landscape orientation.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{Orientation, PaperSize, Size};
+/// assert_eq!(Size::new(297, 210), PaperSize::A4.landscape());
+/// assert_eq!(Size::new(297, 210), PaperSize::A4.size(Orientation::Landscape));
+/// assert_eq!(Size::from(PaperSize::A4), PaperSize::A4.portrait());
+/// ```
 ///
 /// [`Size`]: struct.Size.html
+/// [`landscape`]: #method.landscape
 #[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub enum PaperSize {
+    /// The A3 paper size (297x420mm).
+    A3,
     /// The A4 paper size (210x297mm).
     A4,
-    /// The legal paper size (216x356mm).
+    /// The A5 paper size (148x210mm).
+    A5,
+    /// The A6 paper size (105x148mm).
+    A6,
+    /// The legal paper size (8.5x14in, 215.9x355.6mm).
     Legal,
-    /// The letter paper size (216x279mm).
+    /// The letter paper size (8.5x11in, 215.9x279.4mm).
     Letter,
+    /// The tabloid paper size (11x17in, 279.4x431.8mm).
+    Tabloid,
+}
+
+impl PaperSize {
+    /// Returns the size of this paper size in the given orientation.
+    pub fn size(self, orientation: Orientation) -> Size {
+        let (width, height) = match self {
+            PaperSize::A3 => (297.0, 420.0),
+            PaperSize::A4 => (210.0, 297.0),
+            PaperSize::A5 => (148.0, 210.0),
+            PaperSize::A6 => (105.0, 148.0),
+            PaperSize::Legal => (215.9, 355.6),
+            PaperSize::Letter => (215.9, 279.4),
+            PaperSize::Tabloid => (279.4, 431.8),
+        };
+        match orientation {
+            Orientation::Portrait => Size::new(width, height),
+            Orientation::Landscape => Size::new(height, width),
+        }
+    }
+
+    /// Returns the size of this paper size in portrait orientation.
+    pub fn portrait(self) -> Size {
+        self.size(Orientation::Portrait)
+    }
+
+    /// Returns the size of this paper size in landscape orientation.
+    pub fn landscape(self) -> Size {
+        self.size(Orientation::Landscape)
+    }
 }
 
 impl From<PaperSize> for Size {
     fn from(size: PaperSize) -> Size {
-        match size {
-            PaperSize::A4 => Size::new(210, 297),
-            PaperSize::Legal => Size::new(216, 356),
-            PaperSize::Letter => Size::new(216, 279),
+        size.portrait()
+    }
+}
+
+/// The orientation of a page, see [`PaperSize::size`][].
+///
+/// [`PaperSize::size`]: enum.PaperSize.html#method.size
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Orientation {
+    /// The portrait orientation, i. e. the height of the page is larger than its width.
+    Portrait,
+    /// The landscape orientation, i. e. the width of the page is larger than its height.
+    Landscape,
+}
+
+/// The conformance level of a generated PDF document, see [`Document::set_conformance`][].
+///
+/// [`Document::set_conformance`]: struct.Document.html#method.set_conformance
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Conformance {
+    /// PDF/A-2b (ISO 19005-2, level B) for long-term archiving.
+    ///
+    /// The generated document contains an sRGB output intent and XMP metadata with the PDF/A
+    /// identification.  Features that are not allowed in PDF/A-2b documents cause an error of
+    /// the kind [`ErrorKind::UnsupportedFeature`][] when the document is rendered:
+    /// - built-in fonts, as all fonts must be embedded,
+    /// - CMYK colors, as they do not match the sRGB output intent, and
+    /// - characters that are not contained in the font that is used to print them.
+    ///
+    /// [`ErrorKind::UnsupportedFeature`]: error/enum.ErrorKind.html#variant.UnsupportedFeature
+    PdfA2b,
+    /// A conformance level that is handled by `printpdf`.
+    Printpdf(printpdf::PdfConformance),
+}
+
+impl From<printpdf::PdfConformance> for Conformance {
+    fn from(conformance: printpdf::PdfConformance) -> Conformance {
+        Conformance::Printpdf(conformance)
+    }
+}
+
+/// The boundary boxes of a page.
+///
+/// PDF pages have several boundary boxes that are used by viewers and printers:
+/// - The media box defines the boundaries of the physical medium.  It defaults to the paper size.
+/// - The crop box defines the region that is displayed or printed.  It defaults to the media box.
+/// - The trim box defines the intended dimensions of the finished page after trimming.  It
+///   defaults to the crop box.
+///
+/// The boxes are defined by the position of their upper left corner and their size.  The
+/// positions are relative to the upper left corner of the paper (the page area that is used for
+/// rendering the elements).  The boxes must be nested, i. e. the trim box must be contained in the
+/// crop box and the crop box must be contained in the media box.  Otherwise, an error is returned
+/// when rendering the page.
+///
+/// # Example
+///
+/// ```
+/// // A4 paper with 3 mm bleed
+/// let boxes = genpdf::PageBoxes::new()
+///     .with_trim_box((3, 3), (204, 291));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PageBoxes {
+    media_box: Option<(Position, Size)>,
+    crop_box: Option<(Position, Size)>,
+    trim_box: Option<(Position, Size)>,
+}
+
+impl PageBoxes {
+    /// Creates a new page box configuration with the default boxes.
+    pub fn new() -> PageBoxes {
+        PageBoxes::default()
+    }
+
+    /// Sets the media box.
+    pub fn set_media_box(&mut self, origin: impl Into<Position>, size: impl Into<Size>) {
+        self.media_box = Some((origin.into(), size.into()));
+    }
+
+    /// Sets the media box and returns the page boxes.
+    pub fn with_media_box(mut self, origin: impl Into<Position>, size: impl Into<Size>) -> Self {
+        self.set_media_box(origin, size);
+        self
+    }
+
+    /// Sets the crop box.
+    pub fn set_crop_box(&mut self, origin: impl Into<Position>, size: impl Into<Size>) {
+        self.crop_box = Some((origin.into(), size.into()));
+    }
+
+    /// Sets the crop box and returns the page boxes.
+    pub fn with_crop_box(mut self, origin: impl Into<Position>, size: impl Into<Size>) -> Self {
+        self.set_crop_box(origin, size);
+        self
+    }
+
+    /// Sets the trim box.
+    pub fn set_trim_box(&mut self, origin: impl Into<Position>, size: impl Into<Size>) {
+        self.trim_box = Some((origin.into(), size.into()));
+    }
+
+    /// Sets the trim box and returns the page boxes.
+    pub fn with_trim_box(mut self, origin: impl Into<Position>, size: impl Into<Size>) -> Self {
+        self.set_trim_box(origin, size);
+        self
+    }
+
+    /// Returns the media box for a page with the given paper size.
+    pub fn media_box(&self, paper_size: impl Into<Size>) -> (Position, Size) {
+        self.media_box
+            .unwrap_or_else(|| (Position::default(), paper_size.into()))
+    }
+
+    /// Returns the crop box for a page with the given paper size.
+    pub fn crop_box(&self, paper_size: impl Into<Size>) -> (Position, Size) {
+        self.crop_box.unwrap_or_else(|| self.media_box(paper_size))
+    }
+
+    /// Returns the trim box for a page with the given paper size.
+    pub fn trim_box(&self, paper_size: impl Into<Size>) -> (Position, Size) {
+        self.trim_box.unwrap_or_else(|| self.crop_box(paper_size))
+    }
+
+    /// Checks that the boxes for a page with the given paper size have a positive size and are
+    /// nested.
+    pub fn validate(&self, paper_size: impl Into<Size>) -> Result<(), error::Error> {
+        let paper_size = paper_size.into();
+        let boxes = [
+            ("media", self.media_box(paper_size)),
+            ("crop", self.crop_box(paper_size)),
+            ("trim", self.trim_box(paper_size)),
+        ];
+        for (name, (_, size)) in &boxes {
+            if size.width <= Mm(0.0) || size.height <= Mm(0.0) {
+                return Err(error::Error::new(
+                    format!("The {} box must have a positive size", name),
+                    error::ErrorKind::InvalidData,
+                ));
+            }
         }
+        for pair in boxes.windows(2) {
+            let (outer_name, (outer_origin, outer_size)) = pair[0];
+            let (inner_name, (inner_origin, inner_size)) = pair[1];
+            let is_contained = inner_origin.x >= outer_origin.x
+                && inner_origin.y >= outer_origin.y
+                && inner_origin.x + inner_size.width <= outer_origin.x + outer_size.width
+                && inner_origin.y + inner_size.height <= outer_origin.y + outer_size.height;
+            if !is_contained {
+                return Err(error::Error::new(
+                    format!(
+                        "The {} box must be contained in the {} box",
+                        inner_name, outer_name
+                    ),
+                    error::ErrorKind::InvalidData,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A thumbnail image of a page.
+///
+/// Some PDF viewers and file browsers display the thumbnail as a preview of the page instead of
+/// rendering it.  The thumbnail is stored as an uncompressed RGB image with eight bits per color
+/// component, so it should be small, for example 106 × 106 pixels.  See
+/// [`Document::set_thumbnail`][].
+///
+/// [`Document::set_thumbnail`]: struct.Document.html#method.set_thumbnail
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thumbnail {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// Creates a new thumbnail with the given size in pixels and the given RGB data.
+    ///
+    /// The data must contain three bytes (red, green and blue) for every pixel, row by row and
+    /// starting with the upper left corner.  If the size is zero or if the length of the data does
+    /// not match the size, an error of the kind [`ErrorKind::InvalidData`][] is returned.
+    ///
+    /// [`ErrorKind::InvalidData`]: error/enum.ErrorKind.html#variant.InvalidData
+    pub fn from_rgb(
+        width: u32,
+        height: u32,
+        data: impl Into<Vec<u8>>,
+    ) -> Result<Thumbnail, error::Error> {
+        let data = data.into();
+        if width == 0 || height == 0 {
+            return Err(error::Error::new(
+                "The thumbnail must have a positive size",
+                error::ErrorKind::InvalidData,
+            ));
+        }
+        if data.len() as u64 != u64::from(width) * u64::from(height) * 3 {
+            return Err(error::Error::new(
+                format!(
+                    "Expected {} bytes of RGB data for a {}x{} thumbnail, got {}",
+                    u64::from(width) * u64::from(height) * 3,
+                    width,
+                    height,
+                    data.len()
+                ),
+                error::ErrorKind::InvalidData,
+            ));
+        }
+        Ok(Thumbnail {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Creates a new thumbnail from the given image.
+    ///
+    /// *Only available if the `images` feature is enabled.*
+    ///
+    /// The image is converted to RGB.  Transparency information is discarded.
+    #[cfg(feature = "images")]
+    pub fn from_image(image: &image::DynamicImage) -> Thumbnail {
+        let image = image.to_rgb8();
+        Thumbnail {
+            width: image.width(),
+            height: image.height(),
+            data: image.into_raw(),
+        }
+    }
+
+    /// Returns the width of this thumbnail in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of this thumbnail in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the RGB data of this thumbnail.
+    pub fn data(&self) -> &[u8] {
+        &self.data
     }
 }
 
@@ -569,10 +1101,26 @@ pub struct Document {
     context: Context,
     style: style::Style,
     paper_size: Size,
+    page_boxes: Option<PageBoxes>,
+    page_boxes_for_pages: collections::BTreeMap<usize, PageBoxes>,
+    thumbnail: Option<Thumbnail>,
+    vertical_alignment: VerticalAlignment,
+    vertical_alignments_for_pages: collections::BTreeMap<usize, VerticalAlignment>,
+    is_mirrored: bool,
+    mirrored_for_pages: collections::BTreeMap<usize, bool>,
     decorator: Option<Box<dyn PageDecorator>>,
-    conformance: Option<printpdf::PdfConformance>,
+    conformance: Option<Conformance>,
+    metadata: render::Metadata,
+    #[cfg(feature = "encryption")]
+    encryption: Option<encryption::EncryptionOptions>,
+    attachments: Vec<render::Attachment>,
+    use_object_streams: bool,
+    use_font_subsetting: bool,
+    post_processor: Option<render::PostProcessor>,
+    snap_grid: Option<Mm>,
     creation_date: Option<printpdf::OffsetDateTime>,
     modification_date: Option<printpdf::OffsetDateTime>,
+    is_rendered: bool,
 }
 
 impl Document {
@@ -585,20 +1133,62 @@ impl Document {
             context: Context::new(font_cache),
             style: style::Style::new(),
             paper_size: PaperSize::A4.into(),
+            page_boxes: None,
+            page_boxes_for_pages: collections::BTreeMap::new(),
+            thumbnail: None,
+            vertical_alignment: VerticalAlignment::default(),
+            vertical_alignments_for_pages: collections::BTreeMap::new(),
+            is_mirrored: false,
+            mirrored_for_pages: collections::BTreeMap::new(),
             decorator: None,
             conformance: None,
+            metadata: render::Metadata::default(),
+            #[cfg(feature = "encryption")]
+            encryption: None,
+            attachments: Vec::new(),
+            use_object_streams: false,
+            use_font_subsetting: true,
+            post_processor: None,
+            snap_grid: None,
             creation_date: None,
             modification_date: None,
+            is_rendered: false,
         }
     }
 
     /// Adds the given font family to the font cache for this document and returns a reference to
     /// it.
     ///
+    /// You can register any number of font families.  The returned reference is a handle that
+    /// can be used to select the font family for an element or a string, see
+    /// [`Style::with_font_family`][].  All registered fonts are embedded into the generated PDF
+    /// document.  Fonts with identical data are only embedded once.
+    ///
     /// Note that the returned font reference may only be used for this document.  It cannot be
     /// shared with other `Document` or [`FontCache`][] instances.
     ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use genpdf::{elements, fonts, style, Element as _};
+    /// let font_family = fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let mut doc = genpdf::Document::new(font_family);
+    ///
+    /// let code_font = fonts::from_files("./fonts", "LiberationMono", None)
+    ///     .expect("Failed to load font family");
+    /// let code_font = doc.add_font_family(code_font);
+    ///
+    /// doc.push(
+    ///     elements::Paragraph::new("Use the ")
+    ///         .styled_string("push", style::Style::new().with_font_family(code_font))
+    ///         .string(" method to add elements to a document."),
+    /// );
+    /// doc.push(elements::Paragraph::new("fn main() {}").styled(code_font));
+    /// ```
+    ///
     /// [`FontCache`]: fonts/struct.FontCache.html
+    /// [`Style::with_font_family`]: style/struct.Style.html#method.with_font_family
     pub fn add_font_family(
         &mut self,
         font_family: fonts::FontFamily<fonts::FontData>,
@@ -606,16 +1196,64 @@ impl Document {
         self.context.font_cache.add_font_family(font_family)
     }
 
+    /// Adds the given font family to the font cache for this document, registers it as a fallback
+    /// font family and returns a reference to it.
+    ///
+    /// Characters that are not supported by the font of a string, for example CJK characters or
+    /// emoji, are rendered with the first fallback font family that supports them instead of the
+    /// replacement glyph of the font.  The fallback font families are tried in the order they have
+    /// been added.  The style of the string is preserved, so bold text is rendered with the bold
+    /// font of the fallback font family.  See [`FontCache::add_fallback_font_family`][] for more
+    /// information.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use genpdf::{elements, fonts};
+    /// let font_family = fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let mut doc = genpdf::Document::new(font_family);
+    ///
+    /// let cjk_font = fonts::from_files("./fonts", "NotoSansCJK", None)
+    ///     .expect("Failed to load font family");
+    /// doc.add_fallback_font_family(cjk_font);
+    ///
+    /// doc.push(elements::Paragraph::new("Hello, 世界!"));
+    /// ```
+    ///
+    /// [`FontCache::add_fallback_font_family`]: fonts/struct.FontCache.html#method.add_fallback_font_family
+    pub fn add_fallback_font_family(
+        &mut self,
+        font_family: fonts::FontFamily<fonts::FontData>,
+    ) -> fonts::FontFamily<fonts::Font> {
+        self.context
+            .font_cache
+            .add_fallback_font_family(font_family)
+    }
+
     /// Returns the font cache used by this document.
     ///
     /// You can use the font cache to get the default font and to query glyph metrics for a font.
-    /// Use the [`load_font_family`][] method instead if you want to add fonts to this document.
+    /// Use the [`add_font_family`][] method instead if you want to add fonts to this document.
     ///
-    /// [`load_font_family`]: #method.load_font_family
+    /// [`add_font_family`]: #method.add_font_family
     pub fn font_cache(&self) -> &fonts::FontCache {
         &self.context.font_cache
     }
 
+    /// Returns all characters that could not be rendered because the font that was used to print
+    /// them does not contain a glyph for them.
+    ///
+    /// This method should be called after the document has been rendered, see [`render`][].  The
+    /// missing characters are replaced with the replacement glyph of the font in the generated
+    /// document.  See [`FontCache::missing_glyphs`][] for more information.
+    ///
+    /// [`render`]: #method.render
+    /// [`FontCache::missing_glyphs`]: fonts/struct.FontCache.html#method.missing_glyphs
+    pub fn missing_glyphs(&self) -> collections::BTreeSet<char> {
+        self.context.font_cache.missing_glyphs()
+    }
+
     /// Activates hyphenation and sets the hyphentor to use.
     ///
     /// *Only available if the `hyphenation` feature is enabled.*
@@ -624,6 +1262,116 @@ impl Document {
         self.context.hyphenator = Some(hyphenator);
     }
 
+    /// Adds a hyphenator for the language of its dictionary.
+    ///
+    /// The hyphenator is used for text in its language, see [`set_language`][] and
+    /// [`LanguageElement`][].  Language tags are compared case-insensitively, and a hyphenator
+    /// for a language without a region, for example `fr`, is also used for all regional variants
+    /// like `fr-FR`.  For text without a matching hyphenator, the hyphenator set with
+    /// [`set_hyphenator`][] is used.
+    ///
+    /// *Only available if the `hyphenation` feature is enabled.*
+    ///
+    /// [`set_language`]: #method.set_language
+    /// [`set_hyphenator`]: #method.set_hyphenator
+    /// [`LanguageElement`]: elements/struct.LanguageElement.html
+    #[cfg(feature = "hyphenation")]
+    pub fn add_hyphenator(&mut self, hyphenator: hyphenation::Standard) {
+        self.context.hyphenators.push(hyphenator);
+    }
+
+    /// Sets the natural language of this document.
+    ///
+    /// The language is given as a language tag as defined in [RFC 3066][], for example `en-US`.
+    /// It is written to the document catalog so that screen readers and other assistive
+    /// technologies can pronounce the text correctly, and it is used to select the hyphenator, see
+    /// [`add_hyphenator`][].  Use [`LanguageElement`][] for parts of the document that are
+    /// written in a different language.  If this method is not called, no language is set.
+    ///
+    /// [RFC 3066]: https://tools.ietf.org/html/rfc3066
+    /// [`add_hyphenator`]: #method.add_hyphenator
+    /// [`LanguageElement`]: elements/struct.LanguageElement.html
+    pub fn set_language(&mut self, language: impl Into<String>) {
+        self.context.replace_language(Some(language.into()));
+    }
+
+    /// Sets the style for headings with the given level, see [`Heading`][].
+    ///
+    /// The style is merged into the style of the parent element of the heading.  If this method
+    /// is not called, a font size between 20 points (level 1) and 12 points (levels 4 to 6) is
+    /// used.  Levels 1 to 5 are bold, levels 5 and 6 are italic.  Levels outside of the range from
+    /// 1 to 6 are ignored.
+    ///
+    /// [`Heading`]: elements/struct.Heading.html
+    pub fn set_heading_style(&mut self, level: usize, style: impl Into<style::Style>) {
+        if let Some(heading_style) = level
+            .checked_sub(1)
+            .and_then(|idx| self.context.heading_styles.get_mut(idx))
+        {
+            *heading_style = style.into();
+        }
+    }
+
+    /// Sets whether headings are prefixed with their section number, see [`Heading`][].
+    ///
+    /// If this method is not called, headings are not numbered.
+    ///
+    /// [`Heading`]: elements/struct.Heading.html
+    pub fn set_heading_numbering(&mut self, is_heading_numbering: bool) {
+        self.context.is_heading_numbering = is_heading_numbering;
+    }
+
+    /// Sets the behavior for paragraphs that do not fit into the width of their area, see
+    /// [`Overflow`][].
+    ///
+    /// If this method is not called, an error is returned for these paragraphs.
+    ///
+    /// [`Overflow`]: enum.Overflow.html
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.context.overflow.set(overflow);
+    }
+
+    /// Sets the mark that is printed after the parts of words that are broken because they do
+    /// not fit into a line, see [`Overflow::Break`][].
+    ///
+    /// If this method is not called, no mark is printed.
+    ///
+    /// [`Overflow::Break`]: enum.Overflow.html#variant.Break
+    pub fn set_word_break_mark(&mut self, mark: impl Into<String>) {
+        self.context.word_break_mark = mark.into();
+    }
+
+    /// Sets the treatment of the space added by [`Break`][] elements at page boundaries, see
+    /// [`BreakPolicy`][].
+    ///
+    /// If this method is not called, the space of breaks is always preserved.
+    ///
+    /// [`Break`]: elements/struct.Break.html
+    /// [`BreakPolicy`]: enum.BreakPolicy.html
+    pub fn set_break_policy(&mut self, policy: BreakPolicy) {
+        self.context.break_policy = policy;
+    }
+
+    /// Sets the default minimum number of lines of a paragraph that are moved to the top of the
+    /// next page if the paragraph is split, see [`elements::Paragraph::set_widows`][].
+    ///
+    /// If this method is not called, a single line is allowed.
+    ///
+    /// [`elements::Paragraph::set_widows`]: elements/struct.Paragraph.html#method.set_widows
+    pub fn set_widows(&mut self, lines: usize) {
+        self.context.widows = lines;
+    }
+
+    /// Sets the default minimum number of lines of a paragraph that are kept at the bottom of the
+    /// page if the paragraph is split, see [`elements::Paragraph::set_orphans`][].
+    ///
+    /// If this method is not called, a single line is allowed.
+    ///
+    /// [`elements::Paragraph::set_orphans`]: elements/struct.Paragraph.html#method.set_orphans
+    pub fn set_orphans(&mut self, lines: usize) {
+        self.context.orphans = lines;
+    }
+
     /// Sets the title of the PDF document.
     ///
     /// If this method is not called, the PDF title will be empty.
@@ -631,6 +1379,64 @@ impl Document {
         self.title = title.into();
     }
 
+    /// Sets the author of the PDF document.
+    pub fn set_author(&mut self, author: impl Into<String>) {
+        self.metadata.author = Some(author.into());
+    }
+
+    /// Sets the subject of the PDF document.
+    pub fn set_subject(&mut self, subject: impl Into<String>) {
+        self.metadata.subject = Some(subject.into());
+    }
+
+    /// Sets the keywords of the PDF document.
+    ///
+    /// The keywords are joined with commas.
+    pub fn set_keywords<I, S>(&mut self, keywords: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let keywords: Vec<_> = keywords
+            .into_iter()
+            .map(|keyword| keyword.as_ref().to_owned())
+            .collect();
+        self.metadata.keywords = Some(keywords.join(", "));
+    }
+
+    /// Sets the name of the application that created the content of the PDF document.
+    pub fn set_creator(&mut self, creator: impl Into<String>) {
+        self.metadata.creator = Some(creator.into());
+    }
+
+    /// Sets the name of the application that produced the PDF document.
+    pub fn set_producer(&mut self, producer: impl Into<String>) {
+        self.metadata.producer = Some(producer.into());
+    }
+
+    /// Adds a custom entry with the given key and value to the document information dictionary
+    /// of the PDF document.
+    ///
+    /// The author, subject, keywords, creator and producer are written to the document
+    /// information dictionary and to the XMP metadata of the document.  Custom entries are only
+    /// written to the document information dictionary.  If the key is one of the standard keys,
+    /// for example `Author`, the value is overwritten by the corresponding setter, for example
+    /// [`set_author`](#method.set_author).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None).unwrap();
+    /// let mut doc = genpdf::Document::new(font_family);
+    /// doc.set_title("Invoice 2021-001");
+    /// doc.set_author("ACME Inc.");
+    /// doc.set_keywords(vec!["invoice", "2021"]);
+    /// doc.set_custom_metadata("InvoiceNumber", "2021-001");
+    /// ```
+    pub fn set_custom_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.metadata.custom.insert(key.into(), value.into());
+    }
+
     /// Sets the default font size in points for this document.
     ///
     /// If this method is not called, the default value of 12 points is used.
@@ -645,15 +1451,120 @@ impl Document {
         self.style.set_line_spacing(line_spacing);
     }
 
+    /// Sets the default leading, i. e. the distance between the baselines of consecutive lines,
+    /// for this document.
+    ///
+    /// This replaces the line spacing factor, see [`Style::set_leading`][].  If this method is
+    /// not called, the line height of the font is used.
+    ///
+    /// [`Style::set_leading`]: style/struct.Style.html#method.set_leading
+    pub fn set_leading(&mut self, leading: impl Into<Mm>) {
+        self.style.set_leading(leading);
+    }
+
+    /// Sets the default alignment for the paragraphs and headings of this document.
+    ///
+    /// The alignment can be overridden for single elements, either with their `set_alignment`
+    /// method or with the style of the element or one of its parents, see
+    /// [`Style::set_alignment`][].  If this method is not called, the default alignment
+    /// [`Alignment::Left`][] is used.
+    ///
+    /// [`Style::set_alignment`]: style/struct.Style.html#method.set_alignment
+    /// [`Alignment::Left`]: enum.Alignment.html#variant.Left
+    pub fn set_default_alignment(&mut self, alignment: Alignment) {
+        self.style.set_alignment(alignment);
+    }
+
     /// Sets the paper size for all pages of this document.
     ///
-    /// If this method is not called, the default size [`A4`][] is used.
+    /// If this method is not called, the default size [`A4`][] is used.  You can use the
+    /// [`PaperSize`][] variants to select a typical paper size and orientation:
+    ///
+    /// ```no_run
+    /// # let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None)
+    /// #     .expect("Failed to load font family");
+    /// let mut doc = genpdf::Document::new(font_family);
+    /// doc.set_paper_size(genpdf::PaperSize::Letter.landscape());
+    /// ```
     ///
     /// [`A4`]: enum.PaperSize.html#variant.A4
+    /// [`PaperSize`]: enum.PaperSize.html
     pub fn set_paper_size(&mut self, paper_size: impl Into<Size>) {
         self.paper_size = paper_size.into();
     }
 
+    /// Sets the boundary boxes for all pages of this document.
+    ///
+    /// If this method is not called, all boxes are set to the paper size.  See [`PageBoxes`][] for
+    /// more information.
+    ///
+    /// [`PageBoxes`]: struct.PageBoxes.html
+    pub fn set_page_boxes(&mut self, page_boxes: PageBoxes) {
+        self.page_boxes = Some(page_boxes);
+    }
+
+    /// Sets the boundary boxes for the page with the given number, starting with 1.
+    ///
+    /// This overrides the boxes set with [`set_page_boxes`][] for this page.  See [`PageBoxes`][]
+    /// for more information.
+    ///
+    /// [`set_page_boxes`]: #method.set_page_boxes
+    /// [`PageBoxes`]: struct.PageBoxes.html
+    pub fn set_page_boxes_for_page(&mut self, page: usize, page_boxes: PageBoxes) {
+        self.page_boxes_for_pages.insert(page, page_boxes);
+    }
+
+    /// Sets the thumbnail image for the first page of this document.
+    ///
+    /// Some PDF viewers and file browsers use the thumbnail as a preview of the document.  If this
+    /// method is not called, no thumbnail is added.
+    pub fn set_thumbnail(&mut self, thumbnail: Thumbnail) {
+        self.thumbnail = Some(thumbnail);
+    }
+
+    /// Sets the vertical alignment of the content of all pages of this document.
+    ///
+    /// The content is aligned within the area that remains after the page decorator has been
+    /// applied, see [`set_page_decorator`][].  This is useful for short pages, for example a title
+    /// page with a single centered paragraph.  If the content of a page is split across pages, the
+    /// alignment only affects the space that is left at the end of the page.  If this method is
+    /// not called, the content is top-aligned.
+    ///
+    /// [`set_page_decorator`]: #method.set_page_decorator
+    pub fn set_vertical_alignment(&mut self, alignment: VerticalAlignment) {
+        self.vertical_alignment = alignment;
+    }
+
+    /// Sets the vertical alignment of the content of the page with the given number, starting
+    /// with 1.
+    ///
+    /// This overrides the alignment set with [`set_vertical_alignment`][] for this page.
+    ///
+    /// [`set_vertical_alignment`]: #method.set_vertical_alignment
+    pub fn set_vertical_alignment_for_page(&mut self, page: usize, alignment: VerticalAlignment) {
+        self.vertical_alignments_for_pages.insert(page, alignment);
+    }
+
+    /// Sets whether the content of all pages of this document is mirrored horizontally.
+    ///
+    /// If this option is set, the complete page content, including text, images, shapes and the
+    /// content added by the page decorator, is flipped at the vertical center line of the page.
+    /// This is required for some print processes, for example for iron-on transfers.  If this
+    /// method is not called, the pages are not mirrored.
+    pub fn set_mirrored(&mut self, is_mirrored: bool) {
+        self.is_mirrored = is_mirrored;
+    }
+
+    /// Sets whether the content of the page with the given number, starting with 1, is mirrored
+    /// horizontally.
+    ///
+    /// This overrides the setting of [`set_mirrored`][] for this page.
+    ///
+    /// [`set_mirrored`]: #method.set_mirrored
+    pub fn set_mirrored_for_page(&mut self, page: usize, is_mirrored: bool) {
+        self.mirrored_for_pages.insert(page, is_mirrored);
+    }
+
     /// Sets the page decorator for this document.
     ///
     /// The page decorator is called for every page before it is filled with the document content.
@@ -667,8 +1578,89 @@ impl Document {
     }
 
     /// Sets the PDF conformance settings for this document.
-    pub fn set_conformance(&mut self, conformance: printpdf::PdfConformance) {
-        self.conformance = Some(conformance);
+    ///
+    /// This can either be one of the conformance levels supported by `printpdf` or
+    /// [`Conformance::PdfA2b`][] to generate PDF/A-2b documents for long-term archiving.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None).unwrap();
+    /// let mut doc = genpdf::Document::new(font_family);
+    /// doc.set_conformance(genpdf::Conformance::PdfA2b);
+    /// ```
+    ///
+    /// [`Conformance::PdfA2b`]: enum.Conformance.html#variant.PdfA2b
+    pub fn set_conformance(&mut self, conformance: impl Into<Conformance>) {
+        self.conformance = Some(conformance.into());
+    }
+
+    /// Sets the encryption settings for this document.
+    ///
+    /// The generated PDF document is encrypted and protected with the passwords and permissions
+    /// set in the given options.  See the [`encryption`](encryption/index.html) module for more
+    /// information.  Encrypted documents cannot be PDF/A documents, so rendering the document
+    /// fails with an error of the kind [`ErrorKind::UnsupportedFeature`][] if this method is
+    /// combined with [`Conformance::PdfA2b`][].
+    ///
+    /// *Only available if the `encryption` feature is enabled.*
+    ///
+    /// [`ErrorKind::UnsupportedFeature`]: error/enum.ErrorKind.html#variant.UnsupportedFeature
+    /// [`Conformance::PdfA2b`]: enum.Conformance.html#variant.PdfA2b
+    #[cfg(feature = "encryption")]
+    pub fn set_encryption(&mut self, options: encryption::EncryptionOptions) {
+        self.encryption = Some(options);
+    }
+
+    /// Embeds a file with the given name, content and MIME type in this document.
+    ///
+    /// The file is added as an attachment with an unspecified relationship to the document.  To
+    /// set a description or the relationship, use [`add_attachment`][] instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None).unwrap();
+    /// let mut doc = genpdf::Document::new(font_family);
+    /// doc.attach_file("data.csv", "id,amount\n1,42\n", "text/csv");
+    /// ```
+    ///
+    /// [`add_attachment`]: #method.add_attachment
+    pub fn attach_file(
+        &mut self,
+        name: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+        mime_type: impl Into<String>,
+    ) {
+        self.add_attachment(render::Attachment::new(name, data, mime_type));
+    }
+
+    /// Embeds the given attachment in this document.
+    ///
+    /// The names of all attachments must be unique.  Otherwise, rendering the document fails with
+    /// an error of the kind [`ErrorKind::InvalidData`][].  PDF/A-2b documents may only contain
+    /// PDF/A attachments, so rendering the document fails with an error of the kind
+    /// [`ErrorKind::UnsupportedFeature`][] if this method is combined with
+    /// [`Conformance::PdfA2b`][].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use genpdf::render::{Attachment, AttachmentRelationship};
+    /// # let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None).unwrap();
+    /// let mut doc = genpdf::Document::new(font_family);
+    /// doc.add_attachment(
+    ///     Attachment::new("factur-x.xml", "<rsm:CrossIndustryInvoice/>", "text/xml")
+    ///         .with_description("Factur-X invoice")
+    ///         .with_relationship(AttachmentRelationship::Alternative),
+    /// );
+    /// ```
+    ///
+    /// [`ErrorKind::InvalidData`]: error/enum.ErrorKind.html#variant.InvalidData
+    /// [`ErrorKind::UnsupportedFeature`]: error/enum.ErrorKind.html#variant.UnsupportedFeature
+    /// [`Conformance::PdfA2b`]: enum.Conformance.html#variant.PdfA2b
+    pub fn add_attachment(&mut self, attachment: render::Attachment) {
+        self.attachments.push(attachment);
     }
 
     /// Sets the minimal PDF conformance settings for this document.
@@ -685,6 +1677,79 @@ impl Document {
         ));
     }
 
+    /// Sets whether the generated PDF file uses object streams.
+    ///
+    /// If object streams are enabled, most objects of the PDF file are packed into compressed
+    /// object streams.  This can significantly reduce the file size, especially for documents with
+    /// many small objects.  It also raises the PDF version of the document to 1.5, so some very old
+    /// PDF readers might not be able to open the file.  If this method is not called, object
+    /// streams are disabled.
+    pub fn set_object_streams(&mut self, use_object_streams: bool) {
+        self.use_object_streams = use_object_streams;
+    }
+
+    /// Sets the size of the grid that the coordinates of lines and shapes are snapped to.
+    ///
+    /// If this option is set, the coordinates of lines, frames and borders are rounded to the
+    /// nearest multiple of the grid size, for example 0.25 mm, so that thin lines look crisp in
+    /// PDF viewers.  Text and images are not affected.  If this method is not called, the
+    /// coordinates are not snapped.
+    pub fn set_snap_grid(&mut self, grid: impl Into<Mm>) {
+        self.snap_grid = Some(grid.into());
+    }
+
+    /// Sets whether the embedded fonts of the generated PDF document are subset.
+    ///
+    /// If font subsetting is enabled, the embedded fonts only contain the glyphs that are used in
+    /// the document, which reduces the size of the generated document significantly.  Fonts that
+    /// are not used at all only contain the replacement glyph.  Disable font subsetting if the
+    /// complete fonts should be embedded, for example if the document is edited later.  Per
+    /// default, font subsetting is enabled.
+    pub fn set_font_subsetting(&mut self, use_font_subsetting: bool) {
+        self.use_font_subsetting = use_font_subsetting;
+    }
+
+    /// Sets a function that is called with the generated PDF document before it is written.
+    ///
+    /// This is an advanced escape hatch for features that are not supported by this crate, for
+    /// example custom catalog entries or additional metadata.  The function is called after all
+    /// other processing steps and can modify the [`lopdf::Document`][] in place.  If it returns an
+    /// error, rendering is aborted and the error is returned by [`render`][].
+    ///
+    /// This API is unstable: the structure of the generated document and the `lopdf` version may
+    /// change with every release.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use genpdf::error::Context as _;
+    /// let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None)
+    ///     .expect("Failed to load font family");
+    /// let mut doc = genpdf::Document::new(font_family);
+    /// doc.set_post_processor(|pdf| {
+    ///     let root = pdf
+    ///         .trailer
+    ///         .get(b"Root")
+    ///         .and_then(lopdf::Object::as_reference)
+    ///         .context("Missing document catalog")?;
+    ///     let catalog = pdf
+    ///         .get_object_mut(root)
+    ///         .and_then(lopdf::Object::as_dict_mut)
+    ///         .context("Invalid document catalog")?;
+    ///     catalog.set("PageLayout", "TwoColumnLeft");
+    ///     Ok(())
+    /// });
+    /// ```
+    ///
+    /// [`lopdf::Document`]: https://docs.rs/lopdf/0.26.0/lopdf/struct.Document.html
+    /// [`render`]: #method.render
+    pub fn set_post_processor(
+        &mut self,
+        f: impl FnOnce(&mut lopdf::Document) -> Result<(), error::Error> + 'static,
+    ) {
+        self.post_processor = Some(Box::new(f));
+    }
+
     /// Sets the creation date of the PDF file.
     pub fn set_creation_date(&mut self, date: printpdf::OffsetDateTime) {
         self.creation_date = Some(date);
@@ -707,16 +1772,87 @@ impl Document {
         self.root.push(element);
     }
 
+    /// Returns whether this document has already been rendered.
+    ///
+    /// See [`render`][] for more information.
+    ///
+    /// [`render`]: #method.render
+    pub fn is_rendered(&self) -> bool {
+        self.is_rendered
+    }
+
     /// Renders this document into a PDF file and writes it to the given writer.
     ///
     /// The given writer is always wrapped in a buffered writer.  For details on the rendering
     /// process, see the [Rendering Process section of the crate
     /// documentation](index.html#rendering-process).
-    pub fn render(mut self, w: impl io::Write) -> Result<(), error::Error> {
+    ///
+    /// A document can only be rendered once as the elements discard their content while they are
+    /// rendered.  If this method or [`render_to_file`][] is called again, it returns an error of the
+    /// kind [`ErrorKind::AlreadyRendered`][] without writing any data.  This also applies if new
+    /// elements have been added using [`push`][] after the document has been rendered.  To render an
+    /// updated preview of a document, create a new `Document` instance.
+    ///
+    /// [`push`]: #method.push
+    /// [`render_to_file`]: #method.render_to_file
+    /// [`ErrorKind::AlreadyRendered`]: error/enum.ErrorKind.html#variant.AlreadyRendered
+    pub fn render(&mut self, w: impl io::Write) -> Result<(), error::Error> {
+        self.render_pages()?.write(w)
+    }
+
+    /// Renders this document into a PDF file, signs it with the given signer and writes it to the
+    /// given writer.
+    ///
+    /// The signature is attached to the first signature field of the document, for example a
+    /// [`SignatureLine`][] with a signature field.  If the document does not contain a signature
+    /// field, an invisible signature field is added to the first page.  Encrypted documents cannot
+    /// be signed.  See the [`signature`](signature/index.html) module for more information.
+    ///
+    /// Like [`render`][], this method returns an error if the document has already been rendered.
+    ///
+    /// [`render`]: #method.render
+    /// [`SignatureLine`]: elements/struct.SignatureLine.html
+    pub fn render_signed(
+        &mut self,
+        w: impl io::Write,
+        signer: impl signature::Signer,
+    ) -> Result<(), error::Error> {
+        self.render_pages()?.write_signed(w, signer)
+    }
+
+    fn render_pages(&mut self) -> Result<render::Renderer, error::Error> {
+        if self.is_rendered {
+            return Err(error::Error::new(
+                "The document has already been rendered",
+                error::ErrorKind::AlreadyRendered,
+            ));
+        }
+        self.is_rendered = true;
+
         let mut renderer = render::Renderer::new(self.paper_size, &self.title)?;
-        if let Some(conformance) = self.conformance {
+        if let Some(conformance) = self.conformance.clone() {
             renderer = renderer.with_conformance(conformance);
         }
+        renderer = renderer.with_metadata(self.metadata.clone());
+        #[cfg(feature = "encryption")]
+        if let Some(encryption) = self.encryption.clone() {
+            renderer = renderer.with_encryption(encryption);
+        }
+        for attachment in &self.attachments {
+            renderer = renderer.with_attachment(attachment.clone());
+        }
+        if self.use_object_streams {
+            renderer = renderer.with_object_streams(true);
+        }
+        if let Some(post_processor) = self.post_processor.take() {
+            renderer = renderer.with_post_processor(post_processor);
+        }
+        if let Some(snap_grid) = self.snap_grid {
+            renderer = renderer.with_snap_grid(snap_grid);
+        }
+        if let Some(language) = self.context.language() {
+            renderer = renderer.with_language(language);
+        }
         if let Some(creation_date) = self.creation_date {
             renderer = renderer.with_creation_date(creation_date);
         }
@@ -724,25 +1860,92 @@ impl Document {
             renderer = renderer.with_modification_date(modification_date);
         }
         self.context.font_cache.load_pdf_fonts(&renderer)?;
+        if let Some(thumbnail) = self.thumbnail.take() {
+            renderer.first_page_mut().set_thumbnail(thumbnail);
+        }
         loop {
-            let mut area = renderer.last_page().last_layer().area();
-            if let Some(decorator) = &mut self.decorator {
-                area = decorator.decorate_page(&self.context, area, self.style)?;
-            }
-            let result = self.root.render(&self.context, area, self.style)?;
-            if result.has_more {
-                if result.size == Size::new(0, 0) {
-                    return Err(error::Error::new(
-                        "Could not fit an element on a new page",
-                        error::ErrorKind::PageSizeExceeded,
-                    ));
-                }
+            let page = renderer.page_count();
+            self.context.page.set(page);
+            // All errors that occur while rendering a page are annotated with the page number.
+            let has_more = self
+                .render_page(&mut renderer, page)
+                .map_err(|err| err.with_page(page))?;
+            if has_more {
                 renderer.add_page(self.paper_size);
             } else {
                 break;
             }
         }
-        renderer.write(w)
+        if self.use_font_subsetting {
+            self.context.font_cache.subset_pdf_fonts(&mut renderer);
+        }
+        if self.conformance == Some(Conformance::PdfA2b) {
+            let missing_glyphs = self.context.font_cache.missing_glyphs();
+            if !missing_glyphs.is_empty() {
+                let missing_glyphs: String = missing_glyphs.into_iter().collect();
+                return Err(error::Error::new(
+                    format!(
+                        "Missing glyphs are not allowed in PDF/A documents: {}",
+                        missing_glyphs
+                    ),
+                    error::ErrorKind::UnsupportedFeature,
+                ));
+            }
+        }
+        Ok(renderer)
+    }
+
+    /// Renders the content of the last page of the given renderer and returns whether there is
+    /// more content that has to be rendered on the following pages.
+    fn render_page(
+        &mut self,
+        renderer: &mut render::Renderer,
+        page: usize,
+    ) -> Result<bool, error::Error> {
+        let page_boxes = self
+            .page_boxes_for_pages
+            .get(&page)
+            .or(self.page_boxes.as_ref());
+        if let Some(page_boxes) = page_boxes {
+            renderer.last_page_mut().set_boxes(*page_boxes)?;
+        }
+        let is_mirrored = self
+            .mirrored_for_pages
+            .get(&page)
+            .copied()
+            .unwrap_or(self.is_mirrored);
+        renderer.last_page_mut().set_mirrored(is_mirrored);
+        let mut area = renderer.last_page().last_layer().area();
+        if let Some(decorator) = &mut self.decorator {
+            area = decorator.decorate_page(&self.context, area, self.style)?;
+            renderer.last_page().clear_content();
+        }
+        let alignment = self
+            .vertical_alignments_for_pages
+            .get(&page)
+            .copied()
+            .unwrap_or(self.vertical_alignment);
+        let (root, context, style) = (&mut self.root, &self.context, self.style);
+        let result = if alignment == VerticalAlignment::Top {
+            root.render(context, area, style)?
+        } else {
+            let height = area.size().height;
+            area.with_translation(
+                |area| root.render(context, area, style),
+                |result| Position::new(0, alignment.offset(height - result.size.height)),
+            )?
+        };
+        if result.has_more && result.size == Size::new(0, 0) {
+            let mut err = error::Error::new(
+                "Could not fit an element on a new page",
+                error::ErrorKind::PageSizeExceeded,
+            );
+            if let Some((idx, type_name)) = self.root.current_element() {
+                err = err.with_element(idx, type_name);
+            }
+            return Err(err);
+        }
+        Ok(result.has_more)
     }
 
     /// Renders this document into a PDF file at the given path.
@@ -750,13 +1953,84 @@ impl Document {
     /// If the given file does not exist, it is created.  If it exists, it is overwritten.
     ///
     /// For details on the rendering process, see the [Rendering Process section of the crate
-    /// documentation](index.html#rendering-process).
-    pub fn render_to_file(self, path: impl AsRef<path::Path>) -> Result<(), error::Error> {
+    /// documentation](index.html#rendering-process).  Like [`render`][], this method returns an
+    /// error if the document has already been rendered.  In this case, the file is not created.
+    ///
+    /// [`render`]: #method.render
+    pub fn render_to_file(&mut self, path: impl AsRef<path::Path>) -> Result<(), error::Error> {
+        if self.is_rendered {
+            return Err(error::Error::new(
+                "The document has already been rendered",
+                error::ErrorKind::AlreadyRendered,
+            ));
+        }
         let path = path.as_ref();
         let file = fs::File::create(path)
             .with_context(|| format!("Could not create file {}", path.display()))?;
         self.render(file)
     }
+
+    /// Renders a document that depends on its own layout, for example because it contains a
+    /// [`TableOfContents`][], and writes it to the given writer.
+    ///
+    /// As the elements discard their content while they are rendered, the document has to be
+    /// created anew for every layout pass by the given function.  Every pass is rendered with the
+    /// headings and the page count of the previous pass, see [`Context::previous_headings`][] and
+    /// [`Context::page_count`][].  The document is rendered until the headings and the page count
+    /// of two consecutive passes are identical, but at most five times.  Only the output of the
+    /// last pass is written to the writer.  If the layout does not converge, an error of the kind
+    /// [`ErrorKind::Internal`][] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn build() -> Result<genpdf::Document, genpdf::error::Error> {
+    /// #     let font_family = genpdf::fonts::from_builtin(genpdf::fonts::Builtin::Helvetica);
+    /// let mut doc = genpdf::Document::new(font_family);
+    /// doc.push(genpdf::elements::TableOfContents::new());
+    /// doc.push(genpdf::elements::Heading::new(1, "Introduction"));
+    /// # Ok(doc)
+    /// # }
+    /// let file = std::fs::File::create("output.pdf").expect("Failed to create file");
+    /// genpdf::Document::render_in_passes(build, file).expect("Failed to render document");
+    /// ```
+    ///
+    /// [`TableOfContents`]: elements/struct.TableOfContents.html
+    /// [`Context::previous_headings`]: struct.Context.html#method.previous_headings
+    /// [`Context::page_count`]: struct.Context.html#method.page_count
+    /// [`ErrorKind::Internal`]: error/enum.ErrorKind.html#variant.Internal
+    pub fn render_in_passes<F>(mut build: F, mut w: impl io::Write) -> Result<(), error::Error>
+    where
+        F: FnMut() -> Result<Document, error::Error>,
+    {
+        const MAX_PASSES: usize = 5;
+
+        let mut previous_headings = None;
+        let mut previous_page_count = None;
+        for _ in 0..MAX_PASSES {
+            let mut doc = build()?;
+            doc.context.previous_headings = previous_headings.take();
+            doc.context.page_count = previous_page_count;
+            let mut output = Vec::new();
+            doc.render(&mut output)?;
+            let headings = doc.context.headings();
+            let page_count = doc.context.page.get();
+            if doc.context.previous_headings.as_ref() == Some(&headings)
+                && doc.context.page_count == Some(page_count)
+            {
+                return w.write_all(&output).context("Failed to write the document");
+            }
+            previous_headings = Some(headings);
+            previous_page_count = Some(page_count);
+        }
+        Err(error::Error::new(
+            format!(
+                "The layout of the document did not converge after {} passes",
+                MAX_PASSES
+            ),
+            error::ErrorKind::Internal,
+        ))
+    }
 }
 
 impl<E: elements::IntoBoxedElement> std::iter::Extend<E> for Document {
@@ -790,7 +2064,9 @@ pub struct RenderResult {
 /// If you set an implementation of this trait for a [`Document`][] using the
 /// [`set_page_decorator`][] method, its [`decorate_page`][] method is called every time a new page
 /// is added to the document.  The decorator can prepare the page before it is filled with the
-/// actual content.  See [`SimplePageDecorator`][] for a basic implementation.
+/// actual content.  As the decorator is called before the content is rendered, everything it draws
+/// is placed underneath the content, for example a watermark or a page border.  See
+/// [`SimplePageDecorator`][] for a basic implementation.
 ///
 /// [`Document`]: struct.Document.html
 /// [`set_page_decorator`]: struct.Document.html#method.set_page_decorator
@@ -810,21 +2086,30 @@ pub trait PageDecorator {
 }
 
 type HeaderCallback = Box<dyn Fn(usize) -> Box<dyn Element>>;
+type FooterCallback = Box<dyn Fn(usize, Option<usize>) -> Box<dyn Element>>;
+type BackgroundCallback = Box<dyn Fn(usize, Option<usize>) -> Box<dyn Element>>;
 
-/// Prepares a page of a document with margins and a header.
+/// Prepares a page of a document with margins, a header and a footer.
 ///
 /// Per default, this decorator does not modify the page.  If margins have been set with the
 /// [`set_margins`][] method, they are applied to every page.  If a header callback is configured
 /// with the [`set_header`][] method, it will be called for every page and its return value will be
-/// rendered at the beginning of the page (after the margins have been applied).
+/// rendered at the beginning of the page (after the margins have been applied).  Similarly, the
+/// return value of the footer callback configured with the [`set_footer`][] method will be
+/// rendered at the end of the page.  The return value of the background callback configured with
+/// the [`set_background`][] method is rendered on the full page, underneath the page content.
 ///
 /// [`set_margins`]: #method.set_margins
 /// [`set_header`]: #method.set_header
+/// [`set_footer`]: #method.set_footer
+/// [`set_background`]: #method.set_background
 #[derive(Default)]
 pub struct SimplePageDecorator {
     page: usize,
     margins: Option<Margins>,
     header_cb: Option<HeaderCallback>,
+    footer_cb: Option<FooterCallback>,
+    background_cb: Option<BackgroundCallback>,
 }
 
 impl SimplePageDecorator {
@@ -853,6 +2138,66 @@ impl SimplePageDecorator {
         // We manually box the return type of the callback so that it is easier to write closures.
         self.header_cb = Some(Box::new(move |page| Box::new(cb(page))));
     }
+
+    /// Sets the footer generator for this document.
+    ///
+    /// The given closure will be called once per page.  Its arguments are the page number
+    /// (starting with 1) and the total number of pages, and its return value will be rendered at
+    /// the bottom of the page.  The document content will end directly before the element.
+    ///
+    /// The total number of pages is only known if the document is rendered with
+    /// [`Document::render_in_passes`][], see [`Context::page_count`][].  Otherwise, and in the
+    /// first layout pass, it is `None`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use genpdf::{elements, SimplePageDecorator};
+    /// let mut decorator = SimplePageDecorator::new();
+    /// decorator.set_footer(|page, total| match total {
+    ///     Some(total) => elements::Text::new(format!("Page {} of {}", page, total)),
+    ///     None => elements::Text::new(format!("Page {}", page)),
+    /// });
+    /// ```
+    ///
+    /// [`Document::render_in_passes`]: struct.Document.html#method.render_in_passes
+    /// [`Context::page_count`]: struct.Context.html#method.page_count
+    pub fn set_footer<F, E>(&mut self, cb: F)
+    where
+        F: Fn(usize, Option<usize>) -> E + 'static,
+        E: Element + 'static,
+    {
+        self.footer_cb = Some(Box::new(move |page, total| Box::new(cb(page, total))));
+    }
+
+    /// Sets the background generator for this document.
+    ///
+    /// The given closure will be called once per page.  Its arguments are the page number
+    /// (starting with 1) and the total number of pages (see [`set_footer`][]), and its return value
+    /// will be rendered on the full page, ignoring the margins, before the header, the footer and
+    /// the document content.  It does not reduce the area that is available for the content.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use genpdf::{elements, style, Alignment, Element as _, SimplePageDecorator};
+    /// let mut decorator = SimplePageDecorator::new();
+    /// decorator.set_background(|_, _| {
+    ///     elements::Paragraph::new("DRAFT")
+    ///         .aligned(Alignment::Center)
+    ///         .styled(style::Style::new().with_font_size(72))
+    ///         .with_opacity(0.2)
+    /// });
+    /// ```
+    ///
+    /// [`set_footer`]: #method.set_footer
+    pub fn set_background<F, E>(&mut self, cb: F)
+    where
+        F: Fn(usize, Option<usize>) -> E + 'static,
+        E: Element + 'static,
+    {
+        self.background_cb = Some(Box::new(move |page, total| Box::new(cb(page, total))));
+    }
 }
 
 impl PageDecorator for SimplePageDecorator {
@@ -863,6 +2208,9 @@ impl PageDecorator for SimplePageDecorator {
         style: style::Style,
     ) -> Result<render::Area<'a>, error::Error> {
         self.page += 1;
+        if let Some(cb) = &self.background_cb {
+            cb(self.page, context.page_count()).render(context, area.clone(), style)?;
+        }
         if let Some(margins) = self.margins {
             area.add_margins(margins);
         }
@@ -871,6 +2219,18 @@ impl PageDecorator for SimplePageDecorator {
             let result = element.render(context, area.clone(), style)?;
             area.add_offset(Position::new(0, result.size.height));
         }
+        if let Some(cb) = &self.footer_cb {
+            // Measuring may consume the content of the element, so we render a new one.
+            let total = context.page_count();
+            let height = cb(self.page, total)
+                .measure(context, area.clone(), style)?
+                .size
+                .height;
+            let mut footer_area = area.clone();
+            footer_area.add_offset(Position::new(0, area.size().height - height));
+            cb(self.page, total).render(context, footer_area, style)?;
+            area.set_height(area.size().height - height);
+        }
         Ok(area)
     }
 }
@@ -928,6 +2288,16 @@ pub trait Element {
         style: style::Style,
     ) -> Result<RenderResult, error::Error>;
 
+    /// Returns the type name of this element that is used to identify it in error messages.
+    ///
+    /// Per default, this is the full name of the implementing type, see
+    /// [`std::any::type_name`][].
+    ///
+    /// [`std::any::type_name`]: https://doc.rust-lang.org/std/any/fn.type_name.html
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
     /// Draws a frame around this element using the given line style.
     fn framed(self, line_style: impl Into<style::LineStyle>) -> elements::FramedElement<Self>
     where
@@ -952,6 +2322,67 @@ pub trait Element {
         elements::StyledElement::new(self, style.into())
     }
 
+    /// Draws this element with the given opacity, from 0 (transparent) to 1 (opaque).
+    fn with_opacity(self, opacity: f64) -> elements::TransparentElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::TransparentElement::new(self, opacity)
+    }
+
+    /// Adds margins around this element, see [`elements::MarginedElement`][].
+    ///
+    /// [`elements::MarginedElement`]: elements/struct.MarginedElement.html
+    fn with_margins(self, margins: impl Into<Margins>) -> elements::MarginedElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::MarginedElement::new(self, margins)
+    }
+
+    /// Rotates this element clockwise by the given rotation, see [`elements::RotatedElement`][].
+    ///
+    /// [`elements::RotatedElement`]: elements/struct.RotatedElement.html
+    fn rotated(self, rotation: impl Into<Rotation>) -> elements::RotatedElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::RotatedElement::new(self, rotation)
+    }
+
+    /// Marks this element as written in the given natural language, for example `fr-FR`.
+    fn with_language(self, language: impl Into<String>) -> elements::LanguageElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::LanguageElement::new(self, language)
+    }
+
+    /// Adds a link to the given anchor that covers this element, see [`elements::Anchor`][].
+    ///
+    /// [`elements::Anchor`]: elements/struct.Anchor.html
+    fn link_to(self, anchor: impl Into<render::Anchor>) -> elements::LinkedElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::LinkedElement::new(self, anchor)
+    }
+
+    /// Adds a bookmark with the given title and level for this element to the outline of the
+    /// document, see [`elements::BookmarkedElement`][].
+    ///
+    /// [`elements::BookmarkedElement`]: elements/struct.BookmarkedElement.html
+    fn with_bookmark(
+        self,
+        title: impl Into<String>,
+        level: usize,
+    ) -> elements::BookmarkedElement<Self>
+    where
+        Self: Sized,
+    {
+        elements::BookmarkedElement::new(self, title, level)
+    }
+
     /// Measures the size of this element in the given area using the given style and font cache.
     ///
     /// This method is called to determine the size of the element before rendering it.
@@ -989,12 +2420,50 @@ pub struct Context {
     /// If this field is `None`, hyphenation is disabled.
     #[cfg(feature = "hyphenation")]
     pub hyphenator: Option<hyphenation::Standard>,
+    #[cfg(feature = "hyphenation")]
+    hyphenators: Vec<hyphenation::Standard>,
+    language: cell::RefCell<Option<String>>,
+    heading_styles: [style::Style; elements::Heading::MAX_LEVEL],
+    is_heading_numbering: bool,
+    heading_counters: cell::RefCell<[usize; elements::Heading::MAX_LEVEL]>,
+    headings: cell::RefCell<Vec<elements::HeadingEntry>>,
+    previous_headings: Option<Vec<elements::HeadingEntry>>,
+    page_count: Option<usize>,
+    page: cell::Cell<usize>,
+    overflow: cell::Cell<Overflow>,
+    list_depth: cell::Cell<usize>,
+    list_levels: cell::RefCell<elements::ListLevels>,
+    break_policy: BreakPolicy,
+    break_overflow_page: cell::Cell<Option<usize>>,
+    margin_end: cell::Cell<Option<(usize, Position, Mm)>>,
+    widows: usize,
+    orphans: usize,
+    word_break_mark: String,
 }
 
 impl Context {
     #[cfg(not(feature = "hyphenation"))]
     fn new(font_cache: fonts::FontCache) -> Context {
-        Context { font_cache }
+        Context {
+            font_cache,
+            language: Default::default(),
+            heading_styles: Self::default_heading_styles(),
+            is_heading_numbering: false,
+            heading_counters: Default::default(),
+            headings: Default::default(),
+            previous_headings: None,
+            page_count: None,
+            page: cell::Cell::new(1),
+            overflow: cell::Cell::new(Overflow::Error),
+            list_depth: cell::Cell::new(0),
+            list_levels: Default::default(),
+            break_policy: BreakPolicy::Preserve,
+            break_overflow_page: cell::Cell::new(None),
+            margin_end: cell::Cell::new(None),
+            widows: 1,
+            orphans: 1,
+            word_break_mark: String::new(),
+        }
     }
 
     #[cfg(feature = "hyphenation")]
@@ -1002,7 +2471,158 @@ impl Context {
         Context {
             font_cache,
             hyphenator: None,
+            hyphenators: Vec::new(),
+            language: Default::default(),
+            heading_styles: Self::default_heading_styles(),
+            is_heading_numbering: false,
+            heading_counters: Default::default(),
+            headings: Default::default(),
+            previous_headings: None,
+            page_count: None,
+            page: cell::Cell::new(1),
+            overflow: cell::Cell::new(Overflow::Error),
+            list_depth: cell::Cell::new(0),
+            list_levels: Default::default(),
+            break_policy: BreakPolicy::Preserve,
+            break_overflow_page: cell::Cell::new(None),
+            margin_end: cell::Cell::new(None),
+            widows: 1,
+            orphans: 1,
+            word_break_mark: String::new(),
+        }
+    }
+
+    fn default_heading_styles() -> [style::Style; elements::Heading::MAX_LEVEL] {
+        let style = style::Style::new().bold();
+        [
+            style.with_font_size(20),
+            style.with_font_size(16),
+            style.with_font_size(14),
+            style.with_font_size(12),
+            style.italic().with_font_size(12),
+            style::Style::new().italic().with_font_size(12),
+        ]
+    }
+
+    /// Returns the number of the page that is currently rendered, starting with 1.
+    pub fn page_number(&self) -> usize {
+        self.page.get()
+    }
+
+    /// Returns the natural language of the element that is currently rendered, if set.
+    ///
+    /// This is the language set with [`Document::set_language`][] or the language of the innermost
+    /// [`LanguageElement`][] that contains the current element.
+    ///
+    /// [`Document::set_language`]: struct.Document.html#method.set_language
+    /// [`LanguageElement`]: elements/struct.LanguageElement.html
+    pub fn language(&self) -> Option<String> {
+        self.language.borrow().clone()
+    }
+
+    /// Sets the language of the element that is currently rendered and returns the previous
+    /// language.
+    fn replace_language(&self, language: Option<String>) -> Option<String> {
+        self.language.replace(language)
+    }
+
+    /// Returns the behavior for paragraphs that do not fit into the width of their area, see
+    /// [`Document::set_overflow`][].
+    ///
+    /// [`Document::set_overflow`]: struct.Document.html#method.set_overflow
+    pub fn overflow(&self) -> Overflow {
+        self.overflow.get()
+    }
+
+    /// Returns the treatment of the space added by breaks at page boundaries, see
+    /// [`Document::set_break_policy`][].
+    ///
+    /// [`Document::set_break_policy`]: struct.Document.html#method.set_break_policy
+    pub fn break_policy(&self) -> BreakPolicy {
+        self.break_policy
+    }
+
+    /// Returns the default minimum number of lines of a paragraph at the top of a page, see
+    /// [`Document::set_widows`][].
+    ///
+    /// [`Document::set_widows`]: struct.Document.html#method.set_widows
+    pub fn widows(&self) -> usize {
+        self.widows
+    }
+
+    /// Returns the default minimum number of lines of a paragraph at the bottom of a page, see
+    /// [`Document::set_orphans`][].
+    ///
+    /// [`Document::set_orphans`]: struct.Document.html#method.set_orphans
+    pub fn orphans(&self) -> usize {
+        self.orphans
+    }
+
+    /// Returns the mark that is printed after the parts of broken words, see
+    /// [`Document::set_word_break_mark`][].
+    ///
+    /// [`Document::set_word_break_mark`]: struct.Document.html#method.set_word_break_mark
+    pub fn word_break_mark(&self) -> &str {
+        &self.word_break_mark
+    }
+
+    /// Sets the behavior for paragraphs that do not fit into the width of their area and returns
+    /// the previous behavior.
+    fn replace_overflow(&self, overflow: Overflow) -> Overflow {
+        self.overflow.replace(overflow)
+    }
+
+    /// Returns the style for headings with the given level, see
+    /// [`Document::set_heading_style`][].
+    ///
+    /// [`Document::set_heading_style`]: struct.Document.html#method.set_heading_style
+    pub fn heading_style(&self, level: usize) -> style::Style {
+        self.heading_styles[level.max(1).min(elements::Heading::MAX_LEVEL) - 1]
+    }
+
+    /// Returns all headings that have been rendered so far, in the order of their appearance.
+    ///
+    /// See [`Heading`][] for more information.
+    ///
+    /// [`Heading`]: elements/struct.Heading.html
+    pub fn headings(&self) -> Vec<elements::HeadingEntry> {
+        self.headings.borrow().clone()
+    }
+
+    /// Returns all headings of the document from the previous layout pass, or `None` if this is the
+    /// first pass or if the document is not rendered with [`Document::render_in_passes`][].
+    ///
+    /// [`Document::render_in_passes`]: struct.Document.html#method.render_in_passes
+    pub fn previous_headings(&self) -> Option<&[elements::HeadingEntry]> {
+        self.previous_headings.as_deref()
+    }
+
+    /// Returns the total number of pages of the document from the previous layout pass, or `None`
+    /// if this is the first pass or if the document is not rendered with
+    /// [`Document::render_in_passes`][].
+    ///
+    /// [`Document::render_in_passes`]: struct.Document.html#method.render_in_passes
+    pub fn page_count(&self) -> Option<usize> {
+        self.page_count
+    }
+
+    /// Increments the section counter for the given heading level and returns the section number,
+    /// or `None` if heading numbering is disabled.
+    fn next_heading_number(&self, level: usize) -> Option<String> {
+        if !self.is_heading_numbering {
+            return None;
         }
+        let mut counters = self.heading_counters.borrow_mut();
+        counters[level - 1] += 1;
+        for counter in &mut counters[level..] {
+            *counter = 0;
+        }
+        let numbers: Vec<_> = counters[..level].iter().map(ToString::to_string).collect();
+        Some(numbers.join("."))
+    }
+
+    fn add_heading(&self, heading: elements::HeadingEntry) {
+        self.headings.borrow_mut().push(heading);
     }
 }
 
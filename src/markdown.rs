@@ -0,0 +1,600 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Conversion of Markdown documents into elements.
+//!
+//! *Only available if the `markdown` feature is enabled.*
+//!
+//! This module converts a Markdown string into a tree of elements that can be added to a
+//! [`Document`][].  Use [`to_elements`][] for the default settings or a [`Converter`][] to
+//! customize the conversion.
+//!
+//! # Supported Syntax
+//!
+//! The converter supports a commonly used subset of Markdown:
+//! - ATX headings (`# Heading` to `###### Heading`), converted to [`Heading`][] elements
+//! - paragraphs, where a line ending with two spaces or a backslash forces a line break
+//! - unordered lists (`-`, `*` or `+`) and ordered lists (`1.` or `1)`), converted to
+//!   [`UnorderedList`][] and [`OrderedList`][] elements and nested by indentation
+//! - fenced code blocks (```` ``` ```` or `~~~`)
+//! - block quotes (`>`)
+//! - pipe tables with an optional alignment in the delimiter row, converted to
+//!   [`TableLayout`][] elements
+//! - inline emphasis (`*italic*`, `**bold**`, `~~strikethrough~~`), inline code (`` `code` ``),
+//!   links (`[text](uri)`) and backslash escapes
+//!
+//! Images are replaced by their alternative text.  All other syntax is rendered as plain text.
+//!
+//! # Example
+//!
+//! ```
+//! use genpdf::markdown;
+//! let layout = markdown::to_elements("# Report\n\nThe results are **good**.\n\n- one\n- two");
+//! ```
+//!
+//! [`Document`]: ../struct.Document.html
+//! [`to_elements`]: fn.to_elements.html
+//! [`Converter`]: struct.Converter.html
+//! [`Heading`]: ../elements/struct.Heading.html
+//! [`UnorderedList`]: ../elements/struct.UnorderedList.html
+//! [`OrderedList`]: ../elements/struct.OrderedList.html
+//! [`TableLayout`]: ../elements/struct.TableLayout.html
+
+use crate::elements;
+use crate::style::{Color, Effect, Style, StyledString};
+use crate::{Alignment, Element as _, Margins, Whitespace};
+
+/// Converts the given Markdown string into elements using the default settings.
+///
+/// See the [module documentation](index.html) for the supported syntax.
+pub fn to_elements(markdown: &str) -> elements::LinearLayout {
+    Converter::new().convert(markdown)
+}
+
+/// A converter from Markdown to elements.
+///
+/// Per default, inline code and code blocks are rendered with a light gray background.  Use
+/// [`set_code_style`][] to change this style, for example to select a monospace font family.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{markdown, style};
+/// let converter = markdown::Converter::new()
+///     .with_code_style(style::Style::new().with_color(style::Color::Rgb(0, 0, 128)));
+/// let layout = converter.convert("Call `render` to render the document.");
+/// ```
+///
+/// [`set_code_style`]: #method.set_code_style
+#[derive(Clone, Debug)]
+pub struct Converter {
+    code_style: Style,
+}
+
+impl Converter {
+    /// Creates a new converter with the default settings.
+    pub fn new() -> Converter {
+        Converter {
+            code_style: Style::new().with_background(Color::Rgb(0xee, 0xee, 0xee)),
+        }
+    }
+
+    /// Sets the style for inline code and code blocks.
+    pub fn set_code_style(&mut self, style: impl Into<Style>) {
+        self.code_style = style.into();
+    }
+
+    /// Sets the style for inline code and code blocks and returns the converter.
+    pub fn with_code_style(mut self, style: impl Into<Style>) -> Self {
+        self.set_code_style(style);
+        self
+    }
+
+    /// Converts the given Markdown string into a vertical layout of elements.
+    ///
+    /// Consecutive blocks are separated by an empty line.
+    pub fn convert(&self, markdown: &str) -> elements::LinearLayout {
+        let lines: Vec<&str> = markdown.lines().collect();
+        self.convert_blocks(&lines, 1.0)
+    }
+
+    fn convert_blocks(&self, lines: &[&str], spacing: f64) -> elements::LinearLayout {
+        let mut layout = elements::LinearLayout::vertical();
+        let mut idx = 0;
+        let mut is_first = true;
+        while idx < lines.len() {
+            if is_blank(lines[idx]) {
+                idx += 1;
+                continue;
+            }
+            if !is_first && spacing > 0.0 {
+                layout.push(elements::Break::new(spacing));
+            }
+            is_first = false;
+
+            let line = lines[idx];
+            let trimmed = line.trim();
+            if let Some((level, text)) = parse_heading(trimmed) {
+                let text: String = self.parse_inline(text).into_iter().map(|s| s.s).collect();
+                layout.push(elements::Heading::new(level, text));
+                idx += 1;
+            } else if let Some(fence) = parse_fence(trimmed) {
+                idx = self.convert_code_block(&mut layout, lines, idx, fence);
+            } else if trimmed.starts_with('>') {
+                let mut quote = Vec::new();
+                while idx < lines.len() && lines[idx].trim_start().starts_with('>') {
+                    let line = &lines[idx].trim_start()[1..];
+                    quote.push(line.strip_prefix(' ').unwrap_or(line));
+                    idx += 1;
+                }
+                let quote = self.convert_blocks(&quote, spacing);
+                layout.push(quote.padded(Margins::trbl(0, 0, 0, 5)));
+            } else if is_table_start(lines, idx) {
+                idx = self.convert_table(&mut layout, lines, idx);
+            } else if let Some(marker) = parse_list_marker(line) {
+                idx = self.convert_list(&mut layout, lines, idx, marker);
+            } else {
+                let start = idx;
+                idx += 1;
+                while idx < lines.len() && !is_blank(lines[idx]) && !starts_block(lines, idx) {
+                    idx += 1;
+                }
+                layout.push(self.convert_paragraph(&lines[start..idx]));
+            }
+        }
+        layout
+    }
+
+    fn convert_paragraph(&self, lines: &[&str]) -> elements::Paragraph {
+        let mut text = String::new();
+        for (idx, line) in lines.iter().enumerate() {
+            let line = line.trim_start();
+            if idx + 1 == lines.len() {
+                text.push_str(line.trim_end());
+            } else if line.ends_with("  ") {
+                text.push_str(line.trim_end());
+                text.push('\n');
+            } else if let Some(line) = line.trim_end().strip_suffix('\\') {
+                text.push_str(line);
+                text.push('\n');
+            } else {
+                text.push_str(line.trim_end());
+                text.push(' ');
+            }
+        }
+        elements::Paragraph::from(self.parse_inline(&text))
+    }
+
+    fn convert_code_block(
+        &self,
+        layout: &mut elements::LinearLayout,
+        lines: &[&str],
+        start: usize,
+        fence: &str,
+    ) -> usize {
+        let indent = leading_spaces(lines[start]);
+        let mut code = Vec::new();
+        let mut idx = start + 1;
+        while idx < lines.len() {
+            let trimmed = lines[idx].trim();
+            idx += 1;
+            if trimmed.starts_with(fence) && trimmed.trim_start_matches(&fence[..1]).is_empty() {
+                break;
+            }
+            let line = lines[idx - 1];
+            let line = &line[leading_spaces(line).min(indent)..];
+            code.push(line.replace('\t', "    "));
+        }
+        let code = StyledString::new(code.join("\n"), self.code_style);
+        layout.push(elements::Paragraph::new(code).with_whitespace(Whitespace::Preserve));
+        idx
+    }
+
+    fn convert_list(
+        &self,
+        layout: &mut elements::LinearLayout,
+        lines: &[&str],
+        start: usize,
+        marker: ListMarker,
+    ) -> usize {
+        let mut items = Vec::new();
+        let mut idx = start;
+        while let Some(item) = parse_list_marker(lines[idx]).filter(|m| m.is_sibling_of(&marker)) {
+            let mut content = vec![&lines[idx][item.content..]];
+            idx += 1;
+            while idx < lines.len() {
+                let line = lines[idx];
+                if is_blank(line) {
+                    // A blank line only continues the item if it is followed by indented content.
+                    let next = lines[idx..].iter().position(|line| !is_blank(line));
+                    match next.map(|n| idx + n) {
+                        Some(next) if leading_spaces(lines[next]) >= item.content => {
+                            content.extend(&lines[idx..next]);
+                            idx = next;
+                        }
+                        _ => break,
+                    }
+                } else if leading_spaces(line) >= item.content {
+                    content.push(&line[item.content..]);
+                    idx += 1;
+                } else if parse_list_marker(line).is_none() && !starts_block(lines, idx) {
+                    content.push(line.trim_start());
+                    idx += 1;
+                } else {
+                    break;
+                }
+            }
+            items.push(self.convert_blocks(&content, 0.0));
+
+            let next = lines[idx..].iter().position(|line| !is_blank(line));
+            match next.map(|n| idx + n) {
+                Some(next) if parse_list_marker(lines[next]).is_some() => idx = next,
+                _ => break,
+            }
+        }
+
+        if let Some(number) = marker.number {
+            let mut list = elements::OrderedList::with_start(number);
+            items.into_iter().for_each(|item| list.push(item));
+            layout.push(list);
+        } else {
+            let mut list = elements::UnorderedList::new();
+            items.into_iter().for_each(|item| list.push(item));
+            layout.push(list);
+        }
+        idx
+    }
+
+    fn convert_table(
+        &self,
+        layout: &mut elements::LinearLayout,
+        lines: &[&str],
+        start: usize,
+    ) -> usize {
+        let header = split_table_row(lines[start]);
+        let alignments: Vec<_> = split_table_row(lines[start + 1])
+            .into_iter()
+            .map(|cell| match (cell.starts_with(':'), cell.ends_with(':')) {
+                (true, true) => Alignment::Center,
+                (false, true) => Alignment::Right,
+                _ => Alignment::Left,
+            })
+            .collect();
+        let columns = header.len();
+
+        let mut table = elements::TableLayout::new(vec![1; columns]);
+        table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+        table.set_header_rows(1);
+        let mut push_row = |cells: Vec<&str>, style: Style| {
+            let mut row = table.row();
+            for column in 0..columns {
+                let text = cells.get(column).copied().unwrap_or_default();
+                let alignment = alignments.get(column).copied().unwrap_or_default();
+                let cell = elements::Paragraph::from(self.parse_inline(text))
+                    .aligned(alignment)
+                    .styled(style)
+                    .padded(1);
+                row.push_cloned_element(cell);
+            }
+            row.push().expect("Table row has the wrong number of cells");
+        };
+
+        push_row(header, Style::from(Effect::Bold));
+        let mut idx = start + 2;
+        while idx < lines.len() && !is_blank(lines[idx]) && lines[idx].contains('|') {
+            push_row(split_table_row(lines[idx]), Style::new());
+            idx += 1;
+        }
+        layout.push(table);
+        idx
+    }
+
+    fn parse_inline(&self, text: &str) -> Vec<StyledString> {
+        let mut strings = Vec::new();
+        self.parse_inline_styled(text, Style::new(), &mut strings);
+        strings
+    }
+
+    fn parse_inline_styled(&self, text: &str, style: Style, strings: &mut Vec<StyledString>) {
+        let mut buffer = String::new();
+        let mut idx = 0;
+        while let Some(c) = text[idx..].chars().next() {
+            let rest = &text[idx..];
+            let mut styled = None;
+            let mut len = c.len_utf8();
+            match c {
+                '\\' => {
+                    if let Some(next) = rest[1..].chars().next().filter(char::is_ascii_punctuation)
+                    {
+                        buffer.push(next);
+                        idx += 2;
+                        continue;
+                    }
+                }
+                '`' => {
+                    let ticks = rest.len() - rest.trim_start_matches('`').len();
+                    let delimiter = &rest[..ticks];
+                    if let Some(end) = find_code_end(&rest[ticks..], delimiter) {
+                        let code = &rest[ticks..ticks + end];
+                        let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                            Some(inner) if !inner.trim().is_empty() => inner,
+                            _ => code,
+                        };
+                        let mut code_style = style;
+                        code_style.merge(self.code_style);
+                        styled = Some((vec![StyledString::new(code, code_style)], None));
+                        len = 2 * ticks + end;
+                    } else {
+                        len = ticks;
+                    }
+                }
+                '!' | '[' => {
+                    let is_image = c == '!';
+                    let label = if is_image { &rest[1..] } else { rest };
+                    if let Some((text, uri, end)) = parse_link(label) {
+                        let mut texts = Vec::new();
+                        self.parse_inline_styled(text, style, &mut texts);
+                        let uri = if is_image { None } else { Some(uri.to_owned()) };
+                        styled = Some((texts, uri));
+                        len = end + usize::from(is_image);
+                    }
+                }
+                '*' | '_' | '~' => {
+                    let double = rest[1..].starts_with(c);
+                    let delimiter = &rest[..if double { 2 } else { 1 }];
+                    let effect = match (c, double) {
+                        ('~', true) => Some(Effect::Strikethrough),
+                        ('~', false) => None,
+                        (_, true) => Some(Effect::Bold),
+                        (_, false) => Some(Effect::Italic),
+                    };
+                    let is_word_start = c != '_'
+                        || !text[..idx]
+                            .chars()
+                            .next_back()
+                            .map_or(false, char::is_alphanumeric);
+                    let inner = &rest[delimiter.len()..];
+                    let end = effect
+                        .filter(|_| is_word_start)
+                        .and_then(|effect| Some((effect, find_emphasis_end(inner, delimiter)?)));
+                    if let Some((effect, end)) = end {
+                        let mut texts = Vec::new();
+                        self.parse_inline_styled(&inner[..end], style.and(effect), &mut texts);
+                        styled = Some((texts, None));
+                        len = 2 * delimiter.len() + end;
+                    } else {
+                        len = delimiter.len();
+                    }
+                }
+                _ => {}
+            }
+
+            if let Some((texts, uri)) = styled {
+                if !buffer.is_empty() {
+                    strings.push(StyledString::new(std::mem::take(&mut buffer), style));
+                }
+                for mut s in texts {
+                    if let Some(uri) = &uri {
+                        s.set_link(uri.clone());
+                    }
+                    strings.push(s);
+                }
+            } else {
+                buffer.push_str(&rest[..len]);
+            }
+            idx += len;
+        }
+        if !buffer.is_empty() {
+            strings.push(StyledString::new(buffer, style));
+        }
+    }
+}
+
+impl Default for Converter {
+    fn default() -> Converter {
+        Converter::new()
+    }
+}
+
+/// A list item marker, for example `-` or `1.`.
+#[derive(Clone, Copy, Debug)]
+struct ListMarker {
+    indent: usize,
+    symbol: char,
+    number: Option<usize>,
+    content: usize,
+}
+
+impl ListMarker {
+    fn is_sibling_of(&self, other: &ListMarker) -> bool {
+        self.indent == other.indent
+            && self.symbol == other.symbol
+            && self.number.is_some() == other.number.is_some()
+    }
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn leading_spaces(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn starts_block(lines: &[&str], idx: usize) -> bool {
+    let trimmed = lines[idx].trim();
+    parse_heading(trimmed).is_some()
+        || parse_fence(trimmed).is_some()
+        || trimmed.starts_with('>')
+        || parse_list_marker(lines[idx]).is_some()
+        || is_table_start(lines, idx)
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.len() - line.trim_start_matches('#').len();
+    let text = &line[level..];
+    if (1..=6).contains(&level) && (text.is_empty() || text.starts_with(' ')) {
+        let text = text.trim();
+        let closed = text.trim_end_matches('#');
+        if closed.is_empty() || closed.ends_with(' ') {
+            Some((level, closed.trim_end()))
+        } else {
+            Some((level, text))
+        }
+    } else {
+        None
+    }
+}
+
+fn parse_fence(line: &str) -> Option<&str> {
+    ["```", "~~~"].iter().find_map(|fence| {
+        if line.starts_with(fence) {
+            let c = &fence[..1];
+            Some(&line[..line.len() - line.trim_start_matches(c).len()])
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_list_marker(line: &str) -> Option<ListMarker> {
+    let indent = leading_spaces(line);
+    let rest = &line[indent..];
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (symbol, number, len) = match rest.chars().nth(digits)? {
+        c @ '-' | c @ '*' | c @ '+' if digits == 0 => (c, None, 1),
+        c @ '.' | c @ ')' if (1..=9).contains(&digits) => {
+            (c, rest[..digits].parse().ok(), digits + 1)
+        }
+        _ => return None,
+    };
+    let after = &rest[len..];
+    if after.is_empty() || after.starts_with(' ') {
+        let spaces = leading_spaces(after).max(1).min(4);
+        let content = (indent + len + spaces).min(line.len());
+        Some(ListMarker {
+            indent,
+            symbol,
+            number,
+            content,
+        })
+    } else {
+        None
+    }
+}
+
+fn is_table_start(lines: &[&str], idx: usize) -> bool {
+    let is_delimiter_row = |line: &str| {
+        let cells = split_table_row(line);
+        line.contains('|')
+            && cells.iter().all(|cell| {
+                let cell = cell.strip_prefix(':').unwrap_or(cell);
+                let cell = cell.strip_suffix(':').unwrap_or(cell);
+                !cell.is_empty() && cell.chars().all(|c| c == '-')
+            })
+    };
+    lines[idx].contains('|') && lines.get(idx + 1).copied().map_or(false, is_delimiter_row)
+}
+
+fn split_table_row(line: &str) -> Vec<&str> {
+    let line = line.trim();
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = match line.strip_suffix('|') {
+        Some(stripped) if !stripped.ends_with('\\') => stripped,
+        _ => line,
+    };
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '|' if !escaped => {
+                cells.push(line[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    cells.push(line[start..].trim());
+    cells
+}
+
+fn find_code_end(text: &str, delimiter: &str) -> Option<usize> {
+    let mut idx = 0;
+    while let Some(pos) = text[idx..].find(delimiter) {
+        let pos = idx + pos;
+        let run = text[pos..].len() - text[pos..].trim_start_matches('`').len();
+        if run == delimiter.len() {
+            return Some(pos);
+        }
+        idx = pos + run;
+    }
+    None
+}
+
+fn find_emphasis_end(text: &str, delimiter: &str) -> Option<usize> {
+    if text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let c = delimiter.chars().next()?;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, next)) = chars.next() {
+        if next == '\\' {
+            chars.next();
+        } else if next == '`' {
+            let ticks = text[idx..].len() - text[idx..].trim_start_matches('`').len();
+            let end = find_code_end(&text[idx + ticks..], &text[idx..idx + ticks]);
+            let skip = end.map(|end| end + 2 * ticks).unwrap_or(ticks);
+            while chars.peek().map_or(false, |(i, _)| *i < idx + skip) {
+                chars.next();
+            }
+        } else if idx > 0 && text[idx..].starts_with(delimiter) {
+            let run = text[idx..].len() - text[idx..].trim_start_matches(c).len();
+            let is_word_end = c != '_'
+                || !text[idx + run..]
+                    .chars()
+                    .next()
+                    .map_or(false, char::is_alphanumeric);
+            let after_space = text[..idx].ends_with(char::is_whitespace);
+            if run == delimiter.len() && is_word_end && !after_space {
+                return Some(idx);
+            }
+            // Skip the whole delimiter run so that `**` does not close `*`.
+            while chars.peek().map_or(false, |(i, _)| *i < idx + run) {
+                chars.next();
+            }
+        }
+    }
+    None
+}
+
+fn parse_link(text: &str) -> Option<(&str, &str, usize)> {
+    let mut depth = 0;
+    let mut label_end = None;
+    let mut escaped = false;
+    for (idx, c) in text.char_indices() {
+        match c {
+            '[' if !escaped => depth += 1,
+            ']' if !escaped => {
+                depth -= 1;
+                if depth == 0 {
+                    label_end = Some(idx);
+                    break;
+                }
+            }
+            _ => {}
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    let label_end = label_end?;
+    let rest = text[label_end + 1..].strip_prefix('(')?;
+    let uri_end = rest.find(')')?;
+    let uri = rest[..uri_end].trim();
+    let uri = uri.split_whitespace().next().unwrap_or(uri);
+    let uri = uri
+        .strip_prefix('<')
+        .and_then(|uri| uri.strip_suffix('>'))
+        .unwrap_or(uri);
+    Some((&text[1..label_end], uri, label_end + 2 + uri_end + 1))
+}
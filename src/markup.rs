@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Utilities for the HTML and SVG parsers.
+
+use crate::error::{Error, ErrorKind};
+use crate::style::{self, Color};
+
+/// Decodes the character references in the given text.
+///
+/// The named references `&lt;`, `&gt;`, `&amp;`, `&quot;`, `&apos;` and `&nbsp;` as well as
+/// decimal and hexadecimal numeric references are supported.  An ampersand that does not start a
+/// character reference is kept as it is.  Unknown or invalid references cause an error of the
+/// kind [`ErrorKind::InvalidData`][].
+///
+/// [`ErrorKind::InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+pub fn decode_entities(s: &str) -> Result<String, Error> {
+    let mut decoded = String::new();
+    let mut rest = s;
+    while let Some(idx) = rest.find('&') {
+        decoded.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        let end = rest[1..]
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+            .map(|end| end + 1)
+            .filter(|end| *end > 1 && rest[*end..].starts_with(';'));
+        let end = match end {
+            Some(end) => end,
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => match entity.strip_prefix('#') {
+                Some(hex) if hex.starts_with(&['x', 'X'][..]) => u32::from_str_radix(&hex[1..], 16)
+                    .ok()
+                    .and_then(std::char::from_u32),
+                Some(dec) => dec.parse().ok().and_then(std::char::from_u32),
+                None => None,
+            },
+        };
+        let c = c.ok_or_else(|| {
+            Error::new(
+                format!("Unknown character reference: &{};", entity),
+                ErrorKind::InvalidData,
+            )
+        })?;
+        decoded.push(c);
+        rest = &rest[end + 1..];
+    }
+    decoded.push_str(rest);
+    Ok(decoded)
+}
+
+/// Parses a CSS color.
+///
+/// Supported are the notations `#rgb`, `#rrggbb` and `rgb(r, g, b)` with numbers or percentages
+/// and the basic color keywords of CSS, as well as `orange`.
+pub fn parse_color(s: &str) -> Option<Color> {
+    if let Some(args) = s.strip_prefix("rgb(").and_then(|s| s.strip_suffix(')')) {
+        let components = args
+            .split(',')
+            .map(|arg| {
+                let arg = arg.trim();
+                match arg.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f64>().ok().map(|p| p * 2.55),
+                    None => arg.parse::<f64>().ok(),
+                }
+                .map(|c| c.round().max(0.0).min(255.0) as u8)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        return match components.as_slice() {
+            [r, g, b] => Some(Color::Rgb(*r, *g, *b)),
+            _ => None,
+        };
+    }
+    if s.starts_with('#') && s.len() == 4 {
+        let long: String = s
+            .chars()
+            .flat_map(|c| std::iter::repeat(c).take(2))
+            .skip(1)
+            .collect();
+        return style::parse_hex_color(&long);
+    }
+    if s.starts_with('#') {
+        return style::parse_hex_color(s);
+    }
+    let rgb = match s.to_ascii_lowercase().as_str() {
+        "black" => (0, 0, 0),
+        "silver" => (192, 192, 192),
+        "gray" | "grey" => (128, 128, 128),
+        "white" => (255, 255, 255),
+        "maroon" => (128, 0, 0),
+        "red" => (255, 0, 0),
+        "purple" => (128, 0, 128),
+        "fuchsia" | "magenta" => (255, 0, 255),
+        "green" => (0, 128, 0),
+        "lime" => (0, 255, 0),
+        "olive" => (128, 128, 0),
+        "yellow" => (255, 255, 0),
+        "navy" => (0, 0, 128),
+        "blue" => (0, 0, 255),
+        "teal" => (0, 128, 128),
+        "aqua" | "cyan" => (0, 255, 255),
+        "orange" => (255, 165, 0),
+        _ => return None,
+    };
+    Some(Color::Rgb(rgb.0, rgb.1, rgb.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::Color;
+
+    #[test]
+    fn decode_entities() {
+        let decode = super::decode_entities;
+        assert_eq!("a < b & c", decode("a &lt; b &amp; c").unwrap());
+        assert_eq!("\u{a0}AA", decode("&nbsp;&#65;&#x41;").unwrap());
+        assert_eq!("a & b &; &#", decode("a & b &; &#").unwrap());
+        assert!(decode("&foo;").is_err());
+        assert!(decode("&#xd800;").is_err());
+    }
+
+    #[test]
+    fn parse_color() {
+        let rgb = |r, g, b| Some(Color::Rgb(r, g, b));
+        assert_eq!(rgb(255, 0, 0), super::parse_color("#f00"));
+        assert_eq!(rgb(0, 128, 0), super::parse_color("#008000"));
+        assert_eq!(rgb(1, 2, 3), super::parse_color("rgb(1, 2, 3)"));
+        assert_eq!(rgb(255, 51, 0), super::parse_color("rgb(100%, 20%, 0%)"));
+        assert_eq!(rgb(0, 0, 255), super::parse_color("Blue"));
+        assert_eq!(None, super::parse_color("#ff"));
+        assert_eq!(None, super::parse_color("rgb(1, 2)"));
+        assert_eq!(None, super::parse_color("reddish"));
+    }
+}
@@ -0,0 +1,414 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! PDF/A conformance and XMP metadata.
+//!
+//! This module checks whether a document can be written as a PDF/A-2b document and adds the
+//! entries that are required by the standard: the XMP metadata stream with the PDF/A
+//! identification and the output intent with an embedded sRGB ICC profile.  The XMP metadata is
+//! also written for documents that are not PDF/A documents but have custom metadata.
+
+use crate::error::{Context as _, Error, ErrorKind};
+use crate::render::{decode_text_string, is_name, resolve, FormField};
+
+/// Returns an error if a PDF/A-2b document should be encrypted or have attachments.
+pub fn check_options(is_encrypted: bool, has_attachments: bool) -> Result<(), Error> {
+    if is_encrypted {
+        return Err(Error::new(
+            "Encryption is not allowed in PDF/A documents",
+            ErrorKind::UnsupportedFeature,
+        ));
+    }
+    if has_attachments {
+        return Err(Error::new(
+            "Attachments are not supported in PDF/A-2b documents",
+            ErrorKind::UnsupportedFeature,
+        ));
+    }
+    Ok(())
+}
+
+/// Returns an error if the given form fields contain interactive form fields, i. e. form fields
+/// that are not signature fields.
+pub fn check_form_fields<'a>(mut fields: impl Iterator<Item = &'a FormField>) -> Result<(), Error> {
+    if fields.any(|field| *field != FormField::Signature) {
+        Err(Error::new(
+            "Interactive form fields are not allowed in PDF/A documents",
+            ErrorKind::UnsupportedFeature,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns an error if the given document uses features that are not allowed in PDF/A-2b
+/// documents with an sRGB output intent.
+pub fn check_document(doc: &lopdf::Document) -> Result<(), Error> {
+    let unsupported = |msg: String| Err(Error::new(msg, ErrorKind::UnsupportedFeature));
+    for object in doc.objects.values() {
+        let dict = match object {
+            lopdf::Object::Dictionary(dict) => dict,
+            lopdf::Object::Stream(stream) => &stream.dict,
+            _ => continue,
+        };
+        if is_name(dict, b"Type", b"Font") && !is_name(dict, b"Subtype", b"Type0") {
+            let is_embedded = dict
+                .get(b"FontDescriptor")
+                .and_then(|descriptor| resolve(doc, descriptor).as_dict())
+                .map(|descriptor| {
+                    [&b"FontFile"[..], b"FontFile2", b"FontFile3"]
+                        .iter()
+                        .any(|key| descriptor.has(key))
+                })
+                .unwrap_or(false);
+            if !is_embedded {
+                let name = dict
+                    .get(b"BaseFont")
+                    .and_then(lopdf::Object::as_name_str)
+                    .unwrap_or("unknown");
+                return unsupported(format!(
+                    "Fonts that are not embedded are not allowed in PDF/A documents: {}",
+                    name
+                ));
+            }
+        }
+        if is_name(dict, b"ColorSpace", b"DeviceCMYK") {
+            return unsupported("CMYK images are not allowed in PDF/A documents".to_owned());
+        }
+    }
+    for page_id in doc.get_pages().values() {
+        let content = doc
+            .get_page_content(*page_id)
+            .context("Failed to read page content")?;
+        let content =
+            lopdf::content::Content::decode(&content).context("Failed to decode page content")?;
+        if content
+            .operations
+            .iter()
+            .any(|operation| operation.operator == "k" || operation.operator == "K")
+        {
+            return unsupported("CMYK colors are not allowed in PDF/A documents".to_owned());
+        }
+    }
+    Ok(())
+}
+
+/// Adds an XMP metadata stream with the entries of the document information dictionary to the
+/// document, replacing the metadata stream written by printpdf.
+///
+/// For PDF/A documents, the PDF/A identification is added to the metadata.
+pub fn add_xmp_metadata(doc: &mut lopdf::Document, is_pdf_a: bool) -> Result<(), lopdf::Error> {
+    let info_id = doc.trailer.get(b"Info")?.as_reference()?;
+    let info = doc.get_object(info_id)?.as_dict()?;
+    let text = |key: &[u8]| match info.get(key) {
+        Ok(lopdf::Object::String(value, _)) => Some(escape_xml(&decode_text_string(value))),
+        _ => None,
+    };
+    let date = |key: &[u8]| match info.get(key) {
+        Ok(lopdf::Object::String(date, _)) => to_xmp_date(date),
+        _ => None,
+    };
+
+    let mut properties = vec!["<dc:format>application/pdf</dc:format>".to_owned()];
+    let title = text(b"Title").unwrap_or_default();
+    properties.push(format!(
+        "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>",
+        title
+    ));
+    if let Some(author) = text(b"Author") {
+        properties.push(format!(
+            "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>",
+            author
+        ));
+    }
+    if let Some(subject) = text(b"Subject") {
+        properties.push(format!(
+            "<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt>\
+             </dc:description>",
+            subject
+        ));
+    }
+    let mut simple_property = |name: &str, value: Option<String>| {
+        if let Some(value) = value {
+            properties.push(format!("<{0}>{1}</{0}>", name, value));
+        }
+    };
+    simple_property("pdf:Keywords", text(b"Keywords"));
+    simple_property("pdf:Producer", text(b"Producer"));
+    simple_property("xmp:CreatorTool", text(b"Creator"));
+    simple_property("xmp:CreateDate", date(b"CreationDate"));
+    simple_property("xmp:ModifyDate", date(b"ModDate"));
+    if let Ok(trapped) = info.get(b"Trapped").and_then(lopdf::Object::as_name_str) {
+        simple_property("pdf:Trapped", Some(trapped.to_owned()));
+    }
+    simple_property("pdfx:GTS_PDFXVersion", text(b"GTS_PDFXVersion"));
+    if is_pdf_a {
+        simple_property("pdfaid:part", Some("2".to_owned()));
+        simple_property("pdfaid:conformance", Some("B".to_owned()));
+    }
+
+    let mut metadata = String::new();
+    metadata.push_str("<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n");
+    metadata.push_str("<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n");
+    metadata.push_str("<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n");
+    metadata.push_str("<rdf:Description rdf:about=\"\"");
+    metadata.push_str(" xmlns:dc=\"http://purl.org/dc/elements/1.1/\"");
+    metadata.push_str(" xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"");
+    metadata.push_str(" xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\"");
+    metadata.push_str(" xmlns:pdfx=\"http://ns.adobe.com/pdfx/1.3/\"");
+    if is_pdf_a {
+        metadata.push_str(" xmlns:pdfaid=\"http://www.aiim.org/pdfa/ns/id/\"");
+    }
+    metadata.push_str(">\n");
+    for property in properties {
+        metadata.push_str(&property);
+        metadata.push('\n');
+    }
+    metadata.push_str("</rdf:Description>\n</rdf:RDF>\n</x:xmpmeta>\n");
+    metadata.push_str("<?xpacket end=\"w\"?>");
+    let mut dict = lopdf::Dictionary::new();
+    dict.set("Type", lopdf::Object::Name(b"Metadata".to_vec()));
+    dict.set("Subtype", lopdf::Object::Name(b"XML".to_vec()));
+    // The metadata stream must not be compressed so that it can be read without parsing the PDF.
+    let metadata = lopdf::Stream::new(dict, metadata.into_bytes()).with_compression(false);
+    let metadata_id = doc.add_object(metadata);
+
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    let catalog = doc.get_object_mut(catalog_id)?.as_dict_mut()?;
+    let old_metadata = catalog
+        .get(b"Metadata")
+        .and_then(lopdf::Object::as_reference)
+        .ok();
+    catalog.set("Metadata", lopdf::Object::Reference(metadata_id));
+    if let Some(old_metadata) = old_metadata {
+        doc.objects.remove(&old_metadata);
+    }
+    Ok(())
+}
+
+/// Adds the sRGB output intent required for PDF/A-2b documents and adds the entries that are
+/// required for optional content and annotations in PDF/A documents.
+pub fn prepare_document(doc: &mut lopdf::Document) -> Result<(), lopdf::Error> {
+    doc.version = "1.7".to_owned();
+
+    let mut dict = lopdf::Dictionary::new();
+    dict.set("N", 3);
+    let mut profile = lopdf::Stream::new(dict, srgb_icc_profile());
+    profile.compress()?;
+    let profile_id = doc.add_object(profile);
+    let mut output_intent = lopdf::Dictionary::new();
+    output_intent.set("Type", lopdf::Object::Name(b"OutputIntent".to_vec()));
+    output_intent.set("S", lopdf::Object::Name(b"GTS_PDFA1".to_vec()));
+    output_intent.set(
+        "OutputConditionIdentifier",
+        lopdf::Object::string_literal("sRGB IEC61966-2.1"),
+    );
+    output_intent.set("Info", lopdf::Object::string_literal("sRGB IEC61966-2.1"));
+    output_intent.set(
+        "RegistryName",
+        lopdf::Object::string_literal("http://www.color.org"),
+    );
+    output_intent.set("DestOutputProfile", lopdf::Object::Reference(profile_id));
+
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    let catalog = doc.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("OutputIntents", vec![output_intent.into()]);
+    // Optional content configurations must have a name.
+    if let Ok(properties) = catalog
+        .get_mut(b"OCProperties")
+        .and_then(lopdf::Object::as_dict_mut)
+    {
+        if let Ok(config) = properties
+            .get_mut(b"D")
+            .and_then(lopdf::Object::as_dict_mut)
+        {
+            if !config.has(b"Name") {
+                config.set("Name", lopdf::Object::string_literal("Layers"));
+            }
+        }
+    }
+
+    // Annotations must be printable.
+    for object in doc.objects.values_mut() {
+        if let lopdf::Object::Dictionary(dict) = object {
+            if is_name(dict, b"Type", b"Annot") && !dict.has(b"F") {
+                dict.set("F", 4);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts a PDF date string (`D:YYYYMMDDHHmmSS+HH'mm'`) into an XMP date string
+/// (`YYYY-MM-DDTHH:mm:SS+HH:mm`).
+fn to_xmp_date(date: &[u8]) -> Option<String> {
+    let date = std::str::from_utf8(date).ok()?;
+    let date = date.strip_prefix("D:").unwrap_or(date);
+    if date.len() < 14 || !date[..14].bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let offset = match date[14..].replace('\'', "").as_str() {
+        "" | "Z" => "Z".to_owned(),
+        offset if offset.len() == 5 => format!("{}:{}", &offset[..3], &offset[3..]),
+        _ => return None,
+    };
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}{}",
+        &date[0..4],
+        &date[4..6],
+        &date[6..8],
+        &date[8..10],
+        &date[10..12],
+        &date[12..14],
+        offset
+    ))
+}
+
+/// Escapes the special XML characters in the given string.
+fn escape_xml(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Generates an ICC profile (version 2.1) for the sRGB color space.
+///
+/// The profile uses the sRGB primaries adapted to the D50 illuminant and a sampled sRGB transfer
+/// curve.
+fn srgb_icc_profile() -> Vec<u8> {
+    fn s15_fixed16(value: f64) -> [u8; 4] {
+        ((value * 65536.0).round() as i32).to_be_bytes()
+    }
+
+    fn xyz(x: f64, y: f64, z: f64) -> Vec<u8> {
+        let mut data = b"XYZ \0\0\0\0".to_vec();
+        data.extend(&s15_fixed16(x));
+        data.extend(&s15_fixed16(y));
+        data.extend(&s15_fixed16(z));
+        data
+    }
+
+    let description = b"sRGB IEC61966-2.1\0";
+    let mut desc = b"desc\0\0\0\0".to_vec();
+    desc.extend(&(description.len() as u32).to_be_bytes());
+    desc.extend(description);
+    // Empty Unicode and ScriptCode descriptions
+    desc.extend(&[0; 8]);
+    desc.extend(&[0; 3]);
+    desc.extend_from_slice(&[0; 67]);
+
+    let mut cprt = b"text\0\0\0\0".to_vec();
+    cprt.extend(b"No copyright, use freely\0");
+
+    const CURVE_POINTS: usize = 1024;
+    let mut trc = b"curv\0\0\0\0".to_vec();
+    trc.extend(&(CURVE_POINTS as u32).to_be_bytes());
+    for i in 0..CURVE_POINTS {
+        let v = i as f64 / (CURVE_POINTS - 1) as f64;
+        let linear = if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        };
+        trc.extend(&((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+
+    let tags: Vec<(&[u8; 4], Vec<u8>)> = vec![
+        (b"desc", desc),
+        (b"cprt", cprt),
+        (b"wtpt", xyz(0.9642, 1.0, 0.8249)),
+        (b"rXYZ", xyz(0.4361, 0.2225, 0.0139)),
+        (b"gXYZ", xyz(0.3851, 0.7169, 0.0971)),
+        (b"bXYZ", xyz(0.1431, 0.0606, 0.7141)),
+        (b"rTRC", trc),
+    ];
+    // The green and blue curves share the data of the red curve.
+    let shared_tags: [&[u8; 4]; 2] = [b"gTRC", b"bTRC"];
+
+    let mut table: Vec<u8> = Vec::new();
+    let mut data: Vec<u8> = Vec::new();
+    let data_offset = 128 + 4 + 12 * (tags.len() + shared_tags.len());
+    let mut trc_entry = (0, 0);
+    for (signature, tag) in &tags {
+        let offset = data_offset + data.len();
+        table.extend(*signature);
+        table.extend(&(offset as u32).to_be_bytes());
+        table.extend(&(tag.len() as u32).to_be_bytes());
+        if *signature == b"rTRC" {
+            trc_entry = (offset, tag.len());
+        }
+        data.extend(tag);
+        // Tag data must be aligned to four bytes.
+        data.resize((data.len() + 3) / 4 * 4, 0);
+    }
+    for signature in &shared_tags {
+        table.extend(*signature);
+        table.extend(&(trc_entry.0 as u32).to_be_bytes());
+        table.extend(&(trc_entry.1 as u32).to_be_bytes());
+    }
+
+    let size = data_offset + data.len();
+    let mut profile = Vec::with_capacity(size);
+    profile.extend(&(size as u32).to_be_bytes());
+    // Preferred CMM type
+    profile.extend(&[0; 4]);
+    // Profile version 2.1
+    profile.extend(&[2, 0x10, 0, 0]);
+    profile.extend(b"mntrRGB XYZ ");
+    // Creation date: 2021-01-01 00:00:00
+    for value in &[2021u16, 1, 1, 0, 0, 0] {
+        profile.extend(&value.to_be_bytes());
+    }
+    profile.extend(b"acsp");
+    // Platform, flags, device manufacturer and model, device attributes and rendering intent
+    profile.extend(&[0; 28]);
+    // PCS illuminant (D50)
+    profile.extend(&s15_fixed16(0.9642));
+    profile.extend(&s15_fixed16(1.0));
+    profile.extend(&s15_fixed16(0.8249));
+    // Profile creator and reserved bytes
+    profile.extend_from_slice(&[0; 48]);
+    debug_assert_eq!(128, profile.len());
+    profile.extend(&((tags.len() + shared_tags.len()) as u32).to_be_bytes());
+    profile.extend(table);
+    profile.extend(data);
+    profile
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xmp_dates() {
+        assert_eq!(
+            Some("2021-03-04T05:06:07+01:00".to_owned()),
+            to_xmp_date(b"D:20210304050607+01'00'")
+        );
+        assert_eq!(
+            Some("2021-03-04T05:06:07Z".to_owned()),
+            to_xmp_date(b"20210304050607Z")
+        );
+        assert_eq!(None, to_xmp_date(b"D:2021"));
+        assert_eq!(None, to_xmp_date(b"D:20210304050607+1"));
+    }
+
+    #[test]
+    fn srgb_profile() {
+        let profile = srgb_icc_profile();
+        assert_eq!(
+            profile.len() as u32,
+            u32::from_be_bytes([profile[0], profile[1], profile[2], profile[3]])
+        );
+        assert_eq!(b"acsp", &profile[36..40]);
+        assert_eq!(b"RGB XYZ ", &profile[16..24]);
+    }
+}
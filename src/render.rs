@@ -11,6 +11,9 @@
 //! lines and text.  For more advanced text formatting, you can create a [`TextSection`][] from an
 //! [`Area`][].
 //!
+//! Some features are not supported by [`printpdf`][].  For these features, the renderer stores raw
+//! PDF operations and inserts them into the generated document when it is written.
+//!
 //! [`printpdf`]: https://docs.rs/printpdf/latest/printpdf
 //! [`Renderer`]: struct.Renderer.html
 //! [`Page`]: struct.Page.html
@@ -19,17 +22,46 @@
 //! [`TextSection`]: struct.TextSection.html
 
 use std::cell;
+use std::collections;
 use std::io;
 use std::ops;
 use std::rc;
 
+#[cfg(feature = "shaping")]
+use crate::bidi;
+#[cfg(feature = "encryption")]
+use crate::encryption::{self, Encryptor};
 use crate::error::{Context as _, Error, ErrorKind};
 use crate::fonts;
-use crate::style::{Color, LineStyle, Style};
-use crate::{Margins, Mm, Position, Size};
+use crate::pdfa;
+use crate::signature;
+use crate::style::{Color, LineCap, LineJoin, LineStyle, Style};
+use crate::wrap;
+use crate::{Margins, Mm, PageBoxes, Position, Rotation, Size, Thumbnail};
 
 #[cfg(feature = "images")]
-use crate::{Rotation, Scale};
+use crate::Scale;
+
+/// The offset of the dash pattern that marks the position of raw operations in a content stream.
+///
+/// `printpdf` does not support adding arbitrary operations to a layer.  Its line dash operation is
+/// the only operation that takes unchecked integer operands, so we add a line dash operation with
+/// this offset and the index of the operations as the dash array, and replace it with the
+/// operations in [`Renderer::write`][].  This is also the reason why the actual dash patterns,
+/// which may use fractional lengths, are added as raw operations.
+///
+/// We only use `printpdf`’s line dash operation for markers, so every line dash operation with
+/// this offset is a marker.  Every marker is a single operation in one of the content streams of
+/// its page, and all markers are replaced before the document is written.
+///
+/// [`Renderer::write`]: struct.Renderer.html#method.write
+const OPERATIONS_MARKER: i64 = -0x6765_6e70;
+
+/// The maximum number of objects that are stored in one object stream, see
+/// [`Renderer::with_object_streams`][].
+///
+/// [`Renderer::with_object_streams`]: struct.Renderer.html#method.with_object_streams
+const MAX_OBJECT_STREAM_LEN: usize = 100;
 
 /// A position relative to the top left corner of a layer.
 struct LayerPosition(Position);
@@ -45,10 +77,11 @@ struct UserSpacePosition(Position);
 
 impl UserSpacePosition {
     pub fn from_layer(layer: &Layer<'_>, position: LayerPosition) -> Self {
-        Self(Position::new(
-            position.0.x,
-            layer.page.size.height - position.0.y,
-        ))
+        Self::from_page(layer.page, position)
+    }
+
+    pub fn from_page(page: &Page, position: LayerPosition) -> Self {
+        Self(Position::new(position.0.x, page.size.height - position.0.y))
     }
 }
 
@@ -75,8 +108,147 @@ pub struct Renderer {
     doc: printpdf::PdfDocumentReference,
     // invariant: pages.len() >= 1
     pages: Vec<Page>,
+    use_object_streams: bool,
+    post_processor: Option<PostProcessor>,
+    snap_grid: Option<Mm>,
+    language: Option<String>,
+    metadata: Metadata,
+    is_pdf_a: bool,
+    #[cfg(feature = "encryption")]
+    encryption: Option<encryption::EncryptionOptions>,
+    attachments: Vec<Attachment>,
+    font_subsets: Vec<FontSubset>,
+    has_cff_fonts: cell::Cell<bool>,
+}
+
+/// The relationship of an [`Attachment`][] to the content of the document.
+///
+/// The relationship is written to the `AFRelationship` entry of the file specification, which is
+/// required for some document formats, for example the ZUGFeRD and Factur-X electronic invoices.
+///
+/// [`Attachment`]: struct.Attachment.html
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttachmentRelationship {
+    /// The original source material of the document.
+    Source,
+    /// The data that is used to derive the document, for example the machine-readable data of an
+    /// invoice.
+    Data,
+    /// An alternative representation of the document.
+    Alternative,
+    /// A supplemental representation of the document.
+    Supplement,
+    /// An unknown relationship or a relationship that does not match the other variants.
+    Unspecified,
+}
+
+impl Default for AttachmentRelationship {
+    fn default() -> AttachmentRelationship {
+        AttachmentRelationship::Unspecified
+    }
+}
+
+impl AttachmentRelationship {
+    fn name(&self) -> &'static str {
+        match self {
+            AttachmentRelationship::Source => "Source",
+            AttachmentRelationship::Data => "Data",
+            AttachmentRelationship::Alternative => "Alternative",
+            AttachmentRelationship::Supplement => "Supplement",
+            AttachmentRelationship::Unspecified => "Unspecified",
+        }
+    }
+}
+
+/// A file that is embedded in a PDF document, see [`Renderer::with_attachment`][].
+///
+/// The file is added to the embedded files of the document and to the associated files of the
+/// document catalog.
+///
+/// [`Renderer::with_attachment`]: struct.Renderer.html#method.with_attachment
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Attachment {
+    name: String,
+    data: Vec<u8>,
+    mime_type: String,
+    description: Option<String>,
+    relationship: AttachmentRelationship,
+}
+
+impl Attachment {
+    /// Creates a new attachment with the given file name, content and MIME type.
+    pub fn new(
+        name: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+        mime_type: impl Into<String>,
+    ) -> Attachment {
+        Attachment {
+            name: name.into(),
+            data: data.into(),
+            mime_type: mime_type.into(),
+            description: None,
+            relationship: AttachmentRelationship::default(),
+        }
+    }
+
+    /// Sets the description of this attachment.
+    pub fn set_description(&mut self, description: impl Into<String>) {
+        self.description = Some(description.into());
+    }
+
+    /// Sets the description of this attachment and returns it.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.set_description(description);
+        self
+    }
+
+    /// Sets the relationship of this attachment to the document.
+    ///
+    /// Per default, the relationship is [`AttachmentRelationship::Unspecified`][].
+    ///
+    /// [`AttachmentRelationship::Unspecified`]: enum.AttachmentRelationship.html#variant.Unspecified
+    pub fn set_relationship(&mut self, relationship: AttachmentRelationship) {
+        self.relationship = relationship;
+    }
+
+    /// Sets the relationship of this attachment to the document and returns it.
+    pub fn with_relationship(mut self, relationship: AttachmentRelationship) -> Self {
+        self.set_relationship(relationship);
+        self
+    }
+}
+
+/// The metadata of a PDF document that is written to the document information dictionary and
+/// the XMP metadata, see [`Renderer::with_author`][] and the other `Renderer::with_*` methods.
+///
+/// [`Renderer::with_author`]: struct.Renderer.html#method.with_author
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Metadata {
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creator: Option<String>,
+    pub producer: Option<String>,
+    pub custom: collections::BTreeMap<String, String>,
 }
 
+impl Metadata {
+    fn is_empty(&self) -> bool {
+        self.author.is_none()
+            && self.subject.is_none()
+            && self.keywords.is_none()
+            && self.creator.is_none()
+            && self.producer.is_none()
+            && self.custom.is_empty()
+    }
+}
+
+/// A function that is called with the generated PDF document before it is written, see
+/// [`Renderer::with_post_processor`][].
+///
+/// [`Renderer::with_post_processor`]: struct.Renderer.html#method.with_post_processor
+pub(crate) type PostProcessor = Box<dyn FnOnce(&mut lopdf::Document) -> Result<(), Error>>;
+
 impl Renderer {
     /// Creates a new PDF document renderer with one page of the given size and the given title.
     pub fn new(size: impl Into<Size>, title: impl AsRef<str>) -> Result<Renderer, Error> {
@@ -94,11 +266,46 @@ impl Renderer {
         Ok(Renderer {
             doc,
             pages: vec![page],
+            use_object_streams: false,
+            post_processor: None,
+            snap_grid: None,
+            language: None,
+            metadata: Metadata::default(),
+            is_pdf_a: false,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+            attachments: Vec::new(),
+            font_subsets: Vec::new(),
+            has_cff_fonts: cell::Cell::new(false),
         })
     }
 
     /// Sets the PDF conformance for the generated PDF document.
-    pub fn with_conformance(mut self, conformance: printpdf::PdfConformance) -> Self {
+    ///
+    /// For [`Conformance::PdfA2b`][], the output intent and the XMP metadata are added when the
+    /// document is written, and [`write`][] returns an error if the document uses features that
+    /// are not allowed in PDF/A-2b documents.
+    ///
+    /// [`Conformance::PdfA2b`]: ../enum.Conformance.html#variant.PdfA2b
+    /// [`write`]: #method.write
+    pub fn with_conformance(mut self, conformance: impl Into<crate::Conformance>) -> Self {
+        let conformance = match conformance.into() {
+            crate::Conformance::PdfA2b => {
+                // printpdf only supports PDF/X output intents and metadata, so we add them
+                // ourselves when post-processing the document.
+                self.is_pdf_a = true;
+                printpdf::PdfConformance::Custom(printpdf::CustomPdfConformance {
+                    identifier: "PDF/A-2b".to_owned(),
+                    requires_icc_profile: false,
+                    requires_xmp_metadata: false,
+                    ..Default::default()
+                })
+            }
+            crate::Conformance::Printpdf(conformance) => {
+                self.is_pdf_a = false;
+                conformance
+            }
+        };
         self.doc = self.doc.with_conformance(conformance);
         self
     }
@@ -115,6 +322,167 @@ impl Renderer {
         self
     }
 
+    /// Sets whether the generated PDF document uses object streams.
+    ///
+    /// If object streams are enabled, all objects except for streams are packed into compressed
+    /// object streams and the cross-reference table is written as a cross-reference stream.  This
+    /// requires PDF 1.5 and can reduce the file size significantly for documents with many small
+    /// objects.  Per default, object streams are disabled.
+    pub fn with_object_streams(mut self, use_object_streams: bool) -> Self {
+        self.use_object_streams = use_object_streams;
+        self
+    }
+
+    /// Sets a function that is called with the generated PDF document before it is written.
+    ///
+    /// This is an escape hatch for advanced use cases that are not supported by this crate, for
+    /// example adding custom entries to the document catalog.  The function receives the
+    /// [`lopdf::Document`][] after all other post-processing steps, but before the document is
+    /// encrypted.  If it returns an error, the document is not written and the error is returned
+    /// by [`write`][].
+    ///
+    /// This method is unstable: the representation of the generated document can change with
+    /// every release, including the `lopdf` version.
+    ///
+    /// [`lopdf::Document`]: https://docs.rs/lopdf/0.26.0/lopdf/struct.Document.html
+    /// [`write`]: #method.write
+    pub fn with_post_processor(
+        mut self,
+        f: impl FnOnce(&mut lopdf::Document) -> Result<(), Error> + 'static,
+    ) -> Self {
+        self.post_processor = Some(Box::new(f));
+        self
+    }
+
+    /// Sets the grid that the coordinates of lines and shapes are snapped to.
+    ///
+    /// If a grid is set, the coordinates of all points of lines and shapes that are drawn on the
+    /// pages of this document are rounded to the nearest multiple of the grid size (in user
+    /// space, i. e. relative to the lower left corner of the page).  This can be used to avoid
+    /// blurry thin lines in PDF viewers.  Text and images are not affected.  Per default, the
+    /// coordinates are not snapped.
+    pub fn with_snap_grid(mut self, grid: impl Into<Mm>) -> Self {
+        let grid = Some(grid.into()).filter(|grid| *grid > Mm(0.0));
+        self.snap_grid = grid;
+        for page in &mut self.pages {
+            page.snap_grid = grid;
+        }
+        self
+    }
+
+    /// Sets the natural language of the generated PDF document.
+    ///
+    /// The language is given as a language tag as defined in [RFC 3066][], for example `en-US`.  It
+    /// is written to the `Lang` entry of the document catalog and used by screen readers and other
+    /// assistive technologies.
+    ///
+    /// [RFC 3066]: https://tools.ietf.org/html/rfc3066
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Sets the author of the generated PDF document.
+    pub fn with_author(mut self, author: impl Into<String>) -> Self {
+        self.metadata.author = Some(author.into());
+        self
+    }
+
+    /// Sets the subject of the generated PDF document.
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.metadata.subject = Some(subject.into());
+        self
+    }
+
+    /// Sets the keywords of the generated PDF document.
+    ///
+    /// The keywords are joined with commas.
+    pub fn with_keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let keywords: Vec<_> = keywords
+            .into_iter()
+            .map(|keyword| keyword.as_ref().to_owned())
+            .collect();
+        self.metadata.keywords = Some(keywords.join(", "));
+        self
+    }
+
+    /// Sets the name of the application that created the content of the generated PDF document.
+    pub fn with_creator(mut self, creator: impl Into<String>) -> Self {
+        self.metadata.creator = Some(creator.into());
+        self
+    }
+
+    /// Sets the name of the application that produced the generated PDF document.
+    pub fn with_producer(mut self, producer: impl Into<String>) -> Self {
+        self.metadata.producer = Some(producer.into());
+        self
+    }
+
+    /// Adds a custom entry with the given key and value to the document information dictionary
+    /// of the generated PDF document.
+    ///
+    /// Custom entries are not added to the XMP metadata.  If the key is one of the standard keys,
+    /// for example `Author`, the value is overwritten by the corresponding setter, for example
+    /// [`with_author`](#method.with_author).
+    pub fn with_custom_metadata(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.metadata.custom.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the encryption settings for the generated PDF document.
+    ///
+    /// See the [`encryption`](../encryption/index.html) module for more information.  Encrypted
+    /// documents cannot be PDF/A documents, so [`write`](#method.write) returns an error if this
+    /// method is combined with [`Conformance::PdfA2b`](../enum.Conformance.html#variant.PdfA2b).
+    ///
+    /// *Only available if the `encryption` feature is enabled.*
+    #[cfg(feature = "encryption")]
+    pub fn with_encryption(mut self, options: encryption::EncryptionOptions) -> Self {
+        self.encryption = Some(options);
+        self
+    }
+
+    /// Embeds the given file in the generated PDF document.
+    ///
+    /// The names of all attachments must be unique.  Otherwise, [`write`](#method.write) returns
+    /// an error.  PDF/A-2b documents may only contain PDF/A attachments, so attachments cannot be
+    /// combined with [`Conformance::PdfA2b`](../enum.Conformance.html#variant.PdfA2b).
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Replaces the embedded font with the given data by a subset that only contains the given
+    /// glyphs when the document is written.
+    ///
+    /// The data must be identical to the data passed to [`add_embedded_font`][].  The glyph IDs of
+    /// the subset are identical to the glyph IDs of the original font, but the outlines of all
+    /// other glyphs and all tables that are not required for rendering the glyphs are removed.
+    /// This can reduce the size of the generated document significantly.  Fonts that cannot be
+    /// subset, for example fonts with CFF outlines, are embedded as is.
+    ///
+    /// [`add_embedded_font`]: #method.add_embedded_font
+    pub fn add_font_subset(&mut self, data: &[u8], glyph_ids: collections::BTreeSet<u16>) {
+        self.font_subsets.push(FontSubset {
+            data: data.to_vec(),
+            glyph_ids,
+        });
+    }
+
+    /// Sets the metadata of the generated PDF document.
+    pub(crate) fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
     /// Adds a new page with the given size to the document.
     pub fn add_page(&mut self, size: impl Into<Size>) {
         let size = size.into();
@@ -123,7 +491,9 @@ impl Renderer {
                 .add_page(size.width.into(), size.height.into(), "Layer 1");
         let page_ref = self.doc.get_page(page_idx);
         let layer_ref = page_ref.get_layer(layer_idx);
-        self.pages.push(Page::new(page_ref, layer_ref, size))
+        let mut page = Page::new(page_ref, layer_ref, size);
+        page.snap_grid = self.snap_grid;
+        self.pages.push(page)
     }
 
     /// Returns the number of pages in this document.
@@ -175,7 +545,13 @@ impl Renderer {
 
     /// Loads the font from the given data, adds it to the generated document and returns a
     /// reference to it.
+    ///
+    /// The data must be a single TrueType or OpenType font, not a font collection.  OpenType fonts
+    /// with CFF outlines are embedded as `FontFile3` streams when the document is written.
     pub fn add_embedded_font(&self, data: &[u8]) -> Result<printpdf::IndirectFontRef, Error> {
+        if fonts::sfnt::is_cff(data) {
+            self.has_cff_fonts.set(true);
+        }
         self.doc
             .add_external_font(data)
             .context("Failed to load PDF font")
@@ -183,9 +559,148 @@ impl Renderer {
 
     /// Writes this PDF document to a writer.
     pub fn write(self, w: impl io::Write) -> Result<(), Error> {
-        self.doc
-            .save(&mut io::BufWriter::new(w))
-            .context("Failed to save document")
+        self.write_impl(w, None)
+    }
+
+    /// Writes this PDF document to a writer and signs it with the given signer.
+    ///
+    /// The signature is attached to the first signature field of the document, see
+    /// [`Area::add_signature_field`][].  If there is no signature field, an invisible signature
+    /// field is added to the first page.  See the [`signature`](../signature/index.html) module
+    /// for more information.  Encrypted documents cannot be signed.
+    ///
+    /// [`Area::add_signature_field`]: struct.Area.html#method.add_signature_field
+    pub fn write_signed(
+        self,
+        w: impl io::Write,
+        mut signer: impl signature::Signer,
+    ) -> Result<(), Error> {
+        self.write_impl(w, Some(&mut signer))
+    }
+
+    fn write_impl(
+        self,
+        w: impl io::Write,
+        signer: Option<&mut dyn signature::Signer>,
+    ) -> Result<(), Error> {
+        self.check_options(signer.is_some())?;
+        if !self.needs_post_processing(signer.is_some()) {
+            return self
+                .doc
+                .save(&mut io::BufWriter::new(w))
+                .context("Failed to save document");
+        }
+
+        let Renderer {
+            doc,
+            pages,
+            use_object_streams,
+            post_processor,
+            language,
+            metadata,
+            is_pdf_a,
+            #[cfg(feature = "encryption")]
+            encryption,
+            attachments,
+            font_subsets,
+            has_cff_fonts,
+            ..
+        } = self;
+        let mut data = Vec::new();
+        doc.save(&mut io::BufWriter::new(&mut data))
+            .context("Failed to save document")?;
+        let mut doc =
+            lopdf::Document::load_mem(&data).context("Failed to load generated document")?;
+        if !font_subsets.is_empty() {
+            subset_fonts(&mut doc, &font_subsets)?;
+        }
+        if has_cff_fonts.get() {
+            embed_cff_fonts(&mut doc);
+        }
+
+        let page_ids: Vec<_> = doc.get_pages().values().copied().collect();
+        for (page, page_id) in pages.iter().zip(page_ids.iter().copied()) {
+            page.post_process(&mut doc, page_id)?;
+        }
+        let bookmarks = collect_bookmarks(&pages, &page_ids);
+        let anchors = collect_anchors(&pages, &page_ids)?;
+        let mut form_fields = Vec::new();
+        for (page, page_id) in pages.iter().zip(page_ids.iter().copied()) {
+            page.add_links(&mut doc, page_id, &anchors)?;
+            form_fields.extend(page.add_form_fields(&mut doc, page_id)?);
+        }
+        if is_pdf_a {
+            pdfa::check_form_fields(form_fields.iter().map(|(_, field, _)| field))?;
+        }
+        if let Some(signer) = &signer {
+            add_signature(&mut doc, page_ids[0], &mut form_fields, &**signer)?;
+        }
+
+        if !form_fields.is_empty() {
+            add_acro_form(&mut doc, &form_fields, signer.is_some())?;
+        }
+        if !anchors.is_empty() {
+            add_named_destinations(&mut doc, &anchors)
+                .context("Failed to add named destinations")?;
+        }
+        if !attachments.is_empty() {
+            add_attachments(&mut doc, &attachments)?;
+        }
+        if !bookmarks.is_empty() {
+            add_outline(&mut doc, bookmarks).context("Failed to add document outline")?;
+        }
+        if let Some(language) = language {
+            set_language(&mut doc, language).context("Failed to set document language")?;
+        }
+        add_metadata(&mut doc, &metadata, is_pdf_a)?;
+        if let Some(post_processor) = post_processor {
+            post_processor(&mut doc)?;
+        }
+        #[cfg(feature = "encryption")]
+        let encryptor = encryption
+            .map(|options| Encryptor::new(&mut doc, &options))
+            .transpose()?;
+        #[cfg(not(feature = "encryption"))]
+        let encryptor = None;
+        save_document(doc, w, use_object_streams, encryptor, signer)
+    }
+
+    /// Returns an error if the options of this renderer cannot be combined.
+    fn check_options(&self, is_signed: bool) -> Result<(), Error> {
+        if self.is_pdf_a {
+            pdfa::check_options(self.is_encrypted(), !self.attachments.is_empty())?;
+        }
+        if is_signed && self.is_encrypted() {
+            return Err(Error::new(
+                "Signing encrypted documents is not supported",
+                ErrorKind::UnsupportedFeature,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns whether the document should be encrypted.
+    fn is_encrypted(&self) -> bool {
+        #[cfg(feature = "encryption")]
+        return self.encryption.is_some();
+        #[cfg(not(feature = "encryption"))]
+        return false;
+    }
+
+    /// Returns whether the document generated by `printpdf` has to be modified before it is
+    /// written.
+    fn needs_post_processing(&self, is_signed: bool) -> bool {
+        self.use_object_streams
+            || self.post_processor.is_some()
+            || self.language.is_some()
+            || !self.metadata.is_empty()
+            || self.is_pdf_a
+            || self.is_encrypted()
+            || is_signed
+            || !self.attachments.is_empty()
+            || !self.font_subsets.is_empty()
+            || self.has_cff_fonts.get()
+            || self.pages.iter().any(Page::needs_post_processing)
     }
 }
 
@@ -198,6 +713,19 @@ pub struct Page {
     page: printpdf::PdfPageReference,
     size: Size,
     layers: Layers,
+    operations: cell::RefCell<Vec<Vec<lopdf::content::Operation>>>,
+    opacities: cell::RefCell<Vec<f64>>,
+    bookmarks: cell::RefCell<Vec<Bookmark>>,
+    destinations: cell::RefCell<Vec<Destination>>,
+    links: cell::RefCell<Vec<Link>>,
+    form_fields: cell::RefCell<Vec<FormFieldWidget>>,
+    translations: cell::RefCell<Vec<Position>>,
+    active_translations: cell::RefCell<Vec<usize>>,
+    boxes: Option<PageBoxes>,
+    thumbnail: Option<Thumbnail>,
+    snap_grid: Option<Mm>,
+    is_mirrored: bool,
+    has_content: cell::Cell<bool>,
 }
 
 impl Page {
@@ -210,9 +738,66 @@ impl Page {
             page,
             size,
             layers: Layers::new(layer),
+            operations: Default::default(),
+            opacities: Default::default(),
+            bookmarks: Default::default(),
+            destinations: Default::default(),
+            links: Default::default(),
+            form_fields: Default::default(),
+            translations: Default::default(),
+            active_translations: Default::default(),
+            boxes: None,
+            thumbnail: None,
+            snap_grid: None,
+            is_mirrored: false,
+            has_content: cell::Cell::new(false),
         }
     }
 
+    /// Sets the boundary boxes for this page.
+    ///
+    /// Returns an error if the boxes are not valid for the size of this page, see
+    /// [`PageBoxes::validate`][].
+    ///
+    /// [`PageBoxes::validate`]: ../struct.PageBoxes.html#method.validate
+    pub fn set_boxes(&mut self, boxes: PageBoxes) -> Result<(), Error> {
+        boxes.validate(self.size)?;
+        self.boxes = Some(boxes);
+        Ok(())
+    }
+
+    /// Sets the thumbnail image for this page.
+    pub fn set_thumbnail(&mut self, thumbnail: Thumbnail) {
+        self.thumbnail = Some(thumbnail);
+    }
+
+    /// Sets whether the content of this page is mirrored horizontally.
+    ///
+    /// If this option is set, the complete content of the page, including text, images and
+    /// shapes, is flipped at the vertical center line of the page, for example for printing on
+    /// transfer paper.
+    pub fn set_mirrored(&mut self, is_mirrored: bool) {
+        self.is_mirrored = is_mirrored;
+    }
+
+    /// Returns whether text, lines, shapes or images have been drawn on this page since it has been
+    /// created or since the last call to [`clear_content`][].
+    ///
+    /// [`clear_content`]: #method.clear_content
+    pub fn has_content(&self) -> bool {
+        self.has_content.get()
+    }
+
+    /// Resets the flag returned by [`has_content`][], for example after a page decorator has
+    /// drawn the page header.
+    ///
+    /// This does not remove the content from the page.
+    ///
+    /// [`has_content`]: #method.has_content
+    pub fn clear_content(&self) {
+        self.has_content.set(false);
+    }
+
     /// Adds a new layer with the given name to the page.
     pub fn add_layer(&mut self, name: impl Into<String>) {
         let layer = self.page.add_layer(name);
@@ -239,6 +824,253 @@ impl Page {
         Layer::new(self, self.layers.last())
     }
 
+    fn needs_post_processing(&self) -> bool {
+        !self.operations.borrow().is_empty()
+            || !self.bookmarks.borrow().is_empty()
+            || !self.destinations.borrow().is_empty()
+            || !self.links.borrow().is_empty()
+            || !self.form_fields.borrow().is_empty()
+            || self.boxes.is_some()
+            || self.thumbnail.is_some()
+            || self.is_mirrored
+    }
+
+    /// Applies the given translations, see [`Area::with_translation`][], to the given position.
+    ///
+    /// [`Area::with_translation`]: struct.Area.html#method.with_translation
+    fn translate(&self, position: Position, translations: &[usize]) -> Position {
+        let all_translations = self.translations.borrow();
+        translations
+            .iter()
+            .fold(position, |position, idx| position + all_translations[*idx])
+    }
+
+    /// Registers a graphics state with the given opacity for this page and returns its name.
+    fn add_opacity(&self, opacity: f64) -> String {
+        let mut opacities = self.opacities.borrow_mut();
+        if !opacities.contains(&opacity) {
+            opacities.push(opacity);
+        }
+        get_opacity_name(opacity)
+    }
+
+    /// Inserts the raw operations and the settings for this page into the given document that has
+    /// been generated by `printpdf`.
+    fn post_process(
+        &self,
+        doc: &mut lopdf::Document,
+        page_id: lopdf::ObjectId,
+    ) -> Result<(), Error> {
+        if let Some(boxes) = &self.boxes {
+            let page = doc
+                .get_object_mut(page_id)
+                .and_then(lopdf::Object::as_dict_mut)
+                .context("Failed to update page boxes")?;
+            let rects = [
+                ("MediaBox", boxes.media_box(self.size)),
+                ("CropBox", boxes.crop_box(self.size)),
+                ("TrimBox", boxes.trim_box(self.size)),
+            ];
+            for (name, (origin, size)) in rects.iter() {
+                let lower_right = Position::new(origin.x + size.width, origin.y + size.height);
+                let lower_right = UserSpacePosition::from_page(self, LayerPosition(lower_right));
+                let upper_left = UserSpacePosition::from_page(self, LayerPosition(*origin));
+                let rect = vec![
+                    printpdf::Pt::from(upper_left.x).0.into(),
+                    printpdf::Pt::from(lower_right.y).0.into(),
+                    printpdf::Pt::from(lower_right.x).0.into(),
+                    printpdf::Pt::from(upper_left.y).0.into(),
+                ];
+                page.set(*name, lopdf::Object::Array(rect));
+            }
+        }
+
+        if let Some(thumbnail) = &self.thumbnail {
+            let mut dict = lopdf::Dictionary::new();
+            dict.set("Width", i64::from(thumbnail.width()));
+            dict.set("Height", i64::from(thumbnail.height()));
+            dict.set("ColorSpace", lopdf::Object::Name(b"DeviceRGB".to_vec()));
+            dict.set("BitsPerComponent", 8);
+            let stream = lopdf::Stream::new(dict, thumbnail.data().to_vec());
+            let thumbnail_id = doc.add_object(stream);
+            doc.get_object_mut(page_id)
+                .and_then(lopdf::Object::as_dict_mut)
+                .context("Failed to add page thumbnail")?
+                .set("Thumb", lopdf::Object::Reference(thumbnail_id));
+        }
+
+        let operations = self.operations.borrow();
+        if operations.is_empty() && !self.is_mirrored {
+            return Ok(());
+        }
+
+        let opacities = self.opacities.borrow();
+        if !opacities.is_empty() {
+            let states = get_page_resources_mut(doc, page_id, b"ExtGState")
+                .context("Failed to update page resources")?;
+            for opacity in opacities.iter() {
+                let mut state = lopdf::Dictionary::new();
+                state.set("Type", lopdf::Object::Name(b"ExtGState".to_vec()));
+                state.set("CA", lopdf::Object::Real(*opacity));
+                state.set("ca", lopdf::Object::Real(*opacity));
+                states.set(get_opacity_name(*opacity), state);
+            }
+        }
+
+        // printpdf merges the layers of a page into a single content stream, but we still process
+        // all content streams of the page.  The markers are single operations, so they never span
+        // multiple streams.  The mirroring transformation is opened in the first stream and closed
+        // in the last stream.
+        let content_ids = doc.get_page_contents(page_id);
+        let last_idx = content_ids.len().saturating_sub(1);
+        for (idx, content_id) in content_ids.into_iter().enumerate() {
+            let stream = doc
+                .get_object(content_id)
+                .and_then(lopdf::Object::as_stream)
+                .context("Failed to read page content")?;
+            let content = stream
+                .decompressed_content()
+                .unwrap_or_else(|_| stream.content.clone());
+            let content = lopdf::content::Content::decode(&content)
+                .context("Failed to decode page content")?;
+            let mut new_content = lopdf::content::Content {
+                operations: Vec::with_capacity(content.operations.len() + 3),
+            };
+            if self.is_mirrored && idx == 0 {
+                let width = printpdf::Pt::from(self.size.width).0;
+                let operands = vec![
+                    (-1).into(),
+                    0.into(),
+                    0.into(),
+                    1.into(),
+                    lopdf::Object::Real(width),
+                    0.into(),
+                ];
+                new_content.operations.extend(vec![
+                    lopdf::content::Operation::new("q", vec![]),
+                    lopdf::content::Operation::new("cm", operands),
+                ]);
+            }
+            for operation in content.operations {
+                if let Some(idx) = get_operations_marker(&operation) {
+                    new_content
+                        .operations
+                        .extend(operations[idx].iter().cloned());
+                } else {
+                    new_content.operations.push(operation);
+                }
+            }
+            if self.is_mirrored && idx == last_idx {
+                new_content
+                    .operations
+                    .push(lopdf::content::Operation::new("Q", vec![]));
+            }
+            let content = new_content
+                .encode()
+                .context("Failed to encode page content")?;
+            doc.get_object_mut(content_id)
+                .and_then(lopdf::Object::as_stream_mut)
+                .context("Failed to update page content")?
+                .set_plain_content(content);
+        }
+        Ok(())
+    }
+
+    /// Adds the link annotations of this page to the given document, using the given resolved
+    /// anchors.
+    fn add_links(
+        &self,
+        doc: &mut lopdf::Document,
+        page_id: lopdf::ObjectId,
+        anchors: &collections::BTreeMap<Anchor, (lopdf::ObjectId, UserSpacePosition)>,
+    ) -> Result<(), Error> {
+        let links = self.links.borrow();
+        if links.is_empty() {
+            return Ok(());
+        }
+
+        let mut annotations = Vec::with_capacity(links.len());
+        for link in links.iter() {
+            let upper_left = self.translate(link.position, &link.translations);
+            let lower_right = upper_left + Position::new(link.size.width, link.size.height);
+            let upper_left = UserSpacePosition::from_page(self, LayerPosition(upper_left));
+            let lower_right = UserSpacePosition::from_page(self, LayerPosition(lower_right));
+            let mut dict = lopdf::Dictionary::new();
+            dict.set("Type", lopdf::Object::Name(b"Annot".to_vec()));
+            dict.set("Subtype", lopdf::Object::Name(b"Link".to_vec()));
+            dict.set(
+                "Rect",
+                vec![
+                    printpdf::Pt::from(upper_left.x).0.into(),
+                    printpdf::Pt::from(lower_right.y).0.into(),
+                    printpdf::Pt::from(lower_right.x).0.into(),
+                    printpdf::Pt::from(upper_left.y).0.into(),
+                ],
+            );
+            dict.set("Border", vec![0.into(), 0.into(), 0.into()]);
+            match &link.target {
+                LinkTarget::Anchor(anchor) => {
+                    let (target_page_id, target) = anchors.get(anchor).ok_or_else(|| {
+                        Error::new(
+                            format!("Unknown link target '{}'", anchor.name()),
+                            ErrorKind::InvalidData,
+                        )
+                    })?;
+                    dict.set("Dest", get_destination(*target_page_id, target));
+                }
+                LinkTarget::Uri(uri) => {
+                    let mut action = lopdf::Dictionary::new();
+                    action.set("Type", lopdf::Object::Name(b"Action".to_vec()));
+                    action.set("S", lopdf::Object::Name(b"URI".to_vec()));
+                    action.set("URI", lopdf::Object::string_literal(uri.as_str()));
+                    dict.set("A", action);
+                }
+            }
+            annotations.push(lopdf::Object::Reference(doc.add_object(dict)));
+        }
+
+        add_annotations(doc, page_id, annotations).context("Failed to add link annotations")
+    }
+
+    /// Adds the widget annotations of the form fields of this page to the given document and
+    /// returns the names and fields together with the IDs of the widget annotations.
+    fn add_form_fields(
+        &self,
+        doc: &mut lopdf::Document,
+        page_id: lopdf::ObjectId,
+    ) -> Result<Vec<(String, FormField, lopdf::ObjectId)>, Error> {
+        let fields = self.form_fields.borrow();
+        if fields.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut ids = Vec::with_capacity(fields.len());
+        for field in fields.iter() {
+            let upper_left = self.translate(field.position, &field.translations);
+            let lower_right = upper_left + Position::new(field.size.width, field.size.height);
+            let upper_left = UserSpacePosition::from_page(self, LayerPosition(upper_left));
+            let lower_right = UserSpacePosition::from_page(self, LayerPosition(lower_right));
+            let rect = [
+                printpdf::Pt::from(upper_left.x).0,
+                printpdf::Pt::from(lower_right.y).0,
+                printpdf::Pt::from(lower_right.x).0,
+                printpdf::Pt::from(upper_left.y).0,
+            ];
+            let widget = form_widget(doc, page_id, &field.name, &field.field, rect);
+            ids.push((
+                field.name.clone(),
+                field.field.clone(),
+                doc.add_object(widget),
+            ));
+        }
+        let annotations = ids
+            .iter()
+            .map(|(_, _, id)| lopdf::Object::Reference(*id))
+            .collect();
+        add_annotations(doc, page_id, annotations).context("Failed to add form fields")?;
+        Ok(ids)
+    }
+
     fn next_layer(&self, layer: &printpdf::PdfLayerReference) -> Layer<'_> {
         let layer = self.layers.next(layer).unwrap_or_else(|| {
             let layer = self
@@ -250,6 +1082,149 @@ impl Page {
     }
 }
 
+/// A named anchor that marks a position in a document and can be the target of links.
+///
+/// Anchors are identified by their name, so a link to an anchor can be added before or after the
+/// anchor itself, see [`Area::add_anchor`][] and [`Area::add_link`][].  The links are resolved
+/// when the document is written.  The anchors are also added to the document as named
+/// destinations so that they can be referenced from other documents, for example with the URL
+/// `document.pdf#name`.
+///
+/// [`Area::add_anchor`]: struct.Area.html#method.add_anchor
+/// [`Area::add_link`]: struct.Area.html#method.add_link
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Anchor(String);
+
+impl Anchor {
+    /// Creates a new anchor with the given name.
+    pub fn new(name: impl Into<String>) -> Anchor {
+        Anchor(name.into())
+    }
+
+    /// Returns the name of this anchor.
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Anchor {
+    fn from(name: &str) -> Anchor {
+        Anchor::new(name)
+    }
+}
+
+impl From<String> for Anchor {
+    fn from(name: String) -> Anchor {
+        Anchor::new(name)
+    }
+}
+
+/// The glyphs of an embedded font that are used in the document, see
+/// [`Renderer::add_font_subset`][].
+///
+/// [`Renderer::add_font_subset`]: struct.Renderer.html#method.add_font_subset
+#[derive(Clone, Debug)]
+struct FontSubset {
+    data: Vec<u8>,
+    glyph_ids: collections::BTreeSet<u16>,
+}
+
+/// The position of an [`Anchor`][] on a page.
+///
+/// [`Anchor`]: struct.Anchor.html
+#[derive(Clone, Debug)]
+struct Destination {
+    anchor: Anchor,
+    position: Position,
+    translations: Vec<usize>,
+}
+
+/// A link annotation that points to an [`Anchor`][] or to a URI.
+///
+/// [`Anchor`]: struct.Anchor.html
+#[derive(Clone, Debug)]
+struct Link {
+    target: LinkTarget,
+    position: Position,
+    size: Size,
+    translations: Vec<usize>,
+}
+
+/// An interactive form field that can be filled in with a PDF viewer, see
+/// [`Area::add_form_field`][].
+///
+/// [`Area::add_form_field`]: struct.Area.html#method.add_form_field
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum FormField {
+    /// A text field.
+    Text {
+        /// The initial value of the text field.
+        value: String,
+        /// Whether the text field may contain multiple lines.
+        multiline: bool,
+        /// The maximum number of characters of the text field.
+        max_len: Option<usize>,
+    },
+    /// A check box.
+    CheckBox {
+        /// Whether the check box is initially checked.
+        checked: bool,
+    },
+    /// A radio button.
+    ///
+    /// All radio buttons with the same field name form a group.  Only one of the radio buttons of
+    /// a group can be selected.
+    RadioButton {
+        /// The export value of this radio button that is set as the value of the group if the
+        /// radio button is selected.
+        value: String,
+        /// Whether the radio button is initially selected.
+        checked: bool,
+    },
+    /// A dropdown list with a fixed set of options.
+    Dropdown {
+        /// The options that can be selected.
+        options: Vec<String>,
+        /// The initially selected option.
+        value: Option<String>,
+    },
+    /// A signature field, see [`Area::add_signature_field`][].
+    ///
+    /// [`Area::add_signature_field`]: struct.Area.html#method.add_signature_field
+    Signature,
+}
+
+/// The widget annotation of a [`FormField`][] on a page.
+///
+/// [`FormField`]: enum.FormField.html
+#[derive(Clone, Debug)]
+struct FormFieldWidget {
+    name: String,
+    field: FormField,
+    position: Position,
+    size: Size,
+    translations: Vec<usize>,
+}
+
+/// The target of a [`Link`][].
+///
+/// [`Link`]: struct.Link.html
+#[derive(Clone, Debug)]
+enum LinkTarget {
+    Anchor(Anchor),
+    Uri(String),
+}
+
+/// An entry of the document outline that points to a position on a page.
+#[derive(Clone, Debug)]
+struct Bookmark {
+    title: String,
+    level: usize,
+    position: Position,
+    translations: Vec<usize>,
+}
+
 #[derive(Debug)]
 struct Layers(cell::RefCell<Vec<rc::Rc<LayerData>>>);
 
@@ -328,6 +1303,7 @@ impl<'p> Layer<'p> {
         rotation: Rotation,
         dpi: Option<f64>,
     ) {
+        self.page.has_content.set(true);
         let dynamic_image = printpdf::Image::from_dynamic_image(image);
         let position = self.transform_position(position);
         dynamic_image.add_to_layer(
@@ -341,13 +1317,40 @@ impl<'p> Layer<'p> {
         );
     }
 
+    /// Adds the given raw PDF operations to this layer and returns their index.
+    fn add_operations(&self, operations: Vec<lopdf::content::Operation>) -> usize {
+        let mut page_operations = self.page.operations.borrow_mut();
+        let idx = page_operations.len();
+        page_operations.push(operations);
+        self.data
+            .layer
+            .set_line_dash_pattern(printpdf::LineDashPattern::new(
+                OPERATIONS_MARKER,
+                Some(idx as i64),
+                None,
+                None,
+                None,
+                None,
+                None,
+            ));
+        idx
+    }
+
+    /// Replaces the raw PDF operations with the given index, see [`add_operations`][].
+    ///
+    /// [`add_operations`]: #method.add_operations
+    fn replace_operations(&self, idx: usize, operations: Vec<lopdf::content::Operation>) {
+        self.page.operations.borrow_mut()[idx] = operations;
+    }
+
     fn add_line_shape<I>(&self, points: I)
     where
         I: IntoIterator<Item = LayerPosition>,
     {
+        self.page.has_content.set(true);
         let line_points: Vec<_> = points
             .into_iter()
-            .map(|pos| (self.transform_position(pos).into(), false))
+            .map(|pos| (self.transform_shape_position(pos), false))
             .collect();
         let line = printpdf::Line {
             points: line_points,
@@ -363,9 +1366,10 @@ impl<'p> Layer<'p> {
     where
         I: IntoIterator<Item = LayerPosition>,
     {
+        self.page.has_content.set(true);
         let line_points: Vec<_> = points
             .into_iter()
-            .map(|pos| (self.transform_position(pos).into(), false))
+            .map(|pos| (self.transform_shape_position(pos), false))
             .collect();
         let line = printpdf::Line {
             points: line_points,
@@ -377,6 +1381,45 @@ impl<'p> Layer<'p> {
         self.data.layer.add_shape(line);
     }
 
+    /// Adds a filled rectangle with rounded corners with the given upper left corner, size and
+    /// corner radius.
+    fn add_rounded_rect_with_fill_color(&self, origin: LayerPosition, size: Size, radius: Mm) {
+        self.page.has_content.set(true);
+        let radius = radius
+            .max(Mm(0.0))
+            .min(size.width / 2.0)
+            .min(size.height / 2.0);
+        // The distance of the Bézier control points from the corners for a circular arc
+        let k = radius * (1.0 - 0.552_284_75);
+        let (w, h) = (size.width, size.height);
+        let point = |x: Mm, y: Mm| {
+            let point =
+                self.transform_shape_position(LayerPosition(origin.0 + Position::new(x, y)));
+            vec![
+                lopdf::Object::Real(point.x.0),
+                lopdf::Object::Real(point.y.0),
+            ]
+        };
+        let line = |x, y| lopdf::content::Operation::new("l", point(x, y));
+        let curve = |points: [(Mm, Mm); 3]| {
+            let operands = points.iter().flat_map(|(x, y)| point(*x, *y)).collect();
+            lopdf::content::Operation::new("c", operands)
+        };
+        self.add_operations(vec![
+            lopdf::content::Operation::new("m", point(radius, Mm(0.0))),
+            line(w - radius, Mm(0.0)),
+            curve([(w - k, Mm(0.0)), (w, k), (w, radius)]),
+            line(w, h - radius),
+            curve([(w, h - k), (w - k, h), (w - radius, h)]),
+            line(radius, h),
+            curve([(k, h), (Mm(0.0), h - k), (Mm(0.0), h - radius)]),
+            line(Mm(0.0), radius),
+            curve([(Mm(0.0), k), (k, Mm(0.0)), (radius, Mm(0.0))]),
+            lopdf::content::Operation::new("h", vec![]),
+            lopdf::content::Operation::new("f", vec![]),
+        ]);
+    }
+
     fn set_fill_color(&self, color: Option<Color>) {
         if self.data.update_fill_color(color) {
             self.data
@@ -393,20 +1436,89 @@ impl<'p> Layer<'p> {
         }
     }
 
-    fn set_outline_color(&self, color: Color) {
-        if self.data.update_outline_color(color) {
-            self.data.layer.set_outline_color(color.into());
+    fn set_dash_pattern(&self, dash_pattern: &[Mm]) {
+        if self.data.update_dash_pattern(dash_pattern) {
+            let lengths = dash_pattern
+                .iter()
+                .map(|length| lopdf::Object::Real(printpdf::Pt::from(*length).0))
+                .collect();
+            self.add_operations(vec![lopdf::content::Operation::new(
+                "d",
+                vec![lopdf::Object::Array(lengths), lopdf::Object::Integer(0)],
+            )]);
         }
     }
 
-    fn set_text_cursor(&self, cursor: LayerPosition) {
-        let cursor = self.transform_position(cursor);
-        self.data
-            .layer
-            .set_text_cursor(cursor.x.into(), cursor.y.into());
+    fn set_line_cap(&self, line_cap: LineCap) {
+        if self.data.line_cap.replace(line_cap) != line_cap {
+            let value = match line_cap {
+                LineCap::Butt => 0,
+                LineCap::Round => 1,
+                LineCap::Square => 2,
+            };
+            self.add_operations(vec![lopdf::content::Operation::new(
+                "J",
+                vec![lopdf::Object::Integer(value)],
+            )]);
+        }
     }
 
-    fn begin_text_section(&self) {
+    fn set_line_join(&self, line_join: LineJoin) {
+        if self.data.line_join.replace(line_join) != line_join {
+            let value = match line_join {
+                LineJoin::Miter => 0,
+                LineJoin::Round => 1,
+                LineJoin::Bevel => 2,
+            };
+            self.add_operations(vec![lopdf::content::Operation::new(
+                "j",
+                vec![lopdf::Object::Integer(value)],
+            )]);
+        }
+    }
+
+    /// Sets the outline thickness, color, dash pattern, line cap and line join of the given line
+    /// style.
+    fn set_line_style(&self, line_style: LineStyle) {
+        self.set_outline_thickness(line_style.thickness());
+        self.set_outline_color(line_style.color());
+        self.set_dash_pattern(line_style.dash_pattern());
+        self.set_line_cap(line_style.line_cap());
+        self.set_line_join(line_style.line_join());
+    }
+
+    fn save_graphics_state(&self) {
+        self.data.layer.save_graphics_state();
+        self.data.save_state();
+    }
+
+    fn restore_graphics_state(&self) {
+        self.data.layer.restore_graphics_state();
+        self.data.restore_state();
+    }
+
+    fn set_opacity(&self, opacity: f64) {
+        let name = self.page.add_opacity(opacity);
+        self.add_operations(vec![lopdf::content::Operation::new(
+            "gs",
+            vec![lopdf::Object::Name(name.into_bytes())],
+        )]);
+    }
+
+    fn set_outline_color(&self, color: Color) {
+        if self.data.update_outline_color(color) {
+            self.data.layer.set_outline_color(color.into());
+        }
+    }
+
+    fn set_text_cursor(&self, cursor: LayerPosition) {
+        let cursor = self.transform_position(cursor);
+        self.data
+            .layer
+            .set_text_cursor(cursor.x.into(), cursor.y.into());
+    }
+
+    fn begin_text_section(&self) {
         self.data.layer.begin_text_section();
     }
 
@@ -426,6 +1538,14 @@ impl<'p> Layer<'p> {
         self.data.layer.set_font(font, font_size.into());
     }
 
+    fn set_text_rise(&self, rise: Mm) {
+        self.data.layer.set_line_offset(printpdf::Pt::from(rise).0);
+    }
+
+    fn set_text_rendering_mode(&self, mode: printpdf::TextRenderingMode) {
+        self.data.layer.set_text_rendering_mode(mode);
+    }
+
     fn write_positioned_codepoints<P, C>(&self, positions: P, codepoints: C)
     where
         P: IntoIterator<Item = i64>,
@@ -433,7 +1553,46 @@ impl<'p> Layer<'p> {
     {
         self.data
             .layer
-            .write_positioned_codepoints(positions.into_iter().zip(codepoints.into_iter()));
+            .write_positioned_codepoints(positions.into_iter().zip(codepoints));
+    }
+
+    /// Writes the given single-byte character codes with the given positions, as used by the
+    /// built-in fonts.
+    ///
+    /// `printpdf` always writes two bytes per character, so we have to insert the operation
+    /// manually.
+    fn write_positioned_bytes<P, C>(&self, positions: P, bytes: C)
+    where
+        P: IntoIterator<Item = i64>,
+        C: IntoIterator<Item = u8>,
+    {
+        let mut list = Vec::new();
+        for (pos, byte) in positions.into_iter().zip(bytes) {
+            if pos != 0 {
+                list.push(lopdf::Object::Integer(pos));
+            }
+            list.push(lopdf::Object::String(
+                vec![byte],
+                lopdf::StringFormat::Hexadecimal,
+            ));
+        }
+        self.add_operations(vec![lopdf::content::Operation::new(
+            "TJ",
+            vec![lopdf::Object::Array(list)],
+        )]);
+    }
+
+    /// Transforms the given position of a point of a shape to a `printpdf` point, snapping it to
+    /// the grid of the page if set.
+    fn transform_shape_position(&self, position: LayerPosition) -> printpdf::Point {
+        let position = self.transform_position(position);
+        let position = if let Some(grid) = self.page.snap_grid {
+            let snap = |value: Mm| grid * (value.0 / grid.0).round();
+            Position::new(snap(position.x), snap(position.y))
+        } else {
+            *position
+        };
+        printpdf::Point::new(position.x.into(), position.y.into())
     }
 
     /// Transforms the given position that is relative to the upper left corner of the layer to a
@@ -449,9 +1608,49 @@ struct LayerData {
     fill_color: cell::Cell<Color>,
     outline_color: cell::Cell<Color>,
     outline_thickness: cell::Cell<Mm>,
+    dash_pattern: cell::RefCell<Vec<Mm>>,
+    line_cap: cell::Cell<LineCap>,
+    line_join: cell::Cell<LineJoin>,
+    saved_states: cell::RefCell<Vec<LayerState>>,
+}
+
+/// The cached settings of a layer that are part of the graphics state.
+#[derive(Debug)]
+struct LayerState {
+    fill_color: Color,
+    outline_color: Color,
+    outline_thickness: Mm,
+    dash_pattern: Vec<Mm>,
+    line_cap: LineCap,
+    line_join: LineJoin,
 }
 
 impl LayerData {
+    /// Saves the cached settings so that they can be restored together with the graphics state.
+    pub fn save_state(&self) {
+        let state = LayerState {
+            fill_color: self.fill_color.get(),
+            outline_color: self.outline_color.get(),
+            outline_thickness: self.outline_thickness.get(),
+            dash_pattern: self.dash_pattern.borrow().clone(),
+            line_cap: self.line_cap.get(),
+            line_join: self.line_join.get(),
+        };
+        self.saved_states.borrow_mut().push(state);
+    }
+
+    /// Restores the cached settings that have been saved with the last call to `save_state`.
+    pub fn restore_state(&self) {
+        if let Some(state) = self.saved_states.borrow_mut().pop() {
+            self.fill_color.set(state.fill_color);
+            self.outline_color.set(state.outline_color);
+            self.outline_thickness.set(state.outline_thickness);
+            self.dash_pattern.replace(state.dash_pattern);
+            self.line_cap.set(state.line_cap);
+            self.line_join.set(state.line_join);
+        }
+    }
+
     pub fn update_fill_color(&self, color: Option<Color>) -> bool {
         let color = color.unwrap_or(Color::Rgb(0, 0, 0));
         self.fill_color.replace(color) != color
@@ -464,6 +1663,16 @@ impl LayerData {
     pub fn update_outline_thickness(&self, thickness: Mm) -> bool {
         self.outline_thickness.replace(thickness) != thickness
     }
+
+    pub fn update_dash_pattern(&self, dash_pattern: &[Mm]) -> bool {
+        let mut current = self.dash_pattern.borrow_mut();
+        if current.as_slice() == dash_pattern {
+            false
+        } else {
+            *current = dash_pattern.to_vec();
+            true
+        }
+    }
 }
 
 impl From<printpdf::PdfLayerReference> for LayerData {
@@ -473,6 +1682,10 @@ impl From<printpdf::PdfLayerReference> for LayerData {
             fill_color: Color::Rgb(0, 0, 0).into(),
             outline_color: Color::Rgb(0, 0, 0).into(),
             outline_thickness: Mm::from(printpdf::Pt(1.0)).into(),
+            dash_pattern: Default::default(),
+            line_cap: Default::default(),
+            line_join: Default::default(),
+            saved_states: Default::default(),
         }
     }
 }
@@ -488,6 +1701,7 @@ pub struct Area<'p> {
     layer: Layer<'p>,
     origin: Position,
     size: Size,
+    opacity: f64,
 }
 
 impl<'p> Area<'p> {
@@ -496,6 +1710,7 @@ impl<'p> Area<'p> {
             layer,
             origin,
             size,
+            opacity: 1.0,
         }
     }
 
@@ -509,7 +1724,209 @@ impl<'p> Area<'p> {
             layer,
             origin: self.origin,
             size: self.size,
+            opacity: 1.0,
+        }
+    }
+
+    /// Returns whether anything has been drawn on the page of this area, see
+    /// [`Page::has_content`][].
+    ///
+    /// [`Page::has_content`]: struct.Page.html#method.has_content
+    pub fn has_content(&self) -> bool {
+        self.layer.page.has_content()
+    }
+
+    /// Returns the position of the upper left corner of this area on the page.
+    pub(crate) fn origin(&self) -> Position {
+        self.origin
+    }
+
+    /// Returns the opacity of this area, from 0 (transparent) to 1 (opaque).
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// Calls the given function with a copy of this area that is drawn with the given opacity.
+    ///
+    /// The opacity is clamped to the range from 0 (transparent) to 1 (opaque) and multiplied with
+    /// the opacity of this area.  It is applied to everything that is drawn on the area passed to
+    /// the function, but not to other layers.
+    pub fn with_opacity<T>(&self, opacity: f64, f: impl FnOnce(Area<'p>) -> T) -> T {
+        let opacity = round_opacity(self.opacity * opacity.max(0.0).min(1.0));
+        if opacity == self.opacity {
+            return f(self.clone());
         }
+
+        let mut area = self.clone();
+        area.opacity = opacity;
+        self.layer.save_graphics_state();
+        self.layer.set_opacity(opacity);
+        let result = f(area);
+        self.layer.restore_graphics_state();
+        result
+    }
+
+    /// Calls the given function with a copy of this area and moves everything that it draws by an
+    /// offset that is calculated from its result.
+    ///
+    /// This can be used to position content depending on its size that is only known after it
+    /// has been rendered, for example to vertically center it.  The offset is applied to
+    /// everything that is drawn on the area passed to the function, but not to other layers.  It
+    /// is also applied to the bookmarks added to the area.
+    pub fn with_translation<T, E>(
+        &self,
+        f: impl FnOnce(Area<'p>) -> Result<T, E>,
+        offset: impl FnOnce(&T) -> Position,
+    ) -> Result<T, E> {
+        let page = self.layer.page;
+        let translation_idx = {
+            let mut translations = page.translations.borrow_mut();
+            translations.push(Position::default());
+            translations.len() - 1
+        };
+        self.layer.save_graphics_state();
+        let operations_idx = self.layer.add_operations(Vec::new());
+        page.active_translations.borrow_mut().push(translation_idx);
+
+        let result = f(self.clone());
+
+        page.active_translations.borrow_mut().pop();
+        if let Ok(value) = &result {
+            let offset = offset(value);
+            page.translations.borrow_mut()[translation_idx] = offset;
+            let operands = vec![
+                1.into(),
+                0.into(),
+                0.into(),
+                1.into(),
+                lopdf::Object::Real(printpdf::Pt::from(offset.x).0),
+                lopdf::Object::Real(-printpdf::Pt::from(offset.y).0),
+            ];
+            self.layer.replace_operations(
+                operations_idx,
+                vec![lopdf::content::Operation::new("cm", operands)],
+            );
+        }
+        self.layer.restore_graphics_state();
+        result
+    }
+
+    /// Calls the given function with a copy of this area that is scaled by the given factor.
+    ///
+    /// The area passed to the function is larger than this area by the inverse of the factor, and
+    /// everything that is drawn on it is scaled by the factor around the upper left corner of
+    /// this area.  For example, with a factor of 0.5, the function receives an area that is twice
+    /// as large as this area and content is drawn at half its size.  The scaling is applied to
+    /// everything that is drawn on the area passed to the function, but not to other layers.
+    /// Bookmarks added to the area are not scaled.
+    pub fn with_scale<T>(&self, scale: f64, f: impl FnOnce(Area<'p>) -> T) -> T {
+        let origin: printpdf::Point = self
+            .layer
+            .transform_position(self.position(Position::default()))
+            .into();
+        let operands = vec![
+            lopdf::Object::Real(scale),
+            0.into(),
+            0.into(),
+            lopdf::Object::Real(scale),
+            lopdf::Object::Real(origin.x.0 * (1.0 - scale)),
+            lopdf::Object::Real(origin.y.0 * (1.0 - scale)),
+        ];
+        let mut area = self.clone();
+        area.size = Size::new(self.size.width / scale, self.size.height / scale);
+        self.layer.save_graphics_state();
+        self.layer
+            .add_operations(vec![lopdf::content::Operation::new("cm", operands)]);
+        let result = f(area);
+        self.layer.restore_graphics_state();
+        result
+    }
+
+    /// Calls the given function with a copy of this area and rotates everything that it draws on
+    /// this layer clockwise by the given rotation around the given anchor.
+    ///
+    /// The anchor is relative to the upper left corner of this area.  The rotation is applied to
+    /// everything that is drawn on the area passed to the function, but not to other layers.
+    /// Links and bookmarks added to the area are not rotated.
+    pub fn with_rotation<T>(
+        &self,
+        rotation: Rotation,
+        anchor: impl Into<Position>,
+        f: impl FnOnce(Area<'p>) -> T,
+    ) -> T {
+        let degrees = if let Some(degrees) = rotation.degrees() {
+            degrees
+        } else {
+            return f(self.clone());
+        };
+        let origin: printpdf::Point = self
+            .layer
+            .transform_position(self.position(anchor.into()))
+            .into();
+        let (x, y) = (origin.x.0, origin.y.0);
+        // The y axis of the PDF coordinate system points up, so a clockwise rotation has a
+        // negative angle.
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let operands = vec![
+            lopdf::Object::Real(cos),
+            lopdf::Object::Real(-sin),
+            lopdf::Object::Real(sin),
+            lopdf::Object::Real(cos),
+            lopdf::Object::Real(x - (cos * x + sin * y)),
+            lopdf::Object::Real(y - (-sin * x + cos * y)),
+        ];
+        self.layer.save_graphics_state();
+        self.layer
+            .add_operations(vec![lopdf::content::Operation::new("cm", operands)]);
+        let result = f(self.clone());
+        self.layer.restore_graphics_state();
+        result
+    }
+
+    /// Calls the given function with a copy of this area and clips everything that it draws on
+    /// this layer at the borders of the area.
+    pub fn with_clipping<T>(&self, f: impl FnOnce(Area<'p>) -> T) -> T {
+        let origin: printpdf::Point = self
+            .layer
+            .transform_position(self.position(Position::new(0, self.size.height)))
+            .into();
+        let operands = vec![
+            lopdf::Object::Real(origin.x.0),
+            lopdf::Object::Real(origin.y.0),
+            lopdf::Object::Real(printpdf::Pt::from(self.size.width).0),
+            lopdf::Object::Real(printpdf::Pt::from(self.size.height).0),
+        ];
+        self.layer.save_graphics_state();
+        self.layer.add_operations(vec![
+            lopdf::content::Operation::new("re", operands),
+            lopdf::content::Operation::new("W", vec![]),
+            lopdf::content::Operation::new("n", vec![]),
+        ]);
+        let result = f(self.clone());
+        self.layer.restore_graphics_state();
+        result
+    }
+
+    /// Calls the given function with a copy of this area and marks everything that it draws on
+    /// this layer as written in the given natural language.
+    ///
+    /// The language is given as a language tag as defined in [RFC 3066][], for example `fr-FR`.
+    /// The content is enclosed in a marked-content sequence with the tag `Span` and a `Lang`
+    /// property.
+    ///
+    /// [RFC 3066]: https://tools.ietf.org/html/rfc3066
+    pub fn with_language<T>(&self, language: &str, f: impl FnOnce(Area<'p>) -> T) -> T {
+        let mut properties = lopdf::Dictionary::new();
+        properties.set("Lang", lopdf::Object::string_literal(language));
+        self.layer
+            .add_operations(vec![lopdf::content::Operation::new(
+                "BDC",
+                vec![lopdf::Object::Name(b"Span".to_vec()), properties.into()],
+            )]);
+        let result = f(self.clone());
+        self.layer
+            .add_operations(vec![lopdf::content::Operation::new("EMC", vec![])]);
+        result
     }
 
     /// Reduces the size of the drawable area by the given margins.
@@ -526,6 +1943,14 @@ impl<'p> Area<'p> {
         self.size
     }
 
+    /// Returns a copy of this area that covers the full page.
+    pub fn page_area(&self) -> Self {
+        let mut area = self.clone();
+        area.origin = Position::default();
+        area.size = self.layer.page.size;
+        area
+    }
+
     /// Adds the given offset to the area, reducing the drawable area.
     pub fn add_offset(&mut self, offset: impl Into<Position>) {
         let offset = offset.into();
@@ -558,19 +1983,132 @@ impl<'p> Area<'p> {
     pub fn split_horizontally(&self, weights: &[usize]) -> Vec<Area<'p>> {
         let total_weight: usize = weights.iter().sum();
         let factor = self.size.width / total_weight as f64;
-        let widths = weights.iter().map(|weight| factor * *weight as f64);
+        let widths: Vec<_> = weights
+            .iter()
+            .map(|weight| factor * *weight as f64)
+            .collect();
+        self.split_widths(&widths)
+    }
+
+    /// Splits this area horizontally into areas with the given widths, from left to right.
+    ///
+    /// The areas are placed next to each other, starting at the left border of this area.  The
+    /// widths are not limited to the width of this area.
+    pub fn split_widths(&self, widths: &[Mm]) -> Vec<Area<'p>> {
         let mut offset = Mm(0.0);
         let mut areas = Vec::new();
         for width in widths {
             let mut area = self.clone();
             area.origin.x += offset;
-            area.size.width = width;
+            area.size.width = *width;
             areas.push(area);
-            offset += width;
+            offset += *width;
         }
         areas
     }
 
+    /// Adds a bookmark with the given title and level to the outline of the document.
+    ///
+    /// The bookmark points to the given position, relative to the upper left corner of the area.
+    /// The level starts with 1 for top-level entries.  Bookmarks are ordered by their page and by
+    /// the order in which they have been added.  A bookmark is attached to the last preceding
+    /// bookmark with a lower level.
+    pub fn add_bookmark(&self, title: impl Into<String>, level: usize, position: Position) {
+        self.layer.page.bookmarks.borrow_mut().push(Bookmark {
+            title: title.into(),
+            level: level.max(1),
+            position: self.position(position).0,
+            translations: self.layer.page.active_translations.borrow().clone(),
+        });
+    }
+
+    /// Adds an anchor at the given position and returns it.
+    ///
+    /// The anchor points to the given position, relative to the upper left corner of the area.
+    /// It can be the target of links added with [`add_link`][] anywhere in the document, before
+    /// or after this anchor.  The names of all anchors in a document must be unique.  Otherwise,
+    /// an error is returned when the document is written.
+    ///
+    /// [`add_link`]: #method.add_link
+    pub fn add_anchor(&self, anchor: impl Into<Anchor>, position: Position) -> Anchor {
+        let anchor = anchor.into();
+        self.layer.page.destinations.borrow_mut().push(Destination {
+            anchor: anchor.clone(),
+            position: self.position(position).0,
+            translations: self.layer.page.active_translations.borrow().clone(),
+        });
+        anchor
+    }
+
+    /// Adds a link to the given anchor with the given position and size.
+    ///
+    /// The position is the upper left corner of the clickable rectangle, relative to the upper
+    /// left corner of the area.  The anchor can be added before or after the link, see
+    /// [`add_anchor`][].  If the anchor has not been added when the document is written, an error
+    /// is returned.
+    ///
+    /// [`add_anchor`]: #method.add_anchor
+    pub fn add_link(&self, anchor: impl Into<Anchor>, position: Position, size: Size) {
+        self.push_link(LinkTarget::Anchor(anchor.into()), position, size);
+    }
+
+    /// Adds a link to the given URI, for example a web address, with the given position and size.
+    ///
+    /// The position is the upper left corner of the clickable rectangle, relative to the upper
+    /// left corner of the area.  The URI is not validated.
+    pub fn add_uri_link(&self, uri: impl Into<String>, position: Position, size: Size) {
+        self.push_link(LinkTarget::Uri(uri.into()), position, size);
+    }
+
+    /// Adds a signature form field with the given name, position and size.
+    ///
+    /// The position is the upper left corner of the field, relative to the upper left corner of
+    /// the area.  The names of all signature fields in a document must be unique.  Otherwise, an
+    /// error is returned when the document is written.  If the document is signed, see
+    /// [`Renderer::write_signed`][], the signature is attached to the first signature field.
+    /// The other fields can be signed later with a PDF viewer.
+    ///
+    /// [`Renderer::write_signed`]: struct.Renderer.html#method.write_signed
+    pub fn add_signature_field(&self, name: impl Into<String>, position: Position, size: Size) {
+        self.add_form_field(name, FormField::Signature, position, size);
+    }
+
+    /// Adds an interactive form field with the given name, position and size.
+    ///
+    /// The position is the upper left corner of the field, relative to the upper left corner of
+    /// the area.  The field is only added as an annotation, so its border has to be drawn
+    /// separately.  The names of all form fields in a document must be unique, except for radio
+    /// buttons of the same group.  Otherwise, an error is returned when the document is written.
+    /// Form fields other than signature fields are not supported in PDF/A documents.
+    pub fn add_form_field(
+        &self,
+        name: impl Into<String>,
+        field: FormField,
+        position: Position,
+        size: Size,
+    ) {
+        self.layer
+            .page
+            .form_fields
+            .borrow_mut()
+            .push(FormFieldWidget {
+                name: name.into(),
+                field,
+                position: self.position(position).0,
+                size,
+                translations: self.layer.page.active_translations.borrow().clone(),
+            });
+    }
+
+    fn push_link(&self, target: LinkTarget, position: Position, size: Size) {
+        self.layer.page.links.borrow_mut().push(Link {
+            target,
+            position: self.position(position).0,
+            size,
+            translations: self.layer.page.active_translations.borrow().clone(),
+        });
+    }
+
     /// Inserts an image into the document.
     ///
     /// *Only available if the `images` feature is enabled.*
@@ -601,8 +2139,7 @@ impl<'p> Area<'p> {
     where
         I: IntoIterator<Item = Position>,
     {
-        self.layer.set_outline_thickness(line_style.thickness());
-        self.layer.set_outline_color(line_style.color());
+        self.layer.set_line_style(line_style);
         self.layer
             .add_line_shape(points.into_iter().map(|pos| self.position(pos)));
     }
@@ -659,11 +2196,83 @@ impl<'p> Area<'p> {
             self.position(Position::new(self.size.width, self.size.height)),
             self.position(Position::new(Mm(0.0), self.size.height)),
         ];
-        self.layer
-            .add_line_shape_with_fill_color(points.into_iter());
+        self.layer.add_line_shape_with_fill_color(points);
+    }
+
+    /// Fills the path with the given segments with the given color.
+    ///
+    /// If `even_odd` is true, the even-odd rule is used to determine the inside of the path,
+    /// otherwise the nonzero winding number rule is used.
+    pub fn fill_path(&self, segments: &[PathSegment], color: Color, even_odd: bool) {
+        self.layer.set_fill_color(Some(color));
+        self.add_path(segments, if even_odd { "f*" } else { "f" });
+    }
+
+    /// Draws the outline of the path with the given segments with the given line style.
+    pub fn stroke_path(&self, segments: &[PathSegment], line_style: LineStyle) {
+        self.layer.set_line_style(line_style);
+        self.add_path(segments, "S");
+    }
+
+    /// Adds the given path and paints it with the given PDF operator.
+    fn add_path(&self, segments: &[PathSegment], operator: &str) {
+        let point = |position: Position| {
+            let point = self.layer.transform_shape_position(self.position(position));
+            vec![
+                lopdf::Object::Real(point.x.0),
+                lopdf::Object::Real(point.y.0),
+            ]
+        };
+        let mut operations: Vec<_> = segments
+            .iter()
+            .map(|segment| match *segment {
+                PathSegment::MoveTo(p) => lopdf::content::Operation::new("m", point(p)),
+                PathSegment::LineTo(p) => lopdf::content::Operation::new("l", point(p)),
+                PathSegment::CurveTo(p1, p2, p3) => {
+                    let operands = [p1, p2, p3].iter().flat_map(|p| point(*p)).collect();
+                    lopdf::content::Operation::new("c", operands)
+                }
+                PathSegment::Close => lopdf::content::Operation::new("h", vec![]),
+            })
+            .collect();
+        operations.push(lopdf::content::Operation::new(operator, vec![]));
+        self.layer.page.has_content.set(true);
+        self.layer.add_operations(operations);
+    }
+
+    /// Fills the area with the given color and rounds its corners with the given radius.
+    ///
+    /// The radius is limited to half the width and height of the area, so a large radius can be
+    /// used to draw a pill shape.
+    pub fn fill_rounded(&self, color: Color, radius: impl Into<Mm>) {
+        self.layer.set_fill_color(Some(color));
+        self.layer.add_rounded_rect_with_fill_color(
+            self.position(Position::default()),
+            self.size,
+            radius.into(),
+        );
     }
 }
 
+/// A segment of a path that is drawn with [`Area::fill_path`][] or [`Area::stroke_path`][].
+///
+/// All positions are relative to the upper left corner of the area.
+///
+/// [`Area::fill_path`]: struct.Area.html#method.fill_path
+/// [`Area::stroke_path`]: struct.Area.html#method.stroke_path
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathSegment {
+    /// Starts a new subpath at the given position.
+    MoveTo(Position),
+    /// Draws a straight line from the current position to the given position.
+    LineTo(Position),
+    /// Draws a cubic Bézier curve from the current position to the third position, using the first
+    /// and the second position as control points.
+    CurveTo(Position, Position, Position),
+    /// Closes the current subpath with a straight line to its start.
+    Close,
+}
+
 /// A text section that is drawn on an area of a PDF layer.
 pub struct TextSection<'f, 'p> {
     font_cache: &'f fonts::FontCache,
@@ -671,6 +2280,14 @@ pub struct TextSection<'f, 'p> {
     is_first: bool,
     metrics: fonts::Metrics,
     font: Option<(printpdf::IndirectFontRef, u8)>,
+    baseline_offset: Mm,
+    is_invisible: bool,
+    opacity: f64,
+    word_spacing: Mm,
+    pending_letter_spacing: Mm,
+    line_start: Mm,
+    x: Mm,
+    decorations: Vec<Decoration<'p>>,
 }
 
 impl<'f, 'p> TextSection<'f, 'p> {
@@ -692,10 +2309,20 @@ impl<'f, 'p> TextSection<'f, 'p> {
             is_first: true,
             metrics,
             font: None,
+            baseline_offset: Mm(0.0),
+            is_invisible: false,
+            opacity: 1.0,
+            word_spacing: Mm(0.0),
+            pending_letter_spacing: Mm(0.0),
+            line_start: Mm(0.0),
+            x: Mm(0.0),
+            decorations: Vec::new(),
         })
     }
 
-    fn set_text_cursor(&self, x_offset: Mm) {
+    fn set_text_cursor(&mut self, x_offset: Mm) {
+        self.line_start = x_offset;
+        self.x = x_offset;
         let cursor = self
             .area
             .position(Position::new(x_offset, self.metrics.ascent));
@@ -715,6 +2342,43 @@ impl<'f, 'p> TextSection<'f, 'p> {
         }
     }
 
+    fn set_invisible(&mut self, is_invisible: bool) {
+        if self.is_invisible != is_invisible {
+            self.is_invisible = is_invisible;
+            let mode = if is_invisible {
+                printpdf::TextRenderingMode::Invisible
+            } else {
+                printpdf::TextRenderingMode::Fill
+            };
+            self.area.layer.set_text_rendering_mode(mode);
+        }
+    }
+
+    /// Sets the opacity of the following text relative to the opacity of the area.
+    fn set_opacity(&mut self, opacity: f64) {
+        if self.opacity != opacity {
+            self.opacity = opacity;
+            let opacity = round_opacity(self.area.opacity * opacity);
+            self.area.layer.set_opacity(opacity);
+        }
+    }
+
+    fn set_baseline_offset(&mut self, offset: Mm) {
+        if self.baseline_offset != offset {
+            self.baseline_offset = offset;
+            self.area.layer.set_text_rise(offset);
+        }
+    }
+
+    /// Sets the additional spacing that is added to every space character printed with this text
+    /// section.
+    ///
+    /// This can be used to justify text.  `printpdf` does not support setting the word spacing for
+    /// embedded fonts, so the spacing is implemented by adjusting the glyph positions.
+    pub fn set_word_spacing(&mut self, word_spacing: Mm) {
+        self.word_spacing = word_spacing;
+    }
+
     /// Tries to add a new line and returns `true` if the area was large enough to fit the new
     /// line.
     #[must_use]
@@ -724,16 +2388,29 @@ impl<'f, 'p> TextSection<'f, 'p> {
         } else {
             self.area.layer.add_line_break();
             self.area.add_offset((0, self.metrics.line_height));
+            self.x = self.line_start;
             true
         }
     }
 
     /// Prints the given string with the given style.
     ///
-    /// The font cache for this text section must contain the PDF font for the given style.
+    /// The font cache for this text section must contain the PDF font for the given style.  Soft
+    /// hyphens (U+00AD) are not printed.  Characters that are not supported by the font of the
+    /// style are printed with a fallback font, see [`Style::font_runs`][].
+    ///
+    /// [`Style::font_runs`]: ../style/struct.Style.html#method.font_runs
     pub fn print_str(&mut self, s: impl AsRef<str>, style: Style) -> Result<(), Error> {
-        let font = style.font(self.font_cache);
         let s = s.as_ref();
+        let s = if s.contains(wrap::SOFT_HYPHEN) {
+            std::borrow::Cow::Owned(s.replace(wrap::SOFT_HYPHEN, ""))
+        } else {
+            std::borrow::Cow::Borrowed(s)
+        };
+        let s = s.as_ref();
+        if !s.is_empty() {
+            self.area.layer.page.has_content.set(true);
+        }
 
         // Adjust cursor to remove left bearing of the first character of the first string
         if self.is_first {
@@ -746,46 +2423,1325 @@ impl<'f, 'p> TextSection<'f, 'p> {
         }
         self.is_first = false;
 
-        let positions = font
-            .kerning(self.font_cache, s.chars())
-            .into_iter()
-            // Kerning is measured in 1/1000 em
-            .map(|pos| pos * -1000.0)
-            .map(|pos| pos as i64);
-        let codepoints = if font.is_builtin() {
+        for (font, run) in style.font_runs(self.font_cache, s) {
+            self.print_run(run, font, style)?;
+        }
+        Ok(())
+    }
+
+    /// Prints the given string with the given font and style.
+    ///
+    /// The string must not contain soft hyphens.
+    fn print_run(&mut self, s: &str, font: fonts::Font, style: Style) -> Result<(), Error> {
+        // Positions are measured in 1/1000 em.  The letter spacing is applied before the next
+        // character, so the spacing after the last character of the previous string is pending.
+        let to_em = |mm: Mm| printpdf::Pt::from(mm).0 * 1000.0 / f64::from(style.text_font_size());
+        let word_spacing = to_em(self.word_spacing + style.word_spacing());
+        let letter_spacing = to_em(style.letter_spacing());
+        let pending_letter_spacing = to_em(self.pending_letter_spacing);
+        if !s.is_empty() {
+            self.pending_letter_spacing = style.letter_spacing();
+        }
+        let spacing = |is_first: bool, c: char| {
+            let spacing = if is_first {
+                pending_letter_spacing
+            } else {
+                letter_spacing
+            };
+            if c == ' ' {
+                spacing + word_spacing
+            } else {
+                spacing
+            }
+        };
+        let font_cache = self.font_cache;
+        let kerning_positions = || -> Vec<i64> {
+            font.kerning(font_cache, s.chars())
+                .into_iter()
+                .zip(s.chars())
+                .enumerate()
+                .map(|(i, (pos, c))| f64::from(pos * -1000.0) - spacing(i == 0, c))
+                .map(|pos| pos as i64)
+                .collect()
+        };
+        #[cfg(feature = "shaping")]
+        let shaped = self.shape(s, font, style, spacing);
+        #[cfg(not(feature = "shaping"))]
+        let shaped = None;
+        let (positions, codepoints) = if font.is_builtin() {
             // Built-in fonts always use the Windows-1252 encoding
-            encode_win1252(s)?
+            (kerning_positions(), Codepoints::Bytes(encode_win1252(s)?))
+        } else if let Some((positions, glyph_ids)) = shaped {
+            (positions, Codepoints::GlyphIds(glyph_ids))
         } else {
-            font.glyph_ids(&self.font_cache, s.chars())
+            let glyph_ids = font.glyph_ids(self.font_cache, s.chars());
+            // The glyph ID 0 is reserved for the replacement glyph (.notdef)
+            for (c, _) in s.chars().zip(&glyph_ids).filter(|(_, id)| **id == 0) {
+                self.font_cache.add_missing_glyph(c);
+            }
+            (kerning_positions(), Codepoints::GlyphIds(glyph_ids))
         };
+        if let Codepoints::GlyphIds(glyph_ids) = &codepoints {
+            self.font_cache.add_used_glyphs(font, glyph_ids);
+        }
 
         let font = self
             .font_cache
             .get_pdf_font(font)
             .expect("Could not find PDF font in font cache");
         self.area.layer.set_fill_color(style.color());
-        self.set_font(font, style.font_size());
-
-        self.area
-            .layer
-            .write_positioned_codepoints(positions, codepoints);
+        self.set_font(font, style.text_font_size());
+        let string_metrics = style.font(self.font_cache).metrics(style.text_font_size());
+        let inline_offset = style
+            .inline_alignment()
+            .offset(self.metrics, string_metrics);
+        self.set_baseline_offset(style.baseline_offset() + inline_offset);
+        self.set_invisible(style.is_invisible());
+        self.set_opacity(style.opacity());
+        self.add_decorations(s, style);
+
+        match codepoints {
+            Codepoints::Bytes(bytes) => self.area.layer.write_positioned_bytes(positions, bytes),
+            Codepoints::GlyphIds(glyph_ids) => self
+                .area
+                .layer
+                .write_positioned_codepoints(positions, glyph_ids),
+        }
         Ok(())
     }
+
+    /// Shapes the given string with the given font and returns the glyph positions and IDs, or
+    /// `None` if the font cannot be shaped.
+    ///
+    /// The string is in visual order, see [`bidi::reorder_line`][].  The positions are measured
+    /// in 1/1000 em.  The spacing returned by `spacing` is inserted before every cluster.  PDF
+    /// viewers advance the text cursor by the default advance of the glyphs, so the difference
+    /// to the shaped advance is added to the position of the next glyph.  The difference after
+    /// the last glyph is added to the pending letter spacing.
+    ///
+    /// [`bidi::reorder_line`]: ../bidi/fn.reorder_line.html
+    #[cfg(feature = "shaping")]
+    fn shape(
+        &mut self,
+        s: &str,
+        font: fonts::Font,
+        style: Style,
+        spacing: impl Fn(bool, char) -> f64,
+    ) -> Option<(Vec<i64>, Vec<u16>)> {
+        let text = bidi::logical_order(s);
+        let glyphs = font.shape(self.font_cache, &text)?;
+        let mut positions = Vec::with_capacity(glyphs.len());
+        let mut glyph_ids = Vec::with_capacity(glyphs.len());
+        // The distance between the position of the PDF viewer and the shaped position in em
+        let mut shift = 0.0;
+        let mut last_cluster = None;
+        for (i, glyph) in glyphs.iter().enumerate() {
+            let c = text[glyph.cluster..].chars().next().unwrap_or_default();
+            // The glyph ID 0 is reserved for the replacement glyph (.notdef)
+            if glyph.glyph_id == 0 {
+                self.font_cache.add_missing_glyph(c);
+            }
+            let mut pos = f64::from(shift - glyph.offset) * 1000.0;
+            if last_cluster != Some(glyph.cluster) {
+                pos -= spacing(i == 0, c);
+            }
+            positions.push(pos as i64);
+            glyph_ids.push(glyph.glyph_id);
+            shift = glyph.offset + glyph.default_advance - glyph.advance;
+            last_cluster = Some(glyph.cluster);
+        }
+        let shift = printpdf::Pt(f64::from(shift) * f64::from(style.text_font_size()));
+        self.pending_letter_spacing -= Mm::from(shift);
+        Some((positions, glyph_ids))
+    }
+
+    /// Records the underline and the strikethrough line for the given string and advances the
+    /// horizontal position of the text section.
+    ///
+    /// Paths cannot be drawn inside of a text section, so the lines are drawn when the text
+    /// section is dropped.  Trailing whitespace is not decorated unless it is followed by another
+    /// decorated string.
+    fn add_decorations(&mut self, s: &str, style: Style) {
+        let width = |text: &str| {
+            #[cfg(feature = "shaping")]
+            let text = &bidi::logical_order(text);
+            style.str_width(self.font_cache, text)
+                + self.word_spacing * text.matches(' ').count() as f64
+        };
+        let start = self.x;
+        let end = start + width(s.trim_end());
+        let advance = width(s);
+        self.x += advance;
+        if style.is_invisible() {
+            return;
+        }
+
+        let font = style.font(self.font_cache);
+        let font_size = style.text_font_size();
+        let baseline = self.metrics.ascent - self.baseline_offset;
+        let color = style.color().unwrap_or(Color::Rgb(0, 0, 0));
+        let lines = [
+            (style.is_underline(), font.underline_metrics(font_size)),
+            (
+                style.is_strikethrough(),
+                font.strikethrough_metrics(font_size),
+            ),
+        ];
+        for (_, metrics) in lines.iter().filter(|(is_set, _)| *is_set) {
+            let decoration = Decoration {
+                area: self.area.clone(),
+                y: baseline - metrics.position,
+                start,
+                end,
+                next: self.x,
+                opacity: style.opacity(),
+                line_style: LineStyle::new()
+                    .with_thickness(metrics.thickness)
+                    .with_color(color),
+            };
+            let previous = self
+                .decorations
+                .iter_mut()
+                .rev()
+                .find(|d| d.is_continued_by(&decoration));
+            if let Some(previous) = previous {
+                previous.end = decoration.end;
+                previous.next = decoration.next;
+            } else {
+                self.decorations.push(decoration);
+            }
+        }
+    }
 }
 
 impl<'f, 'p> Drop for TextSection<'f, 'p> {
     fn drop(&mut self) {
+        // The text rise, the rendering mode and the opacity are part of the graphics state, so we
+        // have to reset them for the following text sections.
+        self.set_baseline_offset(Mm(0.0));
+        self.set_invisible(false);
+        self.set_opacity(1.0);
         self.area.layer.end_text_section();
+        for decoration in self.decorations.drain(..) {
+            decoration.draw();
+        }
+    }
+}
+
+/// A line that is drawn below or through a horizontal span of a text section.
+struct Decoration<'p> {
+    area: Area<'p>,
+    y: Mm,
+    start: Mm,
+    end: Mm,
+    next: Mm,
+    opacity: f64,
+    line_style: LineStyle,
+}
+
+impl<'p> Decoration<'p> {
+    /// Returns whether the given decoration directly follows this decoration on the same line and
+    /// can be merged into it.
+    fn is_continued_by(&self, other: &Decoration<'_>) -> bool {
+        self.next == other.start
+            && self.area.origin == other.area.origin
+            && self.y == other.y
+            && self.opacity == other.opacity
+            && self.line_style == other.line_style
+    }
+
+    fn draw(&self) {
+        if self.end > self.start {
+            self.area.with_opacity(self.opacity, |area| {
+                area.draw_line(
+                    vec![
+                        Position::new(self.start, self.y),
+                        Position::new(self.end, self.y),
+                    ],
+                    self.line_style,
+                )
+            });
+        }
+    }
+}
+
+/// Returns the index of the raw operations if the given operation is an operations marker, see
+/// [`OPERATIONS_MARKER`][].
+///
+/// [`OPERATIONS_MARKER`]: constant.OPERATIONS_MARKER.html
+fn get_operations_marker(operation: &lopdf::content::Operation) -> Option<usize> {
+    if operation.operator != "d" {
+        return None;
+    }
+    match operation.operands.as_slice() {
+        [lopdf::Object::Array(idx), lopdf::Object::Integer(OPERATIONS_MARKER)] => {
+            match idx.as_slice() {
+                [lopdf::Object::Integer(idx)] => Some(*idx as usize),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Rounds the given opacity so that we don’t create a new graphics state for every rounding error.
+fn round_opacity(opacity: f64) -> f64 {
+    (opacity * 1000.0).round() / 1000.0
+}
+
+/// Returns the name of the graphics state with the given opacity.
+fn get_opacity_name(opacity: f64) -> String {
+    format!("GenpdfOpacity{}", (opacity * 1000.0).round() as u32)
+}
+
+/// Returns the resource dictionary of the given type for the given page, creating it if required.
+///
+/// If the resource dictionary or the dictionary of the given type are stored directly in their
+/// parent dictionary, they are moved to a separate object so that they can be modified.
+fn get_page_resources_mut<'d>(
+    doc: &'d mut lopdf::Document,
+    page_id: lopdf::ObjectId,
+    resource_type: &[u8],
+) -> Result<&'d mut lopdf::Dictionary, lopdf::Error> {
+    let resources_id = get_or_add_dictionary(doc, page_id, b"Resources")?;
+    let id = get_or_add_dictionary(doc, resources_id, resource_type)?;
+    doc.get_object_mut(id)?.as_dict_mut()
+}
+
+/// Returns the ID of the dictionary stored under the given key in the given dictionary.  If the
+/// dictionary is not stored as a reference, it is moved to a separate object.
+fn get_or_add_dictionary(
+    doc: &mut lopdf::Document,
+    parent_id: lopdf::ObjectId,
+    key: &[u8],
+) -> Result<lopdf::ObjectId, lopdf::Error> {
+    let dict = match doc.get_dictionary(parent_id)?.get(key) {
+        Ok(lopdf::Object::Reference(id)) => return Ok(*id),
+        Ok(object) => object.as_dict()?.clone(),
+        Err(_) => lopdf::Dictionary::new(),
+    };
+    let id = doc.add_object(dict);
+    doc.get_object_mut(parent_id)?
+        .as_dict_mut()?
+        .set(key, lopdf::Object::Reference(id));
+    Ok(id)
+}
+
+/// Returns the bookmarks of the given pages with their positions in user space.
+fn collect_bookmarks(
+    pages: &[Page],
+    page_ids: &[lopdf::ObjectId],
+) -> Vec<(lopdf::ObjectId, UserSpacePosition, Bookmark)> {
+    let mut bookmarks = Vec::new();
+    for (page, page_id) in pages.iter().zip(page_ids.iter().copied()) {
+        for bookmark in page.bookmarks.borrow().iter() {
+            let position = page.translate(bookmark.position, &bookmark.translations);
+            let position = UserSpacePosition::from_page(page, LayerPosition(position));
+            bookmarks.push((page_id, position, bookmark.clone()));
+        }
+    }
+    bookmarks
+}
+
+/// Returns the anchors of the given pages with their positions in user space, or an error if an
+/// anchor is used more than once.
+fn collect_anchors(
+    pages: &[Page],
+    page_ids: &[lopdf::ObjectId],
+) -> Result<collections::BTreeMap<Anchor, (lopdf::ObjectId, UserSpacePosition)>, Error> {
+    let mut anchors = collections::BTreeMap::new();
+    for (page, page_id) in pages.iter().zip(page_ids.iter().copied()) {
+        for destination in page.destinations.borrow().iter() {
+            let position = page.translate(destination.position, &destination.translations);
+            let position = UserSpacePosition::from_page(page, LayerPosition(position));
+            let anchor = destination.anchor.clone();
+            if anchors.insert(anchor, (page_id, position)).is_some() {
+                return Err(Error::new(
+                    format!("Duplicate anchor '{}'", destination.anchor.name()),
+                    ErrorKind::InvalidData,
+                ));
+            }
+        }
+    }
+    Ok(anchors)
+}
+
+/// Adds the signature dictionary for the given signer to the first signature field of the
+/// document, or to a new invisible signature field on the given page if there is no signature
+/// field.
+fn add_signature(
+    doc: &mut lopdf::Document,
+    page_id: lopdf::ObjectId,
+    form_fields: &mut Vec<(String, FormField, lopdf::ObjectId)>,
+    signer: &dyn signature::Signer,
+) -> Result<(), Error> {
+    let signature_field = form_fields
+        .iter()
+        .find(|(_, field, _)| *field == FormField::Signature);
+    let field_id = if let Some((_, _, field_id)) = signature_field {
+        *field_id
+    } else {
+        let rect = [0.0; 4];
+        let widget = form_widget(doc, page_id, "Signature", &FormField::Signature, rect);
+        let field_id = doc.add_object(widget);
+        add_annotations(doc, page_id, vec![lopdf::Object::Reference(field_id)])
+            .context("Failed to add signature field")?;
+        form_fields.push(("Signature".to_owned(), FormField::Signature, field_id));
+        field_id
+    };
+    let date = doc
+        .trailer
+        .get(b"Info")
+        .and_then(lopdf::Object::as_reference)
+        .and_then(|id| doc.get_dictionary(id))
+        .and_then(|info| info.get(b"ModDate"))
+        .ok()
+        .cloned();
+    let signature = signature::signature_dictionary(signer, date);
+    let signature_id = doc.add_object(signature);
+    doc.get_object_mut(field_id)
+        .and_then(lopdf::Object::as_dict_mut)
+        .context("Failed to add signature")?
+        .set("V", lopdf::Object::Reference(signature_id));
+    Ok(())
+}
+
+/// Adds the document information, the XMP metadata and, for PDF/A documents, the output intent
+/// to the given document if necessary.
+fn add_metadata(
+    doc: &mut lopdf::Document,
+    metadata: &Metadata,
+    is_pdf_a: bool,
+) -> Result<(), Error> {
+    if is_pdf_a {
+        pdfa::check_document(doc)?;
+    }
+    if is_pdf_a || !metadata.is_empty() {
+        set_document_info(doc, metadata, is_pdf_a).context("Failed to set document information")?;
+        pdfa::add_xmp_metadata(doc, is_pdf_a).context("Failed to add XMP metadata")?;
+    }
+    if is_pdf_a {
+        pdfa::prepare_document(doc).context("Failed to add PDF/A output intent")?;
+    }
+    Ok(())
+}
+
+/// Encrypts and signs the given document if requested and writes it to the given writer.
+fn save_document(
+    mut doc: lopdf::Document,
+    mut w: impl io::Write,
+    use_object_streams: bool,
+    mut encryptor: Option<Encryptor>,
+    signer: Option<&mut dyn signature::Signer>,
+) -> Result<(), Error> {
+    let mut save = |mut w: &mut dyn io::Write| {
+        if use_object_streams {
+            save_with_object_streams(&mut doc, &mut w, encryptor.as_mut())
+        } else {
+            if let Some(encryptor) = &mut encryptor {
+                encryptor.encrypt_document(&mut doc);
+            }
+            doc.save_to(&mut w).map_err(From::from)
+        }
+    };
+    if let Some(signer) = signer {
+        // The signature covers the complete file, so we have to write it to a buffer first.
+        let mut data = Vec::new();
+        save(&mut data).context("Failed to save document")?;
+        signature::sign(&mut data, signer)?;
+        w.write_all(&data).context("Failed to save document")
+    } else {
+        save(&mut io::BufWriter::new(w)).context("Failed to save document")
+    }
+}
+
+/// Replaces the outline of the given document with a hierarchical outline that contains the
+/// given bookmarks.
+fn add_outline(
+    doc: &mut lopdf::Document,
+    bookmarks: Vec<(lopdf::ObjectId, UserSpacePosition, Bookmark)>,
+) -> Result<(), lopdf::Error> {
+    struct Node {
+        id: lopdf::ObjectId,
+        parent: Option<usize>,
+        children: Vec<usize>,
+        count: i64,
+    }
+
+    let root_id = doc.new_object_id();
+    let mut nodes: Vec<Node> = Vec::with_capacity(bookmarks.len());
+    let mut root_children = Vec::new();
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for (_, _, bookmark) in &bookmarks {
+        while stack
+            .last()
+            .map(|(level, _)| *level >= bookmark.level)
+            .unwrap_or_default()
+        {
+            stack.pop();
+        }
+        let idx = nodes.len();
+        let parent = stack.last().map(|(_, parent)| *parent);
+        if let Some(parent) = parent {
+            nodes[parent].children.push(idx);
+        } else {
+            root_children.push(idx);
+        }
+        for (_, ancestor) in &stack {
+            nodes[*ancestor].count += 1;
+        }
+        nodes.push(Node {
+            id: doc.new_object_id(),
+            parent,
+            children: Vec::new(),
+            count: 0,
+        });
+        stack.push((bookmark.level, idx));
+    }
+
+    let add_links = |dict: &mut lopdf::Dictionary, children: &[usize]| {
+        if let (Some(first), Some(last)) = (children.first(), children.last()) {
+            dict.set("First", lopdf::Object::Reference(nodes[*first].id));
+            dict.set("Last", lopdf::Object::Reference(nodes[*last].id));
+        }
+    };
+
+    let mut root = lopdf::Dictionary::new();
+    root.set("Type", lopdf::Object::Name(b"Outlines".to_vec()));
+    root.set("Count", lopdf::Object::Integer(nodes.len() as i64));
+    add_links(&mut root, &root_children);
+
+    let mut objects = Vec::with_capacity(nodes.len());
+    for (node, (page_id, position, bookmark)) in nodes.iter().zip(&bookmarks) {
+        let siblings = node
+            .parent
+            .map(|parent| nodes[parent].children.as_slice())
+            .unwrap_or(&root_children);
+        let idx = siblings
+            .iter()
+            .position(|idx| nodes[*idx].id == node.id)
+            .expect("Outline node is not a child of its parent");
+
+        let mut dict = lopdf::Dictionary::new();
+        dict.set("Title", encode_text_string(&bookmark.title));
+        let parent_id = node
+            .parent
+            .map(|parent| nodes[parent].id)
+            .unwrap_or(root_id);
+        dict.set("Parent", lopdf::Object::Reference(parent_id));
+        if idx > 0 {
+            let prev = nodes[siblings[idx - 1]].id;
+            dict.set("Prev", lopdf::Object::Reference(prev));
+        }
+        if let Some(next) = siblings.get(idx + 1) {
+            dict.set("Next", lopdf::Object::Reference(nodes[*next].id));
+        }
+        add_links(&mut dict, &node.children);
+        if node.count > 0 {
+            dict.set("Count", lopdf::Object::Integer(node.count));
+        }
+        dict.set(
+            "Dest",
+            lopdf::Object::Array(vec![
+                lopdf::Object::Reference(*page_id),
+                lopdf::Object::Name(b"XYZ".to_vec()),
+                lopdf::Object::Real(printpdf::Pt::from(position.x).0),
+                lopdf::Object::Real(printpdf::Pt::from(position.y).0),
+                lopdf::Object::Null,
+            ]),
+        );
+        objects.push((node.id, dict));
+    }
+
+    doc.objects.insert(root_id, root.into());
+    for (id, dict) in objects {
+        doc.objects.insert(id, dict.into());
+    }
+
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    let catalog = doc.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("Outlines", lopdf::Object::Reference(root_id));
+    catalog.set("PageMode", lopdf::Object::Name(b"UseOutlines".to_vec()));
+    Ok(())
+}
+
+/// Returns an explicit destination that points to the given position on the given page.
+fn get_destination(page_id: lopdf::ObjectId, position: &UserSpacePosition) -> lopdf::Object {
+    lopdf::Object::Array(vec![
+        lopdf::Object::Reference(page_id),
+        lopdf::Object::Name(b"XYZ".to_vec()),
+        lopdf::Object::Real(printpdf::Pt::from(position.x).0),
+        lopdf::Object::Real(printpdf::Pt::from(position.y).0),
+        lopdf::Object::Null,
+    ])
+}
+
+/// Adds the given anchors to the name tree for named destinations in the document catalog.
+fn add_named_destinations(
+    doc: &mut lopdf::Document,
+    anchors: &collections::BTreeMap<Anchor, (lopdf::ObjectId, UserSpacePosition)>,
+) -> Result<(), lopdf::Error> {
+    // The names in a name tree must be sorted, which is guaranteed by the BTreeMap
+    let mut names = Vec::with_capacity(anchors.len() * 2);
+    for (anchor, (page_id, position)) in anchors {
+        names.push(lopdf::Object::string_literal(anchor.name()));
+        names.push(get_destination(*page_id, position));
+    }
+    let mut dests = lopdf::Dictionary::new();
+    dests.set("Names", names);
+    let dests_id = doc.add_object(dests);
+
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    let names_id = get_or_add_dictionary(doc, catalog_id, b"Names")?;
+    doc.get_object_mut(names_id)?
+        .as_dict_mut()?
+        .set("Dests", lopdf::Object::Reference(dests_id));
+    Ok(())
+}
+
+/// Replaces the embedded TrueType fonts of the given document with the given subsets.
+///
+/// The fonts are identified by comparing the content of the `FontFile2` streams with the data of
+/// the subsets.
+fn subset_fonts(doc: &mut lopdf::Document, subsets: &[FontSubset]) -> Result<(), Error> {
+    for object in doc.objects.values_mut() {
+        let stream = match object {
+            lopdf::Object::Stream(stream) if stream.dict.has(b"Length1") => stream,
+            _ => continue,
+        };
+        let data = {
+            let content = if stream.dict.has(b"Filter") {
+                match stream.decompressed_content() {
+                    Ok(content) => std::borrow::Cow::Owned(content),
+                    Err(_) => continue,
+                }
+            } else {
+                std::borrow::Cow::Borrowed(&stream.content)
+            };
+            subsets
+                .iter()
+                .find(|subset| subset.data == *content)
+                .and_then(|subset| fonts::subset::subset_font(&subset.data, &subset.glyph_ids))
+        };
+        if let Some(data) = data {
+            stream.dict.set("Length1", data.len() as i64);
+            stream.set_plain_content(data);
+            stream
+                .compress()
+                .context("Failed to compress font subset")?;
+        }
+    }
+    Ok(())
+}
+
+/// Changes the embedded OpenType fonts with CFF outlines from `FontFile2` to `FontFile3` streams.
+///
+/// `printpdf` embeds all fonts as TrueType fonts.  But fonts with CFF outlines have to be embedded
+/// as `FontFile3` streams with the subtype `OpenType`, and the descendant fonts must have the
+/// subtype `CIDFontType0`.  As the fonts are not CID-keyed, the CIDs are used as glyph IDs.  This
+/// requires PDF 1.6.
+fn embed_cff_fonts(doc: &mut lopdf::Document) {
+    let mut descriptor_ids = collections::BTreeSet::new();
+    let mut stream_ids = Vec::new();
+    for (id, object) in &doc.objects {
+        let font_file = match object {
+            lopdf::Object::Dictionary(dict) if is_name(dict, b"Type", b"FontDescriptor") => {
+                match dict.get(b"FontFile2").and_then(lopdf::Object::as_reference) {
+                    Ok(font_file) => font_file,
+                    Err(_) => continue,
+                }
+            }
+            _ => continue,
+        };
+        let is_cff = doc
+            .get_object(font_file)
+            .and_then(lopdf::Object::as_stream)
+            .map(|stream| {
+                if stream.dict.has(b"Filter") {
+                    stream
+                        .decompressed_content()
+                        .map(|content| fonts::sfnt::is_cff(&content))
+                        .unwrap_or(false)
+                } else {
+                    fonts::sfnt::is_cff(&stream.content)
+                }
+            })
+            .unwrap_or(false);
+        if is_cff {
+            descriptor_ids.insert(*id);
+            stream_ids.push(font_file);
+        }
+    }
+    if descriptor_ids.is_empty() {
+        return;
+    }
+
+    for object in doc.objects.values_mut() {
+        let dict = match object {
+            lopdf::Object::Dictionary(dict) => dict,
+            _ => continue,
+        };
+        let is_cff_font = is_name(dict, b"Subtype", b"CIDFontType2")
+            && dict
+                .get(b"FontDescriptor")
+                .and_then(lopdf::Object::as_reference)
+                .map(|id| descriptor_ids.contains(&id))
+                .unwrap_or(false);
+        if is_cff_font {
+            dict.set("Subtype", lopdf::Object::Name(b"CIDFontType0".to_vec()));
+            dict.remove(b"CIDToGIDMap");
+        }
+    }
+    for id in descriptor_ids {
+        if let Ok(descriptor) = doc.get_object_mut(id).and_then(lopdf::Object::as_dict_mut) {
+            if let Some(font_file) = descriptor.remove(b"FontFile2") {
+                descriptor.set("FontFile3", font_file);
+            }
+        }
+    }
+    for id in stream_ids {
+        if let Ok(lopdf::Object::Stream(stream)) = doc.get_object_mut(id) {
+            stream.dict.remove(b"Length1");
+            stream
+                .dict
+                .set("Subtype", lopdf::Object::Name(b"OpenType".to_vec()));
+        }
+    }
+    if doc.version.as_str() < "1.6" {
+        doc.version = "1.6".to_owned();
+    }
+}
+
+/// Adds the given attachments to the embedded files and to the associated files of the document
+/// catalog.
+fn add_attachments(doc: &mut lopdf::Document, attachments: &[Attachment]) -> Result<(), Error> {
+    let date = doc
+        .trailer
+        .get(b"Info")
+        .and_then(lopdf::Object::as_reference)
+        .and_then(|id| doc.get_dictionary(id))
+        .and_then(|info| info.get(b"ModDate"))
+        .ok()
+        .cloned();
+
+    // The names in a name tree must be sorted
+    let mut names = collections::BTreeMap::new();
+    let mut file_specs = Vec::with_capacity(attachments.len());
+    for attachment in attachments {
+        let mut params = lopdf::Dictionary::new();
+        params.set("Size", attachment.data.len() as i64);
+        if let Some(date) = &date {
+            params.set("ModDate", date.clone());
+        }
+        let mut dict = lopdf::Dictionary::new();
+        dict.set("Type", lopdf::Object::Name(b"EmbeddedFile".to_vec()));
+        dict.set(
+            "Subtype",
+            lopdf::Object::Name(attachment.mime_type.as_bytes().to_vec()),
+        );
+        dict.set("Params", params);
+        let mut stream = lopdf::Stream::new(dict, attachment.data.clone());
+        stream.compress().context("Failed to compress attachment")?;
+        let stream_id = doc.add_object(stream);
+
+        let mut files = lopdf::Dictionary::new();
+        files.set("F", lopdf::Object::Reference(stream_id));
+        files.set("UF", lopdf::Object::Reference(stream_id));
+        let mut file_spec = lopdf::Dictionary::new();
+        file_spec.set("Type", lopdf::Object::Name(b"Filespec".to_vec()));
+        file_spec.set("F", encode_text_string(&attachment.name));
+        file_spec.set("UF", encode_text_string(&attachment.name));
+        file_spec.set("EF", files);
+        if let Some(description) = &attachment.description {
+            file_spec.set("Desc", encode_text_string(description));
+        }
+        file_spec.set(
+            "AFRelationship",
+            lopdf::Object::Name(attachment.relationship.name().as_bytes().to_vec()),
+        );
+        let file_spec_id = doc.add_object(file_spec);
+        file_specs.push(lopdf::Object::Reference(file_spec_id));
+
+        if names
+            .insert(attachment.name.as_str(), file_spec_id)
+            .is_some()
+        {
+            return Err(Error::new(
+                format!("Duplicate attachment '{}'", attachment.name),
+                ErrorKind::InvalidData,
+            ));
+        }
+    }
+
+    let mut embedded_files = lopdf::Dictionary::new();
+    embedded_files.set(
+        "Names",
+        names
+            .into_iter()
+            .flat_map(|(name, id)| vec![encode_text_string(name), lopdf::Object::Reference(id)])
+            .collect::<Vec<_>>(),
+    );
+    let embedded_files_id = doc.add_object(embedded_files);
+
+    let add = |doc: &mut lopdf::Document| -> Result<(), lopdf::Error> {
+        let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+        let names_id = get_or_add_dictionary(doc, catalog_id, b"Names")?;
+        doc.get_object_mut(names_id)?
+            .as_dict_mut()?
+            .set("EmbeddedFiles", lopdf::Object::Reference(embedded_files_id));
+        doc.get_object_mut(catalog_id)?
+            .as_dict_mut()?
+            .set("AF", file_specs);
+        Ok(())
+    };
+    add(doc).context("Failed to add attachments")
+}
+
+/// Sets the `Lang` entry of the document catalog.
+fn set_language(doc: &mut lopdf::Document, language: String) -> Result<(), lopdf::Error> {
+    let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+    let catalog = doc.get_object_mut(catalog_id)?.as_dict_mut()?;
+    catalog.set("Lang", lopdf::Object::string_literal(language));
+    Ok(())
+}
+
+/// The default appearance of variable text in form fields:  Helvetica with an automatic font
+/// size in black.
+const FORM_DEFAULT_APPEARANCE: &str = "/Helv 0 Tf 0 g";
+
+/// Creates the widget annotation for a form field with the given name and rectangle on the given
+/// page.
+///
+/// For radio buttons, the widget annotation does not contain the field entries as they are stored
+/// in the parent field for the group, see [`add_acro_form`][].
+///
+/// [`add_acro_form`]: fn.add_acro_form.html
+fn form_widget(
+    doc: &mut lopdf::Document,
+    page_id: lopdf::ObjectId,
+    name: &str,
+    field: &FormField,
+    rect: [f64; 4],
+) -> lopdf::Dictionary {
+    let (width, height) = (rect[2] - rect[0], rect[3] - rect[1]);
+    let name_object = |name: &str| lopdf::Object::Name(name.as_bytes().to_vec());
+
+    let mut dict = lopdf::Dictionary::new();
+    dict.set("Type", lopdf::Object::Name(b"Annot".to_vec()));
+    dict.set("Subtype", lopdf::Object::Name(b"Widget".to_vec()));
+    dict.set(
+        "Rect",
+        rect.iter().copied().map(From::from).collect::<Vec<_>>(),
+    );
+    // Print
+    dict.set("F", 4);
+    dict.set("P", lopdf::Object::Reference(page_id));
+    if !matches!(field, FormField::RadioButton { .. }) {
+        dict.set("T", encode_text_string(name));
+    }
+    match field {
+        FormField::Text {
+            value,
+            multiline,
+            max_len,
+        } => {
+            dict.set("FT", lopdf::Object::Name(b"Tx".to_vec()));
+            if *multiline {
+                // Multiline
+                dict.set("Ff", 1 << 12);
+            }
+            if let Some(max_len) = max_len {
+                dict.set("MaxLen", *max_len as i64);
+            }
+            dict.set("V", encode_text_string(value));
+            dict.set("DA", lopdf::Object::string_literal(FORM_DEFAULT_APPEARANCE));
+        }
+        FormField::CheckBox { checked } => {
+            let state = if *checked { "Yes" } else { "Off" };
+            dict.set("FT", lopdf::Object::Name(b"Btn".to_vec()));
+            dict.set("V", name_object(state));
+            dict.set("AS", name_object(state));
+            let on = check_mark_appearance(width, height);
+            let appearance = toggle_appearance(doc, "Yes", on, width, height);
+            dict.set("AP", appearance);
+        }
+        FormField::RadioButton { value, checked } => {
+            let state = if *checked { value.as_str() } else { "Off" };
+            dict.set("AS", name_object(state));
+            let on = radio_mark_appearance(width, height);
+            let appearance = toggle_appearance(doc, value, on, width, height);
+            dict.set("AP", appearance);
+        }
+        FormField::Dropdown { options, value } => {
+            dict.set("FT", lopdf::Object::Name(b"Ch".to_vec()));
+            // Combo
+            dict.set("Ff", 1 << 17);
+            dict.set(
+                "Opt",
+                options
+                    .iter()
+                    .map(|option| encode_text_string(option))
+                    .collect::<Vec<_>>(),
+            );
+            if let Some(value) = value {
+                dict.set("V", encode_text_string(value));
+            }
+            dict.set("DA", lopdf::Object::string_literal(FORM_DEFAULT_APPEARANCE));
+        }
+        FormField::Signature => {
+            dict.set("FT", lopdf::Object::Name(b"Sig".to_vec()));
+        }
+    }
+    dict
+}
+
+/// Returns the content stream for the appearance of a checked check box with the given size.
+fn check_mark_appearance(width: f64, height: f64) -> String {
+    let x = |f: f64| width * f;
+    let y = |f: f64| height * f;
+    format!(
+        "q 0 G {} w 1 J 1 j {} {} m {} {} l {} {} l S Q",
+        width.min(height) * 0.12,
+        x(0.2),
+        y(0.5),
+        x(0.42),
+        y(0.25),
+        x(0.8),
+        y(0.78),
+    )
+}
+
+/// Returns the content stream for the appearance of a selected radio button with the given size.
+fn radio_mark_appearance(width: f64, height: f64) -> String {
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let r = width.min(height) * 0.25;
+    // The distance of the Bézier control points from the ends of a quarter circle
+    let k = r * 0.552_284_75;
+    format!(
+        "q 0 g {x1} {cy} m {x1} {y3} {x3} {y1} {cx} {y1} c {x4} {y1} {x0} {y3} {x0} {cy} c \
+         {x0} {y4} {x4} {y0} {cx} {y0} c {x3} {y0} {x1} {y4} {x1} {cy} c f Q",
+        cx = cx,
+        cy = cy,
+        x0 = cx - r,
+        x1 = cx + r,
+        y0 = cy - r,
+        y1 = cy + r,
+        x3 = cx + k,
+        x4 = cx - k,
+        y3 = cy + k,
+        y4 = cy - k,
+    )
+}
+
+/// Creates the appearance dictionary for a check box or a radio button with the given on state
+/// and the given content stream for the on state.
+fn toggle_appearance(
+    doc: &mut lopdf::Document,
+    on_state: &str,
+    on: String,
+    width: f64,
+    height: f64,
+) -> lopdf::Dictionary {
+    let mut stream = |content: Vec<u8>| {
+        let mut dict = lopdf::Dictionary::new();
+        dict.set("Type", lopdf::Object::Name(b"XObject".to_vec()));
+        dict.set("Subtype", lopdf::Object::Name(b"Form".to_vec()));
+        dict.set(
+            "BBox",
+            vec![0.into(), 0.into(), width.into(), height.into()],
+        );
+        lopdf::Object::Reference(doc.add_object(lopdf::Stream::new(dict, content)))
+    };
+    let mut states = lopdf::Dictionary::new();
+    states.set(on_state.as_bytes().to_vec(), stream(on.into_bytes()));
+    states.set("Off", stream(Vec::new()));
+    let mut appearance = lopdf::Dictionary::new();
+    appearance.set("N", states);
+    appearance
+}
+
+/// Appends the given annotations to the annotations of the given page.
+fn add_annotations(
+    doc: &mut lopdf::Document,
+    page_id: lopdf::ObjectId,
+    annotations: Vec<lopdf::Object>,
+) -> Result<(), lopdf::Error> {
+    let page = doc.get_object_mut(page_id)?.as_dict_mut()?;
+    if let Ok(lopdf::Object::Array(existing)) = page.get_mut(b"Annots") {
+        existing.extend(annotations);
+    } else {
+        page.set("Annots", annotations);
+    }
+    Ok(())
+}
+
+/// Adds the interactive form dictionary with the given form fields and the IDs of their widget
+/// annotations to the document catalog.
+///
+/// Radio buttons with the same name are combined into a group with a common parent field.
+fn add_acro_form(
+    doc: &mut lopdf::Document,
+    fields: &[(String, FormField, lopdf::ObjectId)],
+    is_signed: bool,
+) -> Result<(), Error> {
+    // The top-level fields and the widgets of the radio button groups
+    let mut top_level: Vec<(&str, Option<lopdf::ObjectId>)> = Vec::new();
+    let mut groups: collections::HashMap<&str, (Vec<lopdf::ObjectId>, Option<&str>)> =
+        collections::HashMap::new();
+    let duplicate = |name: &str| {
+        Err(Error::new(
+            format!("Duplicate form field '{}'", name),
+            ErrorKind::InvalidData,
+        ))
+    };
+    for (name, field, id) in fields {
+        if let FormField::RadioButton { value, checked } = field {
+            if !groups.contains_key(name.as_str()) {
+                if top_level.iter().any(|(n, _)| n == name) {
+                    return duplicate(name);
+                }
+                top_level.push((name, None));
+            }
+            let (kids, selected) = groups.entry(name).or_default();
+            kids.push(*id);
+            if *checked && selected.is_none() {
+                *selected = Some(value);
+            }
+        } else {
+            if top_level.iter().any(|(n, _)| n == name) {
+                return duplicate(name);
+            }
+            top_level.push((name, Some(*id)));
+        }
+    }
+
+    let mut field_refs = Vec::with_capacity(top_level.len());
+    for (name, id) in top_level {
+        let id = if let Some(id) = id {
+            id
+        } else {
+            let (kids, selected) = &groups[name];
+            let mut parent = lopdf::Dictionary::new();
+            parent.set("FT", lopdf::Object::Name(b"Btn".to_vec()));
+            // NoToggleToOff and Radio
+            parent.set("Ff", (1 << 14) | (1 << 15));
+            parent.set("T", encode_text_string(name));
+            parent.set(
+                "V",
+                lopdf::Object::Name(selected.unwrap_or("Off").as_bytes().to_vec()),
+            );
+            parent.set(
+                "Kids",
+                kids.iter()
+                    .copied()
+                    .map(lopdf::Object::Reference)
+                    .collect::<Vec<_>>(),
+            );
+            let parent_id = doc.add_object(parent);
+            for kid in kids {
+                doc.get_object_mut(*kid)
+                    .and_then(lopdf::Object::as_dict_mut)
+                    .context("Failed to add radio button group")?
+                    .set("Parent", lopdf::Object::Reference(parent_id));
+            }
+            parent_id
+        };
+        field_refs.push(lopdf::Object::Reference(id));
+    }
+
+    let mut form = lopdf::Dictionary::new();
+    form.set("Fields", field_refs);
+    if fields
+        .iter()
+        .any(|(_, field, _)| *field != FormField::Signature)
+    {
+        let mut font = lopdf::Dictionary::new();
+        font.set("Type", lopdf::Object::Name(b"Font".to_vec()));
+        font.set("Subtype", lopdf::Object::Name(b"Type1".to_vec()));
+        font.set("BaseFont", lopdf::Object::Name(b"Helvetica".to_vec()));
+        font.set("Encoding", lopdf::Object::Name(b"WinAnsiEncoding".to_vec()));
+        let mut fonts = lopdf::Dictionary::new();
+        fonts.set("Helv", font);
+        let mut resources = lopdf::Dictionary::new();
+        resources.set("Font", fonts);
+        form.set("DR", resources);
+        form.set("DA", lopdf::Object::string_literal(FORM_DEFAULT_APPEARANCE));
+        // Let the viewer create the appearance of the text fields and dropdown lists
+        form.set("NeedAppearances", true);
+    }
+    if is_signed {
+        // SignaturesExist and AppendOnly
+        form.set("SigFlags", 3);
+    }
+    doc.trailer
+        .get(b"Root")
+        .and_then(lopdf::Object::as_reference)
+        .and_then(|id| doc.get_object_mut(id))
+        .and_then(lopdf::Object::as_dict_mut)
+        .context("Failed to add form fields")?
+        .set("AcroForm", form);
+    Ok(())
+}
+
+/// Returns whether the entry of the given dictionary with the given key is the given name.
+pub(crate) fn is_name(dict: &lopdf::Dictionary, key: &[u8], name: &[u8]) -> bool {
+    dict.get(key)
+        .and_then(lopdf::Object::as_name)
+        .map(|value| value == name)
+        .unwrap_or(false)
+}
+
+/// Resolves the given object if it is a reference.
+pub(crate) fn resolve<'a>(
+    doc: &'a lopdf::Document,
+    object: &'a lopdf::Object,
+) -> &'a lopdf::Object {
+    match object {
+        lopdf::Object::Reference(id) => doc.get_object(*id).unwrap_or(object),
+        _ => object,
     }
 }
 
+/// Writes the given metadata to the document information dictionary.
+///
+/// For PDF/A documents, the PDF/X entries written by printpdf are removed.
+fn set_document_info(
+    doc: &mut lopdf::Document,
+    metadata: &Metadata,
+    is_pdf_a: bool,
+) -> Result<(), lopdf::Error> {
+    let info_id = doc.trailer.get(b"Info")?.as_reference()?;
+    let info = doc.get_object_mut(info_id)?.as_dict_mut()?;
+    if is_pdf_a {
+        info.remove(b"GTS_PDFXVersion");
+        info.remove(b"Trapped");
+    }
+    // printpdf writes the title as UTF-8, but text strings must use PDFDocEncoding or UTF-16.
+    if let Ok(lopdf::Object::String(title, _)) = info.get(b"Title") {
+        let title = String::from_utf8_lossy(title).into_owned();
+        info.set("Title", encode_text_string(&title));
+    }
+    for (key, value) in &metadata.custom {
+        info.set(key.as_bytes().to_vec(), encode_text_string(value));
+    }
+    let entries = [
+        ("Author", &metadata.author),
+        ("Subject", &metadata.subject),
+        ("Keywords", &metadata.keywords),
+        ("Creator", &metadata.creator),
+        ("Producer", &metadata.producer),
+    ];
+    for (key, value) in &entries {
+        if let Some(value) = value {
+            info.set(*key, encode_text_string(value));
+        }
+    }
+    Ok(())
+}
+
+/// A placeholder for the encryptor of the `encryption` module if the `encryption` feature is
+/// disabled.  It cannot be constructed, so documents are never encrypted.
+#[cfg(not(feature = "encryption"))]
+enum Encryptor {}
+
+#[cfg(not(feature = "encryption"))]
+impl Encryptor {
+    fn encrypt_document(&mut self, _doc: &mut lopdf::Document) {
+        match *self {}
+    }
+
+    fn encrypt_object(&mut self, _id: lopdf::ObjectId, _object: &mut lopdf::Object) {
+        match *self {}
+    }
+}
+
+/// An entry of a cross-reference stream.
+enum XrefEntry {
+    /// An object at the given offset with the given generation number.
+    Normal(usize, u16),
+    /// An object in the object stream with the given ID at the given index.
+    Compressed(u32, usize),
+}
+
+/// Writes the given document to the given writer, using object streams and a cross-reference
+/// stream.
+///
+/// `lopdf` does not support writing object streams, so we have to write the document structure
+/// ourselves.  The objects are serialized using `lopdf`.  If an encryptor is given, the objects
+/// that are not packed into object streams and the object streams themselves are encrypted.
+fn save_with_object_streams(
+    doc: &mut lopdf::Document,
+    w: &mut impl io::Write,
+    mut encryptor: Option<&mut Encryptor>,
+) -> Result<(), lopdf::Error> {
+    use std::collections::BTreeMap;
+    use std::io::Write as _;
+
+    fn write_object(
+        buf: &mut Vec<u8>,
+        entries: &mut BTreeMap<u32, XrefEntry>,
+        id: lopdf::ObjectId,
+        object: &lopdf::Object,
+    ) -> Result<(), lopdf::Error> {
+        entries.insert(id.0, XrefEntry::Normal(buf.len(), id.1));
+        writeln!(buf, "{} {} obj", id.0, id.1)?;
+        buf.extend(encode_object(object)?);
+        buf.extend(b"endobj\n");
+        Ok(())
+    }
+
+    let encryption_id = doc
+        .trailer
+        .get(b"Encrypt")
+        .and_then(lopdf::Object::as_reference)
+        .ok();
+    // The signature dictionary must not be packed so that the placeholder for the signature can
+    // be replaced after the document is written.
+    let (packed, unpacked): (Vec<_>, Vec<_>) = doc.objects.iter().partition(|(id, object)| {
+        id.1 == 0
+            && Some(**id) != encryption_id
+            && object.as_stream().is_err()
+            && !object
+                .as_dict()
+                .map(|dict| is_name(dict, b"Type", b"Sig"))
+                .unwrap_or(false)
+    });
+
+    let mut buf = Vec::new();
+    let version = if doc.version.as_str() < "1.5" {
+        "1.5"
+    } else {
+        doc.version.as_str()
+    };
+    writeln!(buf, "%PDF-{}", version)?;
+    // The comment with binary characters indicates that the file contains binary data
+    buf.extend(b"%\xe2\xe3\xcf\xd3\n");
+
+    let mut entries = BTreeMap::new();
+    for (id, object) in unpacked {
+        if let Some(encryptor) = &mut encryptor {
+            let mut object = object.clone();
+            encryptor.encrypt_object(*id, &mut object);
+            write_object(&mut buf, &mut entries, *id, &object)?;
+        } else {
+            write_object(&mut buf, &mut entries, *id, object)?;
+        }
+    }
+
+    let mut next_id = doc.max_id + 1;
+    for chunk in packed.chunks(MAX_OBJECT_STREAM_LEN) {
+        let stream_id = next_id;
+        next_id += 1;
+
+        let mut header = Vec::new();
+        let mut content = Vec::new();
+        for (idx, (id, object)) in chunk.iter().enumerate() {
+            write!(header, "{} {} ", id.0, content.len())?;
+            content.extend(encode_object(object)?);
+            entries.insert(id.0, XrefEntry::Compressed(stream_id, idx));
+        }
+
+        let mut dict = lopdf::Dictionary::new();
+        dict.set("Type", lopdf::Object::Name(b"ObjStm".to_vec()));
+        dict.set("N", chunk.len() as i64);
+        dict.set("First", header.len() as i64);
+        header.extend(content);
+        let mut stream = lopdf::Stream::new(dict, header);
+        stream.compress()?;
+        let mut stream = lopdf::Object::Stream(stream);
+        if let Some(encryptor) = &mut encryptor {
+            encryptor.encrypt_object((stream_id, 0), &mut stream);
+        }
+        write_object(&mut buf, &mut entries, (stream_id, 0), &stream)?;
+    }
+
+    let xref_id = next_id;
+    let xref_offset = buf.len();
+    entries.insert(xref_id, XrefEntry::Normal(xref_offset, 0));
+    let mut content = Vec::new();
+    for id in 0..=xref_id {
+        let (kind, field1, field2) = match entries.get(&id) {
+            Some(XrefEntry::Normal(offset, generation)) => (1, *offset as u32, *generation),
+            Some(XrefEntry::Compressed(stream_id, idx)) => (2, *stream_id, *idx as u16),
+            None if id == 0 => (0, 0, 0xffff),
+            None => (0, 0, 0),
+        };
+        content.push(kind);
+        content.extend(&field1.to_be_bytes());
+        content.extend(&field2.to_be_bytes());
+    }
+
+    let mut dict = doc.trailer.clone();
+    dict.remove(b"Prev");
+    dict.remove(b"XRefStm");
+    dict.set("Type", lopdf::Object::Name(b"XRef".to_vec()));
+    dict.set("Size", i64::from(xref_id + 1));
+    dict.set("W", vec![1.into(), 4.into(), 2.into()]);
+    let mut stream = lopdf::Stream::new(dict, content);
+    stream.compress()?;
+    writeln!(buf, "{} 0 obj", xref_id)?;
+    buf.extend(encode_object(&stream.into())?);
+    write!(buf, "endobj\nstartxref\n{}\n%%EOF", xref_offset)?;
+
+    w.write_all(&buf)?;
+    Ok(())
+}
+
+/// Serializes the given object.
+fn encode_object(object: &lopdf::Object) -> Result<Vec<u8>, lopdf::Error> {
+    // lopdf does not expose its object writer, but we can use it to encode an operation without
+    // an operator.
+    let operation = lopdf::content::Operation::new("", vec![object.clone()]);
+    lopdf::content::Content {
+        operations: vec![operation],
+    }
+    .encode()
+}
+
+/// Encodes the given string as a PDF text string, using UTF-16 if it contains non-ASCII
+/// characters.
+fn encode_text_string(s: &str) -> lopdf::Object {
+    let bytes = if s.is_ascii() {
+        s.as_bytes().to_vec()
+    } else {
+        let mut bytes = vec![0xfe, 0xff];
+        for unit in s.encode_utf16() {
+            bytes.extend_from_slice(&unit.to_be_bytes());
+        }
+        bytes
+    };
+    lopdf::Object::String(bytes, lopdf::StringFormat::Literal)
+}
+
+/// Decodes the given PDF text string, using UTF-16 if it starts with a byte order mark and UTF-8
+/// otherwise.
+///
+/// Strictly speaking, PDF text strings use PDFDocEncoding if they are not UTF-16 encoded, but
+/// printpdf writes UTF-8 strings.
+pub(crate) fn decode_text_string(bytes: &[u8]) -> String {
+    if bytes.starts_with(&[0xfe, 0xff]) {
+        let units: Vec<u16> = bytes[2..]
+            .chunks_exact(2)
+            .map(|chunk| u16::from_be_bytes([chunk[0], chunk[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+}
+
+/// The encoded characters of a string.
+enum Codepoints {
+    /// Single-byte character codes, as used by the built-in fonts.
+    Bytes(Vec<u8>),
+    /// Glyph IDs, as used by embedded fonts.
+    GlyphIds(Vec<u16>),
+}
+
 /// Encodes the given string using the Windows-1252 encoding for use with built-in PDF fonts,
 /// returning an error if it contains unsupported characters.
-fn encode_win1252(s: &str) -> Result<Vec<u16>, Error> {
-    let bytes: Vec<_> = lopdf::Document::encode_text(Some("WinAnsiEncoding"), s)
-        .into_iter()
-        .map(u16::from)
-        .collect();
+fn encode_win1252(s: &str) -> Result<Vec<u8>, Error> {
+    let bytes = lopdf::Document::encode_text(Some("WinAnsiEncoding"), s);
 
     // Windows-1252 is a single-byte encoding, so one byte is one character.
     if bytes.len() != s.chars().count() {
@@ -801,3 +3757,54 @@ fn encode_win1252(s: &str) -> Result<Vec<u16>, Error> {
         Ok(bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{embed_cff_fonts, is_name};
+
+    #[test]
+    fn cff_fonts() {
+        let mut doc = lopdf::Document::with_version("1.3");
+        let add_font = |doc: &mut lopdf::Document, data: &[u8]| {
+            let mut stream = lopdf::Stream::new(lopdf::Dictionary::new(), data.to_vec());
+            stream.dict.set("Length1", data.len() as i64);
+            let font_file = doc.add_object(stream);
+            let mut descriptor = lopdf::Dictionary::new();
+            descriptor.set("Type", lopdf::Object::Name(b"FontDescriptor".to_vec()));
+            descriptor.set("FontFile2", font_file);
+            let descriptor = doc.add_object(descriptor);
+            let mut font = lopdf::Dictionary::new();
+            font.set("Subtype", lopdf::Object::Name(b"CIDFontType2".to_vec()));
+            font.set("CIDToGIDMap", lopdf::Object::Name(b"Identity".to_vec()));
+            font.set("FontDescriptor", descriptor);
+            (doc.add_object(font), descriptor, font_file)
+        };
+        let cff = add_font(&mut doc, b"OTTO\0\0\0\0");
+        let true_type = add_font(&mut doc, b"\0\x01\0\0\0\0\0\0");
+
+        embed_cff_fonts(&mut doc);
+
+        let dict = |id| doc.get_dictionary(id).unwrap();
+        let stream_dict = |id| &doc.get_object(id).unwrap().as_stream().unwrap().dict;
+
+        assert!(is_name(dict(cff.0), b"Subtype", b"CIDFontType0"));
+        assert!(!dict(cff.0).has(b"CIDToGIDMap"));
+        assert!(!dict(cff.1).has(b"FontFile2"));
+        assert_eq!(
+            Some(cff.2),
+            dict(cff.1)
+                .get(b"FontFile3")
+                .and_then(lopdf::Object::as_reference)
+                .ok()
+        );
+        assert!(is_name(stream_dict(cff.2), b"Subtype", b"OpenType"));
+        assert!(!stream_dict(cff.2).has(b"Length1"));
+
+        assert!(is_name(dict(true_type.0), b"Subtype", b"CIDFontType2"));
+        assert!(dict(true_type.0).has(b"CIDToGIDMap"));
+        assert!(dict(true_type.1).has(b"FontFile2"));
+        assert!(stream_dict(true_type.2).has(b"Length1"));
+
+        assert_eq!("1.6", doc.version);
+    }
+}
@@ -0,0 +1,220 @@
+// SPDX-FileCopyrightText: 2021 Robin Krahl <robin.krahl@ireas.org>
+// SPDX-License-Identifier: Apache-2.0 or MIT
+
+//! Digital signatures for generated PDF documents.
+//!
+//! A PDF document can be digitally signed by rendering it with [`Document::render_signed`][].
+//! The document is rendered with a signature dictionary that contains a placeholder for the
+//! signature.  Then the [`Signer`][] is called with the bytes of the rendered document, excluding
+//! the placeholder, and the returned signature is written into the placeholder.  This crate does
+//! not create the signature itself.  Instead, the signer typically creates a detached CMS
+//! (PKCS #7) signature using a cryptography library and the certificate of the signer.
+//!
+//! The signature is attached to the first signature field of the document, see
+//! [`Area::add_signature_field`][] and [`SignatureLine::set_signature_field`][].  If the document
+//! does not contain a signature field, an invisible signature field is added to the first page.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use genpdf::{elements, error, signature};
+//!
+//! struct CmsSigner;
+//!
+//! impl signature::Signer for CmsSigner {
+//!     fn sign(&mut self, data: &[u8]) -> Result<Vec<u8>, error::Error> {
+//!         // create a detached CMS signature for data
+//! #       Ok(Vec::new())
+//!     }
+//! }
+//!
+//! # let font_family = genpdf::fonts::from_files("./fonts", "LiberationSans", None).unwrap();
+//! let mut doc = genpdf::Document::new(font_family);
+//! doc.push(elements::SignatureLine::new(60).with_signature_field("Signature"));
+//! doc.render_signed(std::fs::File::create("signed.pdf").unwrap(), CmsSigner)
+//!     .expect("Failed to render document");
+//! ```
+//!
+//! [`Document::render_signed`]: ../struct.Document.html#method.render_signed
+//! [`Signer`]: trait.Signer.html
+//! [`Area::add_signature_field`]: ../render/struct.Area.html#method.add_signature_field
+//! [`SignatureLine::set_signature_field`]: ../elements/struct.SignatureLine.html#method.set_signature_field
+
+use crate::error::{Error, ErrorKind};
+
+/// The placeholder for the byte range entry that is replaced after the document is written.
+const BYTE_RANGE_PLACEHOLDER: i64 = 9_999_999_999;
+
+/// Additional information about a signature that is written to the signature dictionary.
+///
+/// All fields are optional.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SignatureDetails {
+    /// The name of the person or authority signing the document.
+    pub name: Option<String>,
+    /// The reason for the signing, for example “I agree”.
+    pub reason: Option<String>,
+    /// The location of the signing, for example a city name.
+    pub location: Option<String>,
+    /// Information for contacting the signer, for example a phone number.
+    pub contact_info: Option<String>,
+}
+
+/// Creates a digital signature for a PDF document.
+///
+/// See the [module documentation](index.html) for more information.
+pub trait Signer {
+    /// Signs the given data and returns the DER-encoded signature.
+    ///
+    /// The data consists of all bytes of the rendered PDF file except for the placeholder for the
+    /// signature.  The signature must not be longer than [`max_len`][].
+    ///
+    /// [`max_len`]: #method.max_len
+    fn sign(&mut self, data: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Returns the maximum length of the signature in bytes.
+    ///
+    /// This is the size of the placeholder that is reserved in the rendered document.  The
+    /// default implementation returns 8192, which is enough for most signatures with a
+    /// certificate chain.  If the signature also contains a timestamp or revocation information,
+    /// a larger value might be required.
+    fn max_len(&self) -> usize {
+        8192
+    }
+
+    /// Returns the value of the `SubFilter` entry of the signature dictionary that indicates the
+    /// encoding of the signature.
+    ///
+    /// The default implementation returns `adbe.pkcs7.detached`.  For PAdES signatures, use
+    /// `ETSI.CAdES.detached`.
+    fn sub_filter(&self) -> &str {
+        "adbe.pkcs7.detached"
+    }
+
+    /// Returns additional information about the signature.
+    ///
+    /// The default implementation returns empty details.
+    fn details(&self) -> SignatureDetails {
+        SignatureDetails::default()
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for &mut S {
+    fn sign(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        (**self).sign(data)
+    }
+
+    fn max_len(&self) -> usize {
+        (**self).max_len()
+    }
+
+    fn sub_filter(&self) -> &str {
+        (**self).sub_filter()
+    }
+
+    fn details(&self) -> SignatureDetails {
+        (**self).details()
+    }
+}
+
+/// Creates the signature dictionary with the placeholders for the byte range and the signature.
+pub(crate) fn signature_dictionary(
+    signer: &dyn Signer,
+    date: Option<lopdf::Object>,
+) -> lopdf::Dictionary {
+    let mut dict = lopdf::Dictionary::new();
+    dict.set("Type", lopdf::Object::Name(b"Sig".to_vec()));
+    dict.set("Filter", lopdf::Object::Name(b"Adobe.PPKLite".to_vec()));
+    dict.set(
+        "SubFilter",
+        lopdf::Object::Name(signer.sub_filter().as_bytes().to_vec()),
+    );
+    dict.set(
+        "ByteRange",
+        vec![
+            0.into(),
+            BYTE_RANGE_PLACEHOLDER.into(),
+            BYTE_RANGE_PLACEHOLDER.into(),
+            BYTE_RANGE_PLACEHOLDER.into(),
+        ],
+    );
+    dict.set(
+        "Contents",
+        lopdf::Object::String(vec![0; signer.max_len()], lopdf::StringFormat::Hexadecimal),
+    );
+    if let Some(date) = date {
+        dict.set("M", date);
+    }
+    let details = signer.details();
+    let entries = [
+        ("Name", details.name),
+        ("Reason", details.reason),
+        ("Location", details.location),
+        ("ContactInfo", details.contact_info),
+    ];
+    for (key, value) in entries.iter() {
+        if let Some(value) = value {
+            dict.set(*key, lopdf::Object::string_literal(value.as_str()));
+        }
+    }
+    dict
+}
+
+/// Fills in the byte range and the signature for the signature dictionary created by
+/// [`signature_dictionary`][] in the given rendered document.
+///
+/// [`signature_dictionary`]: fn.signature_dictionary.html
+pub(crate) fn sign(data: &mut [u8], signer: &mut dyn Signer) -> Result<(), Error> {
+    let placeholder = format!("[0 {0} {0} {0}]", BYTE_RANGE_PLACEHOLDER);
+    let byte_range_start = find(data, placeholder.as_bytes())
+        .ok_or_else(|| internal_error("Could not find the byte range placeholder"))?;
+    let mut contents = vec![b'<'];
+    contents.resize(1 + 2 * signer.max_len(), b'0');
+    contents.push(b'>');
+    let contents_start = find(data, &contents)
+        .ok_or_else(|| internal_error("Could not find the signature placeholder"))?;
+    let contents_end = contents_start + contents.len();
+
+    let byte_range = format!(
+        "[0 {} {} {}]",
+        contents_start,
+        contents_end,
+        data.len() - contents_end
+    );
+    if byte_range.len() > placeholder.len() {
+        return Err(internal_error("The document is too large for signing"));
+    }
+    let mut byte_range = byte_range.into_bytes();
+    byte_range.resize(placeholder.len(), b' ');
+    data[byte_range_start..byte_range_start + placeholder.len()].copy_from_slice(&byte_range);
+
+    let mut signed_data = data[..contents_start].to_vec();
+    signed_data.extend(&data[contents_end..]);
+    let signature = signer.sign(&signed_data)?;
+    if signature.len() > signer.max_len() {
+        return Err(Error::new(
+            format!(
+                "The signature is longer than the maximum length ({} > {} bytes)",
+                signature.len(),
+                signer.max_len()
+            ),
+            ErrorKind::InvalidData,
+        ));
+    }
+    let hex = signature
+        .iter()
+        .flat_map(|byte| format!("{:02X}", byte).into_bytes());
+    for (target, byte) in data[contents_start + 1..].iter_mut().zip(hex) {
+        *target = byte;
+    }
+    Ok(())
+}
+
+fn find(data: &[u8], needle: &[u8]) -> Option<usize> {
+    data.windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn internal_error(msg: &str) -> Error {
+    Error::new(msg, ErrorKind::Internal)
+}
@@ -8,7 +8,8 @@
 //! a [`Cow<'_, str>`][] with a [`Style`][] annotation.
 //!
 //! A [`Style`][] is a combination of a [`FontFamily`][], a font size, a line spacing factor, a
-//! [`Color`][] and a combination of [`Effect`][]s (bold or italic).
+//! [`Color`][] and a combination of [`Effect`][]s (bold, italic, superscript, subscript, underline
+//! or strikethrough).
 //!
 //! # Example
 //!
@@ -33,8 +34,10 @@
 use std::borrow;
 use std::iter;
 
+use crate::error::{Error, ErrorKind};
 use crate::fonts;
-use crate::Mm;
+use crate::wrap;
+use crate::{Alignment, Mm};
 
 /// A color, represented by RGB, CMYK or Greyscale values.
 ///
@@ -80,29 +83,140 @@ impl From<Color> for printpdf::Color {
     }
 }
 
-/// A text effect (bold or italic).
+/// A text effect (bold, italic, superscript, subscript, underline or strikethrough).
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Effect {
     /// Bold text.
     Bold,
     /// Italic text.
     Italic,
+    /// Superscript text, i. e. text with a reduced font size that is raised above the baseline.
+    Superscript,
+    /// Subscript text, i. e. text with a reduced font size that is lowered below the baseline.
+    Subscript,
+    /// Underlined text.
+    Underline,
+    /// Struck-through text.
+    Strikethrough,
 }
 
+/// The wrapping mode for text, see [`Style::set_wrap`][].
+///
+/// [`Style::set_wrap`]: struct.Style.html#method.set_wrap
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wrap {
+    /// The text is wrapped into multiple lines (default).
+    Words,
+    /// The text is printed on a single line and all text that does not fit into the line is
+    /// omitted.
+    Clip,
+    /// The text is printed on a single line and all text that does not fit into the line is
+    /// replaced with an ellipsis.
+    Ellipsis,
+}
+
+impl Default for Wrap {
+    fn default() -> Wrap {
+        Wrap::Words
+    }
+}
+
+/// The vertical alignment of a string within its line, see [`Style::set_inline_alignment`][].
+///
+/// This is only relevant for lines that contain strings with different font sizes.  The line
+/// height is always determined by the largest string in the line.
+///
+/// [`Style::set_inline_alignment`]: struct.Style.html#method.set_inline_alignment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InlineAlignment {
+    /// The string is printed on the baseline of the line (default).
+    Baseline,
+    /// The top of the string is aligned with the top of the line.
+    Top,
+    /// The string is vertically centered within the line.
+    Middle,
+    /// The bottom of the string is aligned with the bottom of the line.
+    Bottom,
+}
+
+impl Default for InlineAlignment {
+    fn default() -> InlineAlignment {
+        InlineAlignment::Baseline
+    }
+}
+
+impl InlineAlignment {
+    /// Returns the offset of the baseline of a string with the given metrics, measured upwards
+    /// from the baseline of a line with the given metrics.
+    pub(crate) fn offset(&self, line: fonts::Metrics, string: fonts::Metrics) -> Mm {
+        let descent = string.glyph_height - string.ascent;
+        let baseline = match self {
+            InlineAlignment::Baseline => return Mm(0.0),
+            InlineAlignment::Top => string.ascent,
+            InlineAlignment::Middle => {
+                line.glyph_height / 2.0 + string.ascent - string.glyph_height / 2.0
+            }
+            InlineAlignment::Bottom => line.glyph_height - descent,
+        };
+        line.ascent - baseline
+    }
+}
+
+/// The font size of superscript and subscript text relative to the font size of the surrounding
+/// text.
+pub(crate) const SUPERSCRIPT_SIZE: f64 = 0.6;
+/// The offset of superscript text relative to the font size of the surrounding text.
+const SUPERSCRIPT_OFFSET: f64 = 0.33;
+/// The offset of subscript text relative to the font size of the surrounding text.
+const SUBSCRIPT_OFFSET: f64 = 0.15;
+
 /// A style annotation for a string.
 ///
 /// The annotation consists of:
 /// - a font family, see [`FontFamily`][] (defaults to the [`FontCache`][] default)
 /// - a font size in points (defaults to 12)
-/// - a line spacing factor, with 1 meaning single line spacing (defaults to 1)
+/// - a line spacing factor, with 1 meaning single line spacing (defaults to 1), or a fixed
+///   leading, i. e. the distance between the baselines of consecutive lines, see
+///   [`set_leading`][]
+/// - an additional spacing between letters and between words, see [`set_letter_spacing`][] and
+///   [`set_word_spacing`][] (defaults to zero)
 /// - an outline color, see [`Color`][] (defaults to black)
+/// - an opacity, see [`set_opacity`][] (defaults to opaque)
+/// - a background color that highlights the text, see [`set_background`][] (defaults to none)
 /// - a combination of text effects, see [`Effect`][] (defaults to none)
+/// - a wrapping mode for paragraphs, see [`Wrap`][] (defaults to [`Wrap::Words`][])
+/// - an alignment for paragraphs, see [`Alignment`][] (defaults to [`Alignment::Left`][])
+/// - a vertical alignment within the line, see [`InlineAlignment`][] (defaults to
+///   [`InlineAlignment::Baseline`][])
+///
+/// If the superscript or the subscript effect is set, the text is printed with a reduced font size
+/// and raised above or lowered below the baseline.  The font size of the style is still the font
+/// size of the surrounding text.  Only one of these effects can be set at the same time.
+///
+/// If the underline or the strikethrough effect is set, a line is drawn below or through the text.
+/// The position and thickness of the line are taken from the font metrics, and the line has the
+/// color of the text.
+///
+/// If the style is invisible, the text is not drawn but can still be selected and searched in PDF
+/// viewers, see [`set_invisible`][].
 ///
 /// All properties are optional.  If they are not set, they can be inferred from parent styles or
 /// from the defaults.
 ///
 /// [`Color`]: enum.Color.html
 /// [`Effect`]: enum.Effect.html
+/// [`set_background`]: #method.set_background
+/// [`set_invisible`]: #method.set_invisible
+/// [`set_opacity`]: #method.set_opacity
+/// [`set_leading`]: #method.set_leading
+/// [`set_letter_spacing`]: #method.set_letter_spacing
+/// [`set_word_spacing`]: #method.set_word_spacing
+/// [`Wrap`]: enum.Wrap.html
+/// [`Wrap::Words`]: enum.Wrap.html#variant.Words
+/// [`Alignment`]: ../enum.Alignment.html
+/// [`Alignment::Left`]: ../enum.Alignment.html#variant.Left
+/// [`InlineAlignment`]: enum.InlineAlignment.html
+/// [`InlineAlignment::Baseline`]: enum.InlineAlignment.html#variant.Baseline
 /// [`FontFamily`]: ../fonts/struct.FontFamily.html
 /// [`FontCache`]: ../fonts/struct.FontCache.html
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -110,9 +224,22 @@ pub struct Style {
     font_family: Option<fonts::FontFamily<fonts::Font>>,
     font_size: Option<u8>,
     line_spacing: Option<f64>,
+    leading: Option<Mm>,
+    letter_spacing: Option<Mm>,
+    word_spacing: Option<Mm>,
     color: Option<Color>,
+    opacity: Option<f64>,
+    background: Option<Color>,
     is_bold: bool,
     is_italic: bool,
+    is_superscript: bool,
+    is_subscript: bool,
+    is_underline: bool,
+    is_strikethrough: bool,
+    is_invisible: bool,
+    wrap: Option<Wrap>,
+    inline_alignment: Option<InlineAlignment>,
+    alignment: Option<Alignment>,
 }
 
 impl Style {
@@ -133,12 +260,54 @@ impl Style {
         if let Some(color) = style.color {
             self.color = Some(color);
         }
+        if let Some(opacity) = style.opacity {
+            self.opacity = Some(opacity);
+        }
+        if let Some(background) = style.background {
+            self.background = Some(background);
+        }
+        if let Some(line_spacing) = style.line_spacing {
+            self.set_line_spacing(line_spacing);
+        }
+        if let Some(leading) = style.leading {
+            self.set_leading(leading);
+        }
+        if let Some(letter_spacing) = style.letter_spacing {
+            self.letter_spacing = Some(letter_spacing);
+        }
+        if let Some(word_spacing) = style.word_spacing {
+            self.word_spacing = Some(word_spacing);
+        }
         if style.is_bold {
             self.is_bold = true;
         }
         if style.is_italic {
             self.is_italic = true;
         }
+        if style.is_superscript {
+            self.set_superscript();
+        }
+        if style.is_subscript {
+            self.set_subscript();
+        }
+        if style.is_underline {
+            self.is_underline = true;
+        }
+        if style.is_strikethrough {
+            self.is_strikethrough = true;
+        }
+        if style.is_invisible {
+            self.is_invisible = true;
+        }
+        if let Some(wrap) = style.wrap {
+            self.wrap = Some(wrap);
+        }
+        if let Some(inline_alignment) = style.inline_alignment {
+            self.inline_alignment = Some(inline_alignment);
+        }
+        if let Some(alignment) = style.alignment {
+            self.alignment = Some(alignment);
+        }
     }
 
     /// Combines this style and the given style and returns the result.
@@ -157,6 +326,17 @@ impl Style {
         self.color
     }
 
+    /// Returns the opacity for this style, from 0 (transparent) to 1 (opaque), or 1 if no opacity
+    /// is set.
+    pub fn opacity(&self) -> f64 {
+        self.opacity.unwrap_or(1.0)
+    }
+
+    /// Returns the background color for this style, if set.
+    pub fn background(&self) -> Option<Color> {
+        self.background
+    }
+
     /// Returns whether the bold text effect is set.
     pub fn is_bold(&self) -> bool {
         self.is_bold
@@ -167,16 +347,114 @@ impl Style {
         self.is_italic
     }
 
+    /// Returns whether the superscript text effect is set.
+    pub fn is_superscript(&self) -> bool {
+        self.is_superscript
+    }
+
+    /// Returns whether the subscript text effect is set.
+    pub fn is_subscript(&self) -> bool {
+        self.is_subscript
+    }
+
+    /// Returns whether the underline text effect is set.
+    pub fn is_underline(&self) -> bool {
+        self.is_underline
+    }
+
+    /// Returns whether the strikethrough text effect is set.
+    pub fn is_strikethrough(&self) -> bool {
+        self.is_strikethrough
+    }
+
+    /// Returns whether text with this style is invisible.
+    pub fn is_invisible(&self) -> bool {
+        self.is_invisible
+    }
+
     /// Returns the font size for this style in points, or 12 if no font size is set.
     pub fn font_size(&self) -> u8 {
         self.font_size.unwrap_or(12)
     }
 
+    /// Returns the font size in points that is used to print text with this style.
+    ///
+    /// This is the font size of the style, reduced for superscript and subscript text.
+    pub(crate) fn text_font_size(&self) -> u8 {
+        if self.is_superscript || self.is_subscript {
+            (f64::from(self.font_size()) * SUPERSCRIPT_SIZE)
+                .round()
+                .max(1.0) as u8
+        } else {
+            self.font_size()
+        }
+    }
+
+    /// Returns the offset of the baseline for text with this style, measured upwards from the
+    /// baseline of the surrounding text.
+    pub(crate) fn baseline_offset(&self) -> Mm {
+        if self.is_superscript {
+            Mm::from(printpdf::Pt(
+                f64::from(self.font_size()) * SUPERSCRIPT_OFFSET,
+            ))
+        } else if self.is_subscript {
+            Mm::from(printpdf::Pt(
+                f64::from(self.font_size()) * -SUBSCRIPT_OFFSET,
+            ))
+        } else {
+            Mm(0.0)
+        }
+    }
+
+    /// Returns the wrapping mode for this style, or [`Wrap::Words`][] if no wrapping mode is set.
+    ///
+    /// [`Wrap::Words`]: enum.Wrap.html#variant.Words
+    pub fn wrap(&self) -> Wrap {
+        self.wrap.unwrap_or_default()
+    }
+
+    /// Returns the inline alignment for this style, or [`InlineAlignment::Baseline`][] if no
+    /// inline alignment is set.
+    ///
+    /// [`InlineAlignment::Baseline`]: enum.InlineAlignment.html#variant.Baseline
+    pub fn inline_alignment(&self) -> InlineAlignment {
+        self.inline_alignment.unwrap_or_default()
+    }
+
+    /// Returns the alignment for this style, or [`Alignment::Left`][] if no alignment is set.
+    ///
+    /// [`Alignment::Left`]: ../enum.Alignment.html#variant.Left
+    pub fn alignment(&self) -> Alignment {
+        self.alignment.unwrap_or_default()
+    }
+
+    /// Returns the alignment for this style if an alignment is set.
+    pub(crate) fn explicit_alignment(&self) -> Option<Alignment> {
+        self.alignment
+    }
+
     /// Returns the line spacing factor for this style, or 1 if no line spacing factor is set.
     pub fn line_spacing(&self) -> f64 {
         self.line_spacing.unwrap_or(1.0)
     }
 
+    /// Returns the leading for this style, if set.
+    pub fn leading(&self) -> Option<Mm> {
+        self.leading
+    }
+
+    /// Returns the additional spacing between letters for this style, or zero if no letter
+    /// spacing is set.
+    pub fn letter_spacing(&self) -> Mm {
+        self.letter_spacing.unwrap_or_default()
+    }
+
+    /// Returns the additional spacing between words for this style, or zero if no word spacing is
+    /// set.
+    pub fn word_spacing(&self) -> Mm {
+        self.word_spacing.unwrap_or_default()
+    }
+
     /// Sets the bold effect for this style.
     pub fn set_bold(&mut self) {
         self.is_bold = true;
@@ -199,6 +477,73 @@ impl Style {
         self
     }
 
+    /// Makes text with this style invisible.
+    ///
+    /// Invisible text is printed with the text rendering mode 3, so it is not drawn, but it can
+    /// still be selected, searched and copied in PDF viewers.  This can be used to place an OCR
+    /// text layer on top of a scanned image.  Invisible text still takes up space like visible
+    /// text, so you have to choose the font size and position so that the text roughly matches the
+    /// image.
+    pub fn set_invisible(&mut self) {
+        self.is_invisible = true;
+    }
+
+    /// Makes text with this style invisible and returns the style.
+    pub fn invisible(mut self) -> Style {
+        self.set_invisible();
+        self
+    }
+
+    /// Sets the superscript effect for this style.
+    ///
+    /// This removes the subscript effect.
+    pub fn set_superscript(&mut self) {
+        self.is_superscript = true;
+        self.is_subscript = false;
+    }
+
+    /// Sets the superscript effect for this style and returns it.
+    pub fn superscript(mut self) -> Style {
+        self.set_superscript();
+        self
+    }
+
+    /// Sets the subscript effect for this style.
+    ///
+    /// This removes the superscript effect.
+    pub fn set_subscript(&mut self) {
+        self.is_subscript = true;
+        self.is_superscript = false;
+    }
+
+    /// Sets the subscript effect for this style and returns it.
+    pub fn subscript(mut self) -> Style {
+        self.set_subscript();
+        self
+    }
+
+    /// Sets the underline effect for this style.
+    pub fn set_underline(&mut self) {
+        self.is_underline = true;
+    }
+
+    /// Sets the underline effect for this style and returns it.
+    pub fn underline(mut self) -> Style {
+        self.set_underline();
+        self
+    }
+
+    /// Sets the strikethrough effect for this style.
+    pub fn set_strikethrough(&mut self) {
+        self.is_strikethrough = true;
+    }
+
+    /// Sets the strikethrough effect for this style and returns it.
+    pub fn strikethrough(mut self) -> Style {
+        self.set_strikethrough();
+        self
+    }
+
     /// Sets the font family for this style.
     pub fn set_font_family(&mut self, font_family: fonts::FontFamily<fonts::Font>) {
         self.font_family = Some(font_family);
@@ -211,8 +556,13 @@ impl Style {
     }
 
     /// Sets the line spacing factor for this style.
+    ///
+    /// This removes the leading set with [`set_leading`][].
+    ///
+    /// [`set_leading`]: #method.set_leading
     pub fn set_line_spacing(&mut self, line_spacing: f64) {
         self.line_spacing = Some(line_spacing);
+        self.leading = None;
     }
 
     /// Sets the line spacing factor for this style and returns it.
@@ -221,6 +571,51 @@ impl Style {
         self
     }
 
+    /// Sets the leading for this style, i. e. the fixed distance between the baselines of
+    /// consecutive lines.
+    ///
+    /// The leading replaces the line height of the font, so the line spacing factor set with
+    /// [`set_line_spacing`][] is removed.
+    ///
+    /// [`set_line_spacing`]: #method.set_line_spacing
+    pub fn set_leading(&mut self, leading: impl Into<Mm>) {
+        self.leading = Some(leading.into());
+        self.line_spacing = None;
+    }
+
+    /// Sets the leading for this style and returns it.
+    pub fn with_leading(mut self, leading: impl Into<Mm>) -> Style {
+        self.set_leading(leading);
+        self
+    }
+
+    /// Sets the additional spacing between letters for this style.
+    ///
+    /// The spacing is added after every character, so a positive value tracks out the text and a
+    /// negative value condenses it.
+    pub fn set_letter_spacing(&mut self, letter_spacing: impl Into<Mm>) {
+        self.letter_spacing = Some(letter_spacing.into());
+    }
+
+    /// Sets the additional spacing between letters for this style and returns it.
+    pub fn with_letter_spacing(mut self, letter_spacing: impl Into<Mm>) -> Style {
+        self.set_letter_spacing(letter_spacing);
+        self
+    }
+
+    /// Sets the additional spacing between words for this style.
+    ///
+    /// The spacing is added to every space character, in addition to the letter spacing.
+    pub fn set_word_spacing(&mut self, word_spacing: impl Into<Mm>) {
+        self.word_spacing = Some(word_spacing.into());
+    }
+
+    /// Sets the additional spacing between words for this style and returns it.
+    pub fn with_word_spacing(mut self, word_spacing: impl Into<Mm>) -> Style {
+        self.set_word_spacing(word_spacing);
+        self
+    }
+
     /// Sets the font size in points for this style.
     pub fn set_font_size(&mut self, font_size: u8) {
         self.font_size = Some(font_size);
@@ -232,6 +627,56 @@ impl Style {
         self
     }
 
+    /// Sets the wrapping mode for this style.
+    ///
+    /// The wrapping mode determines whether a [`Paragraph`][] is wrapped into multiple lines or
+    /// printed on a single line.  It is applied to the complete paragraph, so it should be set for
+    /// the paragraph or one of its parent elements, for example a table cell or column.
+    ///
+    /// [`Paragraph`]: ../elements/struct.Paragraph.html
+    pub fn set_wrap(&mut self, wrap: Wrap) {
+        self.wrap = Some(wrap);
+    }
+
+    /// Sets the wrapping mode for this style and returns it.
+    pub fn with_wrap(mut self, wrap: Wrap) -> Style {
+        self.set_wrap(wrap);
+        self
+    }
+
+    /// Sets the vertical alignment of strings with this style within their line.
+    ///
+    /// This can be used to align small strings with the top, middle or bottom of a line that
+    /// contains larger strings, for example icons printed with a large font size.
+    pub fn set_inline_alignment(&mut self, inline_alignment: InlineAlignment) {
+        self.inline_alignment = Some(inline_alignment);
+    }
+
+    /// Sets the vertical alignment of strings with this style within their line and returns the
+    /// style.
+    pub fn with_inline_alignment(mut self, inline_alignment: InlineAlignment) -> Style {
+        self.set_inline_alignment(inline_alignment);
+        self
+    }
+
+    /// Sets the alignment for this style.
+    ///
+    /// The alignment is used by [`Paragraph`][] and [`Heading`][] elements that don’t have an
+    /// alignment of their own.  It is applied to the complete paragraph, so it should be set for
+    /// the paragraph or one of its parent elements.
+    ///
+    /// [`Paragraph`]: ../elements/struct.Paragraph.html
+    /// [`Heading`]: ../elements/struct.Heading.html
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = Some(alignment);
+    }
+
+    /// Sets the alignment for this style and returns it.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Style {
+        self.set_alignment(alignment);
+        self
+    }
+
     /// Sets the outline color for this style.
     pub fn set_color(&mut self, color: Color) {
         self.color = Some(color);
@@ -243,6 +688,39 @@ impl Style {
         self
     }
 
+    /// Sets the opacity for this style, from 0 (transparent) to 1 (opaque).
+    ///
+    /// The opacity is clamped to this range and applies to the text and its underline and
+    /// strikethrough lines.  It is multiplied with the opacity of the area the text is printed on,
+    /// see [`Area::with_opacity`][].
+    ///
+    /// [`Area::with_opacity`]: ../render/struct.Area.html#method.with_opacity
+    pub fn set_opacity(&mut self, opacity: f64) {
+        self.opacity = Some(opacity.max(0.0).min(1.0));
+    }
+
+    /// Sets the opacity for this style and returns it.
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.set_opacity(opacity);
+        self
+    }
+
+    /// Sets the background color for this style.
+    ///
+    /// The background is drawn as a rectangle with the glyph height of the line behind the text,
+    /// like a highlighter.  Paragraphs draw a rectangle for every line of a wrapped string, and
+    /// trailing whitespace is only highlighted if it is followed by text with the same
+    /// background color.
+    pub fn set_background(&mut self, color: Color) {
+        self.background = Some(color);
+    }
+
+    /// Sets the background color for this style and returns it.
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.set_background(color);
+        self
+    }
+
     /// Calculates the width of the given character with this style using the data in the given
     /// font cache.
     ///
@@ -250,8 +728,14 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn char_width(&self, font_cache: &fonts::FontCache, c: char) -> Mm {
-        self.font(font_cache)
-            .char_width(font_cache, c, self.font_size())
+        let width =
+            self.font_for_char(font_cache, c)
+                .char_width(font_cache, c, self.text_font_size());
+        match c {
+            wrap::SOFT_HYPHEN => width,
+            ' ' => width + self.letter_spacing() + self.word_spacing(),
+            _ => width + self.letter_spacing(),
+        }
     }
 
     /// Returns the width of the empty space between the origin of the glyph bounding
@@ -261,8 +745,11 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn char_left_side_bearing(&self, font_cache: &fonts::FontCache, c: char) -> Mm {
-        self.font(font_cache)
-            .char_left_side_bearing(font_cache, c, self.font_size())
+        self.font_for_char(font_cache, c).char_left_side_bearing(
+            font_cache,
+            c,
+            self.text_font_size(),
+        )
     }
 
     /// Calculates the width of the given string with this style using the data in the given font
@@ -272,8 +759,14 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn str_width(&self, font_cache: &fonts::FontCache, s: &str) -> Mm {
-        let font = self.font(font_cache);
-        font.str_width(font_cache, s, self.font_size())
+        let width: Mm = self
+            .font_runs(font_cache, s)
+            .into_iter()
+            .map(|(font, run)| font.str_width(font_cache, run, self.text_font_size()))
+            .sum();
+        let letters = s.chars().filter(|c| *c != wrap::SOFT_HYPHEN).count();
+        let spaces = s.chars().filter(|c| *c == ' ').count();
+        width + self.letter_spacing() * letters as f64 + self.word_spacing() * spaces as f64
     }
 
     /// Returns the font family for this style or the default font family using the given font
@@ -296,6 +789,67 @@ impl Style {
         self.font_family(font_cache).get(*self)
     }
 
+    /// Returns the font for the given character with this style using the given font cache.
+    ///
+    /// If the font of this style does not contain a glyph for the character, a fallback font is
+    /// used, see [`FontCache::font_for_char`][].
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    /// [`FontCache::font_for_char`]: ../fonts/struct.FontCache.html#method.font_for_char
+    pub fn font_for_char(&self, font_cache: &fonts::FontCache, c: char) -> fonts::Font {
+        font_cache.font_for_char(self.font(font_cache), *self, c)
+    }
+
+    /// Splits the given string into runs that are printed with the same font with this style
+    /// using the given font cache.
+    ///
+    /// The characters are assigned to the fonts with [`font_for_char`][].  Whitespace is printed
+    /// with the font of the preceding character.  If no fallback fonts are registered, the
+    /// complete string is printed with the font of this style.  The returned runs are never empty
+    /// unless the given string is empty.
+    ///
+    /// If the font family is set, it must have been created by the given [`FontCache`][].
+    ///
+    /// [`FontCache`]: ../fonts/struct.FontCache.html
+    /// [`font_for_char`]: #method.font_for_char
+    pub fn font_runs<'s>(
+        &self,
+        font_cache: &fonts::FontCache,
+        s: &'s str,
+    ) -> Vec<(fonts::Font, &'s str)> {
+        let font = self.font(font_cache);
+        if font_cache.fallback_font_families().is_empty() {
+            return vec![(font, s)];
+        }
+
+        let mut runs = Vec::new();
+        let mut current: Option<(fonts::Font, usize)> = None;
+        for (idx, c) in s.char_indices() {
+            let char_font = match current {
+                Some((current_font, _)) if c.is_whitespace() || c == wrap::SOFT_HYPHEN => {
+                    current_font
+                }
+                _ => font_cache.font_for_char(font, *self, c),
+            };
+            match current {
+                Some((current_font, _)) if current_font == char_font => {}
+                Some((current_font, start)) => {
+                    runs.push((current_font, &s[start..idx]));
+                    current = Some((char_font, idx));
+                }
+                None => current = Some((char_font, idx)),
+            }
+        }
+        if let Some((current_font, start)) = current {
+            runs.push((current_font, &s[start..]));
+        } else {
+            runs.push((font, s));
+        }
+        runs
+    }
+
     /// Calculates the line height for strings with this style using the data in the given font
     /// cache.
     ///
@@ -303,7 +857,7 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn line_height(&self, font_cache: &fonts::FontCache) -> Mm {
-        self.font(font_cache).get_line_height(self.font_size()) * self.line_spacing()
+        self.metrics(font_cache).line_height
     }
 
     /// Calculate the metrics of the font for this style using the data in the given font cache.
@@ -312,8 +866,18 @@ impl Style {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn metrics(&self, font_cache: &fonts::FontCache) -> fonts::Metrics {
-        let mut metrics = self.font(font_cache).metrics(self.font_size());
-        metrics.line_height *= self.line_spacing();
+        let mut metrics = self.font(font_cache).metrics(self.text_font_size());
+        if let Some(leading) = self.leading {
+            metrics.line_height = leading;
+        } else {
+            metrics.line_height *= self.line_spacing();
+        }
+        // Raised text needs additional space above the baseline, lowered text below the baseline
+        let offset = self.baseline_offset();
+        let shift = offset.max(offset * -1.0);
+        metrics.line_height += shift;
+        metrics.glyph_height += shift;
+        metrics.ascent += offset.max(Mm(0.0));
         metrics
     }
 }
@@ -330,6 +894,10 @@ impl From<Effect> for Style {
         match effect {
             Effect::Bold => style.bold(),
             Effect::Italic => style.italic(),
+            Effect::Superscript => style.superscript(),
+            Effect::Subscript => style.subscript(),
+            Effect::Underline => style.underline(),
+            Effect::Strikethrough => style.strikethrough(),
         }
     }
 }
@@ -364,6 +932,8 @@ impl<T: Into<Style>> iter::FromIterator<T> for Style {
 /// use genpdf::style;
 /// let ss1 = style::StyledString::new("bold".to_owned(), style::Effect::Bold);
 /// let ss2 = style::StyledString::new("red".to_owned(), style::Color::Rgb(255, 0, 0));
+/// let ss3 = style::StyledString::new("genpdf", style::Effect::Italic)
+///     .with_link("https://git.sr.ht/~ireas/genpdf-rs");
 /// ```
 ///
 /// [`Style`]: struct.Style.html
@@ -374,6 +944,8 @@ pub struct StyledString {
     pub s: String,
     /// The style annotation.
     pub style: Style,
+    /// The URI this string links to, if any.
+    pub link: Option<String>,
 }
 
 impl StyledString {
@@ -382,9 +954,32 @@ impl StyledString {
         StyledString {
             s: s.into(),
             style: style.into(),
+            link: None,
         }
     }
 
+    /// Sets the URI this string links to.
+    ///
+    /// If the string is printed by a [`Paragraph`][] or a [`Text`][] element, a link annotation
+    /// for this URI is added to the PDF document, see [`Area::add_uri_link`][].
+    ///
+    /// [`Paragraph`]: ../elements/struct.Paragraph.html
+    /// [`Text`]: ../elements/struct.Text.html
+    /// [`Area::add_uri_link`]: ../render/struct.Area.html#method.add_uri_link
+    pub fn set_link(&mut self, uri: impl Into<String>) {
+        self.link = Some(uri.into());
+    }
+
+    /// Sets the URI this string links to and returns the string.
+    ///
+    /// See [`set_link`][] for more information.
+    ///
+    /// [`set_link`]: #method.set_link
+    pub fn with_link(mut self, uri: impl Into<String>) -> Self {
+        self.set_link(uri);
+        self
+    }
+
     /// Calculates the width of the this string with this style using the data in the given font
     /// cache.
     ///
@@ -433,6 +1028,8 @@ pub struct StyledStr<'s> {
     pub s: &'s str,
     /// The style annotation.
     pub style: Style,
+    /// The URI this string links to, if any.
+    pub link: Option<&'s str>,
 }
 
 impl<'s> StyledStr<'s> {
@@ -441,6 +1038,7 @@ impl<'s> StyledStr<'s> {
         StyledStr {
             s,
             style: style.into(),
+            link: None,
         }
     }
 
@@ -452,7 +1050,7 @@ impl<'s> StyledStr<'s> {
     ///
     /// [`FontCache`]: ../fonts/struct.FontCache.html
     pub fn width(&self, font_cache: &fonts::FontCache) -> Mm {
-        self.style.str_width(font_cache, &self.s)
+        self.style.str_width(font_cache, self.s)
     }
 }
 
@@ -470,7 +1068,11 @@ impl<'s> From<&'s String> for StyledStr<'s> {
 
 impl<'s> From<&'s StyledString> for StyledStr<'s> {
     fn from(s: &'s StyledString) -> StyledStr<'s> {
-        StyledStr::new(&s.s, s.style)
+        StyledStr {
+            s: &s.s,
+            style: s.style,
+            link: s.link.as_deref(),
+        }
     }
 }
 
@@ -492,6 +1094,8 @@ pub struct StyledCow<'s> {
     pub s: borrow::Cow<'s, str>,
     /// The style annotation.
     pub style: Style,
+    /// The URI this string links to, if any.
+    pub link: Option<borrow::Cow<'s, str>>,
 }
 
 impl<'s> StyledCow<'s> {
@@ -500,6 +1104,16 @@ impl<'s> StyledCow<'s> {
         StyledCow {
             s: s.into(),
             style: style.into(),
+            link: None,
+        }
+    }
+
+    /// Creates a new styled string from the given string with the style and link of this string.
+    pub(crate) fn with_str(&self, s: impl Into<borrow::Cow<'s, str>>) -> StyledCow<'s> {
+        StyledCow {
+            s: s.into(),
+            style: self.style,
+            link: self.link.clone(),
         }
     }
 
@@ -535,19 +1149,240 @@ impl<'s> From<String> for StyledCow<'s> {
 
 impl<'s> From<StyledStr<'s>> for StyledCow<'s> {
     fn from(s: StyledStr<'s>) -> StyledCow<'s> {
-        StyledCow::new(s.s, s.style)
+        StyledCow {
+            s: s.s.into(),
+            style: s.style,
+            link: s.link.map(Into::into),
+        }
     }
 }
 
 impl<'s> From<&'s StyledString> for StyledCow<'s> {
     fn from(s: &'s StyledString) -> StyledCow<'s> {
-        StyledCow::new(&s.s, s.style)
+        StyledCow {
+            s: s.s.as_str().into(),
+            style: s.style,
+            link: s.link.as_deref().map(Into::into),
+        }
     }
 }
 
 impl<'s> From<StyledString> for StyledCow<'s> {
     fn from(s: StyledString) -> StyledCow<'s> {
-        StyledCow::new(s.s, s.style)
+        StyledCow {
+            s: s.s.into(),
+            style: s.style,
+            link: s.link.map(Into::into),
+        }
+    }
+}
+
+/// Formats the given number as an ordinal number for the given locale.
+///
+/// The locale is a language tag like `en` or `en-US`.  The number is printed with the given style
+/// and followed by the ordinal suffix for the language of the locale, if there is one:
+/// - English (`en`):  a superscript suffix, e. g. 1ˢᵗ, 2ⁿᵈ, 3ʳᵈ, 4ᵗʰ, 11ᵗʰ or 21ˢᵗ
+/// - French (`fr`):  a superscript suffix, e. g. 1ᵉʳ or 2ᵉ
+/// - Italian (`it`), Portuguese (`pt`) and Spanish (`es`):  the ordinal indicator, e. g. 1º
+///   (or 1.º for Spanish)
+/// - Danish (`da`), Finnish (`fi`), German (`de`), Norwegian (`nb`, `nn`, `no`) and other
+///   languages that use a trailing period, e. g. 1.
+///
+/// For all other languages, only the number is returned.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let mut p = elements::Paragraph::new("June ");
+/// p.extend(style::ordinal(21, "en", style::Style::new()));
+/// ```
+pub fn ordinal(number: u64, locale: &str, style: impl Into<Style>) -> Vec<StyledString> {
+    let style = style.into();
+    let language = locale
+        .split(|c| c == '-' || c == '_')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let suffix = match language.as_str() {
+        "en" => Some((english_ordinal_suffix(number), true)),
+        "fr" => Some((if number == 1 { "er" } else { "e" }, true)),
+        "it" | "pt" => Some(("º", false)),
+        "es" => Some((".º", false)),
+        "cs" | "da" | "de" | "et" | "fi" | "hr" | "hu" | "is" | "lv" | "nb" | "nn" | "no"
+        | "pl" | "sk" | "sl" | "sr" | "tr" => Some((".", false)),
+        _ => None,
+    };
+
+    let mut strings = vec![StyledString::new(number.to_string(), style)];
+    if let Some((suffix, is_superscript)) = suffix {
+        let suffix_style = if is_superscript {
+            style.superscript()
+        } else {
+            style
+        };
+        strings.push(StyledString::new(suffix, suffix_style));
+    }
+    strings
+}
+
+fn english_ordinal_suffix(number: u64) -> &'static str {
+    match (number % 10, number % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Parses the given markup into styled strings.
+///
+/// The markup is plain text with these inline tags that can be nested:
+/// - `<b>…</b>`:  bold text
+/// - `<i>…</i>`:  italic text
+/// - `<u>…</u>`:  underlined text
+/// - `<s>…</s>`:  struck-through text
+/// - `<sup>…</sup>`:  superscript text
+/// - `<sub>…</sub>`:  subscript text
+/// - `<color=#rrggbb>…</color>`:  text with the given RGB color in hexadecimal notation
+///
+/// The characters `<`, `>` and `&` can be escaped as `&lt;`, `&gt;` and `&amp;`.  The returned
+/// strings only contain the styles set by the tags, so they can be combined with the style of the
+/// surrounding element.  If the markup contains an unknown tag or entity, or if the tags are not
+/// balanced, an error with the kind [`ErrorKind::InvalidData`][] is returned.
+///
+/// # Example
+///
+/// ```
+/// use genpdf::{elements, style};
+/// let strings = style::parse_markup("H<sub>2</sub>O is <b>not</b> <color=#ff0000>red</color>")
+///     .expect("Invalid markup");
+/// let p = elements::Paragraph::from(strings);
+/// ```
+///
+/// [`ErrorKind::InvalidData`]: ../error/enum.ErrorKind.html#variant.InvalidData
+pub fn parse_markup(markup: &str) -> Result<Vec<StyledString>, Error> {
+    let invalid = |msg: String| Error::new(msg, ErrorKind::InvalidData);
+    let mut strings = Vec::new();
+    let mut tags: Vec<(&str, Style)> = Vec::new();
+    let mut style = Style::new();
+    let mut text = String::new();
+    let mut rest = markup;
+    while let Some(idx) = rest.find(&['<', '&'][..]) {
+        text.push_str(&rest[..idx]);
+        rest = &rest[idx..];
+        if rest.starts_with('&') {
+            let end = rest
+                .find(';')
+                .ok_or_else(|| invalid(format!("Unterminated entity in markup: {}", rest)))?;
+            let c = match &rest[..=end] {
+                "&lt;" => '<',
+                "&gt;" => '>',
+                "&amp;" => '&',
+                entity => return Err(invalid(format!("Unknown entity in markup: {}", entity))),
+            };
+            text.push(c);
+            rest = &rest[end + 1..];
+            continue;
+        }
+
+        let end = rest
+            .find('>')
+            .ok_or_else(|| invalid(format!("Unterminated tag in markup: {}", rest)))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if !text.is_empty() {
+            strings.push(StyledString::new(std::mem::take(&mut text), style));
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            match tags.pop() {
+                Some((open, previous)) if open == name => style = previous,
+                _ => {
+                    return Err(invalid(format!(
+                        "Unexpected closing tag in markup: {}",
+                        tag
+                    )))
+                }
+            }
+        } else {
+            let mut parts = tag.splitn(2, '=');
+            let name = parts.next().unwrap_or_default();
+            let value = parts.next();
+            let previous = style;
+            match (name, value) {
+                ("b", None) => style.set_bold(),
+                ("i", None) => style.set_italic(),
+                ("u", None) => style.set_underline(),
+                ("s", None) => style.set_strikethrough(),
+                ("sup", None) => style.set_superscript(),
+                ("sub", None) => style.set_subscript(),
+                ("color", Some(value)) => style.set_color(
+                    parse_hex_color(value)
+                        .ok_or_else(|| invalid(format!("Invalid color in markup: {}", value)))?,
+                ),
+                _ => return Err(invalid(format!("Unknown tag in markup: {}", tag))),
+            }
+            tags.push((name, previous));
+        }
+    }
+    text.push_str(rest);
+    if let Some((name, _)) = tags.last() {
+        return Err(invalid(format!("Unclosed tag in markup: {}", name)));
+    }
+    if !text.is_empty() {
+        strings.push(StyledString::new(text, style));
+    }
+    Ok(strings)
+}
+
+/// Parses a color in the hexadecimal notation `#rrggbb`.
+pub(crate) fn parse_hex_color(s: &str) -> Option<Color> {
+    let s = s
+        .strip_prefix('#')
+        .filter(|s| s.len() == 6 && s.chars().all(|c| c.is_ascii_hexdigit()))?;
+    let component = |idx: usize| u8::from_str_radix(&s[idx..idx + 2], 16).ok();
+    Some(Color::Rgb(component(0)?, component(2)?, component(4)?))
+}
+
+/// The maximum number of lengths in a dash pattern of a [`LineStyle`](struct.LineStyle.html).
+const MAX_DASH_PATTERN_LEN: usize = 6;
+
+/// The shape of the ends of a line, see [`LineStyle::set_line_cap`][].
+///
+/// [`LineStyle::set_line_cap`]: struct.LineStyle.html#method.set_line_cap
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// The line ends at its end points (default).
+    Butt,
+    /// The line ends with a semicircle around its end points.
+    Round,
+    /// The line ends with a square around its end points.
+    Square,
+}
+
+impl Default for LineCap {
+    fn default() -> LineCap {
+        LineCap::Butt
+    }
+}
+
+/// The shape of the corners of a line, see [`LineStyle::set_line_join`][].
+///
+/// [`LineStyle::set_line_join`]: struct.LineStyle.html#method.set_line_join
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    /// The outer edges of the line segments are extended until they meet (default).
+    Miter,
+    /// The corners are rounded.
+    Round,
+    /// The corners are cut off.
+    Bevel,
+}
+
+impl Default for LineJoin {
+    fn default() -> LineJoin {
+        LineJoin::Miter
     }
 }
 
@@ -556,15 +1391,29 @@ impl<'s> From<StyledString> for StyledCow<'s> {
 /// The style consists of:
 /// - the line thickness in millimeters (defaults to 0.1)
 /// - the color of the line, see [`Color`][] (defaults to black)
+/// - the dash pattern of the line, see [`set_dash`][] (defaults to a solid line)
+/// - the shape of the line ends and corners, see [`set_line_cap`][] and [`set_line_join`][]
+///   (defaults to butt ends and miter corners)
+/// - the radius of rounded corners for frames and borders, see [`set_corner_radius`][] (defaults
+///   to square corners)
 ///
 /// Note that a line thickness of 0.0 does not make the line disappear, but rather makes it appear
 /// 1px wide across all devices and resolutions.
 ///
 /// [`Color`]: enum.Color.html
+/// [`set_dash`]: #method.set_dash
+/// [`set_line_cap`]: #method.set_line_cap
+/// [`set_line_join`]: #method.set_line_join
+/// [`set_corner_radius`]: #method.set_corner_radius
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct LineStyle {
     thickness: Mm,
     color: Color,
+    dash_pattern: [Mm; MAX_DASH_PATTERN_LEN],
+    dash_pattern_len: usize,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    corner_radius: Mm,
 }
 
 impl Default for LineStyle {
@@ -572,6 +1421,11 @@ impl Default for LineStyle {
         LineStyle {
             thickness: Mm::from(0.1),
             color: Color::Rgb(0, 0, 0),
+            dash_pattern: Default::default(),
+            dash_pattern_len: 0,
+            line_cap: LineCap::default(),
+            line_join: LineJoin::default(),
+            corner_radius: Mm(0.0),
         }
     }
 }
@@ -628,4 +1482,141 @@ impl LineStyle {
     pub fn color(&self) -> Color {
         self.color
     }
+
+    /// Sets the dash pattern of the line.
+    ///
+    /// The pattern consists of alternating lengths of dashes and gaps, starting with a dash.  If
+    /// the pattern has an odd number of lengths, it is repeated so that every length is used both
+    /// for a dash and for a gap.  For example, `&[2, 1]` draws dashes with a length of 2 mm and
+    /// gaps of 1 mm, and `&[1]` draws dashes and gaps with a length of 1 mm.  Only the first six
+    /// lengths are used.  If the pattern is empty or if all lengths are zero, the line is solid.
+    pub fn set_dash<T: Into<Mm> + Copy>(&mut self, pattern: &[T]) {
+        self.dash_pattern = Default::default();
+        self.dash_pattern_len = 0;
+        if pattern.iter().any(|length| (*length).into() > Mm(0.0)) {
+            for (i, length) in pattern.iter().take(MAX_DASH_PATTERN_LEN).enumerate() {
+                self.dash_pattern[i] = (*length).into().max(Mm(0.0));
+                self.dash_pattern_len += 1;
+            }
+        }
+    }
+
+    /// Sets the dash pattern of the line and returns the line style.
+    ///
+    /// See [`set_dash`](#method.set_dash) for a description of the pattern.
+    pub fn with_dash<T: Into<Mm> + Copy>(mut self, pattern: &[T]) -> Self {
+        self.set_dash(pattern);
+        self
+    }
+
+    /// Returns the dash pattern of the line, or an empty slice if the line is solid.
+    pub fn dash_pattern(&self) -> &[Mm] {
+        &self.dash_pattern[..self.dash_pattern_len]
+    }
+
+    /// Sets the shape of the ends of the line.
+    ///
+    /// The line cap is also applied to the ends of the dashes of a dashed line.
+    pub fn set_line_cap(&mut self, line_cap: LineCap) {
+        self.line_cap = line_cap;
+    }
+
+    /// Sets the shape of the ends of the line and returns the line style.
+    ///
+    /// The line cap is also applied to the ends of the dashes of a dashed line.
+    pub fn with_line_cap(mut self, line_cap: LineCap) -> Self {
+        self.set_line_cap(line_cap);
+        self
+    }
+
+    /// Returns the shape of the ends of the line.
+    pub fn line_cap(&self) -> LineCap {
+        self.line_cap
+    }
+
+    /// Sets the shape of the corners of the line.
+    pub fn set_line_join(&mut self, line_join: LineJoin) {
+        self.line_join = line_join;
+    }
+
+    /// Sets the shape of the corners of the line and returns the line style.
+    pub fn with_line_join(mut self, line_join: LineJoin) -> Self {
+        self.set_line_join(line_join);
+        self
+    }
+
+    /// Returns the shape of the corners of the line.
+    pub fn line_join(&self) -> LineJoin {
+        self.line_join
+    }
+
+    /// Sets the radius for rounded corners of frames and borders drawn with this line style.
+    ///
+    /// This is used by the [`FramedElement`][] and by the outer corners of a table with a
+    /// [`FrameCellDecorator`][].  Other lines and shapes are not affected.
+    ///
+    /// [`FramedElement`]: ../elements/struct.FramedElement.html
+    /// [`FrameCellDecorator`]: ../elements/struct.FrameCellDecorator.html
+    pub fn set_corner_radius(&mut self, radius: impl Into<Mm>) {
+        self.corner_radius = radius.into().max(Mm(0.0));
+    }
+
+    /// Sets the radius for rounded corners of frames and borders drawn with this line style and
+    /// returns the line style.
+    ///
+    /// See [`set_corner_radius`](#method.set_corner_radius) for more information.
+    pub fn with_corner_radius(mut self, radius: impl Into<Mm>) -> Self {
+        self.set_corner_radius(radius);
+        self
+    }
+
+    /// Returns the radius for rounded corners of frames and borders drawn with this line style.
+    pub fn corner_radius(&self) -> Mm {
+        self.corner_radius
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ordinal, Style};
+
+    fn format(number: u64, locale: &str) -> Vec<(String, bool)> {
+        ordinal(number, locale, Style::new())
+            .into_iter()
+            .map(|s| (s.s, s.style.is_superscript()))
+            .collect()
+    }
+
+    #[test]
+    fn test_ordinal_english() {
+        let suffix = |n| format(n, "en-US")[1].clone();
+        assert_eq!(("st".to_owned(), true), suffix(1));
+        assert_eq!(("nd".to_owned(), true), suffix(2));
+        assert_eq!(("rd".to_owned(), true), suffix(3));
+        assert_eq!(("th".to_owned(), true), suffix(4));
+        assert_eq!(("th".to_owned(), true), suffix(11));
+        assert_eq!(("th".to_owned(), true), suffix(12));
+        assert_eq!(("th".to_owned(), true), suffix(113));
+        assert_eq!(("st".to_owned(), true), suffix(21));
+        assert_eq!(("nd".to_owned(), true), suffix(102));
+        assert_eq!(("th".to_owned(), true), suffix(0));
+    }
+
+    #[test]
+    fn test_ordinal_other_locales() {
+        assert_eq!(
+            vec![("1".to_owned(), false), ("er".to_owned(), true)],
+            format(1, "fr")
+        );
+        assert_eq!(
+            vec![("2".to_owned(), false), ("e".to_owned(), true)],
+            format(2, "fr_CA")
+        );
+        assert_eq!(
+            vec![("3".to_owned(), false), (".".to_owned(), false)],
+            format(3, "de-DE")
+        );
+        assert_eq!(vec![("4".to_owned(), false)], format(4, "ja"));
+        assert_eq!(vec![("5".to_owned(), false)], format(5, ""));
+    }
 }
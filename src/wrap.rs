@@ -3,22 +3,39 @@
 
 //! Utilities for text wrapping.
 
+use std::collections;
 use std::mem;
 
 use crate::style;
 use crate::Context;
 use crate::Mm;
+use crate::{Newline, Whitespace};
 
 /// Combines a sequence of styled words into lines with a maximum width.
 ///
 /// If a word does not fit into a line, the wrapper tries to split it using the `split` function.
+///
+/// If the wrapping mode is set to [`Wrap::Clip`][] or [`Wrap::Ellipsis`][], the wrapper only
+/// yields a single line and truncates the words that do not fit into it.
+///
+/// If breaking overflows is enabled, words that do not fit into a line even after splitting them
+/// are broken at arbitrary characters, see [`Overflow::Break`][].
+///
+/// [`Wrap::Clip`]: ../style/enum.Wrap.html#variant.Clip
+/// [`Wrap::Ellipsis`]: ../style/enum.Wrap.html#variant.Ellipsis
+/// [`Overflow::Break`]: ../enum.Overflow.html#variant.Break
 pub struct Wrapper<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> {
     iter: I,
     context: &'c Context,
     width: Mm,
     x: Mm,
     buf: Vec<style::StyledCow<'s>>,
-    has_overflowed: bool,
+    overflowed_word: Option<String>,
+    wrap: style::Wrap,
+    is_clip_overflow: bool,
+    is_break_overflow: bool,
+    queued_lines: collections::VecDeque<(Vec<style::StyledCow<'s>>, usize)>,
+    is_done: bool,
 }
 
 impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Wrapper<'c, 's, I> {
@@ -30,14 +47,43 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Wrapper<'c, 's, I> {
             width,
             x: Mm(0.0),
             buf: Vec::new(),
-            has_overflowed: false,
+            overflowed_word: None,
+            wrap: style::Wrap::Words,
+            is_clip_overflow: false,
+            is_break_overflow: false,
+            queued_lines: collections::VecDeque::new(),
+            is_done: false,
         }
     }
 
-    /// Returns true if this wrapper has overflowed, i. e. if it encountered a word that it could
-    /// not split so that it would fit into a line.
-    pub fn has_overflowed(&self) -> bool {
-        self.has_overflowed
+    /// Sets the wrapping mode for this wrapper.
+    pub fn set_wrap(&mut self, wrap: style::Wrap) {
+        self.wrap = wrap;
+    }
+
+    /// Sets whether words that do not fit into a line should be placed on a line of their own
+    /// instead of causing an overflow.
+    ///
+    /// The caller is responsible for clipping the resulting lines that exceed the maximum width.
+    pub fn set_clip_overflow(&mut self, clip_overflow: bool) {
+        self.is_clip_overflow = clip_overflow;
+    }
+
+    /// Sets whether words that do not fit into a line should be broken at arbitrary characters
+    /// instead of causing an overflow.
+    ///
+    /// The broken parts of the word are followed by the mark set with
+    /// [`Document::set_word_break_mark`][].
+    ///
+    /// [`Document::set_word_break_mark`]: ../struct.Document.html#method.set_word_break_mark
+    pub fn set_break_overflow(&mut self, break_overflow: bool) {
+        self.is_break_overflow = break_overflow;
+    }
+
+    /// Returns the word that caused the overflow if this wrapper has overflowed, i. e. if it
+    /// encountered a word that it could not split so that it would fit into a line.
+    pub fn overflowed_word(&self) -> Option<&str> {
+        self.overflowed_word.as_deref()
     }
 }
 
@@ -47,11 +93,34 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Iterator for Wrapper<'c,
     type Item = (Vec<style::StyledCow<'s>>, usize);
 
     fn next(&mut self) -> Option<(Vec<style::StyledCow<'s>>, usize)> {
+        if let Some(line) = self.queued_lines.pop_front() {
+            return Some(line);
+        }
+        if self.is_done {
+            return None;
+        }
+
         // Append words to self.buf until the maximum line length is reached
-        while let Some(s) = self.iter.next() {
+        for s in self.iter.by_ref() {
+            if is_newline(s.s) {
+                // A newline ends the current line.  We keep it in the line so that the caller
+                // knows how many bytes have been processed.
+                self.buf.push(s.into());
+                self.x = Mm(0.0);
+                if self.wrap != style::Wrap::Words {
+                    self.is_done = true;
+                }
+                return Some((mem::take(&mut self.buf), 0));
+            }
+
             let mut width = s.width(&self.context.font_cache);
 
-            if self.x + width > self.width {
+            if self.x + width > self.width && self.wrap != style::Wrap::Words {
+                // The word does not fit into the line, and we only print a single line
+                self.truncate(s.into());
+                self.is_done = true;
+                return Some((mem::take(&mut self.buf), 0));
+            } else if self.x + width > self.width {
                 // The word does not fit into the current line (at least not completely)
 
                 let mut delta = 0;
@@ -67,11 +136,32 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Iterator for Wrapper<'c,
                     s.into()
                 };
 
-                if width > self.width {
+                if width > self.width && self.is_break_overflow {
+                    // The word does not fit into a line of its own, so we break it into parts
+                    // that fit into a line, starting on a new line
+                    let mut lines = collections::VecDeque::new();
+                    if !self.buf.is_empty() {
+                        lines.push_back((mem::take(&mut self.buf), delta));
+                    }
+                    let mut parts = break_word(self.context, s, self.width);
+                    let (last, _) = parts.pop().expect("Broken word without parts");
+                    lines.extend(parts.into_iter().map(|(part, delta)| (vec![part], delta)));
+                    self.x = last.width(&self.context.font_cache);
+                    self.buf.push(last);
+                    if let Some(line) = lines.pop_front() {
+                        self.queued_lines = lines;
+                        return Some(line);
+                    }
+                    continue;
+                } else if width > self.width && self.is_clip_overflow && self.buf.is_empty() {
+                    // The word will be clipped, so we put it on a line of its own
+                    self.x = Mm(0.0);
+                    return Some((vec![s], delta));
+                } else if width > self.width && !self.is_clip_overflow {
                     // The remainder of the word is longer than the current page – we will never be
                     // able to render it completely.
                     // TODO: handle gracefully, emit warning
-                    self.has_overflowed = true;
+                    self.overflowed_word = Some(s.s.into_owned());
                     return None;
                 }
 
@@ -95,8 +185,241 @@ impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Iterator for Wrapper<'c,
     }
 }
 
-#[cfg(not(feature = "hyphenation"))]
+impl<'c, 's, I: Iterator<Item = style::StyledStr<'s>>> Wrapper<'c, 's, I> {
+    /// Appends the longest prefix of the given word that fits into the current line to the line,
+    /// followed by an ellipsis if the wrapping mode is [`Wrap::Ellipsis`][].
+    ///
+    /// If the ellipsis does not fit into the current line, the previous words are truncated too.
+    ///
+    /// [`Wrap::Ellipsis`]: ../style/enum.Wrap.html#variant.Ellipsis
+    fn truncate(&mut self, mut s: style::StyledCow<'s>) {
+        let font_cache = &self.context.font_cache;
+        let ellipsis = if self.wrap == style::Wrap::Ellipsis {
+            "…"
+        } else {
+            ""
+        };
+        loop {
+            let max_width = self.width - self.x - s.style.str_width(font_cache, ellipsis);
+            let mut width = Mm(0.0);
+            let mut len = 0;
+            for c in s.s.chars() {
+                width += s.style.char_width(font_cache, c);
+                if width > max_width {
+                    break;
+                }
+                len += c.len_utf8();
+            }
+
+            if len == 0 && !ellipsis.is_empty() {
+                // The ellipsis does not fit after the previous word, so we have to truncate it
+                if let Some(previous) = self.buf.pop() {
+                    self.x -= previous.width(font_cache);
+                    s = previous;
+                    continue;
+                }
+            }
+
+            let mut truncated = s.s[..len].to_owned();
+            if !ellipsis.is_empty() {
+                truncated.truncate(truncated.trim_end().len());
+                truncated.push_str(ellipsis);
+            }
+            if !truncated.is_empty() {
+                self.x += s.style.str_width(font_cache, &truncated);
+                self.buf.push(s.with_str(truncated));
+            }
+            break;
+        }
+    }
+}
+
+/// The maximum badness of a line, see [`break_lines_optimally`][].
+///
+/// [`break_lines_optimally`]: fn.break_lines_optimally.html
+const MAX_BADNESS: f64 = 10_000.0;
+
+/// Combines a sequence of styled words into lines with a maximum width, minimizing the raggedness
+/// of the whole paragraph.
+///
+/// Instead of filling each line as much as possible like [`Wrapper`][], this function uses a
+/// simplified version of the Knuth-Plass algorithm to choose the line breaks so that the sum of
+/// the demerits of all lines but the last one is minimal.  The demerits of a line are calculated
+/// from the free space in the line relative to the width of the spaces in the line.
+///
+/// Words are never split.  If a word does not fit into a line, `None` is returned and the caller
+/// should fall back to [`Wrapper`][].
+///
+/// [`Wrapper`]: struct.Wrapper.html
+pub fn break_lines_optimally<'s>(
+    context: &Context,
+    words: impl IntoIterator<Item = style::StyledStr<'s>>,
+    width: Mm,
+) -> Option<Vec<Vec<style::StyledCow<'s>>>> {
+    let font_cache = &context.font_cache;
+    let words: Vec<_> = words.into_iter().collect();
+    if words.iter().any(|s| is_newline(s.s)) {
+        return None;
+    }
+    // The width of the words without and with trailing spaces
+    let widths: Vec<_> = words
+        .iter()
+        .map(|s| {
+            let total_width = s.width(font_cache);
+            let space_width = s.style.str_width(font_cache, &s.s[s.s.trim_end().len()..]);
+            (total_width - space_width, space_width)
+        })
+        .collect();
+    if widths.iter().any(|(word_width, _)| *word_width > width) {
+        return None;
+    }
+
+    // demerits[j] is the minimal sum of the demerits for the first j words, and breaks[j] is the
+    // index of the first word of the last line in this solution.
+    let n = words.len();
+    let mut demerits = vec![f64::INFINITY; n + 1];
+    let mut breaks = vec![0; n + 1];
+    demerits[0] = 0.0;
+    for start in 0..n {
+        if demerits[start].is_infinite() {
+            continue;
+        }
+        let mut line_width = Mm(0.0);
+        let mut stretch = Mm(0.0);
+        for end in start + 1..=n {
+            let (word_width, _) = widths[end - 1];
+            if end > start + 1 {
+                let (_, previous_space_width) = widths[end - 2];
+                line_width += previous_space_width;
+                stretch += previous_space_width;
+            }
+            line_width += word_width;
+            if line_width > width && end > start + 1 {
+                break;
+            }
+
+            let line_demerits = if end == n {
+                // The last line is not stretched, so its length does not matter.
+                0.0
+            } else {
+                let slack = (width - line_width).0;
+                let badness = if stretch.0 > 0.0 {
+                    (100.0 * (slack / stretch.0).powi(3)).min(MAX_BADNESS)
+                } else if slack > 0.0 {
+                    MAX_BADNESS
+                } else {
+                    0.0
+                };
+                (1.0 + badness).powi(2)
+            };
+            let total = demerits[start] + line_demerits;
+            if total < demerits[end] {
+                demerits[end] = total;
+                breaks[end] = start;
+            }
+        }
+    }
+
+    let mut ends = Vec::new();
+    let mut end = n;
+    while end > 0 {
+        ends.push(end);
+        end = breaks[end];
+    }
+
+    let mut words = words.into_iter();
+    let mut start = 0;
+    let lines = ends
+        .into_iter()
+        .rev()
+        .map(|end| {
+            let line = words.by_ref().take(end - start).map(Into::into).collect();
+            start = end;
+            line
+        })
+        .collect();
+    Some(lines)
+}
+
+/// Breaks the given word into parts that fit into a line with the given width.
+///
+/// All parts but the last one are followed by the word break mark, and they are returned
+/// together with the number of bytes that have been added for the mark.  Every part contains
+/// at least one character, even if it is wider than the line.
+fn break_word<'s>(
+    context: &Context,
+    s: style::StyledCow<'s>,
+    width: Mm,
+) -> Vec<(style::StyledCow<'s>, usize)> {
+    let font_cache = &context.font_cache;
+    let mark = context.word_break_mark();
+    let max_width = width - s.style.str_width(font_cache, mark);
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut part_width = Mm(0.0);
+    for (idx, c) in s.s.char_indices() {
+        let char_width = s.style.char_width(font_cache, c);
+        if idx > start && part_width + char_width > max_width {
+            parts.push((s.with_str(s.s[start..idx].to_owned() + mark), mark.len()));
+            start = idx;
+            part_width = Mm(0.0);
+        }
+        part_width += char_width;
+    }
+    let last = s.with_str(s.s[start..].to_owned());
+    parts.push((last, 0));
+    parts
+}
+
+/// The soft hyphen that marks a possible hyphenation point in a word.
+pub const SOFT_HYPHEN: char = '\u{ad}';
+
+/// Tries to split the given string into two parts so that the first part is shorter than the given
+/// width.
+///
+/// If the string contains soft hyphens, it is only split at these hyphens.  Otherwise, the
+/// hyphenator for the current language is used if the `hyphenation` feature is enabled.
 fn split<'s>(
+    context: &Context,
+    s: style::StyledStr<'s>,
+    width: Mm,
+) -> Option<(style::StyledCow<'s>, style::StyledCow<'s>)> {
+    if s.s.contains(SOFT_HYPHEN) {
+        split_at_soft_hyphen(context, s, width)
+    } else {
+        hyphenate(context, s, width)
+    }
+}
+
+/// Splits the given string at the last soft hyphen so that the first part and a hyphen are
+/// shorter than the given width.
+///
+/// The soft hyphen is kept at the end of the first part so that the length of the parts matches
+/// the length of the input.  It is not printed, see [`render::TextSection::print_str`][].
+///
+/// [`render::TextSection::print_str`]: ../render/struct.TextSection.html#method.print_str
+fn split_at_soft_hyphen<'s>(
+    context: &Context,
+    s: style::StyledStr<'s>,
+    width: Mm,
+) -> Option<(style::StyledCow<'s>, style::StyledCow<'s>)> {
+    let mark = "-";
+    let mark_width = s.style.str_width(&context.font_cache, mark);
+    let idx =
+        s.s.match_indices(SOFT_HYPHEN)
+            .map(|(idx, hyphen)| idx + hyphen.len())
+            .take_while(|idx| {
+                s.style.str_width(&context.font_cache, &s.s[..*idx]) + mark_width <= width
+            })
+            .last()?;
+    let start = s.s[..idx].to_owned() + mark;
+    let end = &s.s[idx..];
+    let s = style::StyledCow::from(s);
+    Some((s.with_str(start), s.with_str(end)))
+}
+
+#[cfg(not(feature = "hyphenation"))]
+fn hyphenate<'s>(
     _context: &Context,
     _s: style::StyledStr<'s>,
     _len: Mm,
@@ -104,21 +427,24 @@ fn split<'s>(
     None
 }
 
-/// Tries to split the given string into two parts so that the first part is shorter than the given
-/// width.
+/// Tries to hyphenate the given string so that the first part is shorter than the given width.
 #[cfg(feature = "hyphenation")]
-fn split<'s>(
+fn hyphenate<'s>(
     context: &Context,
     s: style::StyledStr<'s>,
     width: Mm,
 ) -> Option<(style::StyledCow<'s>, style::StyledCow<'s>)> {
     use hyphenation::{Hyphenator, Iter};
 
-    let hyphenator = if let Some(hyphenator) = &context.hyphenator {
-        hyphenator
-    } else {
-        return None;
-    };
+    let language = context.language();
+    let hyphenator = language
+        .and_then(|language| {
+            context
+                .hyphenators
+                .iter()
+                .find(|h| matches_language(h.language().code(), &language))
+        })
+        .or(context.hyphenator.as_ref())?;
 
     let mark = "-";
     let mark_width = s.style.str_width(&context.font_cache, mark);
@@ -140,15 +466,75 @@ fn split<'s>(
         let idx = hyphenated.breaks[idx - 1];
         let start = s.s[..idx].to_owned() + mark;
         let end = &s.s[idx..];
-        Some((
-            style::StyledCow::new(start, s.style),
-            style::StyledCow::new(end, s.style),
-        ))
+        let s = style::StyledCow::from(s);
+        Some((s.with_str(start), s.with_str(end)))
     } else {
         None
     }
 }
 
+/// Returns whether the hyphenation language with the given code can be used for text with the given
+/// language tag.
+#[cfg(feature = "hyphenation")]
+fn matches_language(code: &str, language: &str) -> bool {
+    language.eq_ignore_ascii_case(code)
+        || language
+            .split('-')
+            .next()
+            .map(|primary| primary.eq_ignore_ascii_case(code))
+            .unwrap_or_default()
+}
+
+/// Returns whether the given word is a forced line break, see [`Newline::Break`][].
+///
+/// [`Newline::Break`]: ../enum.Newline.html#variant.Break
+pub fn is_newline(s: &str) -> bool {
+    s == "\n"
+}
+
+/// Applies the given newline treatment to a sequence of styled strings.
+///
+/// Carriage returns before newlines are always removed.  If the treatment is
+/// [`Newline::Space`][], newlines are replaced with spaces.
+///
+/// [`Newline::Space`]: ../enum.Newline.html#variant.Space
+pub fn apply_newlines(text: &mut [style::StyledString], newline: Newline) {
+    for s in text {
+        if s.s.contains("\r\n") {
+            s.s = s.s.replace("\r\n", "\n");
+        }
+        if newline == Newline::Space && s.s.contains('\n') {
+            s.s = s.s.replace('\n', " ");
+        }
+    }
+}
+
+/// Applies the given whitespace treatment to a sequence of styled strings.
+///
+/// Tabs are always replaced with spaces.  If the treatment is [`Whitespace::Collapse`][], runs of
+/// spaces are replaced with a single space, also across string borders, and spaces after a
+/// newline are removed.
+///
+/// [`Whitespace::Collapse`]: ../enum.Whitespace.html#variant.Collapse
+pub fn apply_whitespace(text: &mut [style::StyledString], whitespace: Whitespace) {
+    let mut is_space = false;
+    for s in text {
+        if !s.s.contains(&[' ', '\t'][..]) {
+            is_space = s.s.ends_with('\n') || (is_space && s.s.is_empty());
+            continue;
+        }
+        let mut new = String::with_capacity(s.s.len());
+        for c in s.s.chars() {
+            let c = if c == '\t' { ' ' } else { c };
+            if c != ' ' || !is_space || whitespace == Whitespace::Preserve {
+                new.push(c);
+            }
+            is_space = c == ' ' || c == '\n';
+        }
+        s.s = new;
+    }
+}
+
 /// Splits a sequence of styled strings into words.
 pub struct Words<I: Iterator<Item = style::StyledString>> {
     iter: I,
@@ -176,11 +562,21 @@ impl<I: Iterator<Item = style::StyledString>> Iterator for Words<I> {
         }
 
         if let Some(s) = &mut self.s {
-            // Split at the first space or use the complete string
-            let n = s.s.find(' ').map(|i| i + 1).unwrap_or_else(|| s.s.len());
+            // Split after the first run of spaces or before and after a newline, or use the
+            // complete string
+            let n = match s.s.find(&[' ', '\n'][..]) {
+                Some(0) if s.s.starts_with('\n') => 1,
+                Some(i) if s.s[i..].starts_with('\n') => i,
+                Some(i) => i + s.s[i..].bytes().take_while(|b| *b == b' ').count(),
+                None => s.s.len(),
+            };
             let mut tmp = s.s.split_off(n);
             mem::swap(&mut tmp, &mut s.s);
-            Some(style::StyledString::new(tmp, s.style))
+            Some(style::StyledString {
+                s: tmp,
+                style: s.style,
+                link: s.link.clone(),
+            })
         } else {
             None
         }
@@ -7,26 +7,36 @@ const FONT_DIRS: &[&str] = &[
     "/usr/share/fonts/liberation",
     "/usr/share/fonts/truetype/liberation",
 ];
-const DEFAULT_FONT_NAME: &'static str = "LiberationSans";
+const DEFAULT_FONT_NAME: &str = "LiberationSans";
 
-const LOREM_IPSUM: &'static str =
+const LOREM_IPSUM: &str =
     "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut \
     labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco \
     laboris nisi ut aliquip ex ea commodo consequat. Duis aute irure dolor in reprehenderit in \
     voluptate velit esse cillum dolore eu fugiat nulla pariatur. Excepteur sint occaecat cupidatat \
     non proident, sunt in culpa qui officia deserunt mollit anim id est laborum.";
 
+/// Returns the directory that contains the files of the default font family.
+fn font_dir() -> &'static str {
+    FONT_DIRS
+        .iter()
+        .find(|path| std::path::Path::new(path).exists())
+        .expect("Could not find font directory")
+}
+
+/// Loads the default font family from the font directory.
+///
+/// The tests always use the font files from the font directory, even if the `system-fonts` feature
+/// is enabled, so that the output does not depend on the font discovery.
+fn load_font_family(builtin: Option<fonts::Builtin>) -> fonts::FontFamily<fonts::FontData> {
+    fonts::from_files(font_dir(), DEFAULT_FONT_NAME, builtin)
+        .expect("Failed to load the default font family")
+}
+
 /// Creates a new document with the default font, minimal conformance and constant creation and
 /// modification dates.
 fn get_document() -> genpdf::Document {
-    let font_dir = FONT_DIRS
-        .iter()
-        .filter(|path| std::path::Path::new(path).exists())
-        .next()
-        .expect("Could not find font directory");
-    let default_font =
-        fonts::from_files(font_dir, DEFAULT_FONT_NAME, Some(fonts::Builtin::Helvetica))
-            .expect("Failed to load the default font family");
+    let default_font = load_font_family(Some(fonts::Builtin::Helvetica));
 
     let mut doc = genpdf::Document::new(default_font);
     doc.set_minimal_conformance();
@@ -35,12 +45,183 @@ fn get_document() -> genpdf::Document {
     doc
 }
 
+/// Returns the strings of all `TJ` operations of the pages of the given PDF document.
+///
+/// This only works for documents using built-in fonts with the `WinAnsiEncoding`.
+fn get_page_texts(output: &[u8]) -> Vec<Vec<String>> {
+    let pdf_doc = lopdf::Document::load_mem(output).expect("Failed to load document");
+    pdf_doc
+        .get_pages()
+        .values()
+        .map(|page_id| {
+            let content = pdf_doc.get_page_content(*page_id).unwrap();
+            lopdf::content::Content::decode(&content)
+                .unwrap()
+                .operations
+                .iter()
+                .filter(|operation| operation.operator == "TJ")
+                .map(|operation| {
+                    let bytes: Vec<u8> = operation.operands[0]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|operand| match operand {
+                            lopdf::Object::String(bytes, _) => Some(bytes.clone()),
+                            _ => None,
+                        })
+                        .flatten()
+                        .collect();
+                    lopdf::Document::decode_text(Some("WinAnsiEncoding"), &bytes)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the operations of the content streams of the page with the given index of the given
+/// PDF document.
+fn page_operations(output: &[u8], page: usize) -> Vec<lopdf::content::Operation> {
+    let pdf_doc = lopdf::Document::load_mem(output).expect("Failed to load document");
+    let page_id = *pdf_doc
+        .get_pages()
+        .values()
+        .nth(page)
+        .unwrap_or_else(|| panic!("Missing page {}", page));
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    lopdf::content::Content::decode(&content)
+        .unwrap()
+        .operations
+}
+
+/// Returns the numeric operands of the given operation in pt.  The elements of array operands,
+/// for example of the `d` operator, are included as separate operands.
+fn operands(operation: &lopdf::content::Operation) -> Vec<f64> {
+    operation
+        .operands
+        .iter()
+        .flat_map(|operand| match operand {
+            lopdf::Object::Array(values) => values.clone(),
+            _ => vec![operand.clone()],
+        })
+        .filter_map(|operand| {
+            operand
+                .as_i64()
+                .map(|i| i as f64)
+                .or_else(|_| operand.as_f64())
+                .ok()
+        })
+        .collect()
+}
+
+/// Returns the numeric operands of all operations with the given operator.
+fn operator_operands(operations: &[lopdf::content::Operation], operator: &str) -> Vec<Vec<f64>> {
+    operations
+        .iter()
+        .filter(|operation| operation.operator == operator)
+        .map(operands)
+        .collect()
+}
+
+/// Returns the number of operations with the given operator.
+fn count_operator(operations: &[lopdf::content::Operation], operator: &str) -> usize {
+    operations
+        .iter()
+        .filter(|operation| operation.operator == operator)
+        .count()
+}
+
+/// Returns the positions of all `Td` operations in pt.
+fn text_positions(operations: &[lopdf::content::Operation]) -> Vec<(f64, f64)> {
+    operator_operands(operations, "Td")
+        .into_iter()
+        .map(|operands| (operands[0], operands[1]))
+        .collect()
+}
+
+/// Converts the given length from millimeters to points.
+fn pt(mm: f64) -> f64 {
+    printpdf::Pt::from(printpdf::Mm(mm)).0
+}
+
+/// Converts the given length from points to millimeters.
+fn mm(pt: f64) -> f64 {
+    printpdf::Mm::from(printpdf::Pt(pt)).0
+}
+
+/// Asserts that the given values differ by less than the given tolerance.
+fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+    assert!(
+        (expected - actual).abs() < tolerance,
+        "{} != {} (tolerance {})",
+        expected,
+        actual,
+        tolerance
+    );
+}
+
+/// Returns the left and right edges of the text lines of the given page in mm.  The right edge is
+/// the end of the last character of the line that is not a space.
+///
+/// This only works for documents using the built-in Helvetica font family.  Every line must start
+/// with a `Td` operation.
+fn line_extents(output: &[u8], page: usize) -> Vec<(f64, f64)> {
+    let font_cache = fonts::FontCache::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    let font_family = font_cache.default_font_family();
+    let mut font = (font_family.regular, 12.0);
+    let mut cursor = 0.0;
+    let mut lines: Vec<(f64, f64)> = Vec::new();
+    for operation in page_operations(output, page) {
+        match operation.operator.as_str() {
+            "Td" => {
+                cursor = mm(operands(&operation)[0]);
+                lines.push((cursor, cursor));
+            }
+            "Tf" => {
+                let name = operation.operands[0].as_name_str().unwrap();
+                let variant = match name {
+                    "Helvetica" => font_family.regular,
+                    "Helvetica-Bold" => font_family.bold,
+                    "Helvetica-Oblique" => font_family.italic,
+                    "Helvetica-BoldOblique" => font_family.bold_italic,
+                    _ => panic!("Unexpected font {}", name),
+                };
+                font = (variant, operands(&operation)[0]);
+            }
+            "TJ" => {
+                let (variant, size) = font;
+                let line = lines.last_mut().expect("Missing text position");
+                for operand in operation.operands[0].as_array().unwrap() {
+                    if let lopdf::Object::String(bytes, _) = operand {
+                        let s = lopdf::Document::decode_text(Some("WinAnsiEncoding"), bytes);
+                        for c in s.chars() {
+                            let width = variant.char_width(&font_cache, c, size as u8);
+                            cursor += printpdf::Mm::from(width).0;
+                            if c != ' ' {
+                                line.1 = cursor;
+                            }
+                        }
+                    } else {
+                        let offset = operand
+                            .as_i64()
+                            .map(|i| i as f64)
+                            .or_else(|_| operand.as_f64())
+                            .unwrap();
+                        cursor -= mm(offset * size / 1000.0);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
 /// Compares the PDF file generated by the given document with the stored PDF file at
 /// `tests/files/<name>.pdf`.
-fn check(name: &str, doc: genpdf::Document) {
+fn check(name: &str, mut doc: genpdf::Document) {
     let expected_dir = std::path::Path::new("tests/files");
     if !expected_dir.exists() {
-        std::fs::create_dir(&expected_dir).expect("Failed to create expected directory");
+        std::fs::create_dir(expected_dir).expect("Failed to create expected directory");
     }
 
     let expected_path = expected_dir.join(name).with_extension("pdf");
@@ -107,11 +288,11 @@ test_with_document! {
     }
 
     #[test]
-    // Ignore as this currently returns an error
-    #[ignore]
     fn paragraph_long(doc: genpdf::Document) -> genpdf::Document {
         let mut doc = doc;
         doc.set_paper_size((50, 100));
+        // Without this, the word that is wider than the page causes an error.
+        doc.set_overflow(genpdf::Overflow::Break);
         doc.push(elements::Paragraph::new("Donaudampfschifffahrtskapitänsmützenhersteller"));
         doc
     }
@@ -255,3 +436,5230 @@ test_with_document! {
         doc
     }
 }
+
+#[test]
+fn table_dashed() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 50));
+    let mut decorator = genpdf::SimplePageDecorator::new();
+    decorator.set_margins(10);
+    doc.set_page_decorator(decorator);
+
+    let mut table = elements::TableLayout::new(vec![1, 1]);
+    table.set_cell_decorator(
+        elements::FrameCellDecorator::with_line_style(
+            true,
+            true,
+            false,
+            style::LineStyle::new().with_thickness(0.5),
+        )
+        .with_inner_line_style(
+            style::LineStyle::new()
+                .with_dash(&[1.0, 0.5])
+                .with_color(style::Color::Greyscale(128)),
+        ),
+    );
+    for row in 0..2 {
+        table
+            .row()
+            .element(elements::Paragraph::new(format!("Row {}", row)))
+            .element(elements::Paragraph::new("Lorem ipsum"))
+            .push()
+            .expect("invalid table row");
+    }
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let round = |pt: f64| (mm(pt) * 100.0).round() / 100.0;
+    // The initial stroke color is black.
+    let mut style = (0.0, Vec::new(), vec![0.0, 0.0, 0.0]);
+    let mut start = Vec::new();
+    let mut lines = Vec::new();
+    for operation in &page_operations(&output, 0) {
+        let operands = operands(operation);
+        match operation.operator.as_str() {
+            "w" => style.0 = round(operands[0]),
+            "d" => {
+                style.1 = operands[..operands.len() - 1]
+                    .iter()
+                    .copied()
+                    .map(round)
+                    .collect()
+            }
+            "G" | "RG" => style.2 = operands,
+            "m" => start = operands,
+            "l" => {
+                // The position of a vertical line is its x coordinate, the position of a
+                // horizontal line its y coordinate.
+                let is_vertical = start[0] == operands[0];
+                let position = if is_vertical { start[0] } else { start[1] };
+                lines.push((is_vertical, position, style.clone()));
+            }
+            _ => {}
+        }
+    }
+    let is_outer = |is_vertical: bool, position: f64| {
+        let positions = lines
+            .iter()
+            .filter(|line| line.0 == is_vertical)
+            .map(|line| line.1);
+        let min = positions.clone().fold(f64::MAX, f64::min);
+        let max = positions.fold(f64::MIN, f64::max);
+        position == min || position == max
+    };
+    let (outer, inner): (Vec<_>, Vec<_>) = lines
+        .iter()
+        .cloned()
+        .partition(|(is_vertical, position, _)| is_outer(*is_vertical, *position));
+
+    // The outer borders use the solid line style of the decorator, the inner borders use the
+    // dashed grey line style.
+    assert_eq!(8, outer.len());
+    for (_, _, style) in outer {
+        assert_eq!((0.5, Vec::new(), vec![0.0, 0.0, 0.0]), style);
+    }
+    assert_eq!(4, inner.len());
+    for (_, _, style) in inner {
+        assert_eq!((0.1, vec![1.0, 0.5], vec![0.5]), style);
+    }
+}
+
+#[test]
+fn table_no_wrap() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 80));
+    let mut decorator = genpdf::SimplePageDecorator::new();
+    decorator.set_margins(10);
+    doc.set_page_decorator(decorator);
+
+    let mut table =
+        elements::TableLayout::new(vec![1, 3]).with_column_wrap(0, style::Wrap::Ellipsis);
+    table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+    table
+        .row()
+        .element(elements::Paragraph::new("ID-2021-0042-ABCDEF"))
+        .element(elements::Paragraph::new(LOREM_IPSUM))
+        .push()
+        .expect("invalid table row");
+    table
+        .row()
+        .element(
+            elements::Paragraph::new("ID-2021-0043-ABCDEF")
+                .styled(style::Style::new().with_wrap(style::Wrap::Clip)),
+        )
+        .element(elements::Paragraph::new("Lorem ipsum"))
+        .push()
+        .expect("invalid table row");
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let texts = get_page_texts(&output);
+    assert_eq!(2, texts.len());
+    // The first column is truncated with an ellipsis, the cell with the clip wrapping mode is cut
+    // off at the column border.
+    assert_eq!("ID-202…", texts[0][0]);
+    assert_eq!(
+        vec!["laborum.", "ID-2021-", "Lorem ", "ipsum"],
+        texts[1][texts[1].len() - 4..].to_vec()
+    );
+    // Both cells of the first column have a single line, while the long paragraph in the second
+    // column is wrapped and continued on the second page.
+    for page in 0..2 {
+        let positions = text_positions(&page_operations(&output, page));
+        let first_column = positions.iter().filter(|(x, _)| *x < pt(20.0)).count();
+        assert_eq!(1, first_column);
+        assert!(positions.len() > 5);
+    }
+}
+
+#[test]
+fn dashed_line_operations() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 20));
+    doc.push(
+        elements::Paragraph::new("Lorem ipsum")
+            .framed(style::LineStyle::new().with_dash(&[1.0, 0.5])),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+    let content = pdf_doc
+        .get_and_decode_page_content(page_id)
+        .expect("Failed to decode page content");
+    let dash = content
+        .operations
+        .iter()
+        .find(|op| op.operator == "d")
+        .expect("Missing dash operation");
+    let lengths = dash.operands[0].as_array().expect("Invalid dash array");
+    assert_eq!(2, lengths.len());
+    assert!(lengths.iter().all(|l| matches!(l, lopdf::Object::Real(_))));
+}
+
+#[test]
+fn error_location() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 100));
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+    doc.push(elements::Paragraph::new(
+        "Donaudampfschifffahrtskapitänsmützenhersteller",
+    ));
+
+    let err = doc
+        .render(&mut Vec::new())
+        .expect_err("Rendering an overflowing paragraph should fail");
+    assert!(matches!(
+        err.kind(),
+        genpdf::error::ErrorKind::PageSizeExceeded
+    ));
+    match err.location() {
+        Some(genpdf::error::Location::Element { page, elements }) => {
+            assert_eq!(Some(1), *page);
+            assert_eq!(
+                vec![genpdf::error::ElementLocation {
+                    index: 1,
+                    type_name: "genpdf::elements::Paragraph",
+                }],
+                *elements
+            );
+        }
+        location => panic!("Unexpected error location: {:?}", location),
+    }
+    assert!(err
+        .to_string()
+        .ends_with(" (page 1, element 1 (genpdf::elements::Paragraph))"));
+}
+
+#[test]
+fn error_location_page_decorator() {
+    struct FailingDecorator;
+
+    impl genpdf::PageDecorator for FailingDecorator {
+        fn decorate_page<'a>(
+            &mut self,
+            context: &genpdf::Context,
+            area: genpdf::render::Area<'a>,
+            _style: style::Style,
+        ) -> Result<genpdf::render::Area<'a>, genpdf::error::Error> {
+            if context.page_number() > 1 {
+                Err(genpdf::error::Error::new(
+                    "Failed to decorate page",
+                    genpdf::error::ErrorKind::Internal,
+                ))
+            } else {
+                Ok(area)
+            }
+        }
+    }
+
+    let mut doc = get_document();
+    doc.set_paper_size((50, 20));
+    doc.set_page_decorator(FailingDecorator);
+    for _ in 0..5 {
+        doc.push(elements::Paragraph::new("Lorem ipsum"));
+    }
+
+    let err = doc
+        .render(&mut Vec::new())
+        .expect_err("Rendering with a failing page decorator should fail");
+    assert_eq!(Some(&genpdf::error::Location::Page(2)), err.location());
+}
+
+#[test]
+fn push_after_render() {
+    let mut doc = get_document();
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+    assert!(!doc.is_rendered());
+    doc.render(&mut Vec::new())
+        .expect("Failed to render document");
+    assert!(doc.is_rendered());
+
+    doc.push(elements::Paragraph::new("dolor sit amet"));
+    let mut output = Vec::new();
+    let err = doc
+        .render(&mut output)
+        .expect_err("Rendering a document twice should fail");
+    assert!(matches!(
+        err.kind(),
+        genpdf::error::ErrorKind::AlreadyRendered
+    ));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn opacity() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 40));
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+    doc.push(elements::Paragraph::new("Lorem ipsum").with_opacity(0.5));
+    let mut layout = elements::LinearLayout::vertical();
+    layout.push(elements::Paragraph::new("Lorem ipsum"));
+    layout.push(
+        elements::Paragraph::new("Lorem ipsum")
+            .framed(style::LineStyle::new())
+            .with_opacity(0.5),
+    );
+    doc.push(layout.with_opacity(0.5));
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+    let states = pdf_doc
+        .get_dictionary(page_id)
+        .and_then(|page| page.get(b"Resources"))
+        .and_then(|resources| pdf_doc.dereference(resources))
+        .and_then(|(_, resources)| resources.as_dict())
+        .and_then(|resources| resources.get(b"ExtGState"))
+        .and_then(|states| pdf_doc.dereference(states))
+        .and_then(|(_, states)| states.as_dict())
+        .expect("Missing graphics states");
+    let get_opacity = |name: &[u8]| {
+        let state = states
+            .get(name)
+            .and_then(lopdf::Object::as_dict)
+            .expect("Missing graphics state");
+        let fill = state.get(b"ca").and_then(lopdf::Object::as_f64).unwrap();
+        let stroke = state.get(b"CA").and_then(lopdf::Object::as_f64).unwrap();
+        assert_eq!(fill, stroke);
+        fill
+    };
+
+    // The opacity of every text and stroke operation, taking into account that the graphics
+    // state is saved and restored.
+    let mut stack = vec![1.0];
+    let mut opacities = Vec::new();
+    for operation in page_operations(&output, 0) {
+        match operation.operator.as_str() {
+            "q" => stack.push(*stack.last().unwrap()),
+            "Q" => {
+                stack.pop();
+            }
+            "gs" => {
+                *stack.last_mut().unwrap() = get_opacity(operation.operands[0].as_name().unwrap())
+            }
+            "TJ" | "S" => opacities.push((operation.operator, *stack.last().unwrap())),
+            _ => {}
+        }
+    }
+    let text = |opacity: f64| vec![("TJ".to_owned(), opacity); 2];
+    let mut expected = vec![text(1.0), text(0.5), text(0.5), text(0.25)];
+    // The frame has the same opacity as the framed paragraph.
+    expected.push(vec![("S".to_owned(), 0.25); 2]);
+    expected.push(text(1.0));
+    assert_eq!(expected.concat(), opacities);
+}
+
+#[test]
+fn nested_opacity() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 20));
+    doc.push(
+        elements::Paragraph::new("Lorem ipsum")
+            .with_opacity(0.5)
+            .with_opacity(0.5),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+    let content = pdf_doc
+        .get_and_decode_page_content(page_id)
+        .expect("Failed to decode page content");
+    let names: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|op| op.operator == "gs")
+        .map(|op| {
+            op.operands[0]
+                .as_name_str()
+                .expect("Invalid graphics state name")
+        })
+        .collect();
+    assert_eq!(2, names.len());
+
+    let states = pdf_doc
+        .get_dictionary(page_id)
+        .and_then(|page| page.get(b"Resources"))
+        .and_then(|resources| pdf_doc.dereference(resources))
+        .and_then(|(_, resources)| resources.as_dict())
+        .and_then(|resources| resources.get(b"ExtGState"))
+        .and_then(|states| pdf_doc.dereference(states))
+        .and_then(|(_, states)| states.as_dict())
+        .expect("Missing graphics states");
+    let state = states
+        .get(names[1].as_bytes())
+        .and_then(lopdf::Object::as_dict)
+        .expect("Missing graphics state");
+    let opacity = state
+        .get(b"ca")
+        .and_then(lopdf::Object::as_f64)
+        .expect("Missing opacity");
+    assert!((opacity - 0.25).abs() < 1e-6);
+}
+
+#[test]
+fn table_continued() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 60));
+    let mut decorator = genpdf::SimplePageDecorator::new();
+    decorator.set_margins(10);
+    doc.set_page_decorator(decorator);
+
+    let mut table = elements::TableLayout::new(vec![1, 3]);
+    table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, true));
+    for row in 0..8 {
+        table
+            .row()
+            .element(elements::Paragraph::new(format!("Row {}", row)))
+            .element(elements::Paragraph::new("Lorem ipsum"))
+            .push()
+            .expect("invalid table row");
+    }
+    doc.push(elements::ContinuedElement::new(table));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let texts = get_page_texts(&output);
+    let row = |i: usize| {
+        vec![
+            String::from("Row "),
+            i.to_string(),
+            "Lorem ".into(),
+            "ipsum".into(),
+        ]
+    };
+    let mut first_page: Vec<_> = (0..6).flat_map(row).collect();
+    first_page.extend(
+        vec!["(continued ", "on ", "next ", "page)"]
+            .into_iter()
+            .map(String::from),
+    );
+    let mut second_page = vec![String::from("(continued)")];
+    second_page.extend((6..8).flat_map(row));
+    assert_eq!(vec![first_page, second_page], texts);
+
+    // The bottom marker is printed right-aligned within the page margins, and the top marker is
+    // printed above the first row on the next page.
+    let positions = text_positions(&page_operations(&output, 0));
+    let (marker_x, marker_y) = positions[positions.len() - 1];
+    let (_, last_row_y) = positions[positions.len() - 2];
+    assert!(mm(marker_x) > 30.0);
+    assert!(mm(marker_y) > 10.0);
+    assert!(marker_y < last_row_y);
+    let positions = text_positions(&page_operations(&output, 1));
+    assert!(positions[0].1 > positions[1].1);
+}
+
+#[test]
+fn continued_without_split() {
+    fn render(element: impl genpdf::Element + 'static) -> Vec<u8> {
+        let mut doc = get_document();
+        doc.set_paper_size((50, 20));
+        doc.push(element);
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        assert_eq!(1, pdf_doc.get_pages().len());
+        let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+        pdf_doc
+            .get_page_content(page_id)
+            .expect("Failed to read page content")
+    }
+
+    assert_eq!(
+        render(elements::Paragraph::new("Lorem ipsum")),
+        render(elements::ContinuedElement::new(elements::Paragraph::new(
+            "Lorem ipsum"
+        ))),
+    );
+}
+
+#[test]
+fn font_families() {
+    let load_font = || load_font_family(None);
+
+    let mut doc = get_document();
+    doc.set_paper_size((50, 20));
+    let embedded = doc.add_font_family(load_font());
+    // The font data is identical, so the fonts should not be embedded again.
+    let embedded_again = doc.add_font_family(load_font());
+    doc.push(
+        elements::Paragraph::new("Lorem ")
+            .styled_string("ipsum", embedded)
+            .styled_string(" dolor", embedded_again),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let embedded_fonts = pdf_doc
+        .objects
+        .values()
+        .filter_map(|object| object.as_dict().ok())
+        .filter(|dict| dict.has(b"FontFile2"))
+        .count();
+    assert_eq!(4, embedded_fonts);
+
+    let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+    let content = pdf_doc
+        .get_and_decode_page_content(page_id)
+        .expect("Failed to decode page content");
+    let fonts: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|op| op.operator == "Tf")
+        .map(|op| op.operands[0].as_name_str().expect("Invalid font name"))
+        .collect();
+    assert_eq!(2, fonts.len());
+    assert_ne!(fonts[0], fonts[1]);
+}
+
+#[test]
+fn page_boxes() {
+    let mut doc = get_document();
+    doc.set_paper_size((110, 80));
+    doc.set_page_boxes(genpdf::PageBoxes::new().with_trim_box((5, 5), (100, 70)));
+    doc.set_page_boxes_for_page(
+        2,
+        genpdf::PageBoxes::new()
+            .with_crop_box((2, 2), (106, 76))
+            .with_trim_box((5, 10), (100, 60)),
+    );
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+    doc.push(elements::PageBreak::new());
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let get_box = |page_id, name: &[u8]| -> Vec<f64> {
+        pdf_doc
+            .get_dictionary(page_id)
+            .and_then(|page| page.get(name))
+            .and_then(lopdf::Object::as_array)
+            .expect("Missing page box")
+            .iter()
+            .map(|value| {
+                let value = value.as_f64().expect("Invalid page box");
+                (printpdf::Mm::from(printpdf::Pt(value)).0).round()
+            })
+            .collect()
+    };
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    assert_eq!(2, pages.len());
+    assert_eq!(vec![0.0, 0.0, 110.0, 80.0], get_box(pages[0], b"MediaBox"));
+    assert_eq!(vec![0.0, 0.0, 110.0, 80.0], get_box(pages[0], b"CropBox"));
+    assert_eq!(vec![5.0, 5.0, 105.0, 75.0], get_box(pages[0], b"TrimBox"));
+    assert_eq!(vec![0.0, 0.0, 110.0, 80.0], get_box(pages[1], b"MediaBox"));
+    assert_eq!(vec![2.0, 2.0, 108.0, 78.0], get_box(pages[1], b"CropBox"));
+    assert_eq!(vec![5.0, 10.0, 105.0, 70.0], get_box(pages[1], b"TrimBox"));
+}
+
+#[test]
+fn invalid_page_boxes() {
+    let mut doc = get_document();
+    doc.set_paper_size((110, 80));
+    doc.set_page_boxes(
+        genpdf::PageBoxes::new()
+            .with_crop_box((5, 5), (100, 70))
+            .with_trim_box((0, 0), (100, 70)),
+    );
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+
+    let err = doc
+        .render(&mut Vec::new())
+        .expect_err("Rendering a page with invalid boxes should fail");
+    assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData));
+    assert_eq!(Some(1), err.location().and_then(|location| location.page()));
+}
+
+#[test]
+fn headings() {
+    let mut doc = get_document();
+    doc.set_heading_numbering(true);
+    doc.push(elements::Heading::new(1, "Introduction"));
+    doc.push(elements::Heading::new(2, "Motivation"));
+    doc.push(elements::Heading::new(2, "Overview"));
+    doc.push(elements::Heading::new(3, "Notes").numbered(false));
+    doc.push(elements::PageBreak::new());
+    doc.push(elements::Heading::new(1, "Design"));
+    doc.push(elements::Heading::new(2, "Layout"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    let get_ref = |dict: &lopdf::Dictionary, key: &[u8]| {
+        dict.get(key)
+            .and_then(lopdf::Object::as_reference)
+            .and_then(|id| pdf_doc.get_dictionary(id))
+            .ok()
+    };
+    let catalog = get_ref(&pdf_doc.trailer, b"Root").expect("Missing catalog");
+    let outlines = get_ref(catalog, b"Outlines").expect("Missing outlines");
+
+    let mut entries = Vec::new();
+    let mut stack = vec![(0, get_ref(outlines, b"First"))];
+    while let Some((depth, item)) = stack.pop() {
+        if let Some(item) = item {
+            let title = item.get(b"Title").and_then(lopdf::Object::as_str).unwrap();
+            let dest = item.get(b"Dest").and_then(lopdf::Object::as_array).unwrap();
+            let page = pages
+                .iter()
+                .position(|id| *id == dest[0].as_reference().unwrap());
+            entries.push((depth, String::from_utf8_lossy(title).into_owned(), page));
+            stack.push((depth, get_ref(item, b"Next")));
+            stack.push((depth + 1, get_ref(item, b"First")));
+        }
+    }
+    assert_eq!(
+        vec![
+            (0, "1 Introduction".to_owned(), Some(0)),
+            (1, "1.1 Motivation".to_owned(), Some(0)),
+            (1, "1.2 Overview".to_owned(), Some(0)),
+            (2, "Notes".to_owned(), Some(0)),
+            (0, "2 Design".to_owned(), Some(1)),
+            (1, "2.1 Layout".to_owned(), Some(1)),
+        ],
+        entries
+    );
+}
+
+#[test]
+fn vertical_alignment() {
+    let get_offset = |alignment| {
+        let mut doc = get_document();
+        doc.set_paper_size((100, 100));
+        doc.set_vertical_alignment(alignment);
+        doc.push(elements::Paragraph::new("Lorem ipsum"));
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let operation = content
+            .operations
+            .iter()
+            .find(|operation| operation.operator == "cm")
+            .expect("Missing transformation");
+        printpdf::Mm::from(printpdf::Pt(operation.operands[5].as_f64().unwrap())).0
+    };
+    let center = get_offset(genpdf::VerticalAlignment::Center);
+    let bottom = get_offset(genpdf::VerticalAlignment::Bottom);
+    assert!(center < -40.0 && center > -50.0);
+    assert!((bottom - 2.0 * center).abs() < 0.01);
+}
+
+#[test]
+fn missing_glyphs() {
+    let font_family = load_font_family(None);
+
+    let mut doc = get_document();
+    let embedded = doc.add_font_family(font_family);
+    doc.push(elements::Paragraph::new("Lorem ipsum").styled(embedded));
+    doc.push(elements::Paragraph::new("Lorem \u{e000} ipsum \u{e001}\u{e000}").styled(embedded));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let missing: Vec<_> = doc.missing_glyphs().into_iter().collect();
+    assert_eq!(vec!['\u{e000}', '\u{e001}'], missing);
+}
+
+/// Renders a paragraph into two boxes that are side by side.
+struct TwoBoxes(Option<elements::Paragraph>);
+
+impl genpdf::Element for TwoBoxes {
+    fn render(
+        &mut self,
+        context: &genpdf::Context,
+        area: genpdf::render::Area<'_>,
+        style: style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        let height = genpdf::Mm::from(20);
+        let mut result = genpdf::RenderResult::default();
+        for area in area.split_horizontally(&[1, 1]) {
+            if let Some(paragraph) = self.0.take() {
+                let (box_result, remainder) =
+                    paragraph.render_bounded(context, area, style, height)?;
+                assert!(box_result.size.height <= height);
+                result.size.height = result.size.height.max(box_result.size.height);
+                self.0 = remainder;
+            }
+        }
+        result.has_more = self.0.is_some();
+        Ok(result)
+    }
+
+    fn measure(
+        &mut self,
+        context: &genpdf::Context,
+        area: genpdf::render::Area<'_>,
+        style: style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        let height = genpdf::Mm::from(20);
+        let mut result = genpdf::RenderResult::default();
+        if let Some(paragraph) = &self.0 {
+            // The paragraph flows from the first box into the second one, so the boxes are as
+            // high as the first box.
+            let box_area = area.split_horizontally(&[1, 1]).remove(0);
+            let total_height = paragraph
+                .clone()
+                .measure(context, box_area, style)?
+                .size
+                .height;
+            result.size.width = area.size().width;
+            result.size.height = total_height.min(height);
+            result.has_more = total_height > height * 2.0;
+        }
+        Ok(result)
+    }
+}
+
+#[test]
+fn paragraph_justified() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((100, 150));
+    doc.push(elements::Paragraph::new(LOREM_IPSUM).aligned(Alignment::Justify));
+    doc.push(elements::Break::new(1));
+    doc.push(
+        elements::Paragraph::new(LOREM_IPSUM)
+            .aligned(Alignment::Justify)
+            .with_last_line_alignment(Alignment::Center),
+    );
+    doc.push(elements::Break::new(1));
+    doc.push(
+        elements::Paragraph::new("Lorem ipsum dolor sit amet, ")
+            .styled_string("consectetur", style::Effect::Bold)
+            .string(" adipiscing elit, sed do eiusmod")
+            .aligned(Alignment::Justify)
+            .with_last_line_alignment(Alignment::Justify),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let lines = line_extents(&output, 0);
+    assert_eq!(20, lines.len());
+    // All lines but the last line of a paragraph are stretched to the full width.
+    for &(left, right) in lines[0..8]
+        .iter()
+        .chain(&lines[9..17])
+        .chain(&lines[18..19])
+    {
+        assert_close(0.0, left, 0.01);
+        assert_close(100.0, right, 0.05);
+    }
+    // The last lines of the first two paragraphs use the default left alignment and the center
+    // alignment.  The last line of the third paragraph is justified too.
+    assert_close(0.0, lines[8].0, 0.01);
+    assert!(lines[8].1 < 90.0);
+    assert!(lines[17].0 > 10.0);
+    assert_close(50.0, (lines[17].0 + lines[17].1) / 2.0, 0.05);
+}
+
+#[test]
+fn paragraph_optimal() {
+    let render = |alignment: Alignment| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((80, 150));
+        doc.push(elements::Paragraph::new(LOREM_IPSUM).aligned(alignment));
+        doc.push(elements::Break::new(1));
+        doc.push(
+            elements::Paragraph::new(LOREM_IPSUM)
+                .aligned(alignment)
+                .with_line_breaking(genpdf::LineBreaking::Optimal),
+        );
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        output
+    };
+    // Both paragraphs have eleven lines, and all lines but the last ones are justified.
+    let lines = line_extents(&render(Alignment::Justify), 0);
+    assert_eq!(22, lines.len());
+    for &(left, right) in lines[0..10].iter().chain(&lines[11..21]) {
+        assert_close(0.0, left, 0.01);
+        assert_close(80.0, right, 0.05);
+    }
+
+    // Without justification, the optimal line breaks avoid the loose line of the greedy line
+    // breaks.
+    let lines = line_extents(&render(Alignment::Left), 0);
+    let max_slack = |lines: &[(f64, f64)]| {
+        lines
+            .iter()
+            .map(|(_, right)| 80.0 - right)
+            .fold(0.0, f64::max)
+    };
+    let greedy = max_slack(&lines[0..10]);
+    let optimal = max_slack(&lines[11..21]);
+    assert!(greedy > 15.0);
+    assert!(optimal < 13.0);
+}
+
+#[test]
+fn paragraph_render_bounded() {
+    let mut doc = get_document();
+    doc.push(MeasuredElement(TwoBoxes(Some(elements::Paragraph::new(
+        LOREM_IPSUM,
+    )))));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    let mut text = String::new();
+    let mut cursors = Vec::new();
+    for page_id in pages {
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        for operation in content.operations {
+            match operation.operator.as_str() {
+                "Td" => cursors.push(operation.operands[0].as_f64().unwrap()),
+                "TJ" => {
+                    for operand in operation.operands[0].as_array().unwrap() {
+                        if let lopdf::Object::String(bytes, _) = operand {
+                            // The built-in font uses one byte per character
+                            text.extend(bytes.iter().copied().map(char::from));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    assert_eq!(LOREM_IPSUM, text.trim_end());
+    // The text is split into two columns
+    assert!(cursors
+        .iter()
+        .any(|x| *x > printpdf::Pt::from(printpdf::Mm(100.0)).0));
+}
+
+#[test]
+fn whitespace() {
+    fn render(element: impl genpdf::Element + 'static) -> Vec<u8> {
+        let mut doc = get_document();
+        doc.set_paper_size((50, 20));
+        doc.push(element);
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+        pdf_doc
+            .get_page_content(page_id)
+            .expect("Failed to read page content")
+    }
+
+    let collapsed = render(elements::Paragraph::new("Lorem ipsum"));
+    assert_eq!(
+        collapsed,
+        render(elements::Paragraph::new("Lorem  \t ").string(" ipsum"))
+    );
+    assert_eq!(
+        render(
+            elements::Paragraph::new("Lorem  \t ipsum")
+                .with_whitespace(genpdf::Whitespace::Preserve)
+        ),
+        render(
+            elements::Paragraph::new("Lorem    ipsum")
+                .with_whitespace(genpdf::Whitespace::Preserve)
+        ),
+    );
+    assert_ne!(
+        collapsed,
+        render(
+            elements::Paragraph::new("Lorem  ipsum").with_whitespace(genpdf::Whitespace::Preserve)
+        )
+    );
+    assert_eq!(
+        render(elements::Text::new("Lorem ipsum")),
+        render(elements::Text::new("Lorem    ipsum"))
+    );
+    assert_ne!(
+        render(elements::Text::new("Lorem ipsum")),
+        render(elements::Text::new("Lorem    ipsum").with_whitespace(genpdf::Whitespace::Preserve))
+    );
+}
+
+#[test]
+fn thumbnail() {
+    let data = vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+    assert!(genpdf::Thumbnail::from_rgb(2, 2, &data[..9]).is_err());
+    assert!(genpdf::Thumbnail::from_rgb(0, 2, Vec::new()).is_err());
+
+    let mut doc = get_document();
+    doc.set_thumbnail(genpdf::Thumbnail::from_rgb(2, 2, data.clone()).unwrap());
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+    doc.push(elements::PageBreak::new());
+    doc.push(elements::Paragraph::new("Lorem ipsum"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    let get_thumbnail = |page_id| {
+        pdf_doc
+            .get_dictionary(page_id)
+            .unwrap()
+            .get(b"Thumb")
+            .and_then(lopdf::Object::as_reference)
+            .and_then(|id| pdf_doc.get_object(id))
+            .and_then(lopdf::Object::as_stream)
+    };
+    let thumbnail = get_thumbnail(pages[0]).expect("Missing thumbnail");
+    assert_eq!(
+        2,
+        thumbnail
+            .dict
+            .get(b"Width")
+            .and_then(lopdf::Object::as_i64)
+            .unwrap()
+    );
+    assert_eq!(
+        2,
+        thumbnail
+            .dict
+            .get(b"Height")
+            .and_then(lopdf::Object::as_i64)
+            .unwrap()
+    );
+    assert_eq!(data, thumbnail.content);
+    assert!(get_thumbnail(pages[1]).is_err());
+}
+
+#[test]
+fn object_streams() {
+    let render = |use_object_streams| {
+        let mut doc = get_document();
+        doc.set_object_streams(use_object_streams);
+        doc.push(elements::Heading::new(1, "Lorem ipsum"));
+        doc.push(elements::Paragraph::new(LOREM_IPSUM));
+        doc.push(elements::PageBreak::new());
+        doc.push(elements::Heading::new(1, "Dolor sit amet"));
+        doc.push(elements::Paragraph::new(LOREM_IPSUM));
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        output
+    };
+    let get_contents = |data: &[u8]| -> Vec<Vec<u8>> {
+        let pdf_doc = lopdf::Document::load_mem(data).expect("Failed to load document");
+        pdf_doc
+            .get_pages()
+            .values()
+            .map(|page_id| pdf_doc.get_page_content(*page_id).unwrap())
+            .collect()
+    };
+    let contains = |data: &[u8], s: &[u8]| data.windows(s.len()).any(|w| w == s);
+
+    let plain = render(false);
+    let packed = render(true);
+    assert!(packed.starts_with(b"%PDF-1.5"));
+    assert!(contains(&packed, b"/ObjStm"));
+    assert!(contains(&packed, b"/XRef"));
+    assert!(!contains(&plain, b"/ObjStm"));
+    assert!(packed.len() < plain.len());
+
+    let contents = get_contents(&packed);
+    assert_eq!(2, contents.len());
+    assert_eq!(get_contents(&plain), contents);
+}
+
+#[test]
+fn table_line_spacing() {
+    fn get_row_height(table: elements::TableLayout) -> f64 {
+        let mut doc = get_document();
+        doc.set_line_spacing(1.5);
+        doc.push(table);
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let y: Vec<_> = content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "m" || operation.operator == "l")
+            .map(|operation| operation.operands[1].as_f64().unwrap())
+            .collect();
+        let max = y.iter().copied().fold(f64::MIN, f64::max);
+        let min = y.iter().copied().fold(f64::MAX, f64::min);
+        max - min
+    }
+    fn get_table() -> elements::TableLayout {
+        let mut table = elements::TableLayout::new(vec![1, 3]);
+        table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, true));
+        table
+            .row()
+            .element(elements::Paragraph::new("Lorem ipsum dolor sit amet"))
+            .element(elements::Paragraph::new("Lorem"))
+            .push()
+            .unwrap();
+        table
+    }
+
+    let default = get_row_height(get_table());
+    let compact = get_row_height(get_table().with_line_spacing(1.0));
+    assert!((compact / default - 1.0 / 1.5).abs() < 0.01);
+
+    let mut table = elements::TableLayout::new(vec![1, 3]);
+    table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, true));
+    table
+        .row()
+        .element(
+            elements::Paragraph::new("Lorem ipsum dolor sit amet")
+                .styled(style::Style::new().with_line_spacing(1.0)),
+        )
+        .element(elements::Paragraph::new("Lorem"))
+        .push()
+        .unwrap();
+    assert!((get_row_height(table) - compact).abs() < 0.01);
+}
+
+#[test]
+fn signature_lines() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 30));
+    let mut table = elements::TableLayout::new(vec![1, 1]);
+    table
+        .row()
+        .element(elements::SignatureLine::new(40).with_label("Signature"))
+        .element(elements::SignatureLine::new(80).with_space(5).with_label(
+            style::StyledString::new("Date", style::Style::new().italic()),
+        ))
+        .push()
+        .unwrap();
+    doc.push(table);
+    // Does not fit on the first page
+    doc.push(
+        elements::SignatureLine::new(30)
+            .with_space(20)
+            .with_label("Signature"),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    assert_eq!(2, pages.len());
+    let get_lines = |page_id| -> Vec<Vec<f64>> {
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "m" || operation.operator == "l")
+            .map(|operation| {
+                operation
+                    .operands
+                    .iter()
+                    .map(|operand| printpdf::Mm::from(printpdf::Pt(operand.as_f64().unwrap())).0)
+                    .map(f64::round)
+                    .collect()
+            })
+            .collect()
+    };
+    // The second line is shortened to the column width
+    assert_eq!(
+        vec![
+            vec![0.0, 20.0],
+            vec![40.0, 20.0],
+            vec![50.0, 25.0],
+            vec![100.0, 25.0]
+        ],
+        get_lines(pages[0])
+    );
+    assert_eq!(vec![vec![0.0, 10.0], vec![30.0, 10.0]], get_lines(pages[1]));
+}
+
+#[test]
+fn overflow() {
+    let render = |overflow: Option<genpdf::Overflow>, width: f64| {
+        let mut doc = get_document();
+        doc.set_paper_size((50, 40));
+        if let Some(overflow) = overflow {
+            doc.set_overflow(overflow);
+        }
+        doc.push(elements::Paragraph::new("Lorem ipsum").padded((0, 50.0 - width, 0, 0)));
+        let mut output = Vec::new();
+        doc.render(&mut output).map(|_| {
+            let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+            let page_id = *pdf_doc.get_pages().values().next().unwrap();
+            let content = pdf_doc.get_page_content(page_id).unwrap();
+            let content = lopdf::content::Content::decode(&content).unwrap();
+            content
+                .operations
+                .into_iter()
+                .map(|operation| operation.operator)
+                .filter(|operator| operator == "W" || operator == "TJ" || operator == "Tj")
+                .collect::<Vec<_>>()
+        })
+    };
+
+    for width in &[0.0, 1.0] {
+        let err = render(None, *width).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            genpdf::error::ErrorKind::PageSizeExceeded
+        ));
+        assert!(render(Some(genpdf::Overflow::Skip), *width)
+            .unwrap()
+            .is_empty());
+    }
+    assert!(render(Some(genpdf::Overflow::Clip), 0.0)
+        .unwrap()
+        .is_empty());
+    let operators = render(Some(genpdf::Overflow::Clip), 1.0).unwrap();
+    assert_eq!(Some("W"), operators.first().map(String::as_str));
+    assert!(operators.len() > 1);
+}
+
+#[test]
+fn repeat() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 30));
+    doc.push(elements::Repeat::cloned(
+        elements::SignatureLine::new(20).with_space(8),
+        5,
+    ));
+    doc.push(elements::Repeat::new(2, |i| {
+        elements::Paragraph::new(format!("Row {}", i))
+    }));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    assert_eq!(2, pages.len());
+    let count = |page_id, operator: &str| {
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == operator)
+            .count()
+    };
+    // Three lines fit on the first page
+    assert_eq!(3, count(pages[0], "l"));
+    assert_eq!(2, count(pages[1], "l"));
+    assert_eq!(0, count(pages[0], "BT"));
+    assert_eq!(2, count(pages[1], "BT"));
+}
+
+#[test]
+fn fractions() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 40));
+    doc.push(elements::Fraction::new(1, 2).with_whole(3));
+    doc.push(elements::Fraction::new(3, 4).with_layout(elements::FractionLayout::Diagonal));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let count = |operator: &str| {
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == operator)
+            .count()
+    };
+    // Only the stacked fraction has a bar
+    assert_eq!(1, count("l"));
+    assert_eq!(6, count("BT"));
+    let font_sizes: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "Tf")
+        .map(|operation| operation.operands[1].as_f64().unwrap())
+        .collect();
+    assert_eq!(vec![12.0, 7.0, 7.0, 7.0, 7.0, 12.0], font_sizes);
+}
+
+#[test]
+fn default_alignment() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 50));
+    doc.set_default_alignment(Alignment::Right);
+    doc.push(elements::Paragraph::new("right"));
+    doc.push(elements::Paragraph::new("left").aligned(Alignment::Left));
+    doc.push(
+        elements::Paragraph::new("center")
+            .styled(style::Style::new().with_alignment(Alignment::Center)),
+    );
+    doc.push(elements::Heading::new(2, "right"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let cursors: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "Td")
+        .map(|operation| {
+            printpdf::Mm::from(printpdf::Pt(operation.operands[0].as_f64().unwrap())).0
+        })
+        .collect();
+    assert_eq!(4, cursors.len());
+    assert!(cursors[0] > 80.0);
+    assert!(cursors[1] < 1.0);
+    assert!(cursors[2] > 40.0 && cursors[2] < 50.0);
+    assert!(cursors[3] > 80.0);
+}
+
+#[test]
+fn table_right_to_left() {
+    let mut doc = get_document();
+    doc.set_paper_size((90, 50));
+    let mut table = elements::TableLayout::new(vec![1, 2, 3]).with_right_to_left(true);
+    table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+    table
+        .row()
+        .element(elements::Paragraph::new("A"))
+        .element(elements::Paragraph::new("B"))
+        .element(elements::Paragraph::new("C"))
+        .push()
+        .unwrap();
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let mut cursor = 0.0;
+    let mut texts = Vec::new();
+    let mut lines = Vec::new();
+    for operation in content.operations {
+        match operation.operator.as_str() {
+            "Td" => {
+                cursor = printpdf::Mm::from(printpdf::Pt(operation.operands[0].as_f64().unwrap())).0
+            }
+            "TJ" => {
+                for operand in operation.operands[0].as_array().unwrap() {
+                    if let lopdf::Object::String(bytes, _) = operand {
+                        texts.push((char::from(bytes[0]), cursor.round()));
+                    }
+                }
+            }
+            "m" => lines.push(
+                printpdf::Mm::from(printpdf::Pt(operation.operands[0].as_f64().unwrap()))
+                    .0
+                    .round(),
+            ),
+            _ => {}
+        }
+    }
+    // The first column is the rightmost column with a width of 15 mm
+    assert_eq!(vec![('A', 75.0), ('B', 45.0), ('C', 0.0)], texts);
+    // The vertical borders are drawn at the column borders
+    for x in &[0.0, 45.0, 75.0, 90.0] {
+        assert!(lines.contains(x), "missing line at {}", x);
+    }
+}
+
+#[test]
+fn post_processor() {
+    let mut doc = get_document();
+    doc.push(elements::Paragraph::new("Test"));
+    doc.set_post_processor(|pdf| {
+        let root = pdf.trailer.get(b"Root").unwrap().as_reference().unwrap();
+        let catalog = pdf.get_object_mut(root).unwrap().as_dict_mut().unwrap();
+        catalog.set("PageLayout", "TwoColumnLeft");
+        Ok(())
+    });
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_layout = pdf_doc.catalog().unwrap().get(b"PageLayout").unwrap();
+    assert_eq!(b"TwoColumnLeft", page_layout.as_name().unwrap());
+
+    let mut doc = get_document();
+    doc.set_post_processor(|_| {
+        Err(genpdf::error::Error::new(
+            "Test error",
+            genpdf::error::ErrorKind::Internal,
+        ))
+    });
+    let mut output = Vec::new();
+    let err = doc.render(&mut output).unwrap_err();
+    assert_eq!("Test error", err.to_string());
+    assert!(output.is_empty());
+}
+
+#[test]
+fn snap_grid() {
+    let render = |snap_grid: Option<f64>| {
+        let mut doc = get_document();
+        doc.set_paper_size((100, 50));
+        if let Some(snap_grid) = snap_grid {
+            doc.set_snap_grid(snap_grid);
+        }
+        doc.push(elements::Break::new(0.3));
+        doc.push(elements::SignatureLine::new(33.33).with_space(1.23));
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "m" || operation.operator == "l")
+            .flat_map(|operation| operation.operands.clone())
+            .map(|operand| printpdf::Mm::from(printpdf::Pt(operand.as_f64().unwrap())).0)
+            .collect::<Vec<_>>()
+    };
+    let is_snapped = |value: f64| ((value * 4.0).round() - value * 4.0).abs() < 0.01;
+
+    let coordinates = render(None);
+    assert_eq!(4, coordinates.len());
+    assert!(!coordinates.iter().all(|value| is_snapped(*value)));
+
+    let coordinates = render(Some(0.25));
+    assert_eq!(4, coordinates.len());
+    assert!(coordinates.iter().all(|value| is_snapped(*value)));
+}
+
+#[test]
+fn header_line() {
+    let render = |line: elements::HeaderLine| {
+        let mut doc = get_document();
+        doc.set_paper_size((100, 50));
+        let mut decorator = genpdf::SimplePageDecorator::new();
+        decorator.set_header(move |_| line.clone());
+        doc.set_page_decorator(decorator);
+        doc.push(elements::Paragraph::new("Body"));
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let mut cursor = (0.0, 0.0);
+        let mut texts: Vec<(String, f64, f64)> = Vec::new();
+        for operation in content.operations {
+            match operation.operator.as_str() {
+                "Td" => {
+                    let x = operation.operands[0].as_f64().unwrap();
+                    let y = operation.operands[1].as_f64().unwrap();
+                    cursor = (
+                        printpdf::Mm::from(printpdf::Pt(x)).0,
+                        printpdf::Mm::from(printpdf::Pt(y)).0,
+                    );
+                }
+                "TJ" => {
+                    let mut text = String::new();
+                    for operand in operation.operands[0].as_array().unwrap() {
+                        if let lopdf::Object::String(bytes, _) = operand {
+                            text.extend(bytes.iter().copied().map(char::from));
+                        }
+                    }
+                    // Multiple words can be printed with the same cursor
+                    match texts.last_mut() {
+                        Some((last, x, y)) if (*x, *y) == cursor => last.push_str(&text),
+                        _ => texts.push((text, cursor.0, cursor.1)),
+                    }
+                }
+                _ => {}
+            }
+        }
+        texts
+    };
+
+    let texts = render(
+        elements::HeaderLine::new()
+            .with_left("Title")
+            .with_center("Chapter")
+            .with_right("Page 1"),
+    );
+    assert_eq!(4, texts.len());
+    assert_eq!("Title", texts[0].0);
+    assert!(texts[0].1 < 1.0);
+    assert_eq!("Chapter", texts[1].0);
+    assert!(texts[1].1 > 40.0 && texts[1].1 < 50.0);
+    assert_eq!("Page 1", texts[2].0);
+    assert!(texts[2].1 > 80.0);
+    // All parts are printed on the same line, followed by the body
+    assert_eq!(texts[0].2, texts[1].2);
+    assert_eq!(texts[0].2, texts[2].2);
+    assert!(texts[3].2 < texts[0].2);
+
+    // If the parts don’t fit, they are wrapped and don’t overlap
+    let texts = render(
+        elements::HeaderLine::new()
+            .with_left("A very long document title")
+            .with_center("Chapter one")
+            .with_right("Page one of ten"),
+    );
+    assert!(texts.len() > 4);
+    let left_end = texts
+        .iter()
+        .filter(|(text, _, _)| text.contains("title") || text.contains("very"))
+        .map(|(_, x, _)| *x)
+        .fold(0.0, f64::max);
+    let center_start = texts
+        .iter()
+        .filter(|(text, _, _)| text.contains("Chapter"))
+        .map(|(_, x, _)| *x)
+        .fold(100.0, f64::min);
+    assert!(left_end < center_start);
+}
+
+#[test]
+fn invisible_text() {
+    let mut doc = get_document();
+    doc.push(
+        elements::Paragraph::new("visible ")
+            .styled_string("hidden", style::Style::new().invisible()),
+    );
+    doc.push(elements::Paragraph::new("visible"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let operations: Vec<_> = content
+        .operations
+        .iter()
+        .filter_map(|operation| match operation.operator.as_str() {
+            "Tr" => Some(format!("Tr {}", operation.operands[0].as_i64().unwrap())),
+            "TJ" | "ET" => Some(operation.operator.clone()),
+            _ => None,
+        })
+        .collect();
+    // The rendering mode is reset at the end of the text section
+    assert_eq!(
+        vec!["TJ", "Tr 3", "TJ", "Tr 0", "ET", "TJ", "ET"],
+        operations
+    );
+}
+
+#[test]
+fn inline_alignment() {
+    let mut doc = get_document();
+    let small = style::Style::new().with_font_size(10);
+    let mut paragraph = elements::Paragraph::new(style::StyledString::new(
+        "X",
+        style::Style::new().with_font_size(36),
+    ));
+    for alignment in &[
+        style::InlineAlignment::Baseline,
+        style::InlineAlignment::Top,
+        style::InlineAlignment::Middle,
+        style::InlineAlignment::Bottom,
+    ] {
+        paragraph.push_styled(" a", small.with_inline_alignment(*alignment));
+    }
+    doc.push(paragraph);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let rises: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "Ts")
+        .map(|operation| operation.operands[0].as_f64().unwrap())
+        .collect();
+    // Baseline alignment does not need a text rise; the last value resets the rise
+    assert_eq!(4, rises.len());
+    let (top, middle, bottom) = (rises[0], rises[1], rises[2]);
+    assert!(top > middle);
+    assert!(middle > 0.0);
+    assert!(bottom < 0.0);
+    assert_eq!(0.0, rises[3]);
+}
+
+#[test]
+fn newlines() {
+    fn count_lines(element: impl genpdf::Element + 'static) -> usize {
+        let mut doc = get_document();
+        doc.set_paper_size((50, 30));
+        doc.push(element);
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "BT")
+            .count()
+    }
+
+    // Per default, newlines force a line break
+    assert_eq!(2, count_lines(elements::Paragraph::new("first\nsecond")));
+    assert_eq!(2, count_lines(elements::Paragraph::new("first\r\nsecond")));
+    assert_eq!(
+        1,
+        count_lines(elements::Paragraph::new("first\nsecond").with_newline(genpdf::Newline::Space))
+    );
+    assert_eq!(2, count_lines(elements::Text::new("first\nsecond")));
+    assert_eq!(
+        1,
+        count_lines(elements::Text::new("first\nsecond").with_newline(genpdf::Newline::Space))
+    );
+}
+
+#[test]
+fn language() {
+    let mut doc = get_document();
+    doc.set_language("en-US");
+    doc.push(elements::Paragraph::new("I think, therefore I am."));
+    doc.push(elements::Paragraph::new("Je pense, donc je suis.").with_language("fr-FR"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let catalog = pdf_doc.catalog().expect("Missing document catalog");
+    assert_eq!(
+        b"en-US",
+        catalog
+            .get(b"Lang")
+            .and_then(lopdf::Object::as_str)
+            .unwrap()
+    );
+
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let position = |f: &dyn Fn(&lopdf::content::Operation) -> bool| {
+        content.operations.iter().position(f).unwrap()
+    };
+    let is_span = |operation: &lopdf::content::Operation| {
+        operation.operator == "BDC" && operation.operands[0].as_name().ok() == Some(b"Span")
+    };
+    // Only the second paragraph is enclosed in a marked-content sequence
+    let span = position(&is_span);
+    assert!(position(&|operation| operation.operator == "BT") < span);
+    assert_eq!(
+        1,
+        content.operations.iter().filter(|op| is_span(op)).count()
+    );
+    let properties = content.operations[span].operands[1].as_dict().unwrap();
+    assert_eq!(
+        b"fr-FR",
+        properties
+            .get(b"Lang")
+            .and_then(lopdf::Object::as_str)
+            .unwrap()
+    );
+}
+
+#[test]
+fn badge() {
+    let mut doc = get_document();
+    doc.push(
+        elements::Badge::new("Paid")
+            .with_fill_color(style::Color::Rgb(0, 128, 0))
+            .with_text_color(style::Color::Rgb(255, 255, 255)),
+    );
+    doc.push(elements::Badge::new("Draft").with_radius(0));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let count = |operator: &str| {
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == operator)
+            .count()
+    };
+    // Two filled backgrounds with two labels
+    assert_eq!(2, count("f"));
+    assert_eq!(2, count("BT"));
+    // Both backgrounds are drawn with four corner curves, even if the radius is zero
+    assert_eq!(8, count("c"));
+    let fill_colors: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "rg")
+        .map(|operation| {
+            operation
+                .operands
+                .iter()
+                .map(|o| o.as_f64().unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    assert!(fill_colors.contains(&vec![1.0, 1.0, 1.0]));
+}
+
+#[test]
+fn table_fit_to_width() {
+    fn table(is_fit_to_width: bool) -> elements::TableLayout {
+        let mut table = elements::TableLayout::new(vec![1, 1]).with_fit_to_width(is_fit_to_width);
+        table
+            .row()
+            .element(elements::Paragraph::new("Lorem ipsum dolor sit amet"))
+            .element(elements::Paragraph::new(
+                "Supercalifragilisticexpialidocious",
+            ))
+            .push()
+            .expect("Invalid table row");
+        table
+    }
+
+    fn render(is_fit_to_width: bool) -> Result<Vec<f64>, genpdf::error::Error> {
+        let mut doc = get_document();
+        doc.set_paper_size((80, 50));
+        doc.push(table(is_fit_to_width));
+        let mut output = Vec::new();
+        doc.render(&mut output)?;
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        Ok(content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "cm")
+            .map(|operation| operation.operands[0].as_f64().unwrap())
+            .collect())
+    }
+
+    // The long word does not fit into the column at the original size
+    assert!(render(false).is_err());
+    let scales = render(true).expect("Failed to render document");
+    assert_eq!(1, scales.len());
+    assert!(scales[0] < 1.0);
+    assert!(scales[0] > 0.5);
+}
+
+#[test]
+fn mirrored_pages() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 50));
+    doc.set_mirrored(true);
+    doc.set_mirrored_for_page(2, false);
+    doc.push(elements::Paragraph::new("Mirrored"));
+    doc.push(elements::PageBreak::new());
+    doc.push(elements::Paragraph::new("Not mirrored"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    assert_eq!(2, pages.len());
+    let operations = |page_id| {
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        lopdf::content::Content::decode(&content)
+            .unwrap()
+            .operations
+    };
+
+    let mirrored = operations(pages[0]);
+    assert_eq!("q", mirrored[0].operator);
+    assert_eq!("cm", mirrored[1].operator);
+    let matrix: Vec<_> = mirrored[1]
+        .operands
+        .iter()
+        .map(|o| {
+            o.as_i64()
+                .map(|i| i as f64)
+                .or_else(|_| o.as_f64())
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(vec![-1.0, 0.0, 0.0, 1.0, 283.46, 0.0], matrix);
+    assert_eq!("Q", mirrored.last().unwrap().operator);
+
+    assert!(operations(pages[1])
+        .iter()
+        .all(|operation| operation.operator != "cm"));
+}
+
+/// Adds a link or an anchor with the given name.
+enum AnchorOrLink {
+    Anchor(&'static str),
+    Link(&'static str),
+}
+
+impl genpdf::Element for AnchorOrLink {
+    fn render(
+        &mut self,
+        _context: &genpdf::Context,
+        area: genpdf::render::Area<'_>,
+        _style: style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        let size = genpdf::Size::new(20, 10);
+        match self {
+            AnchorOrLink::Anchor(name) => {
+                area.add_anchor(*name, genpdf::Position::default());
+            }
+            AnchorOrLink::Link(name) => {
+                area.add_link(*name, genpdf::Position::default(), size);
+            }
+        }
+        Ok(genpdf::RenderResult {
+            size,
+            has_more: false,
+        })
+    }
+
+    fn measure(
+        &mut self,
+        _context: &genpdf::Context,
+        _area: genpdf::render::Area<'_>,
+        _style: style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        Ok(genpdf::RenderResult {
+            size: genpdf::Size::new(20, 10),
+            has_more: false,
+        })
+    }
+}
+
+#[test]
+fn anchors() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 50));
+    // A forward reference to an anchor on the next page
+    doc.push(AnchorOrLink::Link("appendix"));
+    doc.push(elements::PageBreak::new());
+    doc.push(AnchorOrLink::Anchor("appendix"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    assert_eq!(2, pages.len());
+    let page = pdf_doc.get_dictionary(pages[0]).unwrap();
+    let annotations = page.get(b"Annots").unwrap().as_array().unwrap();
+    assert_eq!(1, annotations.len());
+    let link = pdf_doc
+        .get_dictionary(annotations[0].as_reference().unwrap())
+        .unwrap();
+    assert_eq!(b"Link", link.get(b"Subtype").unwrap().as_name().unwrap());
+    let dest = link.get(b"Dest").unwrap().as_array().unwrap();
+    assert_eq!(pages[1], dest[0].as_reference().unwrap());
+
+    let catalog = pdf_doc.catalog().unwrap();
+    let names = catalog.get(b"Names").unwrap().as_reference().unwrap();
+    let dests = pdf_doc
+        .get_dictionary(names)
+        .unwrap()
+        .get(b"Dests")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let dests = pdf_doc.get_dictionary(dests).unwrap();
+    let names = dests.get(b"Names").unwrap().as_array().unwrap();
+    assert_eq!(2, names.len());
+    assert_eq!(b"appendix", names[0].as_str().unwrap());
+
+    // Links to unknown anchors and duplicate anchors are rejected
+    let mut doc = get_document();
+    doc.push(AnchorOrLink::Link("missing"));
+    assert!(doc.render(&mut Vec::new()).is_err());
+    let mut doc = get_document();
+    doc.push(AnchorOrLink::Anchor("twice"));
+    doc.push(AnchorOrLink::Anchor("twice"));
+    assert!(doc.render(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn break_policy() {
+    /// Renders the document and returns the vertical text positions for every page.
+    fn render(
+        policy: genpdf::BreakPolicy,
+        elements: Vec<Box<dyn genpdf::Element>>,
+    ) -> Vec<Vec<f64>> {
+        let mut doc = get_document();
+        doc.set_paper_size((100, 50));
+        doc.set_break_policy(policy);
+        for element in elements {
+            doc.push(element);
+        }
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        pdf_doc
+            .get_pages()
+            .values()
+            .map(|page_id| {
+                let content = pdf_doc.get_page_content(*page_id).unwrap();
+                lopdf::content::Content::decode(&content)
+                    .unwrap()
+                    .operations
+                    .into_iter()
+                    .filter(|operation| operation.operator == "Td")
+                    .map(|operation| {
+                        let o = &operation.operands[1];
+                        o.as_i64()
+                            .map(|i| i as f64)
+                            .or_else(|_| o.as_f64())
+                            .unwrap()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // A break at the start of the document
+    let elements = || -> Vec<Box<dyn genpdf::Element>> {
+        vec![
+            Box::new(elements::Break::new(2)),
+            Box::new(elements::Paragraph::new("Text")),
+        ]
+    };
+    let no_break = render(
+        genpdf::BreakPolicy::Preserve,
+        vec![Box::new(elements::Paragraph::new("Text"))],
+    );
+    let preserved = render(genpdf::BreakPolicy::Preserve, elements());
+    let collapsed = render(genpdf::BreakPolicy::Collapse, elements());
+    assert!(preserved[0][0] < no_break[0][0]);
+    assert_eq!(no_break, collapsed);
+
+    // A break at the top of a page after a page break
+    let elements = || -> Vec<Box<dyn genpdf::Element>> {
+        vec![
+            Box::new(elements::Paragraph::new("Text")),
+            Box::new(elements::PageBreak::new()),
+            Box::new(elements::Break::new(2)),
+            Box::new(elements::Paragraph::new("Text")),
+        ]
+    };
+    let preserved = render(genpdf::BreakPolicy::Preserve, elements());
+    let collapsed = render(genpdf::BreakPolicy::Collapse, elements());
+    assert_eq!(2, preserved.len());
+    assert_eq!(2, collapsed.len());
+    assert!(preserved[1][0] < preserved[0][0]);
+    assert_eq!(collapsed[0], collapsed[1]);
+
+    // A break that does not fit on the page before a page break
+    let elements = || -> Vec<Box<dyn genpdf::Element>> {
+        vec![
+            Box::new(elements::Paragraph::new("Text")),
+            Box::new(elements::Break::new(100)),
+            Box::new(elements::PageBreak::new()),
+            Box::new(elements::Paragraph::new("Text")),
+        ]
+    };
+    let preserved = render(genpdf::BreakPolicy::Preserve, elements());
+    let collapsed = render(genpdf::BreakPolicy::Collapse, elements());
+    assert_eq!(3, preserved.len());
+    assert!(preserved[1].is_empty());
+    assert_eq!(2, collapsed.len());
+    assert_eq!(collapsed[0], collapsed[1]);
+}
+
+#[test]
+fn builtin_fonts() {
+    let font_family = fonts::from_builtin(fonts::Builtin::Helvetica);
+    let mut font_cache = fonts::FontCache::new(font_family.clone());
+    let font = font_cache.default_font_family().regular;
+    let courier = font_cache.add_font_family(fonts::from_builtin(fonts::Builtin::Courier));
+    assert!(font.is_builtin());
+    assert!(font_cache.get_rt_font(font).is_none());
+    let width = |font: fonts::Font| {
+        let width = font.str_width(&font_cache, "Hello", 10);
+        (printpdf::Pt::from(printpdf::Mm::from(width)).0 * 100.0).round()
+    };
+    // H (722), e (556), l (222), l (222), o (556) in 1/1000 em
+    assert_eq!(2278.0, width(font));
+    assert_eq!(3000.0, width(courier.bold));
+
+    let mut doc = genpdf::Document::new(font_family);
+    doc.set_minimal_conformance();
+    doc.push(elements::Paragraph::new("Hello"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    assert!(pdf_doc.objects.values().all(|object| {
+        object
+            .as_dict()
+            .map(|dict| !dict.has(b"FontFile2"))
+            .unwrap_or(true)
+    }));
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let operations = lopdf::content::Content::decode(&content)
+        .unwrap()
+        .operations;
+    let text: Vec<u8> = operations
+        .iter()
+        .filter(|operation| operation.operator == "TJ")
+        .flat_map(|operation| operation.operands[0].as_array().unwrap())
+        .filter_map(|operand| match operand {
+            lopdf::Object::String(bytes, _) => Some(bytes.clone()),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    assert_eq!(b"Hello", text.as_slice());
+}
+
+#[test]
+fn uri_links() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    doc.push(
+        elements::Paragraph::new("Visit ")
+            .linked_string("the genpdf ", "https://example.org/genpdf")
+            .linked_string(
+                style::StyledString::new("repository", style::Effect::Bold),
+                "https://example.org/genpdf",
+            )
+            .string(" or ")
+            .linked_string("the docs", "https://example.org/docs")
+            .string("."),
+    );
+    doc.push(elements::Text::new(
+        style::StyledString::from("Text").with_link("https://example.org/text"),
+    ));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let page = pdf_doc.get_dictionary(page_id).unwrap();
+    let annotations = page.get(b"Annots").unwrap().as_array().unwrap();
+    let links: Vec<_> = annotations
+        .iter()
+        .map(|annotation| {
+            let link = pdf_doc
+                .get_dictionary(annotation.as_reference().unwrap())
+                .unwrap();
+            let action = link.get(b"A").unwrap().as_dict().unwrap();
+            assert_eq!(b"URI", action.get(b"S").unwrap().as_name().unwrap());
+            let uri = action.get(b"URI").unwrap().as_str().unwrap().to_vec();
+            let rect: Vec<_> = link
+                .get(b"Rect")
+                .unwrap()
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|o| {
+                    o.as_i64()
+                        .map(|i| i as f64)
+                        .or_else(|_| o.as_f64())
+                        .unwrap()
+                })
+                .collect();
+            (String::from_utf8(uri).unwrap(), rect)
+        })
+        .collect();
+
+    // Adjacent strings with the same link are merged
+    assert_eq!(3, links.len());
+    assert_eq!("https://example.org/genpdf", links[0].0);
+    assert_eq!("https://example.org/docs", links[1].0);
+    assert_eq!("https://example.org/text", links[2].0);
+    // The links of the paragraph are on the same line, the link of the text below
+    assert!(links[0].1[2] < links[1].1[0]);
+    assert_eq!(links[0].1[1], links[1].1[1]);
+    assert!(links[2].1[3] <= links[0].1[1]);
+
+    // Wrapped links have one annotation per line
+    let mut doc = get_document();
+    doc.set_paper_size((50, 100));
+    doc.push(elements::Paragraph::default().linked_string(LOREM_IPSUM, "https://example.org"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let page = pdf_doc.get_dictionary(page_id).unwrap();
+    let annotations = page.get(b"Annots").unwrap().as_array().unwrap();
+    let lines = lopdf::content::Content::decode(&pdf_doc.get_page_content(page_id).unwrap())
+        .unwrap()
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "BT")
+        .count();
+    assert!(lines > 1);
+    assert_eq!(lines, annotations.len());
+}
+
+#[test]
+fn internal_links() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 50));
+    doc.push(elements::Paragraph::new("See section 3.").link_to("sec3"));
+    doc.push(elements::PageBreak::new());
+    doc.push(elements::Anchor::new("sec3"));
+    doc.push(elements::Paragraph::new("3. Results"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    assert_eq!(2, pages.len());
+    let page = pdf_doc.get_dictionary(pages[0]).unwrap();
+    let annotations = page.get(b"Annots").unwrap().as_array().unwrap();
+    assert_eq!(1, annotations.len());
+    let link = pdf_doc
+        .get_dictionary(annotations[0].as_reference().unwrap())
+        .unwrap();
+    let dest = link.get(b"Dest").unwrap().as_array().unwrap();
+    assert_eq!(pages[1], dest[0].as_reference().unwrap());
+    assert!(pdf_doc
+        .get_dictionary(pages[1])
+        .unwrap()
+        .get(b"Annots")
+        .is_err());
+
+    // Links to missing anchors are rejected
+    let mut doc = get_document();
+    doc.push(elements::Paragraph::new("See section 4.").link_to("sec4"));
+    assert!(doc.render(&mut Vec::new()).is_err());
+}
+
+#[test]
+fn bookmarked_elements() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 50));
+    doc.push(elements::Paragraph::new("Summary").with_bookmark("Summary", 1));
+    doc.push(elements::Text::new("Figure 1").with_bookmark("Figure 1", 2));
+    doc.push(elements::PageBreak::new());
+    doc.push(elements::Text::new("Appendix").with_bookmark("Appendix", 1));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    let get_ref = |dict: &lopdf::Dictionary, key: &[u8]| {
+        dict.get(key)
+            .and_then(lopdf::Object::as_reference)
+            .and_then(|id| pdf_doc.get_dictionary(id))
+            .ok()
+    };
+    let catalog = get_ref(&pdf_doc.trailer, b"Root").expect("Missing catalog");
+    let outlines = get_ref(catalog, b"Outlines").expect("Missing outlines");
+
+    let mut entries = Vec::new();
+    let mut stack = vec![(0, get_ref(outlines, b"First"))];
+    while let Some((depth, item)) = stack.pop() {
+        if let Some(item) = item {
+            let title = item.get(b"Title").and_then(lopdf::Object::as_str).unwrap();
+            let dest = item.get(b"Dest").and_then(lopdf::Object::as_array).unwrap();
+            let page = pages
+                .iter()
+                .position(|id| *id == dest[0].as_reference().unwrap());
+            entries.push((depth, String::from_utf8_lossy(title).into_owned(), page));
+            stack.push((depth, get_ref(item, b"Next")));
+            stack.push((depth + 1, get_ref(item, b"First")));
+        }
+    }
+    assert_eq!(
+        vec![
+            (0, "Summary".to_owned(), Some(0)),
+            (1, "Figure 1".to_owned(), Some(0)),
+            (0, "Appendix".to_owned(), Some(1)),
+        ],
+        entries
+    );
+}
+
+#[test]
+fn table_of_contents() {
+    let build = || {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_heading_numbering(true);
+        doc.push(elements::TableOfContents::new().with_max_level(1));
+        doc.push(elements::PageBreak::new());
+        doc.push(elements::Heading::new(1, "Introduction"));
+        doc.push(elements::PageBreak::new());
+        doc.push(elements::Heading::new(1, "Design"));
+        doc.push(elements::Heading::new(2, "Layout"));
+        Ok(doc)
+    };
+    let mut output = Vec::new();
+    genpdf::Document::render_in_passes(build, &mut output).expect("Failed to render document");
+
+    let texts = &get_page_texts(&output)[0];
+    assert_eq!(6, texts.len());
+    assert_eq!("1 Introduction", texts[0]);
+    assert_eq!("2", texts[1]);
+    assert!(!texts[2].is_empty() && texts[2].chars().all(|c| c == '.'));
+    assert_eq!("2 Design", texts[3]);
+    assert_eq!("3", texts[4]);
+    assert!(texts.iter().all(|text| !text.contains("Layout")));
+}
+
+#[test]
+fn page_count_footer() {
+    let build = || {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        let mut decorator = genpdf::SimplePageDecorator::new();
+        decorator.set_footer(|page, total| match total {
+            Some(total) => elements::Text::new(format!("Page {} of {}", page, total)),
+            None => elements::Text::new(format!("Page {}", page)),
+        });
+        doc.set_page_decorator(decorator);
+        doc.push(elements::Paragraph::new("First"));
+        doc.push(elements::PageBreak::new());
+        doc.push(elements::Paragraph::new("Second"));
+        doc.push(elements::PageBreak::new());
+        doc.push(elements::Paragraph::new("Third"));
+        Ok(doc)
+    };
+    let mut output = Vec::new();
+    build()
+        .unwrap()
+        .render(&mut output)
+        .expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec!["Page 1".to_owned(), "First".to_owned()],
+            vec!["Page 2".to_owned(), "Second".to_owned()],
+            vec!["Page 3".to_owned(), "Third".to_owned()],
+        ],
+        get_page_texts(&output)
+    );
+
+    let mut output = Vec::new();
+    genpdf::Document::render_in_passes(build, &mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec!["Page 1 of 3".to_owned(), "First".to_owned()],
+            vec!["Page 2 of 3".to_owned(), "Second".to_owned()],
+            vec!["Page 3 of 3".to_owned(), "Third".to_owned()],
+        ],
+        get_page_texts(&output)
+    );
+}
+
+#[test]
+fn ordered_list_numbering() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    let mut list = elements::OrderedList::with_start(3)
+        .element(elements::Text::new("three"))
+        .element(
+            elements::OrderedList::new()
+                .element(elements::Text::new("sub"))
+                .with_numbering(elements::Numbering::UpperAlpha)
+                .with_indent(5),
+        );
+    list.set_numbering(elements::Numbering::LowerRoman);
+    doc.push(list);
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let texts = &get_page_texts(&output)[0];
+    assert_eq!(&["three", "iii.", "sub", "A.", "iv."], texts.as_slice());
+}
+
+#[test]
+fn nested_unordered_lists() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.push(elements::UnorderedList::new().element(elements::Text::new("default")));
+    doc.push(
+        elements::UnorderedList::new()
+            .with_bullets(vec!["*", "-"])
+            .element(elements::Text::new("a"))
+            .element(
+                elements::UnorderedList::new()
+                    .element(elements::Text::new("b"))
+                    .element(
+                        elements::OrderedList::new().element(
+                            elements::UnorderedList::new().element(elements::Text::new("c")),
+                        ),
+                    ),
+            )
+            .element(elements::UnorderedList::with_bullet("+").element(elements::Text::new("d"))),
+    );
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let texts = &get_page_texts(&output)[0];
+    assert_eq!(
+        &["default", "•", "a", "*", "b", "-", "c", "-", "1.", "-", "*", "d", "+", "*"],
+        texts.as_slice()
+    );
+}
+
+/// Measures the wrapped element before rendering it.
+struct MeasuredElement<E>(E);
+
+impl<E: genpdf::Element> genpdf::Element for MeasuredElement<E> {
+    fn render(
+        &mut self,
+        context: &genpdf::Context,
+        area: genpdf::render::Area<'_>,
+        style: style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        self.0.measure(context, area.clone(), style)?;
+        self.0.render(context, area, style)
+    }
+
+    fn measure(
+        &mut self,
+        context: &genpdf::Context,
+        area: genpdf::render::Area<'_>,
+        style: style::Style,
+    ) -> Result<genpdf::RenderResult, genpdf::error::Error> {
+        self.0.measure(context, area, style)
+    }
+}
+
+#[test]
+fn linear_layout_measure() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((50, 30));
+    let mut layout = elements::LinearLayout::vertical();
+    layout.extend((1..=8).map(|i| elements::Text::new(i.to_string())));
+    doc.push(MeasuredElement(layout));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    // Measuring the layout must not skip the elements that are rendered afterwards.
+    let texts: Vec<String> = get_page_texts(&output).into_iter().flatten().collect();
+    assert_eq!((1..=8).map(|i| i.to_string()).collect::<Vec<_>>(), texts);
+}
+
+#[test]
+fn column_layout() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((105, 30));
+    let mut columns = elements::ColumnLayout::new(2).with_rule(style::LineStyle::new());
+    columns.extend((1..=7).map(|i| elements::Text::new(i.to_string())));
+    doc.push(columns);
+    let mut long_columns = elements::ColumnLayout::new(2).with_gutter(15);
+    long_columns.extend((1..=10).map(|i| elements::Text::new(format!("p{}", i))));
+    doc.push(long_columns);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let to_mm = |o: &lopdf::Object| {
+        let pt = o
+            .as_i64()
+            .map(|i| i as f64)
+            .or_else(|_| o.as_f64())
+            .unwrap();
+        printpdf::Mm::from(printpdf::Pt(pt)).0.round()
+    };
+    let mut pages = Vec::new();
+    for page_id in pdf_doc.get_pages().values() {
+        let content = pdf_doc.get_page_content(*page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let mut x = 0.0;
+        let mut texts = Vec::new();
+        let mut rules = Vec::new();
+        for operation in content.operations {
+            match operation.operator.as_str() {
+                "Td" => x = to_mm(&operation.operands[0]),
+                "TJ" => {
+                    let text: String = operation.operands[0]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .filter_map(|operand| operand.as_str().ok())
+                        .map(String::from_utf8_lossy)
+                        .collect();
+                    texts.push((text, x));
+                }
+                "m" => rules.push(to_mm(&operation.operands[0])),
+                _ => {}
+            }
+        }
+        pages.push((texts, rules));
+    }
+    let text = |s: &str, x: f64| (s.to_owned(), x);
+
+    // A page fits six lines per column, so the columns are balanced as 4 + 3 lines.  The second
+    // layout starts below the first one and fills two lines per column on the first page.
+    assert_eq!(2, pages.len());
+    assert_eq!(
+        vec![
+            text("1", 0.0),
+            text("2", 0.0),
+            text("3", 0.0),
+            text("4", 0.0),
+            text("5", 55.0),
+            text("6", 55.0),
+            text("7", 55.0),
+            text("p1", 0.0),
+            text("p2", 0.0),
+            text("p3", 60.0),
+            text("p4", 60.0),
+        ],
+        pages[0].0
+    );
+    assert_eq!(vec![53.0], pages[0].1);
+    assert_eq!(
+        vec![
+            text("p5", 0.0),
+            text("p6", 0.0),
+            text("p7", 0.0),
+            text("p8", 60.0),
+            text("p9", 60.0),
+            text("p10", 60.0),
+        ],
+        pages[1].0
+    );
+    assert!(pages[1].1.is_empty());
+}
+
+#[test]
+fn column_layout_gutter_and_rule() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((104, 30));
+    let rule = style::LineStyle::new()
+        .with_thickness(1)
+        .with_color(style::Color::Rgb(255, 0, 0));
+    let mut columns = elements::ColumnLayout::new(3)
+        .with_gutter(10)
+        .with_rule(rule);
+    columns.extend((1..=20).map(|i| elements::Text::new(i.to_string())));
+    doc.push(columns);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pages: Vec<_> = (0..2)
+        .map(|page| {
+            let operations = page_operations(&output, page);
+            let round = |pt: f64| mm(pt).round();
+            let mut columns: Vec<f64> = text_positions(&operations)
+                .into_iter()
+                .map(|(x, _)| round(x))
+                .collect();
+            columns.dedup();
+            let starts = operator_operands(&operations, "m");
+            let ends = operator_operands(&operations, "l");
+            let rules: Vec<(f64, f64)> = starts
+                .iter()
+                .zip(&ends)
+                .map(|(start, end)| {
+                    assert_eq!(start[0], end[0]);
+                    (round(start[0]), round(start[1] - end[1]))
+                })
+                .collect();
+            assert_eq!(starts.len(), ends.len());
+            let widths: Vec<f64> = operator_operands(&operations, "w")
+                .into_iter()
+                .map(|operands| round(operands[0]))
+                .collect();
+            let colors = operator_operands(&operations, "RG");
+            (columns, rules, widths, colors)
+        })
+        .collect();
+    let red = vec![vec![1.0, 0.0, 0.0]];
+
+    // The columns are 28 mm wide and separated by a 10 mm gutter.  The rules are drawn in the
+    // middle of the gutters and span the used height of the columns:  six lines on the first page
+    // and a single line in two columns on the second page.
+    assert_eq!(2, get_page_texts(&output).len());
+    assert_eq!(
+        (vec![0.0, 38.0, 76.0], vec![(33.0, 29.0), (71.0, 29.0)]),
+        (pages[0].0.clone(), pages[0].1.clone())
+    );
+    assert_eq!(
+        (vec![1.0], red.clone()),
+        (pages[0].2.clone(), pages[0].3.clone())
+    );
+    assert_eq!(
+        (vec![0.0, 38.0], vec![(33.0, 5.0)]),
+        (pages[1].0.clone(), pages[1].1.clone())
+    );
+    assert_eq!((vec![1.0], red), (pages[1].2.clone(), pages[1].3.clone()));
+}
+
+#[test]
+fn table_column_spans() {
+    let render = |is_right_to_left: bool| {
+        let mut doc = get_document();
+        doc.set_paper_size((90, 50));
+        let mut table =
+            elements::TableLayout::new(vec![1, 1, 1]).with_right_to_left(is_right_to_left);
+        table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+        table
+            .row()
+            .element_span(elements::Paragraph::new("A"), 2)
+            .element(elements::Paragraph::new("B"))
+            .push()
+            .unwrap();
+        table
+            .row()
+            .element(elements::Paragraph::new("C"))
+            .element(elements::Paragraph::new("D"))
+            .element(elements::Paragraph::new("E"))
+            .push()
+            .unwrap();
+        assert!(table
+            .row()
+            .element_span(elements::Paragraph::new("F"), 2)
+            .element_span(elements::Paragraph::new("G"), 2)
+            .push()
+            .is_err());
+        doc.push(table);
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let to_mm = |o: &lopdf::Object| {
+            let pt = o
+                .as_i64()
+                .map(|i| i as f64)
+                .or_else(|_| o.as_f64())
+                .unwrap();
+            printpdf::Mm::from(printpdf::Pt(pt)).0.round()
+        };
+        let mut texts = Vec::new();
+        let mut vertical_lines = Vec::new();
+        let mut start = None;
+        for operation in content.operations {
+            match operation.operator.as_str() {
+                "Td" => texts.push(to_mm(&operation.operands[0])),
+                "m" => start = Some(to_mm(&operation.operands[0])),
+                "l" => {
+                    let x = to_mm(&operation.operands[0]);
+                    if start == Some(x) {
+                        vertical_lines.push(x);
+                    }
+                }
+                _ => {}
+            }
+        }
+        vertical_lines.sort_by(|a: &f64, b| a.partial_cmp(b).unwrap());
+        (texts, vertical_lines)
+    };
+
+    // The spanned cell has no inner border, so there is only one line at the column border
+    // within the merged cell
+    let (texts, lines) = render(false);
+    assert_eq!(vec![0.0, 60.0, 0.0, 30.0, 60.0], texts);
+    assert_eq!(vec![0.0, 0.0, 30.0, 60.0, 60.0, 90.0, 90.0], lines);
+
+    let (texts, lines) = render(true);
+    assert_eq!(vec![30.0, 0.0, 60.0, 30.0, 0.0], texts);
+    assert_eq!(vec![0.0, 0.0, 30.0, 30.0, 60.0, 90.0, 90.0], lines);
+}
+
+#[test]
+fn table_row_spans() {
+    let get_table = |values: usize| {
+        let mut table = elements::TableLayout::new(vec![1, 1]);
+        table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+        table
+            .row()
+            .element_row_span(elements::Text::new("Label"), values)
+            .element(elements::Text::new("V1"))
+            .push()
+            .unwrap();
+        for i in 2..=values {
+            table
+                .row()
+                .element(elements::Text::new(format!("V{}", i)))
+                .push()
+                .unwrap();
+        }
+        table
+            .row()
+            .element(elements::Text::new("X"))
+            .element(elements::Text::new("Y"))
+            .push()
+            .unwrap();
+        table
+    };
+    let get_document = || {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((90, 30));
+        doc
+    };
+
+    let mut table = get_table(3);
+    assert!(table
+        .row()
+        .element(elements::Text::new("A"))
+        .element(elements::Text::new("B"))
+        .element(elements::Text::new("C"))
+        .push()
+        .is_err());
+    let mut doc = get_document();
+    doc.push(table);
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![vec!["Label", "V1", "V2", "V3", "X", "Y"]],
+        get_page_texts(&output)
+    );
+
+    // Only the value column has horizontal borders between the spanned rows
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let to_mm = |o: &lopdf::Object| {
+        let pt = o
+            .as_i64()
+            .map(|i| i as f64)
+            .or_else(|_| o.as_f64())
+            .unwrap();
+        printpdf::Mm::from(printpdf::Pt(pt)).0.round()
+    };
+    let mut start = None;
+    let mut horizontal_lines = (0, 0);
+    for operation in content.operations {
+        match operation.operator.as_str() {
+            "m" => start = Some((to_mm(&operation.operands[0]), to_mm(&operation.operands[1]))),
+            "l" => {
+                let y = to_mm(&operation.operands[1]);
+                match start {
+                    Some((x, start_y)) if start_y == y && x == 0.0 => horizontal_lines.0 += 1,
+                    Some((x, start_y)) if start_y == y && x == 45.0 => horizontal_lines.1 += 1,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    // Label column: top borders of the label and of X and bottom border of X; value column: top
+    // borders of the values and of Y and bottom border of Y
+    assert_eq!((3, 5), horizontal_lines);
+
+    // The row group does not fit below the paragraphs, so it is moved to the next page
+    let mut doc = get_document();
+    doc.push(elements::Text::new("first"));
+    doc.push(elements::Text::new("second"));
+    doc.push(elements::Text::new("third"));
+    doc.push(elements::Text::new("fourth"));
+    doc.push(get_table(3));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec!["first", "second", "third", "fourth"],
+            vec!["Label", "V1", "V2", "V3", "X", "Y"],
+        ],
+        get_page_texts(&output)
+    );
+
+    // The row group does not fit on any page
+    let mut doc = get_document();
+    doc.push(get_table(10));
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        genpdf::error::ErrorKind::PageSizeExceeded
+    ));
+}
+
+#[test]
+fn table_header_rows() {
+    let get_table = || {
+        let mut table = elements::TableLayout::new(vec![1, 1]);
+        table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+        table.set_header_rows(1);
+        table
+            .row()
+            .cloned_element(elements::Text::new("Name"))
+            .cloned_element(elements::Text::new("Value"))
+            .push()
+            .unwrap();
+        for i in 1..=5 {
+            table
+                .row()
+                .element(elements::Text::new(format!("N{}", i)))
+                .element(elements::Text::new(format!("V{}", i)))
+                .push()
+                .unwrap();
+        }
+        table
+    };
+    let get_document = || {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((90, 30));
+        doc
+    };
+
+    let mut doc = get_document();
+    doc.push(get_table());
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec!["Name", "Value", "N1", "V1", "N2", "V2", "N3", "V3", "N4", "V4"],
+            vec!["Name", "Value", "N5", "V5"],
+        ],
+        get_page_texts(&output)
+    );
+
+    // The header cells must be cloned elements
+    let mut table = elements::TableLayout::new(vec![1, 1]).with_header_rows(1);
+    table
+        .row()
+        .element(elements::Text::new("Name"))
+        .element(elements::Text::new("Value"))
+        .push()
+        .unwrap();
+    let mut doc = get_document();
+    doc.push(table);
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData));
+}
+
+#[test]
+fn table_cell_padding_alignment() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((90, 50));
+    let mut table = elements::TableLayout::new(vec![1, 1, 1])
+        .with_cell_padding(2)
+        .with_vertical_alignment(genpdf::VerticalAlignment::Center);
+    table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+    table
+        .row()
+        .element(elements::Paragraph::new("A\nB\nC"))
+        .element(elements::Paragraph::new("D"))
+        .element_with_style(
+            elements::Paragraph::new("E"),
+            elements::CellStyle::new()
+                .with_padding(genpdf::Margins::trbl(0, 0, 0, 5))
+                .with_vertical_alignment(genpdf::VerticalAlignment::Bottom),
+        )
+        .push()
+        .unwrap();
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let to_mm = |o: &lopdf::Object| {
+        let pt = o
+            .as_i64()
+            .map(|i| i as f64)
+            .or_else(|_| o.as_f64())
+            .unwrap();
+        (printpdf::Mm::from(printpdf::Pt(pt)).0 * 10.0).round() / 10.0
+    };
+    let texts: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "Td")
+        .map(|operation| (to_mm(&operation.operands[0]), to_mm(&operation.operands[1])))
+        .collect();
+    // The padding is added to the frame line, the middle cell is centered next to the second
+    // line of the first cell, and the last cell is bottom-aligned without a bottom padding
+    assert_eq!(
+        vec![
+            (2.1, 44.0),
+            (2.1, 39.1),
+            (2.1, 34.2),
+            (32.1, 39.1),
+            (65.1, 32.2)
+        ],
+        texts
+    );
+}
+
+#[test]
+fn table_cell_styles() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((90, 50));
+    let mut table = elements::TableLayout::new(vec![1, 1, 1]);
+    table.set_cell_decorator(elements::FrameCellDecorator::new(true, true, false));
+    table
+        .row()
+        .element(elements::Text::new("A"))
+        .element_with_style(
+            elements::Text::new("B"),
+            elements::CellStyle::new()
+                .with_background_color(style::Color::Rgb(0, 0, 255))
+                .with_border_line_style(style::LineStyle::new().with_thickness(1)),
+        )
+        .element(elements::Text::new("C"))
+        .set_background_color(style::Color::Rgb(255, 0, 0))
+        .push()
+        .unwrap();
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let to_f64 = |o: &lopdf::Object| {
+        o.as_i64()
+            .map(|i| i as f64)
+            .or_else(|_| o.as_f64())
+            .unwrap()
+    };
+    let mut colors = Vec::new();
+    let mut widths = Vec::new();
+    for operation in content.operations {
+        match operation.operator.as_str() {
+            "rg" => colors.push(operation.operands.iter().map(to_f64).collect::<Vec<_>>()),
+            "w" => {
+                let width = printpdf::Mm::from(printpdf::Pt(to_f64(&operation.operands[0]))).0;
+                widths.push((width * 10.0).round() / 10.0);
+            }
+            _ => {}
+        }
+    }
+    // The second cell overrides the background color of the row and the line style of the
+    // decorator, then the black text color is set
+    assert_eq!(
+        vec![
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0],
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0],
+        ],
+        colors
+    );
+    assert_eq!(vec![0.1, 1.0, 0.1], widths);
+}
+
+#[test]
+fn table_auto_column_widths() {
+    let render = |first: &str, second: &str, is_fit_to_width: bool| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((90, 50));
+        let mut table = elements::TableLayout::new(vec![1, 1])
+            .with_column_widths(elements::ColumnWidths::Auto)
+            .with_cell_padding(1)
+            .with_fit_to_width(is_fit_to_width);
+        table
+            .row()
+            .element(elements::Paragraph::new(first))
+            .element(elements::Paragraph::new(second))
+            .push()
+            .unwrap();
+        doc.push(table);
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let texts: Vec<_> = content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "Td")
+            .map(|operation| {
+                let o = &operation.operands[0];
+                let pt = o
+                    .as_i64()
+                    .map(|i| i as f64)
+                    .or_else(|_| o.as_f64())
+                    .unwrap();
+                (printpdf::Mm::from(printpdf::Pt(pt)).0 * 10.0).round() / 10.0
+            })
+            .collect();
+        (texts, get_page_texts(&output))
+    };
+
+    // The content fits into the table, so the remaining space is distributed proportionally to
+    // the content widths
+    let (texts, _) = render("ID", "Lorem ipsum dolor", false);
+    assert_eq!(vec![1.0, 14.0], texts);
+
+    // The first column gets the width of its content, the second column is wrapped
+    let (texts, pages) = render("ID", LOREM_IPSUM, false);
+    assert_eq!(7.2, texts[1]);
+    assert_eq!(2, pages.len());
+
+    // The widest words do not fit into the table, so it is scaled down
+    let long_word = "Loremipsumdolorsitametconsectetur";
+    let (texts, _) = render(long_word, "Lorem ipsum dolor sit amet consectetur", true);
+    assert_eq!(vec![1.0, 71.3, 71.3, 71.3, 71.3, 71.3], texts);
+}
+
+#[test]
+fn table_absolute_column_widths() {
+    use elements::ColumnWidth;
+
+    let render = |column_widths: elements::ColumnWidths| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((90, 50));
+        let mut table = elements::TableLayout::from_column_widths(vec![
+            ColumnWidth::Absolute(genpdf::Mm::from(40)),
+            ColumnWidth::Weight(1),
+            ColumnWidth::Weight(3),
+        ])
+        .with_column_widths(column_widths);
+        table
+            .row()
+            .element(elements::Paragraph::new("Name"))
+            .element(elements::Paragraph::new("A"))
+            .element(elements::Paragraph::new("B"))
+            .push()
+            .unwrap();
+        doc.push(table);
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "Td")
+            .map(|operation| {
+                let o = &operation.operands[0];
+                let pt = o
+                    .as_i64()
+                    .map(|i| i as f64)
+                    .or_else(|_| o.as_f64())
+                    .unwrap();
+                (printpdf::Mm::from(printpdf::Pt(pt)).0 * 10.0).round() / 10.0
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The weighted columns share the remaining 50 mm
+    assert_eq!(
+        vec![0.0, 40.0, 52.5],
+        render(elements::ColumnWidths::Weighted)
+    );
+    // The content of the automatic columns has the same width, so they share the remaining width
+    // evenly
+    assert_eq!(vec![0.0, 40.0, 65.0], render(elements::ColumnWidths::Auto));
+}
+
+#[test]
+fn table_row_decorator() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((90, 50));
+    let mut table = elements::TableLayout::new(vec![1]).with_row_decorator(|row| {
+        if row % 2 == 1 {
+            elements::RowStyle::new()
+                .with_background_color(style::Color::Rgb(0, 0, 255))
+                .with_text_style(style::Color::Rgb(255, 0, 0))
+        } else {
+            elements::RowStyle::new()
+        }
+    });
+    for &text in &["A", "B", "C"] {
+        table
+            .push_row(vec![Box::new(elements::Text::new(text))], None)
+            .unwrap();
+    }
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let to_f64 = |o: &lopdf::Object| {
+        o.as_i64()
+            .map(|i| i as f64)
+            .or_else(|_| o.as_f64())
+            .unwrap()
+    };
+    let colors: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "rg")
+        .map(|operation| operation.operands.iter().map(to_f64).collect::<Vec<_>>())
+        .collect();
+    // Only the second row has a background color and a text color
+    assert_eq!(
+        vec![
+            vec![0.0, 0.0, 1.0],
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0]
+        ],
+        colors
+    );
+}
+
+#[test]
+fn keep_together() {
+    let lines = |range: std::ops::Range<usize>| {
+        let mut layout = elements::LinearLayout::vertical();
+        for i in range {
+            layout.push(elements::Text::new(i.to_string()));
+        }
+        layout
+    };
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((90, 30));
+    doc.push(lines(0..4));
+    doc.push(elements::KeepTogether::new(lines(4..7)));
+    // Elements that do not fit on an empty page are split as usual
+    doc.push(elements::KeepTogether::new(lines(7..17)));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let to_strings =
+        |range: std::ops::Range<usize>| -> Vec<String> { range.map(|i| i.to_string()).collect() };
+    assert_eq!(
+        vec![
+            to_strings(0..4),
+            to_strings(4..7),
+            to_strings(7..13),
+            to_strings(13..17)
+        ],
+        get_page_texts(&output)
+    );
+}
+
+#[test]
+fn paragraph_widows_orphans() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((90, 30));
+    doc.set_widows(3);
+    for i in 0..4 {
+        doc.push(elements::Text::new(i.to_string()));
+    }
+    // Only two lines fit on the first page, so the paragraph is moved to the next page
+    doc.push(elements::Paragraph::new("a\nb\nc\nd").with_orphans(3));
+    // Two lines would fit, but three lines have to be moved to the next page
+    doc.push(elements::Paragraph::new("e\nf\ng\nh"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec!["0", "1", "2", "3"],
+            vec!["a", "b", "c", "d", "e"],
+            vec!["f", "g", "h"],
+        ],
+        get_page_texts(&output)
+    );
+}
+
+#[test]
+fn paragraph_soft_hyphens() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((30, 50));
+    doc.push(elements::Paragraph::new(
+        "Donau\u{ad}dampf\u{ad}schiff\u{ad}fahrt",
+    ));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    // The word is split at the last fitting soft hyphen, and the other soft hyphens are not printed
+    assert_eq!(
+        vec![vec!["Donaudampf-", "schifffahrt"]],
+        get_page_texts(&output)
+    );
+}
+
+#[test]
+fn paragraph_break_overflow() {
+    let render = |mark: &str| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((30, 50));
+        doc.set_overflow(genpdf::Overflow::Break);
+        doc.set_word_break_mark(mark);
+        doc.push(elements::Paragraph::new(
+            "The Donaudampfschifffahrtskapitänsmütze",
+        ));
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        get_page_texts(&output)
+    };
+    assert_eq!(
+        vec![vec!["The ", "Donaudampfsc", "hifffahrtskapitän", "smütze"]],
+        render("")
+    );
+    // The mark is taken into account when breaking the word
+    assert_eq!(
+        vec![vec![
+            "The ",
+            "Donaudampfsc-",
+            "hifffahrtskapitä-",
+            "nsmütze"
+        ]],
+        render("-")
+    );
+}
+
+#[test]
+fn paragraph_leading() {
+    let render = |paragraph: elements::Paragraph| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((90, 50));
+        doc.set_line_spacing(3.0);
+        doc.push(paragraph);
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let positions: Vec<_> = content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "Td")
+            .map(|operation| {
+                let o = &operation.operands[1];
+                let pt = o
+                    .as_i64()
+                    .map(|i| i as f64)
+                    .or_else(|_| o.as_f64())
+                    .unwrap();
+                printpdf::Mm::from(printpdf::Pt(pt)).0
+            })
+            .collect();
+        ((positions[0] - positions[1]) * 10.0).round() / 10.0
+    };
+
+    assert_eq!(
+        10.0,
+        render(elements::Paragraph::new("a\nb").with_leading(10))
+    );
+    // The line spacing of the paragraph overrides the line spacing of the document
+    let single = render(elements::Paragraph::new("a\nb").with_line_spacing(1.0));
+    let double = render(elements::Paragraph::new("a\nb").with_line_spacing(2.0));
+    assert_eq!(4.9, single);
+    assert_eq!(9.8, double);
+}
+
+#[test]
+fn letter_word_spacing() {
+    let render = |style: style::Style| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((90, 50));
+        doc.push(
+            elements::Paragraph::new(style::StyledString::new("AB CD", style))
+                .aligned(genpdf::Alignment::Right),
+        );
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let x = content
+            .operations
+            .iter()
+            .find(|operation| operation.operator == "Td")
+            .map(|operation| {
+                let o = &operation.operands[0];
+                let pt = o
+                    .as_i64()
+                    .map(|i| i as f64)
+                    .or_else(|_| o.as_f64())
+                    .unwrap();
+                printpdf::Mm::from(printpdf::Pt(pt)).0
+            })
+            .unwrap();
+        (x * 10.0).round() / 10.0
+    };
+
+    // The spacing is part of the measured width, so right-aligned text moves to the left
+    let plain = render(style::Style::new());
+    let tracked = render(style::Style::new().with_letter_spacing(1));
+    let spaced = render(
+        style::Style::new()
+            .with_letter_spacing(1)
+            .with_word_spacing(2),
+    );
+    assert_eq!(5.0, ((plain - tracked) * 10.0).round() / 10.0);
+    assert_eq!(2.0, ((tracked - spaced) * 10.0).round() / 10.0);
+}
+
+#[test]
+fn underline_strikethrough() {
+    let render = |width: u32| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((width, 50));
+        doc.push(
+            elements::Paragraph::new("plain ")
+                .styled_string("underlined text", style::Effect::Underline)
+                .styled_string(" struck", style::Effect::Strikethrough),
+        );
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        let points: Vec<_> = content
+            .operations
+            .iter()
+            .filter(|operation| {
+                operation.operator == "Td" || operation.operator == "m" || operation.operator == "l"
+            })
+            .map(|operation| {
+                let values: Vec<_> = operation
+                    .operands
+                    .iter()
+                    .map(|o| {
+                        let pt = o
+                            .as_i64()
+                            .map(|i| i as f64)
+                            .or_else(|_| o.as_f64())
+                            .unwrap();
+                        (printpdf::Mm::from(printpdf::Pt(pt)).0 * 10.0).round() / 10.0
+                    })
+                    .collect();
+                (operation.operator.clone(), values[0], values[1])
+            })
+            .collect();
+        points
+    };
+
+    // The underline is drawn below and the strikethrough line above the baseline
+    let points = render(60);
+    let baseline = points[0].2;
+    let lines: Vec<_> = points[1..]
+        .chunks(2)
+        .map(|l| (l[0].1, l[1].1, ((l[0].2 - baseline) * 10.0).round() / 10.0))
+        .collect();
+    assert_eq!(vec![(10.1, 37.9, -0.5), (37.9, 50.4, 1.1)], lines);
+
+    // Wrapped text is decorated on every line
+    let lines: Vec<_> = render(22)
+        .into_iter()
+        .filter(|(operator, _, _)| operator != "Td")
+        .collect::<Vec<_>>()
+        .chunks(2)
+        .map(|l| (l[0].1, l[1].1, l[0].2))
+        .collect();
+    assert_eq!(
+        vec![(0.0, 19.8, 40.7), (0.0, 6.8, 35.8), (6.8, 19.3, 37.4)],
+        lines
+    );
+    // The metrics of embedded fonts are read from the font data
+    let font_family = load_font_family(None);
+    let font_cache = fonts::FontCache::new(font_family);
+    let font = font_cache.default_font_family().regular;
+    let round = |metrics: fonts::LineMetrics| {
+        let round = |mm: genpdf::Mm| (f64::from(mm) * 100.0).round() / 100.0;
+        (round(metrics.position), round(metrics.thickness))
+    };
+    assert_eq!((-0.18, 0.19), round(font.underline_metrics(12)));
+    assert_eq!((0.99, 0.21), round(font.strikethrough_metrics(12)));
+}
+
+#[test]
+fn superscript() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 20));
+    doc.push(elements::Paragraph::new("x").styled_string("2", style::Effect::Superscript));
+    let mut p = elements::Paragraph::new("June ");
+    p.extend(style::ordinal(21, "en", style::Style::new()));
+    doc.push(p);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![vec!["x", "2", "June ", "21", "st"]],
+        get_page_texts(&output)
+    );
+    let operations: Vec<_> = page_operations(&output, 0)
+        .iter()
+        .filter(|operation| operation.operator == "Ts" || operation.operator == "Tf")
+        .map(|operation| (operation.operator.clone(), operands(operation)))
+        .collect();
+    let op = |operator: &str, value: f64| (operator.to_owned(), vec![value]);
+    // Superscript text and the suffix of the ordinal number are raised and printed with a reduced
+    // font size.
+    assert_eq!(
+        vec![
+            op("Tf", 12.0),
+            op("Tf", 7.0),
+            op("Ts", 3.96),
+            op("Ts", 0.0),
+            op("Tf", 12.0),
+            op("Tf", 7.0),
+            op("Ts", 3.96),
+            op("Ts", 0.0),
+        ],
+        operations
+    );
+}
+
+#[test]
+fn subscript() {
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    let mut paragraph = elements::Paragraph::new("H");
+    paragraph.push_styled("2", style::Effect::Subscript);
+    paragraph.push("O");
+    paragraph.push_styled("a", style::Style::new().superscript().subscript());
+    doc.push(paragraph);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let operations: Vec<_> = content
+        .operations
+        .iter()
+        .filter(|operation| operation.operator == "Ts" || operation.operator == "Tf")
+        .map(|operation| {
+            let o = operation.operands.last().unwrap();
+            let value = o
+                .as_i64()
+                .map(|i| i as f64)
+                .or_else(|_| o.as_f64())
+                .unwrap();
+            (operation.operator.as_str(), value)
+        })
+        .collect();
+    // Subscript text is lowered and printed with a reduced font size, and the subscript effect
+    // replaces the superscript effect
+    assert_eq!(
+        vec![
+            ("Tf", 12.0),
+            ("Tf", 7.0),
+            ("Ts", -1.8),
+            ("Tf", 12.0),
+            ("Ts", 0.0),
+            ("Tf", 7.0),
+            ("Ts", -1.8),
+            ("Ts", 0.0),
+        ],
+        operations
+    );
+}
+
+#[test]
+fn style_opacity() {
+    let render = |element: Box<dyn genpdf::Element>| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((50, 20));
+        doc.push(element);
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+        let content = pdf_doc
+            .get_and_decode_page_content(page_id)
+            .expect("Failed to decode page content");
+        content
+            .operations
+            .iter()
+            .filter(|op| ["gs", "TJ", "f", "S"].contains(&op.operator.as_str()))
+            .map(|op| match op.operands.first() {
+                Some(name) if op.operator == "gs" => name.as_name_str().unwrap().to_owned(),
+                _ => op.operator.clone(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let faded = style::Style::new().with_opacity(0.25);
+    let paragraph = elements::Paragraph::new("a ")
+        .styled_string("b", faded.underline())
+        .with_opacity(0.5);
+    // The style opacity is multiplied with the opacity of the area and reset after the text
+    assert_eq!(
+        vec![
+            "GenpdfOpacity500",
+            "TJ",
+            "GenpdfOpacity125",
+            "TJ",
+            "GenpdfOpacity500",
+            "GenpdfOpacity125",
+            "S",
+        ],
+        render(Box::new(paragraph))
+    );
+
+    let mut table = elements::TableLayout::new(vec![1]).with_background_opacity(0.5);
+    table
+        .row()
+        .set_background_color(style::Color::Greyscale(200))
+        .element(elements::Text::new("c"))
+        .push()
+        .unwrap();
+    // The background opacity does not apply to the content of the cells
+    assert_eq!(vec!["GenpdfOpacity500", "f", "TJ"], render(Box::new(table)));
+}
+
+#[test]
+fn text_background() {
+    let render = |element: Box<dyn genpdf::Element>| {
+        let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+        doc.set_minimal_conformance();
+        doc.set_paper_size((22, 50));
+        doc.push(element);
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().expect("Missing page");
+        let content = pdf_doc
+            .get_and_decode_page_content(page_id)
+            .expect("Failed to decode page content");
+        let mm = |o: &lopdf::Object| {
+            let pt = o
+                .as_i64()
+                .map(|i| i as f64)
+                .or_else(|_| o.as_f64())
+                .unwrap();
+            printpdf::Mm::from(printpdf::Pt(pt)).0
+        };
+        let round = |mm: f64| (mm * 10.0).round() / 10.0;
+        // Returns the width and height of the filled rectangles
+        content
+            .operations
+            .iter()
+            .filter(|op| op.operator == "m" || op.operator == "l")
+            .collect::<Vec<_>>()
+            .chunks(4)
+            .map(|points| {
+                let width = mm(&points[1].operands[0]) - mm(&points[0].operands[0]);
+                let height = mm(&points[1].operands[1]) - mm(&points[2].operands[1]);
+                (round(width), round(height))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // The background follows the wrapped lines and does not include trailing spaces
+    let highlight = style::Style::new().with_background(style::Color::Rgb(255, 255, 0));
+    assert_eq!(
+        vec![(20.5, 4.9), (6.8, 4.9)],
+        render(Box::new(
+            elements::Paragraph::new("plain ").styled_string("highlighted text", highlight),
+        ))
+    );
+    assert_eq!(
+        vec![(6.6, 4.9)],
+        render(Box::new(elements::Text::new(style::StyledString::new(
+            "line ", highlight
+        ))))
+    );
+}
+
+#[test]
+fn paragraph_markup() {
+    let strings = style::parse_markup(
+        "H<sub>2</sub>O &amp; <b>bold <i>italic</i></b><color=#ff8000>!</color>",
+    )
+    .expect("Failed to parse markup");
+    let strings: Vec<_> = strings.into_iter().map(|s| (s.s, s.style)).collect();
+    let bold = style::Style::new().bold();
+    assert_eq!(
+        vec![
+            ("H".to_owned(), style::Style::new()),
+            ("2".to_owned(), style::Style::new().subscript()),
+            ("O & ".to_owned(), style::Style::new()),
+            ("bold ".to_owned(), bold),
+            ("italic".to_owned(), bold.italic()),
+            (
+                "!".to_owned(),
+                style::Style::new().with_color(style::Color::Rgb(255, 128, 0))
+            ),
+        ],
+        strings
+    );
+
+    for markup in &[
+        "<b>bold",
+        "<b>bold</i>",
+        "</b>",
+        "<x>",
+        "<color=red>",
+        "&nbsp;",
+        "a < b",
+    ] {
+        let err = style::parse_markup(markup).expect_err("Parsed invalid markup");
+        assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData));
+    }
+
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.push(elements::Paragraph::from_markup("<b>bold</b> and <i>italic</i>").unwrap());
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![vec!["bold", " ", "and ", "italic"]],
+        get_page_texts(&output)
+    );
+}
+
+#[cfg(feature = "markdown")]
+#[test]
+fn markdown() {
+    let markdown = "# Title\n\
+                    \n\
+                    Some **bold** and *italic* text\n\
+                    with `code` and a [link](https://example.org).\n\
+                    \n\
+                    - one\n\
+                    - two\n  \
+                      1. nested\n\
+                    \n\
+                    ```\n\
+                    let x = 1;\n\
+                    ```\n\
+                    \n\
+                    | A | B |\n\
+                    |---|--:|\n\
+                    | 1 | 2 |\n";
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.push(genpdf::markdown::to_elements(markdown));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![vec![
+            "Title", "Some ", "bold", " ", "and ", "italic", " ", "text ", "with ", "code", " ",
+            "and ", "a ", "link", ".", "one", "•", "two", "nested", "1.", "•", "let ", "x ", "= ",
+            "1;", "A", "B", "1", "2",
+        ]],
+        get_page_texts(&output)
+    );
+}
+
+#[cfg(feature = "html")]
+#[test]
+fn html() {
+    let html = r#"<!DOCTYPE html>
+        <h1>Title</h1>
+        <p>Some <b>bold</b> and <i>italic</i>
+           text<br>with <span style="color: #f00">red</span> &amp; a <a href="https://example.org">link</a>.</p>
+        <ul><li>one</li><li>two <ol start="3"><li>nested</li></ol></li></ul>
+        <table>
+          <thead><tr><th>A</th><th>B</th></tr></thead>
+          <tbody><tr><td colspan="2" style="text-align: right">1</td></tr></tbody>
+        </table>"#;
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.push(genpdf::html::to_elements(html).expect("Failed to convert HTML"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![vec![
+            "Title", "Some ", "bold", " ", "and ", "italic", " ", "text", "with ", "red", " ",
+            "& ", "a ", "link", ".", "one", "•", "two", "nested", "3.", "•", "A", "B", "1",
+        ]],
+        get_page_texts(&output)
+    );
+
+    for html in &[
+        "<p>a",
+        "<p>a</b>",
+        "<x>a</x>",
+        "<b><p>a</p></b>",
+        "<p>&foo;</p>",
+        "<ul>a</ul>",
+        "<p style=\"color: reddish\">a</p>",
+        "<table><tr><td colspan=\"1001\">a</td></tr></table>",
+        "<table><tr><td colspan=\"1000\">a</td><td>b</td></tr></table>",
+    ] {
+        match genpdf::html::to_elements(html) {
+            Err(err) => assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData)),
+            Ok(_) => panic!("Converted invalid HTML: {}", html),
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svg() {
+    let svg: elements::Svg = r##"<?xml version="1.0"?>
+        <svg xmlns="http://www.w3.org/2000/svg" width="40mm" height="20mm" viewBox="0 0 4 2">
+          <!-- a comment -->
+          <rect width="2" height="2" fill="#ff0000"/>
+          <g transform="translate(2 0)" style="fill: none; stroke: blue">
+            <path d="M0 0 L2 2 Q 1 0 0 2 z" stroke-width="0.1"/>
+            <circle cx="1" cy="1" r="1" fill-opacity="0.5" fill="lime"/>
+          </g>
+        </svg>"##
+        .parse()
+        .expect("Failed to parse SVG image");
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.set_paper_size((100, 100));
+    doc.push(svg.with_alignment(Alignment::Center));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let operands = |operator: &str| -> Vec<Vec<f64>> {
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == operator)
+            .map(|operation| {
+                operation
+                    .operands
+                    .iter()
+                    .map(|o| {
+                        o.as_i64()
+                            .map(|i| i as f64)
+                            .or_else(|_| o.as_f64())
+                            .unwrap()
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+    // The image is clipped at its borders and centered on the page.
+    assert_eq!(vec![vec![85.04, 226.77, 113.39, 56.69]], operands("re"));
+    assert_eq!(
+        vec![vec![1.0, 0.0, 0.0], vec![0.0, 1.0, 0.0]],
+        operands("rg")
+    );
+    assert_eq!(vec![vec![0.0, 0.0, 1.0]], operands("RG"));
+    assert_eq!(2, operands("f").len());
+    assert_eq!(2, operands("S").len());
+    // The quadratic curve and the circle are drawn with cubic curves, the circle twice.
+    assert_eq!(9, operands("c").len());
+    assert_eq!(vec![vec![2.83], vec![28.35]], operands("w"));
+    assert_eq!(
+        vec![b"GenpdfOpacity500".to_vec()],
+        content
+            .operations
+            .iter()
+            .filter(|operation| operation.operator == "gs")
+            .map(|operation| operation.operands[0].as_name().unwrap().to_vec())
+            .collect::<Vec<_>>()
+    );
+
+    for svg in &[
+        "<svg>",
+        "<svg width=\"1\" height=\"1\"><path d=\"M 0 0 X\"/></svg>",
+        "<rect/>",
+        "<svg/>",
+    ] {
+        let err = svg
+            .parse::<elements::Svg>()
+            .expect_err("Parsed invalid SVG image");
+        assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData));
+    }
+}
+
+#[test]
+fn canvas() {
+    let mut doc = get_document();
+    doc.set_paper_size((50, 40));
+    doc.push(elements::Break::new(1));
+    doc.push(
+        elements::Canvas::new((40, 20))
+            .with_shape(
+                elements::Shape::rectangle((0, 0), (40, 20))
+                    .with_fill_color(style::Color::Rgb(255, 0, 0)),
+            )
+            .with_shape(elements::Shape::circle((10, 10), 5))
+            .with_shape(
+                elements::Shape::path((20, 15))
+                    .curve_by((5, -15), (15, -15), (18, 0))
+                    .line_by((0, -10))
+                    .with_line_style(style::LineStyle::new().with_thickness(0.5)),
+            ),
+    );
+    // The second canvas does not fit on the first page.
+    doc.push(elements::Canvas::new((40, 20)).with_shape(elements::Shape::line((0, 0), (40, 20))));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    assert_eq!(2, pages.len());
+    let operations = |page_id| -> Vec<(String, Vec<f64>)> {
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .into_iter()
+            .filter(|operation| {
+                ["re", "rg", "w", "m", "l", "c", "h", "f", "S"]
+                    .contains(&operation.operator.as_str())
+            })
+            .map(|operation| {
+                let operands = operation
+                    .operands
+                    .iter()
+                    .map(|o| {
+                        o.as_i64()
+                            .map(|i| i as f64)
+                            .or_else(|_| o.as_f64())
+                            .unwrap()
+                    })
+                    .collect();
+                (operation.operator, operands)
+            })
+            .collect()
+    };
+    let op = |operator: &str, operands: &[f64]| (operator.to_owned(), operands.to_vec());
+    assert_eq!(
+        vec![
+            op("re", &[0.0, 42.72, 113.39, 56.69]),
+            op("rg", &[1.0, 0.0, 0.0]),
+            op("m", &[0.0, 99.42]),
+            op("l", &[113.39, 99.42]),
+            op("l", &[113.39, 42.72]),
+            op("l", &[0.0, 42.72]),
+            op("h", &[]),
+            op("f", &[]),
+            op("w", &[0.28]),
+            op("m", &[42.52, 71.07]),
+            op("c", &[42.52, 63.24, 36.17, 56.9, 28.35, 56.9]),
+            op("c", &[20.52, 56.9, 14.17, 63.24, 14.17, 71.07]),
+            op("c", &[14.17, 78.9, 20.52, 85.24, 28.35, 85.24]),
+            op("c", &[36.17, 85.24, 42.52, 78.9, 42.52, 71.07]),
+            op("h", &[]),
+            op("S", &[]),
+            op("w", &[1.42]),
+            op("m", &[56.69, 56.9]),
+            op("c", &[70.87, 99.42, 99.21, 99.42, 107.72, 56.9]),
+            op("l", &[107.72, 85.24]),
+            op("S", &[]),
+        ],
+        operations(pages[0])
+    );
+    assert_eq!(
+        vec![
+            op("re", &[0.0, 56.69, 113.39, 56.69]),
+            op("w", &[0.28]),
+            op("m", &[0.0, 113.39]),
+            op("l", &[113.39, 56.69]),
+            op("S", &[]),
+        ],
+        operations(pages[1])
+    );
+}
+
+#[cfg(feature = "barcodes")]
+#[test]
+fn barcodes() {
+    let mut doc = get_document();
+    doc.set_paper_size((60, 50));
+    let code = elements::QrCode::new("HELLO WORLD", elements::QrErrorCorrection::Quartile)
+        .expect("Failed to encode QR code");
+    assert_eq!(1, code.version());
+    assert_eq!(21, code.module_count());
+    // Every module is 1 mm wide.
+    doc.push(code.clone().with_size(29));
+    // The barcode does not fit on the first page.
+    doc.push(
+        elements::Barcode::ean13("400638133393")
+            .expect("Failed to encode barcode")
+            .with_quiet_zone(5)
+            .with_module_width(0.5)
+            .with_height(20)
+            .with_human_readable(true)
+            .with_alignment(genpdf::Alignment::Center),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![Vec::<String>::new(), vec!["4006381333931".to_owned()]],
+        get_page_texts(&output)
+    );
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    let operations = |page_id| -> Vec<lopdf::content::Operation> {
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        lopdf::content::Content::decode(&content)
+            .unwrap()
+            .operations
+    };
+    let number = |o: &lopdf::Object| {
+        o.as_i64()
+            .map(|i| i as f64)
+            .or_else(|_| o.as_f64())
+            .unwrap()
+    };
+    let count = |operations: &[lopdf::content::Operation], operator: &str| {
+        operations
+            .iter()
+            .filter(|operation| operation.operator == operator)
+            .count()
+    };
+
+    let qr_operations = operations(pages[0]);
+    let dark_runs: usize = (0..21)
+        .map(|row| {
+            (0..21)
+                .filter(|&column| {
+                    code.is_dark(column, row) && (column == 0 || !code.is_dark(column - 1, row))
+                })
+                .count()
+        })
+        .sum();
+    assert_eq!(1, count(&qr_operations, "f"));
+    assert_eq!(dark_runs, count(&qr_operations, "m"));
+    // The top left module of the finder pattern starts after the quiet zone.
+    let first = qr_operations
+        .iter()
+        .find(|operation| operation.operator == "m")
+        .unwrap();
+    let first: Vec<_> = first.operands.iter().map(number).collect();
+    assert_eq!(vec![11.34, 130.39], first);
+
+    let barcode_operations = operations(pages[1]);
+    assert_eq!(1, count(&barcode_operations, "f"));
+    // An EAN-13 code consists of 30 bars.
+    assert_eq!(30, count(&barcode_operations, "m"));
+    let first = barcode_operations
+        .iter()
+        .find(|operation| operation.operator == "m")
+        .unwrap();
+    let first: Vec<_> = first.operands.iter().map(number).collect();
+    // The code is 105 × 0.5 mm wide and centered.
+    assert_eq!(vec![17.72, 141.73], first);
+}
+
+#[test]
+fn charts() {
+    use elements::chart;
+
+    let mut doc = get_document();
+    doc.set_paper_size((100, 120));
+    doc.push(
+        chart::BarChart::new((100, 50), vec!["A", "B"])
+            .with_series(chart::Series::new("X", vec![1.0, 3.0]))
+            .with_series(chart::Series::new("Y", vec![2.0, -1.0])),
+    );
+    doc.push(
+        chart::LineChart::new((100, 50), vec!["A", "B", "C"])
+            .with_series(chart::Series::new("X", vec![1.0, 3.0, 2.0]))
+            .with_legend(false),
+    );
+    // The pie chart does not fit on the first page.
+    doc.push(
+        chart::PieChart::new((100, 50))
+            .with_slice("P", 1.0)
+            .with_slice("Q", 3.0)
+            .with_slice("R", 0.0),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec!["X", "Y", "-1", "0", "1", "2", "3", "A", "B", "0", "1", "2", "3", "A", "B", "C"],
+            vec!["P (25 %)", "Q (75 %)"],
+        ],
+        get_page_texts(&output)
+    );
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let pages: Vec<_> = pdf_doc.get_pages().values().copied().collect();
+    let operations = |page_id| -> Vec<(String, Vec<f64>)> {
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .into_iter()
+            .filter(|operation| ["m", "l", "c", "h"].contains(&operation.operator.as_str()))
+            .map(|operation| {
+                let operands = operation
+                    .operands
+                    .iter()
+                    .map(|o| {
+                        o.as_i64()
+                            .map(|i| i as f64)
+                            .or_else(|_| o.as_f64())
+                            .unwrap()
+                    })
+                    .collect();
+                (operation.operator, operands)
+            })
+            .collect()
+    };
+    let op = |operator: &str, operands: &[f64]| (operator.to_owned(), operands.to_vec());
+    let contains = |operations: &[(String, Vec<f64>)], expected: &[(String, Vec<f64>)]| {
+        operations
+            .windows(expected.len())
+            .any(|window| window == expected)
+    };
+
+    let first_page = operations(pages[0]);
+    // The bars of the series X and Y
+    assert!(contains(
+        &first_page,
+        &[
+            op("m", &[29.37, 284.02]),
+            op("l", &[81.97, 284.02]),
+            op("l", &[81.97, 259.44]),
+            op("l", &[29.37, 259.44]),
+            op("h", &[]),
+            op("m", &[160.86, 333.17]),
+            op("l", &[213.46, 333.17]),
+            op("l", &[213.46, 259.44]),
+            op("l", &[160.86, 259.44]),
+            op("h", &[]),
+        ]
+    ));
+    assert!(contains(
+        &first_page,
+        &[
+            op("m", &[213.46, 259.44]),
+            op("l", &[266.06, 259.44]),
+            op("l", &[266.06, 234.87]),
+            op("l", &[213.46, 234.87]),
+            op("h", &[]),
+        ]
+    ));
+    // The line of the series X
+    assert!(contains(
+        &first_page,
+        &[
+            op("m", &[56.44, 113.76]),
+            op("l", &[145.55, 191.44]),
+            op("l", &[234.66, 152.6]),
+        ]
+    ));
+
+    // The slices P and Q, starting at the top
+    let second_page = operations(pages[1]);
+    assert!(contains(
+        &second_page,
+        &[
+            op("m", &[141.73, 278.4]),
+            op("l", &[141.73, 335.91]),
+            op("c", &[173.49, 335.91, 199.24, 310.16, 199.24, 278.4]),
+            op("h", &[]),
+        ]
+    ));
+    assert!(contains(
+        &second_page,
+        &[
+            op("m", &[141.73, 278.4]),
+            op("l", &[199.24, 278.4]),
+            op("c", &[199.24, 246.64, 173.49, 220.9, 141.73, 220.9]),
+            op("c", &[109.97, 220.9, 84.23, 246.64, 84.23, 278.4]),
+            op("c", &[84.23, 310.16, 109.97, 335.91, 141.73, 335.91]),
+            op("h", &[]),
+        ]
+    ));
+}
+
+#[test]
+fn page_background() {
+    let render = |has_background: bool| {
+        let mut doc = get_document();
+        doc.set_paper_size((100, 50));
+        let mut decorator = genpdf::SimplePageDecorator::new();
+        decorator.set_margins(10);
+        decorator.set_header(|page| elements::Text::new(format!("Header {}", page)));
+        if has_background {
+            decorator.set_background(|page, _| {
+                elements::Text::new(format!("Background {}", page))
+                    .styled(style::Style::new().with_font_size(30))
+            });
+        }
+        doc.set_page_decorator(decorator);
+        doc.push(elements::Paragraph::new("Body"));
+        doc.push(elements::PageBreak::new());
+        doc.push(elements::Paragraph::new("Body"));
+
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        output
+    };
+
+    let output = render(true);
+    assert_eq!(
+        vec![
+            vec!["Background 1", "Header 1", "Body"],
+            vec!["Background 2", "Header 2", "Body"],
+        ],
+        get_page_texts(&output)
+    );
+
+    // The background does not change the position of the content.
+    let positions = |output: &[u8]| -> Vec<Vec<f64>> {
+        let pdf_doc = lopdf::Document::load_mem(output).expect("Failed to load document");
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        let content = lopdf::content::Content::decode(&content).unwrap();
+        content
+            .operations
+            .into_iter()
+            .filter(|operation| operation.operator == "Td")
+            .map(|operation| {
+                operation
+                    .operands
+                    .iter()
+                    .map(|o| o.as_f64().unwrap())
+                    .collect()
+            })
+            .collect()
+    };
+    let with_background = positions(&output);
+    let without_background = positions(&render(false));
+    assert_eq!(3, with_background.len());
+    // The background is printed at the top left corner of the page.
+    assert!(with_background[0][1] > without_background[0][1]);
+    assert_eq!(without_background, with_background[1..].to_vec());
+}
+
+#[test]
+fn rotated_element() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    doc.push(elements::Text::new("Vertical").rotated(-90.0));
+    doc.push(elements::Text::new("Below"));
+    doc.push(
+        elements::Text::new("Watermark")
+            .rotated(45.0)
+            .with_anchor((10, 0)),
+    );
+    doc.push(elements::Text::new("Last"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let operations: Vec<(String, Vec<f64>)> = content
+        .operations
+        .into_iter()
+        .filter(|operation| ["cm", "Td"].contains(&operation.operator.as_str()))
+        .map(|operation| {
+            let operands = operation
+                .operands
+                .iter()
+                .map(|o| {
+                    o.as_i64()
+                        .map(|i| i as f64)
+                        .or_else(|_| o.as_f64())
+                        .unwrap()
+                })
+                .collect();
+            (operation.operator, operands)
+        })
+        .collect();
+    let op = |operator: &str, operands: &[f64]| (operator.to_owned(), operands.to_vec());
+    assert_eq!(
+        vec![
+            // Rotated by 90 degrees counter-clockwise around the center of the text
+            op("cm", &[0.0, 1.0, -1.0, 0.0, 267.99, 254.02]),
+            op("Td", &[-15.57, 256.85]),
+            // Moved down by the width of the rotated text
+            op("Td", &[-1.18, 227.41]),
+            // Rotated by 45 degrees clockwise around the anchor
+            op("cm", &[0.71, -0.71, 0.71, 0.71, -150.5, 85.82]),
+            op("Td", &[-0.4, 213.44]),
+            // Not moved by the rotated text
+            op("Td", &[-1.18, 199.47]),
+        ],
+        operations
+    );
+}
+
+#[test]
+fn positioned_layout() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    let mut decorator = genpdf::SimplePageDecorator::new();
+    decorator.set_margins(10);
+    doc.set_page_decorator(decorator);
+    doc.push(elements::Paragraph::new("Before"));
+    doc.push(
+        elements::PositionedLayout::new()
+            .element((50, 20), elements::Text::new("Left"))
+            .element_with_size(
+                (50, 30),
+                (40, 10),
+                elements::Paragraph::new("Right").aligned(Alignment::Right),
+            ),
+    );
+    doc.push(elements::Paragraph::new("After"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![vec!["Before", "Left", "Right", "After"]],
+        get_page_texts(&output)
+    );
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let content = pdf_doc.get_page_content(page_id).unwrap();
+    let content = lopdf::content::Content::decode(&content).unwrap();
+    let positions: Vec<Vec<f64>> = content
+        .operations
+        .into_iter()
+        .filter(|operation| operation.operator == "Td")
+        .map(|operation| {
+            operation
+                .operands
+                .iter()
+                .map(|o| printpdf::Mm::from(printpdf::Pt(o.as_f64().unwrap())).0)
+                .map(|mm| (mm * 10.0).round() / 10.0)
+                .collect()
+        })
+        .collect();
+    // The positioned elements ignore the margins and do not move the following elements.
+    assert_eq!(
+        vec![
+            vec![9.6, 86.1],
+            vec![49.6, 76.1],
+            vec![78.4, 66.1],
+            vec![10.0, 81.1]
+        ],
+        positions
+    );
+}
+
+#[test]
+fn overlay() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    doc.push(
+        elements::Overlay::new()
+            .element(elements::Break::new(3))
+            .element(elements::Paragraph::new("Top"))
+            .element(elements::Paragraph::new("Stamp").aligned(Alignment::Right)),
+    );
+    doc.push(elements::Paragraph::new("Next"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(vec![vec!["Top", "Stamp", "Next"]], get_page_texts(&output));
+
+    let mut reference = get_document();
+    reference.set_paper_size((100, 100));
+    reference.push(elements::Paragraph::new("Top"));
+    reference.push(elements::Break::new(2));
+    reference.push(elements::Paragraph::new("Next"));
+    let mut reference_output = Vec::new();
+    reference
+        .render(&mut reference_output)
+        .expect("Failed to render document");
+
+    let get_positions = |output: &[u8]| -> Vec<f64> {
+        text_positions(&page_operations(output, 0))
+            .into_iter()
+            .map(|(_, y)| y)
+            .collect()
+    };
+    let positions = get_positions(&output);
+    let reference_positions = get_positions(&reference_output);
+    assert_eq!(3, positions.len());
+    // All elements of the overlay start at the top of the overlay.
+    assert_eq!(positions[0], positions[1]);
+    // The next element starts below the highest element of the overlay.
+    assert_eq!(reference_positions[0], positions[0]);
+    assert_eq!(reference_positions[1], positions[2]);
+}
+
+#[test]
+fn horizontal_layout() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 12));
+    doc.push(
+        elements::LinearLayout::horizontal()
+            .element_with_width(
+                elements::Paragraph::new("Label"),
+                elements::ColumnWidth::Absolute(genpdf::Mm::from(20)),
+            )
+            .element(elements::Paragraph::new("a b c d e f g h i j k l"))
+            .element_with_width(
+                elements::Paragraph::new("Right"),
+                elements::ColumnWidth::Weight(3),
+            ),
+    );
+    doc.push(elements::Paragraph::new("After"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec![
+                "Label", "a ", "b ", "c ", "d ", "e ", "f ", "g ", "h ", "i ", "j ", "k ", "Right"
+            ],
+            vec!["l", "After"],
+        ],
+        get_page_texts(&output)
+    );
+
+    let positions: Vec<Vec<(f64, f64)>> = (0..2)
+        .map(|page| text_positions(&page_operations(&output, page)))
+        .collect();
+    // The first column has a fixed width of 20 mm, the other columns share the remaining 80 mm
+    // with the weights 1 and 3.
+    let (label_x, label_y) = positions[0][0];
+    let (middle_x, middle_y) = positions[0][1];
+    let (right_x, right_y) = positions[0][3];
+    assert_close(20.0, mm(middle_x - label_x), 0.5);
+    assert_close(40.0, mm(right_x - label_x), 0.5);
+    assert_eq!(label_y, middle_y);
+    assert_eq!(label_y, right_y);
+    // The middle column is continued in the same column on the next page.
+    let (continued_x, continued_y) = positions[1][0];
+    assert_close(20.0, mm(continued_x - label_x), 0.5);
+    assert_eq!(label_y, continued_y);
+    assert!(positions[1][1].1 < continued_y);
+}
+
+#[test]
+fn spacer_and_fill() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    doc.push(elements::Paragraph::new("Top"));
+    doc.push(elements::Spacer::vertical(20));
+    doc.push(elements::Paragraph::new("Spaced"));
+    doc.push(elements::Fill::new().with_content(elements::Paragraph::new("Bottom")));
+    doc.push(elements::Paragraph::new("Second"));
+    doc.push(elements::Fill::new());
+    doc.push(elements::Paragraph::new("Third"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec!["Top", "Spaced", "Bottom"],
+            vec!["Second"],
+            vec!["Third"]
+        ],
+        get_page_texts(&output)
+    );
+
+    let operations = page_operations(&output, 0);
+    let positions = text_positions(&operations);
+    let translations = operator_operands(&operations, "cm");
+    assert_eq!(3, positions.len());
+    assert_eq!(1, translations.len());
+    // The spacer adds 20 mm to the line height of 14 pt.
+    assert_close(20.0 + mm(14.0), mm(positions[0].1 - positions[1].1), 0.1);
+    // The content of the fill is moved to the bottom of the page.
+    let bottom = positions[2].1 + translations[0][5];
+    assert!(bottom > 0.0 && bottom < 14.0);
+}
+
+#[test]
+fn horizontal_rule() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    doc.push(elements::Paragraph::new("Above"));
+    doc.push(
+        elements::HorizontalRule::new()
+            .with_line_style(
+                style::LineStyle::new()
+                    .with_thickness(1)
+                    .with_color(style::Color::Rgb(255, 0, 0))
+                    .with_dash(&[2, 1]),
+            )
+            .with_margins((4, 10)),
+    );
+    doc.push(elements::Paragraph::new("Below"));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let operations = page_operations(&output, 0);
+    let get_operands = |operator: &str| -> Vec<f64> {
+        operator_operands(&operations, operator)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| panic!("Missing operator {}", operator))
+    };
+
+    assert_close(pt(1.0), get_operands("w")[0], 0.05);
+    assert_eq!(vec![1.0, 0.0, 0.0], get_operands("RG"));
+    let dash = get_operands("d");
+    assert_close(pt(2.0), dash[0], 0.05);
+    assert_close(pt(1.0), dash[1], 0.05);
+    // The line is shortened by the horizontal margins.
+    let start = get_operands("m");
+    let end = get_operands("l");
+    assert_close(pt(10.0), start[0], 0.05);
+    assert_close(pt(90.0), end[0], 0.05);
+    assert_eq!(start[1], end[1]);
+    // The vertical margins are added above and below the line.
+    let positions = text_positions(&operations);
+    let line_height = positions[0].1 - positions[1].1 - pt(4.0 + 1.0 + 4.0);
+    assert_close(14.0, line_height, 0.1);
+    assert!(positions[0].1 - start[1] > pt(4.5));
+}
+
+#[test]
+fn margined_element() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    doc.push(elements::Paragraph::new("First").with_margins((0, 0, 10, 0)));
+    doc.push(elements::Paragraph::new("Second").with_margins((4, 0, 6, 5)));
+    doc.push(elements::Paragraph::new("Third"));
+    doc.push(elements::Paragraph::new("Fourth").with_margins((8, 0, 0, 0)));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![vec!["First", "Second", "Third", "Fourth"]],
+        get_page_texts(&output)
+    );
+
+    let positions = text_positions(&page_operations(&output, 0));
+    let gap = |i: usize| mm(positions[i].1 - positions[i + 1].1 - 13.98);
+    // The bottom margin of the first element collapses with the top margin of the second element.
+    assert_close(10.0, gap(0), 0.05);
+    assert_close(6.0, gap(1), 0.05);
+    // The third element has no margins, so the top margin of the fourth element is added.
+    assert_close(8.0, gap(2), 0.05);
+    // The left margin moves the second element to the right.
+    assert_close(5.0, mm(positions[1].0 - positions[0].0), 0.5);
+}
+
+#[test]
+fn box_decoration() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 40));
+    doc.push(
+        elements::BoxDecoration::new(elements::Paragraph::new("Box"))
+            .with_border(style::LineStyle::new().with_thickness(0.5))
+            .with_left_border(
+                style::LineStyle::new()
+                    .with_thickness(1.5)
+                    .with_color(style::Color::Rgb(255, 0, 0)),
+            )
+            .with_corner_radius(2)
+            .with_background_color(style::Color::Rgb(0, 0, 255))
+            .with_padding(2),
+    );
+    let mut layout = elements::LinearLayout::vertical();
+    for i in 0..8 {
+        layout.push(elements::Paragraph::new(format!("Line {}", i)));
+    }
+    doc.push(
+        elements::BoxDecoration::new(layout)
+            .with_border(style::LineStyle::new())
+            .with_corner_radius(2),
+    );
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert_eq!(
+        vec![
+            vec![
+                "Box", "Line ", "0", "Line ", "1", "Line ", "2", "Line ", "3", "Line ", "4",
+                "Line ", "5"
+            ],
+            vec!["Line ", "6", "Line ", "7"],
+        ],
+        get_page_texts(&output)
+    );
+
+    let pages: Vec<_> = (0..2).map(|page| page_operations(&output, page)).collect();
+    let count = |page: usize, operator: &str| count_operator(&pages[page], operator);
+    // The first box has a background and four borders.  The second box is split, so there is no
+    // bottom border on the first page and no top border on the second page.
+    assert_eq!(1, count(0, "f"));
+    assert_eq!(4 + 3, count(0, "S"));
+    assert_eq!(3, count(1, "S"));
+    // The corners at the page break are not rounded.  Every rounded corner is drawn as one
+    // curve for the background and two curves for the borders.
+    assert_eq!(4 + 8 + 4, count(0, "c"));
+    assert_eq!(4, count(1, "c"));
+}
+
+#[test]
+fn rounded_line_styles() {
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    let line_style = style::LineStyle::new()
+        .with_dash(&[2, 1])
+        .with_line_cap(style::LineCap::Round)
+        .with_line_join(style::LineJoin::Bevel)
+        .with_corner_radius(3);
+    doc.push(elements::Paragraph::new("Frame").framed(line_style));
+    let mut table = elements::TableLayout::new(vec![1, 1]);
+    table.set_cell_decorator(elements::FrameCellDecorator::with_line_style(
+        true,
+        true,
+        false,
+        style::LineStyle::new().with_corner_radius(2),
+    ));
+    for _ in 0..2 {
+        table
+            .row()
+            .element(elements::Paragraph::new("a"))
+            .element(elements::Paragraph::new("b"))
+            .push()
+            .expect("Invalid table row");
+    }
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let operations = page_operations(&output, 0);
+    // The line cap and join are set for the frame and reset for the table.
+    assert_eq!(
+        vec![vec![1.0], vec![0.0]],
+        operator_operands(&operations, "J")
+    );
+    assert_eq!(
+        vec![vec![2.0], vec![0.0]],
+        operator_operands(&operations, "j")
+    );
+    // The frame is drawn as a single closed path with four rounded corners, and the table has
+    // four rounded outer corners.
+    assert_eq!(1, count_operator(&operations, "h"));
+    assert_eq!(4 + 4, count_operator(&operations, "c"));
+}
+
+#[test]
+fn frame_cell_decorator_borders() {
+    use elements::TableBorder;
+
+    let mut doc = get_document();
+    doc.set_paper_size((100, 100));
+    let mut table = elements::TableLayout::new(vec![1, 1]);
+    table.set_cell_decorator(
+        elements::FrameCellDecorator::new(true, true, false)
+            .with_border_line_style(
+                TableBorder::InnerHorizontal,
+                style::LineStyle::new().with_dash(&[1, 1]),
+            )
+            .with_hidden_border(TableBorder::Left)
+            .with_hidden_border(TableBorder::Right)
+            .with_hidden_border(TableBorder::InnerVertical),
+    );
+    for _ in 0..2 {
+        table
+            .row()
+            .element(elements::Paragraph::new("a"))
+            .element(elements::Paragraph::new("b"))
+            .push()
+            .expect("Invalid table row");
+    }
+    doc.push(table);
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let operations = page_operations(&output, 0);
+    let points: Vec<Vec<f64>> = operations
+        .iter()
+        .filter(|operation| operation.operator == "m" || operation.operator == "l")
+        .map(operands)
+        .collect();
+    // Only the top, inner horizontal and bottom borders are drawn for both columns.
+    assert_eq!(6, count_operator(&operations, "S"));
+    assert_eq!(12, points.len());
+    for line in points.chunks(2) {
+        assert_eq!(line[0][1], line[1][1]);
+    }
+    // A dash pattern is set for the inner horizontal borders.
+    assert!(count_operator(&operations, "d") > 0);
+}
+
+#[test]
+fn pdf_a_conformance() {
+    let get_document = || {
+        let font_family = load_font_family(None);
+        let mut doc = genpdf::Document::new(font_family);
+        doc.set_conformance(genpdf::Conformance::PdfA2b);
+        doc.set_creation_date(printpdf::OffsetDateTime::unix_epoch());
+        doc.set_modification_date(printpdf::OffsetDateTime::unix_epoch());
+        doc
+    };
+
+    let mut doc = get_document();
+    doc.set_title("Invoice <Zürich & Bern>");
+    doc.push(elements::Paragraph::new("Invoice"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    assert_eq!("1.7", pdf_doc.version);
+    let catalog = pdf_doc.catalog().unwrap();
+
+    let metadata = catalog.get(b"Metadata").unwrap().as_reference().unwrap();
+    let metadata = pdf_doc.get_object(metadata).unwrap().as_stream().unwrap();
+    assert!(!metadata.dict.has(b"Filter"));
+    let metadata = String::from_utf8(metadata.content.clone()).unwrap();
+    assert!(metadata.contains("<pdfaid:part>2</pdfaid:part>"));
+    assert!(metadata.contains("<pdfaid:conformance>B</pdfaid:conformance>"));
+    assert!(metadata.contains("Invoice &lt;Zürich &amp; Bern&gt;"));
+    assert!(metadata.contains("<xmp:CreateDate>1970-01-01T00:00:00+00:00</xmp:CreateDate>"));
+
+    let output_intents = catalog.get(b"OutputIntents").unwrap().as_array().unwrap();
+    assert_eq!(1, output_intents.len());
+    let output_intent = output_intents[0].as_dict().unwrap();
+    assert_eq!(
+        b"GTS_PDFA1",
+        output_intent.get(b"S").unwrap().as_name().unwrap()
+    );
+    let profile = output_intent.get(b"DestOutputProfile").unwrap();
+    let profile = pdf_doc
+        .get_object(profile.as_reference().unwrap())
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    assert_eq!(3, profile.dict.get(b"N").unwrap().as_i64().unwrap());
+    let profile = profile.decompressed_content().unwrap();
+    assert_eq!(
+        profile.len() as u32,
+        u32::from_be_bytes([profile[0], profile[1], profile[2], profile[3]])
+    );
+    assert_eq!(b"acsp", &profile[36..40]);
+
+    let info = pdf_doc
+        .trailer
+        .get(b"Info")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let info = pdf_doc.get_object(info).unwrap().as_dict().unwrap();
+    assert!(!info.has(b"GTS_PDFXVersion"));
+    let title = info.get(b"Title").unwrap().as_str().unwrap();
+    assert_eq!(&[0xfe, 0xff], &title[..2]);
+
+    let is_unsupported = |doc: &mut genpdf::Document| {
+        let err = doc.render(Vec::new()).unwrap_err();
+        matches!(err.kind(), genpdf::error::ErrorKind::UnsupportedFeature)
+    };
+
+    let mut doc = get_document();
+    doc.push(elements::Paragraph::new("CMYK").styled(style::Color::Cmyk(255, 0, 0, 0)));
+    assert!(is_unsupported(&mut doc));
+
+    let mut doc = get_document();
+    doc.push(elements::Paragraph::new("\u{4e00}"));
+    assert!(is_unsupported(&mut doc));
+
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_conformance(genpdf::Conformance::PdfA2b);
+    doc.push(elements::Paragraph::new("Built-in"));
+    assert!(is_unsupported(&mut doc));
+}
+
+#[test]
+fn document_metadata() {
+    let mut doc = get_document();
+    doc.set_title("Invoice");
+    doc.set_author("Jane Doe");
+    doc.set_subject("Invoice for <order> 42");
+    doc.set_keywords(vec!["invoice", "order"]);
+    doc.set_creator("Shop");
+    doc.set_producer("genpdf");
+    doc.set_custom_metadata("InvoiceNumber", "2021-001");
+    doc.push(elements::Paragraph::new("Invoice"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+
+    let info = pdf_doc
+        .trailer
+        .get(b"Info")
+        .unwrap()
+        .as_reference()
+        .unwrap();
+    let info = pdf_doc.get_object(info).unwrap().as_dict().unwrap();
+    let entry = |key: &[u8]| info.get(key).unwrap().as_str().unwrap().to_vec();
+    assert_eq!(b"Invoice".to_vec(), entry(b"Title"));
+    assert_eq!(b"Jane Doe".to_vec(), entry(b"Author"));
+    assert_eq!(b"Invoice for <order> 42".to_vec(), entry(b"Subject"));
+    assert_eq!(b"invoice, order".to_vec(), entry(b"Keywords"));
+    assert_eq!(b"Shop".to_vec(), entry(b"Creator"));
+    assert_eq!(b"genpdf".to_vec(), entry(b"Producer"));
+    assert_eq!(b"2021-001".to_vec(), entry(b"InvoiceNumber"));
+
+    let catalog = pdf_doc.catalog().unwrap();
+    let metadata = catalog.get(b"Metadata").unwrap().as_reference().unwrap();
+    let metadata = pdf_doc.get_object(metadata).unwrap().as_stream().unwrap();
+    let metadata = String::from_utf8(metadata.content.clone()).unwrap();
+    assert!(
+        metadata.contains("<dc:creator><rdf:Seq><rdf:li>Jane Doe</rdf:li></rdf:Seq></dc:creator>")
+    );
+    assert!(metadata.contains("Invoice for &lt;order&gt; 42"));
+    assert!(metadata.contains("<pdf:Keywords>invoice, order</pdf:Keywords>"));
+    assert!(metadata.contains("<xmp:CreatorTool>Shop</xmp:CreatorTool>"));
+    assert!(metadata.contains("<pdf:Producer>genpdf</pdf:Producer>"));
+    assert!(!metadata.contains("InvoiceNumber"));
+    assert!(!metadata.contains("pdfaid"));
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+fn encryption() {
+    use genpdf::encryption;
+
+    let get_document = |algorithm| {
+        let mut doc = get_document();
+        doc.set_encryption(encryption::EncryptionOptions {
+            user_password: "user".to_owned(),
+            owner_password: "owner".to_owned(),
+            permissions: encryption::Permissions {
+                copy: false,
+                ..Default::default()
+            },
+            algorithm,
+        });
+        doc.push(elements::Paragraph::new("Salary"));
+        doc
+    };
+
+    for (algorithm, version, revision) in &[
+        (encryption::EncryptionAlgorithm::Aes128, "1.6", 4),
+        (encryption::EncryptionAlgorithm::Rc4, "1.4", 3),
+    ] {
+        let mut output = Vec::new();
+        get_document(*algorithm)
+            .render(&mut output)
+            .expect("Failed to render document");
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        assert_eq!(*version, pdf_doc.version);
+        assert!(pdf_doc.trailer.get(b"ID").is_ok());
+        let dict = pdf_doc
+            .trailer
+            .get(b"Encrypt")
+            .unwrap()
+            .as_reference()
+            .unwrap();
+        let dict = pdf_doc.get_object(dict).unwrap().as_dict().unwrap();
+        assert_eq!(b"Standard", dict.get(b"Filter").unwrap().as_name().unwrap());
+        assert_eq!(*revision, dict.get(b"R").unwrap().as_i64().unwrap());
+        // All permissions except for copying
+        assert_eq!(-20, dict.get(b"P").unwrap().as_i64().unwrap());
+        assert_eq!(32, dict.get(b"O").unwrap().as_str().unwrap().len());
+        assert_eq!(32, dict.get(b"U").unwrap().as_str().unwrap().len());
+
+        let page_id = *pdf_doc.get_pages().values().next().unwrap();
+        let content = pdf_doc.get_page_content(page_id).unwrap();
+        // The content stream is encrypted.
+        assert!(!content.windows(9).any(|w| w == b"Helvetica"));
+    }
+
+    let mut doc = get_document(encryption::EncryptionAlgorithm::Aes128);
+    doc.set_conformance(genpdf::Conformance::PdfA2b);
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        genpdf::error::ErrorKind::UnsupportedFeature
+    ));
+
+    let mut doc = get_document(encryption::EncryptionAlgorithm::Aes128);
+    doc.set_encryption(encryption::EncryptionOptions {
+        user_password: "\u{1f512}".to_owned(),
+        ..Default::default()
+    });
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        genpdf::error::ErrorKind::UnsupportedEncoding
+    ));
+}
+
+#[test]
+fn signature() {
+    use genpdf::signature;
+
+    #[derive(Default)]
+    struct DummySigner {
+        data: Vec<u8>,
+    }
+
+    impl signature::Signer for DummySigner {
+        fn sign(&mut self, data: &[u8]) -> Result<Vec<u8>, genpdf::error::Error> {
+            self.data = data.to_vec();
+            Ok(vec![0xAB, 0xCD, 0xEF])
+        }
+
+        fn max_len(&self) -> usize {
+            16
+        }
+
+        fn details(&self) -> signature::SignatureDetails {
+            signature::SignatureDetails {
+                reason: Some("Approval".to_owned()),
+                ..Default::default()
+            }
+        }
+    }
+
+    for (with_field, use_object_streams) in &[(true, false), (false, false), (true, true)] {
+        let mut doc = get_document();
+        doc.set_object_streams(*use_object_streams);
+        let mut line = elements::SignatureLine::new(60);
+        if *with_field {
+            line.set_signature_field("Approval");
+        }
+        doc.push(line);
+        let mut signer = DummySigner::default();
+        let mut output = Vec::new();
+        doc.render_signed(&mut output, &mut signer)
+            .expect("Failed to render document");
+
+        let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+        let catalog = pdf_doc.catalog().unwrap();
+        let form = catalog.get(b"AcroForm").unwrap().as_dict().unwrap();
+        assert_eq!(3, form.get(b"SigFlags").unwrap().as_i64().unwrap());
+        let fields = form.get(b"Fields").unwrap().as_array().unwrap();
+        assert_eq!(1, fields.len());
+        let field = pdf_doc
+            .get_dictionary(fields[0].as_reference().unwrap())
+            .unwrap();
+        assert_eq!(b"Sig", field.get(b"FT").unwrap().as_name().unwrap());
+        let rect = field.get(b"Rect").unwrap().as_array().unwrap();
+        let number = |object: &lopdf::Object| {
+            object
+                .as_f64()
+                .or_else(|_| object.as_i64().map(|n| n as f64))
+                .unwrap()
+        };
+        let width = number(&rect[2]) - number(&rect[0]);
+        if *with_field {
+            assert!(width > 0.0);
+        } else {
+            assert_eq!(0.0, width);
+        }
+
+        let sig = pdf_doc
+            .get_dictionary(field.get(b"V").unwrap().as_reference().unwrap())
+            .unwrap();
+        assert_eq!(b"Approval", sig.get(b"Reason").unwrap().as_str().unwrap());
+        let contents = sig.get(b"Contents").unwrap().as_str().unwrap();
+        assert_eq!(16, contents.len());
+        assert_eq!(&[0xAB, 0xCD, 0xEF], &contents[..3]);
+        let byte_range: Vec<_> = sig
+            .get(b"ByteRange")
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n.as_i64().unwrap() as usize)
+            .collect();
+        assert_eq!(0, byte_range[0]);
+        assert_eq!(output.len(), byte_range[2] + byte_range[3]);
+        assert_eq!(b'<', output[byte_range[1]]);
+        assert_eq!(b'>', output[byte_range[2] - 1]);
+        let mut signed_data = output[..byte_range[1]].to_vec();
+        signed_data.extend(&output[byte_range[2]..]);
+        assert_eq!(signer.data, signed_data);
+    }
+
+    let mut doc = get_document();
+    doc.push(elements::SignatureLine::new(60).with_signature_field("Signature"));
+    doc.push(elements::SignatureLine::new(60).with_signature_field("Signature"));
+    let err = doc
+        .render_signed(Vec::new(), DummySigner::default())
+        .unwrap_err();
+    assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData));
+}
+
+#[test]
+fn form_fields() {
+    use elements::form;
+
+    let mut doc = get_document();
+    doc.push(form::TextField::new("name", 80).with_value("Jane"));
+    doc.push(
+        form::TextField::new("comments", 80)
+            .with_lines(3)
+            .with_max_len(100),
+    );
+    doc.push(form::CheckBox::new("newsletter").with_checked(true));
+    doc.push(form::RadioButton::new("size", "S").with_label("Small"));
+    doc.push(
+        form::RadioButton::new("size", "L")
+            .with_label("Large")
+            .with_checked(true),
+    );
+    doc.push(form::Dropdown::new("country", 50, vec!["DE", "FR"]).with_value("FR"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let form = pdf_doc
+        .catalog()
+        .unwrap()
+        .get(b"AcroForm")
+        .unwrap()
+        .as_dict()
+        .unwrap();
+    assert!(matches!(
+        form.get(b"NeedAppearances").unwrap(),
+        lopdf::Object::Boolean(true)
+    ));
+    assert!(form.get(b"SigFlags").is_err());
+    let fields: Vec<_> = form
+        .get(b"Fields")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| {
+            pdf_doc
+                .get_dictionary(field.as_reference().unwrap())
+                .unwrap()
+        })
+        .collect();
+    let names: Vec<_> = fields
+        .iter()
+        .map(|field| field.get(b"T").unwrap().as_str().unwrap())
+        .collect();
+    assert_eq!(
+        vec![
+            &b"name"[..],
+            b"comments",
+            b"newsletter",
+            b"size",
+            b"country"
+        ],
+        names
+    );
+
+    assert_eq!(b"Tx", fields[0].get(b"FT").unwrap().as_name().unwrap());
+    assert_eq!(b"Jane", fields[0].get(b"V").unwrap().as_str().unwrap());
+    assert!(fields[0].get(b"Ff").is_err());
+    assert_eq!(4096, fields[1].get(b"Ff").unwrap().as_i64().unwrap());
+    assert_eq!(100, fields[1].get(b"MaxLen").unwrap().as_i64().unwrap());
+
+    assert_eq!(b"Btn", fields[2].get(b"FT").unwrap().as_name().unwrap());
+    assert_eq!(b"Yes", fields[2].get(b"V").unwrap().as_name().unwrap());
+    assert_eq!(b"Yes", fields[2].get(b"AS").unwrap().as_name().unwrap());
+
+    assert_eq!(b"L", fields[3].get(b"V").unwrap().as_name().unwrap());
+    let kids = fields[3].get(b"Kids").unwrap().as_array().unwrap();
+    assert_eq!(2, kids.len());
+    let states: Vec<_> = kids
+        .iter()
+        .map(|kid| {
+            let kid = pdf_doc.get_dictionary(kid.as_reference().unwrap()).unwrap();
+            assert!(kid.get(b"Parent").is_ok());
+            kid.get(b"AS").unwrap().as_name().unwrap()
+        })
+        .collect();
+    assert_eq!(vec![&b"Off"[..], b"L"], states);
+
+    assert_eq!(b"Ch", fields[4].get(b"FT").unwrap().as_name().unwrap());
+    assert_eq!(b"FR", fields[4].get(b"V").unwrap().as_str().unwrap());
+    assert_eq!(2, fields[4].get(b"Opt").unwrap().as_array().unwrap().len());
+
+    let page_id = *pdf_doc.get_pages().values().next().unwrap();
+    let page = pdf_doc.get_dictionary(page_id).unwrap();
+    assert_eq!(6, page.get(b"Annots").unwrap().as_array().unwrap().len());
+
+    let mut doc = get_document();
+    doc.push(form::CheckBox::new("size"));
+    doc.push(form::RadioButton::new("size", "S"));
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData));
+
+    let mut doc = get_document();
+    doc.set_conformance(genpdf::Conformance::PdfA2b);
+    doc.push(form::CheckBox::new("newsletter"));
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        genpdf::error::ErrorKind::UnsupportedFeature
+    ));
+}
+
+#[test]
+fn attachments() {
+    use genpdf::render::{Attachment, AttachmentRelationship};
+
+    let xml = "<Invoice/>".repeat(10);
+    let mut doc = get_document();
+    doc.attach_file("notes.txt", "Hello", "text/plain");
+    doc.add_attachment(
+        Attachment::new("invoice.xml", xml.clone(), "text/xml")
+            .with_description("Invoice data")
+            .with_relationship(AttachmentRelationship::Data),
+    );
+    doc.push(elements::Paragraph::new("Invoice"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+
+    let pdf_doc = lopdf::Document::load_mem(&output).expect("Failed to load document");
+    let catalog = pdf_doc.catalog().unwrap();
+    assert_eq!(2, catalog.get(b"AF").unwrap().as_array().unwrap().len());
+    let names = catalog.get(b"Names").unwrap().as_reference().unwrap();
+    let names = pdf_doc.get_dictionary(names).unwrap();
+    let embedded_files = names.get(b"EmbeddedFiles").unwrap().as_reference().unwrap();
+    let embedded_files = pdf_doc.get_dictionary(embedded_files).unwrap();
+    let names = embedded_files.get(b"Names").unwrap().as_array().unwrap();
+    assert_eq!(4, names.len());
+    // The names are sorted.
+    assert_eq!(b"invoice.xml", names[0].as_str().unwrap());
+    assert_eq!(b"notes.txt", names[2].as_str().unwrap());
+
+    let file_spec = pdf_doc
+        .get_dictionary(names[1].as_reference().unwrap())
+        .unwrap();
+    assert_eq!(
+        b"Data",
+        file_spec.get(b"AFRelationship").unwrap().as_name().unwrap()
+    );
+    assert_eq!(
+        b"Invoice data",
+        file_spec.get(b"Desc").unwrap().as_str().unwrap()
+    );
+    let files = file_spec.get(b"EF").unwrap().as_dict().unwrap();
+    let stream = pdf_doc
+        .get_object(files.get(b"F").unwrap().as_reference().unwrap())
+        .unwrap()
+        .as_stream()
+        .unwrap();
+    assert_eq!(
+        b"text/xml",
+        stream.dict.get(b"Subtype").unwrap().as_name().unwrap()
+    );
+    assert_eq!(xml.into_bytes(), stream.decompressed_content().unwrap());
+
+    let mut doc = get_document();
+    doc.attach_file("notes.txt", "Hello", "text/plain");
+    doc.attach_file("notes.txt", "World", "text/plain");
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidData));
+
+    let mut doc = get_document();
+    doc.set_conformance(genpdf::Conformance::PdfA2b);
+    doc.attach_file("notes.txt", "Hello", "text/plain");
+    let err = doc.render(Vec::new()).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        genpdf::error::ErrorKind::UnsupportedFeature
+    ));
+}
+
+#[test]
+fn right_to_left_paragraphs() {
+    let font_family = load_font_family(None);
+
+    let mut doc = get_document();
+    doc.set_paper_size((100, 50));
+    let embedded = doc.add_font_family(font_family);
+    doc.push(elements::Paragraph::new("abc שלום").styled(embedded));
+    doc.push(
+        elements::Paragraph::new("שלום abc")
+            .with_direction(genpdf::Direction::RightToLeft)
+            .styled(embedded),
+    );
+    doc.push(
+        elements::Paragraph::new("שלום abc")
+            .with_direction(genpdf::Direction::Auto)
+            .styled(embedded),
+    );
+    doc.push(elements::Paragraph::new("שלום abc").styled(embedded));
+
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert!(doc.missing_glyphs().is_empty());
+
+    let operations = page_operations(&output, 0);
+    let cursors: Vec<_> = text_positions(&operations)
+        .into_iter()
+        .map(|(x, _)| mm(x))
+        .collect();
+    let mut texts: Vec<Vec<u8>> = Vec::new();
+    for operation in &operations {
+        if operation.operator == "Td" {
+            texts.push(Vec::new());
+        } else if operation.operator == "TJ" {
+            let operands = operation.operands[0].as_array().unwrap();
+            texts.last_mut().unwrap().extend(
+                operands
+                    .iter()
+                    .filter_map(|operand| match operand {
+                        lopdf::Object::String(bytes, _) => Some(bytes.iter().copied()),
+                        _ => None,
+                    })
+                    .flatten(),
+            );
+        }
+    }
+    assert_eq!(4, cursors.len());
+    assert_eq!(4, texts.len());
+
+    // The right-to-left paragraphs are right-flushed and have the same visual order as the
+    // left-to-right paragraph with the runs in reverse order.
+    assert!(cursors[0] < 1.0);
+    assert!(cursors[1] > 50.0);
+    assert!(cursors[2] > 50.0);
+    assert!(cursors[3] < 1.0);
+    assert_eq!(texts[0], texts[1]);
+    assert_eq!(texts[0], texts[2]);
+    assert_ne!(texts[0], texts[3]);
+}
+
+#[cfg(feature = "shaping")]
+#[test]
+fn shaping() {
+    let font_family = load_font_family(None);
+    let font_cache = fonts::FontCache::new(font_family);
+    let font = font_cache.default_font_family().regular;
+
+    let glyphs = font
+        .shape(&font_cache, "Lorem ipsum")
+        .expect("Failed to shape text");
+    let clusters: Vec<_> = glyphs.iter().map(|glyph| glyph.cluster).collect();
+    assert_eq!((0..11).collect::<Vec<_>>(), clusters);
+    let glyph_ids: Vec<_> = glyphs.iter().map(|glyph| glyph.glyph_id).collect();
+    assert_eq!(
+        font.glyph_ids(&font_cache, "Lorem ipsum".chars()),
+        glyph_ids
+    );
+    let width: f32 = glyphs.iter().map(|glyph| glyph.advance).sum();
+    let str_width = font.str_width(&font_cache, "Lorem ipsum", 10);
+    assert!(
+        (printpdf::Pt::from(printpdf::Mm::from(str_width)).0 - f64::from(width) * 10.0).abs()
+            < 0.001
+    );
+
+    // Right-to-left text is returned in visual order.
+    let glyphs = font
+        .shape(&font_cache, "שלום")
+        .expect("Failed to shape text");
+    let clusters: Vec<_> = glyphs.iter().map(|glyph| glyph.cluster).collect();
+    assert_eq!(vec![6, 4, 2, 0], clusters);
+    assert!(glyphs.iter().all(|glyph| glyph.glyph_id != 0));
+
+    let builtin_cache = fonts::FontCache::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    let builtin = builtin_cache.default_font_family().regular;
+    assert_eq!(None, builtin.shape(&builtin_cache, "Lorem ipsum"));
+
+    let mut doc = get_document();
+    let embedded = doc.add_font_family(load_font_family(None));
+    doc.push(elements::Paragraph::new("office שלום").styled(embedded));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert!(doc.missing_glyphs().is_empty());
+}
+
+#[test]
+fn fallback_fonts() {
+    let load_font = || load_font_family(None);
+
+    let mut font_cache = fonts::FontCache::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    let builtin = font_cache.default_font_family();
+    let fallback = font_cache.add_fallback_font_family(load_font());
+    assert_eq!(&[fallback], font_cache.fallback_font_families());
+    assert!(builtin.regular.has_glyph(&font_cache, 'o'));
+    assert!(!builtin.regular.has_glyph(&font_cache, 'Ł'));
+    assert!(fallback.regular.has_glyph(&font_cache, 'Ł'));
+
+    let style = style::Style::new();
+    let runs: Vec<_> = style
+        .font_runs(&font_cache, "Łódź ok")
+        .into_iter()
+        .map(|(font, s)| (font == builtin.regular, s))
+        .collect();
+    assert_eq!(
+        vec![(false, "Ł"), (true, "ód"), (false, "ź "), (true, "ok")],
+        runs
+    );
+    assert_eq!(fallback.bold, style.bold().font_for_char(&font_cache, 'Ł'));
+    assert_eq!(
+        builtin.regular,
+        style.font_for_char(&font_cache, '\u{10FFFF}')
+    );
+
+    let mut doc = genpdf::Document::new(fonts::from_builtin(fonts::Builtin::Helvetica));
+    doc.set_minimal_conformance();
+    doc.add_fallback_font_family(load_font());
+    doc.push(elements::Paragraph::new("Łódź ok"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    assert!(doc.missing_glyphs().is_empty());
+}
+
+#[test]
+fn fonts_from_missing_files() {
+    let err = fonts::from_files(font_dir(), "Missing", None).unwrap_err();
+    let ttf_path = std::path::Path::new(font_dir()).join("Missing-Regular.ttf");
+    let otf_path = ttf_path.with_extension("otf");
+    assert_eq!(
+        format!(
+            "Failed to open font file {} or {}",
+            ttf_path.display(),
+            otf_path.display()
+        ),
+        err.to_string()
+    );
+    match err.kind() {
+        genpdf::error::ErrorKind::IoError(err) => {
+            assert_eq!(std::io::ErrorKind::NotFound, err.kind())
+        }
+        kind => panic!("Unexpected error kind: {:?}", kind),
+    }
+}
+
+#[test]
+fn fonts_from_bytes() {
+    let font_dir = font_dir();
+    let read = |style: &str| {
+        let path =
+            std::path::Path::new(font_dir).join(format!("{}-{}.ttf", DEFAULT_FONT_NAME, style));
+        std::fs::read(path).expect("Failed to read the font file")
+    };
+    let font_family = fonts::FontFamily::from_bytes(
+        &read("Regular"),
+        &read("Bold"),
+        &read("Italic"),
+        &read("BoldItalic"),
+        None,
+    )
+    .expect("Failed to load the font family");
+
+    let mut font_cache = fonts::FontCache::new(font_family);
+    let from_bytes = font_cache.default_font_family();
+    // The font data is identical, so the fonts are not added again.
+    let from_files = font_cache.add_font_family(
+        fonts::from_files(font_dir, DEFAULT_FONT_NAME, None).expect("Failed to load the font"),
+    );
+    assert_eq!(from_bytes, from_files);
+
+    let font_family = fonts::FontFamily::from_bytes(
+        &read("Regular"),
+        &read("Bold"),
+        &read("Italic"),
+        &read("BoldItalic"),
+        Some(fonts::Builtin::Helvetica),
+    )
+    .expect("Failed to load the font family");
+    let font_cache = fonts::FontCache::new(font_family);
+    assert!(font_cache.default_font_family().bold.is_builtin());
+}
+
+#[cfg(feature = "system-fonts")]
+#[test]
+fn system_fonts() {
+    // Look up the family name stored in the default font files instead of assuming that they
+    // belong to the Liberation Sans family
+    let mut db = fontdb::Database::new();
+    db.load_font_file(
+        std::path::Path::new(font_dir()).join(format!("{}-Regular.ttf", DEFAULT_FONT_NAME)),
+    )
+    .expect("Failed to load the default font");
+    let family = &db.faces()[0].family;
+
+    let font_cache = fonts::FontCache::new(
+        fonts::find_system_font(family, None).expect("Failed to find the font"),
+    );
+    let font_family = font_cache.default_font_family();
+    assert!(!font_family.regular.is_builtin());
+    assert_ne!(font_family.regular, font_family.bold);
+    assert_ne!(font_family.regular, font_family.italic);
+    assert_ne!(font_family.bold, font_family.bold_italic);
+
+    let err = fonts::find_system_font("No Such Font Family", None)
+        .expect_err("Found a font family that should not exist");
+    assert!(matches!(err.kind(), genpdf::error::ErrorKind::FontNotFound));
+}
+
+#[test]
+fn font_subsetting() {
+    let render = |use_font_subsetting: bool| {
+        let mut doc = genpdf::Document::new(load_font_family(None));
+        doc.set_minimal_conformance();
+        doc.set_font_subsetting(use_font_subsetting);
+        doc.push(elements::Paragraph::new("Subset"));
+        let mut output = Vec::new();
+        doc.render(&mut output).expect("Failed to render document");
+        output
+    };
+
+    let subset = render(true);
+    let full = render(false);
+    assert!(subset.len() < full.len());
+
+    let pdf_doc = lopdf::Document::load_mem(&subset).expect("Failed to load document");
+    let font_files: Vec<_> = pdf_doc
+        .objects
+        .values()
+        .filter_map(|object| object.as_stream().ok())
+        .filter(|stream| stream.dict.has(b"Length1"))
+        .collect();
+    assert!(!font_files.is_empty());
+    for font_file in font_files {
+        let data = font_file
+            .decompressed_content()
+            .unwrap_or_else(|_| font_file.content.clone());
+        let length1 = font_file.dict.get(b"Length1").unwrap().as_i64().unwrap();
+        assert_eq!(length1, data.len() as i64);
+        let font_data = fonts::FontData::new(data, None).expect("Failed to load the subset font");
+        let font_cache = fonts::FontCache::new(fonts::FontFamily {
+            regular: font_data.clone(),
+            bold: font_data.clone(),
+            italic: font_data.clone(),
+            bold_italic: font_data,
+        });
+        let font = font_cache.default_font_family().regular;
+        assert!(font.has_glyph(&font_cache, 'S'));
+    }
+}
+
+#[test]
+fn font_collections() {
+    let font_dir = font_dir();
+    let read = |style: &str| {
+        let path =
+            std::path::Path::new(font_dir).join(format!("{}-{}.ttf", DEFAULT_FONT_NAME, style));
+        std::fs::read(path).expect("Failed to read the font file")
+    };
+    let fonts = [read("Regular"), read("Bold")];
+
+    // Create a font collection by concatenating the fonts and adjusting the table offsets
+    let mut collection = b"ttcf\0\x01\0\0".to_vec();
+    collection.extend_from_slice(&(fonts.len() as u32).to_be_bytes());
+    let mut offset = 12 + 4 * fonts.len();
+    for font in &fonts {
+        collection.extend_from_slice(&(offset as u32).to_be_bytes());
+        offset += (font.len() + 3) / 4 * 4;
+    }
+    for font in &fonts {
+        let start = collection.len() as u32;
+        let mut font = font.clone();
+        let num_tables = usize::from(u16::from_be_bytes([font[4], font[5]]));
+        for record in (0..num_tables).map(|i| 12 + 16 * i) {
+            let offset = &mut font[record + 8..record + 12];
+            let value = u32::from_be_bytes([offset[0], offset[1], offset[2], offset[3]]);
+            offset.copy_from_slice(&(value + start).to_be_bytes());
+        }
+        collection.extend_from_slice(&font);
+        while collection.len() % 4 != 0 {
+            collection.push(0);
+        }
+    }
+
+    let load = |index: u32| fonts::FontData::new_from_collection(collection.clone(), index, None);
+    let regular = load(0).expect("Failed to load the regular font from the collection");
+    let bold = load(1).expect("Failed to load the bold font from the collection");
+    let err = load(2).expect_err("Loaded a font that is not part of the collection");
+    assert!(matches!(err.kind(), genpdf::error::ErrorKind::InvalidFont));
+
+    let font_family = fonts::FontFamily {
+        regular: regular.clone(),
+        bold: bold.clone(),
+        italic: regular,
+        bold_italic: bold,
+    };
+    let mut font_cache = fonts::FontCache::new(font_family);
+    let from_collection = font_cache.default_font_family();
+    let from_files = font_cache.add_font_family(load_font_family(None));
+    assert_eq!(
+        from_files.bold.str_width(&font_cache, "Bold", 12),
+        from_collection.bold.str_width(&font_cache, "Bold", 12)
+    );
+    assert_ne!(
+        from_collection.regular.str_width(&font_cache, "Bold", 12),
+        from_collection.bold.str_width(&font_cache, "Bold", 12)
+    );
+
+    let mut doc = genpdf::Document::new(fonts::FontFamily {
+        regular: load(0).unwrap(),
+        bold: load(1).unwrap(),
+        italic: load(0).unwrap(),
+        bold_italic: load(1).unwrap(),
+    });
+    doc.push(elements::Paragraph::new("Collection"));
+    let mut output = Vec::new();
+    doc.render(&mut output).expect("Failed to render document");
+    lopdf::Document::load_mem(&output).expect("Failed to load document");
+}
+
+#[test]
+fn variable_fonts() {
+    fn is_invalid_font<T>(result: Result<T, genpdf::error::Error>) -> bool {
+        match result {
+            Ok(_) => false,
+            Err(err) => matches!(err.kind(), genpdf::error::ErrorKind::InvalidFont),
+        }
+    }
+
+    // The Liberation fonts are not variable fonts
+    let path = std::path::Path::new(font_dir()).join(format!("{}-Regular.ttf", DEFAULT_FONT_NAME));
+    let data = std::fs::read(path).expect("Failed to read the font file");
+    assert!(is_invalid_font(fonts::FontData::new_instance(
+        data.clone(),
+        &[("wght", 700.0)],
+        None
+    )));
+    assert!(is_invalid_font(fonts::FontData::new_named_instance(
+        data.clone(),
+        "Bold",
+        None
+    )));
+    assert!(is_invalid_font(fonts::FontFamily::from_variable_font(
+        &data, None, None
+    )));
+}